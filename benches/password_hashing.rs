@@ -0,0 +1,33 @@
+//! Argon2id hash throughput with the default (OWASP) and a lighter tuning.
+//!
+//! Run with `cargo bench --bench password_hashing`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use scim_server::password::{Argon2Params, Argon2idHasher, PasswordHasher};
+
+fn argon2id_hashing(c: &mut Criterion) {
+    let tunings = [
+        ("default", Argon2Params::default()),
+        (
+            "tuned",
+            Argon2Params {
+                memory_kib: 8192,
+                iterations: 1,
+                parallelism: 1,
+            },
+        ),
+    ];
+
+    let mut group = c.benchmark_group("argon2id_hash");
+    group.sample_size(20);
+    for (name, params) in tunings {
+        let hasher = Argon2idHasher::new(params);
+        group.bench_function(name, |b| {
+            b.iter(|| hasher.hash_password("BenchPassword123!").unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, argon2id_hashing);
+criterion_main!(benches);
@@ -0,0 +1,161 @@
+//! `GET /Users` latency over 100,000 users in an in-memory SQLite backend:
+//! first, middle, filtered and sorted pages of 100.
+//!
+//! Run with `cargo bench --bench user_list`. Seeding takes a while; it
+//! happens once before the first benchmark.
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::routing::get;
+use axum::{middleware, Router};
+use criterion::{criterion_group, criterion_main, Criterion};
+use scim_server::auth_lockout::AuthFailures;
+use scim_server::backend::database::DatabaseBackendConfig;
+use scim_server::backend::{BackendFactory, DatabaseType, ScimBackend};
+use scim_server::config::AppConfig;
+use scim_server::models::User;
+use scim_server::password::PasswordManagerRegistry;
+use scim_server::stats::SyncStats;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+
+const USER_COUNT: usize = 100_000;
+const TENANT_ID: u32 = 1;
+/// Requests timed one by one for the median/p99 report of each query
+const LATENCY_SAMPLES: usize = 200;
+
+const CONFIG: &str = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+  database:
+    type: "sqlite"
+    url: ":memory:"
+tenants:
+  - id: 1
+    path: "/scim/v2"
+    auth:
+      type: "unauthenticated"
+"#;
+
+const QUERIES: [(&str, &str); 4] = [
+    ("first_page", "/scim/v2/Users?count=100"),
+    ("middle_page", "/scim/v2/Users?count=100&startIndex=50001"),
+    (
+        "filtered_page",
+        "/scim/v2/Users?filter=active%20eq%20true&count=100",
+    ),
+    ("sorted_page", "/scim/v2/Users?sortBy=userName&count=100"),
+];
+
+async fn seeded_backend() -> Arc<dyn ScimBackend> {
+    let backend = BackendFactory::create(&DatabaseBackendConfig {
+        database_type: DatabaseType::SQLite,
+        connection_path: ":memory:".to_string(),
+        max_connections: 1,
+        connection_timeout: 30,
+        options: HashMap::new(),
+        external_id_case_insensitive_tenants: Default::default(),
+    })
+    .await
+    .unwrap();
+    backend.init_tenant(TENANT_ID).await.unwrap();
+
+    for i in 0..USER_COUNT {
+        // Insert out of userName order so sorting has work to do
+        let n = i * 7919 % USER_COUNT;
+        let user: User = serde_json::from_value(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": format!("bench.user{:06}", n),
+            "name": {"givenName": "Bench", "familyName": format!("User{:06}", n)},
+            "emails": [{"value": format!("bench.user{:06}@example.com", n), "primary": true}],
+            "active": n.is_multiple_of(2)
+        }))
+        .unwrap();
+        backend.create_user(TENANT_ID, &user, None).await.unwrap();
+    }
+    backend
+}
+
+fn router(backend: Arc<dyn ScimBackend>) -> Router {
+    let app_config: AppConfig = serde_yaml::from_str(CONFIG).unwrap();
+    let app_config = Arc::new(app_config);
+    Router::new()
+        .route(
+            "/scim/v2/Users",
+            get(scim_server::resource::user::search_users),
+        )
+        .layer(middleware::from_fn_with_state(
+            (
+                app_config.clone(),
+                Arc::new(AuthFailures::default()),
+                Arc::new(SyncStats::default()),
+            ),
+            scim_server::auth::auth_middleware,
+        ))
+        .with_state((
+            backend,
+            app_config.clone(),
+            Arc::new(PasswordManagerRegistry::from_config(&app_config)),
+        ))
+}
+
+async fn list_users(app: &Router, uri: &str) {
+    let response = app
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK, "GET {}", uri);
+    to_bytes(response.into_body(), usize::MAX).await.unwrap();
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let rank = ((sorted.len() as f64 * p).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+fn report_latency(runtime: &Runtime, app: &Router, name: &str, uri: &str) {
+    let mut samples: Vec<Duration> = (0..LATENCY_SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            runtime.block_on(list_users(app, uri));
+            start.elapsed()
+        })
+        .collect();
+    samples.sort();
+    println!(
+        "{:<32} median {:>10.3?}   p99 {:>10.3?}",
+        format!("get_users/{}", name),
+        percentile(&samples, 0.50),
+        percentile(&samples, 0.99)
+    );
+}
+
+fn get_users(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let app = router(runtime.block_on(seeded_backend()));
+
+    let mut group = c.benchmark_group("get_users");
+    group
+        .sample_size(30)
+        .warm_up_time(Duration::from_secs(3))
+        .measurement_time(Duration::from_secs(10));
+    for (name, uri) in QUERIES {
+        group.bench_function(name, |b| b.iter(|| runtime.block_on(list_users(&app, uri))));
+    }
+    group.finish();
+
+    for (name, uri) in QUERIES {
+        report_latency(&runtime, &app, name, uri);
+    }
+}
+
+criterion_group!(benches, get_users);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Feed arbitrary `filter` query strings through the parser, the attribute
+//! whitelist and every SQL converter
+//!
+//! Run with `cargo +nightly fuzz run filter_to_sql` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use scim_server::backend::database::filter::FilterConverter;
+use scim_server::backend::database::mysql::filter_impl::MySqlFilterConverter;
+use scim_server::backend::database::postgres::filter_impl::PostgresFilterConverter;
+use scim_server::backend::database::sqlite::filter_impl::SqliteFilterConverter;
+use scim_server::parser::filter_parser::parse_filter;
+use scim_server::parser::ResourceType;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(filter) = parse_filter(input) else {
+        return;
+    };
+
+    let converters: [&dyn FilterConverter; 3] = [
+        &SqliteFilterConverter::new(),
+        &PostgresFilterConverter::new(),
+        &MySqlFilterConverter::new(),
+    ];
+    for resource_type in [ResourceType::User, ResourceType::Group] {
+        for converter in converters {
+            let Ok((sql, _params)) = converter.to_where_clause(&filter, resource_type) else {
+                continue;
+            };
+            // Only whitelisted attribute names reach the SQL and every value
+            // is bound as a parameter, so nothing can end the statement or
+            // comment out the rest of it
+            assert!(
+                !sql.contains(';') && !sql.contains("--") && !sql.contains("/*"),
+                "{:?} produced {}",
+                input,
+                sql
+            );
+        }
+    }
+});
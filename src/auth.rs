@@ -1,27 +1,73 @@
 use axum::{
     extract::{ConnectInfo, Request, State},
-    http::{HeaderMap, StatusCode, Uri},
+    http::{header, HeaderMap, StatusCode, Uri},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
-use crate::config::{AppConfig, AuthConfig, RequestInfo, TenantConfig};
+use crate::auth_lockout::{self, AuthFailures};
+use crate::config::{AppConfig, AuthConfig, RequestInfo, TenantConfig, TokenPermissions};
+use crate::error::forbidden_response;
+use crate::permissions;
+use crate::stats::SyncStats;
 
 /// Tenant information extracted from request
 #[derive(Debug, Clone)]
 pub struct TenantInfo {
     pub tenant_id: u32,
     pub tenant_config: TenantConfig,
-    pub base_path: String, // Resolved absolute base URL for this tenant
+    pub base_path: String,     // Resolved absolute base URL for this tenant
+    pub ref_base_path: String, // Base URL for $ref values (defaults to base_path)
+    /// Authenticated principal: the basic username, the name of a scoped
+    /// token, or else the auth type for token credentials. None for
+    /// unauthenticated tenants.
+    pub principal_name: Option<String>,
+    /// Restrictions of the scoped token the request authenticated with
+    pub permissions: Option<TokenPermissions>,
+}
+
+/// Principal and restrictions of the credential a request matched
+type Credential = (Option<String>, Option<TokenPermissions>);
+
+/// Whether `path` is the share link route registered in `router::build_router`
+///
+/// Only the exact route is matched, so a tenant whose path starts with
+/// `/shared/` still authenticates with its own credentials.
+fn is_share_link_route(path: &str) -> bool {
+    matches!(path.strip_prefix("/shared/"), Some(token) if !token.is_empty() && !token.contains('/'))
+}
+
+/// Whether `path` is one of the server-wide admin routes registered in
+/// `router::build_router`
+///
+/// Only the exact routes are matched, so a tenant whose path starts with
+/// `/admin/` still authenticates with its own credentials.
+fn is_admin_route(path: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+    match segments.as_slice() {
+        ["admin", "tenants"] => true,
+        ["admin", "tenants", id, "share" | "vacuum" | "stats"] => !id.is_empty(),
+        ["admin", "tenants", id, "history", resource_type, resource_id] => {
+            !id.is_empty() && !resource_type.is_empty() && !resource_id.is_empty()
+        }
+        _ => false,
+    }
 }
 
 /// Authentication middleware for SCIM endpoints
+///
+/// Failed attempts are logged per tenant and client IP. Tenants with an
+/// `auth_lockout` policy answer 429 to a client IP that failed too often.
 pub async fn auth_middleware(
-    State(app_config): State<Arc<AppConfig>>,
+    State((app_config, auth_failures, sync_stats)): State<(
+        Arc<AppConfig>,
+        Arc<AuthFailures>,
+        Arc<SyncStats>,
+    )>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
@@ -41,47 +87,118 @@ pub async fn auth_middleware(
         return Ok(next.run(request).await);
     }
 
-    // Resolve tenant and validate authentication
-    let tenant_info = match resolve_tenant_and_authenticate(&app_config, &uri, &headers, client_ip)
-    {
-        Ok(info) => info,
-        Err(StatusCode::UNAUTHORIZED) => {
+    // Share links carry their own signed authorization
+    if is_share_link_route(path) {
+        return Ok(next.run(request).await);
+    }
+
+    // Server-wide admin endpoints are not tied to a tenant
+    if is_admin_route(path) {
+        let Some(token) = &app_config.server.admin_token else {
+            return Err((StatusCode::NOT_FOUND, Json(json!({"message": "Not found"}))));
+        };
+        let admin_auth = AuthConfig {
+            auth_type: "bearer".to_string(),
+            token: Some(token.clone()),
+            basic: None,
+            basic_users: Vec::new(),
+            tokens: Vec::new(),
+        };
+        let auth_header = headers.get("authorization").and_then(|h| h.to_str().ok());
+        if validate_authentication(&admin_auth, auth_header).is_err() {
             return Err((
                 StatusCode::UNAUTHORIZED,
                 Json(json!({"message": "Authentication required"})),
             ));
         }
-        Err(status) => {
+        return Ok(next.run(request).await);
+    }
+
+    // Resolve tenant
+    let tenant = match resolve_tenant_id_from_request(&app_config, &uri, &headers, client_ip)
+        .ok()
+        .and_then(|tenant_id| app_config.tenants.iter().find(|t| t.id == tenant_id))
+    {
+        Some(tenant) => tenant,
+        None => {
             return Err((
-                status,
+                StatusCode::NOT_FOUND,
                 Json(json!({"message": format!("Tenant not found for path '{}'", path)})),
             ));
         }
     };
 
+    // Reject locked out clients before looking at their credentials
+    let lockout_ip = auth_lockout::client_ip(tenant, &headers, client_ip);
+    if let Some(ip) = lockout_ip {
+        if let Some(remaining) = auth_failures.locked_for(tenant.id, ip) {
+            tracing::warn!(
+                tenant_id = tenant.id,
+                client_ip = %ip,
+                "Rejected authentication attempt from locked out client"
+            );
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({"message": "Too many failed authentication attempts"})),
+            )
+                .into_response();
+            // Round up so clients never retry while still locked out
+            let retry_after = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(retry_after));
+            return Ok(response);
+        }
+    }
+
+    // Validate authentication
+    let tenant_info = match authenticate_tenant(&app_config, tenant, &uri, &headers) {
+        Ok(info) => info,
+        Err(status) => {
+            sync_stats.record_auth_failure(tenant.id);
+            if let Some(ip) = lockout_ip {
+                let failures =
+                    auth_failures.record_failure(tenant.id, ip, tenant.auth_lockout.as_ref());
+                tracing::warn!(
+                    tenant_id = tenant.id,
+                    client_ip = %ip,
+                    failures,
+                    "Authentication failed"
+                );
+            } else {
+                tracing::warn!(tenant_id = tenant.id, "Authentication failed");
+            }
+            return Err((status, Json(json!({"message": "Authentication required"}))));
+        }
+    };
+    if let Some(ip) = lockout_ip {
+        auth_failures.reset(tenant.id, ip);
+    }
+
+    // Scoped tokens may be limited to some methods
+    if let Some(token_permissions) = &tenant_info.permissions {
+        if !permissions::method_allowed(token_permissions, request.method(), path) {
+            return Err(forbidden_response(&format!(
+                "{} is not allowed with these credentials",
+                request.method()
+            )));
+        }
+    }
+
     // Store tenant info in request extensions for handlers to use
     request.extensions_mut().insert(tenant_info);
 
     Ok(next.run(request).await)
 }
 
-/// Helper function to resolve tenant and validate authentication
-fn resolve_tenant_and_authenticate(
+/// Helper function to validate authentication for a resolved tenant
+fn authenticate_tenant(
     app_config: &AppConfig,
+    tenant: &TenantConfig,
     uri: &Uri,
     headers: &HeaderMap,
-    client_ip: Option<std::net::IpAddr>,
 ) -> Result<TenantInfo, StatusCode> {
     let path = uri.path();
-    let tenant_id = resolve_tenant_id_from_request(app_config, uri, headers, client_ip)?;
-
-    // Find the tenant configuration
-    let tenant = app_config
-        .tenants
-        .iter()
-        .find(|t| t.id == tenant_id)
-        .ok_or(StatusCode::NOT_FOUND)?
-        .clone();
 
     // Extract Authorization header
     let auth_header = headers.get("authorization").and_then(|h| h.to_str().ok());
@@ -97,15 +214,19 @@ fn resolve_tenant_and_authenticate(
         };
 
     // Validate authentication using the effective auth config
-    validate_authentication(auth_config, auth_header)?;
+    let (principal_name, permissions) = validate_authentication(auth_config, auth_header)?;
 
     // Resolve the absolute base URL for this tenant
-    let base_url = resolve_tenant_base_url(app_config, &tenant, uri, headers);
+    let base_url = resolve_tenant_base_url(app_config, tenant, uri, headers);
+    let ref_base_url = tenant.build_ref_base_url(&base_url);
 
     Ok(TenantInfo {
-        tenant_id,
-        tenant_config: tenant,
+        tenant_id: tenant.id,
+        tenant_config: tenant.clone(),
         base_path: base_url,
+        ref_base_path: ref_base_url,
+        principal_name,
+        permissions,
     })
 }
 
@@ -144,14 +265,17 @@ fn resolve_tenant_id_from_request(
 }
 
 /// Helper function to validate authentication using auth config
+///
+/// Returns the name of the authenticated principal, if any, and the
+/// permissions of a scoped token.
 fn validate_authentication(
     auth_config: &AuthConfig,
     auth_header: Option<&str>,
-) -> Result<(), StatusCode> {
+) -> Result<Credential, StatusCode> {
     match auth_config.auth_type.as_str() {
         "unauthenticated" => {
             // No authentication required - always allow
-            Ok(())
+            Ok((None, None))
         }
         "bearer" => {
             // Validate Bearer token (case-insensitive per RFC 7235)
@@ -165,17 +289,7 @@ fn validate_authentication(
             }
 
             let provided_token = &auth_header[7..]; // Remove "Bearer " prefix
-
-            match &auth_config.token {
-                Some(expected_token) => {
-                    if provided_token == expected_token {
-                        Ok(())
-                    } else {
-                        Err(StatusCode::UNAUTHORIZED)
-                    }
-                }
-                None => Err(StatusCode::UNAUTHORIZED), // No token configured
-            }
+            validate_token(auth_config, provided_token)
         }
         "token" => {
             // Validate token authentication (case-insensitive per RFC 7235)
@@ -188,17 +302,7 @@ fn validate_authentication(
             }
 
             let provided_token = &auth_header[6..]; // Remove "token " prefix
-
-            match &auth_config.token {
-                Some(expected_token) => {
-                    if provided_token == expected_token {
-                        Ok(())
-                    } else {
-                        Err(StatusCode::UNAUTHORIZED)
-                    }
-                }
-                None => Err(StatusCode::UNAUTHORIZED), // No token configured
-            }
+            validate_token(auth_config, provided_token)
         }
         "basic" => {
             // Validate HTTP Basic authentication (case-insensitive per RFC 7235)
@@ -228,18 +332,17 @@ fn validate_authentication(
 
             let (provided_username, provided_password) = (parts[0], parts[1]);
 
-            match &auth_config.basic {
-                Some(basic_config) => {
-                    if provided_username == basic_config.username
+            // No basic auth configured means no credentials match
+            auth_config
+                .basic
+                .iter()
+                .chain(&auth_config.basic_users)
+                .find(|basic_config| {
+                    provided_username == basic_config.username
                         && provided_password == basic_config.password
-                    {
-                        Ok(())
-                    } else {
-                        Err(StatusCode::UNAUTHORIZED)
-                    }
-                }
-                None => Err(StatusCode::UNAUTHORIZED), // No basic auth configured
-            }
+                })
+                .map(|basic_config| (Some(basic_config.username.clone()), None))
+                .ok_or(StatusCode::UNAUTHORIZED)
         }
         _ => {
             // Unknown authentication type
@@ -248,6 +351,27 @@ fn validate_authentication(
     }
 }
 
+/// Match a bearer/token credential against `token` (unrestricted) and the
+/// scoped `tokens`
+fn validate_token(
+    auth_config: &AuthConfig,
+    provided_token: &str,
+) -> Result<Credential, StatusCode> {
+    if auth_config.token.as_deref() == Some(provided_token) {
+        return Ok((Some(auth_config.auth_type.clone()), None));
+    }
+    // No token configured means no credentials match
+    auth_config
+        .tokens
+        .iter()
+        .find(|scoped| scoped.token == provided_token)
+        .map(|scoped| {
+            let name = scoped.name.clone().unwrap_or(auth_config.auth_type.clone());
+            (Some(name), Some(scoped.permissions.clone()))
+        })
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
 /// Helper function to resolve the absolute base URL for a tenant
 fn resolve_tenant_base_url(
     _app_config: &AppConfig,
@@ -255,8 +379,43 @@ fn resolve_tenant_base_url(
     uri: &Uri,
     headers: &HeaderMap,
 ) -> String {
-    // Create RequestInfo for URL resolution
-    let request_info = RequestInfo {
+    // Use the new build_base_url method that handles override_base_url and auto-construction
+    tenant.build_base_url(&url_request_info(uri, headers))
+}
+
+/// Resolve the absolute server URL (without the tenant path) a tenant is reached at
+pub(crate) fn resolve_server_base_url(
+    tenant: &TenantConfig,
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> String {
+    tenant.build_base_url_no_path(&url_request_info(uri, headers))
+}
+
+/// Tenant information for a request authorized without the tenant's
+/// credentials (a share link), so without a principal
+pub(crate) fn unauthenticated_tenant_info(
+    app_config: &AppConfig,
+    tenant: &TenantConfig,
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> TenantInfo {
+    let base_url = resolve_tenant_base_url(app_config, tenant, uri, headers);
+    let ref_base_url = tenant.build_ref_base_url(&base_url);
+
+    TenantInfo {
+        tenant_id: tenant.id,
+        tenant_config: tenant.clone(),
+        base_path: base_url,
+        ref_base_path: ref_base_url,
+        principal_name: None,
+        permissions: None,
+    }
+}
+
+/// RequestInfo for URL resolution
+pub(crate) fn url_request_info<'a>(uri: &'a Uri, headers: &'a HeaderMap) -> RequestInfo<'a> {
+    RequestInfo {
         path: uri.path(),
         host_header: headers.get("host").and_then(|h| h.to_str().ok()),
         forwarded_header: headers.get("forwarded").and_then(|h| h.to_str().ok()),
@@ -270,8 +429,5 @@ fn resolve_tenant_base_url(
             .get("x-forwarded-port")
             .and_then(|h| h.to_str().ok()),
         client_ip: None,
-    };
-
-    // Use the new build_base_url method that handles override_base_url and auto-construction
-    tenant.build_base_url(&request_info)
+    }
 }
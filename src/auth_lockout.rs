@@ -0,0 +1,316 @@
+//! Failed authentication tracking (`auth_lockout`)
+//!
+//! Failures are counted per tenant and client IP in memory, so every process
+//! keeps its own counts and a restart forgets them. Tenants without a lockout
+//! policy are still counted, over `DEFAULT_WINDOW`, for the logs.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+
+use crate::config::{AuthLockoutConfig, HostResolutionType, TenantConfig};
+
+/// Counting window for tenants without a lockout policy
+const DEFAULT_WINDOW: Duration = Duration::from_secs(300);
+
+/// How often expired entries are swept out
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often a full map is swept when new client IPs keep arriving
+const FULL_PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most client IPs tracked at once, so a flood of addresses cannot grow the
+/// map without bound
+const MAX_ENTRIES: usize = 100_000;
+
+#[derive(Debug)]
+struct Failures {
+    count: u32,
+    window_start: Instant,
+    locked_until: Option<Instant>,
+    /// When both the window and the lockout are over
+    expires: Instant,
+}
+
+#[derive(Debug)]
+struct Entries {
+    map: HashMap<(u32, IpAddr), Failures>,
+    last_pruned: Instant,
+}
+
+impl Entries {
+    /// Forget clients whose window and lockout are both over and, when that
+    /// does not free enough room, clients that are not locked out
+    fn prune(&mut self, now: Instant, max_entries: usize) {
+        self.map.retain(|_, failures| failures.expires > now);
+        if self.map.len() >= max_entries {
+            self.map
+                .retain(|_, failures| failures.locked_until.is_some_and(|until| until > now));
+        }
+        self.last_pruned = now;
+    }
+}
+
+/// Failed authentication attempts per tenant and client IP
+#[derive(Debug)]
+pub struct AuthFailures {
+    entries: Mutex<Entries>,
+    max_entries: usize,
+}
+
+impl Default for AuthFailures {
+    fn default() -> Self {
+        Self::with_max_entries(MAX_ENTRIES)
+    }
+}
+
+impl AuthFailures {
+    fn with_max_entries(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                map: HashMap::new(),
+                last_pruned: Instant::now()
+                    .checked_sub(FULL_PRUNE_INTERVAL)
+                    .unwrap_or_else(Instant::now),
+            }),
+            max_entries,
+        }
+    }
+
+    /// Time left until a locked out client IP may authenticate again
+    pub fn locked_for(&self, tenant_id: u32, ip: IpAddr) -> Option<Duration> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let locked_until = entries.map.get(&(tenant_id, ip))?.locked_until?;
+        locked_until.checked_duration_since(Instant::now())
+    }
+
+    /// Count a failed attempt and return the failures in the current window
+    ///
+    /// Reaching `max_failures` locks the client IP out for `lockout_seconds`.
+    /// Expired entries are swept every `PRUNE_INTERVAL`, or earlier when the
+    /// map is full; a new client IP that still finds no room is not tracked.
+    pub fn record_failure(
+        &self,
+        tenant_id: u32,
+        ip: IpAddr,
+        policy: Option<&AuthLockoutConfig>,
+    ) -> u32 {
+        let now = Instant::now();
+        let window = policy.map_or(DEFAULT_WINDOW, |policy| {
+            Duration::from_secs(policy.window_seconds)
+        });
+        let key = (tenant_id, ip);
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let since_pruned = now.duration_since(entries.last_pruned);
+        if entries.map.len() >= self.max_entries && !entries.map.contains_key(&key) {
+            // Sweep a full map at most once a second, however many new
+            // client IPs keep arriving
+            if since_pruned >= FULL_PRUNE_INTERVAL {
+                entries.prune(now, self.max_entries);
+            }
+            if entries.map.len() >= self.max_entries {
+                return 1;
+            }
+        } else if since_pruned >= PRUNE_INTERVAL {
+            entries.prune(now, self.max_entries);
+        }
+
+        let failures = entries.map.entry(key).or_insert(Failures {
+            count: 0,
+            window_start: now,
+            locked_until: None,
+            expires: now + window,
+        });
+        if now.duration_since(failures.window_start) >= window
+            || failures.locked_until.is_some_and(|until| until <= now)
+        {
+            *failures = Failures {
+                count: 0,
+                window_start: now,
+                locked_until: None,
+                expires: now + window,
+            };
+        }
+        failures.count += 1;
+
+        if let Some(policy) = policy {
+            if failures.count >= policy.max_failures {
+                let until = now + Duration::from_secs(policy.lockout_seconds);
+                failures.locked_until = Some(until);
+                failures.expires = failures.expires.max(until);
+            }
+        }
+        failures.count
+    }
+
+    /// Forget the failures of a client IP after it authenticated
+    pub fn reset(&self, tenant_id: u32, ip: IpAddr) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.map.remove(&(tenant_id, ip));
+    }
+}
+
+/// Address of the client a request comes from
+///
+/// For tenants that resolve their URL through proxy headers and list their
+/// `trusted_proxies`, a connecting trusted proxy is looked through: the client
+/// is the rightmost `X-Forwarded-For` or `Forwarded` address that is not a
+/// trusted proxy itself, as everything left of it may be forged by the client.
+/// Otherwise it is the peer address itself.
+pub fn client_ip(
+    tenant: &TenantConfig,
+    headers: &HeaderMap,
+    peer_ip: Option<IpAddr>,
+) -> Option<IpAddr> {
+    let Some(host_resolution) = &tenant.host_resolution else {
+        return peer_ip;
+    };
+    let peer = peer_ip?;
+    // Without an explicit list every peer counts as trusted, which would let
+    // any client pick the address its failures are counted under
+    if host_resolution.trusted_proxies.is_none() || !host_resolution.is_trusted_proxy(peer) {
+        return Some(peer);
+    }
+
+    let header_values = |name: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .collect::<Vec<_>>()
+    };
+    let hops: Vec<Option<IpAddr>> = match host_resolution.resolution_type {
+        HostResolutionType::Host => Vec::new(),
+        HostResolutionType::XForwarded => header_values("x-forwarded-for")
+            .into_iter()
+            .map(|ip| ip.trim().parse().ok())
+            .collect(),
+        HostResolutionType::Forwarded => header_values("forwarded")
+            .into_iter()
+            .map(forwarded_for)
+            .collect(),
+    };
+
+    match hops
+        .into_iter()
+        .rev()
+        .find(|hop| !hop.is_some_and(|ip| host_resolution.is_trusted_proxy(ip)))
+    {
+        Some(Some(ip)) => Some(ip),
+        // An unreadable hop cannot be attributed; count it under the proxy
+        Some(None) | None => Some(peer),
+    }
+}
+
+/// The `for=` address of one Forwarded header element (RFC 7239)
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    let value = element.split(';').find_map(|pair| {
+        let (name, value) = pair.trim().split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("for")
+            .then(|| value.trim().trim_matches('"'))
+    })?;
+
+    // IPv6 addresses are bracketed and may carry a port: "[2001:db8::1]:4711"
+    if let Some(rest) = value.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+    value
+        .parse()
+        .ok()
+        .or_else(|| value.rsplit_once(':')?.0.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AuthLockoutConfig {
+        AuthLockoutConfig {
+            max_failures: 3,
+            window_seconds: 60,
+            lockout_seconds: 30,
+        }
+    }
+
+    #[test]
+    fn test_lockout_after_max_failures() {
+        let failures = AuthFailures::default();
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+
+        assert_eq!(failures.record_failure(1, ip, Some(&policy())), 1);
+        assert_eq!(failures.record_failure(1, ip, Some(&policy())), 2);
+        assert!(failures.locked_for(1, ip).is_none());
+        assert_eq!(failures.record_failure(1, ip, Some(&policy())), 3);
+        assert!(failures.locked_for(1, ip).is_some());
+
+        // Other tenants and addresses are unaffected
+        assert!(failures.locked_for(2, ip).is_none());
+        assert!(failures
+            .locked_for(1, "203.0.113.8".parse().unwrap())
+            .is_none());
+
+        failures.reset(1, ip);
+        assert!(failures.locked_for(1, ip).is_none());
+        assert_eq!(failures.record_failure(1, ip, Some(&policy())), 1);
+    }
+
+    #[test]
+    fn test_counting_without_policy() {
+        let failures = AuthFailures::default();
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+
+        for expected in 1..=10 {
+            assert_eq!(failures.record_failure(1, ip, None), expected);
+        }
+        assert!(failures.locked_for(1, ip).is_none());
+    }
+
+    #[test]
+    fn test_entries_are_capped() {
+        let failures = AuthFailures::with_max_entries(2);
+        let ip = |last: u8| IpAddr::from([203, 0, 113, last]);
+
+        for last in 1..=2 {
+            for _ in 0..3 {
+                failures.record_failure(1, ip(last), Some(&policy()));
+            }
+        }
+        // Both slots hold locked out clients, so a third one is not tracked
+        assert_eq!(failures.record_failure(1, ip(3), Some(&policy())), 1);
+        assert_eq!(failures.record_failure(1, ip(3), Some(&policy())), 1);
+        assert!(failures.locked_for(1, ip(1)).is_some());
+
+        // Clients that are only counted make room for new ones at the next
+        // sweep
+        failures.reset(1, ip(2));
+        failures.record_failure(1, ip(2), None);
+        assert_eq!(failures.record_failure(1, ip(3), Some(&policy())), 1);
+        failures.entries.lock().unwrap().last_pruned -= FULL_PRUNE_INTERVAL;
+        assert_eq!(failures.record_failure(1, ip(3), Some(&policy())), 1);
+        assert_eq!(failures.record_failure(1, ip(3), Some(&policy())), 2);
+        assert!(failures.locked_for(1, ip(1)).is_some());
+    }
+
+    #[test]
+    fn test_forwarded_for() {
+        assert_eq!(
+            forwarded_for("for=192.0.2.60;proto=http;by=203.0.113.43"),
+            "192.0.2.60".parse().ok()
+        );
+        assert_eq!(
+            forwarded_for("For=\"[2001:db8:cafe::17]:4711\""),
+            "2001:db8:cafe::17".parse().ok()
+        );
+        assert_eq!(
+            forwarded_for("for=192.0.2.43:8080"),
+            "192.0.2.43".parse().ok()
+        );
+        assert_eq!(forwarded_for("for=unknown"), None);
+    }
+}
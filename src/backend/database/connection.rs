@@ -0,0 +1,65 @@
+use crate::error::AppError;
+
+/// Messages of sqlx errors meaning the database could not be reached
+///
+/// Backend operations wrap sqlx errors into `AppError::Database` strings, so
+/// the classification works on the rendered message. The first entries are
+/// sqlx's own pool and transport errors; the last are PostgreSQL terminating
+/// sessions during a restart or failover.
+const CONNECTION_FAILURES: &[&str] = &[
+    "pool timed out while waiting for an open connection",
+    "attempted to acquire a connection on a closed pool",
+    "error communicating with database",
+    "error occurred while attempting to establish a TLS connection",
+    "attempted to communicate with a crashed background worker",
+    "terminating connection due to administrator command",
+    "the database system is starting up",
+    "the database system is shutting down",
+];
+
+/// Turn a lost or unobtainable database connection into a retryable error
+///
+/// The pool reconnects on its own once the database is back, so clients get a
+/// 503 they can retry instead of a 500 carrying the driver message.
+pub fn map_connection_error(error: AppError) -> AppError {
+    match error {
+        AppError::Database(message)
+            if CONNECTION_FAILURES
+                .iter()
+                .any(|failure| message.contains(failure)) =>
+        {
+            AppError::Unavailable(message)
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_connection_error() {
+        let mapped = map_connection_error(AppError::Database(format!(
+            "Failed to find user: {}",
+            sqlx::Error::PoolTimedOut
+        )));
+        assert!(matches!(mapped, AppError::Unavailable(_)));
+
+        let mapped = map_connection_error(AppError::Database(format!(
+            "Failed to begin transaction: {}",
+            sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::ConnectionRefused))
+        )));
+        assert!(matches!(mapped, AppError::Unavailable(_)));
+
+        // Query errors stay as they are
+        let mapped = map_connection_error(AppError::Database(
+            "Failed to find user: no such column: foo".to_string(),
+        ));
+        assert!(matches!(mapped, AppError::Database(_)));
+        assert!(matches!(
+            map_connection_error(AppError::BadRequest("x".to_string())),
+            AppError::BadRequest(_)
+        ));
+    }
+}
@@ -1,6 +1,8 @@
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 use crate::parser::filter_operator::FilterOperator;
 use crate::parser::ResourceType;
+use crate::schema::definitions::{AttributeDefinition, SchemaDefinition, SCHEMA_REGISTRY};
+use crate::schema::{SCIM_SCHEMA_CORE_GROUP, SCIM_SCHEMA_CORE_USER, SCIM_SCHEMA_ENTERPRISE_USER};
 use async_trait::async_trait;
 
 /// Trait for converting SCIM filters to database-specific queries
@@ -24,10 +26,20 @@ pub trait FilterConverter: Send + Sync {
     ///
     /// For example:
     /// - PostgreSQL: $1, $2, $3...
-    /// - SQLite: ?1, ?2, ?3... or just ?
-    #[allow(dead_code)]
+    /// - SQLite: ?1, ?2, ?3...
+    /// - MySQL: ? (bound in order)
     fn get_param_placeholder(&self, index: usize) -> String;
 
+    /// Null-safe `left <> right`, true when `left` is NULL
+    fn get_not_equal(&self, left: &str, right: &str) -> String;
+
+    /// 1-based position of `needle` in `haystack` (0 when absent), compared
+    /// case-exactly
+    fn get_position_function(&self, haystack: &str, needle: &str) -> String;
+
+    /// The last `length(suffix)` characters of `expression`
+    fn get_suffix_function(&self, expression: &str, suffix: &str) -> String;
+
     /// Check if a given attribute requires case-insensitive comparison
     #[allow(dead_code)]
     fn is_case_insensitive_attribute(&self, attr: &str, resource_type: ResourceType) -> bool;
@@ -41,8 +53,526 @@ pub trait FilterConverter: Send + Sync {
     /// Get the LOWER function syntax for case-insensitive comparisons
     ///
     /// Most databases use LOWER(), but this allows for customization
-    #[allow(dead_code)]
     fn get_lower_function(&self, expression: &str) -> String {
         format!("LOWER({})", expression)
     }
 }
+
+/// String comparison of an `eq`, `ne`, `co`, `sw` or `ew` filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StringMatch {
+    Equal,
+    NotEqual,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+impl StringMatch {
+    /// The string comparison a filter makes, if any
+    pub fn of(filter: &FilterOperator) -> Option<Self> {
+        match filter {
+            FilterOperator::Equal(..) => Some(Self::Equal),
+            FilterOperator::NotEqual(..) => Some(Self::NotEqual),
+            FilterOperator::Contains(..) => Some(Self::Contains),
+            FilterOperator::StartsWith(..) => Some(Self::StartsWith),
+            FilterOperator::EndsWith(..) => Some(Self::EndsWith),
+            _ => None,
+        }
+    }
+}
+
+/// SQL condition and the values for its placeholders, in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlFragment {
+    pub sql: String,
+    pub params: Vec<String>,
+}
+
+impl SqlFragment {
+    /// Append the values to the statement's parameters and return the condition
+    pub fn bind(self, params: &mut Vec<String>) -> String {
+        params.extend(self.params);
+        self.sql
+    }
+}
+
+/// Compare an attribute with a filter value, honoring the attribute's
+/// `caseExact` characteristic
+///
+/// `attr_path` is the SQL expression reading the attribute as text, and
+/// `first_param` the index of the first placeholder to use. Case-insensitive
+/// attributes must be read from data_norm, whose strings are lowercased when
+/// written: `eq` and `ne` compare it with the lowercased value directly, so
+/// expression indexes stay usable, and `co`, `sw` and `ew` are LIKE matches
+/// with `LOWER(...)` on both sides. Case-exact attributes are compared as
+/// stored, with position functions instead of LIKE, which ignores case in
+/// SQLite.
+pub fn build_comparison_sql(
+    dialect: &(impl FilterConverter + ?Sized),
+    attr_path: &str,
+    operator: StringMatch,
+    value: &serde_json::Value,
+    case_exact: bool,
+    first_param: usize,
+) -> SqlFragment {
+    let value = match value {
+        serde_json::Value::String(s) if case_exact => s.clone(),
+        serde_json::Value::String(s) => s.to_lowercase(),
+        other => other.to_string(),
+    };
+    let placeholder = dialect.get_param_placeholder(first_param);
+
+    let pattern = match operator {
+        StringMatch::Contains => Some(format!("%{}%", value)),
+        StringMatch::StartsWith => Some(format!("{}%", value)),
+        StringMatch::EndsWith => Some(format!("%{}", value)),
+        StringMatch::Equal | StringMatch::NotEqual => None,
+    };
+    if let (Some(pattern), false) = (pattern, case_exact) {
+        return SqlFragment {
+            sql: format!(
+                "{} LIKE {}",
+                dialect.get_lower_function(attr_path),
+                dialect.get_lower_function(&placeholder)
+            ),
+            params: vec![pattern],
+        };
+    }
+
+    let sql = match operator {
+        StringMatch::Equal => format!("{} = {}", attr_path, placeholder),
+        StringMatch::NotEqual => dialect.get_not_equal(attr_path, &placeholder),
+        StringMatch::Contains => {
+            format!(
+                "{} > 0",
+                dialect.get_position_function(attr_path, &placeholder)
+            )
+        }
+        StringMatch::StartsWith => {
+            format!(
+                "{} = 1",
+                dialect.get_position_function(attr_path, &placeholder)
+            )
+        }
+        StringMatch::EndsWith => {
+            // MySQL binds placeholders in order, so the value is bound twice
+            return SqlFragment {
+                sql: format!(
+                    "{} = {}",
+                    dialect.get_suffix_function(attr_path, &placeholder),
+                    dialect.get_param_placeholder(first_param + 1)
+                ),
+                params: vec![value.clone(), value],
+            };
+        }
+    };
+    SqlFragment {
+        sql,
+        params: vec![value],
+    }
+}
+
+/// Translation of a filter on a `meta` sub-attribute
+///
+/// These attributes are not kept in the stored JSON: `resourceType` is fixed
+/// by the endpoint, the timestamps live in the `created_at`/`updated_at`
+/// columns and `location` is built from the id when responding.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaFilter {
+    /// Condition that holds for every resource or for none
+    Constant(bool),
+    /// Compare a timestamp column, truncated to milliseconds, with a UTC
+    /// value formatted as `%Y-%m-%dT%H:%M:%S%.3fZ`
+    Timestamp {
+        column: &'static str,
+        operator: &'static str,
+        value: String,
+    },
+    /// Case-exact match against the resource path, e.g. `/Users/{id}`
+    Location {
+        endpoint: &'static str,
+        operator: StringMatch,
+        value: String,
+    },
+}
+
+/// Translate a comparison on `meta.resourceType`, `meta.created`,
+/// `meta.lastModified` or `meta.location`
+///
+/// Returns `None` for any other filter so the caller handles it as usual.
+pub fn meta_filter(
+    filter: &FilterOperator,
+    resource_type: ResourceType,
+) -> AppResult<Option<MetaFilter>> {
+    let (attr, value) = match filter {
+        FilterOperator::Equal(attr, value)
+        | FilterOperator::NotEqual(attr, value)
+        | FilterOperator::Contains(attr, value)
+        | FilterOperator::StartsWith(attr, value)
+        | FilterOperator::EndsWith(attr, value)
+        | FilterOperator::GreaterThan(attr, value)
+        | FilterOperator::GreaterThanOrEqual(attr, value)
+        | FilterOperator::LessThan(attr, value)
+        | FilterOperator::LessThanOrEqual(attr, value) => (attr, Some(value)),
+        FilterOperator::Present(attr) => (attr, None),
+        _ => return Ok(None),
+    };
+
+    let attr_lower = attr.to_lowercase();
+    if !matches!(
+        attr_lower.as_str(),
+        "meta.resourcetype" | "meta.created" | "meta.lastmodified" | "meta.location"
+    ) {
+        return Ok(None);
+    }
+
+    // Every resource has all of these, so presence is always satisfied
+    let Some(value) = value else {
+        return Ok(Some(MetaFilter::Constant(true)));
+    };
+
+    let unsupported = || {
+        AppError::FilterParse(format!(
+            "Unsupported operator for {} in filter: {:?}",
+            attr, filter
+        ))
+    };
+
+    match attr_lower.as_str() {
+        "meta.resourcetype" => {
+            // caseExact string compared with the endpoint's resource type
+            let actual = resource_type.to_string();
+            let actual = actual.as_str();
+            let matched = match value.as_str() {
+                Some(expected) => match filter {
+                    FilterOperator::Equal(..) => actual == expected,
+                    FilterOperator::NotEqual(..) => actual != expected,
+                    FilterOperator::Contains(..) => actual.contains(expected),
+                    FilterOperator::StartsWith(..) => actual.starts_with(expected),
+                    FilterOperator::EndsWith(..) => actual.ends_with(expected),
+                    FilterOperator::GreaterThan(..) => actual > expected,
+                    FilterOperator::GreaterThanOrEqual(..) => actual >= expected,
+                    FilterOperator::LessThan(..) => actual < expected,
+                    _ => actual <= expected,
+                },
+                // A non-string value never equals the resource type
+                None => matches!(filter, FilterOperator::NotEqual(..)),
+            };
+            Ok(Some(MetaFilter::Constant(matched)))
+        }
+        "meta.location" => {
+            let operator = StringMatch::of(filter).ok_or_else(unsupported)?;
+            let endpoint = match resource_type {
+                ResourceType::User => "/Users/",
+                ResourceType::Group => "/Groups/",
+            };
+            let value = value.as_str().ok_or_else(|| {
+                AppError::FilterParse(format!("{} must be compared with a string", attr))
+            })?;
+            // The base URL depends on how the request reached the server, so
+            // only the part from the resource endpoint onwards is compared
+            let value = value
+                .rfind(endpoint)
+                .map_or(value, |index| &value[index..])
+                .to_string();
+            Ok(Some(MetaFilter::Location {
+                endpoint,
+                operator,
+                value,
+            }))
+        }
+        _ => {
+            let column = if attr_lower == "meta.created" {
+                "created_at"
+            } else {
+                "updated_at"
+            };
+            let operator = match filter {
+                FilterOperator::Equal(..) => "=",
+                FilterOperator::NotEqual(..) => "<>",
+                FilterOperator::GreaterThan(..) => ">",
+                FilterOperator::GreaterThanOrEqual(..) => ">=",
+                FilterOperator::LessThan(..) => "<",
+                FilterOperator::LessThanOrEqual(..) => "<=",
+                _ => return Err(unsupported()),
+            };
+            let timestamp = value
+                .as_str()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .ok_or_else(|| {
+                    AppError::FilterParse(format!(
+                        "{} must be compared with an RFC 3339 dateTime",
+                        attr
+                    ))
+                })?;
+            Ok(Some(MetaFilter::Timestamp {
+                column,
+                operator,
+                value: timestamp
+                    .with_timezone(&chrono::Utc)
+                    .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+                    .to_string(),
+            }))
+        }
+    }
+}
+
+/// Check that every attribute path in a filter is defined by the schemas of
+/// the resource type
+///
+/// The converters build SQL JSON paths from attribute names, so only names
+/// found in `SCHEMA_REGISTRY` may reach them. Attributes of the core and
+/// extension schemas can be written plain or qualified with the schema URN.
+/// Inside a value path (`emails[type eq "work"]`) the names are
+/// sub-attributes of the outer attribute.
+pub fn validate_filter_attributes(
+    filter: &FilterOperator,
+    resource_type: ResourceType,
+) -> AppResult<()> {
+    match filter {
+        FilterOperator::And(left, right) | FilterOperator::Or(left, right) => {
+            validate_filter_attributes(left, resource_type)?;
+            validate_filter_attributes(right, resource_type)
+        }
+        FilterOperator::Not(inner) => validate_filter_attributes(inner, resource_type),
+        FilterOperator::Complex(attr, inner) => {
+            let definition = resolve_attribute(attr, resource_type)?;
+            if definition.sub_attributes.is_empty() {
+                return Err(unknown_attribute(attr));
+            }
+            validate_value_path_filter(attr, definition, inner)
+        }
+        _ => resolve_attribute(filter_attribute(filter), resource_type).map(|_| ()),
+    }
+}
+
+/// Sub-attribute conditions inside `attr[...]`
+fn validate_value_path_filter(
+    attr: &str,
+    definition: &AttributeDefinition,
+    filter: &FilterOperator,
+) -> AppResult<()> {
+    match filter {
+        FilterOperator::And(left, right) | FilterOperator::Or(left, right) => {
+            validate_value_path_filter(attr, definition, left)?;
+            validate_value_path_filter(attr, definition, right)
+        }
+        FilterOperator::Not(inner) => validate_value_path_filter(attr, definition, inner),
+        FilterOperator::Complex(..) => Err(AppError::InvalidFilter(format!(
+            "Nested value paths are not supported in {}",
+            attr
+        ))),
+        _ => {
+            let sub_attr = filter_attribute(filter);
+            find_path(&definition.sub_attributes, sub_attr)
+                .map(|_| ())
+                .ok_or_else(|| unknown_attribute(&format!("{}.{}", attr, sub_attr)))
+        }
+    }
+}
+
+/// Attribute of a comparison or presence filter
+fn filter_attribute(filter: &FilterOperator) -> &str {
+    match filter {
+        FilterOperator::Equal(attr, _)
+        | FilterOperator::NotEqual(attr, _)
+        | FilterOperator::Contains(attr, _)
+        | FilterOperator::StartsWith(attr, _)
+        | FilterOperator::EndsWith(attr, _)
+        | FilterOperator::GreaterThan(attr, _)
+        | FilterOperator::GreaterThanOrEqual(attr, _)
+        | FilterOperator::LessThan(attr, _)
+        | FilterOperator::LessThanOrEqual(attr, _)
+        | FilterOperator::Present(attr) => attr,
+        FilterOperator::And(..)
+        | FilterOperator::Or(..)
+        | FilterOperator::Not(..)
+        | FilterOperator::Complex(..) => "",
+    }
+}
+
+fn unknown_attribute(attr: &str) -> AppError {
+    AppError::InvalidFilter(format!("Unknown attribute in filter: {}", attr))
+}
+
+/// Whether `attr` is a simple, single-valued attribute that filters accept
+///
+/// Only such attributes can be given an expression index: anything else is
+/// either rejected by `validate_filter_attributes` or matched element by
+/// element.
+pub fn is_indexable_attribute(attr: &str, resource_type: ResourceType) -> bool {
+    resolve_attribute(attr, resource_type)
+        .is_ok_and(|definition| !definition.multi_valued && definition.sub_attributes.is_empty())
+}
+
+/// Definition of a (possibly URN-qualified, dotted) attribute path
+fn resolve_attribute(
+    attr: &str,
+    resource_type: ResourceType,
+) -> AppResult<&'static AttributeDefinition> {
+    let (core, extensions): (&str, &[&str]) = match resource_type {
+        ResourceType::User => (SCIM_SCHEMA_CORE_USER, &[SCIM_SCHEMA_ENTERPRISE_USER]),
+        ResourceType::Group => (SCIM_SCHEMA_CORE_GROUP, &[]),
+    };
+    let schema = |id: &str| -> &'static SchemaDefinition { SCHEMA_REGISTRY[id] };
+
+    let qualified = std::iter::once(core)
+        .chain(extensions.iter().copied())
+        .find_map(|id| {
+            let prefix = attr.get(..id.len() + 1)?;
+            (prefix[..id.len()].eq_ignore_ascii_case(id) && prefix.ends_with(':'))
+                .then(|| (schema(id), &attr[id.len() + 1..]))
+        });
+
+    match qualified {
+        Some((schema, path)) => find_path(&schema.attributes, path),
+        // Extension attributes sent at the top level (`department`) are
+        // stored under their plain name, so they are looked up after the core
+        None => std::iter::once(core)
+            .chain(extensions.iter().copied())
+            .find_map(|id| find_path(&schema(id).attributes, attr)),
+    }
+    .ok_or_else(|| unknown_attribute(attr))
+}
+
+/// Follow a dotted path through attribute definitions, case-insensitively
+fn find_path<'a>(
+    attributes: &'a [AttributeDefinition],
+    path: &str,
+) -> Option<&'a AttributeDefinition> {
+    let mut attributes = attributes;
+    let mut found = None;
+    for part in path.split('.') {
+        let definition = attributes
+            .iter()
+            .find(|definition| definition.name.eq_ignore_ascii_case(part))?;
+        attributes = &definition.sub_attributes;
+        found = Some(definition);
+    }
+    found
+}
+
+/// Rewrite a substring comparison on the User `locale` attribute so that it
+/// matches whole BCP 47 subtags
+///
+/// `sw "en"` matches `en` and `en-US` but not `eng`, `ew "US"` matches
+/// `en-US` but not `en-MUS`, and `co "Hant"` matches any tag with a `Hant`
+/// subtag. Returns the alternatives to be ORed together, each of which is an
+/// `eq`, `sw`, `ew` or `co` on the same attribute, or `None` when the filter
+/// needs no rewrite. Comparisons stay case-insensitive as for any other
+/// non-caseExact attribute.
+pub fn locale_filter(
+    filter: &FilterOperator,
+    resource_type: ResourceType,
+) -> Option<Vec<FilterOperator>> {
+    if resource_type != ResourceType::User {
+        return None;
+    }
+    let (attr, value) = match filter {
+        FilterOperator::StartsWith(attr, value)
+        | FilterOperator::EndsWith(attr, value)
+        | FilterOperator::Contains(attr, value) => (attr, value),
+        _ => return None,
+    };
+    if !attr.eq_ignore_ascii_case("locale") {
+        return None;
+    }
+    // A value that already carries its separator, e.g. `sw "en-"`, is a
+    // plain prefix or suffix search. This also keeps the rewritten
+    // alternatives from being rewritten again.
+    let tag = value.as_str()?;
+    if tag.is_empty() || tag.starts_with('-') || tag.ends_with('-') {
+        return None;
+    }
+
+    let attr = attr.clone();
+    let string = |s: String| serde_json::Value::String(s);
+    let mut alternatives = vec![FilterOperator::Equal(attr.clone(), string(tag.to_string()))];
+    if matches!(
+        filter,
+        FilterOperator::StartsWith(..) | FilterOperator::Contains(..)
+    ) {
+        alternatives.push(FilterOperator::StartsWith(
+            attr.clone(),
+            string(format!("{}-", tag)),
+        ));
+    }
+    if matches!(
+        filter,
+        FilterOperator::EndsWith(..) | FilterOperator::Contains(..)
+    ) {
+        alternatives.push(FilterOperator::EndsWith(
+            attr.clone(),
+            string(format!("-{}", tag)),
+        ));
+    }
+    if matches!(filter, FilterOperator::Contains(..)) {
+        alternatives.push(FilterOperator::Contains(attr, string(format!("-{}-", tag))));
+    }
+    Some(alternatives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::filter_parser::parse_filter;
+
+    fn validate(filter: &str, resource_type: ResourceType) -> AppResult<()> {
+        validate_filter_attributes(&parse_filter(filter).unwrap(), resource_type)
+    }
+
+    #[test]
+    fn test_known_attributes_are_accepted() {
+        for filter in [
+            r#"userName eq "alice""#,
+            r#"USERNAME eq "alice""#,
+            r#"name.givenName sw "A" and not (active eq false)"#,
+            r#"emails[type eq "work" and value co "@example.com"]"#,
+            r#"addresses[primary eq true].locality eq "Berlin""#,
+            r#"emails.value pr"#,
+            r#"meta.lastModified gt "2024-01-01T00:00:00Z""#,
+            r#"urn:ietf:params:scim:schemas:core:2.0:User:userName eq "alice""#,
+            r#"urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value eq "1""#,
+            r#"groups.display eq "admins""#,
+            r#"department eq "Engineering""#,
+        ] {
+            assert!(validate(filter, ResourceType::User).is_ok(), "{}", filter);
+        }
+        assert!(validate(r#"members[value eq "1"]"#, ResourceType::Group).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_attributes_are_rejected() {
+        for filter in [
+            r#"nickname2 eq "x""#,
+            r#"a'; DROP TABLE t1_users; -- eq "x""#,
+            r#"name.givenName.familyName eq "x""#,
+            r#"userName.value eq "x""#,
+            r#"emails[kind eq "work"]"#,
+            r#"userName[value eq "x"]"#,
+            r#"urn:ietf:params:scim:schemas:core:2.0:Group:displayName eq "x""#,
+            r#"urn:ietf:params:scim:schemas:core:2.0:User:department eq "x""#,
+            r#"userName eq "a" or x pr"#,
+        ] {
+            assert!(
+                matches!(
+                    validate(filter, ResourceType::User),
+                    Err(AppError::InvalidFilter(_))
+                ),
+                "{}",
+                filter
+            );
+        }
+        // Unbalanced parentheses never reach the attribute check
+        for filter in [
+            r#"a'); DROP TABLE t1_users; -- eq "x""#,
+            r#"userName') OR 1=1 OR ('' eq "x""#,
+        ] {
+            assert!(parse_filter(filter).is_err(), "{}", filter);
+        }
+        // Attributes of the other resource type
+        assert!(validate(r#"members.value eq "1""#, ResourceType::User).is_err());
+        assert!(validate(r#"userName eq "alice""#, ResourceType::Group).is_err());
+    }
+}
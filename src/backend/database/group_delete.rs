@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 
+use super::DbTransaction;
 use crate::error::AppResult;
 
 /// Database-specific adapter for group DELETE operations
@@ -7,7 +8,12 @@ use crate::error::AppResult;
 pub trait GroupDeleter: Send + Sync {
     /// Execute group delete with cascading membership cleanup
     /// Returns whether the group was found and deleted
-    async fn execute_group_delete(&self, tenant_id: u32, id: &str) -> AppResult<bool>;
+    async fn execute_group_delete(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool>;
 }
 
 /// Shared business logic for group DELETE operations
@@ -44,12 +50,17 @@ impl<T: GroupDeleter> UnifiedGroupDeleteOps<T> {
     /// This handles:
     /// - Group deletion
     /// - Cascading membership cleanup (implemented in database-specific layer)
-    pub async fn delete_group(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
+    pub async fn delete_group(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
         // Validate ID using shared business logic
         GroupDeleteProcessor::validate_group_id(id)?;
 
         // Execute database-specific deletion (includes membership cleanup)
-        self.deleter.execute_group_delete(tenant_id, id).await
+        self.deleter.execute_group_delete(tx, tenant_id, id).await
     }
 }
 
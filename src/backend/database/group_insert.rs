@@ -3,6 +3,7 @@ use chrono::{DateTime, Utc};
 use serde_json::Value;
 use uuid::Uuid;
 
+use super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::Group;
 
@@ -25,6 +26,7 @@ pub trait GroupInserter: Send + Sync {
     /// Execute group insert and return the created group
     async fn execute_group_insert(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         data: PreparedGroupData,
     ) -> AppResult<Group>;
@@ -36,10 +38,18 @@ pub struct GroupInsertProcessor;
 impl GroupInsertProcessor {
     /// Prepare group data for database insertion
     pub fn prepare_group_for_insert(group: &Group) -> AppResult<PreparedGroupData> {
-        let mut group = group.clone();
-
         // Always generate a new ID (SCIM 2.0 doesn't allow client-specified IDs in POST)
-        let id = Uuid::new_v4().to_string();
+        Self::prepare_group_for_insert_with_id(group, Uuid::new_v4().to_string())
+    }
+
+    /// Prepare group data for insertion under an id chosen by the caller
+    ///
+    /// Used when a PUT creates a missing resource (`put_creates_missing`).
+    pub fn prepare_group_for_insert_with_id(
+        group: &Group,
+        id: String,
+    ) -> AppResult<PreparedGroupData> {
+        let mut group = group.clone();
         *group.id_mut() = id.clone();
 
         let external_id = group.external_id.clone();
@@ -90,7 +100,7 @@ impl GroupInsertProcessor {
     fn set_group_metadata(group: &mut Group, timestamp: &DateTime<Utc>) {
         let formatted_time = crate::utils::format_scim_datetime(*timestamp);
         let meta = scim_v2::models::scim_schema::Meta {
-            resource_type: Some("Group".to_string()),
+            resource_type: Some(crate::parser::ResourceType::Group.scim_name().to_string()),
             created: Some(formatted_time.clone()),
             last_modified: Some(formatted_time),
             location: None,
@@ -100,6 +110,23 @@ impl GroupInsertProcessor {
     }
 }
 
+/// Map a failed membership INSERT to an application error
+///
+/// Membership rows are written inside the same transaction as the group row, so
+/// returning an error here rolls the whole create/update back. A unique violation
+/// means the request listed the same member twice, which is a client error.
+pub fn map_membership_insert_error(error: sqlx::Error, member_id: &str) -> AppError {
+    let error_str = error.to_string();
+    if error_str.contains("duplicate key") || error_str.contains("UNIQUE constraint") {
+        AppError::BadRequest(format!(
+            "Member '{}' is listed more than once in members",
+            member_id
+        ))
+    } else {
+        AppError::Database(format!("Failed to insert group member: {}", error_str))
+    }
+}
+
 /// Unified group INSERT operations using the adapter pattern
 pub struct UnifiedGroupInsertOps<T: GroupInserter> {
     inserter: T,
@@ -111,18 +138,38 @@ impl<T: GroupInserter> UnifiedGroupInsertOps<T> {
     }
 
     /// Create a new group using shared logic and database-specific execution
-    pub async fn create_group(&self, tenant_id: u32, group: &Group) -> AppResult<Group> {
+    pub async fn create_group(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group: &Group,
+    ) -> AppResult<Group> {
         // Prepare data using shared business logic
         let prepared_data = GroupInsertProcessor::prepare_group_for_insert(group)?;
 
         // Execute database-specific insertion
         let created_group = self
             .inserter
-            .execute_group_insert(tenant_id, prepared_data)
+            .execute_group_insert(tx, tenant_id, prepared_data)
             .await?;
 
         Ok(created_group)
     }
+
+    /// Create a group under the given id
+    pub async fn create_group_with_id(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Group> {
+        let prepared_data =
+            GroupInsertProcessor::prepare_group_for_insert_with_id(group, id.to_string())?;
+        self.inserter
+            .execute_group_insert(tx, tenant_id, prepared_data)
+            .await
+    }
 }
 
 #[cfg(test)]
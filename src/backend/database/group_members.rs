@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::DbTransaction;
+use crate::backend::{MembersAdded, MembersRemoved};
+use crate::error::{AppError, AppResult};
+
+/// Database-specific adapter for bulk group membership changes
+///
+/// Both operations touch only the membership table plus the group's
+/// modification metadata.
+#[async_trait]
+pub trait GroupMemberUpdater: Send + Sync {
+    /// Add the given ids as members of the group
+    ///
+    /// Ids are resolved set-wise against users, then groups; unknown ids are
+    /// reported rather than failing the batch. Returns None when the group
+    /// does not exist.
+    async fn execute_members_add(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<MembersAdded>>;
+
+    /// Remove the given ids from the group's members
+    ///
+    /// Returns None when the group does not exist.
+    async fn execute_members_remove(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<MembersRemoved>>;
+}
+
+/// Shared business logic for bulk group membership changes
+pub struct GroupMembersProcessor;
+
+impl GroupMembersProcessor {
+    /// Validate the group ID and drop blank and repeated member ids
+    ///
+    /// The first occurrence of each id is kept so the outcome lists follow
+    /// the request order.
+    pub fn prepare_ids(group_id: &str, ids: &[String]) -> AppResult<Vec<String>> {
+        if group_id.trim().is_empty() {
+            return Err(AppError::BadRequest("Group ID cannot be empty".to_string()));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        Ok(ids
+            .iter()
+            .map(|id| id.trim())
+            .filter(|id| !id.is_empty() && seen.insert(*id))
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+/// Unified bulk membership operations using the adapter pattern
+pub struct UnifiedGroupMembersOps<T: GroupMemberUpdater> {
+    updater: T,
+}
+
+impl<T: GroupMemberUpdater> UnifiedGroupMembersOps<T> {
+    pub fn new(updater: T) -> Self {
+        Self { updater }
+    }
+
+    /// Add members to a group
+    pub async fn add_members(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersAdded>> {
+        let ids = GroupMembersProcessor::prepare_ids(group_id, ids)?;
+        self.updater
+            .execute_members_add(tx, tenant_id, group_id, &ids, Utc::now())
+            .await
+    }
+
+    /// Remove members from a group
+    pub async fn remove_members(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersRemoved>> {
+        let ids = GroupMembersProcessor::prepare_ids(group_id, ids)?;
+        self.updater
+            .execute_members_remove(tx, tenant_id, group_id, &ids, Utc::now())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_ids() {
+        let ids: Vec<String> = ["b", " a ", "", "b", "c", "  "]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            GroupMembersProcessor::prepare_ids("group-1", &ids).unwrap(),
+            vec!["b", "a", "c"]
+        );
+
+        assert!(GroupMembersProcessor::prepare_ids(" ", &ids).is_err());
+    }
+}
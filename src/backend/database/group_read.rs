@@ -3,8 +3,9 @@
 //! This module provides common interfaces for group read operations
 //! that work across different database backends.
 
+use super::list_query::{external_id_lookup, single_result_page};
 use crate::error::AppResult;
-use crate::models::{Group, ScimPatchOp};
+use crate::models::Group;
 use crate::parser::filter_operator::FilterOperator;
 use crate::parser::SortSpec;
 use async_trait::async_trait;
@@ -22,6 +23,13 @@ pub trait GroupReader: Send + Sync {
         display_name: &str,
     ) -> AppResult<Option<Group>>;
 
+    /// Find a group by externalId (case-exact) through its indexed column
+    async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>>;
+
     /// Find all groups with pagination
     async fn find_all_groups(
         &self,
@@ -48,17 +56,6 @@ pub trait GroupReader: Send + Sync {
         count: Option<i64>,
         sort_spec: Option<&SortSpec>,
     ) -> AppResult<(Vec<Group>, i64)>;
-
-    /// Find groups by user ID
-    async fn find_groups_by_user_id(&self, tenant_id: u32, user_id: &str) -> AppResult<Vec<Group>>;
-
-    /// Apply SCIM PATCH operations to a group (needs read for validation)
-    async fn patch_group(
-        &self,
-        tenant_id: u32,
-        id: &str,
-        patch_ops: &ScimPatchOp,
-    ) -> AppResult<Option<Group>>;
 }
 
 /// Unified group read operations
@@ -90,6 +87,17 @@ impl<T: GroupReader> UnifiedGroupReadOps<T> {
             .await
     }
 
+    /// Find a group by externalId (case-exact)
+    pub async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.reader
+            .find_group_by_external_id(tenant_id, external_id)
+            .await
+    }
+
     /// Find all groups with pagination
     pub async fn find_all_groups(
         &self,
@@ -124,27 +132,17 @@ impl<T: GroupReader> UnifiedGroupReadOps<T> {
         count: Option<i64>,
         sort_spec: Option<&SortSpec>,
     ) -> AppResult<(Vec<Group>, i64)> {
+        // IdP reconciliation looks groups up by externalId on every sync
+        if let Some(external_id) = external_id_lookup(filter) {
+            let group = self
+                .reader
+                .find_group_by_external_id(tenant_id, external_id)
+                .await?;
+            return Ok(single_result_page(group, start_index, count));
+        }
+
         self.reader
             .find_groups_by_filter(tenant_id, filter, start_index, count, sort_spec)
             .await
     }
-
-    /// Find groups by user ID
-    pub async fn find_groups_by_user_id(
-        &self,
-        tenant_id: u32,
-        user_id: &str,
-    ) -> AppResult<Vec<Group>> {
-        self.reader.find_groups_by_user_id(tenant_id, user_id).await
-    }
-
-    /// Apply SCIM PATCH operations to a group
-    pub async fn patch_group(
-        &self,
-        tenant_id: u32,
-        id: &str,
-        patch_ops: &ScimPatchOp,
-    ) -> AppResult<Option<Group>> {
-        self.reader.patch_group(tenant_id, id, patch_ops).await
-    }
 }
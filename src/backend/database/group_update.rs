@@ -2,22 +2,37 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
 
+use super::DbTransaction;
+use crate::config::CompatibilityConfig;
 use crate::error::{AppError, AppResult};
-use crate::models::Group;
+use crate::models::{Group, ScimPatchOp, ScimPatchOperation};
+use crate::parser::patch_parser::ScimPath;
 
 /// Common trait for group update operations across different database backends
 #[async_trait]
 pub trait GroupUpdater: Send + Sync {
     /// Execute group update and return the updated group
     ///
-    /// This should handle both the group data update and member relationship management
-    /// in a transactional manner to ensure data consistency.
+    /// This handles both the group data update and member relationship
+    /// management on the given transaction.
     async fn execute_group_update(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         data: PreparedGroupUpdateData,
     ) -> AppResult<Option<Group>>;
+
+    /// Find a group with its members for patch operations
+    ///
+    /// Read on the patch's transaction, so the operations apply to the
+    /// stored state the update replaces.
+    async fn find_group_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>>;
 }
 
 /// Prepared group data for database update operations
@@ -108,11 +123,82 @@ impl GroupUpdateProcessor {
         Ok(())
     }
 
+    /// Apply a single SCIM PATCH operation to a group's JSON representation
+    ///
+    /// Operations with a path go through `ScimPath`. Without a path the value is
+    /// a partial group object (RFC 7644 Section 3.5.2.1 and 3.5.2.3): `add` appends
+    /// its `members` to the existing list, skipping members that are already
+    /// present, while every other attribute (and everything for `replace`) is
+    /// overwritten.
+    pub fn apply_patch_operation(
+        group_json: &mut Value,
+        operation: &ScimPatchOperation,
+    ) -> AppResult<()> {
+        let value = operation.value.as_ref().unwrap_or(&Value::Null);
+
+        let path = match operation.path.as_deref() {
+            Some(path) if !path.is_empty() => path,
+            _ => return Self::apply_pathless_operation(group_json, &operation.op, value),
+        };
+
+        ScimPath::parse(path)?.apply_operation(group_json, &operation.op, value)
+    }
+
+    fn apply_pathless_operation(group_json: &mut Value, op: &str, value: &Value) -> AppResult<()> {
+        if op != "add" && op != "replace" {
+            return Err(AppError::BadRequest(format!(
+                "Operation '{}' requires a path",
+                op
+            )));
+        }
+
+        let Value::Object(attributes) = value else {
+            return Err(AppError::BadRequest(
+                "Value must be an object when path is omitted".to_string(),
+            ));
+        };
+        let Value::Object(target) = group_json else {
+            return Err(AppError::BadRequest(
+                "Cannot set value: group is not an object".to_string(),
+            ));
+        };
+
+        for (key, new_value) in attributes {
+            match (op, key.as_str(), new_value) {
+                ("add", "members", Value::Array(new_members)) => {
+                    let members = target
+                        .entry("members")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if !members.is_array() {
+                        *members = Value::Array(Vec::new());
+                    }
+                    if let Value::Array(existing) = members {
+                        for member in new_members {
+                            let already_present = existing
+                                .iter()
+                                .any(|m| m.get("value") == member.get("value"));
+                            if !already_present {
+                                existing.push(member.clone());
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    target.insert(key.clone(), new_value.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Set group metadata for update operations
     ///
-    /// This updates the lastModified timestamp in the SCIM meta attribute.
+    /// This updates the lastModified timestamp in the SCIM meta attribute and
+    /// normalizes resourceType, which clients may send in any casing.
     fn set_group_metadata(group: &mut Group, timestamp: &DateTime<Utc>) {
         if let Some(meta) = group.meta_mut() {
+            meta.resource_type = Some(crate::parser::ResourceType::Group.scim_name().to_string());
             meta.last_modified = Some(crate::utils::format_scim_datetime(*timestamp));
         }
     }
@@ -133,10 +219,10 @@ impl<T: GroupUpdater> UnifiedGroupUpdateOps<T> {
 
     /// Update a group with full validation and processing
     ///
-    /// This handles both the group data and member relationship updates
-    /// in a transactional manner.
+    /// This handles both the group data and member relationship updates.
     pub async fn update_group(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         group: &Group,
@@ -149,9 +235,62 @@ impl<T: GroupUpdater> UnifiedGroupUpdateOps<T> {
 
         // Execute the update via database-specific implementation
         self.updater
-            .execute_group_update(tenant_id, id, prepared)
+            .execute_group_update(tx, tenant_id, id, prepared)
             .await
     }
+
+    /// Find a group with its members on the caller's transaction
+    pub async fn find_group(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        if id.is_empty() {
+            return Ok(None);
+        }
+        self.updater.find_group_for_patch(tx, tenant_id, id).await
+    }
+
+    /// Apply SCIM PATCH operations to a group
+    ///
+    /// Every operation is applied in memory before anything is written, so
+    /// an invalid operation leaves the stored group unchanged. A replaced
+    /// member list is deleted and re-inserted on the caller's transaction,
+    /// so concurrent readers see either the old or the new members, never an
+    /// empty group in between. With `compatibility.enforce_immutability` an
+    /// operation on an immutable attribute such as `members.value` is refused.
+    pub async fn patch_group(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
+    ) -> AppResult<Option<Group>> {
+        if id.is_empty() {
+            return Ok(None);
+        }
+
+        let mut group = match self.updater.find_group_for_patch(tx, tenant_id, id).await? {
+            Some(group) => group,
+            None => return Ok(None),
+        };
+
+        for operation in &patch_ops.operations {
+            if compatibility.enforce_immutability {
+                crate::schema::validate_patch_mutability(
+                    operation,
+                    crate::parser::ResourceType::Group,
+                )?;
+            }
+            let mut group_json = serde_json::to_value(&group).map_err(AppError::Serialization)?;
+            GroupUpdateProcessor::apply_patch_operation(&mut group_json, operation)?;
+            group = serde_json::from_value(group_json).map_err(AppError::Serialization)?;
+        }
+
+        self.update_group(tx, tenant_id, id, &group).await
+    }
 }
 
 #[cfg(test)]
@@ -238,4 +377,53 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_apply_patch_operation_without_path() {
+        let mut group_json = serde_json::json!({
+            "displayName": "Before",
+            "members": [{"value": "u1"}]
+        });
+        let operation = |op: &str, value: Value| ScimPatchOperation {
+            op: op.to_string(),
+            path: None,
+            value: Some(value),
+        };
+
+        GroupUpdateProcessor::apply_patch_operation(
+            &mut group_json,
+            &operation(
+                "add",
+                serde_json::json!({
+                    "displayName": "After",
+                    "members": [{"value": "u1"}, {"value": "u2"}, {"value": "u2"}]
+                }),
+            ),
+        )
+        .unwrap();
+        assert_eq!(group_json["displayName"], "After");
+        assert_eq!(
+            group_json["members"],
+            serde_json::json!([{"value": "u1"}, {"value": "u2"}])
+        );
+
+        GroupUpdateProcessor::apply_patch_operation(
+            &mut group_json,
+            &operation("replace", serde_json::json!({"members": [{"value": "u3"}]})),
+        )
+        .unwrap();
+        assert_eq!(group_json["members"], serde_json::json!([{"value": "u3"}]));
+
+        // Non-object values and pathless remove are rejected
+        assert!(GroupUpdateProcessor::apply_patch_operation(
+            &mut group_json,
+            &operation("add", serde_json::json!([{"value": "u4"}])),
+        )
+        .is_err());
+        assert!(GroupUpdateProcessor::apply_patch_operation(
+            &mut group_json,
+            &operation("remove", Value::Null),
+        )
+        .is_err());
+    }
 }
@@ -0,0 +1,135 @@
+//! SQL for paginated list requests
+//!
+//! The COUNT behind `totalResults` and the query for the requested page are
+//! built from the same FROM and WHERE clause, so the total always counts
+//! exactly the rows the pages are taken from.
+
+use serde_json::Value;
+
+use crate::parser::filter_operator::FilterOperator;
+
+/// Page size when the request has no `count`
+const DEFAULT_COUNT: i64 = 100;
+
+/// Largest page returned for one request
+const MAX_COUNT: i64 = 1000;
+
+/// LIMIT and OFFSET for a 1-based SCIM `startIndex` and `count`
+pub fn page_bounds(start_index: Option<i64>, count: Option<i64>) -> (i64, i64) {
+    let offset = start_index.unwrap_or(1).saturating_sub(1).max(0);
+    let limit = count.unwrap_or(DEFAULT_COUNT).clamp(0, MAX_COUNT);
+    (limit, offset)
+}
+
+/// The value of a filter that is exactly `externalId eq "<value>"`
+///
+/// Such filters are answered from the indexed `external_id` column.
+pub fn external_id_lookup(filter: &FilterOperator) -> Option<&str> {
+    match filter {
+        FilterOperator::Equal(attr, Value::String(value))
+            if attr.eq_ignore_ascii_case("externalId") =>
+        {
+            Some(value)
+        }
+        _ => None,
+    }
+}
+
+/// The requested page of a lookup that matches at most one resource
+pub fn single_result_page<T>(
+    resource: Option<T>,
+    start_index: Option<i64>,
+    count: Option<i64>,
+) -> (Vec<T>, i64) {
+    let total = resource.is_some() as i64;
+    let (limit, offset) = page_bounds(start_index, count);
+    let page = resource
+        .filter(|_| offset == 0 && limit > 0)
+        .into_iter()
+        .collect();
+    (page, total)
+}
+
+/// COUNT and page statements of one list request
+pub struct ListQuery {
+    from_where: String,
+}
+
+impl ListQuery {
+    /// List rows of `table`, optionally restricted by a filter condition
+    pub fn new(table: &str, condition: Option<&str>) -> Self {
+        let from_where = match condition {
+            Some(condition) => format!("{} WHERE ({})", table, condition),
+            None => table.to_string(),
+        };
+        Self { from_where }
+    }
+
+    /// Statement counting every matching row
+    pub fn count_sql(&self) -> String {
+        format!("SELECT COUNT(*) FROM {}", self.from_where)
+    }
+
+    /// Statement selecting one page of the matching rows
+    ///
+    /// `order_by` is a complete clause with a leading space; `limit` and
+    /// `offset` are the database's placeholders for the page bounds.
+    pub fn page_sql(&self, columns: &str, order_by: &str, limit: &str, offset: &str) -> String {
+        format!(
+            "SELECT {} FROM {}{} LIMIT {} OFFSET {}",
+            columns, self.from_where, order_by, limit, offset
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_id_lookup() {
+        let lookup = FilterOperator::Equal("externalId".to_string(), Value::from("A-1"));
+        assert_eq!(external_id_lookup(&lookup), Some("A-1"));
+
+        let other = FilterOperator::Equal("userName".to_string(), Value::from("A-1"));
+        let combined = FilterOperator::And(Box::new(lookup.clone()), Box::new(other.clone()));
+        assert_eq!(external_id_lookup(&other), None);
+        assert_eq!(external_id_lookup(&combined), None);
+    }
+
+    #[test]
+    fn test_single_result_page() {
+        assert_eq!(single_result_page(Some(1), None, None), (vec![1], 1));
+        assert_eq!(single_result_page(Some(1), Some(2), None), (vec![], 1));
+        assert_eq!(single_result_page(Some(1), None, Some(0)), (vec![], 1));
+        assert_eq!(single_result_page(None::<i32>, None, None), (vec![], 0));
+    }
+
+    #[test]
+    fn test_page_bounds() {
+        assert_eq!(page_bounds(None, None), (100, 0));
+        assert_eq!(page_bounds(Some(11), Some(5)), (5, 10));
+        assert_eq!(page_bounds(Some(0), Some(5000)), (1000, 0));
+        assert_eq!(page_bounds(Some(-3), Some(0)), (0, 0));
+    }
+
+    #[test]
+    fn test_count_and_page_share_where_clause() {
+        let query = ListQuery::new("t1_users", Some("username = ?1"));
+        assert_eq!(
+            query.count_sql(),
+            "SELECT COUNT(*) FROM t1_users WHERE (username = ?1)"
+        );
+        assert_eq!(
+            query.page_sql("id", " ORDER BY created_at, id", "?2", "?3"),
+            "SELECT id FROM t1_users WHERE (username = ?1) ORDER BY created_at, id LIMIT ?2 OFFSET ?3"
+        );
+
+        let query = ListQuery::new("t1_groups", None);
+        assert_eq!(query.count_sql(), "SELECT COUNT(*) FROM t1_groups");
+        assert_eq!(
+            query.page_sql("id", "", "$1", "$2"),
+            "SELECT id FROM t1_groups LIMIT $1 OFFSET $2"
+        );
+    }
+}
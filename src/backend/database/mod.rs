@@ -1,7 +1,7 @@
 //! Database abstraction layer for SCIM server
 //!
 //! This module provides a unified interface for database operations across
-//! different database backends (PostgreSQL, SQLite) while maintaining
+//! different database backends (PostgreSQL, SQLite, MySQL/MariaDB) while maintaining
 //! database-specific optimizations where needed.
 //!
 //! # Architecture
@@ -10,18 +10,32 @@
 //! Common Logic (user_operations.rs, group_operations.rs, etc.)
 //!     ↓
 //! Database-specific implementations
+//!     ├── mysql/    (MySQL/MariaDB-specific code)
 //!     ├── postgres/ (PostgreSQL-specific code)
 //!     └── sqlite/   (SQLite-specific code)
 //! ```
+//!
+//! Write operations run on a [`DbTransaction`] begun by the backend
+//! (`Backend::begin_transaction`), so a multi-statement flow such as a PATCH
+//! commits as a whole or not at all.
 
 pub mod config;
+pub mod connection;
 pub mod filter;
 pub mod group_delete;
 pub mod group_insert;
+pub mod group_members;
 pub mod group_read;
 pub mod group_update;
+pub mod list_query;
+#[cfg(feature = "backend-mysql")]
+pub mod mysql;
+#[cfg(feature = "backend-postgres")]
 pub mod postgres;
+#[cfg(feature = "backend-sqlite")]
 pub mod sqlite;
+pub mod tenants;
+pub mod transaction;
 pub mod user_delete;
 pub mod user_insert;
 pub mod user_patch;
@@ -34,6 +48,10 @@ mod integration_test;
 // Re-export key types for convenience
 pub use config::DatabaseBackendConfig;
 
+pub use connection::map_connection_error;
+
+pub use transaction::DbTransaction;
+
 pub use user_insert::UnifiedUserInsertOps;
 
 pub use group_insert::UnifiedGroupInsertOps;
@@ -46,6 +64,8 @@ pub use user_update::UnifiedUserUpdateOps;
 
 pub use group_update::UnifiedGroupUpdateOps;
 
+pub use group_members::UnifiedGroupMembersOps;
+
 pub use user_patch::UnifiedUserPatchOps;
 
 pub use user_read::UnifiedUserReadOps;
@@ -53,12 +73,21 @@ pub use user_read::UnifiedUserReadOps;
 pub use group_read::UnifiedGroupReadOps;
 
 // Re-export database-specific implementations (excluding unused backends)
+#[cfg(feature = "backend-mysql")]
+pub use mysql::{
+    MySqlGroupDeleter, MySqlGroupInserter, MySqlGroupMemberUpdater, MySqlGroupReader,
+    MySqlGroupUpdater, MySqlUserDeleter, MySqlUserInserter, MySqlUserPatcher, MySqlUserReader,
+    MySqlUserUpdater,
+};
+#[cfg(feature = "backend-postgres")]
 pub use postgres::{
-    PostgresGroupDeleter, PostgresGroupInserter, PostgresGroupReader, PostgresGroupUpdater,
-    PostgresUserDeleter, PostgresUserInserter, PostgresUserPatcher, PostgresUserReader,
-    PostgresUserUpdater,
+    PostgresGroupDeleter, PostgresGroupInserter, PostgresGroupMemberUpdater, PostgresGroupReader,
+    PostgresGroupUpdater, PostgresUserDeleter, PostgresUserInserter, PostgresUserPatcher,
+    PostgresUserReader, PostgresUserUpdater,
 };
+#[cfg(feature = "backend-sqlite")]
 pub use sqlite::{
-    SqliteGroupDeleter, SqliteGroupInserter, SqliteGroupReader, SqliteGroupUpdater,
-    SqliteUserDeleter, SqliteUserInserter, SqliteUserPatcher, SqliteUserReader, SqliteUserUpdater,
+    SqliteGroupDeleter, SqliteGroupInserter, SqliteGroupMemberUpdater, SqliteGroupReader,
+    SqliteGroupUpdater, SqliteUserDeleter, SqliteUserInserter, SqliteUserPatcher, SqliteUserReader,
+    SqliteUserUpdater,
 };
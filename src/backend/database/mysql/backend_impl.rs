@@ -0,0 +1,804 @@
+use async_trait::async_trait;
+use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
+use sqlx::MySqlPool;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::super::config::DatabaseBackendConfig;
+use crate::backend::database::transaction::{self, DbTransaction};
+use crate::backend::database::{
+    map_connection_error, MySqlGroupDeleter, MySqlGroupInserter, MySqlGroupMemberUpdater,
+    MySqlGroupReader, MySqlGroupUpdater, MySqlUserDeleter, MySqlUserInserter, MySqlUserPatcher,
+    MySqlUserReader, MySqlUserUpdater, UnifiedGroupDeleteOps, UnifiedGroupInsertOps,
+    UnifiedGroupMembersOps, UnifiedGroupReadOps, UnifiedGroupUpdateOps, UnifiedUserDeleteOps,
+    UnifiedUserInsertOps, UnifiedUserPatchOps, UnifiedUserReadOps, UnifiedUserUpdateOps,
+};
+use crate::backend::{
+    Backend, GroupBackend, HistoryBackend, MembersAdded, MembersRemoved, Provenance,
+    ProvenanceBackend, UserBackend,
+};
+use crate::config::CompatibilityConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::ScimPatchOp;
+use crate::models::{Group, User};
+use crate::parser::filter_operator::FilterOperator;
+use crate::parser::{ResourceType, SortSpec};
+
+use super::filter_impl::MySqlFilterConverter;
+
+/// MySQL database backend implementation
+///
+/// This provides a complete SCIM 2.0 database backend using MySQL
+/// with support for JSON columns, complex filtering, and tenant isolation.
+pub struct MySqlBackend {
+    pool: MySqlPool,
+    #[allow(dead_code)]
+    filter_converter: MySqlFilterConverter,
+    // New operations
+    user_insert_ops: UnifiedUserInsertOps<MySqlUserInserter>,
+    user_update_ops: UnifiedUserUpdateOps<MySqlUserUpdater>,
+    user_delete_ops: UnifiedUserDeleteOps<MySqlUserDeleter>,
+    user_patch_ops: UnifiedUserPatchOps<MySqlUserPatcher>,
+    user_read_ops: UnifiedUserReadOps<MySqlUserReader>,
+    group_insert_ops: UnifiedGroupInsertOps<MySqlGroupInserter>,
+    group_update_ops: UnifiedGroupUpdateOps<MySqlGroupUpdater>,
+    group_delete_ops: UnifiedGroupDeleteOps<MySqlGroupDeleter>,
+    group_read_ops: UnifiedGroupReadOps<MySqlGroupReader>,
+    group_members_ops: UnifiedGroupMembersOps<MySqlGroupMemberUpdater>,
+    /// See `DatabaseBackendConfig::external_id_case_insensitive_tenants`
+    case_insensitive_external_ids: Arc<HashSet<u32>>,
+}
+
+impl MySqlBackend {
+    /// Create a new MySQL backend instance
+    pub fn new(pool: MySqlPool) -> Self {
+        // Create database-specific adapters
+        let user_inserter = MySqlUserInserter::new();
+        let user_updater = MySqlUserUpdater::new();
+        let user_deleter = MySqlUserDeleter::new();
+        let user_patcher = MySqlUserPatcher::new();
+        let user_reader = MySqlUserReader::new(pool.clone());
+        let group_inserter = MySqlGroupInserter::new();
+        let group_updater = MySqlGroupUpdater::new();
+        let group_deleter = MySqlGroupDeleter::new();
+        let group_reader = MySqlGroupReader::new(pool.clone());
+        let group_member_updater = MySqlGroupMemberUpdater::new();
+
+        Self {
+            pool,
+            filter_converter: MySqlFilterConverter::new(),
+            // Initialize unified operations
+            user_insert_ops: UnifiedUserInsertOps::new(user_inserter),
+            user_update_ops: UnifiedUserUpdateOps::new(user_updater),
+            user_delete_ops: UnifiedUserDeleteOps::new(user_deleter),
+            user_patch_ops: UnifiedUserPatchOps::new(user_patcher),
+            user_read_ops: UnifiedUserReadOps::new(user_reader),
+            group_insert_ops: UnifiedGroupInsertOps::new(group_inserter),
+            group_update_ops: UnifiedGroupUpdateOps::new(group_updater),
+            group_delete_ops: UnifiedGroupDeleteOps::new(group_deleter),
+            group_read_ops: UnifiedGroupReadOps::new(group_reader),
+            group_members_ops: UnifiedGroupMembersOps::new(group_member_updater),
+            case_insensitive_external_ids: Arc::default(),
+        }
+    }
+
+    /// Match externalId case-insensitively in the given tenants
+    /// (`external_id_case_insensitive`)
+    pub fn with_case_insensitive_external_ids(mut self, tenants: HashSet<u32>) -> Self {
+        let tenants = Arc::new(tenants);
+        self.case_insensitive_external_ids = tenants.clone();
+        self.user_read_ops = UnifiedUserReadOps::new(
+            MySqlUserReader::new(self.pool.clone())
+                .with_case_insensitive_external_ids(tenants.clone()),
+        );
+        self.group_read_ops = UnifiedGroupReadOps::new(
+            MySqlGroupReader::new(self.pool.clone()).with_case_insensitive_external_ids(tenants),
+        );
+        self
+    }
+
+    /// Fail a write in a tenant matching externalId case-insensitively when
+    /// another resource holds the same externalId in a different case
+    ///
+    /// Runs on the write's transaction after the write, so failing rolls it
+    /// back. Exact duplicates are already rejected by the unique column.
+    async fn check_external_id_case(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        id: &str,
+        external_id: Option<&str>,
+    ) -> AppResult<()> {
+        let Some(external_id) = external_id else {
+            return Ok(());
+        };
+        if !self.case_insensitive_external_ids.contains(&tenant_id) {
+            return Ok(());
+        }
+        let (table, holder) = match resource_type {
+            ResourceType::User => (format!("t{}_users", tenant_id), "user"),
+            ResourceType::Group => (format!("t{}_groups", tenant_id), "group"),
+        };
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(external_id) = LOWER(?) AND id <> ?",
+            table
+        );
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(external_id)
+            .bind(id)
+            .fetch_one(&mut *tx.mysql()?)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate externalId: {}", e))
+            })?;
+        if count > 0 {
+            return Err(AppError::Conflict(format!(
+                "externalId is already in use by another {}",
+                holder
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get the connection pool reference
+    #[allow(dead_code)]
+    pub fn pool(&self) -> &MySqlPool {
+        &self.pool
+    }
+
+    /// Get the filter converter reference
+    #[allow(dead_code)]
+    pub fn filter_converter(&self) -> &MySqlFilterConverter {
+        &self.filter_converter
+    }
+
+    /// Generate table name for a resource type and tenant  
+    /// Tables are named as: t{tenant_id}_{resource}
+    #[allow(dead_code)]
+    pub fn table_name(&self, resource: &str, tenant_id: u32) -> String {
+        format!("t{}_{}", tenant_id, resource)
+    }
+
+    /// Get users table name for a tenant
+    #[allow(dead_code)]
+    pub fn users_table(&self, tenant_id: u32) -> String {
+        self.table_name("users", tenant_id)
+    }
+
+    /// Get groups table name for a tenant
+    #[allow(dead_code)]
+    pub fn groups_table(&self, tenant_id: u32) -> String {
+        self.table_name("groups", tenant_id)
+    }
+
+    /// Get group memberships table name for a tenant
+    #[allow(dead_code)]
+    pub fn memberships_table(&self, tenant_id: u32) -> String {
+        self.table_name("group_memberships", tenant_id)
+    }
+}
+
+#[async_trait]
+impl Backend for MySqlBackend {
+    async fn connect(config: &DatabaseBackendConfig) -> AppResult<Self> {
+        // Validate configuration
+        config
+            .validate()
+            .map_err(|e| AppError::Internal(format!("Invalid backend config: {}", e)))?;
+
+        // Binary collation keeps string comparisons case-exact as in the
+        // other backends; case-insensitive matches use LOWER() explicitly
+        let options = MySqlConnectOptions::from_str(&config.connection_path)
+            .map_err(|e| {
+                AppError::Database(format!(
+                    "Invalid MySQL connection string '{}': {}",
+                    config.connection_path, e
+                ))
+            })?
+            .charset("utf8mb4")
+            .collation("utf8mb4_bin");
+
+        // Create connection pool
+        let pool = MySqlPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(config.connection_timeout))
+            .connect_with(options)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to connect to MySQL: {}", e)))?;
+
+        Ok(Self::new(pool).with_case_insensitive_external_ids(
+            config.external_id_case_insensitive_tenants.clone(),
+        ))
+    }
+
+    async fn health_check(&self) -> AppResult<()> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| {
+                map_connection_error(AppError::Database(format!("Health check failed: {}", e)))
+            })?;
+
+        Ok(())
+    }
+
+    async fn begin_transaction(&self) -> AppResult<DbTransaction> {
+        let tx = transaction::begin(&self.pool)
+            .await
+            .map_err(map_connection_error)?;
+        Ok(DbTransaction::from(tx))
+    }
+
+    async fn init_tenant(&self, tenant_id: u32) -> AppResult<()> {
+        super::schema::init_tenant_schema(&self.pool, tenant_id).await
+    }
+
+    async fn list_initialized_tenants(&self) -> AppResult<Vec<u32>> {
+        super::schema::list_tenant_schemas(&self.pool)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn vacuum_tenant(&self, tenant_id: u32) -> AppResult<()> {
+        super::schema::vacuum_tenant_schema(&self.pool, tenant_id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn load_sync_stats(&self, tenant_id: u32) -> AppResult<Option<serde_json::Value>> {
+        super::schema::load_sync_stats(&self.pool, tenant_id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn save_sync_stats(&self, tenant_id: u32, snapshot: &serde_json::Value) -> AppResult<()> {
+        super::schema::save_sync_stats(&self.pool, tenant_id, snapshot)
+            .await
+            .map_err(map_connection_error)
+    }
+}
+
+#[async_trait]
+impl UserBackend for MySqlBackend {
+    async fn create_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        user: &User,
+    ) -> AppResult<User> {
+        let user = self
+            .user_insert_ops
+            .create_user(tx, tenant_id, user)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::User,
+            user.id().as_deref().unwrap_or_default(),
+            user.external_id.as_deref(),
+        )
+        .await?;
+        Ok(user)
+    }
+
+    async fn create_user_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<User> {
+        let user = self
+            .user_insert_ops
+            .create_user_with_id(tx, tenant_id, id, user)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::User,
+            user.id().as_deref().unwrap_or_default(),
+            user.external_id.as_deref(),
+        )
+        .await?;
+        Ok(user)
+    }
+
+    async fn find_user_by_id(
+        &self,
+        tenant_id: u32,
+        id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.user_read_ops
+            .find_user_by_id(tenant_id, id, include_groups)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_user_by_username(
+        &self,
+        tenant_id: u32,
+        username: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.user_read_ops
+            .find_user_by_username(tenant_id, username, include_groups)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.user_read_ops
+            .find_user_by_external_id(tenant_id, external_id, include_groups)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_all_users(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.user_read_ops
+            .find_all_users(tenant_id, start_index, count, include_groups)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_all_users_sorted(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.user_read_ops
+            .find_all_users_sorted(tenant_id, start_index, count, sort_spec, include_groups)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_users_by_filter(
+        &self,
+        tenant_id: u32,
+        filter: &FilterOperator,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.user_read_ops
+            .find_users_by_filter(
+                tenant_id,
+                filter,
+                start_index,
+                count,
+                sort_spec,
+                include_groups,
+            )
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn update_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<Option<User>> {
+        let updated = self
+            .user_update_ops
+            .update_user(tx, tenant_id, id, user)
+            .await
+            .map_err(map_connection_error)?;
+        if let Some(user) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::User,
+                id,
+                user.external_id.as_deref(),
+            )
+            .await?;
+        }
+        Ok(updated)
+    }
+
+    async fn patch_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
+    ) -> AppResult<Option<User>> {
+        // Read, patch and write the user on the same transaction
+        let patched = self
+            .user_patch_ops
+            .patch_user(tx, tenant_id, id, patch_ops, compatibility)
+            .await
+            .map_err(map_connection_error)?;
+        if let Some(user) = &patched {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::User,
+                id,
+                user.external_id.as_deref(),
+            )
+            .await?;
+        }
+        Ok(patched)
+    }
+
+    async fn delete_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        self.user_delete_ops
+            .delete_user(tx, tenant_id, id)
+            .await
+            .map_err(map_connection_error)
+    }
+}
+
+#[async_trait]
+impl GroupBackend for MySqlBackend {
+    async fn create_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group: &Group,
+    ) -> AppResult<Group> {
+        // The group and its memberships are written together
+        let group = self
+            .group_insert_ops
+            .create_group(tx, tenant_id, group)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::Group,
+            group.id(),
+            group.external_id.as_deref(),
+        )
+        .await?;
+        Ok(group)
+    }
+
+    async fn create_group_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Group> {
+        // The group and its memberships are written together
+        let group = self
+            .group_insert_ops
+            .create_group_with_id(tx, tenant_id, id, group)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::Group,
+            group.id(),
+            group.external_id.as_deref(),
+        )
+        .await?;
+        Ok(group)
+    }
+
+    async fn find_group_by_id(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
+        self.group_read_ops
+            .find_group_by_id(tenant_id, id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_group_by_display_name(
+        &self,
+        tenant_id: u32,
+        display_name: &str,
+    ) -> AppResult<Option<Group>> {
+        self.group_read_ops
+            .find_group_by_display_name(tenant_id, display_name)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.group_read_ops
+            .find_group_by_external_id(tenant_id, external_id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_all_groups(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.group_read_ops
+            .find_all_groups(tenant_id, start_index, count)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_all_groups_sorted(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.group_read_ops
+            .find_all_groups_sorted(tenant_id, start_index, count, sort_spec)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_groups_by_filter(
+        &self,
+        tenant_id: u32,
+        filter: &FilterOperator,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.group_read_ops
+            .find_groups_by_filter(tenant_id, filter, start_index, count, sort_spec)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn update_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Option<Group>> {
+        let updated = self
+            .group_update_ops
+            .update_group(tx, tenant_id, id, group)
+            .await
+            .map_err(map_connection_error)?;
+        if let Some(group) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::Group,
+                id,
+                group.external_id.as_deref(),
+            )
+            .await?;
+        }
+        Ok(updated)
+    }
+
+    async fn patch_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
+    ) -> AppResult<Option<Group>> {
+        // Read, patch and write the group on the same transaction
+        let updated = self
+            .group_update_ops
+            .patch_group(tx, tenant_id, id, patch_ops, compatibility)
+            .await
+            .map_err(map_connection_error)?;
+        if let Some(group) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::Group,
+                id,
+                group.external_id.as_deref(),
+            )
+            .await?;
+        }
+        Ok(updated)
+    }
+
+    async fn delete_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        self.group_delete_ops
+            .delete_group(tx, tenant_id, id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn add_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersAdded>> {
+        self.group_members_ops
+            .add_members(tx, tenant_id, group_id, ids)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn remove_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersRemoved>> {
+        self.group_members_ops
+            .remove_members(tx, tenant_id, group_id, ids)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_group_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.group_update_ops
+            .find_group(tx, tenant_id, id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_group_ids_with_member_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        member_id: &str,
+    ) -> AppResult<Vec<String>> {
+        let sql = format!(
+            "SELECT group_id FROM {} WHERE member_id = ? ORDER BY group_id",
+            self.memberships_table(tenant_id)
+        );
+        sqlx::query_scalar(&sql)
+            .bind(member_id)
+            .fetch_all(tx.mysql()?)
+            .await
+            .map_err(|e| {
+                map_connection_error(AppError::Database(format!(
+                    "Failed to find member's groups: {}",
+                    e
+                )))
+            })
+    }
+
+    async fn find_effective_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<Option<(Vec<User>, i64)>> {
+        self.user_read_ops
+            .find_effective_members(
+                tenant_id,
+                group_id,
+                max_depth,
+                start_index,
+                count,
+                include_groups,
+            )
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn count_group_members(
+        &self,
+        tenant_id: u32,
+        group_ids: &[String],
+    ) -> AppResult<HashMap<String, u64>> {
+        if group_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let placeholders = vec!["?"; group_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT group_id, COUNT(*) FROM {} WHERE group_id IN ({}) GROUP BY group_id",
+            self.memberships_table(tenant_id),
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, (String, i64)>(&sql);
+        for id in group_ids {
+            query = query.bind(id);
+        }
+        let rows = query.fetch_all(&self.pool).await.map_err(|e| {
+            map_connection_error(AppError::Database(format!(
+                "Failed to count group members: {}",
+                e
+            )))
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(group_id, count)| (group_id, count as u64))
+            .collect())
+    }
+}
+
+/// Writes are not logged, so there is no history
+impl HistoryBackend for MySqlBackend {}
+
+#[async_trait]
+impl ProvenanceBackend for MySqlBackend {
+    async fn find_provenance(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        ids: &[String],
+    ) -> AppResult<HashMap<String, Provenance>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let table = match resource_type {
+            ResourceType::User => self.users_table(tenant_id),
+            ResourceType::Group => self.groups_table(tenant_id),
+        };
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id, created_by, last_modified_by FROM {} WHERE id IN ({})",
+            table, placeholders
+        );
+        let mut query = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(&sql);
+        for id in ids {
+            query = query.bind(id);
+        }
+        let rows = query.fetch_all(&self.pool).await.map_err(|e| {
+            map_connection_error(AppError::Database(format!(
+                "Failed to fetch provenance: {}",
+                e
+            )))
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, created_by, last_modified_by)| {
+                (
+                    id,
+                    Provenance {
+                        created_by,
+                        last_modified_by,
+                    },
+                )
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,824 @@
+use super::super::filter::{
+    build_comparison_sql, locale_filter, meta_filter, validate_filter_attributes, FilterConverter,
+    MetaFilter, StringMatch,
+};
+use crate::error::AppResult;
+use crate::parser::filter_operator::FilterOperator;
+use crate::parser::ResourceType;
+use crate::schema::is_case_insensitive_attribute;
+use serde_json::Value;
+
+/// MySQL JSON path for a dotted attribute path
+///
+/// Every key is quoted, so attribute names never need to be valid
+/// identifiers, and numeric segments become array indexes:
+/// `emails.0.value` is `$."emails"[0]."value"`.
+fn raw_json_path(path: &str) -> String {
+    let mut json_path = "$".to_string();
+    for segment in path.split('.') {
+        if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+            json_path.push_str(&format!("[{}]", segment));
+        } else {
+            json_path.push_str(&format!(
+                ".\"{}\"",
+                segment.replace('\\', "\\\\").replace('"', "\\\"")
+            ));
+        }
+    }
+    json_path
+}
+
+/// Quote text as a MySQL string literal
+fn sql_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// SQL string literal holding the JSON path for a dotted attribute path
+pub fn json_path(path: &str) -> String {
+    sql_literal(&raw_json_path(path))
+}
+
+/// Text of the JSON value at `path` in `column`; SQL NULL when it is missing
+pub fn json_text(column: &str, path: &str) -> String {
+    format!(
+        "JSON_UNQUOTE(JSON_EXTRACT({}, {}))",
+        column,
+        json_path(path)
+    )
+}
+
+/// MySQL/MariaDB-specific filter converter for SCIM filters
+///
+/// This handles conversion of SCIM filter expressions to MySQL JSON
+/// function queries with positional `?` parameters. MySQL placeholders
+/// cannot be reused, so a value compared twice is bound twice.
+pub struct MySqlFilterConverter {
+    /// Multi-valued attribute kept outside data_norm and the rows standing in
+    /// for it, see `with_elements_source`
+    external_elements: Option<(&'static str, String)>,
+    /// Match externalId case-insensitively, see `with_case_insensitive_external_id`
+    external_id_case_insensitive: bool,
+}
+
+impl Default for MySqlFilterConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MySqlFilterConverter {
+    pub fn new() -> Self {
+        Self {
+            external_elements: None,
+            external_id_case_insensitive: false,
+        }
+    }
+
+    /// Evaluate filters on `attr` against the given query instead of data_norm
+    ///
+    /// Group members and user groups are stored in the memberships table, so
+    /// the query must yield one JSON object per element of the current row
+    /// in a `value` column, normalized like data_norm. It must be a plain
+    /// SELECT that a HAVING clause can be appended to.
+    pub fn with_elements_source(mut self, attr: &'static str, source: String) -> Self {
+        self.external_elements = Some((attr, source));
+        self
+    }
+
+    /// Compare externalId ignoring case, for tenants with
+    /// `external_id_case_insensitive`
+    ///
+    /// The column holds the value as stored, so every operator compares it
+    /// lowercased with the lowercased filter value.
+    pub fn with_case_insensitive_external_id(mut self, enabled: bool) -> Self {
+        self.external_id_case_insensitive = enabled;
+        self
+    }
+
+    /// Condition holding when any element of a multi-valued attribute matches
+    ///
+    /// data_norm arrays are expanded with JSON_TABLE. An external source is
+    /// correlated with the outer row, which MariaDB does not allow inside a
+    /// derived table, so its `value` column is filtered with HAVING instead.
+    fn any_element(&self, attr: &str, condition: Option<&str>) -> String {
+        let attr = attr.to_lowercase();
+        match &self.external_elements {
+            Some((name, source)) if name.eq_ignore_ascii_case(&attr) => match condition {
+                Some(condition) => format!("EXISTS ({} HAVING {})", source, condition),
+                None => format!("EXISTS ({})", source),
+            },
+            _ => format!(
+                "EXISTS (SELECT 1 FROM JSON_TABLE(data_norm, {} \
+                 COLUMNS (value JSON PATH '$')) AS elements WHERE {})",
+                sql_literal(&format!("{}[*]", raw_json_path(&attr))),
+                condition.unwrap_or("1 = 1")
+            ),
+        }
+    }
+}
+
+impl FilterConverter for MySqlFilterConverter {
+    fn to_where_clause(
+        &self,
+        filter: &FilterOperator,
+        resource_type: ResourceType,
+    ) -> AppResult<(String, Vec<String>)> {
+        // Attribute names become part of the SQL, so unknown ones never get this far
+        validate_filter_attributes(filter, resource_type)?;
+        let mut params = Vec::new();
+        let condition = self.convert_filter_to_sql(filter, resource_type, &mut params)?;
+        Ok((condition, params))
+    }
+
+    fn get_param_placeholder(&self, _index: usize) -> String {
+        // MySQL only has positional ? placeholders
+        "?".to_string()
+    }
+
+    fn get_not_equal(&self, left: &str, right: &str) -> String {
+        format!("NOT ({} <=> {})", left, right)
+    }
+
+    fn get_position_function(&self, haystack: &str, needle: &str) -> String {
+        // The connection collation is binary, so LOCATE is case-exact
+        format!("LOCATE({}, {})", needle, haystack)
+    }
+
+    fn get_suffix_function(&self, expression: &str, suffix: &str) -> String {
+        format!("RIGHT({}, CHAR_LENGTH({}))", expression, suffix)
+    }
+
+    fn is_case_insensitive_attribute(&self, attr: &str, resource_type: ResourceType) -> bool {
+        is_case_insensitive_attribute(attr, resource_type)
+    }
+
+    fn get_json_path(&self, attr: &str, resource_type: ResourceType) -> String {
+        // MySQL uses JSON_EXTRACT() for JSON access
+        match resource_type {
+            ResourceType::User => match attr {
+                "userName" => "username".to_string(),
+                "externalId" => "external_id".to_string(),
+                "id" => "id".to_string(),
+                _ => format!("JSON_EXTRACT(data_norm, {})", json_path(attr)),
+            },
+            ResourceType::Group => match attr {
+                "displayName" => "display_name".to_string(),
+                "externalId" => "external_id".to_string(),
+                "id" => "id".to_string(),
+                _ => format!("JSON_EXTRACT(data_norm, {})", json_path(attr)),
+            },
+        }
+    }
+}
+
+impl MySqlFilterConverter {
+    /// Convert a filter operator to SQL condition
+    fn convert_filter_to_sql(
+        &self,
+        filter: &FilterOperator,
+        resource_type: ResourceType,
+        params: &mut Vec<String>,
+    ) -> AppResult<String> {
+        if let Some(meta) = meta_filter(filter, resource_type)? {
+            return Ok(self.meta_filter_to_sql(meta, params));
+        }
+        if let Some(alternatives) = locale_filter(filter, resource_type) {
+            let mut conditions = Vec::with_capacity(alternatives.len());
+            for alternative in &alternatives {
+                conditions.push(self.convert_filter_to_sql(alternative, resource_type, params)?);
+            }
+            return Ok(format!("({})", conditions.join(" OR ")));
+        }
+
+        match filter {
+            FilterOperator::Equal(attr, value) => {
+                self.handle_comparison(attr, StringMatch::Equal, value, resource_type, params)
+            }
+            FilterOperator::NotEqual(attr, value) => {
+                self.handle_comparison(attr, StringMatch::NotEqual, value, resource_type, params)
+            }
+            FilterOperator::Contains(attr, value) => {
+                self.handle_comparison(attr, StringMatch::Contains, value, resource_type, params)
+            }
+            FilterOperator::StartsWith(attr, value) => {
+                self.handle_comparison(attr, StringMatch::StartsWith, value, resource_type, params)
+            }
+            FilterOperator::EndsWith(attr, value) => {
+                self.handle_comparison(attr, StringMatch::EndsWith, value, resource_type, params)
+            }
+            FilterOperator::Present(attr) => self.handle_present(attr, resource_type),
+            FilterOperator::GreaterThan(attr, value) => {
+                Ok(self.handle_ordering(attr, ">", value, resource_type, params))
+            }
+            FilterOperator::GreaterThanOrEqual(attr, value) => {
+                Ok(self.handle_ordering(attr, ">=", value, resource_type, params))
+            }
+            FilterOperator::LessThan(attr, value) => {
+                Ok(self.handle_ordering(attr, "<", value, resource_type, params))
+            }
+            FilterOperator::LessThanOrEqual(attr, value) => {
+                Ok(self.handle_ordering(attr, "<=", value, resource_type, params))
+            }
+            FilterOperator::And(left, right) => {
+                let left_sql = self.convert_filter_to_sql(left, resource_type, params)?;
+                let right_sql = self.convert_filter_to_sql(right, resource_type, params)?;
+                Ok(format!("({} AND {})", left_sql, right_sql))
+            }
+            FilterOperator::Or(left, right) => {
+                let left_sql = self.convert_filter_to_sql(left, resource_type, params)?;
+                let right_sql = self.convert_filter_to_sql(right, resource_type, params)?;
+                Ok(format!("({} OR {})", left_sql, right_sql))
+            }
+            FilterOperator::Not(inner) => {
+                let inner_sql = self.convert_filter_to_sql(inner, resource_type, params)?;
+                Ok(format!("NOT ({})", inner_sql))
+            }
+            FilterOperator::Complex(attr, inner) => {
+                // For complex filters like emails[value eq "work"], we need to handle
+                // multi-valued attributes by checking if any element matches
+                self.handle_complex_filter(attr, inner, resource_type, params)
+            }
+        }
+    }
+
+    /// Render a filter on a `meta` sub-attribute
+    fn meta_filter_to_sql(&self, meta: MetaFilter, params: &mut Vec<String>) -> String {
+        match meta {
+            MetaFilter::Constant(true) => "1 = 1".to_string(),
+            MetaFilter::Constant(false) => "1 = 0".to_string(),
+            MetaFilter::Timestamp {
+                column,
+                operator,
+                value,
+            } => {
+                params.push(value);
+                // DATETIME(6) columns in UTC, truncated to milliseconds as
+                // responses are
+                format!(
+                    "CONCAT(LEFT(DATE_FORMAT({}, '%Y-%m-%dT%H:%i:%s.%f'), 23), 'Z') {} ?",
+                    column, operator
+                )
+            }
+            MetaFilter::Location {
+                endpoint,
+                operator,
+                value,
+            } => {
+                let path = format!("CONCAT('{}', id)", endpoint);
+                build_comparison_sql(
+                    self,
+                    &path,
+                    operator,
+                    &Value::String(value),
+                    true,
+                    params.len() + 1,
+                )
+                .bind(params)
+            }
+        }
+    }
+
+    /// Handle eq, ne, co, sw and ew
+    ///
+    /// `ne` is null-safe: a missing or null attribute is not equal to any value.
+    fn handle_comparison(
+        &self,
+        attr: &str,
+        operator: StringMatch,
+        value: &Value,
+        resource_type: ResourceType,
+        params: &mut Vec<String>,
+    ) -> AppResult<String> {
+        // Check if this is a multi-valued attribute query like "emails.value";
+        // ne holds when no element is equal
+        if let Some((attr_name, sub_attr)) = self.multi_value_sub_attr(attr, resource_type) {
+            let (negated, operator) = match operator {
+                StringMatch::NotEqual => (true, StringMatch::Equal),
+                operator => (false, operator),
+            };
+            let condition = self.element_comparison(
+                attr_name,
+                sub_attr,
+                resource_type,
+                operator,
+                value,
+                params,
+            );
+            let condition = self.any_element(attr_name, Some(&condition));
+            return Ok(if negated {
+                format!("NOT {}", condition)
+            } else {
+                condition
+            });
+        }
+
+        // externalId is kept in its own indexed, case-exact (utf8mb4_bin) column,
+        // compared lowercased for tenants matching it case-insensitively.
+        // Rows left out of it by the startup backfill for duplicating another
+        // row's externalId keep it NULL and are matched by their document.
+        if attr.eq_ignore_ascii_case("externalId") && value.is_string() {
+            let case_exact = !self.external_id_case_insensitive;
+            let document = json_text("data_orig", "externalId");
+            let (column, document) = if case_exact {
+                ("external_id".to_string(), document.to_string())
+            } else {
+                (
+                    self.get_lower_function("external_id"),
+                    self.get_lower_function(&document),
+                )
+            };
+            if operator == StringMatch::Equal {
+                let column_match = build_comparison_sql(
+                    self,
+                    &column,
+                    operator,
+                    value,
+                    case_exact,
+                    params.len() + 1,
+                )
+                .bind(params);
+                let document_match = build_comparison_sql(
+                    self,
+                    &document,
+                    operator,
+                    value,
+                    case_exact,
+                    params.len() + 1,
+                )
+                .bind(params);
+                return Ok(format!(
+                    "({} OR (external_id IS NULL AND {}))",
+                    column_match, document_match
+                ));
+            }
+            if !case_exact {
+                return Ok(build_comparison_sql(
+                    self,
+                    &document,
+                    operator,
+                    value,
+                    false,
+                    params.len() + 1,
+                )
+                .bind(params));
+            }
+        }
+
+        let json_path = self.scim_path_to_json_path(attr, resource_type);
+
+        // JSON booleans unquote to 'true' and 'false'
+        if let (Value::Bool(bool_val), StringMatch::Equal | StringMatch::NotEqual) =
+            (value, operator)
+        {
+            let extracted = json_text("data_norm", &json_path);
+            let literal = format!("'{}'", bool_val);
+            return Ok(if operator == StringMatch::Equal {
+                format!("{} = {}", extracted, literal)
+            } else {
+                self.get_not_equal(&extracted, &literal)
+            });
+        }
+
+        // Case-exact attributes are compared with their original case
+        let case_exact = self.is_case_exact_field(attr, resource_type);
+        let data_column = if case_exact { "data_orig" } else { "data_norm" };
+
+        Ok(build_comparison_sql(
+            self,
+            &json_text(data_column, &json_path),
+            operator,
+            value,
+            case_exact,
+            params.len() + 1,
+        )
+        .bind(params))
+    }
+
+    /// Handle present comparison
+    ///
+    /// Null, empty strings, empty arrays and empty objects are not present
+    /// (RFC 7644 Section 3.4.2.2), so `emails pr` needs at least one element.
+    fn handle_present(&self, attr: &str, resource_type: ResourceType) -> AppResult<String> {
+        // Check if this is a multi-valued attribute query like "emails.value"
+        if let Some((attr_name, sub_attr)) = self.multi_value_sub_attr(attr, resource_type) {
+            return Ok(self.any_element(attr_name, Some(&self.element_present(sub_attr))));
+        }
+
+        if matches!(&self.external_elements, Some((name, _)) if name.eq_ignore_ascii_case(attr)) {
+            return Ok(self.any_element(attr, None));
+        }
+
+        // A bare multi-valued attribute is checked as a whole array, not its first value
+        let path = if crate::schema::is_multi_valued_attribute(attr, resource_type) {
+            attr.to_lowercase()
+        } else {
+            self.scim_path_to_json_path(attr, resource_type)
+        };
+        let extracted = format!("JSON_EXTRACT(data_norm, {})", json_path(&path));
+        Ok(format!(
+            "CASE JSON_TYPE({extracted}) \
+             WHEN 'NULL' THEN 0 \
+             WHEN 'STRING' THEN {text} <> '' \
+             WHEN 'ARRAY' THEN JSON_LENGTH({extracted}) > 0 \
+             WHEN 'OBJECT' THEN JSON_LENGTH({extracted}) > 0 \
+             ELSE {extracted} IS NOT NULL END",
+            extracted = extracted,
+            text = json_text("data_norm", &path)
+        ))
+    }
+
+    /// Handle gt, ge, lt and le, which compare numerically
+    fn handle_ordering(
+        &self,
+        attr: &str,
+        operator: &str,
+        value: &Value,
+        resource_type: ResourceType,
+        params: &mut Vec<String>,
+    ) -> String {
+        let json_path = self.scim_path_to_json_path(attr, resource_type);
+        let value_str = self.value_to_string(value);
+        // For data_norm column, we need to compare with normalized values (lowercase for strings)
+        let normalized_value = if value.is_string() {
+            value_str.to_lowercase()
+        } else {
+            value_str
+        };
+        params.push(normalized_value);
+
+        format!(
+            "CAST({} AS DOUBLE) {} CAST(? AS DOUBLE)",
+            json_text("data_norm", &json_path),
+            operator
+        )
+    }
+
+    /// Split `emails.value` into the multi-valued attribute and its sub-attribute
+    fn multi_value_sub_attr<'a>(
+        &self,
+        attr: &'a str,
+        resource_type: ResourceType,
+    ) -> Option<(&'a str, &'a str)> {
+        let (attr_name, sub_attr) = attr.split_once('.')?;
+        if sub_attr.contains('.')
+            || !crate::schema::is_multi_valued_attribute(attr_name, resource_type)
+        {
+            return None;
+        }
+        Some((attr_name, sub_attr))
+    }
+
+    /// Convert SCIM attribute path to a dotted path into data_norm
+    fn scim_path_to_json_path(&self, attr: &str, resource_type: ResourceType) -> String {
+        // Handle special case for userName (case-insensitive)
+        if attr.eq_ignore_ascii_case("userName") {
+            return "username".to_string();
+        }
+
+        // Handle special case for displayName (case-insensitive)
+        if attr.eq_ignore_ascii_case("displayName") {
+            return "displayname".to_string();
+        }
+
+        // Handle special case for externalId (case-exact)
+        if attr.eq_ignore_ascii_case("externalId") {
+            return "externalId".to_string(); // Preserve original case for case-exact field
+        }
+
+        // Handle nested attributes like name.givenName
+        if attr.contains('.') {
+            let mut path_parts: Vec<String> = Vec::new();
+
+            for part in attr.split('.') {
+                // Convert to lowercase for data_norm column consistency
+                // But preserve case for case-exact fields
+                let current_path = if path_parts.is_empty() {
+                    part.to_string()
+                } else {
+                    format!("{}.{}", path_parts.join("."), part)
+                };
+                if self.is_case_exact_field(&current_path, resource_type) {
+                    path_parts.push(part.to_string());
+                } else {
+                    path_parts.push(part.to_lowercase());
+                }
+            }
+
+            return path_parts.join(".");
+        }
+
+        // Handle multi-valued attributes (emails, phoneNumbers, etc.)
+        if crate::schema::is_multi_valued_attribute(attr, resource_type) {
+            return format!("{}.0.value", attr.to_lowercase());
+        }
+
+        // For case-exact fields, preserve case; for others use lowercase
+        if self.is_case_exact_field(attr, resource_type) {
+            attr.to_string()
+        } else {
+            // Use lowercase for both standard SCIM attributes and custom attributes
+            // since they are stored in lowercase in data_norm column
+            attr.to_lowercase()
+        }
+    }
+
+    /// Check if an attribute is case-exact (case-sensitive)
+    fn is_case_exact_field(&self, attr: &str, resource_type: ResourceType) -> bool {
+        crate::schema::normalization::is_case_exact_field_for_resource(attr, resource_type)
+    }
+
+    /// Handle complex filter expressions like emails[value eq "work"]
+    fn handle_complex_filter(
+        &self,
+        attr: &str,
+        inner: &FilterOperator,
+        resource_type: ResourceType,
+        params: &mut Vec<String>,
+    ) -> AppResult<String> {
+        // Every condition inside the brackets must hold for the same array element,
+        // so the whole inner filter is evaluated per row of one JSON_TABLE
+        let condition = self.element_condition(attr, inner, resource_type, params)?;
+        Ok(self.any_element(attr, Some(&condition)))
+    }
+
+    /// Convert a value path filter to a condition on the current element
+    fn element_condition(
+        &self,
+        attr: &str,
+        filter: &FilterOperator,
+        resource_type: ResourceType,
+        params: &mut Vec<String>,
+    ) -> AppResult<String> {
+        match filter {
+            FilterOperator::Equal(sub_attr, value)
+            | FilterOperator::NotEqual(sub_attr, value)
+            | FilterOperator::Contains(sub_attr, value)
+            | FilterOperator::StartsWith(sub_attr, value)
+            | FilterOperator::EndsWith(sub_attr, value) => {
+                let operator = StringMatch::of(filter).unwrap_or(StringMatch::Equal);
+                Ok(self.element_comparison(attr, sub_attr, resource_type, operator, value, params))
+            }
+            FilterOperator::GreaterThan(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, ">", value, params))
+            }
+            FilterOperator::GreaterThanOrEqual(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, ">=", value, params))
+            }
+            FilterOperator::LessThan(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, "<", value, params))
+            }
+            FilterOperator::LessThanOrEqual(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, "<=", value, params))
+            }
+            FilterOperator::Present(sub_attr) => Ok(self.element_present(sub_attr)),
+            FilterOperator::And(left, right) => {
+                let left_sql = self.element_condition(attr, left, resource_type, params)?;
+                let right_sql = self.element_condition(attr, right, resource_type, params)?;
+                Ok(format!("({} AND {})", left_sql, right_sql))
+            }
+            FilterOperator::Or(left, right) => {
+                let left_sql = self.element_condition(attr, left, resource_type, params)?;
+                let right_sql = self.element_condition(attr, right, resource_type, params)?;
+                Ok(format!("({} OR {})", left_sql, right_sql))
+            }
+            FilterOperator::Not(inner) => {
+                let inner_sql = self.element_condition(attr, inner, resource_type, params)?;
+                Ok(format!("NOT ({})", inner_sql))
+            }
+            FilterOperator::Complex(_, _) => Err(crate::error::AppError::FilterParse(format!(
+                "Nested value paths are not supported in {}",
+                attr
+            ))),
+        }
+    }
+
+    /// Compare a sub-attribute of the current element
+    fn element_comparison(
+        &self,
+        attr: &str,
+        sub_attr: &str,
+        resource_type: ResourceType,
+        operator: StringMatch,
+        value: &Value,
+        params: &mut Vec<String>,
+    ) -> String {
+        let extracted = json_text("value", &sub_attr.to_lowercase());
+
+        // JSON booleans unquote to 'true' and 'false'
+        if let (Value::Bool(bool_val), StringMatch::Equal | StringMatch::NotEqual) =
+            (value, operator)
+        {
+            let literal = format!("'{}'", bool_val);
+            return if operator == StringMatch::Equal {
+                format!("{} = {}", extracted, literal)
+            } else {
+                self.get_not_equal(&extracted, &literal)
+            };
+        }
+
+        // data_norm keeps the original case of case-exact sub-attributes
+        // (e.g. x509Certificates.value)
+        let case_exact = self.is_case_exact_field(&format!("{}.{}", attr, sub_attr), resource_type);
+        build_comparison_sql(
+            self,
+            &extracted,
+            operator,
+            value,
+            case_exact,
+            params.len() + 1,
+        )
+        .bind(params)
+    }
+
+    /// Order a sub-attribute of the current element numerically
+    fn element_ordering(
+        &self,
+        sub_attr: &str,
+        operator: &str,
+        value: &Value,
+        params: &mut Vec<String>,
+    ) -> String {
+        params.push(self.value_to_string(value));
+        format!(
+            "CAST({} AS DOUBLE) {} CAST(? AS DOUBLE)",
+            json_text("value", &sub_attr.to_lowercase()),
+            operator
+        )
+    }
+
+    /// Check that the current element has a non-empty sub-attribute
+    fn element_present(&self, sub_attr: &str) -> String {
+        let extracted = json_text("value", &sub_attr.to_lowercase());
+        format!(
+            "{e} IS NOT NULL AND {e} <> '' AND {e} <> 'null'",
+            e = extracted
+        )
+    }
+
+    /// Convert JSON Value to string for SQL parameters
+    fn value_to_string(&self, value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Null => "null".to_string(),
+            _ => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_path() {
+        assert_eq!(json_path("username"), r#"'$."username"'"#);
+        assert_eq!(json_path("emails.0.value"), r#"'$."emails"[0]."value"'"#);
+        // Quotes in attribute names cannot escape the literal or the key
+        assert_eq!(json_path("a'b\"c"), r#"'$."a''b\\"c"'"#);
+    }
+
+    #[test]
+    fn test_scim_path_conversion() {
+        let converter = MySqlFilterConverter::new();
+
+        assert_eq!(
+            converter.scim_path_to_json_path("userName", ResourceType::User),
+            "username"
+        );
+        assert_eq!(
+            converter.scim_path_to_json_path("name.givenName", ResourceType::User),
+            "name.givenname"
+        );
+        assert_eq!(
+            converter.scim_path_to_json_path("emails", ResourceType::User),
+            "emails.0.value"
+        );
+    }
+
+    #[test]
+    fn test_filter_conversion() {
+        let converter = MySqlFilterConverter::new();
+        let filter = FilterOperator::Equal(
+            "userName".to_string(),
+            Value::String("John.Doe".to_string()),
+        );
+
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+
+        assert_eq!(
+            condition,
+            r#"JSON_UNQUOTE(JSON_EXTRACT(data_norm, '$."username"')) = ?"#
+        );
+        assert_eq!(params, vec!["john.doe"]);
+    }
+
+    #[test]
+    fn test_boolean_and_not_equal_conversion() {
+        let converter = MySqlFilterConverter::new();
+        let filter = FilterOperator::Not(Box::new(FilterOperator::Equal(
+            "active".to_string(),
+            Value::Bool(true),
+        )));
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+        assert_eq!(
+            condition,
+            r#"NOT (JSON_UNQUOTE(JSON_EXTRACT(data_norm, '$."active"')) = 'true')"#
+        );
+        assert!(params.is_empty());
+
+        // Null-safe so users without userType also match
+        let filter = FilterOperator::NotEqual(
+            "userType".to_string(),
+            Value::String("Employee".to_string()),
+        );
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+        assert_eq!(
+            condition,
+            r#"NOT (JSON_UNQUOTE(JSON_EXTRACT(data_norm, '$."usertype"')) <=> ?)"#
+        );
+        assert_eq!(params, vec!["employee"]);
+    }
+
+    #[test]
+    fn test_complex_filter_conditions_share_one_element() {
+        let converter = MySqlFilterConverter::new();
+        let inner_filter = FilterOperator::And(
+            Box::new(FilterOperator::Equal(
+                "type".to_string(),
+                Value::String("work".to_string()),
+            )),
+            Box::new(FilterOperator::Contains(
+                "value".to_string(),
+                Value::String("@corp.com".to_string()),
+            )),
+        );
+        let complex_filter = FilterOperator::Complex("emails".to_string(), Box::new(inner_filter));
+
+        let (condition, params) = converter
+            .to_where_clause(&complex_filter, ResourceType::User)
+            .unwrap();
+
+        assert_eq!(
+            condition,
+            r#"EXISTS (SELECT 1 FROM JSON_TABLE(data_norm, '$."emails"[*]' COLUMNS (value JSON PATH '$')) AS elements WHERE (JSON_UNQUOTE(JSON_EXTRACT(value, '$."type"')) = ? AND LOWER(JSON_UNQUOTE(JSON_EXTRACT(value, '$."value"'))) LIKE LOWER(?)))"#
+        );
+        assert_eq!(params, vec!["work", "%@corp.com%"]);
+    }
+
+    #[test]
+    fn test_meta_filter_conversion() {
+        let converter = MySqlFilterConverter::new();
+
+        // Placeholders are positional, so the suffix is bound twice
+        let filter = FilterOperator::EndsWith(
+            "meta.location".to_string(),
+            Value::String("https://example.com/scim/v2/Users/abc".to_string()),
+        );
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+        assert_eq!(
+            condition,
+            "RIGHT(CONCAT('/Users/', id), CHAR_LENGTH(?)) = ?"
+        );
+        assert_eq!(params, vec!["/Users/abc", "/Users/abc"]);
+
+        let filter = FilterOperator::GreaterThan(
+            "meta.lastModified".to_string(),
+            Value::String("2024-01-01T09:00:00+09:00".to_string()),
+        );
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::Group)
+            .unwrap();
+        assert!(condition.starts_with("CONCAT(LEFT(DATE_FORMAT(updated_at"));
+        assert_eq!(params, vec!["2024-01-01T00:00:00.000Z"]);
+    }
+
+    #[test]
+    fn test_filter_uses_elements_source() {
+        let converter = MySqlFilterConverter::new()
+            .with_elements_source("members", "SELECT m.value FROM m".to_string());
+        let filter = FilterOperator::Contains(
+            "members.display".to_string(),
+            Value::String("Alice".to_string()),
+        );
+
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::Group)
+            .unwrap();
+
+        assert_eq!(
+            condition,
+            r#"EXISTS (SELECT m.value FROM m HAVING LOWER(JSON_UNQUOTE(JSON_EXTRACT(value, '$."display"'))) LIKE LOWER(?))"#
+        );
+        assert_eq!(params, vec!["%alice%"]);
+    }
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+
+use super::super::group_delete::GroupDeleter;
+use super::super::DbTransaction;
+use crate::error::{AppError, AppResult};
+
+/// MySQL-specific implementation of GroupDeleter
+///
+/// This handles MySQL's TEXT-based IDs and group deletion with cascading
+/// membership cleanup.
+#[derive(Default)]
+pub struct MySqlGroupDeleter;
+
+impl MySqlGroupDeleter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl GroupDeleter for MySqlGroupDeleter {
+    async fn execute_group_delete(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        let conn = tx.mysql()?;
+
+        // First, delete group memberships where this group is the parent
+        let membership_table = format!("`t{}_group_memberships`", tenant_id);
+        let parent_membership_sql = format!("DELETE FROM {} WHERE group_id = ?", membership_table);
+
+        sqlx::query(&parent_membership_sql)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to delete group parent memberships: {}", e))
+            })?;
+
+        // Second, delete memberships where this group is a member of other groups
+        let child_membership_sql = format!(
+            "DELETE FROM {} WHERE member_id = ? AND member_type = 'Group'",
+            membership_table
+        );
+
+        sqlx::query(&child_membership_sql)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to delete group child memberships: {}", e))
+            })?;
+
+        // Then, delete the group itself
+        let group_table = format!("`t{}_groups`", tenant_id);
+        let group_sql = format!("DELETE FROM {} WHERE id = ?", group_table);
+
+        let result = sqlx::query(&group_sql)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete group: {}", e)))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
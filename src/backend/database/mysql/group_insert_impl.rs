@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::MySqlConnection;
+
+use super::super::group_insert::{map_membership_insert_error, GroupInserter, PreparedGroupData};
+use super::super::DbTransaction;
+use super::group_update_impl::fetch_group_with_members;
+use crate::error::{AppError, AppResult};
+use crate::models::Group;
+
+/// MySQL-specific implementation of GroupInserter
+///
+/// This handles MySQL's JSON LONGTEXT storage while using shared SQL generation.
+#[derive(Default)]
+pub struct MySqlGroupInserter;
+
+impl MySqlGroupInserter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Convert JSON Value to String for MySQL LONGTEXT storage
+    fn json_value_to_string(&self, value: &Value) -> AppResult<String> {
+        serde_json::to_string(value).map_err(AppError::Serialization)
+    }
+
+    /// Check for case-insensitive duplicate displayName
+    async fn check_duplicate_display_name(
+        &self,
+        conn: &mut MySqlConnection,
+        tenant_id: u32,
+        display_name: &str,
+    ) -> AppResult<()> {
+        let table_name = format!("t{}_groups", tenant_id);
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(display_name) = LOWER(?)",
+            table_name
+        );
+
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(display_name)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate displayName: {}", e))
+            })?;
+
+        if count > 0 {
+            return Err(AppError::Conflict(
+                "Group with this displayName already exists".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GroupInserter for MySqlGroupInserter {
+    async fn execute_group_insert(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        data: PreparedGroupData,
+    ) -> AppResult<Group> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        // Check for case-insensitive duplicate displayName before insertion
+        self.check_duplicate_display_name(conn, tenant_id, &data.display_name)
+            .await?;
+
+        // Insert the group record
+        let group_table = format!("t{}_groups", tenant_id);
+        let group_sql = format!(
+            "INSERT INTO {} (id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at, created_by, last_modified_by) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            group_table
+        );
+
+        // MySQL: convert JSON to strings
+        let data_orig_str = self.json_value_to_string(&data.data_orig)?;
+        let data_norm_str = self.json_value_to_string(&data.data_norm)?;
+
+        sqlx::query(&group_sql)
+            .bind(&data.id)
+            .bind(&data.display_name)
+            .bind(&data.external_id)
+            .bind(&data_orig_str) // MySQL: JSON as TEXT
+            .bind(&data_norm_str)
+            .bind(1i64) // version = 1 for new records
+            .bind(data.timestamp)
+            .bind(data.timestamp)
+            .bind(&actor)
+            .bind(&actor)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| super::user_insert_impl::map_database_error(e, "Group"))?;
+
+        // Insert group memberships if present
+        if let Some(members) = &data.members {
+            let membership_table = format!("t{}_group_memberships", tenant_id);
+            let membership_sql = format!(
+                "INSERT INTO {} (group_id, member_id, member_type) VALUES (?, ?, ?)",
+                membership_table
+            );
+
+            for member in members {
+                if let Some(member_id) = &member.value {
+                    let member_type = member.type_.as_deref().unwrap_or("User");
+
+                    sqlx::query(&membership_sql)
+                        .bind(&data.id)
+                        .bind(member_id)
+                        .bind(member_type)
+                        .execute(&mut *conn)
+                        .await
+                        .map_err(|e| map_membership_insert_error(e, member_id))?;
+                }
+            }
+        }
+
+        // Fetch the created group with properly populated members
+        match fetch_group_with_members(conn, tenant_id, &data.group.base.id).await? {
+            Some(group) => Ok(group),
+            None => Err(AppError::Database(
+                "Failed to fetch created group".to_string(),
+            )),
+        }
+    }
+}
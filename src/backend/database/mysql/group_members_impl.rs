@@ -0,0 +1,227 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::MySqlConnection;
+use std::collections::HashSet;
+
+use super::super::group_members::GroupMemberUpdater;
+use super::super::DbTransaction;
+use super::user_update_impl::map_database_error;
+use crate::backend::{MembersAdded, MembersRemoved};
+use crate::error::{AppError, AppResult};
+
+/// Ids per statement, well below MySQL's bound parameter limit
+const CHUNK_SIZE: usize = 500;
+
+/// MySQL-specific implementation of GroupMemberUpdater
+///
+/// Ids are processed in chunks with multi-row statements inside one
+/// transaction.
+#[derive(Default)]
+pub struct MySqlGroupMemberUpdater;
+
+impl MySqlGroupMemberUpdater {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn group_exists(
+        conn: &mut MySqlConnection,
+        tenant_id: u32,
+        group_id: &str,
+    ) -> AppResult<bool> {
+        let sql = format!("SELECT COUNT(*) FROM `t{}_groups` WHERE id = ?", tenant_id);
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(group_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+        Ok(count > 0)
+    }
+
+    /// Run `{sql_prefix} (?, ...)` over `ids` and collect the returned ids
+    ///
+    /// `leading` values are bound before the IN list.
+    async fn existing_ids(
+        conn: &mut MySqlConnection,
+        sql_prefix: &str,
+        leading: &[&str],
+        ids: &[String],
+    ) -> AppResult<HashSet<String>> {
+        let sql = format!("{} ({})", sql_prefix, placeholders(ids.len()));
+        let mut query = sqlx::query_scalar::<_, String>(&sql);
+        for value in leading {
+            query = query.bind(*value);
+        }
+        for id in ids {
+            query = query.bind(id);
+        }
+        let found = query
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to look up members: {}", e)))?;
+        Ok(found.into_iter().collect())
+    }
+
+    /// Move meta.lastModified and the version forward once for the call
+    async fn touch_group(
+        conn: &mut MySqlConnection,
+        tenant_id: u32,
+        group_id: &str,
+        timestamp: DateTime<Utc>,
+        actor: Option<&str>,
+    ) -> AppResult<()> {
+        let last_modified = crate::utils::format_scim_datetime(timestamp);
+        let sql = format!(
+            "UPDATE `t{}_groups` SET \
+             data_orig = JSON_SET(data_orig, '$.meta.lastModified', ?), \
+             data_norm = JSON_SET(data_norm, '$.meta.lastmodified', ?), \
+             version = version + 1, updated_at = ?, last_modified_by = ? WHERE id = ?",
+            tenant_id
+        );
+        sqlx::query(&sql)
+            .bind(&last_modified)
+            .bind(last_modified.to_lowercase())
+            .bind(timestamp)
+            .bind(actor)
+            .bind(group_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| map_database_error(e, "Group"))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GroupMemberUpdater for MySqlGroupMemberUpdater {
+    async fn execute_members_add(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<MembersAdded>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        if !Self::group_exists(conn, tenant_id, group_id).await? {
+            return Ok(None);
+        }
+
+        let memberships_table = format!("`t{}_group_memberships`", tenant_id);
+        let mut outcome = MembersAdded::default();
+
+        for chunk in ids.chunks(CHUNK_SIZE) {
+            let present = Self::existing_ids(
+                conn,
+                &format!(
+                    "SELECT member_id FROM {} WHERE group_id = ? AND member_id IN",
+                    memberships_table
+                ),
+                &[group_id],
+                chunk,
+            )
+            .await?;
+            let users = Self::existing_ids(
+                conn,
+                &format!("SELECT id FROM `t{}_users` WHERE id IN", tenant_id),
+                &[],
+                chunk,
+            )
+            .await?;
+            // A group cannot be a member of itself
+            let groups = Self::existing_ids(
+                conn,
+                &format!(
+                    "SELECT id FROM `t{}_groups` WHERE id != ? AND id IN",
+                    tenant_id
+                ),
+                &[group_id],
+                chunk,
+            )
+            .await?;
+
+            let mut rows = Vec::new();
+            for id in chunk {
+                if present.contains(id) {
+                    outcome.already_present += 1;
+                } else if users.contains(id) {
+                    rows.push((id, "User"));
+                } else if groups.contains(id) {
+                    rows.push((id, "Group"));
+                } else {
+                    outcome.not_found.push(id.clone());
+                }
+            }
+            if rows.is_empty() {
+                continue;
+            }
+
+            let values = vec!["(?, ?, ?)"; rows.len()].join(", ");
+            let sql = format!(
+                "INSERT INTO {} (group_id, member_id, member_type) VALUES {}",
+                memberships_table, values
+            );
+            let mut query = sqlx::query(&sql);
+            for (id, member_type) in &rows {
+                query = query.bind(group_id).bind(*id).bind(*member_type);
+            }
+            query.execute(&mut *conn).await.map_err(|e| {
+                AppError::Database(format!("Failed to insert group memberships: {}", e))
+            })?;
+            outcome.added += rows.len();
+        }
+
+        if outcome.added > 0 {
+            Self::touch_group(conn, tenant_id, group_id, timestamp, actor.as_deref()).await?;
+        }
+
+        Ok(Some(outcome))
+    }
+
+    async fn execute_members_remove(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<MembersRemoved>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        if !Self::group_exists(conn, tenant_id, group_id).await? {
+            return Ok(None);
+        }
+
+        let mut outcome = MembersRemoved::default();
+
+        for chunk in ids.chunks(CHUNK_SIZE) {
+            let sql = format!(
+                "DELETE FROM `t{}_group_memberships` WHERE group_id = ? AND member_id IN ({})",
+                tenant_id,
+                placeholders(chunk.len())
+            );
+            let mut query = sqlx::query(&sql).bind(group_id);
+            for id in chunk {
+                query = query.bind(id);
+            }
+            let result = query.execute(&mut *conn).await.map_err(|e| {
+                AppError::Database(format!("Failed to delete group memberships: {}", e))
+            })?;
+            outcome.removed += result.rows_affected() as usize;
+        }
+        outcome.not_members = ids.len().saturating_sub(outcome.removed);
+
+        if outcome.removed > 0 {
+            Self::touch_group(conn, tenant_id, group_id, timestamp, actor.as_deref()).await?;
+        }
+
+        Ok(Some(outcome))
+    }
+}
+
+/// `?, ?, ...` for an IN list of `count` values
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
@@ -0,0 +1,415 @@
+use async_trait::async_trait;
+use scim_v2::models::group::Member;
+use sqlx::{MySqlPool, Row};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::super::group_read::GroupReader;
+use super::filter_impl::json_text;
+use crate::backend::database::filter::FilterConverter;
+use crate::backend::database::list_query::{page_bounds, ListQuery};
+use crate::error::{AppError, AppResult};
+use crate::models::Group;
+use crate::parser::filter_operator::FilterOperator;
+use crate::parser::ResourceType;
+use crate::parser::{SortOrder, SortSpec};
+
+/// Display name of a membership row `m` joined to its user `u` or group `g`
+const MEMBER_DISPLAY_SQL: &str = "CASE \
+    WHEN m.member_type = 'User' THEN COALESCE(\
+        JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.displayName')), \
+        JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.name.formatted')), \
+        CONCAT(JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.name.givenName')), ' ', JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.name.familyName')))) \
+    WHEN m.member_type = 'Group' THEN JSON_UNQUOTE(JSON_EXTRACT(g.data_orig, '$.displayName')) \
+    END";
+
+/// MySQL-specific implementation of GroupReader
+pub struct MySqlGroupReader {
+    pool: MySqlPool,
+    /// See `DatabaseBackendConfig::external_id_case_insensitive_tenants`
+    case_insensitive_external_ids: Arc<HashSet<u32>>,
+}
+
+impl MySqlGroupReader {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            case_insensitive_external_ids: Arc::default(),
+        }
+    }
+
+    /// Match externalId case-insensitively in the given tenants
+    pub fn with_case_insensitive_external_ids(mut self, tenants: Arc<HashSet<u32>>) -> Self {
+        self.case_insensitive_external_ids = tenants;
+        self
+    }
+
+    /// Generate table name for a resource type and tenant
+    fn table_name(&self, resource: &str, tenant_id: u32) -> String {
+        format!("t{}_{}", tenant_id, resource)
+    }
+
+    /// Get users table name for a tenant
+    fn users_table(&self, tenant_id: u32) -> String {
+        self.table_name("users", tenant_id)
+    }
+
+    /// Get groups table name for a tenant
+    fn groups_table(&self, tenant_id: u32) -> String {
+        self.table_name("groups", tenant_id)
+    }
+
+    /// Get group memberships table name for a tenant
+    fn memberships_table(&self, tenant_id: u32) -> String {
+        self.table_name("group_memberships", tenant_id)
+    }
+
+    /// Convert SCIM attribute to MySQL column or JSON path for sorting
+    fn get_sort_column(&self, sort_spec: &SortSpec) -> String {
+        // Attribute names are case-insensitive (RFC 7643 Section 2.1), so match on the
+        // lowercased name; meta timestamps map to native columns instead of JSON extraction
+        match sort_spec.attribute.to_lowercase().as_str() {
+            // Special attributes stored in dedicated columns
+            "displayname" => "LOWER(display_name)".to_string(),
+            "id" => "id".to_string(),
+            "externalid" => "external_id".to_string(),
+            "meta.created" => "created_at".to_string(),
+            "meta.lastmodified" => "updated_at".to_string(),
+            // The attribute is interpolated into SQL, so anything that is not a plain
+            // (dotted) attribute name falls back to the default ordering
+            attr if !attr
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') =>
+            {
+                "created_at".to_string()
+            }
+            // JSON attributes - use case-insensitive sorting
+            _ => {
+                // data_norm stores lowercased attribute names, so the lowercased
+                // (possibly dotted) attribute can be used as the JSON path directly
+                let json_path = sort_spec.attribute.to_lowercase();
+                format!("LOWER({})", json_text("data_norm", &json_path))
+            }
+        }
+    }
+
+    /// Build ORDER BY clause from SortSpec
+    fn build_order_by(&self, sort_spec: Option<&SortSpec>) -> String {
+        match sort_spec {
+            Some(spec) => {
+                let column = self.get_sort_column(spec);
+                let direction = match spec.order {
+                    SortOrder::Ascending => "ASC",
+                    SortOrder::Descending => "DESC",
+                };
+                format!(" ORDER BY {} {}, id {}", column, direction, direction)
+            }
+            // Default sort, with id breaking ties between rows created together
+            None => " ORDER BY created_at, id".to_string(),
+        }
+    }
+
+    /// Helper function to fetch a group with its members
+    async fn fetch_group_with_members(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
+        // Return None for empty IDs
+        if id.is_empty() {
+            return Ok(None);
+        }
+
+        let table_name = self.groups_table(tenant_id);
+        let sql = format!(
+            "SELECT id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE id = ?",
+            table_name
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+
+        match row {
+            Some(row) => {
+                let data_orig: String = row.get("data_orig");
+                let mut group: Group =
+                    serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
+
+                // Set version in meta (ensure meta exists)
+                let version: i64 = row.get("version");
+                if group.meta().is_none() {
+                    // Create meta if it doesn't exist
+                    let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
+                    let updated_at: chrono::DateTime<chrono::Utc> = row.get("updated_at");
+                    let meta = scim_v2::models::scim_schema::Meta {
+                        resource_type: Some(ResourceType::Group.scim_name().to_string()),
+                        created: Some(crate::utils::format_scim_datetime(created_at)),
+                        last_modified: Some(crate::utils::format_scim_datetime(updated_at)),
+                        location: None,
+                        version: Some(format!("W/\"{}\"", version)),
+                    };
+                    *group.meta_mut() = Some(meta);
+                } else {
+                    // Update existing meta with version
+                    if let Some(ref mut meta) = group.meta_mut() {
+                        meta.version = Some(format!("W/\"{}\"", version));
+                    }
+                }
+
+                // Fetch members
+                let members = self.fetch_group_members(tenant_id, id).await?;
+                *group.members_mut() = Some(members);
+
+                Ok(Some(group))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Helper function to fetch group members
+    async fn fetch_group_members(&self, tenant_id: u32, group_id: &str) -> AppResult<Vec<Member>> {
+        let users_table = self.users_table(tenant_id);
+        let groups_table = self.groups_table(tenant_id);
+        let memberships_table = self.memberships_table(tenant_id);
+
+        let sql = format!(
+            r#"
+            SELECT 
+                m.member_id,
+                m.member_type,
+                {} as display_name
+            FROM {} m
+            LEFT JOIN {} u ON m.member_id = u.id AND m.member_type = 'User'
+            LEFT JOIN {} g ON m.member_id = g.id AND m.member_type = 'Group'
+            WHERE m.group_id = ?
+            ORDER BY m.created_at
+            "#,
+            MEMBER_DISPLAY_SQL, memberships_table, users_table, groups_table
+        );
+
+        let rows = sqlx::query(&sql)
+            .bind(group_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch group members: {}", e)))?;
+
+        let mut members = Vec::new();
+        for row in rows {
+            let member_id: String = row.get("member_id");
+            let member_type: String = row.get("member_type");
+            let display_name: Option<String> = row.get("display_name");
+
+            // Tenant-relative $ref by member type; the resource handler resolves the base URL
+            let resource_type = match member_type.as_str() {
+                "User" => "Users",
+                "Group" => "Groups",
+                _ => "Resources",
+            };
+            let ref_path = crate::utils::build_tenant_ref(tenant_id, resource_type, &member_id);
+
+            members.push(Member {
+                value: Some(member_id),
+                ref_: Some(ref_path),
+                display: display_name,
+                type_: Some(member_type),
+            });
+        }
+
+        Ok(members)
+    }
+
+    /// Members of the outer group row as normalized JSON objects, for `members` filters
+    fn members_source(&self, tenant_id: u32) -> String {
+        let groups_table = self.groups_table(tenant_id);
+        format!(
+            "SELECT JSON_OBJECT('value', m.member_id, 'type', LOWER(m.member_type), \
+             'display', LOWER({display})) AS value \
+             FROM {memberships} m \
+             LEFT JOIN {users} u ON m.member_id = u.id AND m.member_type = 'User' \
+             LEFT JOIN {groups} g ON m.member_id = g.id AND m.member_type = 'Group' \
+             WHERE m.group_id = {groups}.id",
+            display = MEMBER_DISPLAY_SQL,
+            memberships = self.memberships_table(tenant_id),
+            users = self.users_table(tenant_id),
+            groups = groups_table
+        )
+    }
+
+    /// Create a filter converter for this tenant
+    fn filter_converter(
+        &self,
+        tenant_id: u32,
+    ) -> crate::backend::database::mysql::filter_impl::MySqlFilterConverter {
+        crate::backend::database::mysql::filter_impl::MySqlFilterConverter::new()
+            .with_elements_source("members", self.members_source(tenant_id))
+            .with_case_insensitive_external_id(
+                self.case_insensitive_external_ids.contains(&tenant_id),
+            )
+    }
+
+    /// One page of the groups matching an optional filter, with the number of matches
+    async fn find_groups_page(
+        &self,
+        tenant_id: u32,
+        filter: Option<&FilterOperator>,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        let (condition, params) = match filter {
+            Some(filter) => {
+                let (condition, params) = self
+                    .filter_converter(tenant_id)
+                    .to_where_clause(filter, ResourceType::Group)?;
+                (Some(condition), params)
+            }
+            None => (None, Vec::new()),
+        };
+        let query = ListQuery::new(&self.groups_table(tenant_id), condition.as_deref());
+
+        let count_sql = query.count_sql();
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for param in &params {
+            count_query = count_query.bind(param);
+        }
+        let total = count_query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count groups: {}", e)))?
+            .0;
+
+        let (limit, offset) = page_bounds(start_index, count);
+        let sql = query.page_sql("id", &self.build_order_by(sort_spec), "?", "?");
+        let mut page_query = sqlx::query(&sql);
+        for param in &params {
+            page_query = page_query.bind(param);
+        }
+        let rows = page_query
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch groups: {}", e)))?;
+
+        let mut groups = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            if let Some(group) = self.fetch_group_with_members(tenant_id, &id).await? {
+                groups.push(group);
+            }
+        }
+
+        Ok((groups, total))
+    }
+}
+
+#[async_trait]
+impl GroupReader for MySqlGroupReader {
+    async fn find_group_by_id(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
+        self.fetch_group_with_members(tenant_id, id).await
+    }
+
+    async fn find_group_by_display_name(
+        &self,
+        tenant_id: u32,
+        display_name: &str,
+    ) -> AppResult<Option<Group>> {
+        let table_name = self.groups_table(tenant_id);
+        let sql = format!(
+            "SELECT id FROM {} WHERE LOWER(display_name) = LOWER(?)",
+            table_name
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(display_name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to find group by display name: {}", e))
+            })?;
+
+        match row {
+            Some(row) => {
+                let id: String = row.get("id");
+                self.fetch_group_with_members(tenant_id, &id).await
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>> {
+        let table_name = self.groups_table(tenant_id);
+        let (column, document, external_id) =
+            if self.case_insensitive_external_ids.contains(&tenant_id) {
+                (
+                    "LOWER(external_id)",
+                    "LOWER(JSON_UNQUOTE(JSON_EXTRACT(data_orig, '$.externalId')))",
+                    external_id.to_lowercase(),
+                )
+            } else {
+                (
+                    "external_id",
+                    "JSON_UNQUOTE(JSON_EXTRACT(data_orig, '$.externalId'))",
+                    external_id.to_string(),
+                )
+            };
+        // Rows the startup backfill left out of the column match by their document
+        let sql = format!(
+            "SELECT id FROM {} WHERE {} = ? OR (external_id IS NULL AND {} = ?)",
+            table_name, column, document
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(&external_id)
+            .bind(&external_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to find group by externalId: {}", e))
+            })?;
+
+        match row {
+            Some(row) => {
+                let id: String = row.get("id");
+                self.fetch_group_with_members(tenant_id, &id).await
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all_groups(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.find_groups_page(tenant_id, None, start_index, count, None)
+            .await
+    }
+
+    async fn find_all_groups_sorted(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.find_groups_page(tenant_id, None, start_index, count, sort_spec)
+            .await
+    }
+
+    async fn find_groups_by_filter(
+        &self,
+        tenant_id: u32,
+        filter: &FilterOperator,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.find_groups_page(tenant_id, Some(filter), start_index, count, sort_spec)
+            .await
+    }
+}
@@ -0,0 +1,293 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::{MySqlConnection, Row};
+
+use super::super::group_insert::map_membership_insert_error;
+use super::super::group_update::{GroupUpdater, PreparedGroupUpdateData};
+use super::super::DbTransaction;
+use crate::error::{AppError, AppResult};
+use crate::models::Group;
+
+/// MySQL-specific implementation of GroupUpdater
+///
+/// This handles MySQL's TEXT-based ID storage, JSON TEXT format,
+/// and group membership management.
+#[derive(Default)]
+pub struct MySqlGroupUpdater;
+
+impl MySqlGroupUpdater {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Check for case-insensitive duplicate displayName excluding current group
+    async fn check_duplicate_display_name(
+        &self,
+        conn: &mut MySqlConnection,
+        tenant_id: u32,
+        display_name: &str,
+        exclude_id: &str,
+    ) -> AppResult<()> {
+        let table_name = format!("`t{}_groups`", tenant_id);
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(display_name) = LOWER(?) AND id != ?",
+            table_name
+        );
+
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(display_name)
+            .bind(exclude_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate displayName: {}", e))
+            })?;
+
+        if count > 0 {
+            return Err(AppError::BadRequest("Group already exists".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GroupUpdater for MySqlGroupUpdater {
+    async fn execute_group_update(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        _id: &str,
+        data: PreparedGroupUpdateData,
+    ) -> AppResult<Option<Group>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        // Check for case-insensitive duplicate displayName before update
+        self.check_duplicate_display_name(conn, tenant_id, &data.display_name, &data.id)
+            .await?;
+
+        // Build table names
+        let groups_table = format!("`t{}_groups`", tenant_id);
+        let memberships_table = format!("`t{}_group_memberships`", tenant_id);
+
+        // Convert JSONB Values to JSON strings for MySQL LONGTEXT storage
+        let data_orig_str = json_value_to_string(&data.data_orig)?;
+        let data_norm_str = json_value_to_string(&data.data_norm)?;
+
+        // Update the group record
+        let group_sql = format!(
+            "UPDATE {} SET display_name = ?, external_id = ?, data_orig = ?, data_norm = ?, version = version + 1, updated_at = ?, last_modified_by = ? WHERE id = ?",
+            groups_table
+        );
+
+        let result = sqlx::query(&group_sql)
+            .bind(&data.display_name)
+            .bind(&data.external_id)
+            .bind(&data_orig_str) // MySQL uses TEXT
+            .bind(&data_norm_str) // MySQL uses TEXT
+            .bind(data.timestamp)
+            .bind(&actor)
+            .bind(&data.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| super::user_update_impl::map_database_error(e, "Group"))?;
+
+        if result.rows_affected() == 0 {
+            // Group not found
+            return Ok(None);
+        }
+
+        // Delete existing group memberships
+        let delete_members_sql = format!("DELETE FROM {} WHERE group_id = ?", memberships_table);
+
+        sqlx::query(&delete_members_sql)
+            .bind(&data.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to delete group memberships: {}", e))
+            })?;
+
+        // Insert new group memberships if present
+        if let Some(members) = &data.members {
+            let insert_member_sql = format!(
+                "INSERT INTO {} (group_id, member_id, member_type) VALUES (?, ?, ?)",
+                memberships_table
+            );
+
+            for member in members {
+                if let Some(member_id) = &member.value {
+                    let member_type = member.type_.as_deref().unwrap_or("User");
+
+                    sqlx::query(&insert_member_sql)
+                        .bind(&data.id)
+                        .bind(member_id)
+                        .bind(member_type)
+                        .execute(&mut *conn)
+                        .await
+                        .map_err(|e| map_membership_insert_error(e, member_id))?;
+                }
+            }
+        }
+
+        // Fetch the updated group with properly populated members
+        fetch_group_with_members(conn, tenant_id, &data.id).await
+    }
+
+    async fn find_group_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        fetch_group_with_members(tx.mysql()?, tenant_id, id).await
+    }
+}
+
+/// Fetch a group with its members on the given connection
+pub(super) async fn fetch_group_with_members(
+    conn: &mut MySqlConnection,
+    tenant_id: u32,
+    id: &str,
+) -> AppResult<Option<Group>> {
+    // Return None for empty IDs
+    if id.is_empty() {
+        return Ok(None);
+    }
+
+    let table_name = format!("`t{}_groups`", tenant_id);
+    let sql = format!(
+        "SELECT id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE id = ?",
+        table_name
+    );
+
+    let row = sqlx::query(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+
+    match row {
+        Some(row) => {
+            let data_orig: String = row.get("data_orig");
+            let mut group: Group =
+                serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
+
+            // Set version in meta (ensure meta exists)
+            let version: i64 = row.get("version");
+            if group.meta().is_none() {
+                // Create meta if it doesn't exist
+                let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
+                let updated_at: chrono::DateTime<chrono::Utc> = row.get("updated_at");
+                let meta = scim_v2::models::scim_schema::Meta {
+                    resource_type: Some(crate::parser::ResourceType::Group.scim_name().to_string()),
+                    created: Some(crate::utils::format_scim_datetime(created_at)),
+                    last_modified: Some(crate::utils::format_scim_datetime(updated_at)),
+                    location: None,
+                    version: Some(format!("W/\"{}\"", version)),
+                };
+                *group.meta_mut() = Some(meta);
+            } else {
+                // Update existing meta with version
+                if let Some(ref mut meta) = group.meta_mut() {
+                    meta.version = Some(format!("W/\"{}\"", version));
+                }
+            }
+
+            // Fetch members
+            let members = fetch_group_members(conn, tenant_id, id).await?;
+            *group.members_mut() = if members.is_empty() {
+                None
+            } else {
+                Some(members)
+            };
+
+            Ok(Some(group))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetch the members of a group on the given connection
+async fn fetch_group_members(
+    conn: &mut MySqlConnection,
+    tenant_id: u32,
+    group_id: &str,
+) -> AppResult<Vec<scim_v2::models::group::Member>> {
+    let users_table = format!("`t{}_users`", tenant_id);
+    let groups_table = format!("`t{}_groups`", tenant_id);
+    let memberships_table = format!("`t{}_group_memberships`", tenant_id);
+
+    let sql = format!(
+        r#"
+        SELECT 
+            m.member_id,
+            m.member_type,
+            CASE 
+                WHEN m.member_type = 'User' THEN COALESCE(
+                    JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.displayName')), 
+                    JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.name.formatted')), 
+                    CONCAT(JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.name.givenName')), ' ', JSON_UNQUOTE(JSON_EXTRACT(u.data_orig, '$.name.familyName')))
+                )
+                WHEN m.member_type = 'Group' THEN JSON_UNQUOTE(JSON_EXTRACT(g.data_orig, '$.displayName'))
+            END as display_name
+        FROM {} m
+        LEFT JOIN {} u ON m.member_id = u.id AND m.member_type = 'User'
+        LEFT JOIN {} g ON m.member_id = g.id AND m.member_type = 'Group'
+        WHERE m.group_id = ?
+        ORDER BY m.created_at
+        "#,
+        memberships_table, users_table, groups_table
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(group_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch group members: {}", e)))?;
+
+    let mut members = Vec::new();
+    for row in rows {
+        let member_id: String = row.get("member_id");
+        let member_type: String = row.get("member_type");
+        let display_name: Option<String> = row.get("display_name");
+
+        // Tenant-relative $ref by member type; the resource handler resolves the base URL
+        let resource_type = match member_type.as_str() {
+            "User" => "Users",
+            "Group" => "Groups",
+            _ => "Resources",
+        };
+        let ref_path = crate::utils::build_tenant_ref(tenant_id, resource_type, &member_id);
+
+        members.push(scim_v2::models::group::Member {
+            value: Some(member_id),
+            ref_: Some(ref_path),
+            display: display_name,
+            type_: Some(member_type),
+        });
+    }
+
+    Ok(members)
+}
+
+/// Convert a JSON Value to a string for MySQL LONGTEXT storage
+///
+/// This ensures consistent JSON serialization for MySQL databases.
+fn json_value_to_string(value: &Value) -> AppResult<String> {
+    serde_json::to_string(value).map_err(AppError::Serialization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_value_to_string() {
+        let value = serde_json::json!({"displayName": "Test Group", "members": []});
+        let result = json_value_to_string(&value).unwrap();
+        assert!(result.contains("displayName"));
+        assert!(result.contains("Test Group"));
+    }
+}
@@ -0,0 +1,25 @@
+pub mod backend_impl;
+pub mod filter_impl;
+pub mod group_delete_impl;
+pub mod group_insert_impl;
+pub mod group_members_impl;
+pub mod group_read_impl;
+pub mod group_update_impl;
+pub mod schema;
+pub mod user_delete_impl;
+pub mod user_insert_impl;
+pub mod user_patch_impl;
+pub mod user_read_impl;
+pub mod user_update_impl;
+
+pub use backend_impl::MySqlBackend;
+pub use group_delete_impl::MySqlGroupDeleter;
+pub use group_insert_impl::MySqlGroupInserter;
+pub use group_members_impl::MySqlGroupMemberUpdater;
+pub use group_read_impl::MySqlGroupReader;
+pub use group_update_impl::MySqlGroupUpdater;
+pub use user_delete_impl::MySqlUserDeleter;
+pub use user_insert_impl::MySqlUserInserter;
+pub use user_patch_impl::MySqlUserPatcher;
+pub use user_read_impl::MySqlUserReader;
+pub use user_update_impl::MySqlUserUpdater;
@@ -0,0 +1,304 @@
+use crate::error::{AppError, AppResult};
+use sqlx::MySqlPool;
+
+/// Table options shared by the tenant tables
+///
+/// The binary collation makes unique keys and comparisons case-exact, as in
+/// the other backends; userName and displayName are matched with LOWER().
+const TABLE_OPTIONS: &str = "ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin";
+
+/// Initialize tenant-specific database schema for MySQL/MariaDB
+///
+/// This creates the necessary tables for a tenant including users, groups,
+/// and group memberships with proper indexes and constraints. MySQL has no
+/// `CREATE INDEX IF NOT EXISTS`, so the indexes are part of the table DDL.
+pub async fn init_tenant_schema(pool: &MySqlPool, tenant_id: u32) -> AppResult<()> {
+    let users_table = format!("t{}_users", tenant_id);
+    let groups_table = format!("t{}_groups", tenant_id);
+    let memberships_table = format!("t{}_group_memberships", tenant_id);
+
+    // Create users table
+    let users_sql = format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS {} (
+            id CHAR(36) NOT NULL PRIMARY KEY,
+            username VARCHAR(255) NOT NULL,
+            external_id VARCHAR(255),
+            data_orig LONGTEXT NOT NULL,
+            data_norm LONGTEXT NOT NULL,
+            version BIGINT NOT NULL DEFAULT 1,
+            created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+            updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+            created_by TEXT,
+            last_modified_by TEXT,
+            UNIQUE KEY uq_username (username),
+            UNIQUE KEY uq_external_id (external_id),
+            KEY idx_created_at (created_at)
+        ) {}
+        "#,
+        users_table, TABLE_OPTIONS
+    );
+
+    sqlx::query(&users_sql)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create users table: {}", e)))?;
+
+    // Create groups table
+    let groups_sql = format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS {} (
+            id CHAR(36) NOT NULL PRIMARY KEY,
+            display_name VARCHAR(255) NOT NULL,
+            external_id VARCHAR(255),
+            data_orig LONGTEXT NOT NULL,
+            data_norm LONGTEXT NOT NULL,
+            version BIGINT NOT NULL DEFAULT 1,
+            created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+            updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+            created_by TEXT,
+            last_modified_by TEXT,
+            UNIQUE KEY uq_display_name (display_name),
+            UNIQUE KEY uq_external_id (external_id),
+            KEY idx_created_at (created_at)
+        ) {}
+        "#,
+        groups_table, TABLE_OPTIONS
+    );
+
+    sqlx::query(&groups_sql)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create groups table: {}", e)))?;
+
+    // Create group memberships table
+    let memberships_sql = format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS {} (
+            id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+            group_id CHAR(36) NOT NULL,
+            member_id CHAR(36) NOT NULL,
+            member_type VARCHAR(5) NOT NULL CHECK (member_type IN ('User', 'Group')),
+            created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+            UNIQUE KEY uq_membership (group_id, member_id, member_type),
+            KEY idx_member_id (member_id),
+            KEY idx_member_type (member_type),
+            FOREIGN KEY (group_id) REFERENCES {} (id) ON DELETE CASCADE
+        ) {}
+        "#,
+        memberships_table, groups_table, TABLE_OPTIONS
+    );
+
+    sqlx::query(&memberships_sql)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create memberships table: {}", e)))?;
+
+    backfill_external_ids(pool, &users_table).await?;
+    backfill_external_ids(pool, &groups_table).await?;
+
+    Ok(())
+}
+
+/// Copy `externalId` from the stored document into the indexed column
+///
+/// Filters and lookups on externalId read the column, so rows whose column
+/// was left empty would otherwise not be found. The column is unique: a
+/// value shared with another row's document or column is logged and left
+/// out rather than failing startup, so those rows stay unfindable by
+/// externalId until the duplicate is resolved.
+async fn backfill_external_ids(pool: &MySqlPool, table: &str) -> AppResult<()> {
+    let value = "JSON_UNQUOTE(JSON_EXTRACT(data_orig, '$.externalId'))";
+    let pending =
+        "external_id IS NULL AND JSON_TYPE(JSON_EXTRACT(data_orig, '$.externalId')) = 'STRING'";
+    // MySQL cannot read the table an UPDATE writes, except through a derived table
+    let taken = format!(
+        "SELECT v FROM (SELECT {value} AS v FROM {table} \
+         WHERE JSON_TYPE(JSON_EXTRACT(data_orig, '$.externalId')) = 'STRING' \
+         GROUP BY v HAVING COUNT(*) > 1 \
+         UNION SELECT external_id FROM {table} WHERE external_id IS NOT NULL) AS taken"
+    );
+    let backfill_error = |e: sqlx::Error| {
+        AppError::Database(format!("Failed to backfill externalId in {}: {}", table, e))
+    };
+
+    let duplicates: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT id, {value} FROM {table} WHERE {pending} AND {value} IN ({taken})"
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(backfill_error)?;
+    for (id, external_id) in duplicates {
+        tracing::warn!(
+            table,
+            id,
+            external_id,
+            "Not backfilling a duplicate externalId into the external_id column"
+        );
+    }
+
+    sqlx::query(&format!(
+        "UPDATE {table} SET external_id = {value} WHERE {pending} AND {value} NOT IN ({taken})"
+    ))
+    .execute(pool)
+    .await
+    .map_err(backfill_error)?;
+
+    Ok(())
+}
+
+/// Drop tenant-specific schema (for cleanup/testing)
+#[allow(dead_code)]
+pub async fn drop_tenant_schema(pool: &MySqlPool, tenant_id: u32) -> AppResult<()> {
+    let memberships_table = format!("t{}_group_memberships", tenant_id);
+    let groups_table = format!("t{}_groups", tenant_id);
+    let users_table = format!("t{}_users", tenant_id);
+    let sync_stats_table = format!("t{}_sync_stats", tenant_id);
+
+    // Drop tables in reverse order due to foreign key constraints
+    for table in [
+        &sync_stats_table,
+        &memberships_table,
+        &groups_table,
+        &users_table,
+    ] {
+        let sql = format!("DROP TABLE IF EXISTS {}", table);
+        sqlx::query(&sql)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to drop table {}: {}", table, e)))?;
+    }
+
+    Ok(())
+}
+
+/// Rebuild the tenant's tables and refresh their statistics after bulk deletes
+///
+/// InnoDB implements `OPTIMIZE TABLE` as a table rebuild followed by `ANALYZE`.
+pub async fn vacuum_tenant_schema(pool: &MySqlPool, tenant_id: u32) -> AppResult<()> {
+    let sql = format!(
+        "OPTIMIZE TABLE t{0}_users, t{0}_groups, t{0}_group_memberships",
+        tenant_id
+    );
+    sqlx::raw_sql(&sql).execute(pool).await.map_err(|e| {
+        AppError::Database(format!("Failed to optimize tenant {}: {}", tenant_id, e))
+    })?;
+
+    Ok(())
+}
+
+/// Create the table holding a tenant's sync statistics snapshot
+///
+/// Only used with `server.stats.persist`, so it is created on first use
+/// rather than by `init_tenant_schema`.
+async fn create_sync_stats_table(pool: &MySqlPool, tenant_id: u32) -> AppResult<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS t{}_sync_stats (\
+         id INT NOT NULL PRIMARY KEY CHECK (id = 1), \
+         snapshot LONGTEXT NOT NULL, \
+         updated_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6)\
+         ) {}",
+        tenant_id, TABLE_OPTIONS
+    );
+    sqlx::query(&sql)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create sync stats table: {}", e)))?;
+
+    Ok(())
+}
+
+/// Latest sync statistics snapshot saved for a tenant
+pub async fn load_sync_stats(
+    pool: &MySqlPool,
+    tenant_id: u32,
+) -> AppResult<Option<serde_json::Value>> {
+    create_sync_stats_table(pool, tenant_id).await?;
+    let snapshot: Option<String> = sqlx::query_scalar(&format!(
+        "SELECT snapshot FROM t{}_sync_stats WHERE id = 1",
+        tenant_id
+    ))
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to load sync stats: {}", e)))?;
+
+    snapshot
+        .map(|snapshot| serde_json::from_str(&snapshot).map_err(AppError::Serialization))
+        .transpose()
+}
+
+/// Replace the sync statistics snapshot saved for a tenant
+pub async fn save_sync_stats(
+    pool: &MySqlPool,
+    tenant_id: u32,
+    snapshot: &serde_json::Value,
+) -> AppResult<()> {
+    create_sync_stats_table(pool, tenant_id).await?;
+    let sql = format!(
+        "INSERT INTO t{}_sync_stats (id, snapshot, updated_at) VALUES (1, ?, CURRENT_TIMESTAMP(6)) \
+         ON DUPLICATE KEY UPDATE snapshot = VALUES(snapshot), updated_at = VALUES(updated_at)",
+        tenant_id
+    );
+    sqlx::query(&sql)
+        .bind(snapshot.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to save sync stats: {}", e)))?;
+
+    Ok(())
+}
+
+/// Ids of the tenants whose tables exist, in ascending order
+pub async fn list_tenant_schemas(pool: &MySqlPool) -> AppResult<Vec<u32>> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT CAST(table_name AS CHAR) FROM information_schema.tables \
+         WHERE table_schema = DATABASE()",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list tables: {}", e)))?;
+
+    Ok(crate::backend::database::tenants::tenant_ids_from_tables(
+        tables,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::mysql::MySqlPoolOptions;
+
+    #[tokio::test]
+    async fn test_schema_creation() {
+        // This test requires a running MySQL or MariaDB instance
+        // Skip if MYSQL_DATABASE_URL is not set
+        let Ok(url) = std::env::var("MYSQL_DATABASE_URL") else {
+            return;
+        };
+
+        let pool = MySqlPoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await
+            .unwrap();
+
+        let tenant_id = 1u32;
+
+        // Create schema, twice to check it is idempotent
+        init_tenant_schema(&pool, tenant_id).await.unwrap();
+        init_tenant_schema(&pool, tenant_id).await.unwrap();
+
+        // Verify tables exist
+        let users_table = format!("t{}_users", tenant_id);
+        let count: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", users_table))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(count.0, 0);
+        assert!(list_tenant_schemas(&pool).await.unwrap().contains(&1));
+
+        // Clean up
+        drop_tenant_schema(&pool, tenant_id).await.unwrap();
+    }
+}
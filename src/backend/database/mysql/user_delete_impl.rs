@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+
+use super::super::user_delete::UserDeleter;
+use super::super::DbTransaction;
+use crate::error::{AppError, AppResult};
+
+/// MySQL-specific implementation of UserDeleter
+///
+/// This handles MySQL's TEXT-based IDs and SQL syntax.
+#[derive(Default)]
+pub struct MySqlUserDeleter;
+
+impl MySqlUserDeleter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl UserDeleter for MySqlUserDeleter {
+    async fn execute_user_delete(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        let conn = tx.mysql()?;
+
+        let users_table = format!("t{}_users", tenant_id);
+        let memberships_table = format!("t{}_group_memberships", tenant_id);
+
+        // First, delete the user from group memberships
+        let membership_sql = format!(
+            "DELETE FROM {} WHERE member_id = ? AND member_type = 'User'",
+            memberships_table
+        );
+
+        sqlx::query(&membership_sql)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to delete user group memberships: {}", e))
+            })?;
+
+        // Then, delete the user from users table
+        let user_sql = format!("DELETE FROM {} WHERE id = ?", users_table);
+
+        let result = sqlx::query(&user_sql)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete user: {}", e)))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
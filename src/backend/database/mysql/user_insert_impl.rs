@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::MySqlConnection;
+
+use super::super::user_insert::{PreparedUserData, UserInsertProcessor, UserInserter};
+use super::super::DbTransaction;
+use crate::error::{AppError, AppResult};
+use crate::models::User;
+
+/// MySQL-specific implementation of UserInserter
+///
+/// This handles MySQL's JSON LONGTEXT storage while using shared SQL generation.
+#[derive(Default)]
+pub struct MySqlUserInserter;
+
+impl MySqlUserInserter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Convert JSON Value to String for MySQL LONGTEXT storage
+    fn json_value_to_string(&self, value: &Value) -> AppResult<String> {
+        serde_json::to_string(value).map_err(AppError::Serialization)
+    }
+
+    /// Check for case-insensitive duplicate username
+    async fn check_duplicate_username(
+        &self,
+        conn: &mut MySqlConnection,
+        tenant_id: u32,
+        username: &str,
+    ) -> AppResult<()> {
+        let table_name = format!("t{}_users", tenant_id);
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(username) = LOWER(?)",
+            table_name
+        );
+
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(username)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate username: {}", e))
+            })?;
+
+        if count > 0 {
+            return Err(AppError::Conflict(
+                "User with this userName already exists".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserInserter for MySqlUserInserter {
+    async fn execute_user_insert(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        data: PreparedUserData,
+    ) -> AppResult<User> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        // Check for case-insensitive duplicate username before insertion
+        self.check_duplicate_username(conn, tenant_id, &data.username)
+            .await?;
+
+        let table_name = format!("t{}_users", tenant_id);
+        let sql = format!(
+            "INSERT INTO {} (id, username, external_id, data_orig, data_norm, version, created_at, updated_at, created_by, last_modified_by) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            table_name
+        );
+
+        // MySQL: convert JSON to strings
+        let data_orig_str = self.json_value_to_string(&data.data_orig)?;
+        let data_norm_str = self.json_value_to_string(&data.data_norm)?;
+
+        sqlx::query(&sql)
+            .bind(&data.id)
+            .bind(&data.username)
+            .bind(&data.external_id)
+            .bind(&data_orig_str) // MySQL: JSON as TEXT
+            .bind(&data_norm_str)
+            .bind(1i64) // version = 1 for new records
+            .bind(data.timestamp)
+            .bind(data.timestamp)
+            .bind(&actor)
+            .bind(&actor)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| map_database_error(e, "User"))?;
+
+        Ok(UserInsertProcessor::finalize_user_response(data.user))
+    }
+}
+
+/// Map database errors to AppError using common logic
+///
+/// MySQL reports unique key violations as `Duplicate entry '..' for key
+/// '..'`, naming the violated key (uq_username, uq_external_id, ...).
+pub fn map_database_error(error: sqlx::Error, resource_type: &str) -> AppError {
+    let error_str = error.to_string();
+    if error_str.contains("Duplicate entry") {
+        if error_str.contains("username") {
+            AppError::Conflict("User with this userName already exists".to_string())
+        } else if error_str.contains("external_id") {
+            AppError::Conflict("User with this externalId already exists".to_string())
+        } else if error_str.contains("display_name") {
+            AppError::Conflict("Group with this displayName already exists".to_string())
+        } else {
+            AppError::Conflict("Resource already exists".to_string())
+        }
+    } else {
+        AppError::Database(format!("Failed to create {}: {}", resource_type, error_str))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_duplicate_entry_errors() {
+        let duplicate = |key: &str| {
+            sqlx::Error::Protocol(format!(
+                "1062 (23000): Duplicate entry 'x' for key 't1_users.{}'",
+                key
+            ))
+        };
+
+        assert!(matches!(
+            map_database_error(duplicate("uq_username"), "User"),
+            AppError::Conflict(m) if m.contains("userName")
+        ));
+        assert!(matches!(
+            map_database_error(duplicate("uq_external_id"), "User"),
+            AppError::Conflict(m) if m.contains("externalId")
+        ));
+        assert!(matches!(
+            map_database_error(sqlx::Error::PoolTimedOut, "User"),
+            AppError::Database(_)
+        ));
+    }
+}
@@ -0,0 +1,239 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{MySqlConnection, Row};
+
+use super::super::user_patch::{PreparedUserPatchData, UserPatcher};
+use super::super::DbTransaction;
+use crate::error::{AppError, AppResult};
+use crate::models::User;
+
+/// MySQL-specific implementation of UserPatcher
+///
+/// This handles MySQL's CHAR ID storage and LONGTEXT JSON documents
+/// for user patch operations.
+#[derive(Default)]
+pub struct MySqlUserPatcher;
+
+impl MySqlUserPatcher {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Convert JSON Value to String for MySQL LONGTEXT storage
+    fn json_value_to_string(&self, value: &Value) -> AppResult<String> {
+        serde_json::to_string(value).map_err(AppError::Serialization)
+    }
+
+    /// Check for case-insensitive duplicate username excluding current user
+    async fn check_duplicate_username(
+        &self,
+        conn: &mut MySqlConnection,
+        tenant_id: u32,
+        username: &str,
+        exclude_id: &str,
+    ) -> AppResult<()> {
+        let table_name = format!("t{}_users", tenant_id);
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(username) = LOWER(?) AND id != ?",
+            table_name
+        );
+
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(username)
+            .bind(exclude_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate username: {}", e))
+            })?;
+
+        if count > 0 {
+            return Err(AppError::BadRequest("User already exists".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserPatcher for MySqlUserPatcher {
+    async fn execute_user_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        _id: &str,
+        data: PreparedUserPatchData,
+    ) -> AppResult<Option<User>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        // Check for case-insensitive duplicate username before patch
+        self.check_duplicate_username(conn, tenant_id, &data.username, &data.id)
+            .await?;
+
+        // Build table name
+        let table_name = format!("t{}_users", tenant_id);
+
+        // Convert JSONB Values to JSON strings for MySQL LONGTEXT storage
+        let data_orig_str = self.json_value_to_string(&data.data_orig)?;
+        let data_norm_str = self.json_value_to_string(&data.data_norm)?;
+
+        // MySQL UPDATE SQL with TEXT-based parameter binding and version increment
+        let sql = format!(
+            "UPDATE {} SET username = ?, external_id = ?, data_orig = ?, data_norm = ?, version = version + 1, updated_at = ?, last_modified_by = ? WHERE id = ?",
+            table_name
+        );
+
+        let result = sqlx::query(&sql)
+            .bind(&data.username)
+            .bind(&data.external_id)
+            .bind(&data_orig_str) // MySQL uses TEXT
+            .bind(&data_norm_str) // MySQL uses TEXT
+            .bind(data.timestamp)
+            .bind(&actor)
+            .bind(&data.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| map_database_error(e, "User"))?;
+
+        if result.rows_affected() > 0 {
+            Ok(Some(data.user))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn execute_active_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        active: bool,
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<User>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        let table_name = format!("t{}_users", tenant_id);
+        let last_modified = crate::utils::format_scim_datetime(timestamp);
+
+        // JSON_SET edits both documents in place; data_norm keys and
+        // non-caseExact values are lowercase. MySQL has no RETURNING, so the
+        // patched document is read back afterwards.
+        let sql = format!(
+            "UPDATE {} SET \
+             data_orig = JSON_SET(data_orig, '$.active', JSON_EXTRACT(?, '$'), '$.meta.lastModified', ?), \
+             data_norm = JSON_SET(data_norm, '$.active', JSON_EXTRACT(?, '$'), '$.meta.lastmodified', ?), \
+             version = version + 1, updated_at = ?, last_modified_by = ? WHERE id = ?",
+            table_name
+        );
+
+        let result = sqlx::query(&sql)
+            .bind(active.to_string())
+            .bind(&last_modified)
+            .bind(active.to_string())
+            .bind(last_modified.to_lowercase())
+            .bind(timestamp)
+            .bind(&actor)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| map_database_error(e, "User"))?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let select_sql = format!("SELECT id, data_orig FROM {} WHERE id = ?", table_name);
+        let row = sqlx::query(&select_sql)
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| map_database_error(e, "User"))?;
+
+        match row {
+            Some(row) => {
+                let data_orig: String = row.get("data_orig");
+                let mut user: User =
+                    serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
+                let db_id: String = row.get("id");
+                *user.id_mut() = Some(db_id);
+                Ok(Some(user))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_user_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<User>> {
+        let conn = tx.mysql()?;
+
+        let table_name = format!("t{}_users", tenant_id);
+        let sql = format!(
+            "SELECT id, username, external_id, data_orig, data_norm, created_at, updated_at FROM {} WHERE id = ?",
+            table_name
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find user for patch: {}", e)))?;
+
+        match row {
+            Some(row) => {
+                let data_orig: String = row.get("data_orig");
+                let mut user: User =
+                    serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
+
+                // Ensure ID is set from database (in case data_orig doesn't have it)
+                let db_id: String = row.get("id");
+                *user.id_mut() = Some(db_id);
+
+                // Remove password from response
+                *user.password_mut() = None;
+
+                Ok(Some(user))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Map MySQL-specific database errors to appropriate application errors
+pub fn map_database_error(e: sqlx::Error, resource_type: &str) -> AppError {
+    match e {
+        sqlx::Error::Database(db_err) => {
+            let error_message = db_err.message();
+
+            // Handle unique constraint violations
+            if error_message.contains("Duplicate entry") {
+                if error_message.contains("username") {
+                    return AppError::BadRequest(format!(
+                        "A {} with this username already exists",
+                        resource_type.to_lowercase()
+                    ));
+                } else if error_message.contains("external_id") {
+                    return AppError::BadRequest(format!(
+                        "A {} with this external ID already exists",
+                        resource_type.to_lowercase()
+                    ));
+                }
+                return AppError::BadRequest(format!("{} already exists", resource_type));
+            }
+
+            // Handle other database errors
+            AppError::Database(format!("Database error: {}", error_message))
+        }
+        _ => AppError::Database(format!(
+            "Failed to patch {}: {}",
+            resource_type.to_lowercase(),
+            e
+        )),
+    }
+}
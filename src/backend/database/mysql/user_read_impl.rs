@@ -0,0 +1,498 @@
+use async_trait::async_trait;
+use scim_v2::models::user::Group as UserGroup;
+use sqlx::{MySqlPool, Row};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::super::user_read::{effective_members_condition, UserReader};
+use super::filter_impl::json_text;
+use crate::backend::database::filter::FilterConverter;
+use crate::backend::database::list_query::{page_bounds, ListQuery};
+use crate::error::{AppError, AppResult};
+use crate::models::User;
+use crate::parser::filter_operator::FilterOperator;
+use crate::parser::ResourceType;
+use crate::parser::{SortOrder, SortSpec};
+
+/// MySQL-specific implementation of UserReader
+pub struct MySqlUserReader {
+    pool: MySqlPool,
+    /// See `DatabaseBackendConfig::external_id_case_insensitive_tenants`
+    case_insensitive_external_ids: Arc<HashSet<u32>>,
+}
+
+impl MySqlUserReader {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            case_insensitive_external_ids: Arc::default(),
+        }
+    }
+
+    /// Match externalId case-insensitively in the given tenants
+    pub fn with_case_insensitive_external_ids(mut self, tenants: Arc<HashSet<u32>>) -> Self {
+        self.case_insensitive_external_ids = tenants;
+        self
+    }
+
+    /// Generate table name for a resource type and tenant
+    fn table_name(&self, resource: &str, tenant_id: u32) -> String {
+        format!("t{}_{}", tenant_id, resource)
+    }
+
+    /// Get users table name for a tenant
+    fn users_table(&self, tenant_id: u32) -> String {
+        self.table_name("users", tenant_id)
+    }
+
+    /// Get groups table name for a tenant
+    fn groups_table(&self, tenant_id: u32) -> String {
+        self.table_name("groups", tenant_id)
+    }
+
+    /// Get group memberships table name for a tenant
+    fn memberships_table(&self, tenant_id: u32) -> String {
+        self.table_name("group_memberships", tenant_id)
+    }
+
+    /// Convert SCIM attribute to MySQL column or JSON path for sorting
+    fn get_sort_column(&self, sort_spec: &SortSpec) -> String {
+        // Attribute names are case-insensitive (RFC 7643 Section 2.1), so match on the
+        // lowercased name; meta timestamps map to native columns instead of JSON extraction
+        match sort_spec.attribute.to_lowercase().as_str() {
+            // Special attributes stored in dedicated columns
+            "username" => "LOWER(username)".to_string(),
+            "id" => "id".to_string(),
+            "externalid" => "external_id".to_string(),
+            "meta.created" => "created_at".to_string(),
+            "meta.lastmodified" => "updated_at".to_string(),
+            // The attribute is interpolated into SQL, so anything that is not a plain
+            // (dotted) attribute name falls back to the default ordering
+            attr if !attr
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') =>
+            {
+                "created_at".to_string()
+            }
+            // JSON attributes - use case-insensitive sorting
+            _ => {
+                // data_norm stores lowercased attribute names, so the lowercased
+                // (possibly dotted) attribute can be used as the JSON path directly
+                let json_path = sort_spec.attribute.to_lowercase();
+                format!("LOWER({})", json_text("data_norm", &json_path))
+            }
+        }
+    }
+
+    /// Build ORDER BY clause from SortSpec
+    fn build_order_by(&self, sort_spec: Option<&SortSpec>) -> String {
+        match sort_spec {
+            Some(spec) => {
+                let column = self.get_sort_column(spec);
+                let direction = match spec.order {
+                    SortOrder::Ascending => "ASC",
+                    SortOrder::Descending => "DESC",
+                };
+                format!(" ORDER BY {} {}, id {}", column, direction, direction)
+            }
+            // Default sort, with id breaking ties between rows created together
+            None => " ORDER BY created_at, id".to_string(),
+        }
+    }
+
+    /// Helper function to fetch a user with or without groups
+    async fn fetch_user_with_groups_optional(
+        &self,
+        tenant_id: u32,
+        id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        let table_name = self.users_table(tenant_id);
+        let sql = format!(
+            "SELECT id, username, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE id = ?",
+            table_name
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find user: {}", e)))?;
+
+        match row {
+            Some(row) => {
+                let data_orig: String = row.get("data_orig");
+                let mut user: User =
+                    serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
+
+                // Ensure ID is set from database (in case data_orig doesn't have it)
+                let db_id: String = row.get("id");
+                *user.id_mut() = Some(db_id);
+
+                // Remove password from response
+                *user.password_mut() = None;
+
+                // Set version in meta (ensure meta exists)
+                let version: i64 = row.get("version");
+                if user.meta().is_none() {
+                    // Create meta if it doesn't exist
+                    let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
+                    let updated_at: chrono::DateTime<chrono::Utc> = row.get("updated_at");
+                    let meta = scim_v2::models::scim_schema::Meta {
+                        resource_type: Some(ResourceType::User.scim_name().to_string()),
+                        created: Some(crate::utils::format_scim_datetime(created_at)),
+                        last_modified: Some(crate::utils::format_scim_datetime(updated_at)),
+                        location: None,
+                        version: Some(format!("W/\"{}\"", version)),
+                    };
+                    *user.meta_mut() = Some(meta);
+                } else {
+                    // Update existing meta with version
+                    if let Some(ref mut meta) = user.meta_mut() {
+                        meta.version = Some(format!("W/\"{}\"", version));
+                    }
+                }
+
+                // Only fetch groups if include_groups is true
+                if include_groups {
+                    let groups = self.fetch_user_groups(tenant_id, id).await?;
+                    *user.groups_mut() = Some(groups);
+                } else {
+                    // Set groups to None when not including groups (optimization)
+                    *user.groups_mut() = None;
+                }
+
+                Ok(Some(user))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Helper function to fetch groups that a user belongs to
+    async fn fetch_user_groups(&self, tenant_id: u32, user_id: &str) -> AppResult<Vec<UserGroup>> {
+        let groups_table = self.groups_table(tenant_id);
+        let memberships_table = self.memberships_table(tenant_id);
+
+        let sql = format!(
+            r#"
+            SELECT 
+                g.id,
+                JSON_UNQUOTE(JSON_EXTRACT(g.data_orig, '$.displayName')) AS display_name
+            FROM {} g
+            INNER JOIN {} m ON g.id = m.group_id
+            WHERE m.member_id = ? AND m.member_type = 'User'
+            ORDER BY g.created_at
+            "#,
+            groups_table, memberships_table
+        );
+
+        let rows = sqlx::query(&sql)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch user groups: {}", e)))?;
+
+        let mut groups = Vec::new();
+        for row in rows {
+            let group_id: String = row.get("id");
+            let display_name: Option<String> = row.get("display_name");
+
+            // Tenant-relative $ref; the resource handler resolves the base URL
+            let ref_url = crate::utils::build_tenant_ref(tenant_id, "Groups", &group_id);
+
+            groups.push(UserGroup {
+                value: Some(group_id),
+                ref_: Some(ref_url),
+                display: display_name,
+                type_: Some("direct".to_string()),
+            });
+        }
+
+        Ok(groups)
+    }
+
+    /// Groups of the outer user row as normalized JSON objects, for `groups` filters
+    fn groups_source(&self, tenant_id: u32) -> String {
+        format!(
+            "SELECT JSON_OBJECT('value', g.id, \
+             'display', LOWER(JSON_UNQUOTE(JSON_EXTRACT(g.data_orig, '$.displayName'))), \
+             'type', 'direct') AS value \
+             FROM {groups} g \
+             INNER JOIN {memberships} m ON g.id = m.group_id \
+             WHERE m.member_id = {users}.id AND m.member_type = 'User'",
+            groups = self.groups_table(tenant_id),
+            memberships = self.memberships_table(tenant_id),
+            users = self.users_table(tenant_id)
+        )
+    }
+
+    /// Create a filter converter for this tenant
+    fn filter_converter(
+        &self,
+        tenant_id: u32,
+    ) -> crate::backend::database::mysql::filter_impl::MySqlFilterConverter {
+        crate::backend::database::mysql::filter_impl::MySqlFilterConverter::new()
+            .with_elements_source("groups", self.groups_source(tenant_id))
+            .with_case_insensitive_external_id(
+                self.case_insensitive_external_ids.contains(&tenant_id),
+            )
+    }
+
+    /// One page of the users matching an optional filter, with the number of matches
+    async fn find_users_page(
+        &self,
+        tenant_id: u32,
+        filter: Option<&FilterOperator>,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        let (condition, params) = match filter {
+            Some(filter) => {
+                let (condition, params) = self
+                    .filter_converter(tenant_id)
+                    .to_where_clause(filter, ResourceType::User)?;
+                (Some(condition), params)
+            }
+            None => (None, Vec::new()),
+        };
+        self.find_users_where(
+            tenant_id,
+            condition.as_deref(),
+            &params,
+            start_index,
+            count,
+            sort_spec,
+            include_groups,
+        )
+        .await
+    }
+
+    /// One page of the users matching an optional SQL condition, with the number of matches
+    #[allow(clippy::too_many_arguments)]
+    async fn find_users_where(
+        &self,
+        tenant_id: u32,
+        condition: Option<&str>,
+        params: &[String],
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        let query = ListQuery::new(&self.users_table(tenant_id), condition);
+
+        let count_sql = query.count_sql();
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for param in params {
+            count_query = count_query.bind(param);
+        }
+        let total = count_query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count users: {}", e)))?
+            .0;
+
+        let (limit, offset) = page_bounds(start_index, count);
+        let sql = query.page_sql("id", &self.build_order_by(sort_spec), "?", "?");
+        let mut page_query = sqlx::query(&sql);
+        for param in params {
+            page_query = page_query.bind(param);
+        }
+        let rows = page_query
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch users: {}", e)))?;
+
+        let mut users = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            if let Some(user) = self
+                .fetch_user_with_groups_optional(tenant_id, &id, include_groups)
+                .await?
+            {
+                users.push(user);
+            }
+        }
+
+        Ok((users, total))
+    }
+}
+
+#[async_trait]
+impl UserReader for MySqlUserReader {
+    async fn find_user_by_id(
+        &self,
+        tenant_id: u32,
+        id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.fetch_user_with_groups_optional(tenant_id, id, include_groups)
+            .await
+    }
+
+    async fn find_user_by_username(
+        &self,
+        tenant_id: u32,
+        username: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        let table_name = self.users_table(tenant_id);
+        let sql = format!(
+            "SELECT id FROM {} WHERE LOWER(username) = LOWER(?)",
+            table_name
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find user by username: {}", e)))?;
+
+        match row {
+            Some(row) => {
+                let id: String = row.get("id");
+                self.fetch_user_with_groups_optional(tenant_id, &id, include_groups)
+                    .await
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        let table_name = self.users_table(tenant_id);
+        let (column, document, external_id) =
+            if self.case_insensitive_external_ids.contains(&tenant_id) {
+                (
+                    "LOWER(external_id)",
+                    "LOWER(JSON_UNQUOTE(JSON_EXTRACT(data_orig, '$.externalId')))",
+                    external_id.to_lowercase(),
+                )
+            } else {
+                (
+                    "external_id",
+                    "JSON_UNQUOTE(JSON_EXTRACT(data_orig, '$.externalId'))",
+                    external_id.to_string(),
+                )
+            };
+        // Rows the startup backfill left out of the column match by their document
+        let sql = format!(
+            "SELECT id FROM {} WHERE {} = ? OR (external_id IS NULL AND {} = ?)",
+            table_name, column, document
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(&external_id)
+            .bind(&external_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find user by externalId: {}", e)))?;
+
+        match row {
+            Some(row) => {
+                let id: String = row.get("id");
+                self.fetch_user_with_groups_optional(tenant_id, &id, include_groups)
+                    .await
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_all_users(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.find_users_page(tenant_id, None, start_index, count, None, include_groups)
+            .await
+    }
+
+    async fn find_all_users_sorted(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.find_users_page(
+            tenant_id,
+            None,
+            start_index,
+            count,
+            sort_spec,
+            include_groups,
+        )
+        .await
+    }
+
+    async fn find_users_by_filter(
+        &self,
+        tenant_id: u32,
+        filter: &FilterOperator,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.find_users_page(
+            tenant_id,
+            Some(filter),
+            start_index,
+            count,
+            sort_spec,
+            include_groups,
+        )
+        .await
+    }
+
+    async fn find_effective_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<Option<(Vec<User>, i64)>> {
+        let groups_table = self.groups_table(tenant_id);
+        let exists_sql = format!("SELECT COUNT(*) FROM {} WHERE id = ?", groups_table);
+        let groups: i64 = sqlx::query_scalar(&exists_sql)
+            .bind(group_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+        if groups == 0 {
+            return Ok(None);
+        }
+
+        let condition = effective_members_condition(
+            &groups_table,
+            &self.memberships_table(tenant_id),
+            "?",
+            max_depth,
+        );
+        let sort_spec = SortSpec::new("userName".to_string(), SortOrder::Ascending);
+        self.find_users_where(
+            tenant_id,
+            Some(&condition),
+            &[group_id.to_string()],
+            start_index,
+            count,
+            Some(&sort_spec),
+            include_groups,
+        )
+        .await
+        .map(Some)
+    }
+}
@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::super::user_update::{uniqueness_conflict, PreparedUserUpdateData, UserUpdater};
+use super::super::DbTransaction;
+use crate::error::{AppError, AppResult};
+use crate::models::User;
+
+/// MySQL-specific implementation of UserUpdater
+///
+/// This handles MySQL's CHAR(36) ids and JSON stored as LONGTEXT
+/// for user update operations.
+#[derive(Default)]
+pub struct MySqlUserUpdater;
+
+impl MySqlUserUpdater {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl UserUpdater for MySqlUserUpdater {
+    async fn find_conflicting_attribute(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        username: &str,
+        external_id: Option<&str>,
+    ) -> AppResult<Option<&'static str>> {
+        let conn = tx.mysql()?;
+
+        let table_name = format!("t{}_users", tenant_id);
+
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(username) = LOWER(?) AND id != ?",
+            table_name
+        );
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(username)
+            .bind(id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate username: {}", e))
+            })?;
+        if count > 0 {
+            return Ok(Some("userName"));
+        }
+
+        if let Some(external_id) = external_id {
+            let sql = format!(
+                "SELECT COUNT(*) FROM {} WHERE external_id = ? AND id != ?",
+                table_name
+            );
+            let count: i64 = sqlx::query_scalar(&sql)
+                .bind(external_id)
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to check duplicate externalId: {}", e))
+                })?;
+            if count > 0 {
+                return Ok(Some("externalId"));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn execute_user_update(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        _id: &str,
+        data: PreparedUserUpdateData,
+    ) -> AppResult<Option<User>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.mysql()?;
+
+        // Build table name
+        let table_name = format!("t{}_users", tenant_id);
+
+        // Convert JSON Values to strings for MySQL LONGTEXT storage
+        let data_orig_str = json_value_to_string(&data.data_orig)?;
+        let data_norm_str = json_value_to_string(&data.data_norm)?;
+
+        // MySQL UPDATE SQL with positional parameters and version increment
+        let sql = format!(
+            "UPDATE {} SET username = ?, external_id = ?, data_orig = ?, data_norm = ?, version = version + 1, updated_at = ?, last_modified_by = ? WHERE id = ?",
+            table_name
+        );
+
+        let result = sqlx::query(&sql)
+            .bind(&data.username)
+            .bind(&data.external_id)
+            .bind(&data_orig_str) // MySQL uses LONGTEXT
+            .bind(&data_norm_str) // MySQL uses LONGTEXT
+            .bind(data.timestamp)
+            .bind(&actor)
+            .bind(&data.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(map_user_update_error)?;
+
+        if result.rows_affected() > 0 {
+            Ok(Some(data.user))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Convert a JSON Value to a string for MySQL LONGTEXT storage
+///
+/// This ensures consistent JSON serialization for MySQL databases.
+fn json_value_to_string(value: &Value) -> AppResult<String> {
+    serde_json::to_string(value).map_err(AppError::Serialization)
+}
+
+/// Map a unique violation that slipped past the pre-check (e.g. two
+/// concurrent renames) to the same 409 the pre-check returns
+fn map_user_update_error(e: sqlx::Error) -> AppError {
+    if let sqlx::Error::Database(ref db_err) = e {
+        if db_err.is_unique_violation() {
+            if db_err.message().contains("username") {
+                return uniqueness_conflict("userName");
+            } else if db_err.message().contains("external_id") {
+                return uniqueness_conflict("externalId");
+            }
+        }
+    }
+    map_database_error(e, "User")
+}
+
+/// Map MySQL-specific database errors to appropriate application errors
+pub fn map_database_error(e: sqlx::Error, resource_type: &str) -> AppError {
+    match e {
+        sqlx::Error::Database(db_err) => {
+            let error_message = db_err.message();
+
+            // Handle unique constraint violations
+            if error_message.contains("Duplicate entry") {
+                if error_message.contains("username") {
+                    return AppError::BadRequest(format!(
+                        "A {} with this username already exists",
+                        resource_type.to_lowercase()
+                    ));
+                } else if error_message.contains("external_id") {
+                    return AppError::BadRequest(format!(
+                        "A {} with this external ID already exists",
+                        resource_type.to_lowercase()
+                    ));
+                }
+                return AppError::BadRequest(format!("{} already exists", resource_type));
+            }
+
+            // Handle other database errors
+            AppError::Database(format!("Database error: {}", error_message))
+        }
+        _ => AppError::Database(format!(
+            "Failed to update {}: {}",
+            resource_type.to_lowercase(),
+            e
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_value_to_string() {
+        let value = serde_json::json!({"key": "value", "number": 42});
+        let result = json_value_to_string(&value).unwrap();
+        assert!(result.contains("key"));
+        assert!(result.contains("value"));
+        assert!(result.contains("42"));
+    }
+}
@@ -1,22 +1,34 @@
 use async_trait::async_trait;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 
-use super::super::config::DatabaseBackendConfig;
+use super::super::config::{
+    DatabaseBackendConfig, POSTGRES_INDEXED_GROUP_ATTRIBUTES, POSTGRES_INDEXED_USER_ATTRIBUTES,
+};
+use super::super::filter::is_indexable_attribute;
+use crate::backend::database::transaction::{self, DbTransaction};
 use crate::backend::database::{
-    PostgresGroupDeleter, PostgresGroupInserter, PostgresGroupReader, PostgresGroupUpdater,
-    PostgresUserDeleter, PostgresUserInserter, PostgresUserPatcher, PostgresUserReader,
-    PostgresUserUpdater, UnifiedGroupDeleteOps, UnifiedGroupInsertOps, UnifiedGroupReadOps,
-    UnifiedGroupUpdateOps, UnifiedUserDeleteOps, UnifiedUserInsertOps, UnifiedUserPatchOps,
-    UnifiedUserReadOps, UnifiedUserUpdateOps,
+    map_connection_error, PostgresGroupDeleter, PostgresGroupInserter, PostgresGroupMemberUpdater,
+    PostgresGroupReader, PostgresGroupUpdater, PostgresUserDeleter, PostgresUserInserter,
+    PostgresUserPatcher, PostgresUserReader, PostgresUserUpdater, UnifiedGroupDeleteOps,
+    UnifiedGroupInsertOps, UnifiedGroupMembersOps, UnifiedGroupReadOps, UnifiedGroupUpdateOps,
+    UnifiedUserDeleteOps, UnifiedUserInsertOps, UnifiedUserPatchOps, UnifiedUserReadOps,
+    UnifiedUserUpdateOps,
+};
+use crate::backend::{
+    Backend, GroupBackend, HistoryBackend, MembersAdded, MembersRemoved, Provenance,
+    ProvenanceBackend, UserBackend,
 };
-use crate::backend::{Backend, GroupBackend, UserBackend};
+use crate::config::CompatibilityConfig;
 use crate::error::{AppError, AppResult};
 use crate::models::ScimPatchOp;
 use crate::models::{Group, User};
 use crate::parser::filter_operator::FilterOperator;
-use crate::parser::SortSpec;
+use crate::parser::{ResourceType, SortSpec};
 
 use super::filter_impl::PostgresFilterConverter;
 
@@ -38,21 +50,27 @@ pub struct PostgresBackend {
     group_update_ops: UnifiedGroupUpdateOps<PostgresGroupUpdater>,
     group_delete_ops: UnifiedGroupDeleteOps<PostgresGroupDeleter>,
     group_read_ops: UnifiedGroupReadOps<PostgresGroupReader>,
+    group_members_ops: UnifiedGroupMembersOps<PostgresGroupMemberUpdater>,
+    /// See `DatabaseBackendConfig::external_id_case_insensitive_tenants`
+    case_insensitive_external_ids: Arc<HashSet<u32>>,
+    /// User and Group attributes that get an expression index per tenant
+    indexed_attributes: (Vec<String>, Vec<String>),
 }
 
 impl PostgresBackend {
     /// Create a new PostgreSQL backend instance
     pub fn new(pool: PgPool) -> Self {
         // Create database-specific adapters
-        let user_inserter = PostgresUserInserter::new(pool.clone());
-        let user_updater = PostgresUserUpdater::new(pool.clone());
-        let user_deleter = PostgresUserDeleter::new(pool.clone());
-        let user_patcher = PostgresUserPatcher::new(pool.clone());
+        let user_inserter = PostgresUserInserter::new();
+        let user_updater = PostgresUserUpdater::new();
+        let user_deleter = PostgresUserDeleter::new();
+        let user_patcher = PostgresUserPatcher::new();
         let user_reader = PostgresUserReader::new(pool.clone());
-        let group_inserter = PostgresGroupInserter::new(pool.clone());
-        let group_updater = PostgresGroupUpdater::new(pool.clone());
-        let group_deleter = PostgresGroupDeleter::new(pool.clone());
+        let group_inserter = PostgresGroupInserter::new();
+        let group_updater = PostgresGroupUpdater::new();
+        let group_deleter = PostgresGroupDeleter::new();
         let group_reader = PostgresGroupReader::new(pool.clone());
+        let group_member_updater = PostgresGroupMemberUpdater::new();
 
         Self {
             pool,
@@ -67,7 +85,75 @@ impl PostgresBackend {
             group_update_ops: UnifiedGroupUpdateOps::new(group_updater),
             group_delete_ops: UnifiedGroupDeleteOps::new(group_deleter),
             group_read_ops: UnifiedGroupReadOps::new(group_reader),
+            group_members_ops: UnifiedGroupMembersOps::new(group_member_updater),
+            case_insensitive_external_ids: Arc::default(),
+            indexed_attributes: (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// Index the given User and Group attributes when a tenant is initialized
+    pub fn with_indexed_attributes(mut self, users: Vec<String>, groups: Vec<String>) -> Self {
+        self.indexed_attributes = (users, groups);
+        self
+    }
+
+    /// Match externalId case-insensitively in the given tenants
+    /// (`external_id_case_insensitive`)
+    pub fn with_case_insensitive_external_ids(mut self, tenants: HashSet<u32>) -> Self {
+        let tenants = Arc::new(tenants);
+        self.case_insensitive_external_ids = tenants.clone();
+        self.user_read_ops = UnifiedUserReadOps::new(
+            PostgresUserReader::new(self.pool.clone())
+                .with_case_insensitive_external_ids(tenants.clone()),
+        );
+        self.group_read_ops = UnifiedGroupReadOps::new(
+            PostgresGroupReader::new(self.pool.clone()).with_case_insensitive_external_ids(tenants),
+        );
+        self
+    }
+
+    /// Fail a write in a tenant matching externalId case-insensitively when
+    /// another resource holds the same externalId in a different case
+    ///
+    /// Runs on the write's transaction after the write, so failing rolls it
+    /// back. Exact duplicates are already rejected by the unique column.
+    async fn check_external_id_case(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        id: &str,
+        external_id: Option<&str>,
+    ) -> AppResult<()> {
+        let Some(external_id) = external_id else {
+            return Ok(());
+        };
+        if !self.case_insensitive_external_ids.contains(&tenant_id) {
+            return Ok(());
         }
+        let (table, holder) = match resource_type {
+            ResourceType::User => (format!("t{}_users", tenant_id), "user"),
+            ResourceType::Group => (format!("t{}_groups", tenant_id), "group"),
+        };
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(external_id) = LOWER($1) AND id <> $2",
+            table
+        );
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(external_id)
+            .bind(id)
+            .fetch_one(&mut *tx.postgres()?)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate externalId: {}", e))
+            })?;
+        if count > 0 {
+            return Err(AppError::Conflict(format!(
+                "externalId is already in use by another {}",
+                holder
+            )));
+        }
+        Ok(())
     }
 
     /// Get the connection pool reference
@@ -108,6 +194,44 @@ impl PostgresBackend {
     }
 }
 
+/// Attribute names from a comma-separated backend option
+///
+/// Checked at startup: names end up in index DDL, and an index on an
+/// attribute that filters reject or match element by element would never be
+/// used.
+fn indexed_attributes(
+    config: &DatabaseBackendConfig,
+    key: &str,
+    resource_type: ResourceType,
+) -> AppResult<Vec<String>> {
+    let Some(value) = config.get_option(key) else {
+        return Ok(Vec::new());
+    };
+    let converter = PostgresFilterConverter::new();
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|attr| !attr.is_empty())
+        .map(|attr| {
+            let plain = attr.split('.').all(|part| {
+                part.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+            if plain
+                && is_indexable_attribute(attr, resource_type)
+                && converter.index_expression(attr, resource_type).is_some()
+            {
+                Ok(attr.to_string())
+            } else {
+                Err(AppError::Configuration(format!(
+                    "Cannot index {} attribute {}: only simple, single-valued schema attributes can be indexed",
+                    resource_type, attr
+                )))
+            }
+        })
+        .collect()
+}
+
 #[async_trait]
 impl Backend for PostgresBackend {
     async fn connect(config: &DatabaseBackendConfig) -> AppResult<Self> {
@@ -124,27 +248,113 @@ impl Backend for PostgresBackend {
             .await
             .map_err(|e| AppError::Database(format!("Failed to connect to PostgreSQL: {}", e)))?;
 
-        Ok(Self::new(pool))
+        Ok(Self::new(pool)
+            .with_indexed_attributes(
+                indexed_attributes(config, POSTGRES_INDEXED_USER_ATTRIBUTES, ResourceType::User)?,
+                indexed_attributes(
+                    config,
+                    POSTGRES_INDEXED_GROUP_ATTRIBUTES,
+                    ResourceType::Group,
+                )?,
+            )
+            .with_case_insensitive_external_ids(
+                config.external_id_case_insensitive_tenants.clone(),
+            ))
     }
 
     async fn health_check(&self) -> AppResult<()> {
         sqlx::query("SELECT 1")
             .fetch_one(&self.pool)
             .await
-            .map_err(|e| AppError::Database(format!("Health check failed: {}", e)))?;
+            .map_err(|e| {
+                map_connection_error(AppError::Database(format!("Health check failed: {}", e)))
+            })?;
 
         Ok(())
     }
 
+    async fn begin_transaction(&self) -> AppResult<DbTransaction> {
+        let tx = transaction::begin(&self.pool)
+            .await
+            .map_err(map_connection_error)?;
+        Ok(DbTransaction::from(tx))
+    }
+
     async fn init_tenant(&self, tenant_id: u32) -> AppResult<()> {
-        super::schema::init_tenant_schema(&self.pool, tenant_id).await
+        let (users, groups) = &self.indexed_attributes;
+        super::schema::init_tenant_schema(&self.pool, tenant_id, users, groups).await
+    }
+
+    async fn list_initialized_tenants(&self) -> AppResult<Vec<u32>> {
+        super::schema::list_tenant_schemas(&self.pool)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn vacuum_tenant(&self, tenant_id: u32) -> AppResult<()> {
+        super::schema::vacuum_tenant_schema(&self.pool, tenant_id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn load_sync_stats(&self, tenant_id: u32) -> AppResult<Option<serde_json::Value>> {
+        super::schema::load_sync_stats(&self.pool, tenant_id)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn save_sync_stats(&self, tenant_id: u32, snapshot: &serde_json::Value) -> AppResult<()> {
+        super::schema::save_sync_stats(&self.pool, tenant_id, snapshot)
+            .await
+            .map_err(map_connection_error)
     }
 }
 
 #[async_trait]
 impl UserBackend for PostgresBackend {
-    async fn create_user(&self, tenant_id: u32, user: &User) -> AppResult<User> {
-        self.user_insert_ops.create_user(tenant_id, user).await
+    async fn create_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        user: &User,
+    ) -> AppResult<User> {
+        let user = self
+            .user_insert_ops
+            .create_user(tx, tenant_id, user)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::User,
+            user.id().as_deref().unwrap_or_default(),
+            user.external_id.as_deref(),
+        )
+        .await?;
+        Ok(user)
+    }
+
+    async fn create_user_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<User> {
+        let user = self
+            .user_insert_ops
+            .create_user_with_id(tx, tenant_id, id, user)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::User,
+            user.id().as_deref().unwrap_or_default(),
+            user.external_id.as_deref(),
+        )
+        .await?;
+        Ok(user)
     }
 
     async fn find_user_by_id(
@@ -156,6 +366,7 @@ impl UserBackend for PostgresBackend {
         self.user_read_ops
             .find_user_by_id(tenant_id, id, include_groups)
             .await
+            .map_err(map_connection_error)
     }
 
     async fn find_user_by_username(
@@ -167,6 +378,19 @@ impl UserBackend for PostgresBackend {
         self.user_read_ops
             .find_user_by_username(tenant_id, username, include_groups)
             .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.user_read_ops
+            .find_user_by_external_id(tenant_id, external_id, include_groups)
+            .await
+            .map_err(map_connection_error)
     }
 
     async fn find_all_users(
@@ -179,6 +403,7 @@ impl UserBackend for PostgresBackend {
         self.user_read_ops
             .find_all_users(tenant_id, start_index, count, include_groups)
             .await
+            .map_err(map_connection_error)
     }
 
     async fn find_all_users_sorted(
@@ -192,6 +417,7 @@ impl UserBackend for PostgresBackend {
         self.user_read_ops
             .find_all_users_sorted(tenant_id, start_index, count, sort_spec, include_groups)
             .await
+            .map_err(map_connection_error)
     }
 
     async fn find_users_by_filter(
@@ -213,72 +439,128 @@ impl UserBackend for PostgresBackend {
                 include_groups,
             )
             .await
+            .map_err(map_connection_error)
     }
 
-    async fn update_user(&self, tenant_id: u32, id: &str, user: &User) -> AppResult<Option<User>> {
-        // Perform the update using the unified operations
-        match self
+    async fn update_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<Option<User>> {
+        let updated = self
             .user_update_ops
-            .update_user(tenant_id, id, user)
-            .await?
-        {
-            Some(_) => {
-                // After successful update, fetch the user with groups populated
-                self.user_read_ops
-                    .find_user_by_id(tenant_id, id, true)
-                    .await
-            }
-            None => Ok(None),
+            .update_user(tx, tenant_id, id, user)
+            .await
+            .map_err(map_connection_error)?;
+        if let Some(user) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::User,
+                id,
+                user.external_id.as_deref(),
+            )
+            .await?;
         }
+        Ok(updated)
     }
 
-    async fn patch_user(
+    async fn patch_user_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         patch_ops: &ScimPatchOp,
-        compatibility: &crate::config::CompatibilityConfig,
+        compatibility: &CompatibilityConfig,
     ) -> AppResult<Option<User>> {
-        // Perform the patch using the unified operations
-        match self
+        // Read, patch and write the user on the same transaction
+        let patched = self
             .user_patch_ops
-            .patch_user(tenant_id, id, patch_ops, compatibility)
-            .await?
-        {
-            Some(_) => {
-                // After successful patch, fetch the user with groups populated
-                self.user_read_ops
-                    .find_user_by_id(tenant_id, id, true)
-                    .await
-            }
-            None => Ok(None),
+            .patch_user(tx, tenant_id, id, patch_ops, compatibility)
+            .await
+            .map_err(map_connection_error)?;
+        if let Some(user) = &patched {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::User,
+                id,
+                user.external_id.as_deref(),
+            )
+            .await?;
         }
+        Ok(patched)
     }
 
-    async fn delete_user(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
-        self.user_delete_ops.delete_user(tenant_id, id).await
-    }
-
-    async fn find_users_by_group_id(
+    async fn delete_user_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
-        group_id: &str,
-        include_groups: bool,
-    ) -> AppResult<Vec<User>> {
-        self.user_read_ops
-            .find_users_by_group_id(tenant_id, group_id, include_groups)
+        id: &str,
+    ) -> AppResult<bool> {
+        self.user_delete_ops
+            .delete_user(tx, tenant_id, id)
             .await
+            .map_err(map_connection_error)
     }
 }
 
 #[async_trait]
 impl GroupBackend for PostgresBackend {
-    async fn create_group(&self, tenant_id: u32, group: &Group) -> AppResult<Group> {
-        self.group_insert_ops.create_group(tenant_id, group).await
+    async fn create_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group: &Group,
+    ) -> AppResult<Group> {
+        // The group and its memberships are written together
+        let group = self
+            .group_insert_ops
+            .create_group(tx, tenant_id, group)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::Group,
+            group.id(),
+            group.external_id.as_deref(),
+        )
+        .await?;
+        Ok(group)
+    }
+
+    async fn create_group_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Group> {
+        // The group and its memberships are written together
+        let group = self
+            .group_insert_ops
+            .create_group_with_id(tx, tenant_id, id, group)
+            .await
+            .map_err(map_connection_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::Group,
+            group.id(),
+            group.external_id.as_deref(),
+        )
+        .await?;
+        Ok(group)
     }
 
     async fn find_group_by_id(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
-        self.group_read_ops.find_group_by_id(tenant_id, id).await
+        self.group_read_ops
+            .find_group_by_id(tenant_id, id)
+            .await
+            .map_err(map_connection_error)
     }
 
     async fn find_group_by_display_name(
@@ -289,6 +571,18 @@ impl GroupBackend for PostgresBackend {
         self.group_read_ops
             .find_group_by_display_name(tenant_id, display_name)
             .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.group_read_ops
+            .find_group_by_external_id(tenant_id, external_id)
+            .await
+            .map_err(map_connection_error)
     }
 
     async fn find_all_groups(
@@ -300,6 +594,7 @@ impl GroupBackend for PostgresBackend {
         self.group_read_ops
             .find_all_groups(tenant_id, start_index, count)
             .await
+            .map_err(map_connection_error)
     }
 
     async fn find_all_groups_sorted(
@@ -312,6 +607,7 @@ impl GroupBackend for PostgresBackend {
         self.group_read_ops
             .find_all_groups_sorted(tenant_id, start_index, count, sort_spec)
             .await
+            .map_err(map_connection_error)
     }
 
     async fn find_groups_by_filter(
@@ -325,46 +621,268 @@ impl GroupBackend for PostgresBackend {
         self.group_read_ops
             .find_groups_by_filter(tenant_id, filter, start_index, count, sort_spec)
             .await
+            .map_err(map_connection_error)
     }
 
-    async fn update_group(
+    async fn update_group_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         group: &Group,
     ) -> AppResult<Option<Group>> {
-        self.group_update_ops
-            .update_group(tenant_id, id, group)
+        let updated = self
+            .group_update_ops
+            .update_group(tx, tenant_id, id, group)
             .await
+            .map_err(map_connection_error)?;
+        if let Some(group) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::Group,
+                id,
+                group.external_id.as_deref(),
+            )
+            .await?;
+        }
+        Ok(updated)
     }
 
-    async fn patch_group(
+    async fn patch_group_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
     ) -> AppResult<Option<Group>> {
-        // Perform the patch using the group read ops
-        match self
-            .group_read_ops
-            .patch_group(tenant_id, id, patch_ops)
-            .await?
-        {
-            Some(_) => {
-                // After successful patch, fetch the group with members populated
-                self.group_read_ops.find_group_by_id(tenant_id, id).await
-            }
-            None => Ok(None),
+        // Read, patch and write the group on the same transaction
+        let updated = self
+            .group_update_ops
+            .patch_group(tx, tenant_id, id, patch_ops, compatibility)
+            .await
+            .map_err(map_connection_error)?;
+        if let Some(group) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::Group,
+                id,
+                group.external_id.as_deref(),
+            )
+            .await?;
         }
+        Ok(updated)
+    }
+
+    async fn delete_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        self.group_delete_ops
+            .delete_group(tx, tenant_id, id)
+            .await
+            .map_err(map_connection_error)
     }
 
-    async fn delete_group(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
-        self.group_delete_ops.delete_group(tenant_id, id).await
+    async fn add_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersAdded>> {
+        self.group_members_ops
+            .add_members(tx, tenant_id, group_id, ids)
+            .await
+            .map_err(map_connection_error)
     }
 
-    async fn find_groups_by_user_id(&self, tenant_id: u32, user_id: &str) -> AppResult<Vec<Group>> {
-        self.group_read_ops
-            .find_groups_by_user_id(tenant_id, user_id)
+    async fn remove_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersRemoved>> {
+        self.group_members_ops
+            .remove_members(tx, tenant_id, group_id, ids)
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_group_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.group_update_ops
+            .find_group(tx, tenant_id, id)
             .await
+            .map_err(map_connection_error)
+    }
+
+    async fn find_group_ids_with_member_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        member_id: &str,
+    ) -> AppResult<Vec<String>> {
+        // Members are stored by UUID, so nothing else can be a member
+        let Ok(member_uuid) = uuid::Uuid::parse_str(member_id) else {
+            return Ok(Vec::new());
+        };
+        let sql = format!(
+            "SELECT group_id::text FROM {} WHERE member_id = $1 ORDER BY group_id",
+            self.memberships_table(tenant_id)
+        );
+        sqlx::query_scalar(&sql)
+            .bind(member_uuid)
+            .fetch_all(tx.postgres()?)
+            .await
+            .map_err(|e| {
+                map_connection_error(AppError::Database(format!(
+                    "Failed to find member's groups: {}",
+                    e
+                )))
+            })
+    }
+
+    async fn find_effective_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<Option<(Vec<User>, i64)>> {
+        self.user_read_ops
+            .find_effective_members(
+                tenant_id,
+                group_id,
+                max_depth,
+                start_index,
+                count,
+                include_groups,
+            )
+            .await
+            .map_err(map_connection_error)
+    }
+
+    async fn count_group_members(
+        &self,
+        tenant_id: u32,
+        group_ids: &[String],
+    ) -> AppResult<HashMap<String, u64>> {
+        if group_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        // Group ids in responses come from the table, so they are valid UUIDs
+        let sql = format!(
+            "SELECT group_id::text, COUNT(*) FROM {} WHERE group_id = ANY($1::uuid[]) \
+             GROUP BY group_id",
+            self.memberships_table(tenant_id)
+        );
+        let rows = sqlx::query_as::<_, (String, i64)>(&sql)
+            .bind(group_ids)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                map_connection_error(AppError::Database(format!(
+                    "Failed to count group members: {}",
+                    e
+                )))
+            })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(group_id, count)| (group_id, count as u64))
+            .collect())
+    }
+}
+
+/// Writes are not logged, so there is no history
+impl HistoryBackend for PostgresBackend {}
+
+#[async_trait]
+impl ProvenanceBackend for PostgresBackend {
+    async fn find_provenance(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        ids: &[String],
+    ) -> AppResult<HashMap<String, Provenance>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let table = match resource_type {
+            ResourceType::User => self.users_table(tenant_id),
+            ResourceType::Group => self.groups_table(tenant_id),
+        };
+        let sql = format!(
+            "SELECT id::text, created_by, last_modified_by FROM {} WHERE id::text = ANY($1)",
+            table
+        );
+        let rows = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(&sql)
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                map_connection_error(AppError::Database(format!(
+                    "Failed to fetch provenance: {}",
+                    e
+                )))
+            })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, created_by, last_modified_by)| {
+                (
+                    id,
+                    Provenance {
+                        created_by,
+                        last_modified_by,
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str, resource_type: ResourceType) -> AppResult<Vec<String>> {
+        let config = DatabaseBackendConfig::postgres("postgresql://localhost/scim".to_string())
+            .with_option(
+                POSTGRES_INDEXED_USER_ATTRIBUTES.to_string(),
+                value.to_string(),
+            );
+        indexed_attributes(&config, POSTGRES_INDEXED_USER_ATTRIBUTES, resource_type)
+    }
+
+    #[test]
+    fn test_indexed_attributes_must_be_filterable() {
+        assert_eq!(
+            parse(" costCenter, name.familyName ,", ResourceType::User).unwrap(),
+            vec!["costCenter", "name.familyName"]
+        );
+
+        // Unknown to the schema registry, so filters on it are rejected
+        assert!(parse("badgeNumber", ResourceType::User).is_err());
+        // Multi-valued or complex
+        assert!(parse("emails", ResourceType::User).is_err());
+        assert!(parse("emails.value", ResourceType::User).is_err());
+        assert!(parse("name", ResourceType::User).is_err());
+        // Not a Group attribute
+        assert!(parse("costCenter", ResourceType::Group).is_err());
+        // Never reaches the DDL
+        assert!(parse("userName')); DROP TABLE t1_users; --", ResourceType::User).is_err());
     }
 }
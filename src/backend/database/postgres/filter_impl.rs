@@ -1,4 +1,7 @@
-use super::super::filter::FilterConverter;
+use super::super::filter::{
+    build_comparison_sql, locale_filter, meta_filter, validate_filter_attributes, FilterConverter,
+    MetaFilter, StringMatch,
+};
 use crate::error::AppResult;
 use crate::parser::filter_operator::FilterOperator;
 use crate::parser::ResourceType;
@@ -9,11 +12,51 @@ use serde_json::Value;
 ///
 /// This handles conversion of SCIM filter expressions to PostgreSQL
 /// JSONB queries with proper parameter binding and SQL injection prevention.
-pub struct PostgresFilterConverter;
+pub struct PostgresFilterConverter {
+    /// Multi-valued attribute kept outside data_norm and the rows standing in
+    /// for it, see `with_elements_source`
+    external_elements: Option<(&'static str, String)>,
+    /// Match externalId case-insensitively, see `with_case_insensitive_external_id`
+    external_id_case_insensitive: bool,
+}
 
 impl PostgresFilterConverter {
     pub fn new() -> Self {
-        Self
+        Self {
+            external_elements: None,
+            external_id_case_insensitive: false,
+        }
+    }
+
+    /// Evaluate filters on `attr` against the given query instead of data_norm
+    ///
+    /// Group members and user groups are stored in the memberships table, so
+    /// the query must yield one JSONB object per element of the current row
+    /// in an `elem` column, normalized like data_norm.
+    pub fn with_elements_source(mut self, attr: &'static str, source: String) -> Self {
+        self.external_elements = Some((attr, source));
+        self
+    }
+
+    /// Compare externalId ignoring case, for tenants with
+    /// `external_id_case_insensitive`
+    ///
+    /// The column holds the value as stored, so every operator compares it
+    /// lowercased with the lowercased filter value.
+    pub fn with_case_insensitive_external_id(mut self, enabled: bool) -> Self {
+        self.external_id_case_insensitive = enabled;
+        self
+    }
+
+    /// Row source with one `elem` per element of a multi-valued attribute
+    fn elements(&self, attr: &str) -> String {
+        let attr = attr.to_lowercase();
+        match &self.external_elements {
+            Some((name, source)) if name.eq_ignore_ascii_case(&attr) => {
+                format!("({}) members", source)
+            }
+            _ => format!("jsonb_array_elements(data_norm #> '{{{}}}') elem", attr),
+        }
     }
 }
 
@@ -29,6 +72,8 @@ impl FilterConverter for PostgresFilterConverter {
         filter: &FilterOperator,
         resource_type: ResourceType,
     ) -> AppResult<(String, Vec<String>)> {
+        // Attribute names become part of the SQL, so unknown ones never get this far
+        validate_filter_attributes(filter, resource_type)?;
         let mut params = Vec::new();
         let condition = self.convert_filter_to_sql(filter, resource_type, &mut params)?;
         Ok((condition, params))
@@ -38,6 +83,18 @@ impl FilterConverter for PostgresFilterConverter {
         format!("${}", index)
     }
 
+    fn get_not_equal(&self, left: &str, right: &str) -> String {
+        format!("{} IS DISTINCT FROM {}", left, right)
+    }
+
+    fn get_position_function(&self, haystack: &str, needle: &str) -> String {
+        format!("strpos({}, {}::text)", haystack, needle)
+    }
+
+    fn get_suffix_function(&self, expression: &str, suffix: &str) -> String {
+        format!("right({}, length({}::text))", expression, suffix)
+    }
+
     fn is_case_insensitive_attribute(&self, attr: &str, resource_type: ResourceType) -> bool {
         is_case_insensitive_attribute(attr, resource_type)
     }
@@ -77,21 +134,32 @@ impl PostgresFilterConverter {
         resource_type: ResourceType,
         params: &mut Vec<String>,
     ) -> AppResult<String> {
+        if let Some(meta) = meta_filter(filter, resource_type)? {
+            return Ok(self.meta_filter_to_sql(meta, params));
+        }
+        if let Some(alternatives) = locale_filter(filter, resource_type) {
+            let mut conditions = Vec::with_capacity(alternatives.len());
+            for alternative in &alternatives {
+                conditions.push(self.convert_filter_to_sql(alternative, resource_type, params)?);
+            }
+            return Ok(format!("({})", conditions.join(" OR ")));
+        }
+
         match filter {
             FilterOperator::Equal(attr, value) => {
-                self.handle_equality(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::Equal, value, resource_type, params)
             }
             FilterOperator::NotEqual(attr, value) => {
-                self.handle_not_equality(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::NotEqual, value, resource_type, params)
             }
             FilterOperator::Contains(attr, value) => {
-                self.handle_contains(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::Contains, value, resource_type, params)
             }
             FilterOperator::StartsWith(attr, value) => {
-                self.handle_starts_with(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::StartsWith, value, resource_type, params)
             }
             FilterOperator::EndsWith(attr, value) => {
-                self.handle_ends_with(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::EndsWith, value, resource_type, params)
             }
             FilterOperator::Present(attr) => self.handle_present(attr, resource_type),
             FilterOperator::GreaterThan(attr, value) => {
@@ -128,63 +196,48 @@ impl PostgresFilterConverter {
         }
     }
 
-    /// Handle equality comparison
-    fn handle_equality(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like "emails.value"
-        if attr.contains('.') {
-            let parts: Vec<&str> = attr.split('.').collect();
-            if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
-            {
-                return self.handle_multi_value_equality(parts[0], parts[1], value, params);
+    /// Render a filter on a `meta` sub-attribute
+    fn meta_filter_to_sql(&self, meta: MetaFilter, params: &mut Vec<String>) -> String {
+        match meta {
+            MetaFilter::Constant(true) => "1 = 1".to_string(),
+            MetaFilter::Constant(false) => "1 = 0".to_string(),
+            MetaFilter::Timestamp {
+                column,
+                operator,
+                value,
+            } => {
+                params.push(value);
+                format!(
+                    "date_trunc('milliseconds', {}) {} ${}::timestamptz",
+                    column,
+                    operator,
+                    params.len()
+                )
+            }
+            MetaFilter::Location {
+                endpoint,
+                operator,
+                value,
+            } => {
+                let path = format!("('{}' || id::text)", endpoint);
+                build_comparison_sql(
+                    self,
+                    &path,
+                    operator,
+                    &Value::String(value),
+                    true,
+                    params.len() + 1,
+                )
+                .bind(params)
             }
         }
-
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-
-        // Handle Boolean values specially
-        if let Value::Bool(bool_val) = value {
-            // For Boolean values, we compare with the JSON boolean representation
-            return Ok(format!(
-                "data_norm #> '{{{}}}' = '{}'",
-                json_path,
-                if *bool_val { "true" } else { "false" }
-            ));
-        }
-
-        // Check if this is a case-exact field
-        let is_case_exact = self.is_case_exact_field(attr, resource_type);
-        let data_column = if is_case_exact {
-            "data_orig"
-        } else {
-            "data_norm"
-        };
-
-        let value_str = self.value_to_string(value);
-        // For data_norm column, normalize values; for data_orig, preserve case
-        let comparison_value = if is_case_exact || !value.is_string() {
-            value_str
-        } else {
-            value_str.to_lowercase()
-        };
-        params.push(comparison_value);
-
-        Ok(format!(
-            "{} #>> '{{{}}}' = ${}",
-            data_column, json_path, param_index
-        ))
     }
 
-    /// Handle not equality comparison
-    fn handle_not_equality(
+    /// Handle eq, ne, co, sw and ew
+    fn handle_comparison(
         &self,
         attr: &str,
+        operator: StringMatch,
         value: &Value,
         resource_type: ResourceType,
         params: &mut Vec<String>,
@@ -194,137 +247,143 @@ impl PostgresFilterConverter {
             let parts: Vec<&str> = attr.split('.').collect();
             if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
             {
-                return self.handle_multi_value_not_equality(parts[0], parts[1], value, params);
+                return Ok(self.handle_multi_value_comparison(
+                    parts[0],
+                    parts[1],
+                    resource_type,
+                    operator,
+                    value,
+                    params,
+                ));
             }
         }
 
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-
-        // Handle Boolean values specially
-        if let Value::Bool(bool_val) = value {
-            // For Boolean values, we compare with the JSON boolean representation
-            return Ok(format!(
-                "data_norm #> '{{{}}}' != '{}'",
-                json_path,
-                if *bool_val { "true" } else { "false" }
-            ));
-        }
-
-        // Check if this is a case-exact field
-        let is_case_exact = self.is_case_exact_field(attr, resource_type);
-        let data_column = if is_case_exact {
-            "data_orig"
-        } else {
-            "data_norm"
-        };
-
-        let value_str = self.value_to_string(value);
-        // For data_norm column, normalize values; for data_orig, preserve case
-        let comparison_value = if is_case_exact || !value.is_string() {
-            value_str
-        } else {
-            value_str.to_lowercase()
-        };
-        params.push(comparison_value);
-
-        Ok(format!(
-            "{} #>> '{{{}}}' != ${}",
-            data_column, json_path, param_index
-        ))
-    }
-
-    /// Handle contains comparison
-    fn handle_contains(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like \"emails.value\"
-        if attr.contains('.') {
-            let parts: Vec<&str> = attr.split('.').collect();
-            if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
-            {
-                return self.handle_multi_value_contains(parts[0], parts[1], value, params);
+        // externalId is kept in its own indexed, case-exact column,
+        // compared lowercased for tenants matching it case-insensitively.
+        // Rows left out of it by the startup backfill for duplicating another
+        // row's externalId keep it NULL and are matched by their document.
+        if attr.eq_ignore_ascii_case("externalId") && value.is_string() {
+            let case_exact = !self.external_id_case_insensitive;
+            let document = "data_orig #>> '{externalId}'";
+            let (column, document) = if case_exact {
+                ("external_id".to_string(), document.to_string())
+            } else {
+                (
+                    self.get_lower_function("external_id"),
+                    self.get_lower_function(document),
+                )
+            };
+            if operator == StringMatch::Equal {
+                let column_match = build_comparison_sql(
+                    self,
+                    &column,
+                    operator,
+                    value,
+                    case_exact,
+                    params.len() + 1,
+                )
+                .bind(params);
+                let document_match = build_comparison_sql(
+                    self,
+                    &document,
+                    operator,
+                    value,
+                    case_exact,
+                    params.len() + 1,
+                )
+                .bind(params);
+                return Ok(format!(
+                    "({} OR (external_id IS NULL AND {}))",
+                    column_match, document_match
+                ));
+            }
+            if !case_exact {
+                return Ok(build_comparison_sql(
+                    self,
+                    &document,
+                    operator,
+                    value,
+                    false,
+                    params.len() + 1,
+                )
+                .bind(params));
             }
         }
 
         let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}%", value_str));
-
-        Ok(format!(
-            "LOWER(data_norm #>> '{{{}}}') LIKE LOWER(${})",
-            json_path, param_index
-        ))
-    }
 
-    /// Handle starts with comparison
-    fn handle_starts_with(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like \"emails.value\"
-        if attr.contains('.') {
-            let parts: Vec<&str> = attr.split('.').collect();
-            if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
-            {
-                return self.handle_multi_value_starts_with(parts[0], parts[1], value, params);
-            }
+        // Booleans are compared with the JSON boolean representation
+        if let (Value::Bool(bool_val), StringMatch::Equal | StringMatch::NotEqual) =
+            (value, operator)
+        {
+            let extracted = format!("data_norm #> '{{{}}}'", json_path);
+            let literal = format!("'{}'", bool_val);
+            return Ok(if operator == StringMatch::Equal {
+                format!("{} = {}", extracted, literal)
+            } else {
+                self.get_not_equal(&extracted, &literal)
+            });
         }
 
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("{}%", value_str));
-
-        Ok(format!(
-            "LOWER(data_norm #>> '{{{}}}') LIKE LOWER(${})",
-            json_path, param_index
-        ))
+        // Case-exact attributes are compared with their original case
+        let case_exact = self.is_case_exact_field(attr, resource_type);
+        let data_column = if case_exact { "data_orig" } else { "data_norm" };
+        let attr_path = format!("{} #>> '{{{}}}'", data_column, json_path);
+
+        Ok(build_comparison_sql(
+            self,
+            &attr_path,
+            operator,
+            value,
+            case_exact,
+            params.len() + 1,
+        )
+        .bind(params))
     }
 
-    /// Handle ends with comparison
-    fn handle_ends_with(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like \"emails.value\"
+    /// Handle present comparison
+    ///
+    /// Null, empty strings, empty arrays and empty objects are not present
+    /// (RFC 7644 Section 3.4.2.2), so `emails pr` needs at least one element.
+    fn handle_present(&self, attr: &str, resource_type: ResourceType) -> AppResult<String> {
+        // Check if this is a multi-valued attribute query like "emails.value"
         if attr.contains('.') {
             let parts: Vec<&str> = attr.split('.').collect();
             if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
             {
-                return self.handle_multi_value_ends_with(parts[0], parts[1], value, params);
+                return Ok(self.multi_value_present(parts[0], parts[1]));
             }
         }
 
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}", value_str));
+        if matches!(&self.external_elements, Some((name, _)) if name.eq_ignore_ascii_case(attr)) {
+            return Ok(format!("EXISTS (SELECT 1 FROM {})", self.elements(attr)));
+        }
 
+        // A bare multi-valued attribute is checked as a whole array, not its first value
+        let json_path = if crate::schema::is_multi_valued_attribute(attr, resource_type) {
+            attr.to_lowercase()
+        } else {
+            self.scim_path_to_json_path(attr, resource_type)
+        };
         Ok(format!(
-            "LOWER(data_norm #>> '{{{}}}') LIKE LOWER(${})",
-            json_path, param_index
+            "CASE jsonb_typeof(data_norm #> '{{{path}}}') \
+             WHEN 'null' THEN false \
+             WHEN 'string' THEN data_norm #>> '{{{path}}}' != '' \
+             WHEN 'array' THEN jsonb_array_length(data_norm #> '{{{path}}}') > 0 \
+             WHEN 'object' THEN data_norm #> '{{{path}}}' != '{{}}'::jsonb \
+             ELSE jsonb_typeof(data_norm #> '{{{path}}}') IS NOT NULL END",
+            path = json_path
         ))
     }
 
-    /// Handle present comparison
-    fn handle_present(&self, attr: &str, resource_type: ResourceType) -> AppResult<String> {
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        Ok(format!(
-            "data_norm #>> '{{{}}}' IS NOT NULL AND data_norm #>> '{{{}}}' != ''",
-            json_path, json_path
-        ))
+    /// Check that any element of a multi-valued attribute has a non-empty sub-attribute
+    fn multi_value_present(&self, attr_name: &str, sub_attr: &str) -> String {
+        format!(
+            "EXISTS (SELECT 1 FROM {elements} \
+             WHERE elem ->> '{sub}' IS NOT NULL AND elem ->> '{sub}' != '')",
+            elements = self.elements(attr_name),
+            sub = sub_attr.to_lowercase()
+        )
     }
 
     /// Handle greater than comparison
@@ -501,6 +560,33 @@ impl PostgresFilterConverter {
         }
     }
 
+    /// Expression an equality filter on `attr` compares, for an expression index
+    ///
+    /// None for multi-valued attributes, which are matched element by element
+    /// and cannot use such an index. externalId is compared in its own column.
+    pub fn index_expression(&self, attr: &str, resource_type: ResourceType) -> Option<String> {
+        if attr
+            .split('.')
+            .next()
+            .is_some_and(|top| crate::schema::is_multi_valued_attribute(top, resource_type))
+        {
+            return None;
+        }
+        if attr.eq_ignore_ascii_case("externalId") {
+            return Some("external_id".to_string());
+        }
+        let data_column = if self.is_case_exact_field(attr, resource_type) {
+            "data_orig"
+        } else {
+            "data_norm"
+        };
+        Some(format!(
+            "{} #>> '{{{}}}'",
+            data_column,
+            self.scim_path_to_json_path(attr, resource_type)
+        ))
+    }
+
     /// Check if an attribute is case-exact (case-sensitive)
     fn is_case_exact_field(&self, attr: &str, resource_type: ResourceType) -> bool {
         crate::schema::normalization::is_case_exact_field_for_resource(attr, resource_type)
@@ -511,160 +597,142 @@ impl PostgresFilterConverter {
         &self,
         attr: &str,
         inner: &FilterOperator,
-        _resource_type: ResourceType,
+        resource_type: ResourceType,
         params: &mut Vec<String>,
     ) -> AppResult<String> {
-        // For complex filters, we need to check if any element in the array matches
-        // the inner condition. This is similar to multi-valued attribute handling
-        // but with recursive filter processing.
+        // Every condition inside the brackets must hold for the same array element,
+        // so the whole inner filter is evaluated per element of one jsonb_array_elements
+        let condition = self.element_condition(attr, inner, resource_type, params)?;
+        Ok(format!(
+            "EXISTS (SELECT 1 FROM {} WHERE {})",
+            self.elements(attr),
+            condition
+        ))
+    }
 
-        match inner {
-            FilterOperator::Equal(sub_attr, value) => {
-                self.handle_multi_value_equality(attr, sub_attr, value, params)
+    /// Convert a value path filter to a condition on the current array element
+    fn element_condition(
+        &self,
+        attr: &str,
+        filter: &FilterOperator,
+        resource_type: ResourceType,
+        params: &mut Vec<String>,
+    ) -> AppResult<String> {
+        match filter {
+            FilterOperator::Equal(sub_attr, value)
+            | FilterOperator::NotEqual(sub_attr, value)
+            | FilterOperator::Contains(sub_attr, value)
+            | FilterOperator::StartsWith(sub_attr, value)
+            | FilterOperator::EndsWith(sub_attr, value) => {
+                let operator = StringMatch::of(filter).unwrap_or(StringMatch::Equal);
+                Ok(self.element_comparison(attr, sub_attr, resource_type, operator, value, params))
+            }
+            FilterOperator::GreaterThan(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, ">", value, params))
             }
-            FilterOperator::NotEqual(sub_attr, value) => {
-                self.handle_multi_value_not_equality(attr, sub_attr, value, params)
+            FilterOperator::GreaterThanOrEqual(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, ">=", value, params))
             }
-            FilterOperator::Contains(sub_attr, value) => {
-                self.handle_multi_value_contains(attr, sub_attr, value, params)
+            FilterOperator::LessThan(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, "<", value, params))
             }
-            FilterOperator::StartsWith(sub_attr, value) => {
-                self.handle_multi_value_starts_with(attr, sub_attr, value, params)
+            FilterOperator::LessThanOrEqual(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, "<=", value, params))
+            }
+            FilterOperator::Present(sub_attr) => Ok(format!(
+                "elem ->> '{sub}' IS NOT NULL AND elem ->> '{sub}' != ''",
+                sub = sub_attr.to_lowercase()
+            )),
+            FilterOperator::And(left, right) => {
+                let left_sql = self.element_condition(attr, left, resource_type, params)?;
+                let right_sql = self.element_condition(attr, right, resource_type, params)?;
+                Ok(format!("({} AND {})", left_sql, right_sql))
             }
-            FilterOperator::EndsWith(sub_attr, value) => {
-                self.handle_multi_value_ends_with(attr, sub_attr, value, params)
+            FilterOperator::Or(left, right) => {
+                let left_sql = self.element_condition(attr, left, resource_type, params)?;
+                let right_sql = self.element_condition(attr, right, resource_type, params)?;
+                Ok(format!("({} OR {})", left_sql, right_sql))
             }
-            FilterOperator::Present(sub_attr) => {
-                // For present check in arrays, check if any element has the sub-attribute
-                Ok(format!(
-                    "EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{{{}}}') elem WHERE elem ? '{}')",
-                    attr.to_lowercase(),
-                    sub_attr.to_lowercase()
-                ))
+            FilterOperator::Not(inner) => {
+                let inner_sql = self.element_condition(attr, inner, resource_type, params)?;
+                Ok(format!("NOT ({})", inner_sql))
             }
-            // For other operators like logical operators within complex filters,
-            // we would need more sophisticated handling
-            _ => Err(crate::error::AppError::FilterParse(format!(
-                "Unsupported complex filter operation for {}",
+            FilterOperator::Complex(_, _) => Err(crate::error::AppError::FilterParse(format!(
+                "Nested value paths are not supported in {}",
                 attr
             ))),
         }
     }
 
-    /// Handle multi-valued attribute equality (e.g., emails.value)
-    fn handle_multi_value_equality(
-        &self,
-        attr_name: &str,
-        sub_attr: &str,
-        value: &Value,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        let normalized_value = if value.is_string() {
-            value_str.to_lowercase()
-        } else {
-            value_str
-        };
-        params.push(normalized_value);
-
-        // Use PostgreSQL JSONB functions to search in array
-        // This creates a query like: EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{emails}') elem WHERE elem ->> 'value' = $1)
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{{{}}}') elem WHERE elem ->> '{}' = ${})",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
-    }
-
-    /// Handle multi-valued attribute not equality (e.g., emails.value ne)
-    fn handle_multi_value_not_equality(
-        &self,
-        attr_name: &str,
-        sub_attr: &str,
-        value: &Value,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        let normalized_value = if value.is_string() {
-            value_str.to_lowercase()
-        } else {
-            value_str
-        };
-        params.push(normalized_value);
-
-        // Use NOT EXISTS for not equality
-        Ok(format!(
-            "NOT EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{{{}}}') elem WHERE elem ->> '{}' = ${})",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
-    }
-
-    /// Handle multi-valued attribute contains (e.g., emails.value co)
-    fn handle_multi_value_contains(
+    /// Compare a sub-attribute of the current element
+    ///
+    /// `->>` renders JSON booleans as 'true'/'false', like filter values.
+    fn element_comparison(
         &self,
-        attr_name: &str,
+        attr: &str,
         sub_attr: &str,
+        resource_type: ResourceType,
+        operator: StringMatch,
         value: &Value,
         params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}%", value_str));
-
-        // Use PostgreSQL JSONB functions to search in array with LIKE
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{{{}}}') elem WHERE LOWER(elem ->> '{}') LIKE LOWER(${}))",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
+    ) -> String {
+        // data_norm keeps the original case of case-exact sub-attributes
+        // (e.g. x509Certificates.value)
+        let case_exact = self.is_case_exact_field(&format!("{}.{}", attr, sub_attr), resource_type);
+        build_comparison_sql(
+            self,
+            &format!("elem ->> '{}'", sub_attr.to_lowercase()),
+            operator,
+            value,
+            case_exact,
+            params.len() + 1,
+        )
+        .bind(params)
     }
 
-    /// Handle multi-valued attribute starts with (e.g., emails.value sw)
-    fn handle_multi_value_starts_with(
+    /// Order a sub-attribute of the current element numerically
+    fn element_ordering(
         &self,
-        attr_name: &str,
         sub_attr: &str,
+        operator: &str,
         value: &Value,
         params: &mut Vec<String>,
-    ) -> AppResult<String> {
+    ) -> String {
         let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("{}%", value_str));
-
-        // Use PostgreSQL JSONB functions to search in array with LIKE
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{{{}}}') elem WHERE LOWER(elem ->> '{}') LIKE LOWER(${}))",
-            attr_name.to_lowercase(),
+        params.push(self.value_to_string(value));
+        format!(
+            "(elem ->> '{}')::numeric {} ${}::numeric",
             sub_attr.to_lowercase(),
+            operator,
             param_index
-        ))
+        )
     }
 
-    /// Handle multi-valued attribute ends with (e.g., emails.value ew)
-    fn handle_multi_value_ends_with(
+    /// Handle a comparison on a sub-attribute of a multi-valued attribute
+    /// (e.g., emails.value co), which holds when any element matches
+    ///
+    /// `ne` holds when no element is equal.
+    fn handle_multi_value_comparison(
         &self,
         attr_name: &str,
         sub_attr: &str,
+        resource_type: ResourceType,
+        operator: StringMatch,
         value: &Value,
         params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}", value_str));
-
-        // Use PostgreSQL JSONB functions to search in array with LIKE
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{{{}}}') elem WHERE LOWER(elem ->> '{}') LIKE LOWER(${}))",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
+    ) -> String {
+        let (exists, operator) = match operator {
+            StringMatch::NotEqual => ("NOT EXISTS", StringMatch::Equal),
+            operator => ("EXISTS", operator),
+        };
+        let condition =
+            self.element_comparison(attr_name, sub_attr, resource_type, operator, value, params);
+        format!(
+            "{} (SELECT 1 FROM {} WHERE {})",
+            exists,
+            self.elements(attr_name),
+            condition
+        )
     }
 }
 
@@ -717,6 +785,36 @@ mod tests {
         assert_eq!(params, vec!["john.doe"]);
     }
 
+    #[test]
+    fn test_index_expression_matches_equality_filter() {
+        let converter = PostgresFilterConverter::new();
+        for (attr, resource_type) in [
+            ("costCenter", ResourceType::User),
+            ("name.familyName", ResourceType::User),
+        ] {
+            let filter = FilterOperator::Equal(attr.to_string(), Value::String("x".to_string()));
+            let (condition, _) = converter.to_where_clause(&filter, resource_type).unwrap();
+            let expression = converter.index_expression(attr, resource_type).unwrap();
+            assert_eq!(condition, format!("{} = $1", expression));
+        }
+
+        // externalId also matches rows left out of its column by their document
+        let filter =
+            FilterOperator::Equal("externalId".to_string(), Value::String("x".to_string()));
+        let (condition, _) = converter
+            .to_where_clause(&filter, ResourceType::Group)
+            .unwrap();
+        let expression = converter
+            .index_expression("externalId", ResourceType::Group)
+            .unwrap();
+        assert!(condition.starts_with(&format!("({} = $1 OR ", expression)));
+
+        assert_eq!(
+            converter.index_expression("emails.value", ResourceType::User),
+            None
+        );
+    }
+
     #[test]
     fn test_not_filter_conversion() {
         let converter = PostgresFilterConverter::new();
@@ -767,4 +865,71 @@ mod tests {
         assert_eq!(condition, "NOT (EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{emails}') elem WHERE elem ->> 'value' = $1))");
         assert_eq!(params, vec!["alice@example.com"]);
     }
+
+    #[test]
+    fn test_complex_filter_conditions_share_one_element() {
+        let converter = PostgresFilterConverter::new();
+        let inner_filter = FilterOperator::And(
+            Box::new(FilterOperator::Equal(
+                "type".to_string(),
+                Value::String("work".to_string()),
+            )),
+            Box::new(FilterOperator::Contains(
+                "value".to_string(),
+                Value::String("@corp.com".to_string()),
+            )),
+        );
+        let complex_filter = FilterOperator::Complex("emails".to_string(), Box::new(inner_filter));
+
+        let (condition, params) = converter
+            .to_where_clause(&complex_filter, ResourceType::User)
+            .unwrap();
+
+        assert_eq!(condition, "EXISTS (SELECT 1 FROM jsonb_array_elements(data_norm #> '{emails}') elem WHERE (elem ->> 'type' = $1 AND LOWER(elem ->> 'value') LIKE LOWER($2)))");
+        assert_eq!(params, vec!["work", "%@corp.com%"]);
+    }
+
+    #[test]
+    fn test_not_equal_filter_conversion() {
+        let converter = PostgresFilterConverter::new();
+        let filter = FilterOperator::NotEqual(
+            "userType".to_string(),
+            Value::String("Employee".to_string()),
+        );
+
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+
+        // Null-safe so users without userType also match
+        assert_eq!(condition, "data_norm #>> '{usertype}' IS DISTINCT FROM $1");
+        assert_eq!(params, vec!["employee"]);
+    }
+
+    #[test]
+    fn test_filter_uses_elements_source() {
+        let converter = PostgresFilterConverter::new()
+            .with_elements_source("members", "SELECT m.elem FROM m".to_string());
+        let filter = FilterOperator::Contains(
+            "members.display".to_string(),
+            Value::String("Alice".to_string()),
+        );
+
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::Group)
+            .unwrap();
+
+        assert_eq!(
+            condition,
+            "EXISTS (SELECT 1 FROM (SELECT m.elem FROM m) members WHERE LOWER(elem ->> 'display') LIKE LOWER($1))"
+        );
+        assert_eq!(params, vec!["%alice%"]);
+
+        // Other multi-valued attributes still read data_norm
+        let filter = FilterOperator::Present("emails.value".to_string());
+        let (condition, _) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+        assert!(condition.contains("data_norm"));
+    }
 }
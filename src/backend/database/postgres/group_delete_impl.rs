@@ -1,21 +1,20 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
 use uuid::Uuid;
 
 use super::super::group_delete::GroupDeleter;
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 
 /// PostgreSQL-specific implementation of GroupDeleter
 ///
-/// This handles PostgreSQL's UUID data types and transactional group deletion
-/// with cascading membership cleanup.
-pub struct PostgresGroupDeleter {
-    pool: PgPool,
-}
+/// This handles PostgreSQL's UUID data types and group deletion with
+/// cascading membership cleanup.
+#[derive(Default)]
+pub struct PostgresGroupDeleter;
 
 impl PostgresGroupDeleter {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Validate UUID format for PostgreSQL
@@ -27,16 +26,16 @@ impl PostgresGroupDeleter {
 
 #[async_trait]
 impl GroupDeleter for PostgresGroupDeleter {
-    async fn execute_group_delete(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
+    async fn execute_group_delete(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
         // Validate UUID format for PostgreSQL
         Self::validate_uuid_format(id)?;
 
-        // Begin transaction for atomic group + membership deletion
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))?;
+        let conn = tx.postgres()?;
 
         // First, delete group memberships where this group is the parent
         let membership_table = format!("t{}_group_memberships", tenant_id);
@@ -45,7 +44,7 @@ impl GroupDeleter for PostgresGroupDeleter {
 
         sqlx::query(&parent_membership_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to delete group parent memberships: {}", e))
@@ -59,7 +58,7 @@ impl GroupDeleter for PostgresGroupDeleter {
 
         sqlx::query(&child_membership_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to delete group child memberships: {}", e))
@@ -71,18 +70,11 @@ impl GroupDeleter for PostgresGroupDeleter {
 
         let result = sqlx::query(&group_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| AppError::Database(format!("Failed to delete group: {}", e)))?;
 
-        let group_deleted = result.rows_affected() > 0;
-
-        // Commit transaction
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
-
-        Ok(group_deleted)
+        Ok(result.rows_affected() > 0)
     }
 }
 
@@ -1,31 +1,27 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::PgConnection;
 
-use super::super::group_insert::{GroupInserter, PreparedGroupData};
-use super::super::group_read::GroupReader;
-use super::group_read_impl::PostgresGroupReader;
+use super::super::group_insert::{map_membership_insert_error, GroupInserter, PreparedGroupData};
+use super::super::DbTransaction;
+use super::group_update_impl::fetch_group_with_members;
 use crate::error::{AppError, AppResult};
 use crate::models::Group;
 
 /// PostgreSQL-specific implementation of GroupInserter
 ///
 /// This handles PostgreSQL's JSONB data types while using shared SQL generation.
-pub struct PostgresGroupInserter {
-    pool: PgPool,
-    group_reader: PostgresGroupReader,
-}
+#[derive(Default)]
+pub struct PostgresGroupInserter;
 
 impl PostgresGroupInserter {
-    pub fn new(pool: PgPool) -> Self {
-        Self {
-            group_reader: PostgresGroupReader::new(pool.clone()),
-            pool,
-        }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Check for case-insensitive duplicate displayName
     async fn check_duplicate_display_name(
         &self,
+        conn: &mut PgConnection,
         tenant_id: u32,
         display_name: &str,
     ) -> AppResult<()> {
@@ -37,7 +33,7 @@ impl PostgresGroupInserter {
 
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(display_name)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate displayName: {}", e))
@@ -57,24 +53,21 @@ impl PostgresGroupInserter {
 impl GroupInserter for PostgresGroupInserter {
     async fn execute_group_insert(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         data: PreparedGroupData,
     ) -> AppResult<Group> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.postgres()?;
+
         // Check for case-insensitive duplicate displayName before insertion
-        self.check_duplicate_display_name(tenant_id, &data.display_name)
+        self.check_duplicate_display_name(conn, tenant_id, &data.display_name)
             .await?;
 
-        // Begin transaction for atomic group + membership insertion
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))?;
-
         // Insert the group record
         let table_name = format!("t{}_groups", tenant_id);
         let group_sql = format!(
-            "INSERT INTO {} (id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at) VALUES ($1::uuid, $2, $3, $4, $5, $6, $7, $8)",
+            "INSERT INTO {} (id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at, created_by, last_modified_by) VALUES ($1::uuid, $2, $3, $4, $5, $6, $7, $8, $9, $9)",
             table_name
         );
 
@@ -87,7 +80,8 @@ impl GroupInserter for PostgresGroupInserter {
             .bind(1i64) // version = 1 for new records
             .bind(data.timestamp)
             .bind(data.timestamp)
-            .execute(&mut *tx)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| super::user_insert_impl::map_database_error(e, "Group"))?;
 
@@ -107,29 +101,15 @@ impl GroupInserter for PostgresGroupInserter {
                         .bind(&data.id)
                         .bind(member_id)
                         .bind(member_type)
-                        .execute(&mut *tx)
+                        .execute(&mut *conn)
                         .await
-                        .map_err(|e| {
-                            crate::error::AppError::Database(format!(
-                                "Failed to insert group member: {}",
-                                e
-                            ))
-                        })?;
+                        .map_err(|e| map_membership_insert_error(e, member_id))?;
                 }
             }
         }
 
-        // Commit transaction
-        tx.commit().await.map_err(|e| {
-            crate::error::AppError::Database(format!("Failed to commit transaction: {}", e))
-        })?;
-
         // Fetch the created group with properly populated members
-        match self
-            .group_reader
-            .find_group_by_id(tenant_id, &data.group.base.id)
-            .await?
-        {
+        match fetch_group_with_members(conn, tenant_id, &data.group.base.id).await? {
             Some(group) => Ok(group),
             None => Err(AppError::Database(
                 "Failed to fetch created group".to_string(),
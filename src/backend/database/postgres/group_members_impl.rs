@@ -0,0 +1,236 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::super::group_members::GroupMemberUpdater;
+use super::super::DbTransaction;
+use super::user_update_impl::map_database_error;
+use crate::backend::{MembersAdded, MembersRemoved};
+use crate::error::{AppError, AppResult};
+
+/// PostgreSQL-specific implementation of GroupMemberUpdater
+///
+/// Whole batches are passed as UUID arrays, so each step is a single
+/// statement regardless of the batch size.
+#[derive(Default)]
+pub struct PostgresGroupMemberUpdater;
+
+impl PostgresGroupMemberUpdater {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn group_exists(
+        conn: &mut PgConnection,
+        tenant_id: u32,
+        group_id: &Uuid,
+    ) -> AppResult<bool> {
+        let sql = format!("SELECT COUNT(*) FROM t{}_groups WHERE id = $1", tenant_id);
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(group_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+        Ok(count > 0)
+    }
+
+    /// Run a query returning ids over the `ids` array
+    ///
+    /// With a `group_id` it is bound as $1 and the array as $2; otherwise the
+    /// array is $1.
+    async fn existing_ids(
+        conn: &mut PgConnection,
+        sql: &str,
+        group_id: Option<&Uuid>,
+        ids: &[Uuid],
+    ) -> AppResult<HashSet<Uuid>> {
+        let mut query = sqlx::query_scalar::<_, Uuid>(sql);
+        if let Some(group_id) = group_id {
+            query = query.bind(group_id);
+        }
+        let found = query
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to look up members: {}", e)))?;
+        Ok(found.into_iter().collect())
+    }
+
+    /// Move meta.lastModified and the version forward once for the call
+    async fn touch_group(
+        conn: &mut PgConnection,
+        tenant_id: u32,
+        group_id: &Uuid,
+        timestamp: DateTime<Utc>,
+        actor: Option<&str>,
+    ) -> AppResult<()> {
+        let last_modified = crate::utils::format_scim_datetime(timestamp);
+        let sql = format!(
+            "UPDATE t{}_groups SET \
+             data_orig = jsonb_set(data_orig, '{{meta,lastModified}}', to_jsonb($1::text)), \
+             data_norm = jsonb_set(data_norm, '{{meta,lastmodified}}', to_jsonb($2::text)), \
+             version = version + 1, updated_at = $3, last_modified_by = $5 WHERE id = $4",
+            tenant_id
+        );
+        sqlx::query(&sql)
+            .bind(&last_modified)
+            .bind(last_modified.to_lowercase())
+            .bind(timestamp)
+            .bind(group_id)
+            .bind(actor)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| map_database_error(e, "Group"))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GroupMemberUpdater for PostgresGroupMemberUpdater {
+    async fn execute_members_add(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<MembersAdded>> {
+        let Ok(group_uuid) = Uuid::parse_str(group_id) else {
+            return Ok(None);
+        };
+
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.postgres()?;
+
+        if !Self::group_exists(conn, tenant_id, &group_uuid).await? {
+            return Ok(None);
+        }
+
+        // Ids that are not UUIDs cannot name any resource
+        let uuids: Vec<Uuid> = ids
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+
+        let memberships_table = format!("t{}_group_memberships", tenant_id);
+        let present = Self::existing_ids(
+            conn,
+            &format!(
+                "SELECT member_id FROM {} WHERE group_id = $1 AND member_id = ANY($2)",
+                memberships_table
+            ),
+            Some(&group_uuid),
+            &uuids,
+        )
+        .await?;
+        let users = Self::existing_ids(
+            conn,
+            &format!("SELECT id FROM t{}_users WHERE id = ANY($1)", tenant_id),
+            None,
+            &uuids,
+        )
+        .await?;
+        // A group cannot be a member of itself
+        let groups = Self::existing_ids(
+            conn,
+            &format!(
+                "SELECT id FROM t{}_groups WHERE id <> $1 AND id = ANY($2)",
+                tenant_id
+            ),
+            Some(&group_uuid),
+            &uuids,
+        )
+        .await?;
+
+        let mut outcome = MembersAdded::default();
+        let mut new_ids = Vec::new();
+        let mut new_types = Vec::new();
+        for id in ids {
+            match Uuid::parse_str(id) {
+                Ok(uuid) if present.contains(&uuid) => outcome.already_present += 1,
+                Ok(uuid) if users.contains(&uuid) => {
+                    new_ids.push(uuid);
+                    new_types.push("User");
+                }
+                Ok(uuid) if groups.contains(&uuid) => {
+                    new_ids.push(uuid);
+                    new_types.push("Group");
+                }
+                _ => outcome.not_found.push(id.clone()),
+            }
+        }
+
+        if !new_ids.is_empty() {
+            let sql = format!(
+                "INSERT INTO {} (group_id, member_id, member_type) \
+                 SELECT $1, member_id, member_type FROM UNNEST($2::uuid[], $3::text[]) AS m(member_id, member_type)",
+                memberships_table
+            );
+            sqlx::query(&sql)
+                .bind(group_uuid)
+                .bind(&new_ids)
+                .bind(&new_types)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to insert group memberships: {}", e))
+                })?;
+            outcome.added = new_ids.len();
+
+            Self::touch_group(conn, tenant_id, &group_uuid, timestamp, actor.as_deref()).await?;
+        }
+
+        Ok(Some(outcome))
+    }
+
+    async fn execute_members_remove(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<MembersRemoved>> {
+        let Ok(group_uuid) = Uuid::parse_str(group_id) else {
+            return Ok(None);
+        };
+
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.postgres()?;
+
+        if !Self::group_exists(conn, tenant_id, &group_uuid).await? {
+            return Ok(None);
+        }
+
+        let uuids: Vec<Uuid> = ids
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        let sql = format!(
+            "DELETE FROM t{}_group_memberships WHERE group_id = $1 AND member_id = ANY($2)",
+            tenant_id
+        );
+        let result = sqlx::query(&sql)
+            .bind(group_uuid)
+            .bind(&uuids)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to delete group memberships: {}", e))
+            })?;
+
+        let removed = result.rows_affected() as usize;
+        let outcome = MembersRemoved {
+            removed,
+            not_members: ids.len().saturating_sub(removed),
+        };
+
+        if removed > 0 {
+            Self::touch_group(conn, tenant_id, &group_uuid, timestamp, actor.as_deref()).await?;
+        }
+
+        Ok(Some(outcome))
+    }
+}
@@ -1,26 +1,28 @@
 use async_trait::async_trait;
-use sqlx::{PgPool, Row};
+use sqlx::{PgConnection, Row};
 
+use super::super::group_insert::map_membership_insert_error;
 use super::super::group_update::{GroupUpdater, PreparedGroupUpdateData};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::Group;
 
 /// PostgreSQL-specific implementation of GroupUpdater
 ///
 /// This handles PostgreSQL's UUID types, JSONB storage format,
-/// and transactional group membership management.
-pub struct PostgresGroupUpdater {
-    pool: PgPool,
-}
+/// and group membership management.
+#[derive(Default)]
+pub struct PostgresGroupUpdater;
 
 impl PostgresGroupUpdater {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Check for case-insensitive duplicate displayName excluding current group
     async fn check_duplicate_display_name(
         &self,
+        conn: &mut PgConnection,
         tenant_id: u32,
         display_name: &str,
         exclude_id: &str,
@@ -34,7 +36,7 @@ impl PostgresGroupUpdater {
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(display_name)
             .bind(exclude_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate displayName: {}", e))
@@ -46,134 +48,13 @@ impl PostgresGroupUpdater {
 
         Ok(())
     }
-
-    /// Helper function to fetch a group with its members
-    async fn fetch_group_with_members(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
-        // Return None for empty IDs
-        if id.is_empty() {
-            return Ok(None);
-        }
-
-        // Already validated UUID format in calling functions, but double-check
-        if uuid::Uuid::parse_str(id).is_err() {
-            return Ok(None);
-        }
-        let table_name = format!("t{}_groups", tenant_id);
-        let sql = format!(
-            "SELECT id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE id = $1::uuid",
-            table_name
-        );
-
-        let row = sqlx::query(&sql)
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
-
-        match row {
-            Some(row) => {
-                let mut group: Group = serde_json::from_value(row.get("data_orig"))
-                    .map_err(AppError::Serialization)?;
-
-                // Set version in meta (ensure meta exists)
-                let version: i64 = row.get("version");
-                if group.meta().is_none() {
-                    // Create meta if it doesn't exist
-                    let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
-                    let updated_at: chrono::DateTime<chrono::Utc> = row.get("updated_at");
-                    let meta = scim_v2::models::scim_schema::Meta {
-                        resource_type: Some("Group".to_string()),
-                        created: Some(crate::utils::format_scim_datetime(created_at)),
-                        last_modified: Some(crate::utils::format_scim_datetime(updated_at)),
-                        location: None,
-                        version: Some(format!("W/\"{}\"", version)),
-                    };
-                    *group.meta_mut() = Some(meta);
-                } else {
-                    // Update existing meta with version
-                    if let Some(ref mut meta) = group.meta_mut() {
-                        meta.version = Some(format!("W/\"{}\"", version));
-                    }
-                }
-
-                // Fetch members
-                let members = self.fetch_group_members(tenant_id, id).await?;
-                *group.members_mut() = if members.is_empty() {
-                    None
-                } else {
-                    Some(members)
-                };
-
-                Ok(Some(group))
-            }
-            None => Ok(None),
-        }
-    }
-
-    /// Helper function to fetch group members
-    async fn fetch_group_members(
-        &self,
-        tenant_id: u32,
-        group_id: &str,
-    ) -> AppResult<Vec<scim_v2::models::group::Member>> {
-        let users_table = format!("t{}_users", tenant_id);
-        let groups_table = format!("t{}_groups", tenant_id);
-        let memberships_table = format!("t{}_group_memberships", tenant_id);
-
-        let sql = format!(
-            r#"
-            SELECT 
-                m.member_id,
-                m.member_type,
-                CASE 
-                    WHEN m.member_type = 'User' THEN COALESCE(u.data_orig->>'displayName', u.data_orig->'name'->>'formatted', CONCAT(u.data_orig->'name'->>'givenName', ' ', u.data_orig->'name'->>'familyName'))
-                    WHEN m.member_type = 'Group' THEN g.data_orig->>'displayName'
-                END as display_name
-            FROM {} m
-            LEFT JOIN {} u ON m.member_id = u.id AND m.member_type = 'User'
-            LEFT JOIN {} g ON m.member_id = g.id AND m.member_type = 'Group'
-            WHERE m.group_id = $1::uuid
-            ORDER BY m.created_at
-            "#,
-            memberships_table, users_table, groups_table
-        );
-
-        let rows = sqlx::query(&sql)
-            .bind(group_id)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch group members: {}", e)))?;
-
-        let mut members = Vec::new();
-        for row in rows {
-            let member_id: uuid::Uuid = row.get("member_id");
-            let member_id_string = member_id.to_string();
-            let member_type: String = row.get("member_type");
-            let display_name: Option<String> = row.get("display_name");
-
-            // Construct the proper $ref path based on member type (base URL will be added later)
-            let ref_path = match member_type.as_str() {
-                "User" => format!("/{}/Users/{}", tenant_id, member_id_string),
-                "Group" => format!("/{}/Groups/{}", tenant_id, member_id_string),
-                _ => format!("/{}/Resources/{}", tenant_id, member_id_string),
-            };
-
-            members.push(scim_v2::models::group::Member {
-                value: Some(member_id_string),
-                ref_: Some(ref_path),
-                display: display_name,
-                type_: Some(member_type),
-            });
-        }
-
-        Ok(members)
-    }
 }
 
 #[async_trait]
 impl GroupUpdater for PostgresGroupUpdater {
     async fn execute_group_update(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         data: PreparedGroupUpdateData,
@@ -183,24 +64,20 @@ impl GroupUpdater for PostgresGroupUpdater {
             return Ok(None);
         }
 
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.postgres()?;
+
         // Check for case-insensitive duplicate displayName before update
-        self.check_duplicate_display_name(tenant_id, &data.display_name, &data.id)
+        self.check_duplicate_display_name(conn, tenant_id, &data.display_name, &data.id)
             .await?;
 
-        // Begin transaction for atomic group + membership update
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))?;
-
         // Build table names
         let groups_table = format!("t{}_groups", tenant_id);
         let memberships_table = format!("t{}_group_memberships", tenant_id);
 
         // Update the group record
         let group_sql = format!(
-            "UPDATE {} SET display_name = $1, external_id = $2, data_orig = $3, data_norm = $4, version = version + 1, updated_at = $5 WHERE id = $6::uuid",
+            "UPDATE {} SET display_name = $1, external_id = $2, data_orig = $3, data_norm = $4, version = version + 1, updated_at = $5, last_modified_by = $7 WHERE id = $6::uuid",
             groups_table
         );
 
@@ -211,7 +88,8 @@ impl GroupUpdater for PostgresGroupUpdater {
             .bind(&data.data_norm) // PostgreSQL uses JSONB
             .bind(data.timestamp)
             .bind(&data.id)
-            .execute(&mut *tx)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| super::user_update_impl::map_database_error(e, "Group"))?;
 
@@ -228,7 +106,7 @@ impl GroupUpdater for PostgresGroupUpdater {
 
         sqlx::query(&delete_members_sql)
             .bind(&data.id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to delete group memberships: {}", e))
@@ -249,38 +127,156 @@ impl GroupUpdater for PostgresGroupUpdater {
                         .bind(&data.id)
                         .bind(member_id)
                         .bind(member_type)
-                        .execute(&mut *tx)
+                        .execute(&mut *conn)
                         .await
-                        .map_err(|e| {
-                            AppError::Database(format!("Failed to insert group member: {}", e))
-                        })?;
+                        .map_err(|e| map_membership_insert_error(e, member_id))?;
                 }
             }
         }
 
-        // Commit transaction
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
-
         // Fetch the updated group with properly populated members
-        self.fetch_group_with_members(tenant_id, &data.id).await
+        fetch_group_with_members(conn, tenant_id, &data.id).await
+    }
+
+    async fn find_group_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        fetch_group_with_members(tx.postgres()?, tenant_id, id).await
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Fetch a group with its members on the given connection
+pub(super) async fn fetch_group_with_members(
+    conn: &mut PgConnection,
+    tenant_id: u32,
+    id: &str,
+) -> AppResult<Option<Group>> {
+    // Return None for empty IDs
+    if id.is_empty() {
+        return Ok(None);
+    }
 
-    #[allow(dead_code)]
-    async fn create_test_pool() -> PgPool {
-        // Note: This would need a real PostgreSQL instance for integration tests
-        // For unit tests, we just verify the updater can be created
-        PgPool::connect("postgresql://localhost/test")
-            .await
-            .unwrap()
+    // Already validated UUID format in calling functions, but double-check
+    if uuid::Uuid::parse_str(id).is_err() {
+        return Ok(None);
+    }
+    let table_name = format!("t{}_groups", tenant_id);
+    let sql = format!(
+        "SELECT id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE id = $1::uuid",
+        table_name
+    );
+
+    let row = sqlx::query(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+
+    match row {
+        Some(row) => {
+            let mut group: Group =
+                serde_json::from_value(row.get("data_orig")).map_err(AppError::Serialization)?;
+
+            // Set version in meta (ensure meta exists)
+            let version: i64 = row.get("version");
+            if group.meta().is_none() {
+                // Create meta if it doesn't exist
+                let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
+                let updated_at: chrono::DateTime<chrono::Utc> = row.get("updated_at");
+                let meta = scim_v2::models::scim_schema::Meta {
+                    resource_type: Some(crate::parser::ResourceType::Group.scim_name().to_string()),
+                    created: Some(crate::utils::format_scim_datetime(created_at)),
+                    last_modified: Some(crate::utils::format_scim_datetime(updated_at)),
+                    location: None,
+                    version: Some(format!("W/\"{}\"", version)),
+                };
+                *group.meta_mut() = Some(meta);
+            } else {
+                // Update existing meta with version
+                if let Some(ref mut meta) = group.meta_mut() {
+                    meta.version = Some(format!("W/\"{}\"", version));
+                }
+            }
+
+            // Fetch members
+            let members = fetch_group_members(conn, tenant_id, id).await?;
+            *group.members_mut() = if members.is_empty() {
+                None
+            } else {
+                Some(members)
+            };
+
+            Ok(Some(group))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetch the members of a group on the given connection
+async fn fetch_group_members(
+    conn: &mut PgConnection,
+    tenant_id: u32,
+    group_id: &str,
+) -> AppResult<Vec<scim_v2::models::group::Member>> {
+    let users_table = format!("t{}_users", tenant_id);
+    let groups_table = format!("t{}_groups", tenant_id);
+    let memberships_table = format!("t{}_group_memberships", tenant_id);
+
+    let sql = format!(
+        r#"
+        SELECT 
+            m.member_id,
+            m.member_type,
+            CASE 
+                WHEN m.member_type = 'User' THEN COALESCE(u.data_orig->>'displayName', u.data_orig->'name'->>'formatted', CONCAT(u.data_orig->'name'->>'givenName', ' ', u.data_orig->'name'->>'familyName'))
+                WHEN m.member_type = 'Group' THEN g.data_orig->>'displayName'
+            END as display_name
+        FROM {} m
+        LEFT JOIN {} u ON m.member_id = u.id AND m.member_type = 'User'
+        LEFT JOIN {} g ON m.member_id = g.id AND m.member_type = 'Group'
+        WHERE m.group_id = $1::uuid
+        ORDER BY m.created_at
+        "#,
+        memberships_table, users_table, groups_table
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(group_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch group members: {}", e)))?;
+
+    let mut members = Vec::new();
+    for row in rows {
+        let member_id: uuid::Uuid = row.get("member_id");
+        let member_id_string = member_id.to_string();
+        let member_type: String = row.get("member_type");
+        let display_name: Option<String> = row.get("display_name");
+
+        // Tenant-relative $ref by member type; the resource handler resolves the base URL
+        let resource_type = match member_type.as_str() {
+            "User" => "Users",
+            "Group" => "Groups",
+            _ => "Resources",
+        };
+        let ref_path = crate::utils::build_tenant_ref(tenant_id, resource_type, &member_id_string);
+
+        members.push(scim_v2::models::group::Member {
+            value: Some(member_id_string),
+            ref_: Some(ref_path),
+            display: display_name,
+            type_: Some(member_type),
+        });
     }
 
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
     #[test]
     fn test_validate_uuid_format() {
         // Valid UUID
@@ -2,6 +2,7 @@ pub mod backend_impl;
 pub mod filter_impl;
 pub mod group_delete_impl;
 pub mod group_insert_impl;
+pub mod group_members_impl;
 pub mod group_read_impl;
 pub mod group_update_impl;
 pub mod schema;
@@ -14,6 +15,7 @@ pub mod user_update_impl;
 pub use backend_impl::PostgresBackend;
 pub use group_delete_impl::PostgresGroupDeleter;
 pub use group_insert_impl::PostgresGroupInserter;
+pub use group_members_impl::PostgresGroupMemberUpdater;
 pub use group_read_impl::PostgresGroupReader;
 pub use group_update_impl::PostgresGroupUpdater;
 pub use user_delete_impl::PostgresUserDeleter;
@@ -1,18 +1,58 @@
+use super::filter_impl::PostgresFilterConverter;
 use crate::error::{AppError, AppResult};
-use sqlx::PgPool;
+use crate::parser::ResourceType;
+use sha1::{Digest, Sha1};
+use sqlx::{PgConnection, PgPool};
+
+/// Key of the advisory lock held while tenant schemas are created
+///
+/// Every replica of a deployment initializes the tenants at boot. Concurrent
+/// `CREATE ... IF NOT EXISTS` statements for the same object can still fail on
+/// the catalog's unique indexes, so schema creation is serialized across
+/// connections. One key covers all tenants because the `uuid-ossp` extension
+/// is shared by them.
+const SCHEMA_LOCK_KEY: i64 = 0x7363_696d_5f73_6368; // "scim_sch"
 
 /// Initialize tenant-specific database schema for PostgreSQL
 ///
 /// This creates the necessary tables for a tenant including users, groups,
-/// and group memberships with proper indexes and constraints.
-pub async fn init_tenant_schema(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
+/// and group memberships with proper indexes and constraints, plus an
+/// expression index for each configured attribute. Everything runs in one
+/// transaction under [`SCHEMA_LOCK_KEY`], so concurrent calls from several
+/// instances wait for each other and a failed call leaves nothing half-built.
+pub async fn init_tenant_schema(
+    pool: &PgPool,
+    tenant_id: u32,
+    user_attributes: &[String],
+    group_attributes: &[String],
+) -> AppResult<()> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to begin schema transaction: {}", e)))?;
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(SCHEMA_LOCK_KEY)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to lock schema creation: {}", e)))?;
+
+    create_tenant_tables(&mut tx, tenant_id).await?;
+    create_attribute_indexes(&mut tx, tenant_id, user_attributes, group_attributes).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to commit tenant schema: {}", e)))
+}
+
+/// Create the tenant's tables and their indexes if they do not exist yet
+async fn create_tenant_tables(conn: &mut PgConnection, tenant_id: u32) -> AppResult<()> {
     let users_table = format!("t{}_users", tenant_id);
     let groups_table = format!("t{}_groups", tenant_id);
     let memberships_table = format!("t{}_group_memberships", tenant_id);
 
     // Enable uuid generation extension if not exists
     sqlx::query("CREATE EXTENSION IF NOT EXISTS \"uuid-ossp\"")
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .map_err(|e| AppError::Database(format!("Failed to enable uuid-ossp extension: {}", e)))?;
 
@@ -27,14 +67,16 @@ pub async fn init_tenant_schema(pool: &PgPool, tenant_id: u32) -> AppResult<()>
             data_norm JSONB NOT NULL,
             version BIGINT NOT NULL DEFAULT 1,
             created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+            created_by TEXT,
+            last_modified_by TEXT
         )
         "#,
         users_table
     );
 
     sqlx::query(&users_sql)
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .map_err(|e| AppError::Database(format!("Failed to create users table: {}", e)))?;
 
@@ -49,14 +91,16 @@ pub async fn init_tenant_schema(pool: &PgPool, tenant_id: u32) -> AppResult<()>
             data_norm JSONB NOT NULL,
             version BIGINT NOT NULL DEFAULT 1,
             created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+            updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+            created_by TEXT,
+            last_modified_by TEXT
         )
         "#,
         groups_table
     );
 
     sqlx::query(&groups_sql)
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .map_err(|e| AppError::Database(format!("Failed to create groups table: {}", e)))?;
 
@@ -77,18 +121,83 @@ pub async fn init_tenant_schema(pool: &PgPool, tenant_id: u32) -> AppResult<()>
     );
 
     sqlx::query(&memberships_sql)
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .map_err(|e| AppError::Database(format!("Failed to create memberships table: {}", e)))?;
 
+    // Tables created before provenance tracking lack its columns
+    add_provenance_columns(conn, &users_table).await?;
+    add_provenance_columns(conn, &groups_table).await?;
+    backfill_external_ids(conn, &users_table).await?;
+    backfill_external_ids(conn, &groups_table).await?;
+
     // Create indexes for better performance
-    create_indexes(pool, tenant_id).await?;
+    create_indexes(conn, tenant_id).await?;
+
+    Ok(())
+}
+
+/// Add the provenance columns to a table created by an older version
+async fn add_provenance_columns(conn: &mut PgConnection, table: &str) -> AppResult<()> {
+    let sql = format!(
+        "ALTER TABLE {} ADD COLUMN IF NOT EXISTS created_by TEXT, \
+         ADD COLUMN IF NOT EXISTS last_modified_by TEXT",
+        table
+    );
+    sqlx::query(&sql)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to add provenance to {}: {}", table, e)))?;
+
+    Ok(())
+}
+
+/// Copy `externalId` from the stored document into the indexed column
+///
+/// Filters and lookups on externalId read the column, so rows whose column
+/// was left empty would otherwise not be found. The column is unique: a
+/// value shared with another row's document or column is logged and left
+/// out rather than failing startup, so those rows stay unfindable by
+/// externalId until the duplicate is resolved.
+async fn backfill_external_ids(conn: &mut PgConnection, table: &str) -> AppResult<()> {
+    let value = "data_orig->>'externalId'";
+    let pending = "external_id IS NULL AND jsonb_typeof(data_orig->'externalId') = 'string'";
+    let taken = format!(
+        "SELECT {value} FROM {table} WHERE jsonb_typeof(data_orig->'externalId') = 'string' \
+         GROUP BY 1 HAVING COUNT(*) > 1 \
+         UNION SELECT external_id FROM {table} WHERE external_id IS NOT NULL"
+    );
+    let backfill_error = |e: sqlx::Error| {
+        AppError::Database(format!("Failed to backfill externalId in {}: {}", table, e))
+    };
+
+    let duplicates: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT id::text, {value} FROM {table} WHERE {pending} AND {value} IN ({taken})"
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(backfill_error)?;
+    for (id, external_id) in duplicates {
+        tracing::warn!(
+            table,
+            id,
+            external_id,
+            "Not backfilling a duplicate externalId into the external_id column"
+        );
+    }
+
+    sqlx::query(&format!(
+        "UPDATE {table} SET external_id = {value} WHERE {pending} AND {value} NOT IN ({taken})"
+    ))
+    .execute(&mut *conn)
+    .await
+    .map_err(backfill_error)?;
 
     Ok(())
 }
 
 /// Create performance indexes for tenant tables
-async fn create_indexes(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
+async fn create_indexes(conn: &mut PgConnection, tenant_id: u32) -> AppResult<()> {
     let users_table = format!("t{}_users", tenant_id);
     let groups_table = format!("t{}_groups", tenant_id);
     let memberships_table = format!("t{}_group_memberships", tenant_id);
@@ -96,6 +205,9 @@ async fn create_indexes(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
     // Users table indexes
     let user_indexes = [format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_username_lower\" ON {} (LOWER(username))", tenant_id, users_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_external_id\" ON {} (external_id) WHERE external_id IS NOT NULL", tenant_id, users_table),
+        format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_external_id_lower\" ON {} (LOWER(external_id)) WHERE external_id IS NOT NULL", tenant_id, users_table),
+        format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_external_id_doc\" ON {} ((data_orig #>> '{{externalId}}')) WHERE external_id IS NULL", tenant_id, users_table),
+        format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_external_id_doc_lower\" ON {} (LOWER(data_orig #>> '{{externalId}}')) WHERE external_id IS NULL", tenant_id, users_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_data_orig_gin\" ON {} USING GIN (data_orig)", tenant_id, users_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_data_norm_gin\" ON {} USING GIN (data_norm)", tenant_id, users_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_users_created_at\" ON {} (created_at)", tenant_id, users_table)];
@@ -104,6 +216,9 @@ async fn create_indexes(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
     let group_indexes = [
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_display_name_lower\" ON {} (LOWER(display_name))", tenant_id, groups_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_external_id\" ON {} (external_id) WHERE external_id IS NOT NULL", tenant_id, groups_table),
+        format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_external_id_lower\" ON {} (LOWER(external_id)) WHERE external_id IS NOT NULL", tenant_id, groups_table),
+        format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_external_id_doc\" ON {} ((data_orig #>> '{{externalId}}')) WHERE external_id IS NULL", tenant_id, groups_table),
+        format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_external_id_doc_lower\" ON {} (LOWER(data_orig #>> '{{externalId}}')) WHERE external_id IS NULL", tenant_id, groups_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_data_orig_gin\" ON {} USING GIN (data_orig)", tenant_id, groups_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_data_norm_gin\" ON {} USING GIN (data_norm)", tenant_id, groups_table),
         format!("CREATE INDEX IF NOT EXISTS \"idx_{}_groups_created_at\" ON {} (created_at)", tenant_id, groups_table),
@@ -132,7 +247,7 @@ async fn create_indexes(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
         .chain(membership_indexes.iter())
     {
         sqlx::query(sql)
-            .execute(pool)
+            .execute(&mut *conn)
             .await
             .map_err(|e| AppError::Database(format!("Failed to create index: {}", e)))?;
     }
@@ -140,15 +255,87 @@ async fn create_indexes(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
     Ok(())
 }
 
+/// Create an expression index for each configured attribute
+///
+/// The indexed expression is the one equality filters compare, so
+/// `costCenter eq "..."` can use the index instead of scanning the JSON.
+async fn create_attribute_indexes(
+    conn: &mut PgConnection,
+    tenant_id: u32,
+    user_attributes: &[String],
+    group_attributes: &[String],
+) -> AppResult<()> {
+    let converter = PostgresFilterConverter::new();
+    let tables = [
+        ("users", ResourceType::User, user_attributes),
+        ("groups", ResourceType::Group, group_attributes),
+    ];
+
+    for (resource, resource_type, attributes) in tables {
+        for attr in attributes {
+            let expression = converter
+                .index_expression(attr, resource_type)
+                .ok_or_else(|| {
+                    AppError::Configuration(format!(
+                        "Multi-valued attribute {} cannot be indexed",
+                        attr
+                    ))
+                })?;
+            let sql = format!(
+                "CREATE INDEX IF NOT EXISTS \"{}\" ON t{}_{} (({}))",
+                attribute_index_name(tenant_id, resource, attr),
+                tenant_id,
+                resource,
+                expression
+            );
+            sqlx::query(&sql).execute(&mut *conn).await.map_err(|e| {
+                AppError::Database(format!("Failed to create index on {}: {}", attr, e))
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Longest identifier PostgreSQL keeps; longer ones are silently truncated
+const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// Name of the expression index on `attr`
+///
+/// Names that would exceed the identifier limit are cut short and end in a
+/// hash of the full attribute name, so two long names sharing a prefix do not
+/// collapse into one index that `IF NOT EXISTS` then skips.
+fn attribute_index_name(tenant_id: u32, resource: &str, attr: &str) -> String {
+    let name = format!(
+        "idx_{}_{}_attr_{}",
+        tenant_id,
+        resource,
+        attr.to_lowercase().replace('.', "_")
+    );
+    if name.len() <= MAX_IDENTIFIER_LEN {
+        return name;
+    }
+
+    let digest = Sha1::digest(attr.to_lowercase().as_bytes());
+    let hash: String = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}_{}", &name[..MAX_IDENTIFIER_LEN - hash.len() - 1], hash)
+}
+
 /// Drop tenant-specific schema (for cleanup/testing)
 #[allow(dead_code)]
 pub async fn drop_tenant_schema(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
     let memberships_table = format!("t{}_group_memberships", tenant_id);
     let groups_table = format!("t{}_groups", tenant_id);
     let users_table = format!("t{}_users", tenant_id);
+    let sync_stats_table = format!("t{}_sync_stats", tenant_id);
 
     // Drop tables in reverse order due to foreign key constraints
-    for table in [&memberships_table, &groups_table, &users_table] {
+    for table in [
+        &sync_stats_table,
+        &memberships_table,
+        &groups_table,
+        &users_table,
+    ] {
         let sql = format!("DROP TABLE IF EXISTS {} CASCADE", table);
         sqlx::query(&sql)
             .execute(pool)
@@ -159,11 +346,111 @@ pub async fn drop_tenant_schema(pool: &PgPool, tenant_id: u32) -> AppResult<()>
     Ok(())
 }
 
+/// Reclaim dead rows and refresh planner statistics after bulk deletes
+pub async fn vacuum_tenant_schema(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
+    // VACUUM cannot run inside a transaction block, which the extended
+    // query protocol may open implicitly, so it is sent as a simple query
+    let sql = format!(
+        "VACUUM (ANALYZE) t{0}_users, t{0}_groups, t{0}_group_memberships",
+        tenant_id
+    );
+    sqlx::raw_sql(&sql)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to vacuum tenant {}: {}", tenant_id, e)))?;
+
+    Ok(())
+}
+
+/// Create the table holding a tenant's sync statistics snapshot
+///
+/// Only used with `server.stats.persist`, so it is created on first use
+/// rather than by `init_tenant_schema`.
+async fn create_sync_stats_table(pool: &PgPool, tenant_id: u32) -> AppResult<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS t{}_sync_stats (\
+         id INTEGER PRIMARY KEY CHECK (id = 1), \
+         snapshot JSONB NOT NULL, \
+         updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW())",
+        tenant_id
+    );
+    sqlx::query(&sql)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create sync stats table: {}", e)))?;
+
+    Ok(())
+}
+
+/// Latest sync statistics snapshot saved for a tenant
+pub async fn load_sync_stats(
+    pool: &PgPool,
+    tenant_id: u32,
+) -> AppResult<Option<serde_json::Value>> {
+    create_sync_stats_table(pool, tenant_id).await?;
+    sqlx::query_scalar(&format!(
+        "SELECT snapshot FROM t{}_sync_stats WHERE id = 1",
+        tenant_id
+    ))
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to load sync stats: {}", e)))
+}
+
+/// Replace the sync statistics snapshot saved for a tenant
+pub async fn save_sync_stats(
+    pool: &PgPool,
+    tenant_id: u32,
+    snapshot: &serde_json::Value,
+) -> AppResult<()> {
+    create_sync_stats_table(pool, tenant_id).await?;
+    let sql = format!(
+        "INSERT INTO t{}_sync_stats (id, snapshot, updated_at) VALUES (1, $1, NOW()) \
+         ON CONFLICT (id) DO UPDATE SET snapshot = EXCLUDED.snapshot, updated_at = EXCLUDED.updated_at",
+        tenant_id
+    );
+    sqlx::query(&sql)
+        .bind(snapshot)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to save sync stats: {}", e)))?;
+
+    Ok(())
+}
+
+/// Ids of the tenants whose tables exist, in ascending order
+pub async fn list_tenant_schemas(pool: &PgPool) -> AppResult<Vec<u32>> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT tablename::text FROM pg_tables WHERE schemaname = current_schema()",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to list tables: {}", e)))?;
+
+    Ok(crate::backend::database::tenants::tenant_ids_from_tables(
+        tables,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use sqlx::postgres::PgPoolOptions;
 
+    #[test]
+    fn test_attribute_index_name_fits_identifier_limit() {
+        assert_eq!(
+            attribute_index_name(1, "users", "name.familyName"),
+            "idx_1_users_attr_name_familyname"
+        );
+
+        let long_a = attribute_index_name(4294967295, "groups", &format!("{}A", "x".repeat(60)));
+        let long_b = attribute_index_name(4294967295, "groups", &format!("{}B", "x".repeat(60)));
+        assert_eq!(long_a.len(), MAX_IDENTIFIER_LEN);
+        assert_eq!(long_b.len(), MAX_IDENTIFIER_LEN);
+        assert_ne!(long_a, long_b);
+    }
+
     #[tokio::test]
     async fn test_schema_creation() {
         // This test requires a running PostgreSQL instance
@@ -181,7 +468,9 @@ mod tests {
         let tenant_id = 1u32;
 
         // Create schema
-        init_tenant_schema(&pool, tenant_id).await.unwrap();
+        init_tenant_schema(&pool, tenant_id, &[], &[])
+            .await
+            .unwrap();
 
         // Verify tables exist
         let users_table = format!("t{}_users", tenant_id);
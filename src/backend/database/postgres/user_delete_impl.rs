@@ -1,20 +1,19 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
 use uuid::Uuid;
 
 use super::super::user_delete::UserDeleter;
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 
 /// PostgreSQL-specific implementation of UserDeleter
 ///
 /// This handles PostgreSQL's UUID data types and specific SQL syntax.
-pub struct PostgresUserDeleter {
-    pool: PgPool,
-}
+#[derive(Default)]
+pub struct PostgresUserDeleter;
 
 impl PostgresUserDeleter {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Validate UUID format for PostgreSQL
@@ -26,19 +25,18 @@ impl PostgresUserDeleter {
 
 #[async_trait]
 impl UserDeleter for PostgresUserDeleter {
-    async fn execute_user_delete(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
+    async fn execute_user_delete(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
         // Validate UUID format for PostgreSQL
         Self::validate_uuid_format(id)?;
 
         let users_table = format!("t{}_users", tenant_id);
         let memberships_table = format!("t{}_group_memberships", tenant_id);
-
-        // Start a transaction to ensure atomic operation
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to start transaction: {}", e)))?;
+        let conn = tx.postgres()?;
 
         // First, delete the user from group memberships
         let membership_sql = format!(
@@ -48,7 +46,7 @@ impl UserDeleter for PostgresUserDeleter {
 
         sqlx::query(&membership_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to delete user group memberships: {}", e))
@@ -59,18 +57,11 @@ impl UserDeleter for PostgresUserDeleter {
 
         let result = sqlx::query(&user_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| AppError::Database(format!("Failed to delete user: {}", e)))?;
 
-        let user_was_deleted = result.rows_affected() > 0;
-
-        // Commit the transaction
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
-
-        Ok(user_was_deleted)
+        Ok(result.rows_affected() > 0)
     }
 }
 
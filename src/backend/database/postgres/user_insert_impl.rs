@@ -1,24 +1,29 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::PgConnection;
 
 use super::super::user_insert::{PreparedUserData, UserInsertProcessor, UserInserter};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
 /// PostgreSQL-specific implementation of UserInserter
 ///
 /// This handles PostgreSQL's JSONB data types while using shared SQL generation.
-pub struct PostgresUserInserter {
-    pool: PgPool,
-}
+#[derive(Default)]
+pub struct PostgresUserInserter;
 
 impl PostgresUserInserter {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Check for case-insensitive duplicate username
-    async fn check_duplicate_username(&self, tenant_id: u32, username: &str) -> AppResult<()> {
+    async fn check_duplicate_username(
+        &self,
+        conn: &mut PgConnection,
+        tenant_id: u32,
+        username: &str,
+    ) -> AppResult<()> {
         let table_name = format!("t{}_users", tenant_id);
         let sql = format!(
             "SELECT COUNT(*) FROM {} WHERE LOWER(username) = LOWER($1)",
@@ -27,7 +32,7 @@ impl PostgresUserInserter {
 
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(username)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate username: {}", e))
@@ -45,14 +50,22 @@ impl PostgresUserInserter {
 
 #[async_trait]
 impl UserInserter for PostgresUserInserter {
-    async fn execute_user_insert(&self, tenant_id: u32, data: PreparedUserData) -> AppResult<User> {
+    async fn execute_user_insert(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        data: PreparedUserData,
+    ) -> AppResult<User> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.postgres()?;
+
         // Check for case-insensitive duplicate username before insertion
-        self.check_duplicate_username(tenant_id, &data.username)
+        self.check_duplicate_username(conn, tenant_id, &data.username)
             .await?;
 
         let table_name = format!("t{}_users", tenant_id);
         let sql = format!(
-            "INSERT INTO {} (id, username, external_id, data_orig, data_norm, version, created_at, updated_at) VALUES ($1::uuid, $2, $3, $4, $5, $6, $7, $8)",
+            "INSERT INTO {} (id, username, external_id, data_orig, data_norm, version, created_at, updated_at, created_by, last_modified_by) VALUES ($1::uuid, $2, $3, $4, $5, $6, $7, $8, $9, $9)",
             table_name
         );
 
@@ -65,7 +78,8 @@ impl UserInserter for PostgresUserInserter {
             .bind(1i64) // version = 1 for new records
             .bind(data.timestamp)
             .bind(data.timestamp)
-            .execute(&self.pool)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| map_database_error(e, "User"))?;
 
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
-use sqlx::{PgPool, Row};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, Row};
 
 use super::super::user_patch::{PreparedUserPatchData, UserPatcher};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
@@ -9,18 +11,18 @@ use crate::models::User;
 ///
 /// This handles PostgreSQL's UUID types and JSONB storage format
 /// for user patch operations.
-pub struct PostgresUserPatcher {
-    pool: PgPool,
-}
+#[derive(Default)]
+pub struct PostgresUserPatcher;
 
 impl PostgresUserPatcher {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Check for case-insensitive duplicate username excluding current user
     async fn check_duplicate_username(
         &self,
+        conn: &mut PgConnection,
         tenant_id: u32,
         username: &str,
         exclude_id: &str,
@@ -34,7 +36,7 @@ impl PostgresUserPatcher {
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(username)
             .bind(exclude_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate username: {}", e))
@@ -52,6 +54,7 @@ impl PostgresUserPatcher {
 impl UserPatcher for PostgresUserPatcher {
     async fn execute_user_patch(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         data: PreparedUserPatchData,
@@ -61,8 +64,11 @@ impl UserPatcher for PostgresUserPatcher {
             return Ok(None);
         }
 
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.postgres()?;
+
         // Check for case-insensitive duplicate username before patch
-        self.check_duplicate_username(tenant_id, &data.username, &data.id)
+        self.check_duplicate_username(conn, tenant_id, &data.username, &data.id)
             .await?;
 
         // Build table name
@@ -70,7 +76,7 @@ impl UserPatcher for PostgresUserPatcher {
 
         // PostgreSQL UPDATE SQL with UUID casting, JSONB storage, and version increment
         let sql = format!(
-            "UPDATE {} SET username = $1, external_id = $2, data_orig = $3, data_norm = $4, version = version + 1, updated_at = $5 WHERE id = $6::uuid",
+            "UPDATE {} SET username = $1, external_id = $2, data_orig = $3, data_norm = $4, version = version + 1, updated_at = $5, last_modified_by = $7 WHERE id = $6::uuid",
             table_name
         );
 
@@ -81,7 +87,8 @@ impl UserPatcher for PostgresUserPatcher {
             .bind(&data.data_norm) // PostgreSQL uses JSONB
             .bind(data.timestamp)
             .bind(&data.id)
-            .execute(&self.pool)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| map_database_error(e, "User"))?;
 
@@ -92,7 +99,62 @@ impl UserPatcher for PostgresUserPatcher {
         }
     }
 
-    async fn find_user_for_patch(&self, tenant_id: u32, id: &str) -> AppResult<Option<User>> {
+    async fn execute_active_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        active: bool,
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<User>> {
+        // Validate UUID format for PostgreSQL
+        if uuid::Uuid::parse_str(id).is_err() {
+            return Ok(None);
+        }
+
+        let actor = tx.actor().map(str::to_owned);
+        let table_name = format!("t{}_users", tenant_id);
+        let last_modified = crate::utils::format_scim_datetime(timestamp);
+
+        // jsonb_set edits both documents in place; data_norm keys and
+        // non-caseExact values are lowercase
+        let sql = format!(
+            "UPDATE {} SET \
+             data_orig = jsonb_set(jsonb_set(data_orig, '{{active}}', $1), '{{meta,lastModified}}', to_jsonb($2::text)), \
+             data_norm = jsonb_set(jsonb_set(data_norm, '{{active}}', $1), '{{meta,lastmodified}}', to_jsonb($3::text)), \
+             version = version + 1, updated_at = $4, last_modified_by = $6 WHERE id = $5::uuid RETURNING id, data_orig",
+            table_name
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(serde_json::Value::Bool(active))
+            .bind(&last_modified)
+            .bind(last_modified.to_lowercase())
+            .bind(timestamp)
+            .bind(id)
+            .bind(&actor)
+            .fetch_optional(tx.postgres()?)
+            .await
+            .map_err(|e| map_database_error(e, "User"))?;
+
+        match row {
+            Some(row) => {
+                let mut user: User = serde_json::from_value(row.get("data_orig"))
+                    .map_err(AppError::Serialization)?;
+                let db_id: uuid::Uuid = row.get("id");
+                *user.id_mut() = Some(db_id.to_string());
+                Ok(Some(user))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_user_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<User>> {
         let table_name = format!("t{}_users", tenant_id);
         let sql = format!(
             "SELECT id, username, external_id, data_orig, data_norm, created_at, updated_at FROM {} WHERE id = $1::uuid",
@@ -101,7 +163,7 @@ impl UserPatcher for PostgresUserPatcher {
 
         let row = sqlx::query(&sql)
             .bind(id)
-            .fetch_optional(&self.pool)
+            .fetch_optional(tx.postgres()?)
             .await
             .map_err(|e| AppError::Database(format!("Failed to find user for patch: {}", e)))?;
 
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
 
-use super::super::user_update::{PreparedUserUpdateData, UserUpdater};
+use super::super::user_update::{uniqueness_conflict, PreparedUserUpdateData, UserUpdater};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
@@ -9,49 +9,73 @@ use crate::models::User;
 ///
 /// This handles PostgreSQL's UUID types and JSONB storage format
 /// for user update operations.
-pub struct PostgresUserUpdater {
-    pool: PgPool,
-}
+#[derive(Default)]
+pub struct PostgresUserUpdater;
 
 impl PostgresUserUpdater {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
+}
 
-    /// Check for case-insensitive duplicate username excluding current user
-    async fn check_duplicate_username(
+#[async_trait]
+impl UserUpdater for PostgresUserUpdater {
+    async fn find_conflicting_attribute(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
+        id: &str,
         username: &str,
-        exclude_id: &str,
-    ) -> AppResult<()> {
+        external_id: Option<&str>,
+    ) -> AppResult<Option<&'static str>> {
+        // A malformed id matches no row, so the update itself reports not found
+        if uuid::Uuid::parse_str(id).is_err() {
+            return Ok(None);
+        }
+
         let table_name = format!("t{}_users", tenant_id);
+        let conn = tx.postgres()?;
+
         let sql = format!(
             "SELECT COUNT(*) FROM {} WHERE LOWER(username) = LOWER($1) AND id != $2::uuid",
             table_name
         );
-
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(username)
-            .bind(exclude_id)
-            .fetch_one(&self.pool)
+            .bind(id)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate username: {}", e))
             })?;
-
         if count > 0 {
-            return Err(AppError::BadRequest("User already exists".to_string()));
+            return Ok(Some("userName"));
         }
 
-        Ok(())
+        if let Some(external_id) = external_id {
+            let sql = format!(
+                "SELECT COUNT(*) FROM {} WHERE external_id = $1 AND id != $2::uuid",
+                table_name
+            );
+            let count: i64 = sqlx::query_scalar(&sql)
+                .bind(external_id)
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to check duplicate externalId: {}", e))
+                })?;
+            if count > 0 {
+                return Ok(Some("externalId"));
+            }
+        }
+
+        Ok(None)
     }
-}
 
-#[async_trait]
-impl UserUpdater for PostgresUserUpdater {
     async fn execute_user_update(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         data: PreparedUserUpdateData,
@@ -61,16 +85,14 @@ impl UserUpdater for PostgresUserUpdater {
             return Ok(None);
         }
 
-        // Check for case-insensitive duplicate username before update
-        self.check_duplicate_username(tenant_id, &data.username, &data.id)
-            .await?;
+        let actor = tx.actor().map(str::to_owned);
 
         // Build table name
         let table_name = format!("t{}_users", tenant_id);
 
         // PostgreSQL UPDATE SQL with UUID casting, JSONB storage, and version increment
         let sql = format!(
-            "UPDATE {} SET username = $1, external_id = $2, data_orig = $3, data_norm = $4, version = version + 1, updated_at = $5 WHERE id = $6::uuid",
+            "UPDATE {} SET username = $1, external_id = $2, data_orig = $3, data_norm = $4, version = version + 1, updated_at = $5, last_modified_by = $7 WHERE id = $6::uuid",
             table_name
         );
 
@@ -81,9 +103,10 @@ impl UserUpdater for PostgresUserUpdater {
             .bind(&data.data_norm) // PostgreSQL uses JSONB
             .bind(data.timestamp)
             .bind(&data.id)
-            .execute(&self.pool)
+            .bind(&actor)
+            .execute(tx.postgres()?)
             .await
-            .map_err(|e| map_database_error(e, "User"))?;
+            .map_err(map_user_update_error)?;
 
         if result.rows_affected() > 0 {
             Ok(Some(data.user))
@@ -93,6 +116,21 @@ impl UserUpdater for PostgresUserUpdater {
     }
 }
 
+/// Map a unique violation that slipped past the pre-check (e.g. two
+/// concurrent renames) to the same 409 the pre-check returns
+fn map_user_update_error(e: sqlx::Error) -> AppError {
+    if let sqlx::Error::Database(ref db_err) = e {
+        if db_err.is_unique_violation() {
+            if db_err.message().contains("username") {
+                return uniqueness_conflict("userName");
+            } else if db_err.message().contains("external_id") {
+                return uniqueness_conflict("externalId");
+            }
+        }
+    }
+    map_database_error(e, "User")
+}
+
 /// Map PostgreSQL-specific database errors to appropriate application errors
 pub fn map_database_error(e: sqlx::Error, resource_type: &str) -> AppError {
     match e {
@@ -128,17 +166,6 @@ pub fn map_database_error(e: sqlx::Error, resource_type: &str) -> AppError {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-    #[allow(dead_code)]
-    async fn create_test_pool() -> PgPool {
-        // Note: This would need a real PostgreSQL instance for integration tests
-        // For unit tests, we just verify the updater can be created
-        PgPool::connect("postgresql://localhost/test")
-            .await
-            .unwrap()
-    }
-
     #[test]
     fn test_validate_uuid_format() {
         // Valid UUID
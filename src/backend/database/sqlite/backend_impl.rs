@@ -1,25 +1,56 @@
 use async_trait::async_trait;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{Mutex, MutexGuard};
 
-use super::super::config::DatabaseBackendConfig;
+use super::super::config::{
+    DatabaseBackendConfig, SQLITE_BUSY_TIMEOUT_MS, SQLITE_SERIALIZE_WRITES,
+};
+use crate::backend::database::transaction::{self, DbTransaction};
 use crate::backend::database::{
-    SqliteGroupDeleter, SqliteGroupInserter, SqliteGroupReader, SqliteGroupUpdater,
-    SqliteUserDeleter, SqliteUserInserter, SqliteUserPatcher, SqliteUserReader, SqliteUserUpdater,
-    UnifiedGroupDeleteOps, UnifiedGroupInsertOps, UnifiedGroupReadOps, UnifiedGroupUpdateOps,
+    map_connection_error, SqliteGroupDeleter, SqliteGroupInserter, SqliteGroupMemberUpdater,
+    SqliteGroupReader, SqliteGroupUpdater, SqliteUserDeleter, SqliteUserInserter,
+    SqliteUserPatcher, SqliteUserReader, SqliteUserUpdater, UnifiedGroupDeleteOps,
+    UnifiedGroupInsertOps, UnifiedGroupMembersOps, UnifiedGroupReadOps, UnifiedGroupUpdateOps,
     UnifiedUserDeleteOps, UnifiedUserInsertOps, UnifiedUserPatchOps, UnifiedUserReadOps,
     UnifiedUserUpdateOps,
 };
-use crate::backend::{Backend, GroupBackend, UserBackend};
+use crate::backend::{
+    Backend, GroupBackend, HistoryBackend, MembersAdded, MembersRemoved, Provenance,
+    ProvenanceBackend, UserBackend,
+};
+use crate::config::CompatibilityConfig;
 use crate::error::{AppError, AppResult};
-use crate::models::{Group, User};
+use crate::models::{Group, ScimPatchOp, User};
 use crate::parser::filter_operator::FilterOperator;
-use crate::parser::SortSpec;
+use crate::parser::{ResourceType, SortSpec};
 
 use super::filter_impl::SqliteFilterConverter;
 
+/// busy_timeout used when the config does not set one
+const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+
+/// Turn SQLITE_BUSY ("database is locked") and pool failures into retryable errors
+///
+/// Lock contention that outlasts busy_timeout is transient, so clients get a
+/// 503 they can retry instead of a 500. Everything else goes through
+/// `map_connection_error`.
+pub(crate) fn map_busy_error(error: AppError) -> AppError {
+    match error {
+        AppError::Database(message)
+            if message.contains("database is locked") || message.contains("(code: 5)") =>
+        {
+            AppError::Unavailable(message)
+        }
+        other => map_connection_error(other),
+    }
+}
+
 /// SQLite database backend implementation
 ///
 /// This provides a complete SCIM 2.0 database backend using SQLite
@@ -38,21 +69,27 @@ pub struct SqliteBackend {
     group_update_ops: UnifiedGroupUpdateOps<SqliteGroupUpdater>,
     group_delete_ops: UnifiedGroupDeleteOps<SqliteGroupDeleter>,
     group_read_ops: UnifiedGroupReadOps<SqliteGroupReader>,
+    group_members_ops: UnifiedGroupMembersOps<SqliteGroupMemberUpdater>,
+    /// See `DatabaseBackendConfig::external_id_case_insensitive_tenants`
+    case_insensitive_external_ids: Arc<HashSet<u32>>,
+    // Held for the duration of each write when serialize_writes is enabled
+    write_lock: Option<Arc<Mutex<()>>>,
 }
 
 impl SqliteBackend {
     /// Create a new SQLite backend instance
     pub fn new(pool: SqlitePool) -> Self {
         // Create database-specific adapters
-        let user_inserter = SqliteUserInserter::new(pool.clone());
-        let user_updater = SqliteUserUpdater::new(pool.clone());
-        let user_deleter = SqliteUserDeleter::new(pool.clone());
-        let user_patcher = SqliteUserPatcher::new(pool.clone());
+        let user_inserter = SqliteUserInserter::new();
+        let user_updater = SqliteUserUpdater::new();
+        let user_deleter = SqliteUserDeleter::new();
+        let user_patcher = SqliteUserPatcher::new();
         let user_reader = SqliteUserReader::new(pool.clone());
-        let group_inserter = SqliteGroupInserter::new(pool.clone());
-        let group_updater = SqliteGroupUpdater::new(pool.clone());
-        let group_deleter = SqliteGroupDeleter::new(pool.clone());
+        let group_inserter = SqliteGroupInserter::new();
+        let group_updater = SqliteGroupUpdater::new();
+        let group_deleter = SqliteGroupDeleter::new();
         let group_reader = SqliteGroupReader::new(pool.clone());
+        let group_member_updater = SqliteGroupMemberUpdater::new();
 
         Self {
             pool,
@@ -67,6 +104,82 @@ impl SqliteBackend {
             group_update_ops: UnifiedGroupUpdateOps::new(group_updater),
             group_delete_ops: UnifiedGroupDeleteOps::new(group_deleter),
             group_read_ops: UnifiedGroupReadOps::new(group_reader),
+            group_members_ops: UnifiedGroupMembersOps::new(group_member_updater),
+            case_insensitive_external_ids: Arc::default(),
+            write_lock: None,
+        }
+    }
+
+    /// Run writes one at a time; reads still use the whole pool
+    pub fn with_serialized_writes(mut self) -> Self {
+        self.write_lock = Some(Arc::new(Mutex::new(())));
+        self
+    }
+
+    /// Match externalId case-insensitively in the given tenants
+    /// (`external_id_case_insensitive`)
+    pub fn with_case_insensitive_external_ids(mut self, tenants: HashSet<u32>) -> Self {
+        let tenants = Arc::new(tenants);
+        self.case_insensitive_external_ids = tenants.clone();
+        self.user_read_ops = UnifiedUserReadOps::new(
+            SqliteUserReader::new(self.pool.clone())
+                .with_case_insensitive_external_ids(tenants.clone()),
+        );
+        self.group_read_ops = UnifiedGroupReadOps::new(
+            SqliteGroupReader::new(self.pool.clone()).with_case_insensitive_external_ids(tenants),
+        );
+        self
+    }
+
+    /// Fail a write in a tenant matching externalId case-insensitively when
+    /// another resource holds the same externalId in a different case
+    ///
+    /// Runs on the write's transaction after the write, so failing rolls it
+    /// back. Exact duplicates are already rejected by the unique column.
+    async fn check_external_id_case(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        id: &str,
+        external_id: Option<&str>,
+    ) -> AppResult<()> {
+        let Some(external_id) = external_id else {
+            return Ok(());
+        };
+        if !self.case_insensitive_external_ids.contains(&tenant_id) {
+            return Ok(());
+        }
+        let (table, holder) = match resource_type {
+            ResourceType::User => (format!("t{}_users", tenant_id), "user"),
+            ResourceType::Group => (format!("t{}_groups", tenant_id), "group"),
+        };
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE LOWER(external_id) = LOWER(?1) AND id <> ?2",
+            table
+        );
+        let count: i64 = sqlx::query_scalar(&sql)
+            .bind(external_id)
+            .bind(id)
+            .fetch_one(&mut *tx.sqlite()?)
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to check duplicate externalId: {}", e))
+            })?;
+        if count > 0 {
+            return Err(AppError::Conflict(format!(
+                "externalId is already in use by another {}",
+                holder
+            )));
+        }
+        Ok(())
+    }
+
+    /// Acquire the write lock if writes are serialized
+    async fn lock_writes(&self) -> Option<MutexGuard<'_, ()>> {
+        match &self.write_lock {
+            Some(lock) => Some(lock.lock().await),
+            None => None,
         }
     }
 
@@ -127,7 +240,22 @@ impl Backend for SqliteBackend {
                     config.connection_path, e
                 ))
             })?
-            .create_if_missing(true);
+            .create_if_missing(true)
+            .foreign_keys(true)
+            .busy_timeout(Duration::from_millis(
+                config
+                    .get_option(SQLITE_BUSY_TIMEOUT_MS)
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_BUSY_TIMEOUT_MS),
+            ));
+
+        // WAL lets readers proceed while a write is in progress. It needs a
+        // real file, so in-memory databases keep the default journal.
+        let options = if config.is_memory_database() {
+            options
+        } else {
+            options.journal_mode(SqliteJournalMode::Wal)
+        };
 
         let pool = SqlitePoolOptions::new()
             .max_connections(config.max_connections)
@@ -136,7 +264,18 @@ impl Backend for SqliteBackend {
             .await
             .map_err(|e| AppError::Database(format!("Failed to connect to SQLite: {}", e)))?;
 
-        Ok(Self::new(pool))
+        let backend = Self::new(pool).with_case_insensitive_external_ids(
+            config.external_id_case_insensitive_tenants.clone(),
+        );
+        let serialize_writes = config
+            .get_option(SQLITE_SERIALIZE_WRITES)
+            .is_some_and(|v| v == "true");
+
+        Ok(if serialize_writes {
+            backend.with_serialized_writes()
+        } else {
+            backend
+        })
     }
 
     async fn health_check(&self) -> AppResult<()> {
@@ -148,15 +287,101 @@ impl Backend for SqliteBackend {
         Ok(())
     }
 
+    async fn begin_transaction(&self) -> AppResult<DbTransaction> {
+        // Held until the transaction ends when serialize_writes is enabled
+        let write_guard = match &self.write_lock {
+            Some(lock) => Some(lock.clone().lock_owned().await),
+            None => None,
+        };
+        // Write flows start with a read (uniqueness checks, PATCH), and a
+        // deferred transaction upgrading to a writer fails with SQLITE_BUSY
+        // without waiting on busy_timeout, so the write lock is taken up front
+        let tx = transaction::begin_with(&self.pool, "BEGIN IMMEDIATE")
+            .await
+            .map_err(map_busy_error)?;
+        Ok(DbTransaction::from(tx).with_write_guard(write_guard))
+    }
+
     async fn init_tenant(&self, tenant_id: u32) -> AppResult<()> {
-        super::schema::init_tenant_schema(&self.pool, tenant_id).await
+        // Creating the schema is a write like any other
+        let _write_guard = self.lock_writes().await;
+        super::schema::init_tenant_schema(&self.pool, tenant_id)
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn list_initialized_tenants(&self) -> AppResult<Vec<u32>> {
+        super::schema::list_tenant_schemas(&self.pool)
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn vacuum_tenant(&self, tenant_id: u32) -> AppResult<()> {
+        let _write_guard = self.lock_writes().await;
+        super::schema::vacuum_tenant_schema(&self.pool, tenant_id)
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn load_sync_stats(&self, tenant_id: u32) -> AppResult<Option<serde_json::Value>> {
+        super::schema::load_sync_stats(&self.pool, tenant_id)
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn save_sync_stats(&self, tenant_id: u32, snapshot: &serde_json::Value) -> AppResult<()> {
+        let _write_guard = self.lock_writes().await;
+        super::schema::save_sync_stats(&self.pool, tenant_id, snapshot)
+            .await
+            .map_err(map_busy_error)
     }
 }
 
 #[async_trait]
 impl UserBackend for SqliteBackend {
-    async fn create_user(&self, tenant_id: u32, user: &User) -> AppResult<User> {
-        self.user_insert_ops.create_user(tenant_id, user).await
+    async fn create_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        user: &User,
+    ) -> AppResult<User> {
+        let user = self
+            .user_insert_ops
+            .create_user(tx, tenant_id, user)
+            .await
+            .map_err(map_busy_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::User,
+            user.id().as_deref().unwrap_or_default(),
+            user.external_id.as_deref(),
+        )
+        .await?;
+        Ok(user)
+    }
+
+    async fn create_user_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<User> {
+        let user = self
+            .user_insert_ops
+            .create_user_with_id(tx, tenant_id, id, user)
+            .await
+            .map_err(map_busy_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::User,
+            user.id().as_deref().unwrap_or_default(),
+            user.external_id.as_deref(),
+        )
+        .await?;
+        Ok(user)
     }
 
     async fn find_user_by_id(
@@ -168,6 +393,7 @@ impl UserBackend for SqliteBackend {
         self.user_read_ops
             .find_user_by_id(tenant_id, id, include_groups)
             .await
+            .map_err(map_busy_error)
     }
 
     async fn find_user_by_username(
@@ -179,6 +405,19 @@ impl UserBackend for SqliteBackend {
         self.user_read_ops
             .find_user_by_username(tenant_id, username, include_groups)
             .await
+            .map_err(map_busy_error)
+    }
+
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.user_read_ops
+            .find_user_by_external_id(tenant_id, external_id, include_groups)
+            .await
+            .map_err(map_busy_error)
     }
 
     async fn find_all_users(
@@ -191,6 +430,7 @@ impl UserBackend for SqliteBackend {
         self.user_read_ops
             .find_all_users(tenant_id, start_index, count, include_groups)
             .await
+            .map_err(map_busy_error)
     }
 
     async fn find_all_users_sorted(
@@ -204,6 +444,7 @@ impl UserBackend for SqliteBackend {
         self.user_read_ops
             .find_all_users_sorted(tenant_id, start_index, count, sort_spec, include_groups)
             .await
+            .map_err(map_busy_error)
     }
 
     async fn find_users_by_filter(
@@ -225,72 +466,128 @@ impl UserBackend for SqliteBackend {
                 include_groups,
             )
             .await
+            .map_err(map_busy_error)
     }
 
-    async fn update_user(&self, tenant_id: u32, id: &str, user: &User) -> AppResult<Option<User>> {
-        // Perform the update using the unified operations
-        match self
+    async fn update_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<Option<User>> {
+        let updated = self
             .user_update_ops
-            .update_user(tenant_id, id, user)
-            .await?
-        {
-            Some(_) => {
-                // After successful update, fetch the user with groups populated
-                self.user_read_ops
-                    .find_user_by_id(tenant_id, id, true)
-                    .await
-            }
-            None => Ok(None),
+            .update_user(tx, tenant_id, id, user)
+            .await
+            .map_err(map_busy_error)?;
+        if let Some(user) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::User,
+                id,
+                user.external_id.as_deref(),
+            )
+            .await?;
         }
+        Ok(updated)
     }
 
-    async fn patch_user(
+    async fn patch_user_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
-        patch_ops: &crate::models::ScimPatchOp,
-        compatibility: &crate::config::CompatibilityConfig,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
     ) -> AppResult<Option<User>> {
-        // Perform the patch using the unified operations
-        match self
+        // Read, patch and write the user on the same transaction
+        let patched = self
             .user_patch_ops
-            .patch_user(tenant_id, id, patch_ops, compatibility)
-            .await?
-        {
-            Some(_) => {
-                // After successful patch, fetch the user with groups populated
-                self.user_read_ops
-                    .find_user_by_id(tenant_id, id, true)
-                    .await
-            }
-            None => Ok(None),
+            .patch_user(tx, tenant_id, id, patch_ops, compatibility)
+            .await
+            .map_err(map_busy_error)?;
+        if let Some(user) = &patched {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::User,
+                id,
+                user.external_id.as_deref(),
+            )
+            .await?;
         }
+        Ok(patched)
     }
 
-    async fn delete_user(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
-        self.user_delete_ops.delete_user(tenant_id, id).await
-    }
-
-    async fn find_users_by_group_id(
+    async fn delete_user_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
-        group_id: &str,
-        include_groups: bool,
-    ) -> AppResult<Vec<User>> {
-        self.user_read_ops
-            .find_users_by_group_id(tenant_id, group_id, include_groups)
+        id: &str,
+    ) -> AppResult<bool> {
+        self.user_delete_ops
+            .delete_user(tx, tenant_id, id)
             .await
+            .map_err(map_busy_error)
     }
 }
 
 #[async_trait]
 impl GroupBackend for SqliteBackend {
-    async fn create_group(&self, tenant_id: u32, group: &Group) -> AppResult<Group> {
-        self.group_insert_ops.create_group(tenant_id, group).await
+    async fn create_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group: &Group,
+    ) -> AppResult<Group> {
+        // The group and its memberships are written together
+        let group = self
+            .group_insert_ops
+            .create_group(tx, tenant_id, group)
+            .await
+            .map_err(map_busy_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::Group,
+            group.id(),
+            group.external_id.as_deref(),
+        )
+        .await?;
+        Ok(group)
+    }
+
+    async fn create_group_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Group> {
+        // The group and its memberships are written together
+        let group = self
+            .group_insert_ops
+            .create_group_with_id(tx, tenant_id, id, group)
+            .await
+            .map_err(map_busy_error)?;
+        self.check_external_id_case(
+            tx,
+            tenant_id,
+            ResourceType::Group,
+            group.id(),
+            group.external_id.as_deref(),
+        )
+        .await?;
+        Ok(group)
     }
 
     async fn find_group_by_id(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
-        self.group_read_ops.find_group_by_id(tenant_id, id).await
+        self.group_read_ops
+            .find_group_by_id(tenant_id, id)
+            .await
+            .map_err(map_busy_error)
     }
 
     async fn find_group_by_display_name(
@@ -301,6 +598,18 @@ impl GroupBackend for SqliteBackend {
         self.group_read_ops
             .find_group_by_display_name(tenant_id, display_name)
             .await
+            .map_err(map_busy_error)
+    }
+
+    async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.group_read_ops
+            .find_group_by_external_id(tenant_id, external_id)
+            .await
+            .map_err(map_busy_error)
     }
 
     async fn find_all_groups(
@@ -312,6 +621,7 @@ impl GroupBackend for SqliteBackend {
         self.group_read_ops
             .find_all_groups(tenant_id, start_index, count)
             .await
+            .map_err(map_busy_error)
     }
 
     async fn find_all_groups_sorted(
@@ -324,6 +634,7 @@ impl GroupBackend for SqliteBackend {
         self.group_read_ops
             .find_all_groups_sorted(tenant_id, start_index, count, sort_spec)
             .await
+            .map_err(map_busy_error)
     }
 
     async fn find_groups_by_filter(
@@ -337,46 +648,226 @@ impl GroupBackend for SqliteBackend {
         self.group_read_ops
             .find_groups_by_filter(tenant_id, filter, start_index, count, sort_spec)
             .await
+            .map_err(map_busy_error)
     }
 
-    async fn update_group(
+    async fn update_group_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         group: &Group,
     ) -> AppResult<Option<Group>> {
-        self.group_update_ops
-            .update_group(tenant_id, id, group)
+        let updated = self
+            .group_update_ops
+            .update_group(tx, tenant_id, id, group)
             .await
+            .map_err(map_busy_error)?;
+        if let Some(group) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::Group,
+                id,
+                group.external_id.as_deref(),
+            )
+            .await?;
+        }
+        Ok(updated)
     }
 
-    async fn patch_group(
+    async fn patch_group_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
-        patch_ops: &crate::models::ScimPatchOp,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
     ) -> AppResult<Option<Group>> {
-        // Perform the patch using the group read ops
-        match self
-            .group_read_ops
-            .patch_group(tenant_id, id, patch_ops)
-            .await?
-        {
-            Some(_) => {
-                // After successful patch, fetch the group with members populated
-                self.group_read_ops.find_group_by_id(tenant_id, id).await
-            }
-            None => Ok(None),
+        // Read, patch and write the group on the same transaction
+        let updated = self
+            .group_update_ops
+            .patch_group(tx, tenant_id, id, patch_ops, compatibility)
+            .await
+            .map_err(map_busy_error)?;
+        if let Some(group) = &updated {
+            self.check_external_id_case(
+                tx,
+                tenant_id,
+                ResourceType::Group,
+                id,
+                group.external_id.as_deref(),
+            )
+            .await?;
         }
+        Ok(updated)
     }
 
-    async fn delete_group(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
-        self.group_delete_ops.delete_group(tenant_id, id).await
+    async fn delete_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        self.group_delete_ops
+            .delete_group(tx, tenant_id, id)
+            .await
+            .map_err(map_busy_error)
     }
 
-    async fn find_groups_by_user_id(&self, tenant_id: u32, user_id: &str) -> AppResult<Vec<Group>> {
-        self.group_read_ops
-            .find_groups_by_user_id(tenant_id, user_id)
+    async fn add_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersAdded>> {
+        self.group_members_ops
+            .add_members(tx, tenant_id, group_id, ids)
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn remove_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersRemoved>> {
+        self.group_members_ops
+            .remove_members(tx, tenant_id, group_id, ids)
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn find_group_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.group_update_ops
+            .find_group(tx, tenant_id, id)
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn find_group_ids_with_member_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        member_id: &str,
+    ) -> AppResult<Vec<String>> {
+        let sql = format!(
+            "SELECT group_id FROM {} WHERE member_id = ?1 ORDER BY group_id",
+            self.memberships_table(tenant_id)
+        );
+        sqlx::query_scalar(&sql)
+            .bind(member_id)
+            .fetch_all(tx.sqlite()?)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to find member's groups: {}", e)))
+            .map_err(map_busy_error)
+    }
+
+    async fn find_effective_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<Option<(Vec<User>, i64)>> {
+        self.user_read_ops
+            .find_effective_members(
+                tenant_id,
+                group_id,
+                max_depth,
+                start_index,
+                count,
+                include_groups,
+            )
+            .await
+            .map_err(map_busy_error)
+    }
+
+    async fn count_group_members(
+        &self,
+        tenant_id: u32,
+        group_ids: &[String],
+    ) -> AppResult<HashMap<String, u64>> {
+        if group_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let placeholders = vec!["?"; group_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT group_id, COUNT(*) FROM {} WHERE group_id IN ({}) GROUP BY group_id",
+            self.memberships_table(tenant_id),
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, (String, i64)>(&sql);
+        for id in group_ids {
+            query = query.bind(id);
+        }
+        let rows = query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count group members: {}", e)))
+            .map_err(map_busy_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(group_id, count)| (group_id, count as u64))
+            .collect())
+    }
+}
+
+/// Writes are not logged, so there is no history
+impl HistoryBackend for SqliteBackend {}
+
+#[async_trait]
+impl ProvenanceBackend for SqliteBackend {
+    async fn find_provenance(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        ids: &[String],
+    ) -> AppResult<HashMap<String, Provenance>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let table = match resource_type {
+            ResourceType::User => self.users_table(tenant_id),
+            ResourceType::Group => self.groups_table(tenant_id),
+        };
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT id, created_by, last_modified_by FROM {} WHERE id IN ({})",
+            table, placeholders
+        );
+        let mut query = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(&sql);
+        for id in ids {
+            query = query.bind(id);
+        }
+        let rows = query
+            .fetch_all(&self.pool)
             .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch provenance: {}", e)))
+            .map_err(map_busy_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, created_by, last_modified_by)| {
+                (
+                    id,
+                    Provenance {
+                        created_by,
+                        last_modified_by,
+                    },
+                )
+            })
+            .collect())
     }
 }
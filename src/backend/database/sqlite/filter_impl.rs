@@ -1,4 +1,7 @@
-use super::super::filter::FilterConverter;
+use super::super::filter::{
+    build_comparison_sql, locale_filter, meta_filter, validate_filter_attributes, FilterConverter,
+    MetaFilter, StringMatch,
+};
 use crate::error::AppResult;
 use crate::parser::filter_operator::FilterOperator;
 use crate::parser::ResourceType;
@@ -9,7 +12,13 @@ use serde_json::Value;
 ///
 /// This handles conversion of SCIM filter expressions to SQLite
 /// JSON queries with proper parameter binding and SQL injection prevention.
-pub struct SqliteFilterConverter;
+pub struct SqliteFilterConverter {
+    /// Multi-valued attribute kept outside data_norm and the rows standing in
+    /// for it, see `with_elements_source`
+    external_elements: Option<(&'static str, String)>,
+    /// Match externalId case-insensitively, see `with_case_insensitive_external_id`
+    external_id_case_insensitive: bool,
+}
 
 impl Default for SqliteFilterConverter {
     fn default() -> Self {
@@ -19,7 +28,39 @@ impl Default for SqliteFilterConverter {
 
 impl SqliteFilterConverter {
     pub fn new() -> Self {
-        Self
+        Self {
+            external_elements: None,
+            external_id_case_insensitive: false,
+        }
+    }
+
+    /// Evaluate filters on `attr` against the given query instead of data_norm
+    ///
+    /// Group members and user groups are stored in the memberships table, so
+    /// the query must yield one JSON object per element of the current row
+    /// in a `value` column, normalized like data_norm.
+    pub fn with_elements_source(mut self, attr: &'static str, source: String) -> Self {
+        self.external_elements = Some((attr, source));
+        self
+    }
+
+    /// Compare externalId ignoring case, for tenants with
+    /// `external_id_case_insensitive`
+    ///
+    /// The column holds the value as stored, so every operator compares it
+    /// lowercased with the lowercased filter value.
+    pub fn with_case_insensitive_external_id(mut self, enabled: bool) -> Self {
+        self.external_id_case_insensitive = enabled;
+        self
+    }
+
+    /// Row source with one `value` per element of a multi-valued attribute
+    fn elements(&self, attr: &str) -> String {
+        let attr = attr.to_lowercase();
+        match &self.external_elements {
+            Some((name, source)) if name.eq_ignore_ascii_case(&attr) => format!("({})", source),
+            _ => format!("json_each(data_norm, '$.{}')", attr),
+        }
     }
 }
 
@@ -29,14 +70,32 @@ impl FilterConverter for SqliteFilterConverter {
         filter: &FilterOperator,
         resource_type: ResourceType,
     ) -> AppResult<(String, Vec<String>)> {
+        // Attribute names become part of the SQL, so unknown ones never get this far
+        validate_filter_attributes(filter, resource_type)?;
         let mut params = Vec::new();
         let condition = self.convert_filter_to_sql(filter, resource_type, &mut params)?;
         Ok((condition, params))
     }
 
-    fn get_param_placeholder(&self, _index: usize) -> String {
-        // SQLite uses ? for parameter placeholders
-        "?".to_string()
+    fn get_param_placeholder(&self, index: usize) -> String {
+        // Numbered, so a condition can refer to a parameter bound earlier
+        format!("?{}", index)
+    }
+
+    fn get_not_equal(&self, left: &str, right: &str) -> String {
+        format!("{} IS NOT {}", left, right)
+    }
+
+    fn get_position_function(&self, haystack: &str, needle: &str) -> String {
+        format!("instr({}, {})", haystack, needle)
+    }
+
+    fn get_suffix_function(&self, expression: &str, suffix: &str) -> String {
+        format!(
+            "substr({e}, length({e}) - length({s}) + 1)",
+            e = expression,
+            s = suffix
+        )
     }
 
     fn is_case_insensitive_attribute(&self, attr: &str, resource_type: ResourceType) -> bool {
@@ -78,21 +137,32 @@ impl SqliteFilterConverter {
         resource_type: ResourceType,
         params: &mut Vec<String>,
     ) -> AppResult<String> {
+        if let Some(meta) = meta_filter(filter, resource_type)? {
+            return Ok(self.meta_filter_to_sql(meta, params));
+        }
+        if let Some(alternatives) = locale_filter(filter, resource_type) {
+            let mut conditions = Vec::with_capacity(alternatives.len());
+            for alternative in &alternatives {
+                conditions.push(self.convert_filter_to_sql(alternative, resource_type, params)?);
+            }
+            return Ok(format!("({})", conditions.join(" OR ")));
+        }
+
         match filter {
             FilterOperator::Equal(attr, value) => {
-                self.handle_equality(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::Equal, value, resource_type, params)
             }
             FilterOperator::NotEqual(attr, value) => {
-                self.handle_not_equality(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::NotEqual, value, resource_type, params)
             }
             FilterOperator::Contains(attr, value) => {
-                self.handle_contains(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::Contains, value, resource_type, params)
             }
             FilterOperator::StartsWith(attr, value) => {
-                self.handle_starts_with(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::StartsWith, value, resource_type, params)
             }
             FilterOperator::EndsWith(attr, value) => {
-                self.handle_ends_with(attr, value, resource_type, params)
+                self.handle_comparison(attr, StringMatch::EndsWith, value, resource_type, params)
             }
             FilterOperator::Present(attr) => self.handle_present(attr, resource_type),
             FilterOperator::GreaterThan(attr, value) => {
@@ -129,63 +199,52 @@ impl SqliteFilterConverter {
         }
     }
 
-    /// Handle equality comparison
-    fn handle_equality(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like "emails.value"
-        if attr.contains('.') {
-            let parts: Vec<&str> = attr.split('.').collect();
-            if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
-            {
-                return self.handle_multi_value_equality(parts[0], parts[1], value, params);
+    /// Render a filter on a `meta` sub-attribute
+    fn meta_filter_to_sql(&self, meta: MetaFilter, params: &mut Vec<String>) -> String {
+        match meta {
+            MetaFilter::Constant(true) => "1 = 1".to_string(),
+            MetaFilter::Constant(false) => "1 = 0".to_string(),
+            MetaFilter::Timestamp {
+                column,
+                operator,
+                value,
+            } => {
+                params.push(value);
+                // Columns hold RFC 3339 UTC text with 0, 3, 6 or 9 fraction
+                // digits; truncate it to milliseconds as responses do, since
+                // strftime('%f') rounds instead
+                format!(
+                    "substr({c}, 1, 19) || '.' || substr(CASE WHEN substr({c}, 20, 1) = '.' \
+                     THEN substr({c}, 21, 3) ELSE '' END || '000', 1, 3) || 'Z' {} ?{}",
+                    operator,
+                    params.len(),
+                    c = column
+                )
+            }
+            MetaFilter::Location {
+                endpoint,
+                operator,
+                value,
+            } => {
+                let path = format!("('{}' || id)", endpoint);
+                build_comparison_sql(
+                    self,
+                    &path,
+                    operator,
+                    &Value::String(value),
+                    true,
+                    params.len() + 1,
+                )
+                .bind(params)
             }
         }
-
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-
-        // Handle Boolean values specially
-        if let Value::Bool(bool_val) = value {
-            // For Boolean values, we compare with the JSON boolean representation
-            return Ok(format!(
-                "json_extract(data_norm, '$.{}') = {}",
-                json_path,
-                if *bool_val { "1" } else { "0" }
-            ));
-        }
-
-        // Check if this is a case-exact field
-        let is_case_exact = self.is_case_exact_field(attr, resource_type);
-        let data_column = if is_case_exact {
-            "data_orig"
-        } else {
-            "data_norm"
-        };
-
-        let value_str = self.value_to_string(value);
-        // For data_norm column, normalize values; for data_orig, preserve case
-        let comparison_value = if is_case_exact || !value.is_string() {
-            value_str
-        } else {
-            value_str.to_lowercase()
-        };
-        params.push(comparison_value);
-
-        Ok(format!(
-            "json_extract({}, '$.{}') = ?{}",
-            data_column, json_path, param_index
-        ))
     }
 
-    /// Handle not equality comparison
-    fn handle_not_equality(
+    /// Handle eq, ne, co, sw and ew
+    fn handle_comparison(
         &self,
         attr: &str,
+        operator: StringMatch,
         value: &Value,
         resource_type: ResourceType,
         params: &mut Vec<String>,
@@ -195,137 +254,144 @@ impl SqliteFilterConverter {
             let parts: Vec<&str> = attr.split('.').collect();
             if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
             {
-                return self.handle_multi_value_not_equality(parts[0], parts[1], value, params);
+                return Ok(self.handle_multi_value_comparison(
+                    parts[0],
+                    parts[1],
+                    resource_type,
+                    operator,
+                    value,
+                    params,
+                ));
             }
         }
 
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-
-        // Handle Boolean values specially
-        if let Value::Bool(bool_val) = value {
-            // For Boolean values, we compare with the JSON boolean representation
-            return Ok(format!(
-                "json_extract(data_norm, '$.{}') != {}",
-                json_path,
-                if *bool_val { "1" } else { "0" }
-            ));
-        }
-
-        // Check if this is a case-exact field
-        let is_case_exact = self.is_case_exact_field(attr, resource_type);
-        let data_column = if is_case_exact {
-            "data_orig"
-        } else {
-            "data_norm"
-        };
-
-        let value_str = self.value_to_string(value);
-        // For data_norm column, normalize values; for data_orig, preserve case
-        let comparison_value = if is_case_exact || !value.is_string() {
-            value_str
-        } else {
-            value_str.to_lowercase()
-        };
-        params.push(comparison_value);
-
-        Ok(format!(
-            "json_extract({}, '$.{}') != ?{}",
-            data_column, json_path, param_index
-        ))
-    }
-
-    /// Handle contains comparison
-    fn handle_contains(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like \"emails.value\"
-        if attr.contains('.') {
-            let parts: Vec<&str> = attr.split('.').collect();
-            if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
-            {
-                return self.handle_multi_value_contains(parts[0], parts[1], value, params);
+        // externalId is kept in its own indexed, case-exact column,
+        // compared lowercased for tenants matching it case-insensitively.
+        // Rows left out of it by the startup backfill for duplicating another
+        // row's externalId keep it NULL and are matched by their document.
+        if attr.eq_ignore_ascii_case("externalId") && value.is_string() {
+            let case_exact = !self.external_id_case_insensitive;
+            let document = "json_extract(data_orig, '$.externalId')";
+            let (column, document) = if case_exact {
+                ("external_id".to_string(), document.to_string())
+            } else {
+                (
+                    self.get_lower_function("external_id"),
+                    self.get_lower_function(document),
+                )
+            };
+            if operator == StringMatch::Equal {
+                let column_match = build_comparison_sql(
+                    self,
+                    &column,
+                    operator,
+                    value,
+                    case_exact,
+                    params.len() + 1,
+                )
+                .bind(params);
+                let document_match = build_comparison_sql(
+                    self,
+                    &document,
+                    operator,
+                    value,
+                    case_exact,
+                    params.len() + 1,
+                )
+                .bind(params);
+                return Ok(format!(
+                    "({} OR (external_id IS NULL AND {}))",
+                    column_match, document_match
+                ));
+            }
+            if !case_exact {
+                return Ok(build_comparison_sql(
+                    self,
+                    &document,
+                    operator,
+                    value,
+                    false,
+                    params.len() + 1,
+                )
+                .bind(params));
             }
         }
 
         let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}%", value_str));
-
-        Ok(format!(
-            "LOWER(json_extract(data_norm, '$.{}')) LIKE LOWER(?{})",
-            json_path, param_index
-        ))
-    }
 
-    /// Handle starts with comparison
-    fn handle_starts_with(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like \"emails.value\"
-        if attr.contains('.') {
-            let parts: Vec<&str> = attr.split('.').collect();
-            if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
-            {
-                return self.handle_multi_value_starts_with(parts[0], parts[1], value, params);
-            }
+        // json_extract returns JSON booleans as 1/0
+        if let (Value::Bool(bool_val), StringMatch::Equal | StringMatch::NotEqual) =
+            (value, operator)
+        {
+            let extracted = format!("json_extract(data_norm, '$.{}')", json_path);
+            let literal = if *bool_val { "1" } else { "0" };
+            return Ok(if operator == StringMatch::Equal {
+                format!("{} = {}", extracted, literal)
+            } else {
+                self.get_not_equal(&extracted, literal)
+            });
         }
 
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("{}%", value_str));
-
-        Ok(format!(
-            "LOWER(json_extract(data_norm, '$.{}')) LIKE LOWER(?{})",
-            json_path, param_index
-        ))
+        // Case-exact attributes are compared with their original case
+        let case_exact = self.is_case_exact_field(attr, resource_type);
+        let data_column = if case_exact { "data_orig" } else { "data_norm" };
+        let attr_path = format!("json_extract({}, '$.{}')", data_column, json_path);
+
+        Ok(build_comparison_sql(
+            self,
+            &attr_path,
+            operator,
+            value,
+            case_exact,
+            params.len() + 1,
+        )
+        .bind(params))
     }
 
-    /// Handle ends with comparison
-    fn handle_ends_with(
-        &self,
-        attr: &str,
-        value: &Value,
-        resource_type: ResourceType,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        // Check if this is a multi-valued attribute query like \"emails.value\"
+    /// Handle present comparison
+    ///
+    /// Null, empty strings, empty arrays and empty objects are not present
+    /// (RFC 7644 Section 3.4.2.2), so `emails pr` needs at least one element.
+    fn handle_present(&self, attr: &str, resource_type: ResourceType) -> AppResult<String> {
+        // Check if this is a multi-valued attribute query like "emails.value"
         if attr.contains('.') {
             let parts: Vec<&str> = attr.split('.').collect();
             if parts.len() == 2 && crate::schema::is_multi_valued_attribute(parts[0], resource_type)
             {
-                return self.handle_multi_value_ends_with(parts[0], parts[1], value, params);
+                return Ok(self.multi_value_present(parts[0], parts[1]));
             }
         }
 
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}", value_str));
+        if matches!(&self.external_elements, Some((name, _)) if name.eq_ignore_ascii_case(attr)) {
+            return Ok(format!("EXISTS (SELECT 1 FROM {})", self.elements(attr)));
+        }
 
+        // A bare multi-valued attribute is checked as a whole array, not its first value
+        let json_path = if crate::schema::is_multi_valued_attribute(attr, resource_type) {
+            attr.to_lowercase()
+        } else {
+            self.scim_path_to_json_path(attr, resource_type)
+        };
         Ok(format!(
-            "LOWER(json_extract(data_norm, '$.{}')) LIKE LOWER(?{})",
-            json_path, param_index
+            "CASE json_type(data_norm, '$.{path}') \
+             WHEN 'null' THEN 0 \
+             WHEN 'text' THEN json_extract(data_norm, '$.{path}') != '' \
+             WHEN 'array' THEN json_array_length(data_norm, '$.{path}') > 0 \
+             WHEN 'object' THEN json_extract(data_norm, '$.{path}') != '{{}}' \
+             ELSE json_type(data_norm, '$.{path}') IS NOT NULL END",
+            path = json_path
         ))
     }
 
-    /// Handle present comparison
-    fn handle_present(&self, attr: &str, resource_type: ResourceType) -> AppResult<String> {
-        let json_path = self.scim_path_to_json_path(attr, resource_type);
-        Ok(format!(
-            "json_extract(data_norm, '$.{}') IS NOT NULL AND json_extract(data_norm, '$.{}') != ''",
-            json_path, json_path
-        ))
+    /// Check that any element of a multi-valued attribute has a non-empty sub-attribute
+    fn multi_value_present(&self, attr_name: &str, sub_attr: &str) -> String {
+        format!(
+            "EXISTS (SELECT 1 FROM {elements} \
+             WHERE json_extract(value, '$.{sub}') IS NOT NULL \
+             AND json_extract(value, '$.{sub}') != '')",
+            elements = self.elements(attr_name),
+            sub = sub_attr.to_lowercase()
+        )
     }
 
     /// Handle greater than comparison
@@ -501,160 +567,155 @@ impl SqliteFilterConverter {
         &self,
         attr: &str,
         inner: &FilterOperator,
-        _resource_type: ResourceType,
+        resource_type: ResourceType,
         params: &mut Vec<String>,
     ) -> AppResult<String> {
-        // For complex filters, we need to check if any element in the array matches
-        // the inner condition. This is similar to multi-valued attribute handling
-        // but with recursive filter processing.
+        // Every condition inside the brackets must hold for the same array element,
+        // so the whole inner filter is evaluated per element of one json_each
+        let condition = self.element_condition(attr, inner, resource_type, params)?;
+        Ok(format!(
+            "EXISTS (SELECT 1 FROM {} WHERE {})",
+            self.elements(attr),
+            condition
+        ))
+    }
 
-        match inner {
-            FilterOperator::Equal(sub_attr, value) => {
-                self.handle_multi_value_equality(attr, sub_attr, value, params)
+    /// Convert a value path filter to a condition on the current json_each element
+    fn element_condition(
+        &self,
+        attr: &str,
+        filter: &FilterOperator,
+        resource_type: ResourceType,
+        params: &mut Vec<String>,
+    ) -> AppResult<String> {
+        match filter {
+            FilterOperator::Equal(sub_attr, value)
+            | FilterOperator::NotEqual(sub_attr, value)
+            | FilterOperator::Contains(sub_attr, value)
+            | FilterOperator::StartsWith(sub_attr, value)
+            | FilterOperator::EndsWith(sub_attr, value) => {
+                let operator = StringMatch::of(filter).unwrap_or(StringMatch::Equal);
+                Ok(self.element_comparison(attr, sub_attr, resource_type, operator, value, params))
             }
-            FilterOperator::NotEqual(sub_attr, value) => {
-                self.handle_multi_value_not_equality(attr, sub_attr, value, params)
+            FilterOperator::GreaterThan(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, ">", value, params))
             }
-            FilterOperator::Contains(sub_attr, value) => {
-                self.handle_multi_value_contains(attr, sub_attr, value, params)
+            FilterOperator::GreaterThanOrEqual(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, ">=", value, params))
             }
-            FilterOperator::StartsWith(sub_attr, value) => {
-                self.handle_multi_value_starts_with(attr, sub_attr, value, params)
+            FilterOperator::LessThan(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, "<", value, params))
             }
-            FilterOperator::EndsWith(sub_attr, value) => {
-                self.handle_multi_value_ends_with(attr, sub_attr, value, params)
+            FilterOperator::LessThanOrEqual(sub_attr, value) => {
+                Ok(self.element_ordering(sub_attr, "<=", value, params))
             }
-            FilterOperator::Present(sub_attr) => {
-                // For present check in arrays, check if any element has the sub-attribute
-                Ok(format!(
-                    "EXISTS (SELECT 1 FROM json_each(data_norm, '$.{}') WHERE json_extract(value, '$.{}') IS NOT NULL)",
-                    attr.to_lowercase(),
-                    sub_attr.to_lowercase()
-                ))
+            FilterOperator::Present(sub_attr) => Ok(format!(
+                "json_extract(value, '$.{sub}') IS NOT NULL \
+                 AND json_extract(value, '$.{sub}') != ''",
+                sub = sub_attr.to_lowercase()
+            )),
+            FilterOperator::And(left, right) => {
+                let left_sql = self.element_condition(attr, left, resource_type, params)?;
+                let right_sql = self.element_condition(attr, right, resource_type, params)?;
+                Ok(format!("({} AND {})", left_sql, right_sql))
             }
-            // For other operators like logical operators within complex filters,
-            // we would need more sophisticated handling
-            _ => Err(crate::error::AppError::FilterParse(format!(
-                "Unsupported complex filter operation for {}",
+            FilterOperator::Or(left, right) => {
+                let left_sql = self.element_condition(attr, left, resource_type, params)?;
+                let right_sql = self.element_condition(attr, right, resource_type, params)?;
+                Ok(format!("({} OR {})", left_sql, right_sql))
+            }
+            FilterOperator::Not(inner) => {
+                let inner_sql = self.element_condition(attr, inner, resource_type, params)?;
+                Ok(format!("NOT ({})", inner_sql))
+            }
+            FilterOperator::Complex(_, _) => Err(crate::error::AppError::FilterParse(format!(
+                "Nested value paths are not supported in {}",
                 attr
             ))),
         }
     }
 
-    /// Handle multi-valued attribute equality (e.g., emails.value)
-    fn handle_multi_value_equality(
-        &self,
-        attr_name: &str,
-        sub_attr: &str,
-        value: &Value,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        let normalized_value = if value.is_string() {
-            value_str.to_lowercase()
-        } else {
-            value_str
-        };
-        params.push(normalized_value);
-
-        // Use SQLite JSON functions to search in array
-        // This creates a query like: EXISTS (SELECT 1 FROM json_each(data_norm, '$.emails') WHERE json_extract(value, '$.value') = ?)
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM json_each(data_norm, '$.{}') WHERE json_extract(value, '$.{}') = ?{})",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
-    }
-
-    /// Handle multi-valued attribute not equality (e.g., emails.value ne)
-    fn handle_multi_value_not_equality(
-        &self,
-        attr_name: &str,
-        sub_attr: &str,
-        value: &Value,
-        params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        let normalized_value = if value.is_string() {
-            value_str.to_lowercase()
-        } else {
-            value_str
-        };
-        params.push(normalized_value);
-
-        // Use NOT EXISTS for not equality
-        Ok(format!(
-            "NOT EXISTS (SELECT 1 FROM json_each(data_norm, '$.{}') WHERE json_extract(value, '$.{}') = ?{})",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
-    }
-
-    /// Handle multi-valued attribute contains (e.g., emails.value co)
-    fn handle_multi_value_contains(
+    /// Compare a sub-attribute of the current element
+    fn element_comparison(
         &self,
-        attr_name: &str,
+        attr: &str,
         sub_attr: &str,
+        resource_type: ResourceType,
+        operator: StringMatch,
         value: &Value,
         params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}%", value_str));
+    ) -> String {
+        let extracted = format!("json_extract(value, '$.{}')", sub_attr.to_lowercase());
+
+        // json_extract returns booleans as 1/0
+        if let (Value::Bool(bool_val), StringMatch::Equal | StringMatch::NotEqual) =
+            (value, operator)
+        {
+            let literal = if *bool_val { "1" } else { "0" };
+            return if operator == StringMatch::Equal {
+                format!("{} = {}", extracted, literal)
+            } else {
+                self.get_not_equal(&extracted, literal)
+            };
+        }
 
-        // Use SQLite JSON functions to search in array with LIKE
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM json_each(data_norm, '$.{}') WHERE LOWER(json_extract(value, '$.{}')) LIKE LOWER(?{}))",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
+        // data_norm keeps the original case of case-exact sub-attributes
+        // (e.g. x509Certificates.value)
+        let case_exact = self.is_case_exact_field(&format!("{}.{}", attr, sub_attr), resource_type);
+        build_comparison_sql(
+            self,
+            &extracted,
+            operator,
+            value,
+            case_exact,
+            params.len() + 1,
+        )
+        .bind(params)
     }
 
-    /// Handle multi-valued attribute starts with (e.g., emails.value sw)
-    fn handle_multi_value_starts_with(
+    /// Order a sub-attribute of the current element numerically
+    fn element_ordering(
         &self,
-        attr_name: &str,
         sub_attr: &str,
+        operator: &str,
         value: &Value,
         params: &mut Vec<String>,
-    ) -> AppResult<String> {
+    ) -> String {
         let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("{}%", value_str));
-
-        // Use SQLite JSON functions to search in array with LIKE
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM json_each(data_norm, '$.{}') WHERE LOWER(json_extract(value, '$.{}')) LIKE LOWER(?{}))",
-            attr_name.to_lowercase(),
+        params.push(self.value_to_string(value));
+        format!(
+            "CAST(json_extract(value, '$.{}') AS REAL) {} CAST(?{} AS REAL)",
             sub_attr.to_lowercase(),
+            operator,
             param_index
-        ))
+        )
     }
 
-    /// Handle multi-valued attribute ends with (e.g., emails.value ew)
-    fn handle_multi_value_ends_with(
+    /// Handle a comparison on a sub-attribute of a multi-valued attribute
+    /// (e.g., emails.value co), which holds when any element matches
+    ///
+    /// `ne` holds when no element is equal.
+    fn handle_multi_value_comparison(
         &self,
         attr_name: &str,
         sub_attr: &str,
+        resource_type: ResourceType,
+        operator: StringMatch,
         value: &Value,
         params: &mut Vec<String>,
-    ) -> AppResult<String> {
-        let param_index = params.len() + 1;
-        let value_str = self.value_to_string(value);
-        params.push(format!("%{}", value_str));
-
-        // Use SQLite JSON functions to search in array with LIKE
-        Ok(format!(
-            "EXISTS (SELECT 1 FROM json_each(data_norm, '$.{}') WHERE LOWER(json_extract(value, '$.{}')) LIKE LOWER(?{}))",
-            attr_name.to_lowercase(),
-            sub_attr.to_lowercase(),
-            param_index
-        ))
+    ) -> String {
+        let (exists, operator) = match operator {
+            StringMatch::NotEqual => ("NOT EXISTS", StringMatch::Equal),
+            operator => ("EXISTS", operator),
+        };
+        let condition =
+            self.element_comparison(attr_name, sub_attr, resource_type, operator, value, params);
+        format!(
+            "{} (SELECT 1 FROM {} WHERE {})",
+            exists,
+            self.elements(attr_name),
+            condition
+        )
     }
 
     /// Convert JSON Value to string for SQL parameters
@@ -765,4 +826,184 @@ mod tests {
         assert_eq!(condition, "NOT (EXISTS (SELECT 1 FROM json_each(data_norm, '$.emails') WHERE json_extract(value, '$.value') = ?1))");
         assert_eq!(params, vec!["alice@example.com"]);
     }
+
+    #[test]
+    fn test_complex_filter_conditions_share_one_element() {
+        let converter = SqliteFilterConverter::new();
+        let inner_filter = FilterOperator::And(
+            Box::new(FilterOperator::Equal(
+                "type".to_string(),
+                Value::String("work".to_string()),
+            )),
+            Box::new(FilterOperator::Contains(
+                "value".to_string(),
+                Value::String("@corp.com".to_string()),
+            )),
+        );
+        let complex_filter = FilterOperator::Complex("emails".to_string(), Box::new(inner_filter));
+
+        let (condition, params) = converter
+            .to_where_clause(&complex_filter, ResourceType::User)
+            .unwrap();
+
+        assert_eq!(condition, "EXISTS (SELECT 1 FROM json_each(data_norm, '$.emails') WHERE (json_extract(value, '$.type') = ?1 AND LOWER(json_extract(value, '$.value')) LIKE LOWER(?2)))");
+        assert_eq!(params, vec!["work", "%@corp.com%"]);
+    }
+
+    #[test]
+    fn test_comparison_case_sensitivity_matrix() {
+        let converter = SqliteFilterConverter::new();
+        let value = || Value::String("AbC".to_string());
+        let cases = [
+            (
+                FilterOperator::Equal("userName".to_string(), value()),
+                "json_extract(data_norm, '$.username') = ?1",
+                vec!["abc"],
+            ),
+            (
+                FilterOperator::NotEqual("userName".to_string(), value()),
+                "json_extract(data_norm, '$.username') IS NOT ?1",
+                vec!["abc"],
+            ),
+            (
+                FilterOperator::Contains("userName".to_string(), value()),
+                "LOWER(json_extract(data_norm, '$.username')) LIKE LOWER(?1)",
+                vec!["%abc%"],
+            ),
+            (
+                FilterOperator::StartsWith("userName".to_string(), value()),
+                "LOWER(json_extract(data_norm, '$.username')) LIKE LOWER(?1)",
+                vec!["abc%"],
+            ),
+            (
+                FilterOperator::EndsWith("userName".to_string(), value()),
+                "LOWER(json_extract(data_norm, '$.username')) LIKE LOWER(?1)",
+                vec!["%abc"],
+            ),
+            (
+                FilterOperator::Equal("externalId".to_string(), value()),
+                "(external_id = ?1 OR (external_id IS NULL AND \
+                 json_extract(data_orig, '$.externalId') = ?2))",
+                vec!["AbC", "AbC"],
+            ),
+            (
+                FilterOperator::NotEqual("externalId".to_string(), value()),
+                "json_extract(data_orig, '$.externalId') IS NOT ?1",
+                vec!["AbC"],
+            ),
+            (
+                FilterOperator::Contains("externalId".to_string(), value()),
+                "instr(json_extract(data_orig, '$.externalId'), ?1) > 0",
+                vec!["AbC"],
+            ),
+            (
+                FilterOperator::StartsWith("externalId".to_string(), value()),
+                "instr(json_extract(data_orig, '$.externalId'), ?1) = 1",
+                vec!["AbC"],
+            ),
+            (
+                FilterOperator::EndsWith("externalId".to_string(), value()),
+                "substr(json_extract(data_orig, '$.externalId'), \
+                 length(json_extract(data_orig, '$.externalId')) - length(?1) + 1) = ?2",
+                vec!["AbC", "AbC"],
+            ),
+        ];
+
+        for (filter, expected_sql, expected_params) in cases {
+            let (condition, params) = converter
+                .to_where_clause(&filter, ResourceType::User)
+                .unwrap();
+            assert_eq!(condition, expected_sql, "{:?}", filter);
+            assert_eq!(params, expected_params, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_not_equal_filter_conversion() {
+        let converter = SqliteFilterConverter::new();
+        let filter = FilterOperator::NotEqual(
+            "userType".to_string(),
+            Value::String("Employee".to_string()),
+        );
+
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+
+        // Null-safe so users without userType also match
+        assert_eq!(condition, "json_extract(data_norm, '$.usertype') IS NOT ?1");
+        assert_eq!(params, vec!["employee"]);
+    }
+
+    #[test]
+    fn test_meta_filter_conversion() {
+        let converter = SqliteFilterConverter::new();
+
+        // resourceType is decided by the endpoint, not the stored data
+        let filter = FilterOperator::Equal(
+            "meta.resourceType".to_string(),
+            Value::String("Group".to_string()),
+        );
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+        assert_eq!(condition, "1 = 0");
+        assert!(params.is_empty());
+
+        let filter = FilterOperator::Contains(
+            "meta.location".to_string(),
+            Value::String("https://example.com/scim/v2/Users/abc".to_string()),
+        );
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+        assert_eq!(condition, "instr(('/Users/' || id), ?1) > 0");
+        assert_eq!(params, vec!["/Users/abc"]);
+
+        // Offsets are normalized to UTC milliseconds
+        let filter = FilterOperator::GreaterThan(
+            "meta.lastModified".to_string(),
+            Value::String("2024-01-01T09:00:00+09:00".to_string()),
+        );
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::Group)
+            .unwrap();
+        assert!(condition.starts_with("substr(updated_at, 1, 19)"));
+        assert_eq!(params, vec!["2024-01-01T00:00:00.000Z"]);
+
+        let filter = FilterOperator::GreaterThan(
+            "meta.location".to_string(),
+            Value::String("x".to_string()),
+        );
+        assert!(converter
+            .to_where_clause(&filter, ResourceType::User)
+            .is_err());
+    }
+
+    #[test]
+    fn test_filter_uses_elements_source() {
+        let converter = SqliteFilterConverter::new()
+            .with_elements_source("members", "SELECT m.value FROM m".to_string());
+        let filter = FilterOperator::Contains(
+            "members.display".to_string(),
+            Value::String("Alice".to_string()),
+        );
+
+        let (condition, params) = converter
+            .to_where_clause(&filter, ResourceType::Group)
+            .unwrap();
+
+        assert_eq!(
+            condition,
+            "EXISTS (SELECT 1 FROM (SELECT m.value FROM m) WHERE LOWER(json_extract(value, '$.display')) LIKE LOWER(?1))"
+        );
+        assert_eq!(params, vec!["%alice%"]);
+
+        // Other multi-valued attributes still read data_norm
+        let filter = FilterOperator::Present("emails.value".to_string());
+        let (condition, _) = converter
+            .to_where_clause(&filter, ResourceType::User)
+            .unwrap();
+        assert!(condition.contains("data_norm"));
+    }
 }
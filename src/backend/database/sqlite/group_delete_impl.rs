@@ -1,32 +1,31 @@
 use async_trait::async_trait;
-use sqlx::SqlitePool;
 
 use super::super::group_delete::GroupDeleter;
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 
 /// SQLite-specific implementation of GroupDeleter
 ///
-/// This handles SQLite's TEXT-based IDs and transactional group deletion
-/// with cascading membership cleanup.
-pub struct SqliteGroupDeleter {
-    pool: SqlitePool,
-}
+/// This handles SQLite's TEXT-based IDs and group deletion with cascading
+/// membership cleanup.
+#[derive(Default)]
+pub struct SqliteGroupDeleter;
 
 impl SqliteGroupDeleter {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 }
 
 #[async_trait]
 impl GroupDeleter for SqliteGroupDeleter {
-    async fn execute_group_delete(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
-        // Begin transaction for atomic group + membership deletion
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))?;
+    async fn execute_group_delete(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        let conn = tx.sqlite()?;
 
         // First, delete group memberships where this group is the parent
         let membership_table = format!("`t{}_group_memberships`", tenant_id);
@@ -34,7 +33,7 @@ impl GroupDeleter for SqliteGroupDeleter {
 
         sqlx::query(&parent_membership_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to delete group parent memberships: {}", e))
@@ -48,7 +47,7 @@ impl GroupDeleter for SqliteGroupDeleter {
 
         sqlx::query(&child_membership_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to delete group child memberships: {}", e))
@@ -60,18 +59,11 @@ impl GroupDeleter for SqliteGroupDeleter {
 
         let result = sqlx::query(&group_sql)
             .bind(id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| AppError::Database(format!("Failed to delete group: {}", e)))?;
 
-        let group_deleted = result.rows_affected() > 0;
-
-        // Commit transaction
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
-
-        Ok(group_deleted)
+        Ok(result.rows_affected() > 0)
     }
 }
 
@@ -79,14 +71,9 @@ impl GroupDeleter for SqliteGroupDeleter {
 mod tests {
     use super::*;
 
-    async fn create_test_pool() -> SqlitePool {
-        SqlitePool::connect(":memory:").await.unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_deleter_creation() {
-        let pool = create_test_pool().await;
-        let deleter = SqliteGroupDeleter::new(pool);
+    #[test]
+    fn test_deleter_creation() {
+        let deleter = SqliteGroupDeleter::new();
         // Just verify the deleter can be created
         assert!(!format!("{:?}", &deleter as *const _).is_empty());
     }
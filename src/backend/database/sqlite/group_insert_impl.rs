@@ -1,27 +1,22 @@
 use async_trait::async_trait;
 use serde_json::Value;
-use sqlx::SqlitePool;
+use sqlx::SqliteConnection;
 
-use super::super::group_insert::{GroupInserter, PreparedGroupData};
-use super::super::group_read::GroupReader;
-use super::group_read_impl::SqliteGroupReader;
+use super::super::group_insert::{map_membership_insert_error, GroupInserter, PreparedGroupData};
+use super::super::DbTransaction;
+use super::group_update_impl::fetch_group_with_members;
 use crate::error::{AppError, AppResult};
 use crate::models::Group;
 
 /// SQLite-specific implementation of GroupInserter
 ///
 /// This handles SQLite's JSON TEXT storage while using shared SQL generation.
-pub struct SqliteGroupInserter {
-    pool: SqlitePool,
-    group_reader: SqliteGroupReader,
-}
+#[derive(Default)]
+pub struct SqliteGroupInserter;
 
 impl SqliteGroupInserter {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self {
-            group_reader: SqliteGroupReader::new(pool.clone()),
-            pool,
-        }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Convert JSON Value to String for SQLite TEXT storage
@@ -32,6 +27,7 @@ impl SqliteGroupInserter {
     /// Check for case-insensitive duplicate displayName
     async fn check_duplicate_display_name(
         &self,
+        conn: &mut SqliteConnection,
         tenant_id: u32,
         display_name: &str,
     ) -> AppResult<()> {
@@ -43,7 +39,7 @@ impl SqliteGroupInserter {
 
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(display_name)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate displayName: {}", e))
@@ -63,24 +59,21 @@ impl SqliteGroupInserter {
 impl GroupInserter for SqliteGroupInserter {
     async fn execute_group_insert(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         data: PreparedGroupData,
     ) -> AppResult<Group> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.sqlite()?;
+
         // Check for case-insensitive duplicate displayName before insertion
-        self.check_duplicate_display_name(tenant_id, &data.display_name)
+        self.check_duplicate_display_name(conn, tenant_id, &data.display_name)
             .await?;
 
-        // Begin transaction for atomic group + membership insertion
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))?;
-
         // Insert the group record
         let group_table = format!("t{}_groups", tenant_id);
         let group_sql = format!(
-            "INSERT INTO {} (id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO {} (id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at, created_by, last_modified_by) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
             group_table
         );
 
@@ -97,7 +90,8 @@ impl GroupInserter for SqliteGroupInserter {
             .bind(1i64) // version = 1 for new records
             .bind(data.timestamp)
             .bind(data.timestamp)
-            .execute(&mut *tx)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| super::user_insert_impl::map_database_error(e, "Group"))?;
 
@@ -117,26 +111,15 @@ impl GroupInserter for SqliteGroupInserter {
                         .bind(&data.id)
                         .bind(member_id)
                         .bind(member_type)
-                        .execute(&mut *tx)
+                        .execute(&mut *conn)
                         .await
-                        .map_err(|e| {
-                            AppError::Database(format!("Failed to insert group member: {}", e))
-                        })?;
+                        .map_err(|e| map_membership_insert_error(e, member_id))?;
                 }
             }
         }
 
-        // Commit transaction
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
-
         // Fetch the created group with properly populated members
-        match self
-            .group_reader
-            .find_group_by_id(tenant_id, &data.group.base.id)
-            .await?
-        {
+        match fetch_group_with_members(conn, tenant_id, &data.group.base.id).await? {
             Some(group) => Ok(group),
             None => Err(AppError::Database(
                 "Failed to fetch created group".to_string(),
@@ -149,14 +132,9 @@ impl GroupInserter for SqliteGroupInserter {
 mod tests {
     use super::*;
 
-    async fn create_test_pool() -> SqlitePool {
-        SqlitePool::connect(":memory:").await.unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_json_value_to_string() {
-        let pool = create_test_pool().await;
-        let inserter = SqliteGroupInserter::new(pool);
+    #[test]
+    fn test_json_value_to_string() {
+        let inserter = SqliteGroupInserter::new();
 
         let test_value = serde_json::json!({"test": "value"});
         let result = inserter.json_value_to_string(&test_value);
@@ -1,27 +1,46 @@
 use async_trait::async_trait;
 use scim_v2::models::group::Member;
-use serde_json::Value;
 use sqlx::{Row, SqlitePool};
+use std::collections::HashSet;
+use std::sync::Arc;
 
 use super::super::group_read::GroupReader;
-use super::super::group_update::UnifiedGroupUpdateOps;
-use super::SqliteGroupUpdater;
 use crate::backend::database::filter::FilterConverter;
+use crate::backend::database::list_query::{page_bounds, ListQuery};
 use crate::error::{AppError, AppResult};
-use crate::models::{Group, ScimPatchOp};
+use crate::models::Group;
 use crate::parser::filter_operator::FilterOperator;
-use crate::parser::patch_parser::ScimPath;
 use crate::parser::ResourceType;
 use crate::parser::{SortOrder, SortSpec};
 
+/// Display name of a membership row `m` joined to its user `u` or group `g`
+const MEMBER_DISPLAY_SQL: &str = "CASE \
+    WHEN m.member_type = 'User' THEN COALESCE(\
+        json_extract(u.data_orig, '$.displayName'), \
+        json_extract(u.data_orig, '$.name.formatted'), \
+        (json_extract(u.data_orig, '$.name.givenName') || ' ' || json_extract(u.data_orig, '$.name.familyName'))) \
+    WHEN m.member_type = 'Group' THEN json_extract(g.data_orig, '$.displayName') \
+    END";
+
 /// SQLite-specific implementation of GroupReader
 pub struct SqliteGroupReader {
     pool: SqlitePool,
+    /// See `DatabaseBackendConfig::external_id_case_insensitive_tenants`
+    case_insensitive_external_ids: Arc<HashSet<u32>>,
 }
 
 impl SqliteGroupReader {
     pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            case_insensitive_external_ids: Arc::default(),
+        }
+    }
+
+    /// Match externalId case-insensitively in the given tenants
+    pub fn with_case_insensitive_external_ids(mut self, tenants: Arc<HashSet<u32>>) -> Self {
+        self.case_insensitive_external_ids = tenants;
+        self
     }
 
     /// Generate table name for a resource type and tenant
@@ -46,19 +65,29 @@ impl SqliteGroupReader {
 
     /// Convert SCIM attribute to SQLite column or JSON path for sorting
     fn get_sort_column(&self, sort_spec: &SortSpec) -> String {
-        match sort_spec.attribute.as_str() {
+        // Attribute names are case-insensitive (RFC 7643 Section 2.1), so match on the
+        // lowercased name; meta timestamps map to native columns instead of JSON extraction
+        match sort_spec.attribute.to_lowercase().as_str() {
             // Special attributes stored in dedicated columns
-            "displayName" => "LOWER(display_name)".to_string(),
+            "displayname" => "LOWER(display_name)".to_string(),
             "id" => "id".to_string(),
-            "externalId" => "external_id".to_string(),
+            "externalid" => "external_id".to_string(),
             "meta.created" => "created_at".to_string(),
-            "meta.lastModified" => "updated_at".to_string(),
+            "meta.lastmodified" => "updated_at".to_string(),
+            // The attribute is interpolated into SQL, so anything that is not a plain
+            // (dotted) attribute name falls back to the default ordering
+            attr if !attr
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') =>
+            {
+                "created_at".to_string()
+            }
             // JSON attributes - use case-insensitive sorting
             _ => {
-                // Normalize attribute name to lowercase for JSON path
-                let normalized_attr = sort_spec.attribute.to_lowercase();
-                let json_path = normalized_attr;
-                format!("LOWER(json_extract(data_orig, '$.{}'))", json_path)
+                // data_norm stores lowercased attribute names, so the lowercased
+                // (possibly dotted) attribute can be used as the JSON path directly
+                let json_path = sort_spec.attribute.to_lowercase();
+                format!("LOWER(json_extract(data_norm, '$.{}'))", json_path)
             }
         }
     }
@@ -72,9 +101,10 @@ impl SqliteGroupReader {
                     SortOrder::Ascending => "ASC",
                     SortOrder::Descending => "DESC",
                 };
-                format!(" ORDER BY {} {}", column, direction)
+                format!(" ORDER BY {} {}, id {}", column, direction, direction)
             }
-            None => " ORDER BY created_at".to_string(), // Default sort
+            // Default sort, with id breaking ties between rows created together
+            None => " ORDER BY created_at, id".to_string(),
         }
     }
 
@@ -110,7 +140,7 @@ impl SqliteGroupReader {
                     let created_at: String = row.get("created_at");
                     let updated_at: String = row.get("updated_at");
                     let meta = scim_v2::models::scim_schema::Meta {
-                        resource_type: Some("Group".to_string()),
+                        resource_type: Some(ResourceType::Group.scim_name().to_string()),
                         created: Some(created_at),
                         last_modified: Some(updated_at),
                         location: None,
@@ -145,21 +175,14 @@ impl SqliteGroupReader {
             SELECT 
                 m.member_id,
                 m.member_type,
-                CASE 
-                    WHEN m.member_type = 'User' THEN COALESCE(
-                        json_extract(u.data_orig, '$.displayName'),
-                        json_extract(u.data_orig, '$.name.formatted'),
-                        (json_extract(u.data_orig, '$.name.givenName') || ' ' || json_extract(u.data_orig, '$.name.familyName'))
-                    )
-                    WHEN m.member_type = 'Group' THEN json_extract(g.data_orig, '$.displayName')
-                END as display_name
+                {} as display_name
             FROM {} m
             LEFT JOIN {} u ON m.member_id = u.id AND m.member_type = 'User'
             LEFT JOIN {} g ON m.member_id = g.id AND m.member_type = 'Group'
             WHERE m.group_id = ?1
             ORDER BY m.created_at
             "#,
-            memberships_table, users_table, groups_table
+            MEMBER_DISPLAY_SQL, memberships_table, users_table, groups_table
         );
 
         let rows = sqlx::query(&sql)
@@ -174,12 +197,13 @@ impl SqliteGroupReader {
             let member_type: String = row.get("member_type");
             let display_name: Option<String> = row.get("display_name");
 
-            // Construct the proper $ref path based on member type (base URL will be added later)
-            let ref_path = match member_type.as_str() {
-                "User" => format!("/{}/Users/{}", tenant_id, member_id),
-                "Group" => format!("/{}/Groups/{}", tenant_id, member_id),
-                _ => format!("/{}/Resources/{}", tenant_id, member_id),
+            // Tenant-relative $ref by member type; the resource handler resolves the base URL
+            let resource_type = match member_type.as_str() {
+                "User" => "Users",
+                "Group" => "Groups",
+                _ => "Resources",
             };
+            let ref_path = crate::utils::build_tenant_ref(tenant_id, resource_type, &member_id);
 
             members.push(Member {
                 value: Some(member_id),
@@ -192,11 +216,93 @@ impl SqliteGroupReader {
         Ok(members)
     }
 
+    /// Members of the outer group row as normalized JSON objects, for `members` filters
+    fn members_source(&self, tenant_id: u32) -> String {
+        let groups_table = self.groups_table(tenant_id);
+        format!(
+            "SELECT json_object('value', m.member_id, 'type', LOWER(m.member_type), \
+             'display', LOWER({display})) AS value \
+             FROM {memberships} m \
+             LEFT JOIN {users} u ON m.member_id = u.id AND m.member_type = 'User' \
+             LEFT JOIN {groups} g ON m.member_id = g.id AND m.member_type = 'Group' \
+             WHERE m.group_id = {groups}.id",
+            display = MEMBER_DISPLAY_SQL,
+            memberships = self.memberships_table(tenant_id),
+            users = self.users_table(tenant_id),
+            groups = groups_table
+        )
+    }
+
     /// Create a filter converter for this tenant
     fn filter_converter(
         &self,
+        tenant_id: u32,
     ) -> crate::backend::database::sqlite::filter_impl::SqliteFilterConverter {
         crate::backend::database::sqlite::filter_impl::SqliteFilterConverter::new()
+            .with_elements_source("members", self.members_source(tenant_id))
+            .with_case_insensitive_external_id(
+                self.case_insensitive_external_ids.contains(&tenant_id),
+            )
+    }
+
+    /// One page of the groups matching an optional filter, with the number of matches
+    async fn find_groups_page(
+        &self,
+        tenant_id: u32,
+        filter: Option<&FilterOperator>,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        let (condition, params) = match filter {
+            Some(filter) => {
+                let (condition, params) = self
+                    .filter_converter(tenant_id)
+                    .to_where_clause(filter, ResourceType::Group)?;
+                (Some(condition), params)
+            }
+            None => (None, Vec::new()),
+        };
+        let query = ListQuery::new(&self.groups_table(tenant_id), condition.as_deref());
+
+        let count_sql = query.count_sql();
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for param in &params {
+            count_query = count_query.bind(param);
+        }
+        let total = count_query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count groups: {}", e)))?
+            .0;
+
+        let (limit, offset) = page_bounds(start_index, count);
+        let sql = query.page_sql(
+            "id",
+            &self.build_order_by(sort_spec),
+            &format!("?{}", params.len() + 1),
+            &format!("?{}", params.len() + 2),
+        );
+        let mut page_query = sqlx::query(&sql);
+        for param in &params {
+            page_query = page_query.bind(param);
+        }
+        let rows = page_query
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch groups: {}", e)))?;
+
+        let mut groups = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            if let Some(group) = self.fetch_group_with_members(tenant_id, &id).await? {
+                groups.push(group);
+            }
+        }
+
+        Ok((groups, total))
     }
 }
 
@@ -234,46 +340,57 @@ impl GroupReader for SqliteGroupReader {
         }
     }
 
-    async fn find_all_groups(
+    async fn find_group_by_external_id(
         &self,
         tenant_id: u32,
-        start_index: Option<i64>,
-        count: Option<i64>,
-    ) -> AppResult<(Vec<Group>, i64)> {
+        external_id: &str,
+    ) -> AppResult<Option<Group>> {
         let table_name = self.groups_table(tenant_id);
-
-        // Get total count
-        let count_sql = format!("SELECT COUNT(*) FROM {}", table_name);
-        let total: (i64,) = sqlx::query_as(&count_sql)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to count groups: {}", e)))?;
-
-        // Get groups with pagination
-        let offset = start_index.unwrap_or(1).saturating_sub(1).max(0);
-        let limit = count.unwrap_or(100).min(1000); // Max 1000 per page
-
+        let (column, document, external_id) =
+            if self.case_insensitive_external_ids.contains(&tenant_id) {
+                (
+                    "LOWER(external_id)",
+                    "LOWER(json_extract(data_orig, '$.externalId'))",
+                    external_id.to_lowercase(),
+                )
+            } else {
+                (
+                    "external_id",
+                    "json_extract(data_orig, '$.externalId')",
+                    external_id.to_string(),
+                )
+            };
+        // Rows the startup backfill left out of the column match by their document
         let sql = format!(
-            "SELECT id FROM {} ORDER BY created_at LIMIT ?1 OFFSET ?2",
-            table_name
+            "SELECT id FROM {} WHERE {} = ?1 OR (external_id IS NULL AND {} = ?1)",
+            table_name, column, document
         );
 
-        let rows = sqlx::query(&sql)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
+        let row = sqlx::query(&sql)
+            .bind(&external_id)
+            .fetch_optional(&self.pool)
             .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch groups: {}", e)))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to find group by externalId: {}", e))
+            })?;
 
-        let mut groups = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(group) = self.fetch_group_with_members(tenant_id, &id).await? {
-                groups.push(group);
+        match row {
+            Some(row) => {
+                let id: String = row.get("id");
+                self.fetch_group_with_members(tenant_id, &id).await
             }
+            None => Ok(None),
         }
+    }
 
-        Ok((groups, total.0))
+    async fn find_all_groups(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.find_groups_page(tenant_id, None, start_index, count, None)
+            .await
     }
 
     async fn find_all_groups_sorted(
@@ -283,45 +400,8 @@ impl GroupReader for SqliteGroupReader {
         count: Option<i64>,
         sort_spec: Option<&SortSpec>,
     ) -> AppResult<(Vec<Group>, i64)> {
-        if sort_spec.is_none() {
-            return self.find_all_groups(tenant_id, start_index, count).await;
-        }
-
-        let table_name = self.groups_table(tenant_id);
-
-        // Get total count
-        let count_sql = format!("SELECT COUNT(*) FROM {}", table_name);
-        let total: (i64,) = sqlx::query_as(&count_sql)
-            .fetch_one(&self.pool)
+        self.find_groups_page(tenant_id, None, start_index, count, sort_spec)
             .await
-            .map_err(|e| AppError::Database(format!("Failed to count groups: {}", e)))?;
-
-        // Get groups with pagination and sorting
-        let offset = start_index.unwrap_or(1).saturating_sub(1).max(0);
-        let limit = count.unwrap_or(100).min(1000); // Max 1000 per page
-
-        let order_by = self.build_order_by(sort_spec);
-        let sql = format!(
-            "SELECT id FROM {}{} LIMIT ?1 OFFSET ?2",
-            table_name, order_by
-        );
-
-        let rows = sqlx::query(&sql)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch sorted groups: {}", e)))?;
-
-        let mut groups = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(group) = self.fetch_group_with_members(tenant_id, &id).await? {
-                groups.push(group);
-            }
-        }
-
-        Ok((groups, total.0))
     }
 
     async fn find_groups_by_filter(
@@ -332,140 +412,7 @@ impl GroupReader for SqliteGroupReader {
         count: Option<i64>,
         sort_spec: Option<&SortSpec>,
     ) -> AppResult<(Vec<Group>, i64)> {
-        let table_name = self.groups_table(tenant_id);
-
-        // Convert filter to SQL
-        let (where_clause, params) = self
-            .filter_converter()
-            .to_where_clause(filter, ResourceType::Group)?;
-
-        // Get total count with filter
-        let count_sql = format!(
-            "SELECT COUNT(*) FROM {} WHERE ({})",
-            table_name, where_clause
-        );
-
-        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
-        for param in &params {
-            count_query = count_query.bind(param);
-        }
-
-        let total = count_query
-            .fetch_one(&self.pool)
+        self.find_groups_page(tenant_id, Some(filter), start_index, count, sort_spec)
             .await
-            .map_err(|e| AppError::Database(format!("Failed to count filtered groups: {}", e)))?
-            .0;
-
-        // Get groups with filter and pagination
-        let offset = start_index.unwrap_or(1).saturating_sub(1).max(0);
-        let limit = count.unwrap_or(100).min(1000);
-
-        let order_by = self.build_order_by(sort_spec);
-        let sql = format!(
-            "SELECT id FROM {} WHERE ({}){} LIMIT ?{} OFFSET ?{}",
-            table_name,
-            where_clause,
-            order_by,
-            params.len() + 1,
-            params.len() + 2
-        );
-
-        let mut query = sqlx::query(&sql);
-        for param in &params {
-            query = query.bind(param);
-        }
-        query = query.bind(limit).bind(offset);
-
-        let rows = query
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch filtered groups: {}", e)))?;
-
-        let mut groups = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(group) = self.fetch_group_with_members(tenant_id, &id).await? {
-                groups.push(group);
-            }
-        }
-
-        Ok((groups, total))
-    }
-
-    async fn find_groups_by_user_id(&self, tenant_id: u32, user_id: &str) -> AppResult<Vec<Group>> {
-        // Return empty for invalid or empty user IDs
-        if user_id.is_empty() || user_id == "default_id" {
-            return Ok(Vec::new());
-        }
-        let groups_table = self.groups_table(tenant_id);
-        let memberships_table = self.memberships_table(tenant_id);
-
-        let sql = format!(
-            r#"
-            SELECT g.id
-            FROM {} g
-            INNER JOIN {} m ON g.id = m.group_id
-            WHERE m.member_id = ?1 AND m.member_type = 'User'
-            ORDER BY g.created_at
-            "#,
-            groups_table, memberships_table
-        );
-
-        let rows = sqlx::query(&sql)
-            .bind(user_id)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to find groups by user: {}", e)))?;
-
-        let mut groups = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(group) = self.fetch_group_with_members(tenant_id, &id).await? {
-                groups.push(group);
-            }
-        }
-
-        Ok(groups)
-    }
-
-    async fn patch_group(
-        &self,
-        tenant_id: u32,
-        id: &str,
-        patch_ops: &ScimPatchOp,
-    ) -> AppResult<Option<Group>> {
-        // Return None for empty IDs
-        if id.is_empty() {
-            return Ok(None);
-        }
-
-        // First, find the existing group
-        let mut group = match self.find_group_by_id(tenant_id, id).await? {
-            Some(group) => group,
-            None => return Ok(None),
-        };
-
-        // Apply patch operations
-        for operation in &patch_ops.operations {
-            let scim_path = ScimPath::parse(&operation.path.clone().unwrap_or_default())?;
-
-            // Convert group to JSON for patch operations
-            let mut group_json = serde_json::to_value(&group).map_err(AppError::Serialization)?;
-
-            // Apply the operation
-            scim_path.apply_operation(
-                &mut group_json,
-                &operation.op,
-                &operation.value.as_ref().unwrap_or(&Value::Null).clone(),
-            )?;
-
-            // Convert back to Group
-            group = serde_json::from_value(group_json).map_err(AppError::Serialization)?;
-        }
-
-        // Use the new update system to save the patched group
-        let group_updater = SqliteGroupUpdater::new(self.pool.clone());
-        let update_ops = UnifiedGroupUpdateOps::new(group_updater);
-        update_ops.update_group(tenant_id, id, &group).await
     }
 }
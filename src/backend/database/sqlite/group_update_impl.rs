@@ -1,27 +1,29 @@
 use async_trait::async_trait;
 use serde_json::Value;
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection};
 
+use super::super::group_insert::map_membership_insert_error;
 use super::super::group_update::{GroupUpdater, PreparedGroupUpdateData};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::Group;
 
 /// SQLite-specific implementation of GroupUpdater
 ///
 /// This handles SQLite's TEXT-based ID storage, JSON TEXT format,
-/// and transactional group membership management.
-pub struct SqliteGroupUpdater {
-    pool: SqlitePool,
-}
+/// and group membership management.
+#[derive(Default)]
+pub struct SqliteGroupUpdater;
 
 impl SqliteGroupUpdater {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Check for case-insensitive duplicate displayName excluding current group
     async fn check_duplicate_display_name(
         &self,
+        conn: &mut SqliteConnection,
         tenant_id: u32,
         display_name: &str,
         exclude_id: &str,
@@ -35,7 +37,7 @@ impl SqliteGroupUpdater {
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(display_name)
             .bind(exclude_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate displayName: {}", e))
@@ -47,149 +49,24 @@ impl SqliteGroupUpdater {
 
         Ok(())
     }
-
-    /// Helper function to fetch a group with its members
-    async fn fetch_group_with_members(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
-        // Return None for empty IDs
-        if id.is_empty() {
-            return Ok(None);
-        }
-
-        let table_name = format!("`t{}_groups`", tenant_id);
-        let sql = format!(
-            "SELECT id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE id = ?1",
-            table_name
-        );
-
-        let row = sqlx::query(&sql)
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
-
-        match row {
-            Some(row) => {
-                let data_orig: String = row.get("data_orig");
-                let mut group: Group =
-                    serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
-
-                // Set version in meta (ensure meta exists)
-                let version: i64 = row.get("version");
-                if group.meta().is_none() {
-                    // Create meta if it doesn't exist
-                    let created_at: String = row.get("created_at");
-                    let updated_at: String = row.get("updated_at");
-                    let meta = scim_v2::models::scim_schema::Meta {
-                        resource_type: Some("Group".to_string()),
-                        created: Some(created_at),
-                        last_modified: Some(updated_at),
-                        location: None,
-                        version: Some(format!("W/\"{}\"", version)),
-                    };
-                    *group.meta_mut() = Some(meta);
-                } else {
-                    // Update existing meta with version
-                    if let Some(ref mut meta) = group.meta_mut() {
-                        meta.version = Some(format!("W/\"{}\"", version));
-                    }
-                }
-
-                // Fetch members
-                let members = self.fetch_group_members(tenant_id, id).await?;
-                *group.members_mut() = if members.is_empty() {
-                    None
-                } else {
-                    Some(members)
-                };
-
-                Ok(Some(group))
-            }
-            None => Ok(None),
-        }
-    }
-
-    /// Helper function to fetch group members
-    async fn fetch_group_members(
-        &self,
-        tenant_id: u32,
-        group_id: &str,
-    ) -> AppResult<Vec<scim_v2::models::group::Member>> {
-        let users_table = format!("`t{}_users`", tenant_id);
-        let groups_table = format!("`t{}_groups`", tenant_id);
-        let memberships_table = format!("`t{}_group_memberships`", tenant_id);
-
-        let sql = format!(
-            r#"
-            SELECT 
-                m.member_id,
-                m.member_type,
-                CASE 
-                    WHEN m.member_type = 'User' THEN COALESCE(
-                        json_extract(u.data_orig, '$.displayName'), 
-                        json_extract(u.data_orig, '$.name.formatted'), 
-                        (json_extract(u.data_orig, '$.name.givenName') || ' ' || json_extract(u.data_orig, '$.name.familyName'))
-                    )
-                    WHEN m.member_type = 'Group' THEN json_extract(g.data_orig, '$.displayName')
-                END as display_name
-            FROM {} m
-            LEFT JOIN {} u ON m.member_id = u.id AND m.member_type = 'User'
-            LEFT JOIN {} g ON m.member_id = g.id AND m.member_type = 'Group'
-            WHERE m.group_id = ?1
-            ORDER BY m.created_at
-            "#,
-            memberships_table, users_table, groups_table
-        );
-
-        let rows = sqlx::query(&sql)
-            .bind(group_id)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch group members: {}", e)))?;
-
-        let mut members = Vec::new();
-        for row in rows {
-            let member_id: String = row.get("member_id");
-            let member_type: String = row.get("member_type");
-            let display_name: Option<String> = row.get("display_name");
-
-            // Construct the proper $ref path based on member type (base URL will be added later)
-            let ref_path = match member_type.as_str() {
-                "User" => format!("/{}/Users/{}", tenant_id, member_id),
-                "Group" => format!("/{}/Groups/{}", tenant_id, member_id),
-                _ => format!("/{}/Resources/{}", tenant_id, member_id),
-            };
-
-            members.push(scim_v2::models::group::Member {
-                value: Some(member_id),
-                ref_: Some(ref_path),
-                display: display_name,
-                type_: Some(member_type),
-            });
-        }
-
-        Ok(members)
-    }
 }
 
 #[async_trait]
 impl GroupUpdater for SqliteGroupUpdater {
     async fn execute_group_update(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         _id: &str,
         data: PreparedGroupUpdateData,
     ) -> AppResult<Option<Group>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.sqlite()?;
+
         // Check for case-insensitive duplicate displayName before update
-        self.check_duplicate_display_name(tenant_id, &data.display_name, &data.id)
+        self.check_duplicate_display_name(conn, tenant_id, &data.display_name, &data.id)
             .await?;
 
-        // Begin transaction for atomic group + membership update
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))?;
-
         // Build table names
         let groups_table = format!("`t{}_groups`", tenant_id);
         let memberships_table = format!("`t{}_group_memberships`", tenant_id);
@@ -200,7 +77,7 @@ impl GroupUpdater for SqliteGroupUpdater {
 
         // Update the group record
         let group_sql = format!(
-            "UPDATE {} SET display_name = ?1, external_id = ?2, data_orig = ?3, data_norm = ?4, version = version + 1, updated_at = ?5 WHERE id = ?6",
+            "UPDATE {} SET display_name = ?1, external_id = ?2, data_orig = ?3, data_norm = ?4, version = version + 1, updated_at = ?5, last_modified_by = ?7 WHERE id = ?6",
             groups_table
         );
 
@@ -211,7 +88,8 @@ impl GroupUpdater for SqliteGroupUpdater {
             .bind(&data_norm_str) // SQLite uses TEXT
             .bind(data.timestamp)
             .bind(&data.id)
-            .execute(&mut *tx)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| super::user_update_impl::map_database_error(e, "Group"))?;
 
@@ -225,7 +103,7 @@ impl GroupUpdater for SqliteGroupUpdater {
 
         sqlx::query(&delete_members_sql)
             .bind(&data.id)
-            .execute(&mut *tx)
+            .execute(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to delete group memberships: {}", e))
@@ -246,25 +124,154 @@ impl GroupUpdater for SqliteGroupUpdater {
                         .bind(&data.id)
                         .bind(member_id)
                         .bind(member_type)
-                        .execute(&mut *tx)
+                        .execute(&mut *conn)
                         .await
-                        .map_err(|e| {
-                            AppError::Database(format!("Failed to insert group member: {}", e))
-                        })?;
+                        .map_err(|e| map_membership_insert_error(e, member_id))?;
                 }
             }
         }
 
-        // Commit transaction
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to commit transaction: {}", e)))?;
-
         // Fetch the updated group with properly populated members
-        self.fetch_group_with_members(tenant_id, &data.id).await
+        fetch_group_with_members(conn, tenant_id, &data.id).await
+    }
+
+    async fn find_group_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        fetch_group_with_members(tx.sqlite()?, tenant_id, id).await
     }
 }
 
+/// Fetch a group with its members on the given connection
+pub(super) async fn fetch_group_with_members(
+    conn: &mut SqliteConnection,
+    tenant_id: u32,
+    id: &str,
+) -> AppResult<Option<Group>> {
+    // Return None for empty IDs
+    if id.is_empty() {
+        return Ok(None);
+    }
+
+    let table_name = format!("`t{}_groups`", tenant_id);
+    let sql = format!(
+        "SELECT id, display_name, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE id = ?1",
+        table_name
+    );
+
+    let row = sqlx::query(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+
+    match row {
+        Some(row) => {
+            let data_orig: String = row.get("data_orig");
+            let mut group: Group =
+                serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
+
+            // Set version in meta (ensure meta exists)
+            let version: i64 = row.get("version");
+            if group.meta().is_none() {
+                // Create meta if it doesn't exist
+                let created_at: String = row.get("created_at");
+                let updated_at: String = row.get("updated_at");
+                let meta = scim_v2::models::scim_schema::Meta {
+                    resource_type: Some(crate::parser::ResourceType::Group.scim_name().to_string()),
+                    created: Some(created_at),
+                    last_modified: Some(updated_at),
+                    location: None,
+                    version: Some(format!("W/\"{}\"", version)),
+                };
+                *group.meta_mut() = Some(meta);
+            } else {
+                // Update existing meta with version
+                if let Some(ref mut meta) = group.meta_mut() {
+                    meta.version = Some(format!("W/\"{}\"", version));
+                }
+            }
+
+            // Fetch members
+            let members = fetch_group_members(conn, tenant_id, id).await?;
+            *group.members_mut() = if members.is_empty() {
+                None
+            } else {
+                Some(members)
+            };
+
+            Ok(Some(group))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetch the members of a group on the given connection
+async fn fetch_group_members(
+    conn: &mut SqliteConnection,
+    tenant_id: u32,
+    group_id: &str,
+) -> AppResult<Vec<scim_v2::models::group::Member>> {
+    let users_table = format!("`t{}_users`", tenant_id);
+    let groups_table = format!("`t{}_groups`", tenant_id);
+    let memberships_table = format!("`t{}_group_memberships`", tenant_id);
+
+    let sql = format!(
+        r#"
+        SELECT 
+            m.member_id,
+            m.member_type,
+            CASE 
+                WHEN m.member_type = 'User' THEN COALESCE(
+                    json_extract(u.data_orig, '$.displayName'), 
+                    json_extract(u.data_orig, '$.name.formatted'), 
+                    (json_extract(u.data_orig, '$.name.givenName') || ' ' || json_extract(u.data_orig, '$.name.familyName'))
+                )
+                WHEN m.member_type = 'Group' THEN json_extract(g.data_orig, '$.displayName')
+            END as display_name
+        FROM {} m
+        LEFT JOIN {} u ON m.member_id = u.id AND m.member_type = 'User'
+        LEFT JOIN {} g ON m.member_id = g.id AND m.member_type = 'Group'
+        WHERE m.group_id = ?1
+        ORDER BY m.created_at
+        "#,
+        memberships_table, users_table, groups_table
+    );
+
+    let rows = sqlx::query(&sql)
+        .bind(group_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to fetch group members: {}", e)))?;
+
+    let mut members = Vec::new();
+    for row in rows {
+        let member_id: String = row.get("member_id");
+        let member_type: String = row.get("member_type");
+        let display_name: Option<String> = row.get("display_name");
+
+        // Tenant-relative $ref by member type; the resource handler resolves the base URL
+        let resource_type = match member_type.as_str() {
+            "User" => "Users",
+            "Group" => "Groups",
+            _ => "Resources",
+        };
+        let ref_path = crate::utils::build_tenant_ref(tenant_id, resource_type, &member_id);
+
+        members.push(scim_v2::models::group::Member {
+            value: Some(member_id),
+            ref_: Some(ref_path),
+            display: display_name,
+            type_: Some(member_type),
+        });
+    }
+
+    Ok(members)
+}
+
 /// Convert a JSON Value to a string for SQLite TEXT storage
 ///
 /// This ensures consistent JSON serialization for SQLite databases.
@@ -276,14 +283,9 @@ fn json_value_to_string(value: &Value) -> AppResult<String> {
 mod tests {
     use super::*;
 
-    async fn create_test_pool() -> SqlitePool {
-        SqlitePool::connect(":memory:").await.unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_updater_creation() {
-        let pool = create_test_pool().await;
-        let updater = SqliteGroupUpdater::new(pool);
+    #[test]
+    fn test_updater_creation() {
+        let updater = SqliteGroupUpdater::new();
         // Just verify the updater can be created
         assert!(!format!("{:?}", &updater as *const _).is_empty());
     }
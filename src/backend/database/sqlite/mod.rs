@@ -2,6 +2,7 @@ pub mod backend_impl;
 pub mod filter_impl;
 pub mod group_delete_impl;
 pub mod group_insert_impl;
+pub mod group_members_impl;
 pub mod group_read_impl;
 pub mod group_update_impl;
 pub mod schema;
@@ -14,6 +15,7 @@ pub mod user_update_impl;
 pub use backend_impl::SqliteBackend;
 pub use group_delete_impl::SqliteGroupDeleter;
 pub use group_insert_impl::SqliteGroupInserter;
+pub use group_members_impl::SqliteGroupMemberUpdater;
 pub use group_read_impl::SqliteGroupReader;
 pub use group_update_impl::SqliteGroupUpdater;
 pub use user_delete_impl::SqliteUserDeleter;
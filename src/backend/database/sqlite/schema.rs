@@ -21,7 +21,9 @@ pub async fn init_tenant_schema(pool: &SqlitePool, tenant_id: u32) -> AppResult<
             data_norm TEXT NOT NULL,
             version INTEGER NOT NULL DEFAULT 1,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            created_by TEXT,
+            last_modified_by TEXT
         )
         "#,
         users_table
@@ -43,7 +45,9 @@ pub async fn init_tenant_schema(pool: &SqlitePool, tenant_id: u32) -> AppResult<
             data_norm TEXT NOT NULL,
             version INTEGER NOT NULL DEFAULT 1,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            created_by TEXT,
+            last_modified_by TEXT
         )
         "#,
         groups_table
@@ -75,12 +79,84 @@ pub async fn init_tenant_schema(pool: &SqlitePool, tenant_id: u32) -> AppResult<
         .await
         .map_err(|e| AppError::Database(format!("Failed to create memberships table: {}", e)))?;
 
+    // Tables created before provenance tracking lack its columns
+    add_provenance_columns(pool, &users_table).await?;
+    add_provenance_columns(pool, &groups_table).await?;
+    backfill_external_ids(pool, &users_table).await?;
+    backfill_external_ids(pool, &groups_table).await?;
+
     // Create indexes for better performance
     create_indexes(pool, tenant_id).await?;
 
     Ok(())
 }
 
+/// Add the provenance columns to a table created by an older version
+async fn add_provenance_columns(pool: &SqlitePool, table: &str) -> AppResult<()> {
+    let columns: Vec<String> =
+        sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to inspect {}: {}", table, e)))?;
+
+    for column in ["created_by", "last_modified_by"] {
+        if !columns.iter().any(|c| c == column) {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} TEXT", table, column))
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to add {} to {}: {}", column, table, e))
+                })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy `externalId` from the stored document into the indexed column
+///
+/// Filters and lookups on externalId read the column, so rows whose column
+/// was left empty would otherwise not be found. The column is unique: a
+/// value shared with another row's document or column is logged and left
+/// out rather than failing startup, so those rows stay unfindable by
+/// externalId until the duplicate is resolved.
+async fn backfill_external_ids(pool: &SqlitePool, table: &str) -> AppResult<()> {
+    let value = "json_extract(data_orig, '$.externalId')";
+    let pending = "external_id IS NULL AND json_type(data_orig, '$.externalId') = 'text'";
+    let taken = format!(
+        "SELECT {value} FROM {table} WHERE json_type(data_orig, '$.externalId') = 'text' \
+         GROUP BY 1 HAVING COUNT(*) > 1 \
+         UNION SELECT external_id FROM {table} WHERE external_id IS NOT NULL"
+    );
+    let backfill_error = |e: sqlx::Error| {
+        AppError::Database(format!("Failed to backfill externalId in {}: {}", table, e))
+    };
+
+    let duplicates: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT id, {value} FROM {table} WHERE {pending} AND {value} IN ({taken})"
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(backfill_error)?;
+    for (id, external_id) in duplicates {
+        tracing::warn!(
+            table,
+            id,
+            external_id,
+            "Not backfilling a duplicate externalId into the external_id column"
+        );
+    }
+
+    sqlx::query(&format!(
+        "UPDATE {table} SET external_id = {value} WHERE {pending} AND {value} NOT IN ({taken})"
+    ))
+    .execute(pool)
+    .await
+    .map_err(backfill_error)?;
+
+    Ok(())
+}
+
 /// Create performance indexes for tenant tables
 async fn create_indexes(pool: &SqlitePool, tenant_id: u32) -> AppResult<()> {
     let users_table = format!("t{}_users", tenant_id);
@@ -100,6 +176,11 @@ async fn create_indexes(pool: &SqlitePool, tenant_id: u32) -> AppResult<()> {
             "CREATE INDEX IF NOT EXISTS idx_{}_users_external_id ON {} (external_id)",
             sanitized_tenant_id, users_table
         ),
+        // Tenants matching externalId case-insensitively compare it lowercased
+        format!(
+            "CREATE INDEX IF NOT EXISTS idx_{}_users_external_id_lower ON {} (LOWER(external_id))",
+            sanitized_tenant_id, users_table
+        ),
         format!(
             "CREATE INDEX IF NOT EXISTS idx_{}_users_created_at ON {} (created_at)",
             sanitized_tenant_id, users_table
@@ -116,6 +197,11 @@ async fn create_indexes(pool: &SqlitePool, tenant_id: u32) -> AppResult<()> {
             "CREATE INDEX IF NOT EXISTS idx_{}_groups_external_id ON {} (external_id)",
             sanitized_tenant_id, groups_table
         ),
+        // Tenants matching externalId case-insensitively compare it lowercased
+        format!(
+            "CREATE INDEX IF NOT EXISTS idx_{}_groups_external_id_lower ON {} (LOWER(external_id))",
+            sanitized_tenant_id, groups_table
+        ),
         format!(
             "CREATE INDEX IF NOT EXISTS idx_{}_groups_created_at ON {} (created_at)",
             sanitized_tenant_id, groups_table
@@ -159,9 +245,15 @@ pub async fn drop_tenant_schema(pool: &SqlitePool, tenant_id: u32) -> AppResult<
     let memberships_table = format!("t{}_group_memberships", tenant_id);
     let groups_table = format!("t{}_groups", tenant_id);
     let users_table = format!("t{}_users", tenant_id);
+    let sync_stats_table = format!("t{}_sync_stats", tenant_id);
 
     // Drop tables in reverse order due to foreign key constraints
-    for table in [&memberships_table, &groups_table, &users_table] {
+    for table in [
+        &sync_stats_table,
+        &memberships_table,
+        &groups_table,
+        &users_table,
+    ] {
         let sql = format!("DROP TABLE IF EXISTS {}", table);
         sqlx::query(&sql)
             .execute(pool)
@@ -172,9 +264,107 @@ pub async fn drop_tenant_schema(pool: &SqlitePool, tenant_id: u32) -> AppResult<
     Ok(())
 }
 
+/// Reclaim free pages and refresh planner statistics after bulk deletes
+///
+/// SQLite can only `VACUUM` the whole database file, so this compacts every
+/// tenant's tables; `ANALYZE` is limited to this tenant's.
+pub async fn vacuum_tenant_schema(pool: &SqlitePool, tenant_id: u32) -> AppResult<()> {
+    sqlx::query("VACUUM")
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to vacuum database: {}", e)))?;
+
+    for table in ["users", "groups", "group_memberships"] {
+        let sql = format!("ANALYZE t{}_{}", tenant_id, table);
+        sqlx::query(&sql).execute(pool).await.map_err(|e| {
+            AppError::Database(format!("Failed to analyze t{}_{}: {}", tenant_id, table, e))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Create the table holding a tenant's sync statistics snapshot
+///
+/// Only used with `server.stats.persist`, so it is created on first use
+/// rather than by `init_tenant_schema`.
+async fn create_sync_stats_table(pool: &SqlitePool, tenant_id: u32) -> AppResult<()> {
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS t{}_sync_stats (\
+         id INTEGER PRIMARY KEY CHECK (id = 1), \
+         snapshot TEXT NOT NULL, \
+         updated_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+        tenant_id
+    );
+    sqlx::query(&sql)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create sync stats table: {}", e)))?;
+
+    Ok(())
+}
+
+/// Latest sync statistics snapshot saved for a tenant
+pub async fn load_sync_stats(
+    pool: &SqlitePool,
+    tenant_id: u32,
+) -> AppResult<Option<serde_json::Value>> {
+    create_sync_stats_table(pool, tenant_id).await?;
+    let snapshot: Option<String> = sqlx::query_scalar(&format!(
+        "SELECT snapshot FROM t{}_sync_stats WHERE id = 1",
+        tenant_id
+    ))
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to load sync stats: {}", e)))?;
+
+    snapshot
+        .map(|snapshot| serde_json::from_str(&snapshot).map_err(AppError::Serialization))
+        .transpose()
+}
+
+/// Replace the sync statistics snapshot saved for a tenant
+pub async fn save_sync_stats(
+    pool: &SqlitePool,
+    tenant_id: u32,
+    snapshot: &serde_json::Value,
+) -> AppResult<()> {
+    create_sync_stats_table(pool, tenant_id).await?;
+    let sql = format!(
+        "INSERT INTO t{}_sync_stats (id, snapshot, updated_at) VALUES (1, ?1, CURRENT_TIMESTAMP) \
+         ON CONFLICT (id) DO UPDATE SET snapshot = excluded.snapshot, updated_at = excluded.updated_at",
+        tenant_id
+    );
+    sqlx::query(&sql)
+        .bind(snapshot.to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to save sync stats: {}", e)))?;
+
+    Ok(())
+}
+
+/// Ids of the tenants whose tables exist, in ascending order
+pub async fn list_tenant_schemas(pool: &SqlitePool) -> AppResult<Vec<u32>> {
+    let tables: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to list tables: {}", e)))?;
+
+    Ok(crate::backend::database::tenants::tenant_ids_from_tables(
+        tables,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::database::filter::FilterConverter;
+    use crate::backend::database::sqlite::filter_impl::SqliteFilterConverter;
+    use crate::parser::filter_parser::FilterOperator;
+    use crate::parser::ResourceType;
+    use serde_json::Value;
     use sqlx::sqlite::SqlitePoolOptions;
 
     #[tokio::test]
@@ -202,4 +392,152 @@ mod tests {
         // Clean up
         drop_tenant_schema(&pool, tenant_id).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_provenance_columns_added_to_existing_tables() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        // Users table as created before provenance tracking
+        sqlx::query(
+            "CREATE TABLE t1_users (id TEXT PRIMARY KEY, username TEXT NOT NULL UNIQUE, \
+             external_id TEXT UNIQUE, data_orig TEXT NOT NULL, data_norm TEXT NOT NULL, \
+             version INTEGER NOT NULL DEFAULT 1, created_at DATETIME, updated_at DATETIME)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        init_tenant_schema(&pool, 1).await.unwrap();
+        // Running again must not try to add the columns twice
+        init_tenant_schema(&pool, 1).await.unwrap();
+
+        for table in ["t1_users", "t1_groups"] {
+            let columns: Vec<String> =
+                sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{}')", table))
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            assert!(columns.contains(&"created_by".to_string()));
+            assert!(columns.contains(&"last_modified_by".to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_ids_backfilled_from_documents() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        init_tenant_schema(&pool, 1).await.unwrap();
+
+        // Rows whose externalId only lives in the document
+        for (id, data) in [
+            ("u1", r#"{"userName":"a","externalId":"Ext-A"}"#),
+            ("u2", r#"{"userName":"b"}"#),
+            ("u3", r#"{"userName":"c","externalId":null}"#),
+        ] {
+            sqlx::query(
+                "INSERT INTO t1_users (id, username, data_orig, data_norm) VALUES (?1, ?2, ?3, ?3)",
+            )
+            .bind(id)
+            .bind(id)
+            .bind(data)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        init_tenant_schema(&pool, 1).await.unwrap();
+
+        let external_ids: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT id, external_id FROM t1_users ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            external_ids,
+            vec![
+                ("u1".to_string(), Some("Ext-A".to_string())),
+                ("u2".to_string(), None),
+                ("u3".to_string(), None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_external_ids_are_not_backfilled() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        init_tenant_schema(&pool, 1).await.unwrap();
+
+        // u1 already holds Ext-A; u2 repeats it, u3 and u4 share Ext-B
+        for (id, external_id, data) in [
+            (
+                "u1",
+                Some("Ext-A"),
+                r#"{"userName":"a","externalId":"Ext-A"}"#,
+            ),
+            ("u2", None, r#"{"userName":"b","externalId":"Ext-A"}"#),
+            ("u3", None, r#"{"userName":"c","externalId":"Ext-B"}"#),
+            ("u4", None, r#"{"userName":"d","externalId":"Ext-B"}"#),
+            ("u5", None, r#"{"userName":"e","externalId":"Ext-C"}"#),
+        ] {
+            sqlx::query(
+                "INSERT INTO t1_users (id, username, external_id, data_orig, data_norm) \
+                 VALUES (?1, ?2, ?3, ?4, ?4)",
+            )
+            .bind(id)
+            .bind(id)
+            .bind(external_id)
+            .bind(data)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // Startup still succeeds
+        init_tenant_schema(&pool, 1).await.unwrap();
+
+        let external_ids: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT id, external_id FROM t1_users ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            external_ids,
+            vec![
+                ("u1".to_string(), Some("Ext-A".to_string())),
+                ("u2".to_string(), None),
+                ("u3".to_string(), None),
+                ("u4".to_string(), None),
+                ("u5".to_string(), Some("Ext-C".to_string())),
+            ]
+        );
+
+        // They are still found by an eq filter
+        let converter = SqliteFilterConverter::new();
+        for (external_id, expected) in [("Ext-A", vec!["u1", "u2"]), ("Ext-B", vec!["u3", "u4"])] {
+            let filter = FilterOperator::Equal(
+                "externalId".to_string(),
+                Value::String(external_id.to_string()),
+            );
+            let (condition, params) = converter
+                .to_where_clause(&filter, ResourceType::User)
+                .unwrap();
+            let sql = format!("SELECT id FROM t1_users WHERE {} ORDER BY id", condition);
+            let mut query = sqlx::query_scalar::<_, String>(&sql);
+            for param in &params {
+                query = query.bind(param);
+            }
+            assert_eq!(query.fetch_all(&pool).await.unwrap(), expected);
+        }
+    }
 }
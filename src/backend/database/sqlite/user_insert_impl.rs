@@ -1,21 +1,21 @@
 use async_trait::async_trait;
 use serde_json::Value;
-use sqlx::SqlitePool;
+use sqlx::SqliteConnection;
 
 use super::super::user_insert::{PreparedUserData, UserInsertProcessor, UserInserter};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
 /// SQLite-specific implementation of UserInserter
 ///
 /// This handles SQLite's JSON TEXT storage while using shared SQL generation.
-pub struct SqliteUserInserter {
-    pool: SqlitePool,
-}
+#[derive(Default)]
+pub struct SqliteUserInserter;
 
 impl SqliteUserInserter {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Convert JSON Value to String for SQLite TEXT storage
@@ -24,7 +24,12 @@ impl SqliteUserInserter {
     }
 
     /// Check for case-insensitive duplicate username
-    async fn check_duplicate_username(&self, tenant_id: u32, username: &str) -> AppResult<()> {
+    async fn check_duplicate_username(
+        &self,
+        conn: &mut SqliteConnection,
+        tenant_id: u32,
+        username: &str,
+    ) -> AppResult<()> {
         let table_name = format!("t{}_users", tenant_id);
         let sql = format!(
             "SELECT COUNT(*) FROM {} WHERE LOWER(username) = LOWER(?1)",
@@ -33,7 +38,7 @@ impl SqliteUserInserter {
 
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(username)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate username: {}", e))
@@ -51,14 +56,22 @@ impl SqliteUserInserter {
 
 #[async_trait]
 impl UserInserter for SqliteUserInserter {
-    async fn execute_user_insert(&self, tenant_id: u32, data: PreparedUserData) -> AppResult<User> {
+    async fn execute_user_insert(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        data: PreparedUserData,
+    ) -> AppResult<User> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.sqlite()?;
+
         // Check for case-insensitive duplicate username before insertion
-        self.check_duplicate_username(tenant_id, &data.username)
+        self.check_duplicate_username(conn, tenant_id, &data.username)
             .await?;
 
         let table_name = format!("t{}_users", tenant_id);
         let sql = format!(
-            "INSERT INTO {} (id, username, external_id, data_orig, data_norm, version, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO {} (id, username, external_id, data_orig, data_norm, version, created_at, updated_at, created_by, last_modified_by) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
             table_name
         );
 
@@ -75,7 +88,8 @@ impl UserInserter for SqliteUserInserter {
             .bind(1i64) // version = 1 for new records
             .bind(data.timestamp)
             .bind(data.timestamp)
-            .execute(&self.pool)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| map_database_error(e, "User"))?;
 
@@ -105,14 +119,9 @@ pub fn map_database_error(error: sqlx::Error, resource_type: &str) -> AppError {
 mod tests {
     use super::*;
 
-    async fn create_test_pool() -> SqlitePool {
-        SqlitePool::connect(":memory:").await.unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_json_value_to_string() {
-        let pool = create_test_pool().await;
-        let inserter = SqliteUserInserter::new(pool);
+    #[test]
+    fn test_json_value_to_string() {
+        let inserter = SqliteUserInserter::new();
 
         let test_value = serde_json::json!({"test": "value"});
         let result = inserter.json_value_to_string(&test_value);
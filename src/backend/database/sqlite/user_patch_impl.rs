@@ -1,8 +1,10 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection};
 
 use super::super::user_patch::{PreparedUserPatchData, UserPatcher};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
@@ -10,13 +12,12 @@ use crate::models::User;
 ///
 /// This handles SQLite's TEXT-based ID storage and JSON TEXT format
 /// for user patch operations.
-pub struct SqliteUserPatcher {
-    pool: SqlitePool,
-}
+#[derive(Default)]
+pub struct SqliteUserPatcher;
 
 impl SqliteUserPatcher {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
 
     /// Convert JSON Value to String for SQLite TEXT storage
@@ -27,6 +28,7 @@ impl SqliteUserPatcher {
     /// Check for case-insensitive duplicate username excluding current user
     async fn check_duplicate_username(
         &self,
+        conn: &mut SqliteConnection,
         tenant_id: u32,
         username: &str,
         exclude_id: &str,
@@ -40,7 +42,7 @@ impl SqliteUserPatcher {
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(username)
             .bind(exclude_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate username: {}", e))
@@ -58,12 +60,16 @@ impl SqliteUserPatcher {
 impl UserPatcher for SqliteUserPatcher {
     async fn execute_user_patch(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         _id: &str,
         data: PreparedUserPatchData,
     ) -> AppResult<Option<User>> {
+        let actor = tx.actor().map(str::to_owned);
+        let conn = tx.sqlite()?;
+
         // Check for case-insensitive duplicate username before patch
-        self.check_duplicate_username(tenant_id, &data.username, &data.id)
+        self.check_duplicate_username(conn, tenant_id, &data.username, &data.id)
             .await?;
 
         // Build table name
@@ -75,7 +81,7 @@ impl UserPatcher for SqliteUserPatcher {
 
         // SQLite UPDATE SQL with TEXT-based parameter binding and version increment
         let sql = format!(
-            "UPDATE {} SET username = ?1, external_id = ?2, data_orig = ?3, data_norm = ?4, version = version + 1, updated_at = ?5 WHERE id = ?6",
+            "UPDATE {} SET username = ?1, external_id = ?2, data_orig = ?3, data_norm = ?4, version = version + 1, updated_at = ?5, last_modified_by = ?7 WHERE id = ?6",
             table_name
         );
 
@@ -86,7 +92,8 @@ impl UserPatcher for SqliteUserPatcher {
             .bind(&data_norm_str) // SQLite uses TEXT
             .bind(data.timestamp)
             .bind(&data.id)
-            .execute(&self.pool)
+            .bind(&actor)
+            .execute(&mut *conn)
             .await
             .map_err(|e| map_database_error(e, "User"))?;
 
@@ -97,7 +104,58 @@ impl UserPatcher for SqliteUserPatcher {
         }
     }
 
-    async fn find_user_for_patch(&self, tenant_id: u32, id: &str) -> AppResult<Option<User>> {
+    async fn execute_active_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        active: bool,
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<User>> {
+        let actor = tx.actor().map(str::to_owned);
+        let table_name = format!("t{}_users", tenant_id);
+        let last_modified = crate::utils::format_scim_datetime(timestamp);
+
+        // json_set edits both documents in place; data_norm keys and
+        // non-caseExact values are lowercase
+        let sql = format!(
+            "UPDATE {} SET \
+             data_orig = json_set(data_orig, '$.active', json(?1), '$.meta.lastModified', ?2), \
+             data_norm = json_set(data_norm, '$.active', json(?1), '$.meta.lastmodified', ?3), \
+             version = version + 1, updated_at = ?4, last_modified_by = ?6 WHERE id = ?5 RETURNING id, data_orig",
+            table_name
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(active.to_string())
+            .bind(&last_modified)
+            .bind(last_modified.to_lowercase())
+            .bind(timestamp)
+            .bind(id)
+            .bind(&actor)
+            .fetch_optional(tx.sqlite()?)
+            .await
+            .map_err(|e| map_database_error(e, "User"))?;
+
+        match row {
+            Some(row) => {
+                let data_orig: String = row.get("data_orig");
+                let mut user: User =
+                    serde_json::from_str(&data_orig).map_err(AppError::Serialization)?;
+                let db_id: String = row.get("id");
+                *user.id_mut() = Some(db_id);
+                Ok(Some(user))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn find_user_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<User>> {
         let table_name = format!("t{}_users", tenant_id);
         let sql = format!(
             "SELECT id, username, external_id, data_orig, data_norm, created_at, updated_at FROM {} WHERE id = ?1",
@@ -106,7 +164,7 @@ impl UserPatcher for SqliteUserPatcher {
 
         let row = sqlx::query(&sql)
             .bind(id)
-            .fetch_optional(&self.pool)
+            .fetch_optional(tx.sqlite()?)
             .await
             .map_err(|e| AppError::Database(format!("Failed to find user for patch: {}", e)))?;
 
@@ -167,14 +225,9 @@ pub fn map_database_error(e: sqlx::Error, resource_type: &str) -> AppError {
 mod tests {
     use super::*;
 
-    async fn create_test_pool() -> SqlitePool {
-        SqlitePool::connect(":memory:").await.unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_patcher_creation() {
-        let pool = create_test_pool().await;
-        let patcher = SqliteUserPatcher::new(pool);
+    #[test]
+    fn test_patcher_creation() {
+        let patcher = SqliteUserPatcher::new();
         // Just verify the patcher can be created
         assert!(!format!("{:?}", &patcher as *const _).is_empty());
     }
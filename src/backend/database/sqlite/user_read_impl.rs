@@ -1,9 +1,12 @@
 use async_trait::async_trait;
 use scim_v2::models::user::Group as UserGroup;
 use sqlx::{Row, SqlitePool};
+use std::collections::HashSet;
+use std::sync::Arc;
 
-use super::super::user_read::UserReader;
+use super::super::user_read::{effective_members_condition, UserReader};
 use crate::backend::database::filter::FilterConverter;
+use crate::backend::database::list_query::{page_bounds, ListQuery};
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 use crate::parser::filter_operator::FilterOperator;
@@ -13,11 +16,22 @@ use crate::parser::{SortOrder, SortSpec};
 /// SQLite-specific implementation of UserReader
 pub struct SqliteUserReader {
     pool: SqlitePool,
+    /// See `DatabaseBackendConfig::external_id_case_insensitive_tenants`
+    case_insensitive_external_ids: Arc<HashSet<u32>>,
 }
 
 impl SqliteUserReader {
     pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            case_insensitive_external_ids: Arc::default(),
+        }
+    }
+
+    /// Match externalId case-insensitively in the given tenants
+    pub fn with_case_insensitive_external_ids(mut self, tenants: Arc<HashSet<u32>>) -> Self {
+        self.case_insensitive_external_ids = tenants;
+        self
     }
 
     /// Generate table name for a resource type and tenant
@@ -42,20 +56,29 @@ impl SqliteUserReader {
 
     /// Convert SCIM attribute to SQLite column or JSON path for sorting
     fn get_sort_column(&self, sort_spec: &SortSpec) -> String {
-        match sort_spec.attribute.as_str() {
+        // Attribute names are case-insensitive (RFC 7643 Section 2.1), so match on the
+        // lowercased name; meta timestamps map to native columns instead of JSON extraction
+        match sort_spec.attribute.to_lowercase().as_str() {
             // Special attributes stored in dedicated columns
-            "userName" => "LOWER(username)".to_string(),
-            "displayName" => "LOWER(display_name)".to_string(),
+            "username" => "LOWER(username)".to_string(),
             "id" => "id".to_string(),
-            "externalId" => "external_id".to_string(),
+            "externalid" => "external_id".to_string(),
             "meta.created" => "created_at".to_string(),
-            "meta.lastModified" => "updated_at".to_string(),
+            "meta.lastmodified" => "updated_at".to_string(),
+            // The attribute is interpolated into SQL, so anything that is not a plain
+            // (dotted) attribute name falls back to the default ordering
+            attr if !attr
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') =>
+            {
+                "created_at".to_string()
+            }
             // JSON attributes - use case-insensitive sorting
             _ => {
-                // Normalize attribute name to lowercase for JSON path
-                let normalized_attr = sort_spec.attribute.to_lowercase();
-                let json_path = normalized_attr;
-                format!("LOWER(json_extract(data_orig, '$.{}'))", json_path)
+                // data_norm stores lowercased attribute names, so the lowercased
+                // (possibly dotted) attribute can be used as the JSON path directly
+                let json_path = sort_spec.attribute.to_lowercase();
+                format!("LOWER(json_extract(data_norm, '$.{}'))", json_path)
             }
         }
     }
@@ -69,9 +92,10 @@ impl SqliteUserReader {
                     SortOrder::Ascending => "ASC",
                     SortOrder::Descending => "DESC",
                 };
-                format!(" ORDER BY {} {}", column, direction)
+                format!(" ORDER BY {} {}, id {}", column, direction, direction)
             }
-            None => " ORDER BY created_at".to_string(), // Default sort
+            // Default sort, with id breaking ties between rows created together
+            None => " ORDER BY created_at, id".to_string(),
         }
     }
 
@@ -114,7 +138,7 @@ impl SqliteUserReader {
                     let created_at: String = row.get("created_at");
                     let updated_at: String = row.get("updated_at");
                     let meta = scim_v2::models::scim_schema::Meta {
-                        resource_type: Some("User".to_string()),
+                        resource_type: Some(ResourceType::User.scim_name().to_string()),
                         created: Some(created_at),
                         last_modified: Some(updated_at),
                         location: None,
@@ -172,11 +196,8 @@ impl SqliteUserReader {
             let group_id: String = row.get("id");
             let display_name: Option<String> = row.get("display_name");
 
-            // TODO: We should get the base URL from configuration/context
-            // For now, we'll use a format that works with the test expectations
-            // In production, this should come from the request context or configuration
-            // Generate relative URL that will be fixed by the resource handler
-            let ref_url = format!("/{}/Groups/{}", tenant_id, group_id);
+            // Tenant-relative $ref; the resource handler resolves the base URL
+            let ref_url = crate::utils::build_tenant_ref(tenant_id, "Groups", &group_id);
 
             groups.push(UserGroup {
                 value: Some(group_id),
@@ -189,11 +210,118 @@ impl SqliteUserReader {
         Ok(groups)
     }
 
+    /// Groups of the outer user row as normalized JSON objects, for `groups` filters
+    fn groups_source(&self, tenant_id: u32) -> String {
+        format!(
+            "SELECT json_object('value', g.id, 'display', LOWER(json_extract(g.data_orig, '$.displayName')), \
+             'type', 'direct') AS value \
+             FROM {groups} g \
+             INNER JOIN {memberships} m ON g.id = m.group_id \
+             WHERE m.member_id = {users}.id AND m.member_type = 'User'",
+            groups = self.groups_table(tenant_id),
+            memberships = self.memberships_table(tenant_id),
+            users = self.users_table(tenant_id)
+        )
+    }
+
     /// Create a filter converter for this tenant
     fn filter_converter(
         &self,
+        tenant_id: u32,
     ) -> crate::backend::database::sqlite::filter_impl::SqliteFilterConverter {
         crate::backend::database::sqlite::filter_impl::SqliteFilterConverter::new()
+            .with_elements_source("groups", self.groups_source(tenant_id))
+            .with_case_insensitive_external_id(
+                self.case_insensitive_external_ids.contains(&tenant_id),
+            )
+    }
+
+    /// One page of the users matching an optional filter, with the number of matches
+    async fn find_users_page(
+        &self,
+        tenant_id: u32,
+        filter: Option<&FilterOperator>,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        let (condition, params) = match filter {
+            Some(filter) => {
+                let (condition, params) = self
+                    .filter_converter(tenant_id)
+                    .to_where_clause(filter, ResourceType::User)?;
+                (Some(condition), params)
+            }
+            None => (None, Vec::new()),
+        };
+        self.find_users_where(
+            tenant_id,
+            condition.as_deref(),
+            &params,
+            start_index,
+            count,
+            sort_spec,
+            include_groups,
+        )
+        .await
+    }
+
+    /// One page of the users matching an optional SQL condition, with the number of matches
+    #[allow(clippy::too_many_arguments)]
+    async fn find_users_where(
+        &self,
+        tenant_id: u32,
+        condition: Option<&str>,
+        params: &[String],
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        let query = ListQuery::new(&self.users_table(tenant_id), condition);
+
+        let count_sql = query.count_sql();
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for param in params {
+            count_query = count_query.bind(param);
+        }
+        let total = count_query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count users: {}", e)))?
+            .0;
+
+        let (limit, offset) = page_bounds(start_index, count);
+        let sql = query.page_sql(
+            "id",
+            &self.build_order_by(sort_spec),
+            &format!("?{}", params.len() + 1),
+            &format!("?{}", params.len() + 2),
+        );
+        let mut page_query = sqlx::query(&sql);
+        for param in params {
+            page_query = page_query.bind(param);
+        }
+        let rows = page_query
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to fetch users: {}", e)))?;
+
+        let mut users = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            if let Some(user) = self
+                .fetch_user_with_groups_optional(tenant_id, &id, include_groups)
+                .await?
+            {
+                users.push(user);
+            }
+        }
+
+        Ok((users, total))
     }
 }
 
@@ -237,50 +365,58 @@ impl UserReader for SqliteUserReader {
         }
     }
 
-    async fn find_all_users(
+    async fn find_user_by_external_id(
         &self,
         tenant_id: u32,
-        start_index: Option<i64>,
-        count: Option<i64>,
+        external_id: &str,
         include_groups: bool,
-    ) -> AppResult<(Vec<User>, i64)> {
+    ) -> AppResult<Option<User>> {
         let table_name = self.users_table(tenant_id);
-
-        // Get total count
-        let count_sql = format!("SELECT COUNT(*) FROM {}", table_name);
-        let total: (i64,) = sqlx::query_as(&count_sql)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to count users: {}", e)))?;
-
-        // Get users with pagination
-        let offset = start_index.unwrap_or(1).saturating_sub(1).max(0);
-        let limit = count.unwrap_or(100).min(1000); // Max 1000 per page
-
+        let (column, document, external_id) =
+            if self.case_insensitive_external_ids.contains(&tenant_id) {
+                (
+                    "LOWER(external_id)",
+                    "LOWER(json_extract(data_orig, '$.externalId'))",
+                    external_id.to_lowercase(),
+                )
+            } else {
+                (
+                    "external_id",
+                    "json_extract(data_orig, '$.externalId')",
+                    external_id.to_string(),
+                )
+            };
+        // Rows the startup backfill left out of the column match by their document
         let sql = format!(
-            "SELECT id, username, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} ORDER BY created_at LIMIT ?1 OFFSET ?2",
-            table_name
+            "SELECT id FROM {} WHERE {} = ?1 OR (external_id IS NULL AND {} = ?1)",
+            table_name, column, document
         );
 
-        let rows = sqlx::query(&sql)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
+        let row = sqlx::query(&sql)
+            .bind(&external_id)
+            .fetch_optional(&self.pool)
             .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch users: {}", e)))?;
+            .map_err(|e| AppError::Database(format!("Failed to find user by externalId: {}", e)))?;
 
-        let mut users = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(user) = self
-                .fetch_user_with_groups_optional(tenant_id, &id, include_groups)
-                .await?
-            {
-                users.push(user);
+        match row {
+            Some(row) => {
+                let id: String = row.get("id");
+                self.fetch_user_with_groups_optional(tenant_id, &id, include_groups)
+                    .await
             }
+            None => Ok(None),
         }
+    }
 
-        Ok((users, total.0))
+    async fn find_all_users(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.find_users_page(tenant_id, None, start_index, count, None, include_groups)
+            .await
     }
 
     async fn find_all_users_sorted(
@@ -291,50 +427,15 @@ impl UserReader for SqliteUserReader {
         sort_spec: Option<&SortSpec>,
         include_groups: bool,
     ) -> AppResult<(Vec<User>, i64)> {
-        if sort_spec.is_none() {
-            return self
-                .find_all_users(tenant_id, start_index, count, include_groups)
-                .await;
-        }
-
-        let table_name = self.users_table(tenant_id);
-
-        // Get total count
-        let count_sql = format!("SELECT COUNT(*) FROM {}", table_name);
-        let total: (i64,) = sqlx::query_as(&count_sql)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to count users: {}", e)))?;
-
-        // Get users with pagination and sorting
-        let offset = start_index.unwrap_or(1).saturating_sub(1).max(0);
-        let limit = count.unwrap_or(100).min(1000); // Max 1000 per page
-
-        let order_by = self.build_order_by(sort_spec);
-        let sql = format!(
-            "SELECT id, username, external_id, data_orig, data_norm, version, created_at, updated_at FROM {}{} LIMIT ?1 OFFSET ?2",
-            table_name, order_by
-        );
-
-        let rows = sqlx::query(&sql)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch sorted users: {}", e)))?;
-
-        let mut users = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(user) = self
-                .fetch_user_with_groups_optional(tenant_id, &id, include_groups)
-                .await?
-            {
-                users.push(user);
-            }
-        }
-
-        Ok((users, total.0))
+        self.find_users_page(
+            tenant_id,
+            None,
+            start_index,
+            count,
+            sort_spec,
+            include_groups,
+        )
+        .await
     }
 
     async fn find_users_by_filter(
@@ -346,102 +447,54 @@ impl UserReader for SqliteUserReader {
         sort_spec: Option<&SortSpec>,
         include_groups: bool,
     ) -> AppResult<(Vec<User>, i64)> {
-        let table_name = self.users_table(tenant_id);
-
-        // Convert filter to SQL
-        let (where_clause, params) = self
-            .filter_converter()
-            .to_where_clause(filter, ResourceType::User)?;
-
-        // Get total count with filter
-        let count_sql = format!(
-            "SELECT COUNT(*) FROM {} WHERE ({})",
-            table_name, where_clause
-        );
-
-        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
-        for param in &params {
-            count_query = count_query.bind(param);
-        }
-
-        let total = count_query
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to count filtered users: {}", e)))?
-            .0;
-
-        // Get users with filter and pagination
-        let offset = start_index.unwrap_or(1).saturating_sub(1).max(0);
-        let limit = count.unwrap_or(100).min(1000);
-
-        let order_by = self.build_order_by(sort_spec);
-        let sql = format!(
-            "SELECT id, username, external_id, data_orig, data_norm, version, created_at, updated_at FROM {} WHERE ({}){} LIMIT ?{} OFFSET ?{}",
-            table_name, where_clause, order_by, params.len() + 1, params.len() + 2
-        );
-
-        let mut query = sqlx::query(&sql);
-        for param in &params {
-            query = query.bind(param);
-        }
-        query = query.bind(limit).bind(offset);
-
-        let rows = query
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to fetch filtered users: {}", e)))?;
-
-        let mut users = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(user) = self
-                .fetch_user_with_groups_optional(tenant_id, &id, include_groups)
-                .await?
-            {
-                users.push(user);
-            }
-        }
-
-        Ok((users, total))
+        self.find_users_page(
+            tenant_id,
+            Some(filter),
+            start_index,
+            count,
+            sort_spec,
+            include_groups,
+        )
+        .await
     }
 
-    async fn find_users_by_group_id(
+    async fn find_effective_members(
         &self,
         tenant_id: u32,
         group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
         include_groups: bool,
-    ) -> AppResult<Vec<User>> {
-        let users_table = self.users_table(tenant_id);
-        let memberships_table = self.memberships_table(tenant_id);
-
-        let sql = format!(
-            r#"
-            SELECT u.id, u.username, u.external_id, u.data_orig, u.data_norm, u.version, u.created_at, u.updated_at
-            FROM {} u
-            INNER JOIN {} m ON u.id = m.member_id
-            WHERE m.group_id = ?1 AND m.member_type = 'User'
-            ORDER BY u.created_at
-            "#,
-            users_table, memberships_table
-        );
-
-        let rows = sqlx::query(&sql)
+    ) -> AppResult<Option<(Vec<User>, i64)>> {
+        let groups_table = self.groups_table(tenant_id);
+        let exists_sql = format!("SELECT COUNT(*) FROM {} WHERE id = ?1", groups_table);
+        let groups: i64 = sqlx::query_scalar(&exists_sql)
             .bind(group_id)
-            .fetch_all(&self.pool)
+            .fetch_one(&self.pool)
             .await
-            .map_err(|e| AppError::Database(format!("Failed to find users by group: {}", e)))?;
-
-        let mut users = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            if let Some(user) = self
-                .fetch_user_with_groups_optional(tenant_id, &id, include_groups)
-                .await?
-            {
-                users.push(user);
-            }
+            .map_err(|e| AppError::Database(format!("Failed to find group: {}", e)))?;
+        if groups == 0 {
+            return Ok(None);
         }
 
-        Ok(users)
+        let condition = effective_members_condition(
+            &groups_table,
+            &self.memberships_table(tenant_id),
+            "?1",
+            max_depth,
+        );
+        let sort_spec = SortSpec::new("userName".to_string(), SortOrder::Ascending);
+        self.find_users_where(
+            tenant_id,
+            Some(&condition),
+            &[group_id.to_string()],
+            start_index,
+            count,
+            Some(&sort_spec),
+            include_groups,
+        )
+        .await
+        .map(Some)
     }
 }
@@ -1,8 +1,8 @@
 use async_trait::async_trait;
 use serde_json::Value;
-use sqlx::SqlitePool;
 
-use super::super::user_update::{PreparedUserUpdateData, UserUpdater};
+use super::super::user_update::{uniqueness_conflict, PreparedUserUpdateData, UserUpdater};
+use super::super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
@@ -10,56 +10,73 @@ use crate::models::User;
 ///
 /// This handles SQLite's TEXT-based ID storage and JSON TEXT format
 /// for user update operations.
-pub struct SqliteUserUpdater {
-    pool: SqlitePool,
-}
+#[derive(Default)]
+pub struct SqliteUserUpdater;
 
 impl SqliteUserUpdater {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new() -> Self {
+        Self
     }
+}
 
-    /// Check for case-insensitive duplicate username excluding current user
-    async fn check_duplicate_username(
+#[async_trait]
+impl UserUpdater for SqliteUserUpdater {
+    async fn find_conflicting_attribute(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
+        id: &str,
         username: &str,
-        exclude_id: &str,
-    ) -> AppResult<()> {
+        external_id: Option<&str>,
+    ) -> AppResult<Option<&'static str>> {
         let table_name = format!("t{}_users", tenant_id);
+        let conn = tx.sqlite()?;
+
         let sql = format!(
             "SELECT COUNT(*) FROM {} WHERE LOWER(username) = LOWER(?1) AND id != ?2",
             table_name
         );
-
         let count: i64 = sqlx::query_scalar(&sql)
             .bind(username)
-            .bind(exclude_id)
-            .fetch_one(&self.pool)
+            .bind(id)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 AppError::Database(format!("Failed to check duplicate username: {}", e))
             })?;
-
         if count > 0 {
-            return Err(AppError::BadRequest("User already exists".to_string()));
+            return Ok(Some("userName"));
         }
 
-        Ok(())
+        if let Some(external_id) = external_id {
+            let sql = format!(
+                "SELECT COUNT(*) FROM {} WHERE external_id = ?1 AND id != ?2",
+                table_name
+            );
+            let count: i64 = sqlx::query_scalar(&sql)
+                .bind(external_id)
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to check duplicate externalId: {}", e))
+                })?;
+            if count > 0 {
+                return Ok(Some("externalId"));
+            }
+        }
+
+        Ok(None)
     }
-}
 
-#[async_trait]
-impl UserUpdater for SqliteUserUpdater {
     async fn execute_user_update(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         _id: &str,
         data: PreparedUserUpdateData,
     ) -> AppResult<Option<User>> {
-        // Check for case-insensitive duplicate username before update
-        self.check_duplicate_username(tenant_id, &data.username, &data.id)
-            .await?;
+        let actor = tx.actor().map(str::to_owned);
 
         // Build table name
         let table_name = format!("t{}_users", tenant_id);
@@ -70,7 +87,7 @@ impl UserUpdater for SqliteUserUpdater {
 
         // SQLite UPDATE SQL with TEXT-based parameter binding and version increment
         let sql = format!(
-            "UPDATE {} SET username = ?1, external_id = ?2, data_orig = ?3, data_norm = ?4, version = version + 1, updated_at = ?5 WHERE id = ?6",
+            "UPDATE {} SET username = ?1, external_id = ?2, data_orig = ?3, data_norm = ?4, version = version + 1, updated_at = ?5, last_modified_by = ?7 WHERE id = ?6",
             table_name
         );
 
@@ -81,9 +98,10 @@ impl UserUpdater for SqliteUserUpdater {
             .bind(&data_norm_str) // SQLite uses TEXT
             .bind(data.timestamp)
             .bind(&data.id)
-            .execute(&self.pool)
+            .bind(&actor)
+            .execute(tx.sqlite()?)
             .await
-            .map_err(|e| map_database_error(e, "User"))?;
+            .map_err(map_user_update_error)?;
 
         if result.rows_affected() > 0 {
             Ok(Some(data.user))
@@ -100,6 +118,21 @@ fn json_value_to_string(value: &Value) -> AppResult<String> {
     serde_json::to_string(value).map_err(AppError::Serialization)
 }
 
+/// Map a unique violation that slipped past the pre-check (e.g. two
+/// concurrent renames) to the same 409 the pre-check returns
+fn map_user_update_error(e: sqlx::Error) -> AppError {
+    if let sqlx::Error::Database(ref db_err) = e {
+        if db_err.is_unique_violation() {
+            if db_err.message().contains("username") {
+                return uniqueness_conflict("userName");
+            } else if db_err.message().contains("external_id") {
+                return uniqueness_conflict("externalId");
+            }
+        }
+    }
+    map_database_error(e, "User")
+}
+
 /// Map SQLite-specific database errors to appropriate application errors
 pub fn map_database_error(e: sqlx::Error, resource_type: &str) -> AppError {
     match e {
@@ -137,14 +170,9 @@ pub fn map_database_error(e: sqlx::Error, resource_type: &str) -> AppError {
 mod tests {
     use super::*;
 
-    async fn create_test_pool() -> SqlitePool {
-        SqlitePool::connect(":memory:").await.unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_updater_creation() {
-        let pool = create_test_pool().await;
-        let updater = SqliteUserUpdater::new(pool);
+    #[test]
+    fn test_updater_creation() {
+        let updater = SqliteUserUpdater::new();
         // Just verify the updater can be created
         assert!(!format!("{:?}", &updater as *const _).is_empty());
     }
@@ -0,0 +1,65 @@
+//! Discovery of the tenants provisioned in a database
+
+use std::collections::BTreeMap;
+
+/// Tables created by `init_tenant`, named `t{tenant_id}_{table}`
+const TENANT_TABLES: [&str; 3] = ["users", "groups", "group_memberships"];
+
+/// Tenant ids whose tables all exist, given the table names of the database
+///
+/// The result is sorted; other tables are ignored.
+pub fn tenant_ids_from_tables<I, S>(tables: I) -> Vec<u32>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut found: BTreeMap<u32, usize> = BTreeMap::new();
+    for table in tables {
+        let Some((id, suffix)) = table
+            .as_ref()
+            .strip_prefix('t')
+            .and_then(|rest| rest.split_once('_'))
+        else {
+            continue;
+        };
+        if !TENANT_TABLES.contains(&suffix) || !id.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        if let Ok(id) = id.parse::<u32>() {
+            *found.entry(id).or_default() += 1;
+        }
+    }
+
+    found
+        .into_iter()
+        .filter(|(_, tables)| *tables == TENANT_TABLES.len())
+        .map(|(id, _)| id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_ids_from_tables() {
+        let tables = [
+            "t12_users",
+            "t12_groups",
+            "t12_group_memberships",
+            "t3_users",
+            "t3_groups",
+            "t3_group_memberships",
+            // Partially provisioned tenant
+            "t7_users",
+            // Unrelated tables
+            "t+1_users",
+            "tx_groups",
+            "t4_audit",
+            "users",
+            "_sqlx_migrations",
+        ];
+        assert_eq!(tenant_ids_from_tables(tables), vec![3, 12]);
+        assert!(tenant_ids_from_tables(Vec::<String>::new()).is_empty());
+    }
+}
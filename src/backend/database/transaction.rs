@@ -0,0 +1,171 @@
+//! Database transactions shared by the unified operations
+//!
+//! The write adapters (`UserInserter`, `GroupUpdater`, ...) never open their
+//! own connections: they run on a `DbTransaction` begun by the backend, so a
+//! flow made of several statements (uniqueness check, row write, membership
+//! rewrite) either commits as a whole or leaves nothing behind. Dropping a
+//! transaction without committing rolls it back.
+
+use tokio::sync::OwnedMutexGuard;
+
+use crate::error::{AppError, AppResult};
+
+#[cfg(feature = "backend-mysql")]
+use sqlx::{MySql, MySqlConnection};
+#[cfg(feature = "backend-postgres")]
+use sqlx::{PgConnection, Postgres};
+#[cfg(feature = "backend-sqlite")]
+use sqlx::{Sqlite, SqliteConnection};
+
+/// An open transaction on one of the database backends
+///
+/// Begun with `Backend::begin_transaction`, so code holding only a
+/// `dyn ScimBackend` can run several writes on it through the `*_in`
+/// operations and commit them together.
+pub struct DbTransaction {
+    connection: Connection,
+    actor: Option<String>,
+    // Serialized-writes lock (SQLite), released when the transaction ends
+    _write_guard: Option<OwnedMutexGuard<()>>,
+}
+
+enum Connection {
+    #[cfg(feature = "backend-mysql")]
+    MySql(sqlx::Transaction<'static, MySql>),
+    #[cfg(feature = "backend-postgres")]
+    Postgres(sqlx::Transaction<'static, Postgres>),
+    #[cfg(feature = "backend-sqlite")]
+    Sqlite(sqlx::Transaction<'static, Sqlite>),
+}
+
+#[cfg(feature = "backend-mysql")]
+impl From<sqlx::Transaction<'static, MySql>> for DbTransaction {
+    fn from(tx: sqlx::Transaction<'static, MySql>) -> Self {
+        Self::new(Connection::MySql(tx))
+    }
+}
+
+#[cfg(feature = "backend-postgres")]
+impl From<sqlx::Transaction<'static, Postgres>> for DbTransaction {
+    fn from(tx: sqlx::Transaction<'static, Postgres>) -> Self {
+        Self::new(Connection::Postgres(tx))
+    }
+}
+
+#[cfg(feature = "backend-sqlite")]
+impl From<sqlx::Transaction<'static, Sqlite>> for DbTransaction {
+    fn from(tx: sqlx::Transaction<'static, Sqlite>) -> Self {
+        Self::new(Connection::Sqlite(tx))
+    }
+}
+
+impl DbTransaction {
+    fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            actor: None,
+            _write_guard: None,
+        }
+    }
+
+    /// Attribute the resources written on this transaction to `actor`
+    pub fn with_actor(mut self, actor: Option<&str>) -> Self {
+        self.actor = actor.map(str::to_string);
+        self
+    }
+
+    /// Principal the writes are attributed to, stored as created_by and
+    /// last_modified_by by the same statements that write the resources
+    pub fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+
+    /// Hold a write lock until the transaction is committed or dropped
+    pub fn with_write_guard(mut self, guard: Option<OwnedMutexGuard<()>>) -> Self {
+        self._write_guard = guard;
+        self
+    }
+
+    /// Make every statement run on this transaction permanent
+    pub async fn commit(self) -> AppResult<()> {
+        let commit_error =
+            |e: sqlx::Error| AppError::Database(format!("Failed to commit transaction: {}", e));
+        match self.connection {
+            #[cfg(feature = "backend-mysql")]
+            Connection::MySql(tx) => tx
+                .commit()
+                .await
+                .map_err(commit_error)
+                .map_err(super::map_connection_error),
+            #[cfg(feature = "backend-postgres")]
+            Connection::Postgres(tx) => tx
+                .commit()
+                .await
+                .map_err(commit_error)
+                .map_err(super::map_connection_error),
+            #[cfg(feature = "backend-sqlite")]
+            Connection::Sqlite(tx) => tx
+                .commit()
+                .await
+                .map_err(commit_error)
+                .map_err(super::sqlite::backend_impl::map_busy_error),
+        }
+    }
+
+    /// Connection of a MySQL transaction
+    #[cfg(feature = "backend-mysql")]
+    pub fn mysql(&mut self) -> AppResult<&mut MySqlConnection> {
+        #[allow(unreachable_patterns)]
+        match &mut self.connection {
+            Connection::MySql(tx) => Ok(&mut **tx),
+            _ => Err(wrong_backend("MySQL")),
+        }
+    }
+
+    /// Connection of a PostgreSQL transaction
+    #[cfg(feature = "backend-postgres")]
+    pub fn postgres(&mut self) -> AppResult<&mut PgConnection> {
+        #[allow(unreachable_patterns)]
+        match &mut self.connection {
+            Connection::Postgres(tx) => Ok(&mut **tx),
+            _ => Err(wrong_backend("PostgreSQL")),
+        }
+    }
+
+    /// Connection of a SQLite transaction
+    #[cfg(feature = "backend-sqlite")]
+    pub fn sqlite(&mut self) -> AppResult<&mut SqliteConnection> {
+        #[allow(unreachable_patterns)]
+        match &mut self.connection {
+            Connection::Sqlite(tx) => Ok(&mut **tx),
+            _ => Err(wrong_backend("SQLite")),
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn wrong_backend(expected: &str) -> AppError {
+    AppError::Internal(format!(
+        "A {} adapter was handed another backend's transaction",
+        expected
+    ))
+}
+
+/// Begin a transaction on a connection pool
+pub async fn begin<DB: sqlx::Database>(
+    pool: &sqlx::Pool<DB>,
+) -> AppResult<sqlx::Transaction<'static, DB>> {
+    pool.begin()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))
+}
+
+/// Begin a transaction with a custom statement such as `BEGIN IMMEDIATE`
+pub async fn begin_with<DB: sqlx::Database>(
+    pool: &sqlx::Pool<DB>,
+    statement: &'static str,
+) -> AppResult<sqlx::Transaction<'static, DB>> {
+    pool.begin_with(statement)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to begin transaction: {}", e)))
+}
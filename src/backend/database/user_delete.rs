@@ -1,12 +1,18 @@
 use async_trait::async_trait;
 
+use super::DbTransaction;
 use crate::error::AppResult;
 
 /// Database-specific adapter for user DELETE operations
 #[async_trait]
 pub trait UserDeleter: Send + Sync {
     /// Execute user delete and return whether the user was found and deleted
-    async fn execute_user_delete(&self, tenant_id: u32, id: &str) -> AppResult<bool>;
+    async fn execute_user_delete(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool>;
 }
 
 /// Shared business logic for user DELETE operations
@@ -39,12 +45,17 @@ impl<T: UserDeleter> UnifiedUserDeleteOps<T> {
     }
 
     /// Delete a user using shared logic and database-specific execution
-    pub async fn delete_user(&self, tenant_id: u32, id: &str) -> AppResult<bool> {
+    pub async fn delete_user(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
         // Validate ID using shared business logic
         UserDeleteProcessor::validate_user_id(id)?;
 
         // Execute database-specific deletion
-        self.deleter.execute_user_delete(tenant_id, id).await
+        self.deleter.execute_user_delete(tx, tenant_id, id).await
     }
 }
 
@@ -3,6 +3,7 @@ use chrono::{DateTime, Utc};
 use serde_json::Value;
 use uuid::Uuid;
 
+use super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
@@ -23,7 +24,12 @@ pub struct PreparedUserData {
 #[async_trait]
 pub trait UserInserter: Send + Sync {
     /// Execute user insert and return the created user
-    async fn execute_user_insert(&self, tenant_id: u32, data: PreparedUserData) -> AppResult<User>;
+    async fn execute_user_insert(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        data: PreparedUserData,
+    ) -> AppResult<User>;
 }
 
 /// Shared business logic for user INSERT operations
@@ -39,10 +45,15 @@ impl UserInsertProcessor {
     /// - Metadata generation
     /// - Data serialization and normalization
     pub fn prepare_user_for_insert(user: &User) -> AppResult<PreparedUserData> {
-        let mut user = user.clone();
-
         // Always generate a new ID (SCIM 2.0 doesn't allow client-specified IDs in POST)
-        let id = Uuid::new_v4().to_string();
+        Self::prepare_user_for_insert_with_id(user, Uuid::new_v4().to_string())
+    }
+
+    /// Prepare user data for insertion under an id chosen by the caller
+    ///
+    /// Used when a PUT creates a missing resource (`put_creates_missing`).
+    pub fn prepare_user_for_insert_with_id(user: &User, id: String) -> AppResult<PreparedUserData> {
+        let mut user = user.clone();
         *user.id_mut() = Some(id.clone());
 
         // Process password if present
@@ -108,7 +119,7 @@ impl UserInsertProcessor {
     fn set_user_metadata(user: &mut User, timestamp: &DateTime<Utc>) {
         let formatted_time = crate::utils::format_scim_datetime(*timestamp);
         let meta = scim_v2::models::scim_schema::Meta {
-            resource_type: Some("User".to_string()),
+            resource_type: Some(crate::parser::ResourceType::User.scim_name().to_string()),
             created: Some(formatted_time.clone()),
             last_modified: Some(formatted_time),
             location: None,
@@ -129,14 +140,42 @@ impl<T: UserInserter> UnifiedUserInsertOps<T> {
     }
 
     /// Create a new user using shared logic and database-specific execution
-    pub async fn create_user(&self, tenant_id: u32, user: &User) -> AppResult<User> {
+    pub async fn create_user(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        user: &User,
+    ) -> AppResult<User> {
         // Prepare data using shared business logic
         let prepared_data = UserInsertProcessor::prepare_user_for_insert(user)?;
+        self.insert_prepared_user(tx, tenant_id, prepared_data)
+            .await
+    }
+
+    /// Create a user under the given id
+    pub async fn create_user_with_id(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<User> {
+        let prepared_data =
+            UserInsertProcessor::prepare_user_for_insert_with_id(user, id.to_string())?;
+        self.insert_prepared_user(tx, tenant_id, prepared_data)
+            .await
+    }
 
+    async fn insert_prepared_user(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        prepared_data: PreparedUserData,
+    ) -> AppResult<User> {
         // Execute database-specific insertion
         let created_user = self
             .inserter
-            .execute_user_insert(tenant_id, prepared_data)
+            .execute_user_insert(tx, tenant_id, prepared_data)
             .await?;
 
         // Apply shared post-processing
@@ -2,10 +2,11 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
 
+use super::DbTransaction;
 use crate::config::CompatibilityConfig;
 use crate::error::{AppError, AppResult};
 use crate::models::{ScimPatchOp, User};
-use crate::parser::patch_parser::ScimPath;
+use crate::parser::patch_parser::{apply_pathless_operation, ScimPath};
 
 /// Common trait for user patch operations across different database backends
 #[async_trait]
@@ -13,13 +14,33 @@ pub trait UserPatcher: Send + Sync {
     /// Execute user patch operations and return the updated user
     async fn execute_user_patch(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         data: PreparedUserPatchData,
     ) -> AppResult<Option<User>>;
 
+    /// Set `active` and the modification metadata in place, leaving the
+    /// rest of the stored document untouched
+    async fn execute_active_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        active: bool,
+        timestamp: DateTime<Utc>,
+    ) -> AppResult<Option<User>>;
+
     /// Find user by ID for patch operations
-    async fn find_user_for_patch(&self, tenant_id: u32, id: &str) -> AppResult<Option<User>>;
+    ///
+    /// Read on the patch's transaction, so the operations apply to the
+    /// stored state the update replaces.
+    async fn find_user_for_patch(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<User>>;
 }
 
 /// Prepared user patch data for database operations
@@ -47,32 +68,62 @@ impl UserPatchProcessor {
     ///
     /// This processes SCIM PATCH operations according to RFC 7644,
     /// applies them to the user object, and prepares the data for storage.
+    ///
+    /// Every operation is applied in memory before anything is written, so
+    /// an invalid operation leaves the stored user unchanged.
     pub async fn apply_patch_operations<P: UserPatcher>(
         patcher: &P,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         patch_ops: &ScimPatchOp,
         compatibility: &CompatibilityConfig,
     ) -> AppResult<Option<User>> {
         // First, find the existing user
-        let mut user = match patcher.find_user_for_patch(tenant_id, id).await? {
+        let mut user = match patcher.find_user_for_patch(tx, tenant_id, id).await? {
             Some(user) => user,
             None => return Ok(None),
         };
 
         // Apply patch operations
         for operation in &patch_ops.operations {
-            let scim_path = ScimPath::parse(&operation.path.clone().unwrap_or_default())?;
+            if compatibility.enforce_immutability {
+                crate::schema::validate_patch_mutability(
+                    operation,
+                    crate::parser::ResourceType::User,
+                )?;
+            }
+
+            // Without a path the value is a partial user object
+            let scim_path = match operation.path.as_deref() {
+                Some(path) if !path.is_empty() => Some(ScimPath::parse(path)?),
+                _ => None,
+            };
 
             // Convert user to JSON for patch operations
             let mut user_json = serde_json::to_value(&user).map_err(AppError::Serialization)?;
 
+            // Clients send active as "false" or 0 too; store a JSON boolean
+            let value = Self::normalize_active_value(
+                operation.path.as_deref(),
+                operation.value.as_ref().unwrap_or(&Value::Null),
+            )?;
+
             // Apply the operation with compatibility settings
-            scim_path.apply_operation_with_compatibility(
+            match scim_path {
+                Some(scim_path) => scim_path.apply_operation_with_compatibility(
+                    &mut user_json,
+                    &operation.op,
+                    &value,
+                    compatibility,
+                )?,
+                None => {
+                    apply_pathless_operation(&mut user_json, &operation.op, &value, compatibility)?
+                }
+            }
+            crate::schema::enforce_single_primaries(
                 &mut user_json,
-                &operation.op,
-                &operation.value.as_ref().unwrap_or(&Value::Null).clone(),
-                compatibility,
+                crate::parser::ResourceType::User,
             )?;
 
             // Convert back to User
@@ -82,13 +133,98 @@ impl UserPatchProcessor {
         // Prepare user data for database storage
         let prepared = Self::prepare_user_for_patch(id, &user)?;
 
-        // Execute the patch via database-specific implementation
-        let result = patcher.execute_user_patch(tenant_id, id, prepared).await?;
+        // IdPs deprovision with a lone `replace active`; once it has been
+        // validated like any other patch, update it in place instead of
+        // rewriting the whole document
+        let result = match Self::single_active_replace(patch_ops)? {
+            Some(active) => {
+                patcher
+                    .execute_active_patch(tx, tenant_id, id, active, prepared.timestamp)
+                    .await?
+            }
+            // Execute the patch via database-specific implementation
+            None => {
+                patcher
+                    .execute_user_patch(tx, tenant_id, id, prepared)
+                    .await?
+            }
+        };
 
         // Finalize the response by removing sensitive data
         Ok(result.map(Self::finalize_user_response))
     }
 
+    /// Convert boolean-like `active` values to JSON booleans
+    ///
+    /// Accepts `true`/`false`, the strings `"true"`/`"false"` (any case) and
+    /// the integers `1`/`0`, both for `path: "active"` and for an `active`
+    /// member of a path-less value object. Other values are rejected.
+    pub fn normalize_active_value(path: Option<&str>, value: &Value) -> AppResult<Value> {
+        match path {
+            Some(path) if path.eq_ignore_ascii_case("active") => Self::coerce_active(value),
+            None | Some("") => match value {
+                Value::Object(obj) => {
+                    let mut obj = obj.clone();
+                    for (key, member) in obj.iter_mut() {
+                        if key.eq_ignore_ascii_case("active") {
+                            *member = Self::coerce_active(member)?;
+                        }
+                    }
+                    Ok(Value::Object(obj))
+                }
+                _ => Ok(value.clone()),
+            },
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// Recognize a request consisting of a single `replace` of `active`
+    ///
+    /// Both `{"path": "active", "value": false}` and the path-less
+    /// `{"value": {"active": false}}` form qualify. Returns the new value.
+    pub fn single_active_replace(patch_ops: &ScimPatchOp) -> AppResult<Option<bool>> {
+        let [operation] = patch_ops.operations.as_slice() else {
+            return Ok(None);
+        };
+        if !operation.op.eq_ignore_ascii_case("replace") {
+            return Ok(None);
+        }
+
+        let value = match (operation.path.as_deref(), operation.value.as_ref()) {
+            (Some(path), Some(value)) if path.eq_ignore_ascii_case("active") => value,
+            (None | Some(""), Some(Value::Object(obj))) if obj.len() == 1 => {
+                match obj.iter().next() {
+                    Some((key, value)) if key.eq_ignore_ascii_case("active") => value,
+                    _ => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        match Self::coerce_active(value)? {
+            Value::Bool(active) => Ok(Some(active)),
+            _ => Ok(None),
+        }
+    }
+
+    fn coerce_active(value: &Value) -> AppResult<Value> {
+        let active = match value {
+            // Nothing to coerce, e.g. the value of a remove operation
+            Value::Null | Value::Bool(_) => return Ok(value.clone()),
+            Value::String(s) if s.trim().eq_ignore_ascii_case("true") => true,
+            Value::String(s) if s.trim().eq_ignore_ascii_case("false") => false,
+            Value::Number(n) if n.as_i64() == Some(1) => true,
+            Value::Number(n) if n.as_i64() == Some(0) => false,
+            _ => {
+                return Err(AppError::InvalidValue(format!(
+                    "active must be a boolean, got {}",
+                    value
+                )))
+            }
+        };
+        Ok(Value::Bool(active))
+    }
+
     /// Prepare user data for database patch
     ///
     /// This processes passwords, validates data, sets metadata,
@@ -99,6 +235,16 @@ impl UserPatchProcessor {
         // Process password if present
         Self::process_password_for_storage(&mut user)?;
 
+        // The patched user does not go through validate_user
+        if let Some(locale) = &user.base.locale {
+            if !crate::schema::validation::validate_locale(locale) {
+                return Err(AppError::InvalidValue(format!(
+                    "Invalid locale format: {}",
+                    locale
+                )));
+            }
+        }
+
         // Ensure ID matches the path parameter
         *user.id_mut() = Some(id.to_string());
 
@@ -143,21 +289,25 @@ impl UserPatchProcessor {
     /// Process password for secure storage
     ///
     /// This applies password hashing if a password is present in the user data.
+    /// Passwords the handler already hashed with the tenant's algorithm are kept.
     fn process_password_for_storage(user: &mut User) -> AppResult<()> {
         if let Some(password) = user.password().clone() {
-            let password_manager =
-                crate::password::PasswordManager::new(crate::password::PasswordAlgorithm::Argon2id);
-            let hashed = password_manager.hash_password(&password)?;
-            *user.password_mut() = Some(hashed);
+            let password_manager = crate::password::PasswordManager::default();
+            if !password_manager.is_hashed_password(&password) {
+                let hashed = password_manager.hash_password(&password)?;
+                *user.password_mut() = Some(hashed);
+            }
         }
         Ok(())
     }
 
     /// Set user metadata for patch operations
     ///
-    /// This updates the lastModified timestamp in the SCIM meta attribute.
+    /// This updates the lastModified timestamp in the SCIM meta attribute and
+    /// normalizes resourceType, which clients may send in any casing.
     fn set_user_metadata(user: &mut User, timestamp: &DateTime<Utc>) {
         if let Some(meta) = user.meta_mut() {
+            meta.resource_type = Some(crate::parser::ResourceType::User.scim_name().to_string());
             meta.last_modified = Some(crate::utils::format_scim_datetime(*timestamp));
         }
     }
@@ -189,6 +339,7 @@ impl<T: UserPatcher> UnifiedUserPatchOps<T> {
     /// Apply SCIM patch operations to a user with full validation and processing
     pub async fn patch_user(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         patch_ops: &ScimPatchOp,
@@ -200,6 +351,7 @@ impl<T: UserPatcher> UnifiedUserPatchOps<T> {
         // Apply patch operations using shared business logic
         UserPatchProcessor::apply_patch_operations(
             &self.patcher,
+            tx,
             tenant_id,
             id,
             patch_ops,
@@ -240,4 +392,107 @@ mod tests {
         assert!(prepared.data_norm.is_object());
         assert!(prepared.timestamp.timestamp() > 0);
     }
+
+    #[test]
+    fn test_normalize_active_value() {
+        use serde_json::json;
+
+        for (input, expected) in [
+            (json!(false), json!(false)),
+            (json!("false"), json!(false)),
+            (json!("TRUE"), json!(true)),
+            (json!(0), json!(false)),
+            (json!(1), json!(true)),
+        ] {
+            assert_eq!(
+                UserPatchProcessor::normalize_active_value(Some("active"), &input).unwrap(),
+                expected
+            );
+        }
+
+        // Path-less replace with an active member
+        assert_eq!(
+            UserPatchProcessor::normalize_active_value(
+                None,
+                &json!({"active": "false", "displayName": "0"})
+            )
+            .unwrap(),
+            json!({"active": false, "displayName": "0"})
+        );
+
+        // Other attributes are left alone
+        assert_eq!(
+            UserPatchProcessor::normalize_active_value(Some("title"), &json!(0)).unwrap(),
+            json!(0)
+        );
+
+        assert!(matches!(
+            UserPatchProcessor::normalize_active_value(Some("active"), &json!("no")),
+            Err(AppError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            UserPatchProcessor::normalize_active_value(Some("active"), &json!(2)),
+            Err(AppError::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_single_active_replace() {
+        use crate::models::ScimPatchOperation;
+        use serde_json::json;
+
+        let patch = |operations: Vec<Value>| ScimPatchOp {
+            schemas: vec!["urn:ietf:params:scim:api:messages:2.0:PatchOp".to_string()],
+            operations: operations
+                .into_iter()
+                .map(|op| serde_json::from_value::<ScimPatchOperation>(op).unwrap())
+                .collect(),
+        };
+
+        for (op, expected) in [
+            (
+                json!({"op": "replace", "path": "active", "value": false}),
+                Some(false),
+            ),
+            (
+                json!({"op": "Replace", "path": "active", "value": "True"}),
+                Some(true),
+            ),
+            (
+                json!({"op": "replace", "value": {"active": false}}),
+                Some(false),
+            ),
+            (json!({"op": "add", "path": "active", "value": false}), None),
+            (
+                json!({"op": "replace", "path": "title", "value": "x"}),
+                None,
+            ),
+            (
+                json!({"op": "replace", "value": {"active": false, "title": "x"}}),
+                None,
+            ),
+        ] {
+            assert_eq!(
+                UserPatchProcessor::single_active_replace(&patch(vec![op.clone()])).unwrap(),
+                expected,
+                "{}",
+                op
+            );
+        }
+
+        // Several operations always take the full path
+        let ops = patch(vec![
+            json!({"op": "replace", "path": "active", "value": false}),
+            json!({"op": "replace", "path": "title", "value": "x"}),
+        ]);
+        assert_eq!(
+            UserPatchProcessor::single_active_replace(&ops).unwrap(),
+            None
+        );
+
+        assert!(UserPatchProcessor::single_active_replace(&patch(vec![
+            json!({"op": "replace", "path": "active", "value": "no"})
+        ]))
+        .is_err());
+    }
 }
@@ -3,6 +3,7 @@
 //! This module provides common interfaces for user read operations
 //! that work across different database backends.
 
+use super::list_query::{external_id_lookup, single_result_page};
 use crate::error::AppResult;
 use crate::models::User;
 use crate::parser::filter_operator::FilterOperator;
@@ -28,6 +29,14 @@ pub trait UserReader: Send + Sync {
         include_groups: bool,
     ) -> AppResult<Option<User>>;
 
+    /// Find a user by externalId (case-exact) through its indexed column
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>>;
+
     /// Find all users with pagination
     async fn find_all_users(
         &self,
@@ -58,13 +67,49 @@ pub trait UserReader: Send + Sync {
         include_groups: bool,
     ) -> AppResult<(Vec<User>, i64)>;
 
-    /// Find users by group ID
-    async fn find_users_by_group_id(
+    /// Find the users in a group or, transitively, in its nested groups
+    ///
+    /// Each user is returned once, ordered by userName. Returns None when
+    /// the group does not exist.
+    async fn find_effective_members(
         &self,
         tenant_id: u32,
         group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
         include_groups: bool,
-    ) -> AppResult<Vec<User>>;
+    ) -> AppResult<Option<(Vec<User>, i64)>>;
+}
+
+/// Condition on a users table selecting the effective members of a group
+///
+/// `group_id` is the dialect's SQL for the bound group id. Nested groups are
+/// followed at most `max_depth` levels below the group; together with UNION
+/// dropping repeated rows, the bound also ends the recursion for membership
+/// cycles.
+pub fn effective_members_condition(
+    groups_table: &str,
+    memberships_table: &str,
+    group_id: &str,
+    max_depth: u32,
+) -> String {
+    format!(
+        "id IN (\
+         WITH RECURSIVE nested (group_id, depth) AS (\
+         SELECT id, 0 FROM {groups} WHERE id = {group_id} \
+         UNION \
+         SELECT m.member_id, n.depth + 1 FROM {memberships} m \
+         INNER JOIN nested n ON m.group_id = n.group_id \
+         WHERE m.member_type = 'Group' AND n.depth < {max_depth}) \
+         SELECT m.member_id FROM {memberships} m \
+         INNER JOIN nested n ON m.group_id = n.group_id \
+         WHERE m.member_type = 'User')",
+        groups = groups_table,
+        memberships = memberships_table,
+        group_id = group_id,
+        max_depth = max_depth
+    )
 }
 
 /// Unified user read operations
@@ -104,6 +149,18 @@ impl<T: UserReader> UnifiedUserReadOps<T> {
             .await
     }
 
+    /// Find a user by externalId (case-exact)
+    pub async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.reader
+            .find_user_by_external_id(tenant_id, external_id, include_groups)
+            .await
+    }
+
     /// Find all users with pagination
     pub async fn find_all_users(
         &self,
@@ -141,6 +198,15 @@ impl<T: UserReader> UnifiedUserReadOps<T> {
         sort_spec: Option<&SortSpec>,
         include_groups: bool,
     ) -> AppResult<(Vec<User>, i64)> {
+        // IdP reconciliation looks users up by externalId on every sync
+        if let Some(external_id) = external_id_lookup(filter) {
+            let user = self
+                .reader
+                .find_user_by_external_id(tenant_id, external_id, include_groups)
+                .await?;
+            return Ok(single_result_page(user, start_index, count));
+        }
+
         self.reader
             .find_users_by_filter(
                 tenant_id,
@@ -153,15 +219,25 @@ impl<T: UserReader> UnifiedUserReadOps<T> {
             .await
     }
 
-    /// Find users by group ID
-    pub async fn find_users_by_group_id(
+    /// Find the users in a group or its nested groups
+    pub async fn find_effective_members(
         &self,
         tenant_id: u32,
         group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
         include_groups: bool,
-    ) -> AppResult<Vec<User>> {
+    ) -> AppResult<Option<(Vec<User>, i64)>> {
         self.reader
-            .find_users_by_group_id(tenant_id, group_id, include_groups)
+            .find_effective_members(
+                tenant_id,
+                group_id,
+                max_depth,
+                start_index,
+                count,
+                include_groups,
+            )
             .await
     }
 }
@@ -1,16 +1,52 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use super::DbTransaction;
 use crate::error::{AppError, AppResult};
 use crate::models::User;
 
+/// Test hook: when set, updates skip the uniqueness pre-check
+static SKIP_CONFLICT_PRECHECK: AtomicBool = AtomicBool::new(false);
+
+/// Skip the uniqueness pre-check so the database constraint fallback is
+/// exercised, as when two concurrent renames both pass the pre-check.
+///
+/// Only meant for tests; the fallback returns the same 409 response.
+#[doc(hidden)]
+#[allow(dead_code)]
+pub fn set_skip_conflict_precheck(skip: bool) {
+    SKIP_CONFLICT_PRECHECK.store(skip, Ordering::SeqCst);
+}
+
+/// 409 `uniqueness` error naming the conflicting attribute
+///
+/// The detail never mentions the user that already holds the value.
+pub fn uniqueness_conflict(attribute: &str) -> AppError {
+    AppError::Conflict(format!("{} is already in use by another user", attribute))
+}
+
 /// Common trait for user update operations across different database backends
 #[async_trait]
 pub trait UserUpdater: Send + Sync {
+    /// Return the first unique attribute (`userName`, then `externalId`)
+    /// whose new value is already held by a user other than `id`.
+    ///
+    /// userName is compared case-insensitively; externalId is case-exact.
+    async fn find_conflicting_attribute(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        username: &str,
+        external_id: Option<&str>,
+    ) -> AppResult<Option<&'static str>>;
+
     /// Execute user update and return the updated user
     async fn execute_user_update(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         data: PreparedUserUpdateData,
@@ -92,21 +128,25 @@ impl UserUpdateProcessor {
     /// Process password for secure storage
     ///
     /// This applies password hashing if a password is present in the user data.
+    /// Passwords the handler already hashed with the tenant's algorithm are kept.
     fn process_password_for_storage(user: &mut User) -> AppResult<()> {
         if let Some(password) = user.password().clone() {
-            let password_manager =
-                crate::password::PasswordManager::new(crate::password::PasswordAlgorithm::Argon2id);
-            let hashed = password_manager.hash_password(&password)?;
-            *user.password_mut() = Some(hashed);
+            let password_manager = crate::password::PasswordManager::default();
+            if !password_manager.is_hashed_password(&password) {
+                let hashed = password_manager.hash_password(&password)?;
+                *user.password_mut() = Some(hashed);
+            }
         }
         Ok(())
     }
 
     /// Set user metadata for update operations
     ///
-    /// This updates the lastModified timestamp in the SCIM meta attribute.
+    /// This updates the lastModified timestamp in the SCIM meta attribute and
+    /// normalizes resourceType, which clients may send in any casing.
     fn set_user_metadata(user: &mut User, timestamp: &DateTime<Utc>) {
         if let Some(meta) = user.meta_mut() {
+            meta.resource_type = Some(crate::parser::ResourceType::User.scim_name().to_string());
             meta.last_modified = Some(crate::utils::format_scim_datetime(*timestamp));
         }
     }
@@ -138,6 +178,7 @@ impl<T: UserUpdater> UnifiedUserUpdateOps<T> {
     /// Update a user with full validation and processing
     pub async fn update_user(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
         id: &str,
         user: &User,
@@ -148,10 +189,28 @@ impl<T: UserUpdater> UnifiedUserUpdateOps<T> {
         // Prepare user data for update
         let prepared = UserUpdateProcessor::prepare_user_for_update(id, user)?;
 
+        // Report which attribute collides before touching the row; the
+        // backends map unique violations to the same error if this races
+        if !SKIP_CONFLICT_PRECHECK.load(Ordering::SeqCst) {
+            if let Some(attribute) = self
+                .updater
+                .find_conflicting_attribute(
+                    tx,
+                    tenant_id,
+                    id,
+                    &prepared.username,
+                    prepared.external_id.as_deref(),
+                )
+                .await?
+            {
+                return Err(uniqueness_conflict(attribute));
+            }
+        }
+
         // Execute the update via database-specific implementation
         let result = self
             .updater
-            .execute_user_update(tenant_id, id, prepared)
+            .execute_user_update(tx, tenant_id, id, prepared)
             .await?;
 
         // Finalize the response by removing sensitive data
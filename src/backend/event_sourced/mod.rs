@@ -0,0 +1,971 @@
+//! Event-sourced backend
+//!
+//! Every create, replace, patch, delete and member change is appended to an
+//! immutable `events` log on the same database transaction that writes the
+//! tenant tables, so a write and its event are committed together or not at
+//! all. The tables of the wrapped database backend act as the materialized
+//! view that serves reads, filters and sorting, and always hold what replaying
+//! the log yields; the log is the source of truth for audit and time-travel
+//! queries. Every `snapshot_every` events of a resource its state is stored in
+//! `snapshots`, so replays start from the latest snapshot instead of the first
+//! event.
+//!
+//! Each event carries the resource as it stands after the operation (no
+//! payload for deletes), so replaying is folding payloads in event order.
+
+use async_trait::async_trait;
+use chrono::{DateTime, SubsecRound, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::backend::database::{DatabaseBackendConfig, DbTransaction};
+use crate::backend::{
+    Backend, DatabaseType, GroupBackend, HistoryBackend, MembersAdded, MembersRemoved, Provenance,
+    ProvenanceBackend, ScimBackend, UserBackend,
+};
+use crate::config::CompatibilityConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::{Group, ScimPatchOp, User};
+use crate::parser::filter_operator::FilterOperator;
+use crate::parser::{ResourceType, SortSpec};
+
+#[cfg(feature = "backend-postgres")]
+pub mod postgres;
+#[cfg(feature = "backend-sqlite")]
+pub mod sqlite;
+
+/// Backend option: events per resource between snapshots ("0" disables them)
+pub const SNAPSHOT_EVERY: &str = "event_sourced.snapshot_every";
+
+/// Snapshot interval used when the config does not set one
+pub const DEFAULT_SNAPSHOT_EVERY: u32 = 100;
+
+/// Kind of mutation recorded by an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOperation {
+    Create,
+    Replace,
+    Patch,
+    Delete,
+    AddMembers,
+    RemoveMembers,
+}
+
+impl EventOperation {
+    /// Name stored in the `operation` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventOperation::Create => "create",
+            EventOperation::Replace => "replace",
+            EventOperation::Patch => "patch",
+            EventOperation::Delete => "delete",
+            EventOperation::AddMembers => "add_members",
+            EventOperation::RemoveMembers => "remove_members",
+        }
+    }
+
+    /// Parse a stored operation name
+    pub fn from_name(name: &str) -> AppResult<Self> {
+        match name {
+            "create" => Ok(EventOperation::Create),
+            "replace" => Ok(EventOperation::Replace),
+            "patch" => Ok(EventOperation::Patch),
+            "delete" => Ok(EventOperation::Delete),
+            "add_members" => Ok(EventOperation::AddMembers),
+            "remove_members" => Ok(EventOperation::RemoveMembers),
+            other => Err(AppError::Database(format!(
+                "Unknown event operation: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for EventOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parse a resource type stored as "User" or "Group"
+pub(crate) fn resource_type_from_name(name: &str) -> AppResult<ResourceType> {
+    match name {
+        "User" => Ok(ResourceType::User),
+        "Group" => Ok(ResourceType::Group),
+        other => Err(AppError::Database(format!(
+            "Unknown resource type in event log: {}",
+            other
+        ))),
+    }
+}
+
+/// An event about to be appended
+#[derive(Debug, Clone)]
+pub struct NewEvent {
+    pub tenant_id: u32,
+    pub resource_type: ResourceType,
+    pub resource_id: String,
+    pub operation: EventOperation,
+    /// Resource state after the operation; None for deletes
+    pub payload: Option<Value>,
+    /// Authenticated principal behind the operation, if any
+    pub actor: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A stored, immutable event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Position in the log; increases with every append
+    pub event_id: i64,
+    pub tenant_id: u32,
+    pub resource_type: ResourceType,
+    pub resource_id: String,
+    pub operation: EventOperation,
+    pub payload: Option<Value>,
+    /// Authenticated principal behind the operation, if any
+    pub actor: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// State of a resource after the event `event_id`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub tenant_id: u32,
+    pub resource_type: ResourceType,
+    pub resource_id: String,
+    pub event_id: i64,
+    /// None when the resource was deleted at that point
+    pub state: Option<Value>,
+    /// Timestamp of the event the snapshot was taken at
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Storage for the event log and snapshots
+///
+/// Everything but `init` runs on the transaction of the write the events
+/// belong to, which must be on the database holding the log.
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    /// Create the `events` and `snapshots` tables if they do not exist
+    async fn init(&self) -> AppResult<()>;
+
+    /// Append an event and return it with its assigned id
+    async fn append(&self, tx: &mut DbTransaction, event: NewEvent) -> AppResult<Event>;
+
+    /// Events of a resource after `after_event_id`, up to `until` if given, in order
+    async fn events(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        after_event_id: i64,
+        until: Option<DateTime<Utc>>,
+    ) -> AppResult<Vec<Event>>;
+
+    /// Latest snapshot of a resource taken at or before `until` if given
+    async fn latest_snapshot(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> AppResult<Option<Snapshot>>;
+
+    /// Store a snapshot
+    async fn save_snapshot(&self, tx: &mut DbTransaction, snapshot: &Snapshot) -> AppResult<()>;
+}
+
+/// Fold events into the state they leave a resource in
+pub fn replay(initial: Option<Value>, events: &[Event]) -> Option<Value> {
+    events
+        .iter()
+        .fold(initial, |_, event| match event.operation {
+            EventOperation::Delete => None,
+            _ => event.payload.clone(),
+        })
+}
+
+fn to_payload<T: serde::Serialize>(resource: &T) -> AppResult<Value> {
+    serde_json::to_value(resource).map_err(AppError::Serialization)
+}
+
+/// Backend that records every mutation in an event log
+///
+/// Mutations are applied to the wrapped backend first, so uniqueness and
+/// existence checks behave exactly as without event sourcing, and are then
+/// appended to the log on the same transaction, attributed to its actor. A
+/// failed append fails the write. Reads go to the wrapped backend.
+pub struct EventSourcedBackend {
+    inner: Box<dyn ScimBackend>,
+    store: Box<dyn EventStore>,
+    snapshot_every: u32,
+}
+
+impl EventSourcedBackend {
+    /// Wrap a backend, logging its mutations to `store`
+    pub fn new(inner: Box<dyn ScimBackend>, store: Box<dyn EventStore>) -> Self {
+        Self {
+            inner,
+            store,
+            snapshot_every: DEFAULT_SNAPSHOT_EVERY,
+        }
+    }
+
+    /// Take a snapshot of a resource after every `snapshot_every` of its events
+    ///
+    /// Zero disables snapshots.
+    pub fn with_snapshot_every(mut self, snapshot_every: u32) -> Self {
+        self.snapshot_every = snapshot_every;
+        self
+    }
+
+    /// Replay a resource on `tx`, returning how many events follow its latest
+    /// snapshot along with the state they lead to
+    async fn replay_on(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> AppResult<(usize, Option<Value>)> {
+        let snapshot = self
+            .store
+            .latest_snapshot(tx, tenant_id, resource_type, resource_id, until)
+            .await?;
+        let (after_event_id, initial) = match snapshot {
+            Some(snapshot) => (snapshot.event_id, snapshot.state),
+            None => (0, None),
+        };
+        let events = self
+            .store
+            .events(
+                tx,
+                tenant_id,
+                resource_type,
+                resource_id,
+                after_event_id,
+                until,
+            )
+            .await?;
+        Ok((events.len(), replay(initial, &events)))
+    }
+
+    /// Append an event on the write's transaction, snapshotting the resource
+    /// when the interval is reached
+    async fn record(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        operation: EventOperation,
+        payload: Option<Value>,
+    ) -> AppResult<()> {
+        let event = self
+            .store
+            .append(
+                tx,
+                NewEvent {
+                    tenant_id,
+                    resource_type,
+                    resource_id: resource_id.to_string(),
+                    operation,
+                    payload,
+                    actor: tx.actor().map(str::to_string),
+                    // Both stores keep microseconds; match them in the returned event
+                    timestamp: Utc::now().trunc_subsecs(6),
+                },
+            )
+            .await?;
+
+        if self.snapshot_every == 0 {
+            return Ok(());
+        }
+
+        let (pending, state) = self
+            .replay_on(tx, tenant_id, resource_type, resource_id, None)
+            .await?;
+        if pending < self.snapshot_every as usize {
+            return Ok(());
+        }
+
+        self.store
+            .save_snapshot(
+                tx,
+                &Snapshot {
+                    tenant_id,
+                    resource_type,
+                    resource_id: resource_id.to_string(),
+                    event_id: event.event_id,
+                    state,
+                    timestamp: event.timestamp,
+                },
+            )
+            .await
+    }
+
+    async fn record_user(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        user: &User,
+        operation: EventOperation,
+    ) -> AppResult<()> {
+        let Some(id) = user.id() else {
+            return Ok(());
+        };
+        self.record(
+            tx,
+            tenant_id,
+            ResourceType::User,
+            id,
+            operation,
+            Some(to_payload(user)?),
+        )
+        .await
+    }
+
+    async fn record_group(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group: &Group,
+        operation: EventOperation,
+    ) -> AppResult<()> {
+        self.record(
+            tx,
+            tenant_id,
+            ResourceType::Group,
+            group.id(),
+            operation,
+            Some(to_payload(group)?),
+        )
+        .await
+    }
+
+    /// Record the current state of a group after a member change on `tx`
+    async fn record_members_change(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        operation: EventOperation,
+    ) -> AppResult<()> {
+        if let Some(group) = self
+            .inner
+            .find_group_by_id_in(tx, tenant_id, group_id)
+            .await?
+        {
+            self.record_group(tx, tenant_id, &group, operation).await?;
+        }
+        Ok(())
+    }
+
+    /// Record the membership removals a delete cascaded into other groups
+    async fn record_cascade(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_ids: Vec<String>,
+    ) -> AppResult<()> {
+        for group_id in group_ids {
+            self.record_members_change(tx, tenant_id, &group_id, EventOperation::RemoveMembers)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Backend for EventSourcedBackend {
+    async fn connect(config: &DatabaseBackendConfig) -> AppResult<Self> {
+        let snapshot_every = match config.get_option(SNAPSHOT_EVERY) {
+            Some(value) => value.parse().map_err(|_| {
+                AppError::Configuration(format!("Invalid {}: {}", SNAPSHOT_EVERY, value))
+            })?,
+            None => DEFAULT_SNAPSHOT_EVERY,
+        };
+
+        let backend = match &config.database_type {
+            #[cfg(feature = "backend-postgres")]
+            DatabaseType::PostgreSQL => {
+                let inner =
+                    crate::backend::database::postgres::PostgresBackend::connect(config).await?;
+                let store = postgres::PostgresEventStore::new(inner.pool().clone());
+                Self::new(Box::new(inner), Box::new(store))
+            }
+            #[cfg(feature = "backend-sqlite")]
+            DatabaseType::SQLite => {
+                let inner =
+                    crate::backend::database::sqlite::SqliteBackend::connect(config).await?;
+                let store = sqlite::SqliteEventStore::new(inner.pool().clone());
+                Self::new(Box::new(inner), Box::new(store))
+            }
+            DatabaseType::MySQL => {
+                return Err(AppError::Configuration(
+                    "The event_sourced backend does not support MySQL yet".to_string(),
+                ))
+            }
+            #[allow(unreachable_patterns)]
+            other => {
+                return Err(AppError::Configuration(format!(
+                    "{:?} support is not compiled in; enable the matching backend feature",
+                    other
+                )))
+            }
+        };
+
+        Ok(backend.with_snapshot_every(snapshot_every))
+    }
+
+    async fn health_check(&self) -> AppResult<()> {
+        self.inner.health_check().await
+    }
+
+    async fn begin_transaction(&self) -> AppResult<DbTransaction> {
+        self.inner.begin_transaction().await
+    }
+
+    async fn init_tenant(&self, tenant_id: u32) -> AppResult<()> {
+        self.inner.init_tenant(tenant_id).await?;
+        // The log is shared by all tenants; creating it is idempotent
+        self.store.init().await
+    }
+
+    async fn list_initialized_tenants(&self) -> AppResult<Vec<u32>> {
+        self.inner.list_initialized_tenants().await
+    }
+
+    async fn vacuum_tenant(&self, tenant_id: u32) -> AppResult<()> {
+        self.inner.vacuum_tenant(tenant_id).await
+    }
+
+    async fn load_sync_stats(&self, tenant_id: u32) -> AppResult<Option<serde_json::Value>> {
+        self.inner.load_sync_stats(tenant_id).await
+    }
+
+    async fn save_sync_stats(&self, tenant_id: u32, snapshot: &serde_json::Value) -> AppResult<()> {
+        self.inner.save_sync_stats(tenant_id, snapshot).await
+    }
+
+    async fn cleanup(&self) -> AppResult<()> {
+        self.inner.cleanup().await
+    }
+}
+
+#[async_trait]
+impl UserBackend for EventSourcedBackend {
+    async fn create_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        user: &User,
+    ) -> AppResult<User> {
+        let created = self.inner.create_user_in(tx, tenant_id, user).await?;
+        self.record_user(tx, tenant_id, &created, EventOperation::Create)
+            .await?;
+        Ok(created)
+    }
+
+    async fn create_user_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<User> {
+        let created = self
+            .inner
+            .create_user_with_id_in(tx, tenant_id, id, user)
+            .await?;
+        self.record_user(tx, tenant_id, &created, EventOperation::Create)
+            .await?;
+        Ok(created)
+    }
+
+    async fn find_user_by_id(
+        &self,
+        tenant_id: u32,
+        id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.inner
+            .find_user_by_id(tenant_id, id, include_groups)
+            .await
+    }
+
+    async fn find_user_by_username(
+        &self,
+        tenant_id: u32,
+        username: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.inner
+            .find_user_by_username(tenant_id, username, include_groups)
+            .await
+    }
+
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>> {
+        self.inner
+            .find_user_by_external_id(tenant_id, external_id, include_groups)
+            .await
+    }
+
+    async fn find_all_users(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.inner
+            .find_all_users(tenant_id, start_index, count, include_groups)
+            .await
+    }
+
+    async fn find_all_users_sorted(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.inner
+            .find_all_users_sorted(tenant_id, start_index, count, sort_spec, include_groups)
+            .await
+    }
+
+    async fn find_users_by_filter(
+        &self,
+        tenant_id: u32,
+        filter: &FilterOperator,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+        include_groups: bool,
+    ) -> AppResult<(Vec<User>, i64)> {
+        self.inner
+            .find_users_by_filter(
+                tenant_id,
+                filter,
+                start_index,
+                count,
+                sort_spec,
+                include_groups,
+            )
+            .await
+    }
+
+    async fn update_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<Option<User>> {
+        let updated = self.inner.update_user_in(tx, tenant_id, id, user).await?;
+        if let Some(ref updated) = updated {
+            self.record_user(tx, tenant_id, updated, EventOperation::Replace)
+                .await?;
+        }
+        Ok(updated)
+    }
+
+    async fn patch_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
+    ) -> AppResult<Option<User>> {
+        let patched = self
+            .inner
+            .patch_user_in(tx, tenant_id, id, patch_ops, compatibility)
+            .await?;
+        if let Some(ref patched) = patched {
+            self.record_user(tx, tenant_id, patched, EventOperation::Patch)
+                .await?;
+        }
+        Ok(patched)
+    }
+
+    async fn delete_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        let groups = self
+            .inner
+            .find_group_ids_with_member_in(tx, tenant_id, id)
+            .await?;
+        let deleted = self.inner.delete_user_in(tx, tenant_id, id).await?;
+        if deleted {
+            self.record(
+                tx,
+                tenant_id,
+                ResourceType::User,
+                id,
+                EventOperation::Delete,
+                None,
+            )
+            .await?;
+            self.record_cascade(tx, tenant_id, groups).await?;
+        }
+        Ok(deleted)
+    }
+}
+
+#[async_trait]
+impl GroupBackend for EventSourcedBackend {
+    async fn create_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group: &Group,
+    ) -> AppResult<Group> {
+        let created = self.inner.create_group_in(tx, tenant_id, group).await?;
+        self.record_group(tx, tenant_id, &created, EventOperation::Create)
+            .await?;
+        Ok(created)
+    }
+
+    async fn create_group_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Group> {
+        let created = self
+            .inner
+            .create_group_with_id_in(tx, tenant_id, id, group)
+            .await?;
+        self.record_group(tx, tenant_id, &created, EventOperation::Create)
+            .await?;
+        Ok(created)
+    }
+
+    async fn find_group_by_id(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>> {
+        self.inner.find_group_by_id(tenant_id, id).await
+    }
+
+    async fn find_group_by_display_name(
+        &self,
+        tenant_id: u32,
+        display_name: &str,
+    ) -> AppResult<Option<Group>> {
+        self.inner
+            .find_group_by_display_name(tenant_id, display_name)
+            .await
+    }
+
+    async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.inner
+            .find_group_by_external_id(tenant_id, external_id)
+            .await
+    }
+
+    async fn find_all_groups(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.inner
+            .find_all_groups(tenant_id, start_index, count)
+            .await
+    }
+
+    async fn find_all_groups_sorted(
+        &self,
+        tenant_id: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.inner
+            .find_all_groups_sorted(tenant_id, start_index, count, sort_spec)
+            .await
+    }
+
+    async fn find_groups_by_filter(
+        &self,
+        tenant_id: u32,
+        filter: &FilterOperator,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        sort_spec: Option<&SortSpec>,
+    ) -> AppResult<(Vec<Group>, i64)> {
+        self.inner
+            .find_groups_by_filter(tenant_id, filter, start_index, count, sort_spec)
+            .await
+    }
+
+    async fn update_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Option<Group>> {
+        let updated = self.inner.update_group_in(tx, tenant_id, id, group).await?;
+        if let Some(ref updated) = updated {
+            self.record_group(tx, tenant_id, updated, EventOperation::Replace)
+                .await?;
+        }
+        Ok(updated)
+    }
+
+    async fn patch_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
+    ) -> AppResult<Option<Group>> {
+        let patched = self
+            .inner
+            .patch_group_in(tx, tenant_id, id, patch_ops, compatibility)
+            .await?;
+        if let Some(ref patched) = patched {
+            self.record_group(tx, tenant_id, patched, EventOperation::Patch)
+                .await?;
+        }
+        Ok(patched)
+    }
+
+    async fn delete_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool> {
+        let parents = self
+            .inner
+            .find_group_ids_with_member_in(tx, tenant_id, id)
+            .await?;
+        let deleted = self.inner.delete_group_in(tx, tenant_id, id).await?;
+        if deleted {
+            self.record(
+                tx,
+                tenant_id,
+                ResourceType::Group,
+                id,
+                EventOperation::Delete,
+                None,
+            )
+            .await?;
+            self.record_cascade(tx, tenant_id, parents).await?;
+        }
+        Ok(deleted)
+    }
+
+    async fn add_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersAdded>> {
+        let outcome = self
+            .inner
+            .add_group_members_in(tx, tenant_id, group_id, ids)
+            .await?;
+        if matches!(outcome, Some(ref added) if added.added > 0) {
+            self.record_members_change(tx, tenant_id, group_id, EventOperation::AddMembers)
+                .await?;
+        }
+        Ok(outcome)
+    }
+
+    async fn remove_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersRemoved>> {
+        let outcome = self
+            .inner
+            .remove_group_members_in(tx, tenant_id, group_id, ids)
+            .await?;
+        if matches!(outcome, Some(ref removed) if removed.removed > 0) {
+            self.record_members_change(tx, tenant_id, group_id, EventOperation::RemoveMembers)
+                .await?;
+        }
+        Ok(outcome)
+    }
+
+    async fn find_group_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>> {
+        self.inner.find_group_by_id_in(tx, tenant_id, id).await
+    }
+
+    async fn find_group_ids_with_member_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        member_id: &str,
+    ) -> AppResult<Vec<String>> {
+        self.inner
+            .find_group_ids_with_member_in(tx, tenant_id, member_id)
+            .await
+    }
+
+    async fn find_effective_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<Option<(Vec<User>, i64)>> {
+        self.inner
+            .find_effective_members(
+                tenant_id,
+                group_id,
+                max_depth,
+                start_index,
+                count,
+                include_groups,
+            )
+            .await
+    }
+
+    async fn count_group_members(
+        &self,
+        tenant_id: u32,
+        group_ids: &[String],
+    ) -> AppResult<HashMap<String, u64>> {
+        self.inner.count_group_members(tenant_id, group_ids).await
+    }
+}
+
+#[async_trait]
+impl HistoryBackend for EventSourcedBackend {
+    fn keeps_history(&self) -> bool {
+        true
+    }
+
+    async fn history(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+    ) -> AppResult<Vec<Event>> {
+        let mut tx = self.inner.begin_transaction().await?;
+        self.store
+            .events(&mut tx, tenant_id, resource_type, resource_id, 0, None)
+            .await
+    }
+
+    /// Replayed from the latest snapshot taken at or before `at`
+    async fn resource_at(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        at: Option<DateTime<Utc>>,
+    ) -> AppResult<Option<Value>> {
+        // One transaction, so the snapshot and the events after it agree
+        let mut tx = self.inner.begin_transaction().await?;
+        self.replay_on(&mut tx, tenant_id, resource_type, resource_id, at)
+            .await
+            .map(|(_, state)| state)
+    }
+}
+
+#[async_trait]
+impl ProvenanceBackend for EventSourcedBackend {
+    async fn find_provenance(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        ids: &[String],
+    ) -> AppResult<HashMap<String, Provenance>> {
+        self.inner
+            .find_provenance(tenant_id, resource_type, ids)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(event_id: i64, operation: EventOperation, payload: Option<Value>) -> Event {
+        Event {
+            event_id,
+            tenant_id: 1,
+            resource_type: ResourceType::User,
+            resource_id: "u1".to_string(),
+            operation,
+            payload,
+            actor: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        assert_eq!(replay(None, &[]), None);
+        assert_eq!(
+            replay(Some(json!({"userName": "snap"})), &[]),
+            Some(json!({"userName": "snap"}))
+        );
+
+        let events = [
+            event(1, EventOperation::Create, Some(json!({"userName": "a"}))),
+            event(2, EventOperation::Patch, Some(json!({"userName": "b"}))),
+        ];
+        assert_eq!(replay(None, &events), Some(json!({"userName": "b"})));
+
+        let events = [
+            event(3, EventOperation::Replace, Some(json!({"userName": "c"}))),
+            event(4, EventOperation::Delete, None),
+        ];
+        assert_eq!(replay(Some(json!({"userName": "b"})), &events), None);
+    }
+
+    #[test]
+    fn test_operation_names_round_trip() {
+        for operation in [
+            EventOperation::Create,
+            EventOperation::Replace,
+            EventOperation::Patch,
+            EventOperation::Delete,
+            EventOperation::AddMembers,
+            EventOperation::RemoveMembers,
+        ] {
+            assert_eq!(
+                EventOperation::from_name(operation.as_str()).unwrap(),
+                operation
+            );
+        }
+        assert!(EventOperation::from_name("upsert").is_err());
+    }
+}
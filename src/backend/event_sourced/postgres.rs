@@ -0,0 +1,207 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+
+use super::{resource_type_from_name, Event, EventOperation, EventStore, NewEvent, Snapshot};
+use crate::backend::database::DbTransaction;
+use crate::error::{AppError, AppResult};
+use crate::parser::ResourceType;
+
+type EventRow = (
+    i64,
+    i64,
+    String,
+    String,
+    String,
+    Option<Value>,
+    Option<String>,
+    DateTime<Utc>,
+);
+
+type SnapshotRow = (i64, String, String, i64, Option<Value>, DateTime<Utc>);
+
+fn event_from_row(row: EventRow) -> AppResult<Event> {
+    let (event_id, tenant_id, resource_type, resource_id, operation, payload, actor, timestamp) =
+        row;
+    Ok(Event {
+        event_id,
+        tenant_id: tenant_id as u32,
+        resource_type: resource_type_from_name(&resource_type)?,
+        resource_id,
+        operation: EventOperation::from_name(&operation)?,
+        payload,
+        actor,
+        timestamp,
+    })
+}
+
+/// Event log stored in the PostgreSQL database of the wrapped backend
+///
+/// The pool only creates the tables; events and snapshots are read and
+/// written on the transaction of the write they belong to.
+pub struct PostgresEventStore {
+    pool: PgPool,
+}
+
+impl PostgresEventStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventStore for PostgresEventStore {
+    async fn init(&self) -> AppResult<()> {
+        let statements = [
+            r#"
+            CREATE TABLE IF NOT EXISTS events (
+                event_id BIGSERIAL PRIMARY KEY,
+                tenant_id BIGINT NOT NULL,
+                resource_type TEXT NOT NULL,
+                resource_id TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                payload JSONB,
+                actor TEXT,
+                timestamp TIMESTAMP WITH TIME ZONE NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_events_resource \
+             ON events (tenant_id, resource_type, resource_id, event_id)",
+            r#"
+            CREATE TABLE IF NOT EXISTS snapshots (
+                tenant_id BIGINT NOT NULL,
+                resource_type TEXT NOT NULL,
+                resource_id TEXT NOT NULL,
+                event_id BIGINT NOT NULL,
+                state JSONB,
+                timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+                PRIMARY KEY (tenant_id, resource_type, resource_id, event_id)
+            )
+            "#,
+        ];
+
+        for sql in statements {
+            sqlx::query(sql)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to create event log: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    async fn append(&self, tx: &mut DbTransaction, event: NewEvent) -> AppResult<Event> {
+        let event_id: i64 = sqlx::query_scalar(
+            "INSERT INTO events \
+             (tenant_id, resource_type, resource_id, operation, payload, actor, timestamp) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING event_id",
+        )
+        .bind(event.tenant_id as i64)
+        .bind(event.resource_type.to_string())
+        .bind(&event.resource_id)
+        .bind(event.operation.as_str())
+        .bind(&event.payload)
+        .bind(&event.actor)
+        .bind(event.timestamp)
+        .fetch_one(tx.postgres()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to append event: {}", e)))?;
+
+        Ok(Event {
+            event_id,
+            tenant_id: event.tenant_id,
+            resource_type: event.resource_type,
+            resource_id: event.resource_id,
+            operation: event.operation,
+            payload: event.payload,
+            actor: event.actor,
+            timestamp: event.timestamp,
+        })
+    }
+
+    async fn events(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        after_event_id: i64,
+        until: Option<DateTime<Utc>>,
+    ) -> AppResult<Vec<Event>> {
+        let rows: Vec<EventRow> = sqlx::query_as(
+            "SELECT event_id, tenant_id, resource_type, resource_id, operation, payload, \
+             actor, timestamp FROM events \
+             WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3 \
+             AND event_id > $4 AND ($5::timestamptz IS NULL OR timestamp <= $5) \
+             ORDER BY event_id",
+        )
+        .bind(tenant_id as i64)
+        .bind(resource_type.to_string())
+        .bind(resource_id)
+        .bind(after_event_id)
+        .bind(until)
+        .fetch_all(tx.postgres()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to read events: {}", e)))?;
+
+        rows.into_iter().map(event_from_row).collect()
+    }
+
+    async fn latest_snapshot(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> AppResult<Option<Snapshot>> {
+        let row: Option<SnapshotRow> = sqlx::query_as(
+            "SELECT tenant_id, resource_type, resource_id, event_id, state, timestamp \
+             FROM snapshots \
+             WHERE tenant_id = $1 AND resource_type = $2 AND resource_id = $3 \
+             AND ($4::timestamptz IS NULL OR timestamp <= $4) \
+             ORDER BY event_id DESC LIMIT 1",
+        )
+        .bind(tenant_id as i64)
+        .bind(resource_type.to_string())
+        .bind(resource_id)
+        .bind(until)
+        .fetch_optional(tx.postgres()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to read snapshot: {}", e)))?;
+
+        row.map(
+            |(tenant_id, resource_type, resource_id, event_id, state, timestamp)| {
+                Ok(Snapshot {
+                    tenant_id: tenant_id as u32,
+                    resource_type: resource_type_from_name(&resource_type)?,
+                    resource_id,
+                    event_id,
+                    state,
+                    timestamp,
+                })
+            },
+        )
+        .transpose()
+    }
+
+    async fn save_snapshot(&self, tx: &mut DbTransaction, snapshot: &Snapshot) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO snapshots \
+             (tenant_id, resource_type, resource_id, event_id, state, timestamp) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (tenant_id, resource_type, resource_id, event_id) \
+             DO UPDATE SET state = EXCLUDED.state",
+        )
+        .bind(snapshot.tenant_id as i64)
+        .bind(snapshot.resource_type.to_string())
+        .bind(&snapshot.resource_id)
+        .bind(snapshot.event_id)
+        .bind(&snapshot.state)
+        .bind(snapshot.timestamp)
+        .execute(tx.postgres()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to save snapshot: {}", e)))?;
+        Ok(())
+    }
+}
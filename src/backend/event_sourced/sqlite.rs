@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use super::{resource_type_from_name, Event, EventOperation, EventStore, NewEvent, Snapshot};
+use crate::backend::database::DbTransaction;
+use crate::error::{AppError, AppResult};
+use crate::parser::ResourceType;
+
+/// Fixed-width UTC timestamps, so text comparison orders them chronologically
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.6fZ";
+
+type EventRow = (
+    i64,
+    i64,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    String,
+);
+
+type SnapshotRow = (i64, String, String, i64, Option<String>, String);
+
+fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.format(TIMESTAMP_FORMAT).to_string()
+}
+
+fn parse_timestamp(value: &str) -> AppResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| AppError::Database(format!("Invalid event timestamp '{}': {}", value, e)))
+}
+
+fn parse_json(value: Option<String>) -> AppResult<Option<Value>> {
+    value
+        .map(|v| serde_json::from_str(&v).map_err(AppError::Serialization))
+        .transpose()
+}
+
+fn event_from_row(row: EventRow) -> AppResult<Event> {
+    let (event_id, tenant_id, resource_type, resource_id, operation, payload, actor, timestamp) =
+        row;
+    Ok(Event {
+        event_id,
+        tenant_id: tenant_id as u32,
+        resource_type: resource_type_from_name(&resource_type)?,
+        resource_id,
+        operation: EventOperation::from_name(&operation)?,
+        payload: parse_json(payload)?,
+        actor,
+        timestamp: parse_timestamp(&timestamp)?,
+    })
+}
+
+/// Event log stored in the SQLite database of the wrapped backend
+///
+/// The pool only creates the tables; events and snapshots are read and
+/// written on the transaction of the write they belong to.
+pub struct SqliteEventStore {
+    pool: SqlitePool,
+}
+
+impl SqliteEventStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventStore for SqliteEventStore {
+    async fn init(&self) -> AppResult<()> {
+        let statements = [
+            r#"
+            CREATE TABLE IF NOT EXISTS events (
+                event_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                tenant_id INTEGER NOT NULL,
+                resource_type TEXT NOT NULL,
+                resource_id TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                payload TEXT,
+                actor TEXT,
+                timestamp TEXT NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_events_resource \
+             ON events (tenant_id, resource_type, resource_id, event_id)",
+            r#"
+            CREATE TABLE IF NOT EXISTS snapshots (
+                tenant_id INTEGER NOT NULL,
+                resource_type TEXT NOT NULL,
+                resource_id TEXT NOT NULL,
+                event_id INTEGER NOT NULL,
+                state TEXT,
+                timestamp TEXT NOT NULL,
+                PRIMARY KEY (tenant_id, resource_type, resource_id, event_id)
+            )
+            "#,
+        ];
+
+        for sql in statements {
+            sqlx::query(sql)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to create event log: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    async fn append(&self, tx: &mut DbTransaction, event: NewEvent) -> AppResult<Event> {
+        let payload = event.payload.as_ref().map(Value::to_string);
+        let event_id: i64 = sqlx::query_scalar(
+            "INSERT INTO events \
+             (tenant_id, resource_type, resource_id, operation, payload, actor, timestamp) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7) RETURNING event_id",
+        )
+        .bind(event.tenant_id as i64)
+        .bind(event.resource_type.to_string())
+        .bind(&event.resource_id)
+        .bind(event.operation.as_str())
+        .bind(payload)
+        .bind(&event.actor)
+        .bind(format_timestamp(&event.timestamp))
+        .fetch_one(tx.sqlite()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to append event: {}", e)))?;
+
+        Ok(Event {
+            event_id,
+            tenant_id: event.tenant_id,
+            resource_type: event.resource_type,
+            resource_id: event.resource_id,
+            operation: event.operation,
+            payload: event.payload,
+            actor: event.actor,
+            timestamp: event.timestamp,
+        })
+    }
+
+    async fn events(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        after_event_id: i64,
+        until: Option<DateTime<Utc>>,
+    ) -> AppResult<Vec<Event>> {
+        let rows: Vec<EventRow> = sqlx::query_as(
+            "SELECT event_id, tenant_id, resource_type, resource_id, operation, payload, \
+             actor, timestamp FROM events \
+             WHERE tenant_id = ?1 AND resource_type = ?2 AND resource_id = ?3 \
+             AND event_id > ?4 AND (?5 IS NULL OR timestamp <= ?5) \
+             ORDER BY event_id",
+        )
+        .bind(tenant_id as i64)
+        .bind(resource_type.to_string())
+        .bind(resource_id)
+        .bind(after_event_id)
+        .bind(until.as_ref().map(format_timestamp))
+        .fetch_all(tx.sqlite()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to read events: {}", e)))?;
+
+        rows.into_iter().map(event_from_row).collect()
+    }
+
+    async fn latest_snapshot(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        resource_id: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> AppResult<Option<Snapshot>> {
+        let row: Option<SnapshotRow> = sqlx::query_as(
+            "SELECT tenant_id, resource_type, resource_id, event_id, state, timestamp \
+             FROM snapshots \
+             WHERE tenant_id = ?1 AND resource_type = ?2 AND resource_id = ?3 \
+             AND (?4 IS NULL OR timestamp <= ?4) \
+             ORDER BY event_id DESC LIMIT 1",
+        )
+        .bind(tenant_id as i64)
+        .bind(resource_type.to_string())
+        .bind(resource_id)
+        .bind(until.as_ref().map(format_timestamp))
+        .fetch_optional(tx.sqlite()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to read snapshot: {}", e)))?;
+
+        row.map(
+            |(tenant_id, resource_type, resource_id, event_id, state, timestamp)| {
+                Ok(Snapshot {
+                    tenant_id: tenant_id as u32,
+                    resource_type: resource_type_from_name(&resource_type)?,
+                    resource_id,
+                    event_id,
+                    state: parse_json(state)?,
+                    timestamp: parse_timestamp(&timestamp)?,
+                })
+            },
+        )
+        .transpose()
+    }
+
+    async fn save_snapshot(&self, tx: &mut DbTransaction, snapshot: &Snapshot) -> AppResult<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO snapshots \
+             (tenant_id, resource_type, resource_id, event_id, state, timestamp) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(snapshot.tenant_id as i64)
+        .bind(snapshot.resource_type.to_string())
+        .bind(&snapshot.resource_id)
+        .bind(snapshot.event_id)
+        .bind(snapshot.state.as_ref().map(Value::to_string))
+        .bind(format_timestamp(&snapshot.timestamp))
+        .execute(tx.sqlite()?)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to save snapshot: {}", e)))?;
+        Ok(())
+    }
+}
@@ -1,19 +1,26 @@
+use crate::backend::database::DbTransaction;
+use crate::backend::event_sourced::Event;
 use crate::config::CompatibilityConfig;
 use crate::error::AppResult;
 use crate::models::ScimPatchOp;
 use crate::models::{Group, User};
 use crate::parser::filter_operator::FilterOperator;
-use crate::parser::SortSpec;
+use crate::parser::{ResourceType, SortSpec};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 pub mod database;
+pub mod event_sourced;
 
 /// Supported database backend types
 #[derive(Debug, Clone, PartialEq)]
 pub enum DatabaseType {
     PostgreSQL,
     SQLite,
+    MySQL,
 }
 
 /// Core backend abstraction for SCIM resources
@@ -32,9 +39,29 @@ pub trait Backend: Send + Sync {
     #[allow(dead_code)]
     async fn health_check(&self) -> AppResult<()>;
 
+    /// Begin a transaction for the `*_in` write operations
+    ///
+    /// Nothing written on it becomes visible until `DbTransaction::commit`;
+    /// dropping it rolls back. Reads through the other methods do not see
+    /// its writes before the commit.
+    async fn begin_transaction(&self) -> AppResult<DbTransaction>;
+
     /// Initialize tenant-specific schemas/tables if needed
     async fn init_tenant(&self, tenant_id: u32) -> AppResult<()>;
 
+    /// Ids of the tenants whose schemas/tables exist, in ascending order
+    async fn list_initialized_tenants(&self) -> AppResult<Vec<u32>>;
+
+    /// Reclaim space and refresh statistics of an initialized tenant's tables,
+    /// e.g. after bulk deletes (`VACUUM`/`ANALYZE`)
+    async fn vacuum_tenant(&self, tenant_id: u32) -> AppResult<()>;
+
+    /// Latest sync statistics snapshot saved for a tenant (`server.stats.persist`)
+    async fn load_sync_stats(&self, tenant_id: u32) -> AppResult<Option<serde_json::Value>>;
+
+    /// Replace the sync statistics snapshot saved for a tenant
+    async fn save_sync_stats(&self, tenant_id: u32, snapshot: &serde_json::Value) -> AppResult<()>;
+
     /// Clean up resources when storage is no longer needed
     #[allow(dead_code)]
     async fn cleanup(&self) -> AppResult<()> {
@@ -49,7 +76,53 @@ pub trait Backend: Send + Sync {
 #[async_trait]
 pub trait UserBackend: Backend {
     /// Create a new user in the specified tenant
-    async fn create_user(&self, tenant_id: u32, user: &User) -> AppResult<User>;
+    ///
+    /// `actor` is the principal the write is attributed to, stored as the
+    /// user's created_by and last_modified_by.
+    async fn create_user(
+        &self,
+        tenant_id: u32,
+        user: &User,
+        actor: Option<&str>,
+    ) -> AppResult<User> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let created = self.create_user_in(&mut tx, tenant_id, user).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// Create a user under an id chosen by the client (PUT to a missing id)
+    async fn create_user_with_id(
+        &self,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+        actor: Option<&str>,
+    ) -> AppResult<User> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let created = self
+            .create_user_with_id_in(&mut tx, tenant_id, id, user)
+            .await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// Create a new user on an open transaction
+    async fn create_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        user: &User,
+    ) -> AppResult<User>;
+
+    /// Create a user under a client-chosen id on an open transaction
+    async fn create_user_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+    ) -> AppResult<User>;
 
     /// Find a user by ID within a tenant
     async fn find_user_by_id(
@@ -68,6 +141,15 @@ pub trait UserBackend: Backend {
         include_groups: bool,
     ) -> AppResult<Option<User>>;
 
+    /// Find a user by externalId (case-exact), e.g. for IdP reconciliation
+    #[allow(dead_code)]
+    async fn find_user_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+        include_groups: bool,
+    ) -> AppResult<Option<User>>;
+
     /// Find all users in a tenant with pagination
     async fn find_all_users(
         &self,
@@ -99,7 +181,22 @@ pub trait UserBackend: Backend {
     ) -> AppResult<(Vec<User>, i64)>;
 
     /// Update an existing user (full replacement)
-    async fn update_user(&self, tenant_id: u32, id: &str, user: &User) -> AppResult<Option<User>>;
+    async fn update_user(
+        &self,
+        tenant_id: u32,
+        id: &str,
+        user: &User,
+        actor: Option<&str>,
+    ) -> AppResult<Option<User>> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let updated = self.update_user_in(&mut tx, tenant_id, id, user).await?;
+        tx.commit().await?;
+        match updated {
+            // Read back with the user's groups populated
+            Some(_) => self.find_user_by_id(tenant_id, id, true).await,
+            None => Ok(None),
+        }
+    }
 
     /// Apply SCIM PATCH operations to a user
     async fn patch_user(
@@ -108,18 +205,58 @@ pub trait UserBackend: Backend {
         id: &str,
         patch_ops: &ScimPatchOp,
         compatibility: &CompatibilityConfig,
-    ) -> AppResult<Option<User>>;
+        actor: Option<&str>,
+    ) -> AppResult<Option<User>> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let patched = self
+            .patch_user_in(&mut tx, tenant_id, id, patch_ops, compatibility)
+            .await?;
+        tx.commit().await?;
+        match patched {
+            // Read back with the user's groups populated
+            Some(_) => self.find_user_by_id(tenant_id, id, true).await,
+            None => Ok(None),
+        }
+    }
 
     /// Delete a user from the tenant
-    async fn delete_user(&self, tenant_id: u32, id: &str) -> AppResult<bool>;
+    async fn delete_user(&self, tenant_id: u32, id: &str, actor: Option<&str>) -> AppResult<bool> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let deleted = self.delete_user_in(&mut tx, tenant_id, id).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
 
-    /// Find users that are members of a specific group
-    async fn find_users_by_group_id(
+    /// Replace a user on an open transaction
+    ///
+    /// Returns the user as written, without its groups.
+    async fn update_user_in(
         &self,
+        tx: &mut DbTransaction,
         tenant_id: u32,
-        group_id: &str,
-        include_groups: bool,
-    ) -> AppResult<Vec<User>>;
+        id: &str,
+        user: &User,
+    ) -> AppResult<Option<User>>;
+
+    /// Patch a user on an open transaction
+    ///
+    /// Returns the user as written, without its groups.
+    async fn patch_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
+    ) -> AppResult<Option<User>>;
+
+    /// Delete a user, and its group memberships, on an open transaction
+    async fn delete_user_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool>;
 }
 
 /// Group-specific backend operations
@@ -129,7 +266,53 @@ pub trait UserBackend: Backend {
 #[async_trait]
 pub trait GroupBackend: Backend {
     /// Create a new group in the specified tenant
-    async fn create_group(&self, tenant_id: u32, group: &Group) -> AppResult<Group>;
+    ///
+    /// `actor` is the principal the write is attributed to, stored as the
+    /// group's created_by and last_modified_by.
+    async fn create_group(
+        &self,
+        tenant_id: u32,
+        group: &Group,
+        actor: Option<&str>,
+    ) -> AppResult<Group> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let created = self.create_group_in(&mut tx, tenant_id, group).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// Create a group under an id chosen by the client (PUT to a missing id)
+    async fn create_group_with_id(
+        &self,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+        actor: Option<&str>,
+    ) -> AppResult<Group> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let created = self
+            .create_group_with_id_in(&mut tx, tenant_id, id, group)
+            .await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// Create a new group, with its memberships, on an open transaction
+    async fn create_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group: &Group,
+    ) -> AppResult<Group>;
+
+    /// Create a group under a client-chosen id on an open transaction
+    async fn create_group_with_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Group>;
 
     /// Find a group by ID within a tenant
     async fn find_group_by_id(&self, tenant_id: u32, id: &str) -> AppResult<Option<Group>>;
@@ -142,6 +325,14 @@ pub trait GroupBackend: Backend {
         display_name: &str,
     ) -> AppResult<Option<Group>>;
 
+    /// Find a group by externalId (case-exact), e.g. for IdP reconciliation
+    #[allow(dead_code)]
+    async fn find_group_by_external_id(
+        &self,
+        tenant_id: u32,
+        external_id: &str,
+    ) -> AppResult<Option<Group>>;
+
     /// Find all groups in a tenant with pagination
     async fn find_all_groups(
         &self,
@@ -175,7 +366,13 @@ pub trait GroupBackend: Backend {
         tenant_id: u32,
         id: &str,
         group: &Group,
-    ) -> AppResult<Option<Group>>;
+        actor: Option<&str>,
+    ) -> AppResult<Option<Group>> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let updated = self.update_group_in(&mut tx, tenant_id, id, group).await?;
+        tx.commit().await?;
+        Ok(updated)
+    }
 
     /// Apply SCIM PATCH operations to a group
     async fn patch_group(
@@ -183,23 +380,237 @@ pub trait GroupBackend: Backend {
         tenant_id: u32,
         id: &str,
         patch_ops: &ScimPatchOp,
-    ) -> AppResult<Option<Group>>;
+        compatibility: &CompatibilityConfig,
+        actor: Option<&str>,
+    ) -> AppResult<Option<Group>> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let patched = self
+            .patch_group_in(&mut tx, tenant_id, id, patch_ops, compatibility)
+            .await?;
+        tx.commit().await?;
+        match patched {
+            // Read back with the members populated
+            Some(_) => self.find_group_by_id(tenant_id, id).await,
+            None => Ok(None),
+        }
+    }
 
     /// Delete a group from the tenant
-    async fn delete_group(&self, tenant_id: u32, id: &str) -> AppResult<bool>;
+    async fn delete_group(&self, tenant_id: u32, id: &str, actor: Option<&str>) -> AppResult<bool> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let deleted = self.delete_group_in(&mut tx, tenant_id, id).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
 
-    /// Find groups that contain a specific user as a member
-    async fn find_groups_by_user_id(&self, tenant_id: u32, user_id: &str) -> AppResult<Vec<Group>>;
+    /// Add users or groups by id to a group's members in one transaction
+    ///
+    /// Returns None when the group does not exist.
+    async fn add_group_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        actor: Option<&str>,
+    ) -> AppResult<Option<MembersAdded>> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let added = self
+            .add_group_members_in(&mut tx, tenant_id, group_id, ids)
+            .await?;
+        tx.commit().await?;
+        Ok(added)
+    }
+
+    /// Remove members by id from a group in one transaction
+    ///
+    /// Returns None when the group does not exist.
+    async fn remove_group_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+        actor: Option<&str>,
+    ) -> AppResult<Option<MembersRemoved>> {
+        let mut tx = self.begin_transaction().await?.with_actor(actor);
+        let removed = self
+            .remove_group_members_in(&mut tx, tenant_id, group_id, ids)
+            .await?;
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    /// Replace a group, with its memberships, on an open transaction
+    async fn update_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        group: &Group,
+    ) -> AppResult<Option<Group>>;
+
+    /// Patch a group on an open transaction
+    async fn patch_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+        patch_ops: &ScimPatchOp,
+        compatibility: &CompatibilityConfig,
+    ) -> AppResult<Option<Group>>;
+
+    /// Delete a group, and the memberships naming it, on an open transaction
+    async fn delete_group_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<bool>;
+
+    /// Add members by id to a group on an open transaction
+    async fn add_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersAdded>>;
+
+    /// Remove members by id from a group on an open transaction
+    async fn remove_group_members_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        group_id: &str,
+        ids: &[String],
+    ) -> AppResult<Option<MembersRemoved>>;
+
+    /// Find a group, with its members, on an open transaction
+    ///
+    /// Unlike `find_group_by_id` this sees the transaction's own writes.
+    async fn find_group_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        id: &str,
+    ) -> AppResult<Option<Group>>;
+
+    /// Ids of the groups listing `member_id` as a direct member, read on an
+    /// open transaction
+    async fn find_group_ids_with_member_in(
+        &self,
+        tx: &mut DbTransaction,
+        tenant_id: u32,
+        member_id: &str,
+    ) -> AppResult<Vec<String>>;
+
+    /// Users in a group or in groups nested below it, for
+    /// `GET /Groups/{id}/effectiveMembers`
+    ///
+    /// Each user is returned once, ordered by userName. Nested groups are
+    /// followed at most `max_depth` levels. Returns None when the group does
+    /// not exist.
+    async fn find_effective_members(
+        &self,
+        tenant_id: u32,
+        group_id: &str,
+        max_depth: u32,
+        start_index: Option<i64>,
+        count: Option<i64>,
+        include_groups: bool,
+    ) -> AppResult<Option<(Vec<User>, i64)>>;
+
+    /// Number of direct members of each given group, keyed by group id
+    ///
+    /// Counted in the database without loading the members; groups without
+    /// members are left out.
+    async fn count_group_members(
+        &self,
+        tenant_id: u32,
+        group_ids: &[String],
+    ) -> AppResult<HashMap<String, u64>>;
+}
+
+/// Outcome of a bulk member add
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MembersAdded {
+    pub added: usize,
+    pub already_present: usize,
+    /// Ids matching neither a user nor another group, in request order
+    pub not_found: Vec<String>,
+}
+
+/// Outcome of a bulk member remove
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MembersRemoved {
+    pub removed: usize,
+    pub not_members: usize,
+}
+
+/// Principals recorded as having created and last modified a resource
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Provenance {
+    pub created_by: Option<String>,
+    pub last_modified_by: Option<String>,
+}
+
+/// Provenance tracking for users and groups
+///
+/// Kept outside the resource JSON so clients can never write it. The
+/// principals are written by the same statements as the resources, taken
+/// from `DbTransaction::actor`.
+#[async_trait]
+pub trait ProvenanceBackend: Backend {
+    /// Look up provenance for the given resource ids, keyed by id
+    async fn find_provenance(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        ids: &[String],
+    ) -> AppResult<HashMap<String, Provenance>>;
+}
+
+/// Change history of users and groups
+///
+/// Only backends logging every write keep one; the defaults report none.
+#[async_trait]
+pub trait HistoryBackend: Backend {
+    /// Whether writes are logged, so the other methods have anything to return
+    fn keeps_history(&self) -> bool {
+        false
+    }
+
+    /// Every event of a resource, oldest first
+    async fn history(
+        &self,
+        _tenant_id: u32,
+        _resource_type: ResourceType,
+        _resource_id: &str,
+    ) -> AppResult<Vec<Event>> {
+        Ok(Vec::new())
+    }
+
+    /// State of a resource at `at`, or its current state without one
+    ///
+    /// Returns None if the resource did not exist at that time.
+    async fn resource_at(
+        &self,
+        _tenant_id: u32,
+        _resource_type: ResourceType,
+        _resource_id: &str,
+        _at: Option<DateTime<Utc>>,
+    ) -> AppResult<Option<serde_json::Value>> {
+        Ok(None)
+    }
 }
 
 /// Combined backend interface for both users and groups
 ///
 /// This trait combines UserBackend and GroupBackend for backends that
 /// handle both resource types in a unified manner.
-pub trait ScimBackend: UserBackend + GroupBackend {}
+pub trait ScimBackend: UserBackend + GroupBackend + ProvenanceBackend + HistoryBackend {}
 
-/// Automatic implementation for any type that implements both traits
-impl<T> ScimBackend for T where T: UserBackend + GroupBackend {}
+/// Automatic implementation for any type that implements all four traits
+impl<T> ScimBackend for T where T: UserBackend + GroupBackend + ProvenanceBackend + HistoryBackend {}
 
 /// Factory for creating backend instances
 pub struct BackendFactory;
@@ -213,21 +624,44 @@ impl BackendFactory {
         Ok(Arc::from(backend))
     }
 
+    /// Create an event-sourced backend on top of the configured database
+    ///
+    /// Reads the snapshot interval from the `event_sourced.snapshot_every` option.
+    pub async fn create_event_sourced(
+        config: &crate::backend::database::DatabaseBackendConfig,
+    ) -> AppResult<Arc<dyn ScimBackend>> {
+        let backend = event_sourced::EventSourcedBackend::connect(config).await?;
+        Ok(Arc::new(backend))
+    }
+
     /// Create a backend based on configuration (returns Box)
     pub async fn create_backend(
         config: &crate::backend::database::DatabaseBackendConfig,
     ) -> AppResult<Box<dyn ScimBackend>> {
-        match config.database_type {
+        match &config.database_type {
+            #[cfg(feature = "backend-postgres")]
             DatabaseType::PostgreSQL => {
                 let backend =
                     crate::backend::database::postgres::PostgresBackend::connect(config).await?;
                 Ok(Box::new(backend))
             }
+            #[cfg(feature = "backend-sqlite")]
             DatabaseType::SQLite => {
                 let backend =
                     crate::backend::database::sqlite::SqliteBackend::connect(config).await?;
                 Ok(Box::new(backend))
             }
+            #[cfg(feature = "backend-mysql")]
+            DatabaseType::MySQL => {
+                let backend =
+                    crate::backend::database::mysql::MySqlBackend::connect(config).await?;
+                Ok(Box::new(backend))
+            }
+            #[allow(unreachable_patterns)]
+            other => Err(crate::error::AppError::Configuration(format!(
+                "{:?} support is not compiled in; enable the matching backend feature",
+                other
+            ))),
         }
     }
 }
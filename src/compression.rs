@@ -0,0 +1,132 @@
+//! Response compression (`server.compression`)
+//!
+//! gzip and br are negotiated with the request's `Accept-Encoding`. The layer
+//! wraps the whole router, outside the logging middleware, so access logs
+//! report uncompressed sizes and handler headers such as ETag and Location
+//! are passed through untouched.
+
+use axum::body::HttpBody;
+use axum::http::{header, Response};
+use tower_http::compression::{CompressionLayer, Predicate};
+
+use crate::config::{CompressionAlgorithm, CompressionConfig};
+
+/// Content types that are compressed already and gain nothing from gzip/br
+const COMPRESSED_CONTENT_TYPES: &[&str] = &[
+    "application/gzip",
+    "application/x-gzip",
+    "application/zip",
+    "application/zstd",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/x-7z-compressed",
+    "font/woff",
+    "font/woff2",
+];
+
+/// Compression layer for the configuration, or None when it is disabled
+pub fn layer(config: &CompressionConfig) -> Option<CompressionLayer<CompressWhen>> {
+    if !config.enabled || config.algorithms.is_empty() {
+        return None;
+    }
+
+    Some(
+        CompressionLayer::new()
+            .gzip(config.algorithms.contains(&CompressionAlgorithm::Gzip))
+            .br(config.algorithms.contains(&CompressionAlgorithm::Br))
+            .compress_when(CompressWhen {
+                min_size_bytes: config.min_size_bytes,
+            }),
+    )
+}
+
+/// Compress responses of at least `min_size_bytes` that are not compressed already
+///
+/// Streamed responses of unknown size (e.g. NDJSON exports) are compressed.
+#[derive(Debug, Clone, Copy)]
+pub struct CompressWhen {
+    min_size_bytes: u64,
+}
+
+impl Predicate for CompressWhen {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        let size = response.body().size_hint().exact().or_else(|| {
+            response
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+        });
+        if size.is_some_and(|size| size < self.min_size_bytes) {
+            return false;
+        }
+
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        !is_compressed_content_type(content_type)
+    }
+}
+
+fn is_compressed_content_type(content_type: &str) -> bool {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    match media_type.split_once('/') {
+        Some(("image", subtype)) => subtype != "svg+xml",
+        Some(("audio" | "video", _)) => true,
+        _ => COMPRESSED_CONTENT_TYPES.contains(&media_type.as_str()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn response(content_type: &str, body: &'static str) -> Response<Body> {
+        Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        assert!(layer(&CompressionConfig::default()).is_none());
+
+        let config = CompressionConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(layer(&config).is_some());
+        assert!(layer(&CompressionConfig {
+            algorithms: vec![],
+            ..config
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn test_compress_when() {
+        let predicate = CompressWhen { min_size_bytes: 8 };
+
+        assert!(predicate.should_compress(&response("application/scim+json", "0123456789")));
+        assert!(predicate.should_compress(&response("image/svg+xml", "0123456789")));
+        // Below the threshold
+        assert!(!predicate.should_compress(&response("application/scim+json", "0123")));
+        // Compressed already
+        assert!(!predicate.should_compress(&response("application/gzip", "0123456789")));
+        assert!(!predicate.should_compress(&response("image/png; q=1", "0123456789")));
+        assert!(!predicate.should_compress(&response("Video/MP4", "0123456789")));
+    }
+}
@@ -1,9 +1,15 @@
+use crate::parser::ResourceType;
+use crate::password::{Argon2Params, PasswordAlgorithm};
+use base64::{engine::general_purpose, Engine as _};
 use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::net::IpAddr;
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AppConfig {
@@ -12,12 +18,135 @@ pub struct AppConfig {
     pub tenants: Vec<TenantConfig>,
     #[serde(default)]
     pub compatibility: CompatibilityConfig,
+    /// Reject writes for every tenant (maintenance mode)
+    #[serde(default)]
+    pub read_only: RuntimeFlag,
+    /// Password hashing cost settings
+    #[serde(default)]
+    pub password: PasswordConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct PasswordConfig {
+    /// Algorithm for newly hashed passwords, unless a tenant sets its own
+    #[serde(default)]
+    pub algorithm: PasswordAlgorithm,
+    /// Argon2id cost parameters for newly hashed passwords
+    #[serde(default)]
+    pub argon2: Argon2Params,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Log output format: "text", "json" or "gelf"
+    #[serde(default = "default_log_format")]
+    pub log_format: String,
+    /// Log filter directive, e.g. "info" or "scim_server=debug" (RUST_LOG takes precedence)
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// Include the detail of internal errors (e.g. SQL messages) in 500 responses
+    #[serde(default)]
+    pub expose_internal_errors: bool,
+    /// Bearer token for the server-wide `/admin/*` endpoints; they are disabled when unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    /// Largest inline `response` or decoded `response_base64` a custom endpoint
+    /// may be configured with, in bytes; `response_file` is not capped
+    #[serde(default = "default_custom_endpoint_max_response_bytes")]
+    pub custom_endpoint_max_response_bytes: usize,
+    /// Refuse to start with more tenants than this; unlimited when unset
+    #[serde(default)]
+    pub max_tenants: Option<usize>,
+    /// HMAC key for read-only share links (`/shared/{token}`); they are disabled when unset
+    #[serde(default)]
+    pub share_link_secret: Option<String>,
+    /// Enable `POST /admin/tenants/{id}/vacuum`, which reclaims space in a tenant's tables
+    #[serde(default)]
+    pub maintenance_endpoints: bool,
+    /// gzip/br compression of responses, negotiated with `Accept-Encoding`
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    /// Per-tenant write counters served at `GET /admin/tenants/{id}/stats`
+    #[serde(default)]
+    pub stats: StatsConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct CompressionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Smaller responses are sent uncompressed
+    #[serde(default = "default_compression_min_size_bytes")]
+    pub min_size_bytes: u64,
+    /// Encodings offered to clients, in `Accept-Encoding` terms
+    #[serde(default = "default_compression_algorithms")]
+    pub algorithms: Vec<CompressionAlgorithm>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    Gzip,
+    Br,
+}
+
+fn default_compression_min_size_bytes() -> u64 {
+    1024
+}
+
+fn default_compression_algorithms() -> Vec<CompressionAlgorithm> {
+    vec![CompressionAlgorithm::Gzip, CompressionAlgorithm::Br]
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_size_bytes: default_compression_min_size_bytes(),
+            algorithms: default_compression_algorithms(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct StatsConfig {
+    /// Save the counters to each tenant's `t{id}_sync_stats` table so they
+    /// survive restarts; otherwise they start from zero with every process
+    #[serde(default)]
+    pub persist: bool,
+    /// How often persisted counters are saved
+    #[serde(default = "default_stats_snapshot_interval_seconds")]
+    pub snapshot_interval_seconds: u64,
+}
+
+fn default_stats_snapshot_interval_seconds() -> u64 {
+    60
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            persist: false,
+            snapshot_interval_seconds: default_stats_snapshot_interval_seconds(),
+        }
+    }
+}
+
+/// Tenant count above which startup warns unless `max_tenants` is set
+pub const LARGE_TENANT_COUNT: usize = 1000;
+
+fn default_custom_endpoint_max_response_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_log_format() -> String {
+    "text".to_string()
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -25,6 +154,28 @@ pub struct BackendConfig {
     #[serde(rename = "type")]
     pub backend_type: String,
     pub database: Option<DatabaseConfig>,
+    /// Settings for `type: event_sourced`, which logs every write on top of `database`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_sourced: Option<EventSourcedConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EventSourcedConfig {
+    /// Events per resource between snapshots; 0 disables snapshots
+    #[serde(default = "default_snapshot_every")]
+    pub snapshot_every: u32,
+}
+
+fn default_snapshot_every() -> u32 {
+    100
+}
+
+impl Default for EventSourcedConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_every: default_snapshot_every(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -34,6 +185,52 @@ pub struct DatabaseConfig {
     pub url: String,
     #[serde(default = "default_max_connections")]
     pub max_connections: u32,
+    /// SQLite-specific tuning (ignored for PostgreSQL)
+    #[serde(default)]
+    pub sqlite: SqliteConfig,
+    /// PostgreSQL-specific tuning (ignored for SQLite and MySQL)
+    #[serde(default)]
+    pub postgres: PostgresConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SqliteConfig {
+    /// How long a connection waits for a lock before failing with SQLITE_BUSY
+    #[serde(default = "default_busy_timeout_ms")]
+    pub busy_timeout_ms: u64,
+    /// Queue writes behind a single lock instead of letting them contend
+    #[serde(default)]
+    pub serialize_writes: bool,
+}
+
+fn default_busy_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            busy_timeout_ms: default_busy_timeout_ms(),
+            serialize_writes: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct PostgresConfig {
+    /// Attributes that get an expression index on every tenant's tables
+    #[serde(default)]
+    pub indexed_attributes: IndexedAttributesConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct IndexedAttributesConfig {
+    /// User attributes, e.g. `costCenter` or `name.familyName`
+    #[serde(default)]
+    pub users: Vec<String>,
+    /// Group attributes
+    #[serde(default)]
+    pub groups: Vec<String>,
 }
 
 fn default_max_connections() -> u32 {
@@ -44,6 +241,16 @@ fn default_max_connections() -> u32 {
 pub struct TenantConfig {
     pub id: u32,
     pub path: String,
+    /// Path segment of the Users endpoint below `path` (default `Users`)
+    #[serde(default)]
+    pub users_path: Option<String>,
+    /// Path segment of the Groups endpoint below `path` (default `Groups`)
+    #[serde(default)]
+    pub groups_path: Option<String>,
+    /// Resource types served for this tenant (default `User` and `Group`);
+    /// the endpoints of the others answer 501
+    #[serde(default)]
+    pub enabled_resources: Option<Vec<ResourceType>>,
     #[serde(default)]
     pub host: Option<String>,
     #[serde(default)]
@@ -51,10 +258,92 @@ pub struct TenantConfig {
     pub auth: AuthConfig,
     #[serde(default)]
     pub override_base_url: Option<String>,
+    /// Base URL used only for `$ref` values (falls back to the tenant base URL)
+    #[serde(default)]
+    pub ref_base_url: Option<String>,
     #[serde(default)]
     pub custom_endpoints: Vec<CustomEndpoint>,
     #[serde(default)]
     pub compatibility: Option<CompatibilityConfig>,
+    /// Reject writes for this tenant only (maintenance mode)
+    #[serde(default)]
+    pub read_only: RuntimeFlag,
+    /// Require an If-Match header on PUT/PATCH/DELETE (428 when missing)
+    #[serde(default)]
+    pub require_if_match: bool,
+    /// Allowed values per sub-attribute, e.g. `emails.type: [work, home, other]`
+    #[serde(default)]
+    pub canonical_values: HashMap<String, Vec<String>>,
+    /// Format checks for emails, phone numbers, URLs and language tags
+    #[serde(default)]
+    pub validate_formats: FormatValidationConfig,
+    /// Match and deduplicate `externalId` ignoring case (stored lowercased)
+    #[serde(default)]
+    pub external_id_case_insensitive: bool,
+    /// Accept `password` in User writes; false rejects them with 400
+    #[serde(default = "default_accept_password")]
+    pub accept_password: bool,
+    /// Answer 429 to a client IP after repeated failed authentication
+    #[serde(default)]
+    pub auth_lockout: Option<AuthLockoutConfig>,
+    /// Allow `GET /Users?format=ndjson` and `GET /Groups?format=ndjson` exports
+    #[serde(default)]
+    pub ndjson_export: bool,
+    /// Return who created and last modified each resource
+    #[serde(default)]
+    pub expose_provenance: bool,
+    /// Non-standard `POST /Groups/{id}/members:add` and `members:remove` endpoints
+    #[serde(default)]
+    pub member_batch: MemberBatchConfig,
+    /// Non-standard `GET /Groups/{id}/effectiveMembers` endpoint
+    #[serde(default)]
+    pub effective_members: EffectiveMembersConfig,
+    /// Details shown in this tenant's ServiceProviderConfig
+    #[serde(default)]
+    pub service_provider: ServiceProviderInfo,
+    /// Algorithm for this tenant's newly hashed passwords; unset uses
+    /// `password.algorithm`
+    #[serde(default)]
+    pub password_algorithm: Option<PasswordAlgorithm>,
+}
+
+fn default_accept_password() -> bool {
+    true
+}
+
+/// Boolean setting that can be toggled while the server is running
+///
+/// Reads from YAML as a plain bool. Clones share the underlying value, so a
+/// change made through any clone of the configuration is seen by the next
+/// request without a restart.
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeFlag(Arc<AtomicBool>);
+
+impl RuntimeFlag {
+    pub fn new(value: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(value)))
+    }
+
+    pub fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    #[allow(dead_code)]
+    pub fn set(&self, value: bool) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+}
+
+impl Serialize for RuntimeFlag {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for RuntimeFlag {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        bool::deserialize(deserializer).map(RuntimeFlag::new)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -126,6 +415,13 @@ pub struct AuthConfig {
     pub auth_type: String,
     pub token: Option<String>,
     pub basic: Option<BasicAuthConfig>,
+    /// Further basic credentials accepted alongside `basic`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub basic_users: Vec<BasicAuthConfig>,
+    /// Further bearer/token credentials accepted alongside `token`, each
+    /// optionally restricted by its own permissions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<ScopedTokenConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -134,6 +430,31 @@ pub struct BasicAuthConfig {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ScopedTokenConfig {
+    pub token: String,
+    /// Principal recorded for this token (defaults to the auth type)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub permissions: TokenPermissions,
+}
+
+/// What a scoped token may do. The default allows everything.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct TokenPermissions {
+    /// HTTP methods the token may use; empty allows all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_methods: Vec<String>,
+    /// Attributes the token can neither read nor write, e.g. `phoneNumbers`,
+    /// `name.familyName` or an extension-qualified path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_attributes: Vec<String>,
+    /// Only GET, HEAD and POST `.search` are allowed
+    #[serde(default)]
+    pub readonly: bool,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CompatibilityConfig {
     #[serde(default = "default_meta_datetime_format")]
@@ -142,6 +463,8 @@ pub struct CompatibilityConfig {
     pub show_empty_groups_members: bool,
     #[serde(default = "default_include_user_groups")]
     pub include_user_groups: bool,
+    #[serde(default = "default_return_external_id")]
+    pub return_external_id: bool,
     #[serde(default = "default_support_group_members_filter")]
     pub support_group_members_filter: bool,
     #[serde(default = "default_support_group_displayname_filter")]
@@ -150,6 +473,38 @@ pub struct CompatibilityConfig {
     pub support_patch_replace_empty_array: bool,
     #[serde(default = "default_support_patch_replace_empty_value")]
     pub support_patch_replace_empty_value: bool,
+    #[serde(default = "default_total_count_header")]
+    pub total_count_header: bool,
+    #[serde(default = "default_validate_timezone")]
+    pub validate_timezone: bool,
+    #[serde(default = "default_patch_supported")]
+    pub patch_supported: bool,
+    #[serde(default = "default_validate_responses")]
+    pub validate_responses: bool,
+    #[serde(default = "default_detailed_validation_errors")]
+    pub detailed_validation_errors: bool,
+    #[serde(default = "default_max_patch_operations")]
+    pub max_patch_operations: u32,
+    #[serde(default = "default_max_attribute_value_length")]
+    pub max_attribute_value_length: u32,
+    #[serde(default = "default_expose_backend_info")]
+    pub expose_backend_info: bool,
+    #[serde(default = "default_delete_returns_resource")]
+    pub delete_returns_resource: bool,
+    #[serde(default = "default_put_creates_missing", alias = "put_upsert")]
+    pub put_creates_missing: bool,
+    #[serde(default = "default_include_next_start_index")]
+    pub include_next_start_index: bool,
+    #[serde(default = "default_default_page_size")]
+    pub default_page_size: u32,
+    #[serde(default = "default_export_endpoints")]
+    pub export_endpoints: bool,
+    #[serde(default = "default_enforce_immutability")]
+    pub enforce_immutability: bool,
+    #[serde(default = "default_strict_patch_schema")]
+    pub strict_patch_schema: bool,
+    #[serde(default = "default_include_member_count")]
+    pub include_member_count: bool,
 }
 
 fn default_meta_datetime_format() -> String {
@@ -164,6 +519,10 @@ fn default_include_user_groups() -> bool {
     true // true: include groups field in User responses, false: omit groups field entirely
 }
 
+fn default_return_external_id() -> bool {
+    true // true: return externalId in responses, false: keep it stored and filterable but omit it
+}
+
 fn default_support_group_members_filter() -> bool {
     true // true: support filtering Groups by members.value, false: reject such filters
 }
@@ -180,16 +539,198 @@ fn default_support_patch_replace_empty_value() -> bool {
     false // false: reject op=replace with value=[{"value":""}] pattern, true: allow this non-standard clearing method
 }
 
+fn default_total_count_header() -> bool {
+    false // true: also send totalResults as an X-Total-Count header on list responses
+}
+
+fn default_patch_supported() -> bool {
+    true // false: answer PATCH with 501 and advertise patch.supported=false
+}
+
+fn default_validate_timezone() -> bool {
+    false // true: accept only canonical IANA identifiers for User timezone (no offsets or legacy links)
+}
+
+fn default_validate_responses() -> bool {
+    false // true: check every User/Group response against the schema definitions and log violations (for tests)
+}
+
+fn default_detailed_validation_errors() -> bool {
+    true // true: 400 validation errors also list each problem in a non-standard "errors" array
+}
+
+fn default_max_patch_operations() -> u32 {
+    100 // PATCH bodies with more Operations are rejected with 400
+}
+
+fn default_max_attribute_value_length() -> u32 {
+    65536 // longer string values in User/Group writes are rejected with 400; 0 disables the check
+}
+
+fn default_expose_backend_info() -> bool {
+    false // true: ServiceProviderConfig also reports the backend, server version and enabled flags
+}
+
+fn default_delete_returns_resource() -> bool {
+    false // true: DELETE answers 200 with the deleted resource instead of 204 (legacy clients)
+}
+
+fn default_put_creates_missing() -> bool {
+    false // true: PUT to an unknown id creates the resource under that id (201) instead of 404
+}
+
+fn default_include_next_start_index() -> bool {
+    false // true: list responses with more pages carry a non-standard nextStartIndex
+}
+
+fn default_default_page_size() -> u32 {
+    100 // page size of list requests without a count (at most 1000 are returned)
+}
+
+fn default_export_endpoints() -> bool {
+    false // true: serve GET /Users/.export and /Groups/.export as NDJSON streams
+}
+
+fn default_enforce_immutability() -> bool {
+    true // false: PATCH may modify immutable attributes such as members.value
+}
+
+fn default_strict_patch_schema() -> bool {
+    false // true: PATCH bodies need exactly "Operations", the PatchOp URN and no other members
+}
+
+fn default_include_member_count() -> bool {
+    false // true: Group responses carry a non-standard top-level memberCount
+}
+
 impl Default for CompatibilityConfig {
     fn default() -> Self {
         Self {
             meta_datetime_format: default_meta_datetime_format(),
             show_empty_groups_members: default_show_empty_groups_members(),
             include_user_groups: default_include_user_groups(),
+            return_external_id: default_return_external_id(),
             support_group_members_filter: default_support_group_members_filter(),
             support_group_displayname_filter: default_support_group_displayname_filter(),
             support_patch_replace_empty_array: default_support_patch_replace_empty_array(),
             support_patch_replace_empty_value: default_support_patch_replace_empty_value(),
+            total_count_header: default_total_count_header(),
+            validate_timezone: default_validate_timezone(),
+            patch_supported: default_patch_supported(),
+            validate_responses: default_validate_responses(),
+            detailed_validation_errors: default_detailed_validation_errors(),
+            max_patch_operations: default_max_patch_operations(),
+            max_attribute_value_length: default_max_attribute_value_length(),
+            expose_backend_info: default_expose_backend_info(),
+            delete_returns_resource: default_delete_returns_resource(),
+            put_creates_missing: default_put_creates_missing(),
+            include_next_start_index: default_include_next_start_index(),
+            default_page_size: default_default_page_size(),
+            export_endpoints: default_export_endpoints(),
+            enforce_immutability: default_enforce_immutability(),
+            strict_patch_schema: default_strict_patch_schema(),
+            include_member_count: default_include_member_count(),
+        }
+    }
+}
+
+/// Per-tenant ServiceProviderConfig details; unset fields use the server defaults
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct ServiceProviderInfo {
+    #[serde(default)]
+    pub documentation_uri: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub support_email: Option<String>,
+}
+
+/// Per-tenant format validation of well-known User attributes
+///
+/// Everything is off by default. `emails: strict` also checks that
+/// `profileUrl` and `photos` values are URLs and that `preferredLanguage`
+/// and `locale` are BCP 47 language tags.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct FormatValidationConfig {
+    #[serde(default)]
+    pub emails: EmailFormat,
+    #[serde(default)]
+    pub phone_numbers: PhoneNumberFormat,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailFormat {
+    #[default]
+    Off,
+    Strict,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PhoneNumberFormat {
+    #[default]
+    Off,
+    /// Digits and common separators only
+    Lenient,
+    /// E.164; accepted values are stored normalized, e.g. `+12015550123`
+    E164,
+}
+
+/// Lockout of client IPs that keep failing authentication
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AuthLockoutConfig {
+    /// Failed attempts within `window_seconds` that trigger a lockout
+    pub max_failures: u32,
+    pub window_seconds: u64,
+    /// How long the client IP gets 429 once locked out
+    pub lockout_seconds: u64,
+}
+
+/// Bulk membership endpoints for very large groups
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MemberBatchConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Requests with more ids are rejected with 413
+    #[serde(default = "default_member_batch_max_size")]
+    pub max_batch_size: usize,
+}
+
+fn default_member_batch_max_size() -> usize {
+    10000
+}
+
+impl Default for MemberBatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_batch_size: default_member_batch_max_size(),
+        }
+    }
+}
+
+/// Users of a group including those of nested groups
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct EffectiveMembersConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Levels of nested groups followed below the requested group
+    #[serde(default = "default_effective_members_max_depth")]
+    pub max_depth: u32,
+}
+
+fn default_effective_members_max_depth() -> u32 {
+    10
+}
+
+impl Default for EffectiveMembersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_depth: default_effective_members_max_depth(),
         }
     }
 }
@@ -197,11 +738,29 @@ impl Default for CompatibilityConfig {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CustomEndpoint {
     pub path: String,
+    /// Response body; at most one of `response`, `response_file` and
+    /// `response_base64` may be set
+    #[serde(default)]
     pub response: String,
+    /// File served as the response body, streamed from disk on each request
+    /// and answering `If-Modified-Since` with 304
+    #[serde(default)]
+    pub response_file: Option<String>,
+    /// Response body as base64, for binary content
+    #[serde(default)]
+    pub response_base64: Option<String>,
+    /// Keep `response_file` in memory, re-reading it only when its
+    /// modification time changes
+    #[serde(default)]
+    pub cache_response_file: bool,
     #[serde(default = "default_status_code")]
     pub status_code: u16,
     #[serde(default = "default_content_type")]
     pub content_type: String,
+    /// HTTP method the endpoint answers: `GET` (the default, also serving
+    /// HEAD) or `POST`, whose request body is accepted and ignored
+    #[serde(default)]
+    pub method: Option<String>,
     /// Optional authentication override for this specific endpoint
     /// If not specified, inherits tenant's authentication settings
     pub auth: Option<AuthConfig>,
@@ -215,7 +774,71 @@ fn default_content_type() -> String {
     "application/json".to_string()
 }
 
+/// Path part of a tenant path that may be a full URL
+fn url_path(path: &str) -> &str {
+    match path
+        .strip_prefix("https://")
+        .or_else(|| path.strip_prefix("http://"))
+    {
+        Some(rest) => rest.find('/').map_or("/", |i| &rest[i..]),
+        None => path,
+    }
+}
+
+/// Whether `path` is `base` itself or a path below it
+fn path_is_under(path: &str, base: &str) -> bool {
+    let base = base.trim_end_matches('/');
+    match path.strip_prefix(base) {
+        Some(rest) => base.is_empty() || rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// RFC 7230 token characters
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+/// Whether `value` is a media type such as `text/plain; charset=utf-8`
+fn is_valid_media_type(value: &str) -> bool {
+    let mut parts = value.split(';');
+    let essence = parts.next().unwrap_or_default().trim();
+    let Some((type_, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    if !is_token(type_) || !is_token(subtype) {
+        return false;
+    }
+
+    parts.all(|parameter| {
+        let Some((name, value)) = parameter.trim().split_once('=') else {
+            return false;
+        };
+        let quoted = value.len() >= 2
+            && value.starts_with('"')
+            && value.ends_with('"')
+            && !value[1..value.len() - 1].contains(|c: char| c == '"' || c.is_control());
+        is_token(name) && (is_token(value) || quoted)
+    })
+}
+
 impl CustomEndpoint {
+    /// The configured method in upper case, `GET` when unset
+    pub fn effective_method(&self) -> String {
+        self.method.as_deref().unwrap_or("GET").to_ascii_uppercase()
+    }
+
+    /// The inline response body, decoding `response_base64` when set
+    pub fn inline_body(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        match &self.response_base64 {
+            Some(encoded) => general_purpose::STANDARD.decode(encoded.trim()),
+            None => Ok(self.response.clone().into_bytes()),
+        }
+    }
+
     /// Get the effective authentication config for this endpoint
     /// Returns the endpoint's auth config if specified, otherwise the tenant's auth config
     pub fn effective_auth_config<'a>(&'a self, tenant_auth: &'a AuthConfig) -> &'a AuthConfig {
@@ -224,6 +847,38 @@ impl CustomEndpoint {
 }
 
 impl TenantConfig {
+    /// Path prefix of this tenant's SCIM endpoints, without a trailing slash
+    pub fn route_path(&self) -> &str {
+        url_path(&self.path).trim_end_matches('/')
+    }
+
+    /// Path segment serving Users below the tenant path
+    pub fn users_path(&self) -> &str {
+        self.users_path.as_deref().unwrap_or("Users")
+    }
+
+    /// Path segment serving Groups below the tenant path
+    pub fn groups_path(&self) -> &str {
+        self.groups_path.as_deref().unwrap_or("Groups")
+    }
+
+    /// Whether this tenant serves `resource_type`
+    pub fn resource_enabled(&self, resource_type: ResourceType) -> bool {
+        self.enabled_resources
+            .as_ref()
+            .is_none_or(|enabled| enabled.contains(&resource_type))
+    }
+
+    /// Path segment serving a resource type given by its default endpoint
+    /// segment (`Users` or `Groups`); other segments are returned unchanged
+    pub fn endpoint_path<'a>(&'a self, segment: &'a str) -> &'a str {
+        match segment {
+            "Users" => self.users_path(),
+            "Groups" => self.groups_path(),
+            other => other,
+        }
+    }
+
     /// Build the base URL for this tenant based on configuration and request
     /// - If override_base_url is set: use override_base_url + path (forced override)
     /// - If override_base_url is unset: use host resolution result + path (auto-constructed)
@@ -271,6 +926,16 @@ impl TenantConfig {
         }
     }
 
+    /// Build the base URL for `$ref` values in groups/members
+    /// - If ref_base_url is set: use ref_base_url + path
+    /// - Otherwise: reuse the already resolved tenant base URL
+    pub fn build_ref_base_url(&self, tenant_base_url: &str) -> String {
+        match &self.ref_base_url {
+            Some(ref_url) => format!("{}{}", ref_url.trim_end_matches('/'), &self.path),
+            None => tenant_base_url.to_string(),
+        }
+    }
+
     /// Build the base URL without path for this tenant - returns just protocol://host:port
     /// Used for constructing individual resource URLs where path is added separately
     #[allow(dead_code)]
@@ -576,6 +1241,11 @@ impl AppConfig {
             return Err("Configuration must contain at least one tenant".to_string());
         }
 
+        app_config.password.argon2.validate()?;
+        app_config.validate_tenant_count()?;
+        app_config.validate_custom_endpoints()?;
+        app_config.validate_resource_paths()?;
+
         Ok(app_config)
     }
 
@@ -585,16 +1255,31 @@ impl AppConfig {
             server: ServerConfig {
                 host: "127.0.0.1".to_string(),
                 port: 3000,
+                log_format: default_log_format(),
+                log_level: default_log_level(),
+                expose_internal_errors: false,
+                admin_token: None,
+                custom_endpoint_max_response_bytes: default_custom_endpoint_max_response_bytes(),
+                max_tenants: None,
+                share_link_secret: None,
+                maintenance_endpoints: false,
+                compression: Default::default(),
+                stats: Default::default(),
             },
+            password: Default::default(),
             backend: BackendConfig {
                 backend_type: "database".to_string(),
+                event_sourced: None,
                 database: Some(DatabaseConfig {
                     db_type: "sqlite".to_string(),
                     url: ":memory:".to_string(),
                     max_connections: 1,
+                    sqlite: Default::default(),
+                    postgres: Default::default(),
                 }),
             },
             compatibility: CompatibilityConfig::default(),
+            read_only: RuntimeFlag::default(), // Writable unless toggled
             tenants: vec![TenantConfig {
                 id: 1,
                 path: "/scim/v2".to_string(),
@@ -604,10 +1289,29 @@ impl AppConfig {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 override_base_url: None, // Use auto-constructed URL for zero-config mode
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None, // Use global compatibility settings
+                read_only: RuntimeFlag::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             }],
         }
     }
@@ -702,27 +1406,249 @@ impl AppConfig {
             }
         }
 
-        // If no custom endpoint matches, try regular SCIM endpoints
+        // If no custom endpoint matches, try regular SCIM endpoints. Several
+        // tenants can match when paths are nested (e.g. /scim/v2 and
+        // /scim/v2/contractors): a host-scoped tenant wins over a path-only
+        // one, then the longest path, then the tenant listed first.
+        let mut best: Option<((bool, usize), &TenantConfig, ResolvedUrl)> = None;
         for tenant in &self.tenants {
             if let Some(resolved_url) = tenant.matches_request(request_info) {
-                return Some((tenant, resolved_url));
+                let rank = (tenant.host.is_some(), tenant.path.len());
+                if best
+                    .as_ref()
+                    .is_none_or(|(best_rank, ..)| rank > *best_rank)
+                {
+                    best = Some((rank, tenant, resolved_url));
+                }
             }
         }
 
-        None
+        best.map(|(_, tenant, resolved_url)| (tenant, resolved_url))
+    }
+
+    /// Pairs of tenant ids `(outer, inner)` where a request for `inner` also
+    /// matches the path of `outer`
+    ///
+    /// Requests go to the longest matching path, so these configurations
+    /// work, but they are worth a warning at startup.
+    pub fn overlapping_tenant_paths(&self) -> Vec<(u32, u32)> {
+        // Look up the proper prefixes of each path instead of comparing every
+        // pair, so large tenant lists stay cheap to check
+        let mut by_path: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, tenant) in self.tenants.iter().enumerate() {
+            by_path.entry(tenant.path.as_str()).or_default().push(index);
+        }
+
+        let mut overlaps = Vec::new();
+        for (inner_index, inner) in self.tenants.iter().enumerate() {
+            for (end, _) in inner.path.char_indices().skip(1) {
+                let Some(outer_indexes) = by_path.get(&inner.path[..end]) else {
+                    continue;
+                };
+                for &outer_index in outer_indexes {
+                    let outer = &self.tenants[outer_index];
+                    let hosts_overlap =
+                        outer.host.is_none() || inner.host.is_none() || outer.host == inner.host;
+                    if outer.id != inner.id && hosts_overlap {
+                        overlaps.push((outer_index, inner_index));
+                    }
+                }
+            }
+        }
+        overlaps.sort_unstable();
+        overlaps
+            .into_iter()
+            .map(|(outer, inner)| (self.tenants[outer].id, self.tenants[inner].id))
+            .collect()
     }
 
-    /// Find custom endpoint that matches the given path
-    /// Note: This method is deprecated in favor of find_tenant_by_request which handles both SCIM and custom endpoints.
-    pub fn find_custom_endpoint(&self, path: &str) -> Option<(&TenantConfig, &CustomEndpoint)> {
+    /// Refuse configurations with more tenants than `server.max_tenants`
+    pub fn validate_tenant_count(&self) -> Result<(), String> {
+        match self.server.max_tenants {
+            Some(max) if self.tenants.len() > max => Err(format!(
+                "{} tenants are configured, more than the {} allowed by server.max_tenants",
+                self.tenants.len(),
+                max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the Users and Groups endpoint paths of every tenant
+    ///
+    /// Each must be a single path segment, distinct from the other one and
+    /// from the fixed endpoints (`ServiceProviderConfig`, `.search`, ...).
+    pub fn validate_resource_paths(&self) -> Result<(), String> {
+        const RESERVED: [&str; 3] = ["ServiceProviderConfig", "Schemas", "ResourceTypes"];
+
+        for tenant in &self.tenants {
+            for (setting, segment) in [
+                ("users_path", tenant.users_path()),
+                ("groups_path", tenant.groups_path()),
+            ] {
+                let invalid = |reason: &str| {
+                    format!("Tenant {} {} '{}': {}", tenant.id, setting, segment, reason)
+                };
+                if segment.is_empty()
+                    || segment.starts_with('.')
+                    || segment.contains(['/', '?', '#', '{', '}'])
+                {
+                    return Err(invalid("must be a single path segment"));
+                }
+                if RESERVED.contains(&segment) {
+                    return Err(invalid("is reserved for another SCIM endpoint"));
+                }
+            }
+            if tenant.users_path() == tenant.groups_path() {
+                return Err(format!(
+                    "Tenant {}: users_path and groups_path must differ",
+                    tenant.id
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check every tenant's custom endpoints
+    ///
+    /// Paths must be absolute, stay clear of all tenants' SCIM base paths and
+    /// be unique, except between tenants bound to different hosts. Status
+    /// codes, content types and response sizes must be servable as-is.
+    pub fn validate_custom_endpoints(&self) -> Result<(), String> {
+        let max_bytes = self.server.custom_endpoint_max_response_bytes;
+
         for tenant in &self.tenants {
             for endpoint in &tenant.custom_endpoints {
-                if path == endpoint.path {
-                    return Some((tenant, endpoint));
+                let invalid = |reason: String| {
+                    format!(
+                        "Tenant {} custom endpoint '{}': {}",
+                        tenant.id, endpoint.path, reason
+                    )
+                };
+
+                if !endpoint.path.starts_with('/') {
+                    return Err(invalid("path must start with '/'".to_string()));
+                }
+                if let Some(owner) = self
+                    .tenants
+                    .iter()
+                    .find(|t| path_is_under(&endpoint.path, url_path(&t.path)))
+                {
+                    return Err(invalid(format!(
+                        "path falls under the SCIM base path '{}' of tenant {}",
+                        url_path(&owner.path),
+                        owner.id
+                    )));
+                }
+                if !matches!(endpoint.effective_method().as_str(), "GET" | "POST") {
+                    return Err(invalid(format!(
+                        "method '{}' is not supported, use GET or POST",
+                        endpoint.effective_method()
+                    )));
+                }
+                if !(100..=599).contains(&endpoint.status_code) {
+                    return Err(invalid(format!(
+                        "status_code {} is not a valid HTTP status",
+                        endpoint.status_code
+                    )));
+                }
+                if !is_valid_media_type(&endpoint.content_type) {
+                    return Err(invalid(format!(
+                        "content_type '{}' is not a valid media type",
+                        endpoint.content_type
+                    )));
+                }
+
+                let bodies = [
+                    !endpoint.response.is_empty(),
+                    endpoint.response_file.is_some(),
+                    endpoint.response_base64.is_some(),
+                ];
+                if bodies.iter().filter(|set| **set).count() > 1 {
+                    return Err(invalid(
+                        "response, response_file and response_base64 are mutually exclusive"
+                            .to_string(),
+                    ));
+                }
+                if let Some(file) = &endpoint.response_file {
+                    // Files are streamed, so their size is not capped
+                    match fs::metadata(file) {
+                        Ok(metadata) if metadata.is_file() => {}
+                        Ok(_) => {
+                            return Err(invalid(format!("response_file '{}' is not a file", file)))
+                        }
+                        Err(e) => {
+                            return Err(invalid(format!(
+                                "response_file '{}' cannot be read: {}",
+                                file, e
+                            )))
+                        }
+                    }
+                } else if endpoint.cache_response_file {
+                    return Err(invalid(
+                        "cache_response_file requires response_file".to_string(),
+                    ));
+                }
+                let body = endpoint
+                    .inline_body()
+                    .map_err(|e| invalid(format!("response_base64 is not valid base64: {}", e)))?;
+                if body.len() > max_bytes {
+                    return Err(invalid(format!(
+                        "response is {} bytes, more than the {} allowed by \
+                         server.custom_endpoint_max_response_bytes",
+                        body.len(),
+                        max_bytes
+                    )));
                 }
             }
         }
-        None
+
+        // Each pair of endpoints sharing a path, compared once
+        let endpoints: Vec<(&TenantConfig, &CustomEndpoint)> = self
+            .tenants
+            .iter()
+            .flat_map(|t| t.custom_endpoints.iter().map(move |e| (t, e)))
+            .collect();
+        for (i, (tenant, endpoint)) in endpoints.iter().enumerate() {
+            for (other_tenant, other) in &endpoints[i + 1..] {
+                let host_disambiguated = tenant.host.is_some()
+                    && other_tenant.host.is_some()
+                    && tenant.host != other_tenant.host;
+                if endpoint.path == other.path && !host_disambiguated {
+                    return Err(format!(
+                        "Tenant {} custom endpoint '{}': path is also configured for tenant {}",
+                        other_tenant.id, other.path, tenant.id
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Distinct custom endpoint paths of all tenants with the methods to route
+    /// at each, so every path is routed once
+    ///
+    /// Tenants bound to different hosts may share a path, with different methods.
+    pub fn custom_endpoint_routes(&self) -> BTreeMap<&str, BTreeSet<String>> {
+        let mut routes: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+        for endpoint in self.tenants.iter().flat_map(|t| t.custom_endpoints.iter()) {
+            routes
+                .entry(endpoint.path.as_str())
+                .or_default()
+                .insert(endpoint.effective_method());
+        }
+        routes
+    }
+
+    /// Find the custom endpoint of a tenant at the given path
+    pub fn find_custom_endpoint(&self, tenant_id: u32, path: &str) -> Option<&CustomEndpoint> {
+        self.tenants
+            .iter()
+            .find(|t| t.id == tenant_id)?
+            .custom_endpoints
+            .iter()
+            .find(|e| e.path == path)
     }
 
     /// Get effective compatibility configuration for a tenant
@@ -737,6 +1663,32 @@ impl AppConfig {
         }
         &self.compatibility
     }
+
+    /// Whether User responses of a tenant carry `groups`
+    ///
+    /// Off when the compatibility settings leave them out or the tenant does
+    /// not serve Groups.
+    pub fn include_user_groups(&self, tenant_id: u32) -> bool {
+        self.get_effective_compatibility(tenant_id)
+            .include_user_groups
+            && self
+                .tenants
+                .iter()
+                .find(|t| t.id == tenant_id)
+                .is_none_or(|t| t.resource_enabled(ResourceType::Group))
+    }
+
+    /// Check whether writes are currently rejected for a tenant
+    ///
+    /// Either the global flag or the tenant's own flag puts the tenant into
+    /// read-only maintenance mode. Evaluated on every request.
+    pub fn is_read_only(&self, tenant_id: u32) -> bool {
+        self.read_only.get()
+            || self
+                .tenants
+                .iter()
+                .any(|t| t.id == tenant_id && t.read_only.get())
+    }
 }
 
 impl DatabaseConfig {
@@ -778,16 +1730,31 @@ mod tests {
             server: ServerConfig {
                 host: "127.0.0.1".to_string(),
                 port: 3000,
+                log_format: "text".to_string(),
+                log_level: "info".to_string(),
+                expose_internal_errors: false,
+                admin_token: None,
+                custom_endpoint_max_response_bytes: default_custom_endpoint_max_response_bytes(),
+                max_tenants: None,
+                share_link_secret: None,
+                maintenance_endpoints: false,
+                compression: Default::default(),
+                stats: Default::default(),
             },
+            password: Default::default(),
             backend: BackendConfig {
                 backend_type: "database".to_string(),
+                event_sourced: None,
                 database: Some(DatabaseConfig {
                     db_type: "sqlite".to_string(),
                     url: "test.db".to_string(),
                     max_connections: 10,
+                    sqlite: Default::default(),
+                    postgres: Default::default(),
                 }),
             },
             compatibility: CompatibilityConfig::default(),
+            read_only: Default::default(),
             tenants: vec![
                 TenantConfig {
                     id: 1,
@@ -798,10 +1765,29 @@ mod tests {
                         auth_type: "bearer".to_string(),
                         token: Some("example_token_123".to_string()),
                         basic: None,
+                        basic_users: Vec::new(),
+                        tokens: Vec::new(),
                     },
                     override_base_url: None,
+                    ref_base_url: None,
                     custom_endpoints: vec![],
                     compatibility: None,
+                    read_only: Default::default(),
+                    require_if_match: false,
+                    canonical_values: Default::default(),
+                    validate_formats: Default::default(),
+                    external_id_case_insensitive: false,
+                    accept_password: true,
+                    auth_lockout: None,
+                    ndjson_export: false,
+                    expose_provenance: false,
+                    member_batch: Default::default(),
+                    effective_members: Default::default(),
+                    service_provider: Default::default(),
+                    password_algorithm: None,
+                    users_path: None,
+                    groups_path: None,
+                    enabled_resources: None,
                 },
                 TenantConfig {
                     id: 2,
@@ -812,10 +1798,29 @@ mod tests {
                         auth_type: "bearer".to_string(),
                         token: Some("acme_scim_token_456".to_string()),
                         basic: None,
+                        basic_users: Vec::new(),
+                        tokens: Vec::new(),
                     },
                     override_base_url: None,
+                    ref_base_url: None,
                     custom_endpoints: vec![],
                     compatibility: None,
+                    read_only: Default::default(),
+                    require_if_match: false,
+                    canonical_values: Default::default(),
+                    validate_formats: Default::default(),
+                    external_id_case_insensitive: false,
+                    accept_password: true,
+                    auth_lockout: None,
+                    ndjson_export: false,
+                    expose_provenance: false,
+                    member_batch: Default::default(),
+                    effective_members: Default::default(),
+                    service_provider: Default::default(),
+                    password_algorithm: None,
+                    users_path: None,
+                    groups_path: None,
+                    enabled_resources: None,
                 },
             ],
         };
@@ -880,16 +1885,31 @@ tenants:
             server: ServerConfig {
                 host: "127.0.0.1".to_string(),
                 port: 3000,
+                log_format: "text".to_string(),
+                log_level: "info".to_string(),
+                expose_internal_errors: false,
+                admin_token: None,
+                custom_endpoint_max_response_bytes: default_custom_endpoint_max_response_bytes(),
+                max_tenants: None,
+                share_link_secret: None,
+                maintenance_endpoints: false,
+                compression: Default::default(),
+                stats: Default::default(),
             },
+            password: Default::default(),
             backend: BackendConfig {
                 backend_type: "database".to_string(),
+                event_sourced: None,
                 database: Some(DatabaseConfig {
                     db_type: "sqlite".to_string(),
                     url: "test.db".to_string(),
                     max_connections: 10,
+                    sqlite: Default::default(),
+                    postgres: Default::default(),
                 }),
             },
             compatibility: CompatibilityConfig::default(),
+            read_only: Default::default(),
             tenants: vec![TenantConfig {
                 id: 3,
                 path: "https://basic.example.com".to_string(),
@@ -902,10 +1922,29 @@ tenants:
                         username: "testuser".to_string(),
                         password: "testpass".to_string(),
                     }),
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             }],
         };
 
@@ -917,6 +1956,63 @@ tenants:
         assert_eq!(basic_config.password, "testpass");
     }
 
+    #[test]
+    fn test_read_only_flags() {
+        let yaml = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+tenants:
+  - id: 1
+    path: "/a/scim/v2"
+    auth:
+      type: "unauthenticated"
+    read_only: true
+  - id: 2
+    path: "/b/scim/v2"
+    auth:
+      type: "unauthenticated"
+"#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.is_read_only(1));
+        assert!(!config.is_read_only(2));
+
+        // Clones share the flags, so a runtime toggle is visible everywhere
+        let shared = config.clone();
+        shared.read_only.set(true);
+        assert!(config.is_read_only(2));
+        shared.read_only.set(false);
+        shared.tenants[0].read_only.set(false);
+        assert!(!config.is_read_only(1));
+    }
+
+    #[test]
+    fn test_put_upsert_alias() {
+        let yaml = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+tenants:
+  - id: 1
+    path: "/a/scim/v2"
+    auth:
+      type: "unauthenticated"
+    compatibility:
+      put_upsert: true
+  - id: 2
+    path: "/b/scim/v2"
+    auth:
+      type: "unauthenticated"
+"#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.get_effective_compatibility(1).put_creates_missing);
+        assert!(!config.get_effective_compatibility(2).put_creates_missing);
+    }
+
     #[test]
     fn test_missing_config_file() {
         let result = AppConfig::load_from_file("/nonexistent/path/config.yaml");
@@ -931,6 +2027,7 @@ tenants:
         // Check server settings
         assert_eq!(config.server.host, "127.0.0.1");
         assert_eq!(config.server.port, 3000);
+        assert!(!config.server.expose_internal_errors);
 
         // Check backend settings
         assert_eq!(config.backend.backend_type, "database");
@@ -1007,6 +2104,378 @@ tenants: []
         std::fs::remove_file(temp_file).unwrap();
     }
 
+    #[test]
+    fn test_argon2_params() {
+        let base = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+tenants:
+  - id: 1
+    path: "/scim/v2"
+    auth:
+      type: "unauthenticated"
+"#;
+        let config: AppConfig = serde_yaml::from_str(base).unwrap();
+        assert_eq!(config.password.argon2, Argon2Params::default());
+
+        // Unset fields keep their OWASP defaults
+        let config: AppConfig = serde_yaml::from_str(&format!(
+            "{}password:\n  argon2:\n    memory_kib: 65536\n",
+            base
+        ))
+        .unwrap();
+        assert_eq!(config.password.argon2.memory_kib, 65536);
+        assert_eq!(config.password.argon2.iterations, 2);
+        assert_eq!(config.password.argon2.parallelism, 1);
+
+        let temp_file = "/tmp/invalid_argon2_config.yaml";
+        std::fs::write(
+            temp_file,
+            format!("{}password:\n  argon2:\n    iterations: 0\n", base),
+        )
+        .unwrap();
+        let result = AppConfig::load_from_file(temp_file);
+        assert!(result.unwrap_err().contains("Invalid Argon2 parameters"));
+        std::fs::remove_file(temp_file).unwrap();
+    }
+
+    #[test]
+    fn test_event_sourced_backend_config() {
+        let base = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+tenants:
+  - id: 1
+    path: "/scim/v2"
+    auth:
+      type: "unauthenticated"
+"#;
+        let config: AppConfig =
+            serde_yaml::from_str(&format!("{}backend:\n  type: \"database\"\n", base)).unwrap();
+        assert!(config.backend.event_sourced.is_none());
+
+        let config: AppConfig = serde_yaml::from_str(&format!(
+            "{}backend:\n  type: \"event_sourced\"\n  event_sourced:\n    snapshot_every: 25\n",
+            base
+        ))
+        .unwrap();
+        assert_eq!(config.backend.backend_type, "event_sourced");
+        assert_eq!(config.backend.event_sourced.unwrap().snapshot_every, 25);
+
+        // An empty section keeps the default interval
+        let config: AppConfig = serde_yaml::from_str(&format!(
+            "{}backend:\n  type: \"event_sourced\"\n  event_sourced: {{}}\n",
+            base
+        ))
+        .unwrap();
+        assert_eq!(config.backend.event_sourced.unwrap().snapshot_every, 100);
+    }
+
+    fn custom_endpoint(path: &str) -> CustomEndpoint {
+        CustomEndpoint {
+            path: path.to_string(),
+            response: "{}".to_string(),
+            status_code: 200,
+            content_type: "application/json".to_string(),
+            method: None,
+            response_file: None,
+            response_base64: None,
+            cache_response_file: false,
+            auth: None,
+        }
+    }
+
+    /// Default config plus a second tenant, with the given endpoints on tenant 1
+    fn config_with_endpoints(endpoints: Vec<CustomEndpoint>) -> AppConfig {
+        let mut config = AppConfig::default_config();
+        let mut second = config.tenants[0].clone();
+        second.id = 2;
+        second.path = "http://b.example.com/tenant-b/scim".to_string();
+        config.tenants.push(second);
+        config.tenants[0].custom_endpoints = endpoints;
+        config
+    }
+
+    fn endpoint_error(config: &AppConfig) -> String {
+        config.validate_custom_endpoints().unwrap_err()
+    }
+
+    #[test]
+    fn test_custom_endpoint_paths_are_validated() {
+        let config = config_with_endpoints(vec![
+            custom_endpoint("/api/status"),
+            // Only shares a prefix with the SCIM base path
+            custom_endpoint("/scim/v2x"),
+        ]);
+        assert!(config.validate_custom_endpoints().is_ok());
+
+        let config = config_with_endpoints(vec![custom_endpoint("api/status")]);
+        assert_eq!(
+            endpoint_error(&config),
+            "Tenant 1 custom endpoint 'api/status': path must start with '/'"
+        );
+
+        for path in ["/scim/v2/Users", "/scim/v2", "/scim/v2/"] {
+            let config = config_with_endpoints(vec![custom_endpoint(path)]);
+            assert_eq!(
+                endpoint_error(&config),
+                format!(
+                    "Tenant 1 custom endpoint '{}': path falls under the SCIM base path '/scim/v2' of tenant 1",
+                    path
+                )
+            );
+        }
+
+        // Other tenants' base paths count too, including ones given as full URLs
+        let config = config_with_endpoints(vec![custom_endpoint("/tenant-b/scim/Users")]);
+        assert!(endpoint_error(&config).contains("'/tenant-b/scim' of tenant 2"));
+    }
+
+    #[test]
+    fn test_resource_paths_are_validated() {
+        let mut config = AppConfig::default_config();
+        assert!(config.validate_resource_paths().is_ok());
+        config.tenants[0].groups_path = Some("Teams".to_string());
+        assert!(config.validate_resource_paths().is_ok());
+        // Swapping the two is allowed too
+        config.tenants[0].users_path = Some("Groups".to_string());
+        config.tenants[0].groups_path = Some("Users".to_string());
+        assert!(config.validate_resource_paths().is_ok());
+
+        for (users_path, groups_path, expected) in [
+            (
+                "people/v2",
+                "Groups",
+                "users_path 'people/v2': must be a single path segment",
+            ),
+            ("", "Groups", "users_path '': must be a single path segment"),
+            (
+                "Users",
+                ".search",
+                "groups_path '.search': must be a single path segment",
+            ),
+            (
+                "Users",
+                "Schemas",
+                "groups_path 'Schemas': is reserved for another SCIM endpoint",
+            ),
+            ("Teams", "Teams", "users_path and groups_path must differ"),
+            ("Groups", "Groups", "users_path and groups_path must differ"),
+        ] {
+            config.tenants[0].users_path = Some(users_path.to_string());
+            config.tenants[0].groups_path = Some(groups_path.to_string());
+            let error = config.validate_resource_paths().unwrap_err();
+            assert!(error.starts_with("Tenant 1"), "{}", error);
+            assert!(error.ends_with(expected), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_custom_endpoint_paths_must_be_unique() {
+        let config = config_with_endpoints(vec![
+            custom_endpoint("/api/status"),
+            custom_endpoint("/api/status"),
+        ]);
+        assert_eq!(
+            endpoint_error(&config),
+            "Tenant 1 custom endpoint '/api/status': path is also configured for tenant 1"
+        );
+
+        let mut config = config_with_endpoints(vec![custom_endpoint("/api/status")]);
+        config.tenants[1].custom_endpoints = vec![custom_endpoint("/api/status")];
+        assert_eq!(
+            endpoint_error(&config),
+            "Tenant 2 custom endpoint '/api/status': path is also configured for tenant 1"
+        );
+
+        // Tenants on different hosts may share a path, but not on the same host
+        config.tenants[0].host = Some("a.example.com".to_string());
+        config.tenants[1].host = Some("b.example.com".to_string());
+        assert!(config.validate_custom_endpoints().is_ok());
+        config.tenants[1].host = Some("a.example.com".to_string());
+        assert!(endpoint_error(&config).contains("also configured for tenant 1"));
+    }
+
+    #[test]
+    fn test_custom_endpoint_method_is_validated() {
+        for method in ["GET", "post", "Post"] {
+            let mut endpoint = custom_endpoint("/api/status");
+            endpoint.method = Some(method.to_string());
+            assert!(config_with_endpoints(vec![endpoint])
+                .validate_custom_endpoints()
+                .is_ok());
+        }
+        let mut endpoint = custom_endpoint("/api/status");
+        endpoint.method = Some("delete".to_string());
+        assert_eq!(
+            endpoint_error(&config_with_endpoints(vec![endpoint])),
+            "Tenant 1 custom endpoint '/api/status': method 'DELETE' is not supported, use GET or POST"
+        );
+    }
+
+    #[test]
+    fn test_custom_endpoint_status_code_is_validated() {
+        for status_code in [100, 204, 418, 599] {
+            let mut endpoint = custom_endpoint("/api/status");
+            endpoint.status_code = status_code;
+            assert!(config_with_endpoints(vec![endpoint])
+                .validate_custom_endpoints()
+                .is_ok());
+        }
+        for status_code in [0, 99, 600, 1000] {
+            let mut endpoint = custom_endpoint("/api/status");
+            endpoint.status_code = status_code;
+            assert_eq!(
+                endpoint_error(&config_with_endpoints(vec![endpoint])),
+                format!(
+                    "Tenant 1 custom endpoint '/api/status': status_code {} is not a valid HTTP status",
+                    status_code
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_endpoint_content_type_is_validated() {
+        for content_type in [
+            "application/json",
+            "text/plain; charset=utf-8",
+            "application/scim+json;charset=\"utf-8\"",
+            "application/vnd.api+json",
+        ] {
+            let mut endpoint = custom_endpoint("/api/status");
+            endpoint.content_type = content_type.to_string();
+            assert!(
+                config_with_endpoints(vec![endpoint])
+                    .validate_custom_endpoints()
+                    .is_ok(),
+                "{}",
+                content_type
+            );
+        }
+        for content_type in [
+            "",
+            "json",
+            "text/",
+            "text/ plain",
+            "text/plain;",
+            "text/plain; charset",
+            "text/plain\r\nX-Injected: 1",
+        ] {
+            let mut endpoint = custom_endpoint("/api/status");
+            endpoint.content_type = content_type.to_string();
+            assert!(
+                endpoint_error(&config_with_endpoints(vec![endpoint]))
+                    .contains("is not a valid media type"),
+                "{}",
+                content_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_endpoint_response_size_is_capped() {
+        let mut endpoint = custom_endpoint("/api/status");
+        endpoint.response = "x".repeat(11);
+        let mut config = config_with_endpoints(vec![endpoint]);
+        config.server.custom_endpoint_max_response_bytes = 11;
+        assert!(config.validate_custom_endpoints().is_ok());
+
+        config.server.custom_endpoint_max_response_bytes = 10;
+        assert_eq!(
+            endpoint_error(&config),
+            "Tenant 1 custom endpoint '/api/status': response is 11 bytes, more than the 10 allowed by server.custom_endpoint_max_response_bytes"
+        );
+    }
+
+    #[test]
+    fn test_custom_endpoint_response_sources_are_validated() {
+        let file = std::env::temp_dir().join("scim_custom_endpoint_response.json");
+        std::fs::write(&file, "{}").unwrap();
+        let file = file.to_string_lossy().into_owned();
+
+        let mut endpoint = custom_endpoint("/api/status");
+        endpoint.response = String::new();
+        endpoint.response_file = Some(file.clone());
+        endpoint.cache_response_file = true;
+        assert!(config_with_endpoints(vec![endpoint.clone()])
+            .validate_custom_endpoints()
+            .is_ok());
+
+        endpoint.response_base64 = Some("aGk=".to_string());
+        assert_eq!(
+            endpoint_error(&config_with_endpoints(vec![endpoint.clone()])),
+            "Tenant 1 custom endpoint '/api/status': response, response_file and response_base64 are mutually exclusive"
+        );
+
+        endpoint.response_base64 = None;
+        endpoint.response_file = Some("/nonexistent/logo.png".to_string());
+        assert!(
+            endpoint_error(&config_with_endpoints(vec![endpoint.clone()]))
+                .contains("response_file '/nonexistent/logo.png' cannot be read")
+        );
+        endpoint.response_file = Some(std::env::temp_dir().to_string_lossy().into_owned());
+        assert!(
+            endpoint_error(&config_with_endpoints(vec![endpoint.clone()]))
+                .ends_with("is not a file")
+        );
+
+        endpoint.response_file = None;
+        assert!(
+            endpoint_error(&config_with_endpoints(vec![endpoint.clone()]))
+                .ends_with("cache_response_file requires response_file")
+        );
+
+        endpoint.cache_response_file = false;
+        endpoint.response_base64 = Some("not base64!".to_string());
+        assert!(
+            endpoint_error(&config_with_endpoints(vec![endpoint.clone()]))
+                .contains("response_base64 is not valid base64")
+        );
+
+        // The decoded size counts against the cap
+        endpoint.response_base64 = Some("aGVsbG8gd29ybGQ=".to_string());
+        let mut config = config_with_endpoints(vec![endpoint]);
+        config.server.custom_endpoint_max_response_bytes = 10;
+        assert!(endpoint_error(&config).ends_with(
+            "response is 11 bytes, more than the 10 allowed by server.custom_endpoint_max_response_bytes"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_custom_endpoint_fails_load() {
+        let temp_file = "/tmp/invalid_custom_endpoint_config.yaml";
+        std::fs::write(
+            temp_file,
+            r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+tenants:
+  - id: 7
+    path: "/scim/v2"
+    auth:
+      type: "unauthenticated"
+    custom_endpoints:
+      - path: "/scim/v2/Users"
+        response: "{}"
+"#,
+        )
+        .unwrap();
+        let result = AppConfig::load_from_file(temp_file);
+        std::fs::remove_file(temp_file).unwrap();
+        assert_eq!(
+            result.unwrap_err(),
+            "Tenant 7 custom endpoint '/scim/v2/Users': path falls under the SCIM base path '/scim/v2' of tenant 7"
+        );
+    }
+
     #[test]
     fn test_relative_path_with_host() {
         let config_content = r#"
@@ -1072,16 +2541,31 @@ tenants:
             server: ServerConfig {
                 host: "127.0.0.1".to_string(),
                 port: 3000,
+                log_format: "text".to_string(),
+                log_level: "info".to_string(),
+                expose_internal_errors: false,
+                admin_token: None,
+                custom_endpoint_max_response_bytes: default_custom_endpoint_max_response_bytes(),
+                max_tenants: None,
+                share_link_secret: None,
+                maintenance_endpoints: false,
+                compression: Default::default(),
+                stats: Default::default(),
             },
+            password: Default::default(),
             backend: BackendConfig {
                 backend_type: "database".to_string(),
+                event_sourced: None,
                 database: Some(DatabaseConfig {
                     db_type: "sqlite".to_string(),
                     url: "test.db".to_string(),
                     max_connections: 10,
+                    sqlite: Default::default(),
+                    postgres: Default::default(),
                 }),
             },
             compatibility: CompatibilityConfig::default(),
+            read_only: Default::default(),
             tenants: vec![TenantConfig {
                 id: 4,
                 path: "/api/scim".to_string(),
@@ -1094,10 +2578,29 @@ tenants:
                     auth_type: "bearer".to_string(),
                     token: Some("forwarded_token".to_string()),
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             }],
         };
 
@@ -1126,16 +2629,31 @@ tenants:
             server: ServerConfig {
                 host: "127.0.0.1".to_string(),
                 port: 3000,
+                log_format: "text".to_string(),
+                log_level: "info".to_string(),
+                expose_internal_errors: false,
+                admin_token: None,
+                custom_endpoint_max_response_bytes: default_custom_endpoint_max_response_bytes(),
+                max_tenants: None,
+                share_link_secret: None,
+                maintenance_endpoints: false,
+                compression: Default::default(),
+                stats: Default::default(),
             },
+            password: Default::default(),
             backend: BackendConfig {
                 backend_type: "database".to_string(),
+                event_sourced: None,
                 database: Some(DatabaseConfig {
                     db_type: "sqlite".to_string(),
                     url: "test.db".to_string(),
                     max_connections: 10,
+                    sqlite: Default::default(),
+                    postgres: Default::default(),
                 }),
             },
             compatibility: CompatibilityConfig::default(),
+            read_only: Default::default(),
             tenants: vec![TenantConfig {
                 id: 5,
                 path: "/scim".to_string(),
@@ -1151,10 +2669,29 @@ tenants:
                         username: "xfwd_user".to_string(),
                         password: "xfwd_pass".to_string(),
                     }),
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             }],
         };
 
@@ -1275,6 +2812,118 @@ tenants:
         std::fs::remove_file(temp_file).unwrap();
     }
 
+    fn request_for<'a>(path: &'a str, host: &'a str) -> RequestInfo<'a> {
+        RequestInfo {
+            path,
+            host_header: Some(host),
+            forwarded_header: None,
+            x_forwarded_proto: None,
+            x_forwarded_host: None,
+            x_forwarded_port: None,
+            client_ip: None,
+        }
+    }
+
+    #[test]
+    fn test_nested_tenant_paths_use_longest_prefix() {
+        let base = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+tenants:
+"#;
+        let tenants = [
+            r#"
+  - id: 1
+    path: "/scim/v2"
+    auth:
+      type: "unauthenticated"
+"#,
+            r#"
+  - id: 2
+    path: "/scim/v2/contractors"
+    auth:
+      type: "unauthenticated"
+"#,
+        ];
+
+        // The result must not depend on the order of the tenants
+        for order in [[0, 1], [1, 0]] {
+            let yaml = format!("{}{}{}", base, tenants[order[0]], tenants[order[1]]);
+            let config: AppConfig = serde_yaml::from_str(&yaml).unwrap();
+
+            let matched = |path| {
+                config
+                    .find_tenant_by_request(&request_for(path, "any.host.com"))
+                    .map(|(tenant, _)| tenant.id)
+            };
+            assert_eq!(matched("/scim/v2/Users"), Some(1));
+            assert_eq!(matched("/scim/v2/contractors/Users"), Some(2));
+            assert_eq!(matched("/scim/v2/contractors"), Some(2));
+            assert_eq!(matched("/other/Users"), None);
+
+            assert_eq!(config.overlapping_tenant_paths(), vec![(1, 2)]);
+        }
+    }
+
+    #[test]
+    fn test_host_scoped_tenant_wins_over_longer_path() {
+        let yaml = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+tenants:
+  - id: 1
+    path: "/scim/v2/contractors"
+    auth:
+      type: "unauthenticated"
+  - id: 2
+    path: "/scim"
+    host: "acme.example.com"
+    auth:
+      type: "unauthenticated"
+  - id: 3
+    path: "/scim/v2"
+    host: "acme.example.com"
+    auth:
+      type: "unauthenticated"
+  - id: 4
+    path: "/scim/v2"
+    host: "other.example.com"
+    auth:
+      type: "unauthenticated"
+"#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        let matched = |path, host| {
+            config
+                .find_tenant_by_request(&request_for(path, host))
+                .map(|(tenant, _)| tenant.id)
+        };
+
+        // Among host-scoped tenants the longest path wins
+        assert_eq!(matched("/scim/v2/Users", "acme.example.com"), Some(3));
+        assert_eq!(matched("/scim/Users", "acme.example.com"), Some(2));
+        // A matching host takes precedence over a longer path-only tenant
+        assert_eq!(
+            matched("/scim/v2/contractors/Users", "acme.example.com"),
+            Some(3)
+        );
+        assert_eq!(
+            matched("/scim/v2/contractors/Users", "any.host.com"),
+            Some(1)
+        );
+
+        // Tenants on different hosts never overlap
+        assert_eq!(
+            config.overlapping_tenant_paths(),
+            vec![(2, 1), (2, 3), (3, 1), (4, 1)]
+        );
+    }
+
     #[test]
     fn test_build_base_url_functionality() {
         // Test the new build_base_url method with different configurations
@@ -1289,10 +2938,29 @@ tenants:
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             override_base_url: Some("https://custom.example.com".to_string()),
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         };
 
         let request_info = RequestInfo {
@@ -1318,10 +2986,29 @@ tenants:
                 auth_type: "bearer".to_string(),
                 token: Some("token123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         };
 
         let result = tenant_path_only.build_base_url(&request_info);
@@ -1340,10 +3027,29 @@ tenants:
                     username: "admin".to_string(),
                     password: "pass".to_string(),
                 }),
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         };
 
         let request_info_with_matching_host = RequestInfo {
@@ -1372,10 +3078,29 @@ tenants:
                 auth_type: "bearer".to_string(),
                 token: Some("secure_token".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         };
 
         let request_info_forwarded = RequestInfo {
@@ -1,36 +1,102 @@
+#[cfg(feature = "server")]
 use axum::{http::StatusCode, Json};
+#[cfg(feature = "server")]
 use serde_json::json;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether 500 responses carry the internal error detail (`server.expose_internal_errors`)
+static EXPOSE_INTERNAL_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Set whether internal error details are returned to clients
+///
+/// Called once at startup. When off, 500 responses only say "Internal server
+/// error" and the detail is logged.
+pub fn set_expose_internal_errors(expose: bool) {
+    EXPOSE_INTERNAL_ERRORS.store(expose, Ordering::Relaxed);
+}
+
+/// One problem found while validating a resource
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// Attribute path, e.g. `emails[1].value`
+    pub path: String,
+    /// SCIM error type of the problem, e.g. `invalidValue`
+    pub scim_type: &'static str,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn invalid_value(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            scim_type: "invalidValue",
+            message: message.into(),
+        }
+    }
+
+    pub fn mutability(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            scim_type: "mutability",
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_syntax(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            scim_type: "invalidSyntax",
+            message: message.into(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum AppError {
     Database(String),
+    #[cfg(feature = "backend-sqlite")]
     Rusqlite(rusqlite::Error),
     Serialization(serde_json::Error),
     BadRequest(String),
+    /// Value outside the configured canonical set (SCIM `invalidValue`)
+    InvalidValue(String),
+    /// Every problem found while validating a resource, reported in one 400
+    Validation(Vec<ValidationIssue>),
     Conflict(String),
     Internal(String),
     #[allow(dead_code)]
     FilterParse(String),
+    /// Filter on an attribute the resource's schemas do not define (SCIM `invalidFilter`)
+    InvalidFilter(String),
     Configuration(String),
     #[allow(dead_code)]
     PreconditionFailed,
+    /// Transient backend contention or connection loss; clients should retry
+    Unavailable(String),
 }
 
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AppError::Database(e) => write!(f, "Database error: {}", e),
+            #[cfg(feature = "backend-sqlite")]
             AppError::Rusqlite(e) => write!(f, "SQLite error: {}", e),
             AppError::Serialization(e) => write!(f, "Serialization error: {}", e),
             AppError::BadRequest(e) => write!(f, "Bad request: {}", e),
+            AppError::InvalidValue(e) => write!(f, "Invalid value: {}", e),
+            AppError::Validation(issues) => {
+                write!(f, "Validation failed: {}", validation_summary(issues))
+            }
             AppError::Conflict(e) => write!(f, "Conflict: {}", e),
             AppError::Internal(e) => write!(f, "Internal error: {}", e),
             AppError::FilterParse(e) => write!(f, "Filter parse error: {}", e),
+            AppError::InvalidFilter(e) => write!(f, "Invalid filter: {}", e),
             AppError::Configuration(e) => write!(f, "Configuration error: {}", e),
             AppError::PreconditionFailed => {
                 write!(f, "Precondition failed: Resource version mismatch")
             }
+            AppError::Unavailable(e) => write!(f, "Service unavailable: {}", e),
         }
     }
 }
@@ -38,6 +104,7 @@ impl fmt::Display for AppError {
 impl std::error::Error for AppError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
+            #[cfg(feature = "backend-sqlite")]
             AppError::Rusqlite(e) => Some(e),
             AppError::Serialization(e) => Some(e),
             _ => None,
@@ -45,6 +112,7 @@ impl std::error::Error for AppError {
     }
 }
 
+#[cfg(feature = "backend-sqlite")]
 impl From<rusqlite::Error> for AppError {
     fn from(err: rusqlite::Error) -> Self {
         AppError::Rusqlite(err)
@@ -57,6 +125,7 @@ impl From<serde_json::Error> for AppError {
     }
 }
 
+#[cfg(feature = "backend-sqlite")]
 impl From<std::sync::PoisonError<std::sync::MutexGuard<'_, rusqlite::Connection>>> for AppError {
     fn from(err: std::sync::PoisonError<std::sync::MutexGuard<'_, rusqlite::Connection>>) -> Self {
         AppError::Internal(err.to_string())
@@ -65,7 +134,24 @@ impl From<std::sync::PoisonError<std::sync::MutexGuard<'_, rusqlite::Connection>
 
 pub type AppResult<T> = Result<T, AppError>;
 
+/// The message of a single issue, or all messages numbered in one line
+fn validation_summary(issues: &[ValidationIssue]) -> String {
+    match issues {
+        [issue] => issue.message.clone(),
+        _ => format!(
+            "{} validation errors: {}",
+            issues.len(),
+            issues
+                .iter()
+                .map(|issue| issue.message.as_str())
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+    }
+}
+
 // SCIM 2.0 standard error response helper
+#[cfg(feature = "server")]
 pub fn scim_error_response(
     status_code: StatusCode,
     scim_type: &str,
@@ -83,7 +169,103 @@ pub fn scim_error_response(
     )
 }
 
+// 428 response for tenants that require conditional writes (RFC 6585)
+#[cfg(feature = "server")]
+pub fn precondition_required_response() -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::PRECONDITION_REQUIRED,
+        Json(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "detail": "If-Match header is required for this operation",
+            "status": "428"
+        })),
+    )
+}
+
+// 403 response for requests a scoped token's permissions do not allow
+#[cfg(feature = "server")]
+pub fn forbidden_response(detail: &str) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "detail": detail,
+            "status": "403"
+        })),
+    )
+}
+
+// 501 response for tenants that have PATCH disabled (compatibility.patch_supported)
+#[cfg(feature = "server")]
+pub fn patch_not_supported_response() -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::NOT_IMPLEMENTED,
+        Json(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "detail": "PATCH is not supported",
+            "status": "501"
+        })),
+    )
+}
+
+// 501 response for resource types a tenant does not serve (enabled_resources)
+#[cfg(feature = "server")]
+pub fn resource_type_disabled_response(
+    resource_type: crate::parser::ResourceType,
+) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::NOT_IMPLEMENTED,
+        Json(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "detail": format!("{} resources are not enabled for this tenant", resource_type),
+            "status": "501"
+        })),
+    )
+}
+
+// 400 response for PATCH bodies over compatibility.max_patch_operations
+#[cfg(feature = "server")]
+pub fn too_many_patch_operations_response(
+    count: usize,
+    max: u32,
+) -> (StatusCode, Json<serde_json::Value>) {
+    scim_error_response(
+        StatusCode::BAD_REQUEST,
+        "invalidValue",
+        &format!(
+            "PATCH request contains {} operations, maximum allowed is {}",
+            count, max
+        ),
+    )
+}
+
+// 400 listing every validation issue; `errors` is a non-standard extension
+#[cfg(feature = "server")]
+fn validation_error_response(issues: &[ValidationIssue]) -> (StatusCode, Json<serde_json::Value>) {
+    let scim_type = issues
+        .first()
+        .map(|issue| issue.scim_type)
+        .unwrap_or("invalidValue");
+    let (status, Json(mut body)) = scim_error_response(
+        StatusCode::BAD_REQUEST,
+        scim_type,
+        &validation_summary(issues),
+    );
+    body["errors"] = issues
+        .iter()
+        .map(|issue| {
+            json!({
+                "path": issue.path,
+                "scimType": issue.scim_type,
+                "message": issue.message
+            })
+        })
+        .collect();
+    (status, Json(body))
+}
+
 // HTTPレスポンスへの変換
+#[cfg(feature = "server")]
 impl AppError {
     pub fn to_response(&self) -> (StatusCode, Json<serde_json::Value>) {
         let (status, message) = match self {
@@ -91,6 +273,7 @@ impl AppError {
                 eprintln!("Database error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, e.clone())
             }
+            #[cfg(feature = "backend-sqlite")]
             AppError::Rusqlite(e) => {
                 eprintln!("SQLite error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
@@ -100,6 +283,10 @@ impl AppError {
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
             }
             AppError::BadRequest(e) => (StatusCode::BAD_REQUEST, e.clone()),
+            AppError::InvalidValue(e) => {
+                return scim_error_response(StatusCode::BAD_REQUEST, "invalidValue", e);
+            }
+            AppError::Validation(issues) => return validation_error_response(issues),
             AppError::Conflict(e) => {
                 return scim_error_response(StatusCode::CONFLICT, "uniqueness", e);
             }
@@ -108,6 +295,9 @@ impl AppError {
                 (StatusCode::INTERNAL_SERVER_ERROR, e.clone())
             }
             AppError::FilterParse(e) => (StatusCode::BAD_REQUEST, e.clone()),
+            AppError::InvalidFilter(e) => {
+                return scim_error_response(StatusCode::BAD_REQUEST, "invalidFilter", e);
+            }
             AppError::Configuration(e) => {
                 eprintln!("Configuration error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, e.clone())
@@ -119,8 +309,40 @@ impl AppError {
                     "Resource version mismatch",
                 );
             }
+            AppError::Unavailable(e) => {
+                // The detail stays generic; the driver message is only logged
+                eprintln!("Backend unavailable: {}", e);
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({
+                        "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+                        "detail": "The service is temporarily unavailable, please retry",
+                        "status": "503"
+                    })),
+                );
+            }
+        };
+
+        let message = if status == StatusCode::INTERNAL_SERVER_ERROR
+            && !EXPOSE_INTERNAL_ERRORS.load(Ordering::Relaxed)
+        {
+            "Internal server error".to_string()
+        } else {
+            message
         };
 
         (status, Json(json!({ "error": message })))
     }
+
+    /// `to_response`, leaving out the `errors` list of validation failures
+    /// unless `detailed` (`compatibility.detailed_validation_errors`)
+    pub fn to_validation_response(&self, detailed: bool) -> (StatusCode, Json<serde_json::Value>) {
+        let (status, Json(mut body)) = self.to_response();
+        if !detailed {
+            if let Some(body) = body.as_object_mut() {
+                body.remove("errors");
+            }
+        }
+        (status, Json(body))
+    }
 }
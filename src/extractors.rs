@@ -1,11 +1,21 @@
 use axum::{
-    extract::{rejection::JsonRejection, FromRequest, Request},
-    http::{header, HeaderMap, StatusCode},
+    extract::{
+        rejection::JsonRejection, ConnectInfo, FromRequest, FromRequestParts, OriginalUri, Request,
+    },
+    http::{header, request::Parts, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::de::DeserializeOwned;
 use serde_json::json;
+use std::net::SocketAddr;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::auth::{self, TenantInfo};
+use crate::backend::ScimBackend;
+use crate::config::{AppConfig, RequestInfo, ResolvedUrl};
+use crate::password::PasswordManagerRegistry;
 
 /// Custom JSON extractor that accepts both application/json and application/scim+json
 /// as required by SCIM 2.0 specification (RFC 7644)
@@ -80,6 +90,90 @@ impl IntoResponse for ScimJsonRejection {
     }
 }
 
+/// Router states the `Tenant` extractor can read the configuration from
+pub trait TenantConfigSource {
+    fn app_config(&self) -> &AppConfig;
+}
+
+impl TenantConfigSource for Arc<AppConfig> {
+    fn app_config(&self) -> &AppConfig {
+        self
+    }
+}
+
+impl TenantConfigSource
+    for (
+        Arc<dyn ScimBackend>,
+        Arc<AppConfig>,
+        Arc<PasswordManagerRegistry>,
+    )
+{
+    fn app_config(&self) -> &AppConfig {
+        &self.1
+    }
+}
+
+/// The tenant a SCIM request addresses
+///
+/// Resolved with `AppConfig::find_tenant_by_request` from the host and the
+/// original request path (the router strips the tenant base path before the
+/// handlers run). Derefs to the `TenantInfo` the response helpers take; the
+/// principal is the one `auth_middleware` authenticated.
+pub struct Tenant {
+    pub info: TenantInfo,
+    #[allow(dead_code)]
+    pub resolved_url: ResolvedUrl,
+}
+
+impl Deref for Tenant {
+    type Target = TenantInfo;
+
+    fn deref(&self) -> &TenantInfo {
+        &self.info
+    }
+}
+
+impl<S> FromRequestParts<S> for Tenant
+where
+    S: TenantConfigSource + Send + Sync,
+{
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_config = state.app_config();
+        let uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or(&parts.uri, |original| &original.0);
+        let request_info = RequestInfo {
+            client_ip: parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|connect_info| connect_info.0.ip()),
+            ..auth::url_request_info(uri, &parts.headers)
+        };
+
+        let Some((tenant, resolved_url)) = app_config.find_tenant_by_request(&request_info) else {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+                    "detail": "No tenant is configured for this URL",
+                    "status": "404"
+                })),
+            ));
+        };
+
+        let mut info = auth::unauthenticated_tenant_info(app_config, tenant, uri, &parts.headers);
+        if let Some(authenticated) = parts.extensions.get::<TenantInfo>() {
+            info.principal_name = authenticated.principal_name.clone();
+            info.permissions = authenticated.permissions.clone();
+        }
+
+        Ok(Tenant { info, resolved_url })
+    }
+}
+
 // Helper function to set SCIM content type in responses
 #[allow(dead_code)]
 pub fn scim_content_type() -> HeaderMap {
@@ -90,3 +184,105 @@ pub fn scim_content_type() -> HeaderMap {
     );
     headers
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn app_config() -> Arc<AppConfig> {
+        let yaml = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+backend:
+  type: "database"
+tenants:
+  - id: 1
+    path: "/scim/v2"
+    host: "a.example.com"
+    auth:
+      type: "unauthenticated"
+  - id: 2
+    path: "/scim/v2"
+    host: "b.example.com"
+    auth:
+      type: "unauthenticated"
+  - id: 3
+    path: "/scim/v2"
+    auth:
+      type: "unauthenticated"
+  - id: 4
+    path: "/scim/v2/contractors"
+    auth:
+      type: "unauthenticated"
+"#;
+        Arc::new(serde_yaml::from_str(yaml).unwrap())
+    }
+
+    async fn extract(host: &str, path: &str) -> Result<Tenant, StatusCode> {
+        let (mut parts, _) = Request::builder()
+            .uri(path)
+            .header(header::HOST, host)
+            .body(())
+            .unwrap()
+            .into_parts();
+        Tenant::from_request_parts(&mut parts, &app_config())
+            .await
+            .map_err(|(status, _)| status)
+    }
+
+    #[tokio::test]
+    async fn test_host_based_tenants() {
+        let tenant = extract("a.example.com", "/scim/v2/Users").await.unwrap();
+        assert_eq!(tenant.tenant_id, 1);
+        assert_eq!(tenant.resolved_url.host, "a.example.com");
+        assert_eq!(tenant.base_path, "http://a.example.com/scim/v2");
+
+        let tenant = extract("b.example.com", "/scim/v2/Users").await.unwrap();
+        assert_eq!(tenant.tenant_id, 2);
+
+        // A host-scoped tenant wins over the nested path-only tenant
+        let tenant = extract("a.example.com", "/scim/v2/contractors/Users")
+            .await
+            .unwrap();
+        assert_eq!(tenant.tenant_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_path_based_tenants() {
+        let tenant = extract("c.example.com", "/scim/v2/Users").await.unwrap();
+        assert_eq!(tenant.tenant_id, 3);
+
+        let tenant = extract("c.example.com", "/scim/v2/contractors/Groups")
+            .await
+            .unwrap();
+        assert_eq!(tenant.tenant_id, 4);
+        assert_eq!(tenant.base_path, "http://c.example.com/scim/v2/contractors");
+        assert!(tenant.principal_name.is_none());
+
+        assert_eq!(
+            extract("c.example.com", "/other/Users").await.err(),
+            Some(StatusCode::NOT_FOUND)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolves_from_the_original_uri() {
+        // Handlers see the path below the tenant base path
+        let (mut parts, _) = Request::builder()
+            .uri("/Users")
+            .header(header::HOST, "c.example.com")
+            .body(())
+            .unwrap()
+            .into_parts();
+        parts
+            .extensions
+            .insert(OriginalUri("/scim/v2/contractors/Users".parse().unwrap()));
+
+        let tenant = Tenant::from_request_parts(&mut parts, &app_config())
+            .await
+            .unwrap();
+        assert_eq!(tenant.tenant_id, 4);
+    }
+}
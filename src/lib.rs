@@ -1,15 +1,64 @@
-pub mod auth;
-pub mod backend;
+//! SCIM 2.0 server and toolkit
+//!
+//! With default features this crate is the full server. Other services can
+//! depend on it with `default-features = false, features = ["core"]` to get
+//! the models, filter/PATCH parsers and schema validation without tokio,
+//! axum or sqlx:
+//!
+//! ```
+//! use scim_server::models::User;
+//! use scim_server::parser::filter_parser::{parse_filter, FilterOperator};
+//! use scim_server::schema::validate_user;
+//!
+//! let user: User = serde_json::from_value(serde_json::json!({
+//!     "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+//!     "userName": "alice",
+//!     "emails": [{"value": "alice@example.com", "type": "work", "primary": true}]
+//! }))?;
+//! validate_user(&user.base)?;
+//!
+//! let filter = parse_filter(r#"emails[type eq "work"] and active eq true"#)?;
+//! assert!(matches!(filter, FilterOperator::And(_, _)));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
 pub mod config;
 pub mod error;
-pub mod extractors;
-pub mod logging;
 pub mod models;
 pub mod parser;
 pub mod password;
 pub mod resource;
 pub mod schema;
+
+#[cfg(feature = "server")]
+pub mod auth;
+#[cfg(feature = "server")]
+pub mod auth_lockout;
+#[cfg(feature = "server")]
+pub mod backend;
+#[cfg(feature = "server")]
+pub mod compression;
+#[cfg(feature = "server")]
+pub mod extractors;
+#[cfg(feature = "server")]
+pub mod logging;
+#[cfg(feature = "server")]
+pub mod maintenance;
+#[cfg(feature = "server")]
+pub mod permissions;
+#[cfg(feature = "server")]
+pub mod response_validation;
+#[cfg(feature = "server")]
+pub mod router;
+#[cfg(feature = "server")]
+pub mod share;
+#[cfg(feature = "server")]
 pub mod startup;
+#[cfg(feature = "server")]
+pub mod stats;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+#[cfg(feature = "server")]
 pub mod utils;
 
 // Re-export commonly used types for easier access
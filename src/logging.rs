@@ -1,58 +1,141 @@
 use axum::{
-    extract::Request,
-    http::{Method, Uri},
+    body::HttpBody,
+    extract::{ConnectInfo, Request},
+    http::{HeaderValue, Method, Uri},
     middleware::Next,
     response::Response,
 };
 use chrono::Utc;
+use serde_json::{Map, Value};
+use std::net::SocketAddr;
 use std::time::Instant;
-use tracing::info;
+use tracing::{
+    field::{Field, Visit},
+    info, Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields, MakeWriter},
+    registry::LookupSpan,
+    EnvFilter,
+};
+
+use crate::auth::TenantInfo;
+use crate::config::ServerConfig;
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Install the global tracing subscriber according to `server.log_format`
+pub fn init_tracing(server: &ServerConfig) -> Result<(), String> {
+    let subscriber = build_subscriber(&server.log_format, &server.log_level, std::io::stdout)?;
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| format!("Failed to initialize logging: {}", e))
+}
+
+/// Build a subscriber for "text", "json" or "gelf" output
+///
+/// `RUST_LOG` overrides `log_level` when set, as with `fmt::init()`.
+pub fn build_subscriber<W>(
+    log_format: &str,
+    log_level: &str,
+    make_writer: W,
+) -> Result<Box<dyn Subscriber + Send + Sync>, String>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(log_level))
+        .map_err(|e| format!("Invalid log_level '{}': {}", log_level, e))?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(make_writer);
+
+    match log_format {
+        "text" => Ok(Box::new(builder.finish())),
+        "json" => Ok(Box::new(builder.json().flatten_event(true).finish())),
+        "gelf" => Ok(Box::new(builder.event_format(GelfFormat::new()).finish())),
+        other => Err(format!(
+            "Unsupported log_format '{}' (expected text, json or gelf)",
+            other
+        )),
+    }
+}
 
 pub async fn logging_middleware(request: Request, next: Next) -> Response {
     let start = Instant::now();
     let method = request.method().clone();
     let uri = request.uri().clone();
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
     let user_agent = request
         .headers()
         .get("user-agent")
         .and_then(|h| h.to_str().ok())
         .unwrap_or("-")
         .to_string();
-    let remote_addr = request
+    let remote_ip = request
         .headers()
         .get("x-forwarded-for")
         .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.split(',').next())
+        .map(|ip| ip.trim().to_string())
         .or_else(|| {
             request
                 .headers()
                 .get("x-real-ip")
                 .and_then(|h| h.to_str().ok())
+                .map(String::from)
         })
-        .unwrap_or("-")
-        .to_string();
+        .or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|connect_info| connect_info.0.ip().to_string())
+        })
+        .unwrap_or_else(|| "-".to_string());
+    // Set by auth_middleware for tenant routes; absent for /health and the like
+    let tenant = request
+        .extensions()
+        .get::<TenantInfo>()
+        .map(|tenant_info| tenant_info.tenant_id);
+    let tenant_id = tenant
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    #[cfg(feature = "otel")]
+    let span = crate::telemetry::start_request_span(&method, uri.path(), request.headers(), tenant);
 
-    let response = next.run(request).await;
+    let mut response = next.run(request).await;
 
-    let duration = start.elapsed();
-    let status = response.status();
-    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let status = response.status().as_u16();
+    #[cfg(feature = "otel")]
+    crate::telemetry::end_request_span(span, status);
+    // Uncompressed size; omitted for streamed bodies of unknown length
+    let response_bytes = response.body().size_hint().exact();
 
     info!(
         target: "access_log",
-        "{} {} \"{}\" {} {} {}ms \"{}\" \"{}\"",
-        timestamp,
-        remote_addr,
-        format_request(&method, &uri),
-        status.as_u16(),
-        response.headers()
-            .get("content-length")
-            .and_then(|h| h.to_str().ok())
-            .unwrap_or("-"),
-        duration.as_millis(),
-        user_agent,
-        method
+        request_id = %request_id,
+        method = %method,
+        path = %uri.path(),
+        status,
+        duration_ms,
+        response_bytes,
+        tenant_id = %tenant_id,
+        remote_ip = %remote_ip,
+        user_agent = %user_agent,
+        "{}",
+        format_request(&method, &uri)
     );
 
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
     response
 }
 
@@ -60,21 +143,224 @@ fn format_request(method: &Method, uri: &Uri) -> String {
     format!("{} {} HTTP/1.1", method, uri)
 }
 
+/// GELF 1.1 event formatter writing one JSON message per line
+///
+/// Event fields become GELF additional fields (prefixed with `_`), so the
+/// output can be forwarded to Graylog by any line-based shipper.
+struct GelfFormat {
+    host: String,
+}
+
+impl GelfFormat {
+    fn new() -> Self {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "scim-server".to_string());
+        Self { host }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for GelfFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let mut visitor = GelfVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+
+        let mut message = Map::new();
+        message.insert("version".to_string(), "1.1".into());
+        message.insert("host".to_string(), self.host.clone().into());
+        message.insert(
+            "short_message".to_string(),
+            visitor.message.unwrap_or_default().into(),
+        );
+        message.insert(
+            "timestamp".to_string(),
+            (Utc::now().timestamp_millis() as f64 / 1000.0).into(),
+        );
+        message.insert(
+            "level".to_string(),
+            syslog_severity(metadata.level()).into(),
+        );
+        message.insert("_target".to_string(), metadata.target().into());
+        for (name, value) in visitor.fields {
+            // "_id" is reserved by GELF
+            let key = if name == "id" {
+                "_event_id".to_string()
+            } else {
+                format!("_{}", name)
+            };
+            message.insert(key, value);
+        }
+
+        writeln!(writer, "{}", Value::Object(message))
+    }
+}
+
+fn syslog_severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        _ => 7,
+    }
+}
+
+#[derive(Default)]
+struct GelfVisitor {
+    message: Option<String>,
+    fields: Map<String, Value>,
+}
+
+impl GelfVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            });
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for GelfVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use axum::{http::StatusCode, response::Html, routing::get, Router};
     use axum_test::TestServer;
+    use std::sync::{Arc, Mutex};
 
-    #[tokio::test]
-    async fn test_logging_middleware() {
-        let app = Router::new()
+    #[derive(Clone, Default)]
+    struct CaptureWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CaptureWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for CaptureWriter {
+        type Writer = CaptureWriter;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn test_app() -> Router {
+        Router::new()
             .route("/test", get(|| async { Html("Hello, World!") }))
-            .layer(axum::middleware::from_fn(logging_middleware));
+            .layer(axum::middleware::from_fn(logging_middleware))
+    }
+
+    /// Run one request with the given format and return the parsed access log line
+    async fn capture_access_log(log_format: &str) -> Value {
+        let writer = CaptureWriter::default();
+        let subscriber = build_subscriber(log_format, "info", writer.clone()).unwrap();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let server = TestServer::new(test_app()).unwrap();
+        let response = server
+            .get("/test")
+            .add_header("x-request-id", "req-123")
+            .add_header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.headers()["x-request-id"], "req-123");
 
-        let server = TestServer::new(app).unwrap();
+        let output = String::from_utf8(writer.0.lock().unwrap().clone()).unwrap();
+        let line = output
+            .lines()
+            .find(|line| line.contains("req-123"))
+            .expect("access log line");
+        serde_json::from_str(line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_logging_middleware() {
+        let server = TestServer::new(test_app()).unwrap();
         let response = server.get("/test").await;
 
         assert_eq!(response.status_code(), StatusCode::OK);
+        // A request id is generated when the client does not send one
+        assert!(response.headers().contains_key("x-request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_json_access_log_fields() {
+        let log = capture_access_log("json").await;
+
+        assert_eq!(log["target"], "access_log");
+        assert_eq!(log["request_id"], "req-123");
+        assert_eq!(log["method"], "GET");
+        assert_eq!(log["path"], "/test");
+        assert_eq!(log["status"], 200);
+        assert!(log["duration_ms"].is_u64());
+        assert_eq!(log["response_bytes"], "Hello, World!".len());
+        assert_eq!(log["tenant_id"], "-");
+        assert_eq!(log["remote_ip"], "203.0.113.7");
+    }
+
+    #[tokio::test]
+    async fn test_gelf_access_log_fields() {
+        let log = capture_access_log("gelf").await;
+
+        assert_eq!(log["version"], "1.1");
+        assert!(log["short_message"].as_str().unwrap().starts_with("GET "));
+        assert_eq!(log["level"], 6);
+        assert!(log["timestamp"].is_f64());
+        assert_eq!(log["_request_id"], "req-123");
+        assert_eq!(log["_method"], "GET");
+        assert_eq!(log["_path"], "/test");
+        assert_eq!(log["_status"], 200);
+        assert!(log["_duration_ms"].is_u64());
+        assert_eq!(log["_response_bytes"], "Hello, World!".len());
+        assert_eq!(log["_tenant_id"], "-");
+        assert_eq!(log["_remote_ip"], "203.0.113.7");
+    }
+
+    #[test]
+    fn test_build_subscriber_rejects_unknown_format() {
+        assert!(build_subscriber("text", "info", std::io::sink).is_ok());
+        assert!(build_subscriber("xml", "info", std::io::sink).is_err());
     }
 }
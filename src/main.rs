@@ -1,25 +1,31 @@
-use axum::{
-    middleware,
-    routing::{delete, get, patch, post, put},
-    Router,
-};
 use clap::Parser;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
 
 mod auth;
+mod auth_lockout;
 mod backend;
+mod compression;
 mod config;
 mod error;
 mod extractors;
 mod logging;
+mod maintenance;
 mod models;
 mod parser;
 mod password;
+mod permissions;
 mod resource;
+mod response_validation;
+mod router;
 mod schema;
+mod share;
 mod startup;
+mod stats;
+#[cfg(feature = "otel")]
+mod telemetry;
 mod utils;
 
 use backend::database::DatabaseBackendConfig;
@@ -48,24 +54,20 @@ async fn setup_backend(
     app_config: &AppConfig,
 ) -> Result<Arc<dyn ScimBackend>, Box<dyn std::error::Error>> {
     // Create backend configuration from app config
-    if app_config.backend.backend_type != "database" {
-        return Err(format!(
-            "Unsupported backend type: {}",
-            app_config.backend.backend_type
-        )
-        .into());
+    let backend_type = app_config.backend.backend_type.as_str();
+    if backend_type != "database" && backend_type != "event_sourced" {
+        return Err(format!("Unsupported backend type: {}", backend_type).into());
     }
 
-    let database_config = app_config
-        .backend
-        .database
-        .as_ref()
-        .ok_or("Database configuration is required when backend type is 'database'")?;
+    let database_config = app_config.backend.database.as_ref().ok_or(
+        "Database configuration is required for the 'database' and 'event_sourced' backends",
+    )?;
 
     let backend_config = DatabaseBackendConfig {
         database_type: match database_config.db_type.as_str() {
             "postgresql" => backend::DatabaseType::PostgreSQL,
             "sqlite" => backend::DatabaseType::SQLite,
+            "mysql" | "mariadb" => backend::DatabaseType::MySQL,
             _ => {
                 return Err(
                     format!("Unsupported database type: {}", database_config.db_type).into(),
@@ -76,12 +78,52 @@ async fn setup_backend(
         max_connections: database_config.max_connections,
         connection_timeout: 30,
         options: std::collections::HashMap::new(),
-    };
+        external_id_case_insensitive_tenants: app_config
+            .tenants
+            .iter()
+            .filter(|tenant| tenant.external_id_case_insensitive)
+            .map(|tenant| tenant.id)
+            .collect(),
+    }
+    .with_option(
+        backend::database::config::SQLITE_BUSY_TIMEOUT_MS.to_string(),
+        database_config.sqlite.busy_timeout_ms.to_string(),
+    )
+    .with_option(
+        backend::database::config::SQLITE_SERIALIZE_WRITES.to_string(),
+        database_config.sqlite.serialize_writes.to_string(),
+    )
+    .with_option(
+        backend::database::config::POSTGRES_INDEXED_USER_ATTRIBUTES.to_string(),
+        database_config.postgres.indexed_attributes.users.join(","),
+    )
+    .with_option(
+        backend::database::config::POSTGRES_INDEXED_GROUP_ATTRIBUTES.to_string(),
+        database_config.postgres.indexed_attributes.groups.join(","),
+    );
 
     println!("Setting up {} backend...", database_config.db_type);
 
     // Create backend instance
-    let backend = BackendFactory::create(&backend_config).await?;
+    let backend = if backend_type == "event_sourced" {
+        let snapshot_every = app_config
+            .backend
+            .event_sourced
+            .clone()
+            .unwrap_or_default()
+            .snapshot_every;
+        println!(
+            "   Event sourcing enabled (snapshot every {} events)",
+            snapshot_every
+        );
+        BackendFactory::create_event_sourced(&backend_config.with_option(
+            backend::event_sourced::SNAPSHOT_EVERY.to_string(),
+            snapshot_every.to_string(),
+        ))
+        .await?
+    } else {
+        BackendFactory::create(&backend_config).await?
+    };
 
     // Initialize tenant schemas using the same backend instance
     for tenant in &app_config.tenants {
@@ -97,9 +139,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse command line arguments
     let args = Args::parse();
 
-    // Initialize tracing for better debugging
-    tracing_subscriber::fmt::init();
-
     // Load configuration from specified file or use defaults
     let (mut app_config, using_defaults) = if let Some(config_path) = &args.config {
         let config = AppConfig::load_from_file(config_path)
@@ -122,6 +161,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         app_config.server.host = host;
     }
 
+    // Initialize tracing with the configured log format
+    logging::init_tracing(&app_config.server)?;
+    #[cfg(feature = "otel")]
+    let tracer_provider = telemetry::init_from_env()?;
+
+    if app_config.server.max_tenants.is_none()
+        && app_config.tenants.len() > config::LARGE_TENANT_COUNT
+    {
+        tracing::warn!(
+            "{} tenants are configured; set server.max_tenants to confirm the count is intended",
+            app_config.tenants.len()
+        );
+    }
+
+    for (outer, inner) in app_config.overlapping_tenant_paths() {
+        tracing::warn!(
+            "Tenant {} path is a prefix of tenant {} path; requests are routed to the longest matching path",
+            outer,
+            inner
+        );
+    }
+
     if !using_defaults {
         println!("🔧 Configuration loaded:");
         println!(
@@ -139,139 +200,48 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("   Tenants: {} configured", app_config.tenants.len());
     }
 
+    // Apply password hashing costs before any password is stored
+    password::set_argon2_params(app_config.password.argon2);
+
+    // Internal error details stay in the log unless explicitly exposed
+    error::set_expose_internal_errors(app_config.server.expose_internal_errors);
+
+    // ServiceProviderConfig reports the start time as meta.created/lastModified
+    resource::service_provider::record_start_time();
+
     // Setup backend
     let backend = setup_backend(&app_config).await?;
 
     // Use AppConfig directly
     let app_config_arc = Arc::new(app_config.clone());
 
-    // Build our application with multi-tenant routes
-    let mut app = Router::new();
-
-    // Add custom endpoints first (before SCIM routes)
-    // Custom endpoints are routed as absolute paths, not under tenant URLs
     for tenant in &app_config.tenants {
         for endpoint in &tenant.custom_endpoints {
             println!(
                 "🔗 Setting up custom endpoint for tenant {} at {}",
                 tenant.id, endpoint.path
             );
-            app = app.route(
-                &endpoint.path,
-                get(resource::custom::handle_custom_endpoint),
-            );
         }
     }
 
-    // Always use the existing handlers, but enhance them to support host resolution
-    // For now, let's use a unified approach that supports both static and dynamic routing
-
-    for tenant in &app_config.tenants {
-        // For tenants with route, we'll handle them dynamically in the handlers
-        // For simple URL tenants, we'll use static routing as before
-
-        let base_path = if tenant.path.starts_with("http://") || tenant.path.starts_with("https://")
-        {
-            // Extract path from full URL
-            if let Ok(url) = url::Url::parse(&tenant.path) {
-                url.path().trim_end_matches('/').to_string()
-            } else {
-                "/scim".to_string() // fallback
-            }
-        } else {
-            // Already a path
-            tenant.path.trim_end_matches('/').to_string()
-        };
-
-        if tenant.host.is_some() {
-            println!(
-                "🔧 Setting up host-based routing for tenant {} (host: {})",
-                tenant.id,
-                tenant.host.as_ref().unwrap_or(&"unspecified".to_string())
-            );
-        } else {
-            println!(
-                "🔗 Setting up path-only routes for tenant {} at {}",
-                tenant.id, base_path
-            );
-        }
-
-        // ServiceProviderConfig routes
-        app = app.route(
-            &format!("{}/ServiceProviderConfig", base_path),
-            get(resource::service_provider::service_provider_config),
-        );
-
-        // Schema and ResourceType routes
-        app = app.route(
-            &format!("{}/Schemas", base_path),
-            get(resource::schema::schemas),
-        );
-        app = app.route(
-            &format!("{}/ResourceTypes", base_path),
-            get(resource::resource_type::resource_types),
-        );
-
-        // User routes
-        app = app.route(
-            &format!("{}/Users", base_path),
-            post(resource::user::create_user),
-        );
-        app = app.route(
-            &format!("{}/Users", base_path),
-            get(resource::user::search_users),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            get(resource::user::get_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            put(resource::user::update_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            patch(resource::user::patch_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            delete(resource::user::delete_user),
-        );
-
-        // Group routes
-        app = app.route(
-            &format!("{}/Groups", base_path),
-            post(resource::group::create_group),
-        );
-        app = app.route(
-            &format!("{}/Groups", base_path),
-            get(resource::group::search_groups),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            get(resource::group::get_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            put(resource::group::update_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            patch(resource::group::patch_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            delete(resource::group::delete_group),
+    let sync_stats = Arc::new(stats::SyncStats::default());
+    if app_config.server.stats.persist {
+        stats::restore_snapshots(backend.as_ref(), &sync_stats, &app_config).await;
+        stats::spawn_snapshots(
+            backend.clone(),
+            sync_stats.clone(),
+            Duration::from_secs(app_config.server.stats.snapshot_interval_seconds.max(1)),
         );
     }
 
-    let app = app
-        .layer(middleware::from_fn(logging::logging_middleware))
-        .layer(middleware::from_fn_with_state(
-            app_config_arc.clone(),
-            auth::auth_middleware,
-        ))
-        .with_state((backend, app_config_arc.clone()));
+    // One set of tenant-relative routes serves every tenant
+    let routes_started = Instant::now();
+    let app = router::build_router(app_config_arc.clone(), backend, sync_stats);
+    tracing::info!(
+        "Built routes for {} tenants in {:?}",
+        app_config.tenants.len(),
+        routes_started.elapsed()
+    );
 
     // Start the server
     let host: std::net::IpAddr = app_config.server.host.parse().unwrap_or_else(|_| {
@@ -333,8 +303,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
         println!("    📋 Schemas: {}/Schemas", tenant.path);
         println!("    🏷️ ResourceTypes: {}/ResourceTypes", tenant.path);
-        println!("    👥 Users: {}/Users", tenant.path);
-        println!("    👥 Groups: {}/Groups", tenant.path);
+        println!("    👥 Users: {}/{}", tenant.path, tenant.users_path());
+        println!("    👥 Groups: {}/{}", tenant.path, tenant.groups_path());
 
         // Display custom endpoints if any
         if !tenant.custom_endpoints.is_empty() {
@@ -361,6 +331,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Perform cleanup
     println!("🧹 Performing cleanup...");
 
+    // Flush the spans still waiting in the batch exporter
+    #[cfg(feature = "otel")]
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("Failed to flush OpenTelemetry spans: {}", e);
+        }
+    }
+
     // Note: Backend cleanup would be implemented here if needed
     // Currently SQLite/PostgreSQL connections are automatically cleaned up
     // when the connection pools are dropped
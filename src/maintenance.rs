@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::auth::TenantInfo;
+use crate::config::AppConfig;
+
+/// Seconds clients are asked to wait before retrying a rejected write
+pub const READ_ONLY_RETRY_AFTER_SECS: u64 = 120;
+
+/// Retry-After for 503s raised by the backend (e.g. a busy SQLite database)
+pub const BACKEND_BUSY_RETRY_AFTER_SECS: u64 = 1;
+
+/// Reject write requests for tenants in read-only maintenance mode
+///
+/// Must run after `auth_middleware` so the resolved `TenantInfo` is available.
+/// GET/HEAD/OPTIONS (resources, discovery endpoints and custom endpoints) and
+/// POST `.search` queries always pass through. The flag is read on every
+/// request so toggling it takes effect immediately.
+///
+/// 503 responses produced further down (backend contention) that do not carry
+/// a Retry-After header get a short one added here.
+pub async fn read_only_middleware(
+    State(app_config): State<Arc<AppConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_write = !matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) && !request.uri().path().ends_with("/.search");
+    let read_only = is_write
+        && request
+            .extensions()
+            .get::<TenantInfo>()
+            .is_some_and(|tenant_info| app_config.is_read_only(tenant_info.tenant_id));
+
+    if !read_only {
+        let mut response = next.run(request).await;
+        if response.status() == StatusCode::SERVICE_UNAVAILABLE
+            && !response.headers().contains_key(header::RETRY_AFTER)
+        {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(BACKEND_BUSY_RETRY_AFTER_SECS),
+            );
+        }
+        return response;
+    }
+
+    let mut response = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "detail": "tenant is in read-only maintenance mode",
+            "status": "503"
+        })),
+    )
+        .into_response();
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(READ_ONLY_RETRY_AFTER_SECS),
+    );
+    response
+}
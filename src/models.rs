@@ -158,6 +158,11 @@ impl Clone for Group {
     }
 }
 
+/// SCIM ListResponse
+///
+/// Fields serialize in declaration order, which follows RFC 7644 Section 3.4.2
+/// (`schemas` first). Build list responses from this struct rather than `json!`,
+/// whose map sorts keys alphabetically.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScimListResponse {
     pub schemas: Vec<String>,
@@ -167,6 +172,12 @@ pub struct ScimListResponse {
     pub start_index: Option<i64>,
     #[serde(rename = "itemsPerPage", skip_serializing_if = "Option::is_none")]
     pub items_per_page: Option<i64>,
+    /// Non-standard hint for the next page, set only when the tenant enables
+    /// `include_next_start_index` and more results remain
+    #[serde(rename = "nextStartIndex", skip_serializing_if = "Option::is_none")]
+    pub next_start_index: Option<i64>,
+    /// Always serialized, as `[]` when nothing matched; some clients
+    /// require the key even then
     #[serde(rename = "Resources")]
     pub resources: Vec<serde_json::Value>,
 }
@@ -4,7 +4,79 @@ use crate::error::{AppError, AppResult};
 pub use crate::parser::filter_operator::FilterOperator;
 
 /// Custom SCIM filter parser that handles quoted strings and complex expressions
+///
+/// Precedence from loosest to tightest is `or`, `and`, `not`, so
+/// `not emails pr and active eq true` is `(not emails pr) and active eq true`.
+/// Parentheses and value path brackets group explicitly.
+///
+/// Unterminated quotes and unbalanced parentheses or brackets are rejected
+/// up front, naming the position of the offending character.
 pub fn parse_filter(filter_str: &str) -> AppResult<FilterOperator> {
+    check_balanced(filter_str)?;
+    parse_expression(filter_str)
+}
+
+/// Reject unterminated quoted strings and unbalanced `()` or `[]`
+///
+/// Positions are 1-based character offsets into `filter_str`. Backslash
+/// escapes are skipped as in the rest of the parser.
+fn check_balanced(filter_str: &str) -> AppResult<()> {
+    let error = |reason: String| {
+        Err(AppError::FilterParse(format!(
+            "{} in filter: {}",
+            reason, filter_str
+        )))
+    };
+    let mut open: Vec<(char, usize)> = Vec::new();
+    let mut quote_start = None;
+    let mut escape_next = false;
+
+    for (i, ch) in filter_str.chars().enumerate() {
+        let position = i + 1;
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+
+        match ch {
+            '\\' => escape_next = true,
+            '"' => quote_start = quote_start.xor(Some(position)),
+            _ if quote_start.is_some() => {}
+            '(' | '[' => open.push((ch, position)),
+            ')' | ']' => {
+                let opener = if ch == ')' { '(' } else { '[' };
+                match open.pop() {
+                    Some((open_ch, _)) if open_ch == opener => {}
+                    Some((open_ch, open_position)) => {
+                        return error(format!(
+                            "'{}' at position {} does not close '{}' at position {}",
+                            ch, position, open_ch, open_position
+                        ))
+                    }
+                    None => return error(format!("Unexpected '{}' at position {}", ch, position)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = quote_start {
+        return error(format!(
+            "Unterminated quoted string starting at position {}",
+            start
+        ));
+    }
+    if let Some((open_ch, open_position)) = open.pop() {
+        return error(format!(
+            "Unclosed '{}' at position {}",
+            open_ch, open_position
+        ));
+    }
+    Ok(())
+}
+
+/// Parse a filter already checked by [`check_balanced`]
+fn parse_expression(filter_str: &str) -> AppResult<FilterOperator> {
     let trimmed = filter_str.trim();
     // eprintln!("DEBUG parser: input filter_str='{}'", filter_str);
 
@@ -54,45 +126,112 @@ pub fn parse_filter(filter_str: &str) -> AppResult<FilterOperator> {
         if depth == 0 && first_paren_closes_at_end {
             let inner = &trimmed[1..trimmed.len() - 1];
             // eprintln!("DEBUG: Removing outer parentheses, inner='{}'", inner);
-            return parse_filter(inner);
+            return parse_expression(inner);
         }
     }
 
-    // Check for NOT operator first (highest precedence for unary operators)
-    if trimmed.to_lowercase().starts_with("not ") {
-        let inner_expr = &trimmed[4..].trim(); // Skip "not " and trim
-        let inner_filter = parse_filter(inner_expr)?;
-        return Ok(FilterOperator::Not(Box::new(inner_filter)));
+    // OR has the lowest precedence, so it is split off first
+    if let Some((left, right)) = split_top_level(trimmed, "or") {
+        return Ok(FilterOperator::Or(
+            Box::new(parse_expression(left)?),
+            Box::new(parse_expression(right)?),
+        ));
     }
 
-    // Then check for logical operators (AND/OR) at the top level
-    if let Some(logical_op) = find_logical_operator(trimmed)? {
-        return Ok(logical_op);
+    if let Some((left, right)) = split_top_level(trimmed, "and") {
+        return Ok(FilterOperator::And(
+            Box::new(parse_expression(left)?),
+            Box::new(parse_expression(right)?),
+        ));
+    }
+
+    // NOT binds tightest: with no top-level and/or left, it negates the rest
+    if let Some(operand) = strip_not(trimmed) {
+        return Ok(FilterOperator::Not(Box::new(parse_expression(operand)?)));
     }
 
     // Handle complex filter expressions like emails[value eq "alice@example.com"]
-    if let Some(bracket_pos) = trimmed.find('[') {
-        if let Some(bracket_end) = trimmed.rfind(']') {
-            let attr = &trimmed[..bracket_pos];
-            let filter_expr = &trimmed[bracket_pos + 1..bracket_end];
-            eprintln!(
-                "DEBUG parser: complex filter - attr='{}', filter_expr='{}'",
-                attr, filter_expr
-            );
+    if let Some(value_path) = parse_value_path(trimmed)? {
+        return Ok(value_path);
+    }
 
-            // Parse the inner filter expression
-            let inner_filter = parse_simple_filter(filter_expr)?;
+    // Handle simple filter expressions
+    parse_simple_filter(trimmed)
+}
 
-            // Return as Complex variant to match kanidm structure
-            return Ok(FilterOperator::Complex(
-                attr.to_string(),
-                Box::new(inner_filter),
-            ));
+/// Parse a value path such as `emails[type eq "work" and value co "@corp.com"]`
+///
+/// A trailing sub-attribute comparison (`addresses[primary eq true].locality eq "Berlin"`)
+/// is folded into the bracketed filter so every condition applies to the same element.
+/// Returns None when the expression has no unquoted '['.
+fn parse_value_path(filter_str: &str) -> AppResult<Option<FilterOperator>> {
+    let mut in_quotes = false;
+    let mut escape_next = false;
+    let mut depth = 0;
+    let mut bracket_start = None;
+    let mut bracket_end = None;
+
+    for (i, ch) in filter_str.char_indices() {
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+
+        match ch {
+            '\\' => escape_next = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => {
+                if depth == 0 && bracket_start.is_none() {
+                    bracket_start = Some(i);
+                }
+                depth += 1;
+            }
+            ']' if !in_quotes && depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    bracket_end = Some(i);
+                    break;
+                }
+            }
+            _ => {}
         }
     }
 
-    // Handle simple filter expressions
-    parse_simple_filter(trimmed)
+    let Some(bracket_start) = bracket_start else {
+        return Ok(None);
+    };
+    let bracket_end = bracket_end
+        .ok_or_else(|| AppError::FilterParse(format!("Missing ']' in filter: {}", filter_str)))?;
+
+    let attr = filter_str[..bracket_start].trim();
+    if attr.is_empty() || attr.contains(char::is_whitespace) {
+        return Err(AppError::FilterParse(format!(
+            "Invalid value path in filter: {}",
+            filter_str
+        )));
+    }
+
+    // Inner filters may combine conditions with and/or/not
+    let mut inner_filter = parse_expression(&filter_str[bracket_start + 1..bracket_end])?;
+
+    let rest = filter_str[bracket_end + 1..].trim();
+    if !rest.is_empty() {
+        let Some(sub_filter) = rest.strip_prefix('.') else {
+            return Err(AppError::FilterParse(format!(
+                "Unexpected expression after value path: {}",
+                rest
+            )));
+        };
+        inner_filter = FilterOperator::And(
+            Box::new(inner_filter),
+            Box::new(parse_simple_filter(sub_filter)?),
+        );
+    }
+
+    Ok(Some(FilterOperator::Complex(
+        attr.to_string(),
+        Box::new(inner_filter),
+    )))
 }
 
 /// Parse simple SCIM filter expressions (attr op value)
@@ -205,8 +344,13 @@ fn find_operator_position(text: &str, operator: &str) -> Option<usize> {
 fn parse_filter_value(value_str: &str) -> AppResult<Value> {
     let trimmed = value_str.trim();
 
-    // Handle quoted strings
+    // Handle quoted strings. They are JSON strings (RFC 7644 3.4.2.2), so
+    // `\"`, `\\` and `\u0040` escapes are decoded; anything else is
+    // taken verbatim with only `\"` unescaped
     if trimmed.starts_with('"') && trimmed.ends_with('"') && trimmed.len() >= 2 {
+        if let Ok(Value::String(decoded)) = serde_json::from_str(trimmed) {
+            return Ok(Value::String(decoded));
+        }
         let unquoted = &trimmed[1..trimmed.len() - 1];
         // Unescape any escaped quotes
         let unescaped = unquoted.replace("\\\"", "\"");
@@ -242,113 +386,65 @@ fn parse_filter_value(value_str: &str) -> AppResult<Value> {
     Ok(Value::String(trimmed.to_string()))
 }
 
-/// Find and parse logical operators (AND/OR) at the top level of the expression
-/// Returns None if no logical operators are found at the top level
-fn find_logical_operator(filter_str: &str) -> AppResult<Option<FilterOperator>> {
-    // Look for " and " and " or " operators, but not inside quotes or parentheses
+/// Split `filter_str` at the first top-level occurrence of a logical keyword
+///
+/// Keywords inside quotes, parentheses or value path brackets are skipped.
+/// Returns None if the keyword does not occur at the top level.
+fn split_top_level<'a>(filter_str: &'a str, keyword: &str) -> Option<(&'a str, &'a str)> {
+    let bytes = filter_str.as_bytes();
     let mut depth = 0;
     let mut in_quotes = false;
     let mut escape_next = false;
-    let chars: Vec<char> = filter_str.chars().collect();
-
-    // First pass: find OR operators (lower precedence)
-    for i in 0..chars.len() {
-        let ch = chars[i];
 
+    for i in 0..bytes.len() {
         if escape_next {
             escape_next = false;
             continue;
         }
 
-        if ch == '\\' {
-            escape_next = true;
-            continue;
-        }
-
-        if ch == '"' && !escape_next {
-            in_quotes = !in_quotes;
-            continue;
-        }
-
-        if !in_quotes {
-            if ch == '(' {
-                depth += 1;
-            } else if ch == ')' {
-                depth -= 1;
-            }
-
-            // Only look for operators at the top level (depth 0)
-            if depth == 0 && i + 4 < chars.len() {
-                // Check for " or " (with spaces)
-                if chars[i] == ' '
-                    && chars[i + 1] == 'o'
-                    && chars[i + 2] == 'r'
-                    && chars[i + 3] == ' '
-                {
-                    let left_expr = filter_str[..i].trim();
-                    let right_expr = filter_str[i + 4..].trim();
-
-                    let left = parse_filter(left_expr)?;
-                    let right = parse_filter(right_expr)?;
-
-                    return Ok(Some(FilterOperator::Or(Box::new(left), Box::new(right))));
-                }
+        match bytes[i] {
+            b'\\' => escape_next = true,
+            b'"' => in_quotes = !in_quotes,
+            _ if in_quotes => {}
+            // Brackets of value paths nest like parentheses
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth -= 1,
+            _ if depth == 0 && is_keyword_at(bytes, i, keyword) => {
+                let left = filter_str[..i].trim();
+                let right = filter_str[i + keyword.len()..].trim();
+                return Some((left, right));
             }
+            _ => {}
         }
     }
 
-    // Second pass: find AND operators (higher precedence)
-    depth = 0;
-    in_quotes = false;
-    escape_next = false;
-
-    for i in 0..chars.len() {
-        let ch = chars[i];
-
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        if ch == '\\' {
-            escape_next = true;
-            continue;
-        }
-
-        if ch == '"' && !escape_next {
-            in_quotes = !in_quotes;
-            continue;
-        }
+    None
+}
 
-        if !in_quotes {
-            if ch == '(' {
-                depth += 1;
-            } else if ch == ')' {
-                depth -= 1;
-            }
+/// Whether a binary logical keyword (any case) starts at byte `i`
+///
+/// The keyword must follow whitespace or ')' and precede whitespace or '(',
+/// so attribute names such as `order` never match `or`.
+fn is_keyword_at(bytes: &[u8], i: usize, keyword: &str) -> bool {
+    let end = i + keyword.len();
+    i > 0
+        && end < bytes.len()
+        && bytes[i..end].eq_ignore_ascii_case(keyword.as_bytes())
+        && (bytes[i - 1].is_ascii_whitespace() || bytes[i - 1] == b')')
+        && (bytes[end].is_ascii_whitespace() || bytes[end] == b'(')
+}
 
-            // Only look for operators at the top level (depth 0)
-            if depth == 0 && i + 5 < chars.len() {
-                // Check for " and " (with spaces)
-                if chars[i] == ' '
-                    && chars[i + 1] == 'a'
-                    && chars[i + 2] == 'n'
-                    && chars[i + 3] == 'd'
-                    && chars[i + 4] == ' '
-                {
-                    let left_expr = filter_str[..i].trim();
-                    let right_expr = filter_str[i + 5..].trim();
-
-                    let left = parse_filter(left_expr)?;
-                    let right = parse_filter(right_expr)?;
-
-                    return Ok(Some(FilterOperator::And(Box::new(left), Box::new(right))));
-                }
-            }
-        }
+/// Strip a leading `not` (any case), with or without a space before '('
+fn strip_not(filter_str: &str) -> Option<&str> {
+    let bytes = filter_str.as_bytes();
+    if bytes.len() > 3
+        && bytes[..3].eq_ignore_ascii_case(b"not")
+        && (bytes[3].is_ascii_whitespace() || bytes[3] == b'(')
+    {
+        Some(filter_str[3..].trim())
+    } else {
+        None
     }
-
-    Ok(None)
 }
 
 #[cfg(test)]
@@ -674,4 +770,321 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn test_complex_filter_with_logical_operators() {
+        let result =
+            parse_filter("emails[type eq \"work\" and value co \"@corp.com\"] and active eq true")
+                .unwrap();
+        assert_eq!(
+            result,
+            FilterOperator::And(
+                Box::new(FilterOperator::Complex(
+                    "emails".to_string(),
+                    Box::new(FilterOperator::And(
+                        Box::new(FilterOperator::Equal("type".to_string(), json!("work"))),
+                        Box::new(FilterOperator::Contains(
+                            "value".to_string(),
+                            json!("@corp.com")
+                        ))
+                    ))
+                )),
+                Box::new(FilterOperator::Equal("active".to_string(), json!(true)))
+            )
+        );
+    }
+
+    #[test]
+    fn test_complex_filter_with_sub_attribute() {
+        let result = parse_filter("addresses[primary eq true].locality eq \"Berlin\"").unwrap();
+        assert_eq!(
+            result,
+            FilterOperator::Complex(
+                "addresses".to_string(),
+                Box::new(FilterOperator::And(
+                    Box::new(FilterOperator::Equal("primary".to_string(), json!(true))),
+                    Box::new(FilterOperator::Equal(
+                        "locality".to_string(),
+                        json!("Berlin")
+                    ))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_bracket_inside_quoted_value() {
+        let result = parse_filter("displayName eq \"Team [A]\"").unwrap();
+        assert_eq!(
+            result,
+            FilterOperator::Equal("displayName".to_string(), json!("Team [A]"))
+        );
+    }
+
+    // Filters as they arrive after the query string is URL-decoded once, e.g.
+    // `userName%20eq%20%22alice%40example.com%22`
+    #[test]
+    fn test_special_characters_in_quoted_values() {
+        let cases = [
+            ("userName eq \"alice@example.com\"", "alice@example.com"),
+            // %2540 decodes once to a literal %40, which is kept as is
+            ("userName eq \"alice%40example.com\"", "alice%40example.com"),
+            ("userName eq \"100%\"", "100%"),
+            (
+                "userName eq \"alice+tag@example.com\"",
+                "alice+tag@example.com",
+            ),
+            ("displayName eq \"Barbara Jensen\"", "Barbara Jensen"),
+            ("displayName eq \"say \\\"hi\\\"\"", "say \"hi\""),
+            ("displayName eq \"Team (A)\"", "Team (A)"),
+            ("displayName eq \")\"", ")"),
+            ("displayName eq \"((\"", "(("),
+            ("displayName eq \"C:\\\\temp\"", "C:\\temp"),
+            ("displayName eq \"\\u0040\"", "@"),
+        ];
+        for (filter, expected) in cases {
+            assert_eq!(
+                parse_filter(filter).unwrap(),
+                FilterOperator::Equal(
+                    filter.split_whitespace().next().unwrap().to_string(),
+                    json!(expected)
+                ),
+                "{}",
+                filter
+            );
+        }
+    }
+
+    #[test]
+    fn test_special_characters_with_grouping() {
+        let result =
+            parse_filter("(userName eq \"a(b)@example.com\") or displayName co \") or (\"")
+                .unwrap();
+        assert_eq!(
+            result,
+            FilterOperator::Or(
+                Box::new(FilterOperator::Equal(
+                    "userName".to_string(),
+                    json!("a(b)@example.com")
+                )),
+                Box::new(FilterOperator::Contains(
+                    "displayName".to_string(),
+                    json!(") or (")
+                )),
+            )
+        );
+
+        let result =
+            parse_filter("emails[value ew \"+1@example.com\" and type eq \"work\"]").unwrap();
+        assert_eq!(
+            result,
+            FilterOperator::Complex(
+                "emails".to_string(),
+                Box::new(FilterOperator::And(
+                    Box::new(FilterOperator::EndsWith(
+                        "value".to_string(),
+                        json!("+1@example.com")
+                    )),
+                    Box::new(FilterOperator::Equal("type".to_string(), json!("work"))),
+                )),
+            )
+        );
+    }
+
+    fn parse_error(filter: &str) -> String {
+        match parse_filter(filter) {
+            Err(AppError::FilterParse(message)) => message,
+            other => panic!("{:?} parsed as {:?}", filter, other),
+        }
+    }
+
+    #[test]
+    fn test_unbalanced_filters_are_rejected_with_position() {
+        for (filter, expected) in [
+            (
+                "userName eq \"alice",
+                "Unterminated quoted string starting at position 13",
+            ),
+            ("emails[value eq \"x\"", "Unclosed '[' at position 7"),
+            (
+                "(userName eq \"a\" or title pr",
+                "Unclosed '(' at position 1",
+            ),
+            ("userName eq \"a\")", "Unexpected ')' at position 16"),
+            (
+                "emails[value eq \"x\")",
+                "')' at position 20 does not close '[' at position 7",
+            ),
+            (
+                "(emails[type eq \"work\")]",
+                "')' at position 23 does not close '[' at position 8",
+            ),
+            (
+                "displayName eq \"Müller",
+                "Unterminated quoted string starting at position 16",
+            ),
+        ] {
+            let message = parse_error(filter);
+            assert!(message.starts_with(expected), "{:?}: {}", filter, message);
+            assert!(message.ends_with(filter), "{:?}: {}", filter, message);
+        }
+    }
+
+    #[test]
+    fn test_quoted_and_escaped_delimiters_are_balanced() {
+        for filter in [
+            "displayName eq \"Team (A\"",
+            "displayName eq \"]\"",
+            "displayName eq \"say \\\"hi\\\" (\"",
+            "emails[value eq \"x]\"] and (title pr)",
+        ] {
+            assert!(parse_filter(filter).is_ok(), "{:?}", filter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod precedence_tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eq(attr: &str, value: Value) -> FilterOperator {
+        FilterOperator::Equal(attr.to_string(), value)
+    }
+
+    fn pr(attr: &str) -> FilterOperator {
+        FilterOperator::Present(attr.to_string())
+    }
+
+    fn and(left: FilterOperator, right: FilterOperator) -> FilterOperator {
+        FilterOperator::And(Box::new(left), Box::new(right))
+    }
+
+    fn or(left: FilterOperator, right: FilterOperator) -> FilterOperator {
+        FilterOperator::Or(Box::new(left), Box::new(right))
+    }
+
+    fn not(operand: FilterOperator) -> FilterOperator {
+        FilterOperator::Not(Box::new(operand))
+    }
+
+    fn complex(attr: &str, filter: FilterOperator) -> FilterOperator {
+        FilterOperator::Complex(attr.to_string(), Box::new(filter))
+    }
+
+    fn assert_parses(filter: &str, expected: FilterOperator) {
+        assert_eq!(parse_filter(filter).unwrap(), expected, "{}", filter);
+    }
+
+    #[test]
+    fn test_not_binds_tighter_than_and() {
+        assert_parses(
+            "not emails pr and active eq true",
+            and(not(pr("emails")), eq("active", json!(true))),
+        );
+        assert_parses(
+            "active eq true and not emails pr",
+            and(eq("active", json!(true)), not(pr("emails"))),
+        );
+        assert_parses(
+            "not title pr and not emails pr",
+            and(not(pr("title")), not(pr("emails"))),
+        );
+    }
+
+    #[test]
+    fn test_not_binds_tighter_than_or() {
+        assert_parses(
+            "not emails pr or active eq true",
+            or(not(pr("emails")), eq("active", json!(true))),
+        );
+        assert_parses(
+            "NOT (title pr) OR emails pr",
+            or(not(pr("title")), pr("emails")),
+        );
+    }
+
+    #[test]
+    fn test_parentheses_widen_not() {
+        assert_parses(
+            "not (emails pr and active eq true)",
+            not(and(pr("emails"), eq("active", json!(true)))),
+        );
+        assert_parses(
+            "not(emails pr or title pr)",
+            not(or(pr("emails"), pr("title"))),
+        );
+    }
+
+    #[test]
+    fn test_not_inside_mixed_and_or() {
+        assert_parses(
+            "title pr and not(emails pr) or active eq true",
+            or(
+                and(pr("title"), not(pr("emails"))),
+                eq("active", json!(true)),
+            ),
+        );
+        assert_parses(
+            "title pr or emails pr and not active eq true",
+            or(
+                pr("title"),
+                and(pr("emails"), not(eq("active", json!(true)))),
+            ),
+        );
+        assert_parses(
+            "(title pr or emails pr) and not active eq true",
+            and(
+                or(pr("title"), pr("emails")),
+                not(eq("active", json!(true))),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_double_not() {
+        assert_parses("not not emails pr", not(not(pr("emails"))));
+    }
+
+    #[test]
+    fn test_not_with_value_paths() {
+        assert_parses(
+            "not emails[type eq \"work\"] and active eq true",
+            and(
+                not(complex("emails", eq("type", json!("work")))),
+                eq("active", json!(true)),
+            ),
+        );
+        assert_parses(
+            "emails[not (type eq \"work\") and primary eq true]",
+            complex(
+                "emails",
+                and(not(eq("type", json!("work"))), eq("primary", json!(true))),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_keywords_only_match_whole_words() {
+        // `notes` is an attribute, not `not es`
+        assert_parses(
+            "notes eq \"x\" and not title pr",
+            and(eq("notes", json!("x")), not(pr("title"))),
+        );
+        assert_parses(
+            "displayName eq \"not a or b and c\" and not title pr",
+            and(
+                eq("displayName", json!("not a or b and c")),
+                not(pr("title")),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_non_ascii_before_keyword() {
+        assert_parses(
+            "displayName eq \"Müller\" or not title pr",
+            or(eq("displayName", json!("Müller")), not(pr("title"))),
+        );
+    }
 }
@@ -1,7 +1,13 @@
 use crate::config::CompatibilityConfig;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, ValidationIssue};
+use crate::models::ScimPatchOp;
 use crate::parser::filter_operator::FilterOperator;
 use crate::parser::filter_parser::parse_filter;
+use crate::parser::ResourceType;
+use crate::schema::{
+    find_attribute, Mutability, GROUP_SCHEMA, SCHEMA_REGISTRY, SCIM_SCHEMA_CORE_GROUP,
+    SCIM_SCHEMA_CORE_USER, USER_SCHEMA,
+};
 use serde_json::Value;
 
 /// SCIM PATH parser and processor according to RFC 7644
@@ -27,6 +33,10 @@ pub struct ScimFilter {
 impl ScimPath {
     /// Parse a SCIM path according to RFC 7644 PATH ABNF
     pub fn parse(path: &str) -> AppResult<Self> {
+        if let Some((schema_urn, attr_path)) = split_registered_schema(path) {
+            return Self::parse_schema_qualified(path, schema_urn, attr_path);
+        }
+
         if path.contains('[') {
             // This is a valuePath with filter
             Self::parse_value_path(path)
@@ -36,6 +46,46 @@ impl ScimPath {
         }
     }
 
+    /// Parse a path that starts with a registered schema URN
+    ///
+    /// Core schema attributes live at the top level of the resource, so the
+    /// URN is dropped. Extension attributes live in the object keyed by the
+    /// URN, so it becomes the first segment; the URN on its own addresses the
+    /// whole extension object.
+    fn parse_schema_qualified(
+        path: &str,
+        schema_urn: &'static str,
+        attr_path: Option<&str>,
+    ) -> AppResult<Self> {
+        let core = schema_urn == SCIM_SCHEMA_CORE_USER || schema_urn == SCIM_SCHEMA_CORE_GROUP;
+        match (core, attr_path) {
+            (_, Some("")) | (true, None) => Err(AppError::BadRequest(format!(
+                "Invalid schema-qualified attribute path: {}",
+                path
+            ))),
+            (true, Some(attr_path)) => Self::parse(attr_path),
+            (false, None) => Ok(ScimPath::AttrPath(vec![schema_urn.to_string()])),
+            (false, Some(attr_path)) => match Self::parse(attr_path)? {
+                ScimPath::AttrPath(parts) => Ok(ScimPath::AttrPath(
+                    std::iter::once(schema_urn.to_string())
+                        .chain(parts)
+                        .collect(),
+                )),
+                ScimPath::ValuePath {
+                    attr_path,
+                    filter,
+                    sub_attr,
+                } => Ok(ScimPath::ValuePath {
+                    attr_path: std::iter::once(schema_urn.to_string())
+                        .chain(attr_path)
+                        .collect(),
+                    filter,
+                    sub_attr,
+                }),
+            },
+        }
+    }
+
     fn parse_attr_path(path: &str) -> AppResult<Self> {
         // Handle schema-qualified attributes like "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department"
         // or "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager.value"
@@ -145,6 +195,58 @@ impl ScimPath {
         op: &str,
         value: &Value,
         compatibility: &CompatibilityConfig,
+    ) -> AppResult<()> {
+        self.apply_to_target(user_json, op, value, compatibility)?;
+
+        // Keep `schemas` in step with the extension object the path went into
+        if let Some(schema_urn) = self.extension_schema() {
+            sync_extension_schema(user_json, schema_urn);
+        }
+        Ok(())
+    }
+
+    /// Schema URN heading the path when it addresses an extension attribute
+    fn extension_schema(&self) -> Option<&str> {
+        let first = match self {
+            ScimPath::AttrPath(path) => path.first(),
+            ScimPath::ValuePath { attr_path, .. } => attr_path.first(),
+        };
+        first.map(String::as_str).filter(|s| s.contains(':'))
+    }
+
+    /// Mutability of the attribute the path addresses
+    ///
+    /// A value filter does not change the attribute: `members[value eq "x"].value`
+    /// addresses `members.value`. `None` when the schemas do not define it.
+    pub fn mutability(&self, resource_type: ResourceType) -> Option<Mutability> {
+        let segments: Vec<&str> = match self {
+            ScimPath::AttrPath(path) => path.iter().map(String::as_str).collect(),
+            ScimPath::ValuePath {
+                attr_path,
+                sub_attr,
+                ..
+            } => attr_path
+                .iter()
+                .map(String::as_str)
+                .chain(sub_attr.as_deref())
+                .collect(),
+        };
+        let (schema, segments) = match self.extension_schema() {
+            Some(schema_urn) => (*SCHEMA_REGISTRY.get(schema_urn)?, &segments[1..]),
+            None => match resource_type {
+                ResourceType::User => (&*USER_SCHEMA, &segments[..]),
+                ResourceType::Group => (&*GROUP_SCHEMA, &segments[..]),
+            },
+        };
+        find_attribute(schema, &segments.join(".")).map(|attr| attr.mutability.clone())
+    }
+
+    fn apply_to_target(
+        &self,
+        user_json: &mut Value,
+        op: &str,
+        value: &Value,
+        compatibility: &CompatibilityConfig,
     ) -> AppResult<()> {
         match self {
             ScimPath::AttrPath(path) => self.apply_attr_path_operation_with_compatibility(
@@ -170,24 +272,6 @@ impl ScimPath {
         }
     }
 
-    fn apply_attr_path_operation(
-        &self,
-        user_json: &mut Value,
-        path: &[String],
-        op: &str,
-        value: &Value,
-    ) -> AppResult<()> {
-        // Use default compatibility config for backward compatibility
-        let default_config = CompatibilityConfig::default();
-        self.apply_attr_path_operation_with_compatibility(
-            user_json,
-            path,
-            op,
-            value,
-            &default_config,
-        )
-    }
-
     fn apply_attr_path_operation_with_compatibility(
         &self,
         user_json: &mut Value,
@@ -200,32 +284,7 @@ impl ScimPath {
             return Err(AppError::BadRequest("Empty attribute path".to_string()));
         }
 
-        // Check for schema updates first
-        let final_key = &path[path.len() - 1];
-        let needs_schema_update = final_key.starts_with("urn:ietf:params:scim:schemas:");
-
-        // Navigate to the parent and apply operation
-        let final_key_name = final_key.clone();
-        self.navigate_and_apply_with_compatibility(user_json, path, op, value, compatibility)?;
-
-        // Handle schema updates for fully qualified names after modifying the tree
-        if needs_schema_update && op != "remove" {
-            self.update_schemas_attribute(user_json, &final_key_name)?;
-        }
-
-        Ok(())
-    }
-
-    fn navigate_and_apply(
-        &self,
-        user_json: &mut Value,
-        path: &[String],
-        op: &str,
-        value: &Value,
-    ) -> AppResult<()> {
-        // Use default compatibility config for backward compatibility
-        let default_config = CompatibilityConfig::default();
-        self.navigate_and_apply_with_compatibility(user_json, path, op, value, &default_config)
+        self.navigate_and_apply_with_compatibility(user_json, path, op, value, compatibility)
     }
 
     fn navigate_and_apply_with_compatibility(
@@ -260,6 +319,14 @@ impl ScimPath {
         let final_key = &path[path.len() - 1];
         match op {
             "add" => {
+                // A single complex value added to a multi-valued attribute is one more element
+                let value = &match value {
+                    Value::Object(_) if is_multi_valued_attribute(final_key) => {
+                        Value::Array(vec![value.clone()])
+                    }
+                    _ => value.clone(),
+                };
+
                 if let Value::Object(obj) = current {
                     // For add operation, check if target is array and append to it
                     if let Some(existing) = obj.get_mut(final_key) {
@@ -332,17 +399,16 @@ impl ScimPath {
                             }
 
                             // Handle special empty value pattern [{"value":""}] - remove attribute entirely
+                            // (only when the compatibility option is enabled; otherwise the empty value is stored)
                             if arr.len() == 1 {
                                 if let Value::Object(ref item) = arr[0] {
                                     if item.len() == 1
                                         && item.get("value") == Some(&Value::String("".to_string()))
+                                        && compatibility.support_patch_replace_empty_value
                                     {
-                                        if compatibility.support_patch_replace_empty_value {
-                                            // Remove the attribute entirely for this special pattern
-                                            obj.remove(final_key);
-                                            return Ok(());
-                                        }
-                                        // If not supported, continue with normal processing (will store the empty value)
+                                        // Remove the attribute entirely for this special pattern
+                                        obj.remove(final_key);
+                                        return Ok(());
                                     }
                                 }
                             }
@@ -459,28 +525,7 @@ impl ScimPath {
         false
     }
 
-    fn apply_value_path_operation(
-        &self,
-        user_json: &mut Value,
-        attr_path: &[String],
-        filter: &ScimFilter,
-        sub_attr: Option<&str>,
-        op: &str,
-        value: &Value,
-    ) -> AppResult<()> {
-        // Use default compatibility config for backward compatibility
-        let default_config = CompatibilityConfig::default();
-        self.apply_value_path_operation_with_compatibility(
-            user_json,
-            attr_path,
-            filter,
-            sub_attr,
-            op,
-            value,
-            &default_config,
-        )
-    }
-
+    #[allow(clippy::too_many_arguments)]
     fn apply_value_path_operation_with_compatibility(
         &self,
         user_json: &mut Value,
@@ -613,58 +658,179 @@ impl ScimPath {
 
         Ok(())
     }
+}
 
-    fn update_schemas_attribute(
-        &self,
-        user_json: &mut Value,
-        fully_qualified_attr: &str,
-    ) -> AppResult<()> {
-        // Extract schema URN from fully qualified attribute name
-        // Example: "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber"
-        // -> "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User"
-
-        let parts: Vec<&str> = fully_qualified_attr.split(':').collect();
-        if parts.len() >= 7
-            && parts[0] == "urn"
-            && parts[1] == "ietf"
-            && parts[2] == "params"
-            && parts[3] == "scim"
-            && parts[4] == "schemas"
-        {
-            // Reconstruct schema URN (everything except the last part which is the attribute name)
-            let schema_urn = parts[..parts.len() - 1].join(":");
-
-            // Add to schemas array if not already present
-            if let Value::Object(user_obj) = user_json {
-                let schemas = user_obj
-                    .entry("schemas".to_string())
-                    .or_insert(Value::Array(vec![]));
-                if let Value::Array(schemas_array) = schemas {
-                    let schema_value = Value::String(schema_urn);
-                    if !schemas_array.contains(&schema_value) {
-                        schemas_array.push(schema_value);
-                    }
+/// Registered schema URN that `path` starts with, and the attribute path after
+/// its terminating colon (`None` when the path is the URN itself)
+///
+/// URNs compare case-insensitively and the longest registered URN wins.
+fn split_registered_schema(path: &str) -> Option<(&'static str, Option<&str>)> {
+    SCHEMA_REGISTRY
+        .keys()
+        .copied()
+        .filter_map(|urn| {
+            let prefix = path.get(..urn.len())?;
+            if !prefix.eq_ignore_ascii_case(urn) {
+                return None;
+            }
+            match &path[urn.len()..] {
+                "" => Some((urn, None)),
+                rest => rest
+                    .strip_prefix(':')
+                    .map(|attr_path| (urn, Some(attr_path))),
+            }
+        })
+        .max_by_key(|(urn, _)| urn.len())
+}
+
+/// List `schema_urn` in `schemas` while its extension object has attributes;
+/// otherwise drop both the empty object and the URN
+fn sync_extension_schema(resource: &mut Value, schema_urn: &str) {
+    let Value::Object(obj) = resource else {
+        return;
+    };
+
+    let present = match obj.get(schema_urn) {
+        None | Some(Value::Null) => false,
+        Some(Value::Object(extension)) => !extension.is_empty(),
+        Some(_) => true,
+    };
+    if !present {
+        obj.remove(schema_urn);
+    }
+
+    let schemas = obj
+        .entry("schemas".to_string())
+        .or_insert(Value::Array(vec![]));
+    if let Value::Array(schemas) = schemas {
+        let listed = schemas.iter().any(|s| s.as_str() == Some(schema_urn));
+        if present && !listed {
+            schemas.push(Value::String(schema_urn.to_string()));
+        } else if !present && listed {
+            schemas.retain(|s| s.as_str() != Some(schema_urn));
+        }
+    }
+}
+
+/// Apply an `add` or `replace` operation that has no path
+///
+/// The value is a partial resource (RFC 7644 Section 3.5.2.1 and 3.5.2.3).
+/// Each member is applied as if its key were the operation's path, so a
+/// schema-qualified key such as
+/// `urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:department`
+/// lands in the extension object, and a key naming an extension merges its
+/// object attribute by attribute.
+pub fn apply_pathless_operation(
+    resource: &mut Value,
+    op: &str,
+    value: &Value,
+    compatibility: &CompatibilityConfig,
+) -> AppResult<()> {
+    if op != "add" && op != "replace" {
+        return Err(AppError::BadRequest(format!(
+            "Operation '{}' requires a path",
+            op
+        )));
+    }
+    let Value::Object(attributes) = value else {
+        return Err(AppError::BadRequest(
+            "Value must be an object when path is omitted".to_string(),
+        ));
+    };
+
+    for (key, member) in attributes {
+        let path = ScimPath::parse(key)?;
+        match (&path, member) {
+            (ScimPath::AttrPath(parts), Value::Object(extension))
+                if parts.len() == 1 && path.extension_schema().is_some() =>
+            {
+                for (attr, attr_value) in extension {
+                    ScimPath::AttrPath(vec![parts[0].clone(), attr.clone()])
+                        .apply_operation_with_compatibility(
+                            resource,
+                            op,
+                            attr_value,
+                            compatibility,
+                        )?;
                 }
             }
+            _ => path.apply_operation_with_compatibility(resource, op, member, compatibility)?,
         }
+    }
+    Ok(())
+}
 
-        Ok(())
+/// URN a PatchOp request lists in `schemas` (RFC 7644 Section 3.5.2)
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+
+/// Read a `PATCH /Users/{id}` or `PATCH /Groups/{id}` body
+///
+/// By default the body is read leniently: `Operations` is matched in any
+/// casing (Azure AD has sent `operations`), `schemas` may be left out and
+/// other top-level members are ignored. With `strict`
+/// (`compatibility.strict_patch_schema`) the member must be spelled
+/// `Operations`, `schemas` must list [`PATCH_OP_SCHEMA`] and any other member
+/// is rejected. Bodies that do not fit get 400 `invalidSyntax`.
+pub fn parse_patch_op(body: Value, strict: bool) -> AppResult<ScimPatchOp> {
+    let Value::Object(members) = body else {
+        return Err(invalid_patch_syntax(
+            "",
+            "PatchOp body must be a JSON object",
+        ));
+    };
+
+    let mut schemas = None;
+    let mut operations = None;
+    for (key, member) in members {
+        if key == "schemas" {
+            schemas = Some(member);
+        } else if key == "Operations" || (!strict && key.eq_ignore_ascii_case("Operations")) {
+            if operations.is_some() {
+                return Err(invalid_patch_syntax(
+                    &key,
+                    "Operations must be given only once",
+                ));
+            }
+            operations = Some(member);
+        } else if strict {
+            return Err(invalid_patch_syntax(
+                &key,
+                format!("Unknown PatchOp member: {}", key),
+            ));
+        }
     }
+
+    let schemas: Vec<String> = match schemas {
+        Some(schemas) => serde_json::from_value(schemas)
+            .map_err(|_| invalid_patch_syntax("schemas", "schemas must be an array of strings"))?,
+        None => Vec::new(),
+    };
+    if strict && !schemas.iter().any(|s| s == PATCH_OP_SCHEMA) {
+        return Err(invalid_patch_syntax(
+            "schemas",
+            format!("schemas must contain {}", PATCH_OP_SCHEMA),
+        ));
+    }
+
+    let Some(operations) = operations else {
+        return Err(invalid_patch_syntax("Operations", "Operations is required"));
+    };
+    let operations = serde_json::from_value(operations)
+        .map_err(|e| invalid_patch_syntax("Operations", format!("Invalid Operations: {}", e)))?;
+
+    Ok(ScimPatchOp {
+        schemas,
+        operations,
+    })
+}
+
+fn invalid_patch_syntax(path: &str, message: impl Into<String>) -> AppError {
+    AppError::Validation(vec![ValidationIssue::invalid_syntax(path, message)])
 }
 
 /// Check if an attribute is a multi-valued attribute that supports primary
 fn is_multi_valued_attribute(attr_name: &str) -> bool {
-    matches!(
-        attr_name,
-        "emails"
-            | "phoneNumbers"
-            | "addresses"
-            | "photos"
-            | "ims"
-            | "entitlements"
-            | "roles"
-            | "x509Certificates"
-    )
+    crate::schema::primary_attributes(ResourceType::User).contains(&attr_name)
 }
 
 impl ScimFilter {
@@ -870,4 +1036,199 @@ mod tests {
             _ => panic!("Expected ValuePath"),
         }
     }
+
+    #[test]
+    fn test_add_single_object_to_multi_valued_attribute_appends() {
+        let mut user = serde_json::json!({
+            "x509Certificates": [{"value": "QUJD"}]
+        });
+        ScimPath::parse("x509Certificates")
+            .unwrap()
+            .apply_operation(&mut user, "add", &serde_json::json!({"value": "REVG"}))
+            .unwrap();
+        assert_eq!(
+            user["x509Certificates"],
+            serde_json::json!([{"value": "QUJD"}, {"value": "REVG"}])
+        );
+
+        // Adding to a missing attribute still yields an array
+        let mut user = serde_json::json!({});
+        ScimPath::parse("x509Certificates")
+            .unwrap()
+            .apply_operation(&mut user, "add", &serde_json::json!({"value": "QUJD"}))
+            .unwrap();
+        assert_eq!(
+            user["x509Certificates"],
+            serde_json::json!([{"value": "QUJD"}])
+        );
+    }
+
+    #[test]
+    fn test_remove_by_value_filter_is_case_exact() {
+        let mut user = serde_json::json!({
+            "x509Certificates": [{"value": "QUJD"}, {"value": "qujd"}]
+        });
+        ScimPath::parse("x509Certificates[value eq \"QUJD\"]")
+            .unwrap()
+            .apply_operation(&mut user, "remove", &Value::Null)
+            .unwrap();
+        assert_eq!(
+            user["x509Certificates"],
+            serde_json::json!([{"value": "qujd"}])
+        );
+    }
+
+    const ENTERPRISE: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+
+    fn attr_path(path: &str) -> Vec<String> {
+        match ScimPath::parse(path).unwrap() {
+            ScimPath::AttrPath(parts) => parts,
+            _ => panic!("Expected AttrPath for {}", path),
+        }
+    }
+
+    #[test]
+    fn test_parse_schema_qualified_paths() {
+        assert_eq!(
+            attr_path(&format!("{}:manager.value", ENTERPRISE)),
+            vec![ENTERPRISE, "manager", "value"]
+        );
+        assert_eq!(attr_path(ENTERPRISE), vec![ENTERPRISE]);
+        // URNs compare case-insensitively; the stored key is the registered one
+        assert_eq!(
+            attr_path(&format!("{}:department", ENTERPRISE.to_uppercase())),
+            vec![ENTERPRISE, "department"]
+        );
+        // Core attributes are top-level
+        assert_eq!(
+            attr_path("urn:ietf:params:scim:schemas:core:2.0:User:name.givenName"),
+            vec!["name", "givenName"]
+        );
+        assert!(ScimPath::parse("urn:ietf:params:scim:schemas:core:2.0:User").is_err());
+        assert!(ScimPath::parse(&format!("{}:", ENTERPRISE)).is_err());
+    }
+
+    #[test]
+    fn test_extension_schema_follows_extension_attributes() {
+        let mut user = serde_json::json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ext"
+        });
+        let compatibility = CompatibilityConfig::default();
+
+        apply_pathless_operation(
+            &mut user,
+            "add",
+            &serde_json::json!({ format!("{}:department", ENTERPRISE): "Sales" }),
+            &compatibility,
+        )
+        .unwrap();
+        assert_eq!(user[ENTERPRISE]["department"], "Sales");
+        assert_eq!(user["schemas"][1], ENTERPRISE);
+
+        // A key naming the extension merges into it
+        apply_pathless_operation(
+            &mut user,
+            "replace",
+            &serde_json::json!({ ENTERPRISE: {"costCenter": "CC1"} }),
+            &compatibility,
+        )
+        .unwrap();
+        assert_eq!(
+            user[ENTERPRISE],
+            serde_json::json!({"department": "Sales", "costCenter": "CC1"})
+        );
+
+        for attr in ["department", "costCenter"] {
+            ScimPath::parse(&format!("{}:{}", ENTERPRISE, attr))
+                .unwrap()
+                .apply_operation(&mut user, "remove", &Value::Null)
+                .unwrap();
+        }
+        assert!(user.get(ENTERPRISE).is_none());
+        assert_eq!(
+            user["schemas"],
+            serde_json::json!(["urn:ietf:params:scim:schemas:core:2.0:User"])
+        );
+
+        assert!(
+            apply_pathless_operation(&mut user, "remove", &Value::Null, &compatibility).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_patch_op_body_shapes() {
+        use serde_json::json;
+
+        let ops = json!([{"op": "replace", "path": "displayName", "value": "x"}]);
+        // (body, accepted leniently, accepted strictly)
+        let cases = [
+            (
+                json!({"schemas": [PATCH_OP_SCHEMA], "Operations": ops}),
+                true,
+                true,
+            ),
+            (
+                json!({"schemas": [PATCH_OP_SCHEMA], "operations": ops}),
+                true,
+                false,
+            ),
+            (
+                json!({"schemas": [PATCH_OP_SCHEMA], "OPERATIONS": ops}),
+                true,
+                false,
+            ),
+            (json!({"Operations": ops}), true, false),
+            (json!({"schemas": [], "Operations": ops}), true, false),
+            (
+                json!({"schemas": ["urn:example:Other"], "Operations": ops}),
+                true,
+                false,
+            ),
+            (
+                json!({"schemas": [PATCH_OP_SCHEMA], "Operations": ops, "id": "1"}),
+                true,
+                false,
+            ),
+            (
+                json!({"schemas": [PATCH_OP_SCHEMA], "Operations": ops, "operations": ops}),
+                false,
+                false,
+            ),
+            (json!({"schemas": [PATCH_OP_SCHEMA]}), false, false),
+            (
+                json!({"schemas": PATCH_OP_SCHEMA, "Operations": ops}),
+                false,
+                false,
+            ),
+            (
+                json!({"schemas": [PATCH_OP_SCHEMA], "Operations": {}}),
+                false,
+                false,
+            ),
+            (
+                json!({"schemas": [PATCH_OP_SCHEMA], "Operations": [{"path": "x"}]}),
+                false,
+                false,
+            ),
+            (json!([ops]), false, false),
+        ];
+
+        for (body, lenient, strict) in cases {
+            for (strict_mode, accepted) in [(false, lenient), (true, strict)] {
+                match parse_patch_op(body.clone(), strict_mode) {
+                    Ok(patch) => {
+                        assert!(accepted, "accepted (strict={}): {}", strict_mode, body);
+                        assert_eq!(patch.operations.len(), 1);
+                        assert_eq!(patch.operations[0].op, "replace");
+                    }
+                    Err(AppError::Validation(issues)) => {
+                        assert!(!accepted, "rejected (strict={}): {}", strict_mode, body);
+                        assert_eq!(issues[0].scim_type, "invalidSyntax");
+                    }
+                    Err(e) => panic!("unexpected error for {}: {}", body, e),
+                }
+            }
+        }
+    }
 }
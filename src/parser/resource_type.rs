@@ -1,18 +1,25 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub enum ResourceType {
     User,
     Group,
 }
 
-impl ResourceType {}
+impl ResourceType {
+    /// Name used for `meta.resourceType`, the ResourceType `id`/`name` and
+    /// error messages, always capitalized as RFC 7643 defines it
+    pub fn scim_name(&self) -> &'static str {
+        match self {
+            ResourceType::User => "User",
+            ResourceType::Group => "Group",
+        }
+    }
+}
 
 impl fmt::Display for ResourceType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ResourceType::User => write!(f, "User"),
-            ResourceType::Group => write!(f, "Group"),
-        }
+        f.write_str(self.scim_name())
     }
 }
@@ -4,32 +4,83 @@ use argon2::{
     password_hash::SaltString, Algorithm, Argon2, Params, PasswordHash,
     PasswordHasher as Argon2PasswordHasher, PasswordVerifier, Version,
 };
+use serde::{Deserialize, Serialize};
 
-/// Argon2id password hasher with OWASP recommended settings
+/// Argon2id cost parameters (`password.argon2` in the config file)
 ///
-/// OWASP recommendations:
+/// The defaults are the OWASP recommended minimums:
 /// - Use Argon2id with a minimum configuration of 19 MiB of memory
 /// - An iteration count of 2
 /// - 1 degree of parallelism
-pub struct Argon2idHasher {
-    argon2: Argon2<'static>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Argon2Params {
+    /// Memory cost in KiB
+    #[serde(default = "default_memory_kib")]
+    pub memory_kib: u32,
+    /// Time cost (number of passes)
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    /// Degree of parallelism (lanes)
+    #[serde(default = "default_parallelism")]
+    pub parallelism: u32,
 }
 
-impl Argon2idHasher {
-    /// Create a new Argon2id hasher with OWASP recommended settings
-    pub fn new() -> Self {
-        // OWASP recommended parameters:
-        // - Memory: 19 MiB = 19 * 1024 KiB = 19456 KiB
-        // - Iterations: 2
-        // - Parallelism: 1
-        let params = Params::new(
-            19456,    // memory cost in KiB (19 MiB)
-            2,        // time cost (iterations)
-            1,        // parallelism
+fn default_memory_kib() -> u32 {
+    19456 // 19 MiB = 19 * 1024 KiB
+}
+
+fn default_iterations() -> u32 {
+    2
+}
+
+fn default_parallelism() -> u32 {
+    1
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Self {
+            memory_kib: default_memory_kib(),
+            iterations: default_iterations(),
+            parallelism: default_parallelism(),
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Check the parameters against the limits of the argon2 crate
+    pub fn validate(&self) -> Result<(), String> {
+        self.to_params().map(|_| ())
+    }
+
+    fn to_params(self) -> Result<Params, String> {
+        Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
             Some(32), // output length
         )
-        .expect("Invalid Argon2 parameters");
+        .map_err(|e| {
+            format!(
+                "Invalid Argon2 parameters (memory_kib={}, iterations={}, parallelism={}): {}",
+                self.memory_kib, self.iterations, self.parallelism, e
+            )
+        })
+    }
+}
+
+/// Argon2id password hasher, OWASP recommended settings by default
+pub struct Argon2idHasher {
+    argon2: Argon2<'static>,
+}
 
+impl Argon2idHasher {
+    /// Create a new Argon2id hasher with the given cost parameters
+    ///
+    /// Panics on parameters rejected by [`Argon2Params::validate`]; configured
+    /// values are validated when the config file is loaded.
+    pub fn new(params: Argon2Params) -> Self {
+        let params = params.to_params().expect("Invalid Argon2 parameters");
         let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
 
         Self { argon2 }
@@ -46,7 +97,7 @@ impl Argon2idHasher {
 
 impl Default for Argon2idHasher {
     fn default() -> Self {
-        Self::new()
+        Self::new(Argon2Params::default())
     }
 }
 
@@ -97,7 +148,7 @@ mod tests {
 
     #[test]
     fn test_argon2id_hash_and_verify() {
-        let hasher = Argon2idHasher::new();
+        let hasher = Argon2idHasher::default();
         let password = "TestPassword123!";
 
         let hash = hasher.hash_password(password).unwrap();
@@ -115,13 +166,13 @@ mod tests {
 
     #[test]
     fn test_argon2id_algorithm() {
-        let hasher = Argon2idHasher::new();
+        let hasher = Argon2idHasher::default();
         assert_eq!(hasher.algorithm(), PasswordAlgorithm::Argon2id);
     }
 
     #[test]
     fn test_argon2id_is_hash() {
-        let hasher = Argon2idHasher::new();
+        let hasher = Argon2idHasher::default();
 
         // Valid Argon2id hash
         let valid_hash =
@@ -134,9 +185,48 @@ mod tests {
         assert!(!hasher.is_hash("{SSHA}example")); // SSHA
     }
 
+    #[test]
+    fn test_argon2id_custom_params() {
+        let params = Argon2Params {
+            memory_kib: 8192,
+            iterations: 1,
+            parallelism: 2,
+        };
+        let hasher = Argon2idHasher::new(params);
+
+        let hash = hasher.hash_password("TestPassword123!").unwrap();
+        assert!(hash.contains("m=8192,t=1,p=2"));
+        assert!(hasher.verify_password("TestPassword123!", &hash).unwrap());
+
+        // Hashes made with other parameters still verify
+        let default_hash = Argon2idHasher::default()
+            .hash_password("TestPassword123!")
+            .unwrap();
+        assert!(hasher
+            .verify_password("TestPassword123!", &default_hash)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_argon2_params_validate() {
+        assert!(Argon2Params::default().validate().is_ok());
+        assert!(Argon2Params {
+            iterations: 0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(Argon2Params {
+            parallelism: 0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
     #[test]
     fn test_argon2id_different_salts() {
-        let hasher = Argon2idHasher::new();
+        let hasher = Argon2idHasher::default();
         let password = "SamePassword123!";
 
         let hash1 = hasher.hash_password(password).unwrap();
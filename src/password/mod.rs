@@ -1,23 +1,23 @@
 use crate::error::{AppError, AppResult};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
 
 /// Password hashing algorithm types
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Configured as `bcrypt`, `ssha` or `argon2id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PasswordAlgorithm {
     /// bcrypt algorithm (current default for backward compatibility)
     Bcrypt,
     /// SSHA (Salted SHA-1) algorithm for LDAP compatibility
     Ssha,
-    /// Argon2id algorithm (OWASP recommended for new passwords)
+    /// Argon2id algorithm (OWASP recommended for new passwords, default)
+    #[default]
     Argon2id,
 }
 
-impl Default for PasswordAlgorithm {
-    fn default() -> Self {
-        // Default to Argon2id as OWASP recommended
-        Self::Argon2id
-    }
-}
-
 impl std::fmt::Display for PasswordAlgorithm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -46,12 +46,31 @@ pub trait PasswordHasher: Send + Sync {
 
 pub mod argon2_hasher;
 pub mod bcrypt_hasher;
+pub mod registry;
 pub mod ssha_hasher;
 
-pub use argon2_hasher::Argon2idHasher;
+pub use argon2_hasher::{Argon2Params, Argon2idHasher};
 pub use bcrypt_hasher::BcryptHasher;
+pub use registry::PasswordManagerRegistry;
 pub use ssha_hasher::SshaHasher;
 
+lazy_static! {
+    /// Argon2id parameters applied by every PasswordManager
+    static ref ARGON2_PARAMS: RwLock<Argon2Params> = RwLock::new(Argon2Params::default());
+}
+
+/// Set the Argon2id parameters used for newly hashed passwords
+///
+/// Called once at startup with `password.argon2` from the config file.
+pub fn set_argon2_params(params: Argon2Params) {
+    *ARGON2_PARAMS.write().unwrap_or_else(|e| e.into_inner()) = params;
+}
+
+/// Argon2id parameters currently in effect
+pub fn argon2_params() -> Argon2Params {
+    *ARGON2_PARAMS.read().unwrap_or_else(|e| e.into_inner())
+}
+
 /// Password manager with support for multiple algorithms
 pub struct PasswordManager {
     /// Current algorithm for new passwords
@@ -68,11 +87,18 @@ impl Default for PasswordManager {
 
 impl PasswordManager {
     /// Create a new PasswordManager with specified default algorithm
+    ///
+    /// Argon2id uses the parameters set with [`set_argon2_params`].
     pub fn new(default_algorithm: PasswordAlgorithm) -> Self {
+        Self::with_argon2_params(default_algorithm, argon2_params())
+    }
+
+    /// Create a PasswordManager with explicit Argon2id parameters
+    pub fn with_argon2_params(default_algorithm: PasswordAlgorithm, params: Argon2Params) -> Self {
         let hashers: Vec<Box<dyn PasswordHasher>> = vec![
             Box::new(BcryptHasher::new()),
             Box::new(SshaHasher::new()),
-            Box::new(Argon2idHasher::new()),
+            Box::new(Argon2idHasher::new(params)),
         ];
 
         Self {
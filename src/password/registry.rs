@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::PasswordManager;
+use crate::config::AppConfig;
+
+/// One [`PasswordManager`] per tenant, built once at startup
+///
+/// Tenants without `password_algorithm` share the default manager, which
+/// uses `password.algorithm`. Every manager hashes Argon2id with
+/// `password.argon2`, read once when the registry is built.
+pub struct PasswordManagerRegistry {
+    default: Arc<PasswordManager>,
+    tenants: HashMap<u32, Arc<PasswordManager>>,
+}
+
+impl PasswordManagerRegistry {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let params = config.password.argon2;
+        let default = Arc::new(PasswordManager::with_argon2_params(
+            config.password.algorithm,
+            params,
+        ));
+        let tenants = config
+            .tenants
+            .iter()
+            .filter_map(|tenant| {
+                let algorithm = tenant.password_algorithm?;
+                let manager = PasswordManager::with_argon2_params(algorithm, params);
+                Some((tenant.id, Arc::new(manager)))
+            })
+            .collect();
+
+        Self { default, tenants }
+    }
+
+    /// Manager hashing the given tenant's passwords
+    pub fn for_tenant(&self, tenant_id: u32) -> Arc<PasswordManager> {
+        self.tenants
+            .get(&tenant_id)
+            .unwrap_or(&self.default)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::password::PasswordAlgorithm;
+
+    #[test]
+    fn test_tenant_algorithm_overrides_default() {
+        let mut config = AppConfig::default_config();
+        let mut second = config.tenants[0].clone();
+        second.id = 2;
+        second.password_algorithm = Some(PasswordAlgorithm::Bcrypt);
+        config.tenants.push(second);
+        config.password.algorithm = PasswordAlgorithm::Ssha;
+
+        let registry = PasswordManagerRegistry::from_config(&config);
+        assert_eq!(
+            registry.for_tenant(1).current_algorithm(),
+            PasswordAlgorithm::Ssha
+        );
+        assert_eq!(
+            registry.for_tenant(2).current_algorithm(),
+            PasswordAlgorithm::Bcrypt
+        );
+        // Unknown tenants fall back to the default too
+        assert!(Arc::ptr_eq(
+            &registry.for_tenant(1),
+            &registry.for_tenant(99)
+        ));
+    }
+}
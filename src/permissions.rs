@@ -0,0 +1,780 @@
+//! Permissions of scoped tokens (`auth.tokens[].permissions`)
+//!
+//! Method restrictions are checked by `auth_middleware`. Denied attributes
+//! are enforced here: writes carrying them get 400 `mutability`, filters and
+//! sortBy naming them get 400 `invalidFilter`, and they are stripped from
+//! every User and Group the server returns. A PUT keeps their stored values,
+//! since the token could neither see nor send them.
+
+use axum::{
+    body::{self, Body, Bytes},
+    extract::{FromRequest, Query, Request},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures_util::{stream, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::auth::TenantInfo;
+use crate::config::TokenPermissions;
+use crate::error::scim_error_response;
+use crate::parser::filter_operator::FilterOperator;
+use crate::parser::filter_parser::parse_filter;
+use crate::resource::attribute_filter::AttributeFilter;
+use crate::response_validation::is_json;
+use crate::schema::{
+    SCIM_API_MESSAGES_LIST_RESPONSE, SCIM_SCHEMA_CORE_GROUP, SCIM_SCHEMA_CORE_USER,
+};
+
+/// Whether a token may send `method` to `path`
+///
+/// Read-only tokens may still POST `.search` queries.
+pub fn method_allowed(permissions: &TokenPermissions, method: &Method, path: &str) -> bool {
+    let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || (method == Method::POST && path.ends_with("/.search"));
+    if permissions.readonly && !is_read {
+        return false;
+    }
+    permissions.allowed_methods.is_empty()
+        || permissions
+            .allowed_methods
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(method.as_str()))
+}
+
+/// Enforce the denied attributes of the token a request authenticated with
+///
+/// Must run after `auth_middleware` so the resolved `TenantInfo` is available.
+pub async fn denied_attributes_middleware(request: Request, next: Next) -> Response {
+    let denied: Vec<String> = match request
+        .extensions()
+        .get::<TenantInfo>()
+        .and_then(|tenant_info| tenant_info.permissions.as_ref())
+    {
+        Some(permissions) if !permissions.denied_attributes.is_empty() => {
+            permissions.denied_attributes.clone()
+        }
+        _ => return next.run(request).await,
+    };
+    let normalized: Vec<String> = denied.iter().map(|attr| normalize(attr)).collect();
+
+    let query = Query::<HashMap<String, String>>::try_from_uri(request.uri())
+        .map(|Query(query)| query)
+        .unwrap_or_default();
+    if let Some(attr) = denied_in_query(&query, &denied, &normalized) {
+        return invalid_filter_response(attr);
+    }
+
+    let request = if matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH
+    ) {
+        let is_search = request.uri().path().ends_with("/.search");
+        let is_patch = request.method() == Method::PATCH;
+        // Read within the body limit the handlers' extractors would apply
+        let (parts, body) = request.into_parts();
+        let bytes = match Bytes::from_request(Request::from_parts(parts.clone(), body), &()).await {
+            Ok(bytes) => bytes,
+            Err(rejection) => return rejection.into_response(),
+        };
+        // Malformed bodies are left for the handlers to reject
+        if let Ok(payload) = serde_json::from_slice::<Value>(&bytes) {
+            if is_search {
+                let query = ["filter", "sortBy"]
+                    .into_iter()
+                    .filter_map(|name| {
+                        let value = payload.get(name)?.as_str()?;
+                        Some((name.to_string(), value.to_string()))
+                    })
+                    .collect();
+                if let Some(attr) = denied_in_query(&query, &denied, &normalized) {
+                    return invalid_filter_response(attr);
+                }
+            } else if let Some(attr) = denied_in_write(&payload, is_patch, &denied, &normalized) {
+                return scim_error_response(
+                    StatusCode::BAD_REQUEST,
+                    "mutability",
+                    &format!(
+                        "Attribute '{}' may not be written with these credentials",
+                        attr
+                    ),
+                )
+                .into_response();
+            }
+        }
+        Request::from_parts(parts, Body::from(bytes))
+    } else {
+        request
+    };
+
+    let response = next.run(request).await;
+    let ndjson = is_ndjson(&response);
+    if !ndjson && !is_json(&response) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let filter = AttributeFilter {
+        attributes: None,
+        excluded_attributes: None,
+        withheld_attributes: denied,
+    };
+    if ndjson {
+        return Response::from_parts(parts, strip_denied_ndjson(body, filter));
+    }
+
+    let bytes = match body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let bytes = match strip_denied(&bytes, &filter) {
+        Some(stripped) => stripped,
+        None => bytes.to_vec(),
+    };
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// An NDJSON body with the denied attributes stripped line by line as it
+/// streams. Only a line still incomplete at the end of a chunk is held back.
+fn strip_denied_ndjson(body: Body, filter: AttributeFilter) -> Body {
+    let lines = stream::unfold(
+        Some((body.into_data_stream(), Vec::new(), filter)),
+        |state| async move {
+            let (mut chunks, mut pending, filter) = state?;
+            loop {
+                match chunks.next().await {
+                    Some(Ok(chunk)) => {
+                        pending.extend_from_slice(&chunk);
+                        let Some(end) = pending.iter().rposition(|byte| *byte == b'\n') else {
+                            continue;
+                        };
+                        let rest = pending.split_off(end + 1);
+                        let complete = strip_denied_lines(&pending, &filter);
+                        return Some((Ok(Bytes::from(complete)), Some((chunks, rest, filter))));
+                    }
+                    Some(Err(e)) => return Some((Err(e), None)),
+                    None if pending.is_empty() => return None,
+                    // A last line without a trailing newline
+                    None => {
+                        let last = strip_denied_lines(&pending, &filter);
+                        return Some((Ok(Bytes::from(last)), None));
+                    }
+                }
+            }
+        },
+    );
+    Body::from_stream(lines)
+}
+
+fn strip_denied_lines(bytes: &[u8], filter: &AttributeFilter) -> Vec<u8> {
+    let lines: Vec<Vec<u8>> = bytes
+        .split(|byte| *byte == b'\n')
+        .map(|line| strip_denied(line, filter).unwrap_or_else(|| line.to_vec()))
+        .collect();
+    lines.join(&b'\n')
+}
+
+fn invalid_filter_response(attr: &str) -> Response {
+    scim_error_response(
+        StatusCode::BAD_REQUEST,
+        "invalidFilter",
+        &format!(
+            "Attribute '{}' may not be filtered or sorted on with these credentials",
+            attr
+        ),
+    )
+    .into_response()
+}
+
+fn is_ndjson(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| {
+            media_type
+                .trim()
+                .eq_ignore_ascii_case("application/x-ndjson")
+        })
+}
+
+/// A JSON document with the denied attributes removed from the User, Group
+/// or ListResponse it holds. None when it is not JSON.
+fn strip_denied(bytes: &[u8], filter: &AttributeFilter) -> Option<Vec<u8>> {
+    let mut json: Value = serde_json::from_slice(bytes).ok()?;
+    if has_schema(&json, SCIM_API_MESSAGES_LIST_RESPONSE) {
+        if let Some(resources) = json["Resources"].as_array_mut() {
+            for resource in resources {
+                strip_resource(resource, filter);
+            }
+        }
+    } else {
+        strip_resource(&mut json, filter);
+    }
+    serde_json::to_vec(&json).ok()
+}
+
+fn strip_resource(resource: &mut Value, filter: &AttributeFilter) {
+    // Projected resources (attributes=...) keep their id but not schemas
+    let is_resource = match resource.get("schemas") {
+        Some(_) => {
+            has_schema(resource, SCIM_SCHEMA_CORE_USER)
+                || has_schema(resource, SCIM_SCHEMA_CORE_GROUP)
+        }
+        None => resource.get("id").is_some(),
+    };
+    if is_resource {
+        filter.withhold(resource);
+    }
+}
+
+fn has_schema(json: &Value, schema: &str) -> bool {
+    json["schemas"]
+        .as_array()
+        .is_some_and(|schemas| schemas.iter().any(|s| s == schema))
+}
+
+/// The denied attribute a `filter` or `sortBy` parameter refers to
+fn denied_in_query<'a>(
+    query: &HashMap<String, String>,
+    denied: &'a [String],
+    normalized: &[String],
+) -> Option<&'a str> {
+    let mut paths = Vec::new();
+    for (name, value) in query {
+        if name.eq_ignore_ascii_case("sortBy") {
+            paths.push(value.clone());
+        } else if name.eq_ignore_ascii_case("filter") {
+            // Unparsable filters are left for the handlers to reject
+            if let Ok(filter) = parse_filter(value) {
+                filter_paths(&filter, None, &mut paths);
+            }
+        }
+    }
+    find_denied(&paths, denied, normalized, false)
+}
+
+/// Attribute paths a filter compares
+fn filter_paths(filter: &FilterOperator, parent: Option<&str>, paths: &mut Vec<String>) {
+    let path = |attr: &str| match parent {
+        Some(parent) => format!("{}.{}", parent, attr),
+        None => attr.to_string(),
+    };
+    match filter {
+        FilterOperator::Equal(attr, _)
+        | FilterOperator::NotEqual(attr, _)
+        | FilterOperator::Contains(attr, _)
+        | FilterOperator::StartsWith(attr, _)
+        | FilterOperator::EndsWith(attr, _)
+        | FilterOperator::GreaterThan(attr, _)
+        | FilterOperator::GreaterThanOrEqual(attr, _)
+        | FilterOperator::LessThan(attr, _)
+        | FilterOperator::LessThanOrEqual(attr, _)
+        | FilterOperator::Present(attr) => paths.push(path(attr)),
+        FilterOperator::And(left, right) | FilterOperator::Or(left, right) => {
+            filter_paths(left, parent, paths);
+            filter_paths(right, parent, paths);
+        }
+        FilterOperator::Not(inner) => filter_paths(inner, parent, paths),
+        FilterOperator::Complex(attr, inner) => {
+            let attr = path(attr);
+            paths.push(attr.clone());
+            filter_paths(inner, Some(&attr), paths);
+        }
+    }
+}
+
+/// The denied attribute a resource body or PATCH request writes
+fn denied_in_write<'a>(
+    payload: &Value,
+    is_patch: bool,
+    denied: &'a [String],
+    normalized: &[String],
+) -> Option<&'a str> {
+    if !is_patch {
+        return find_denied(&body_paths(payload), denied, normalized, false);
+    }
+
+    let operations = payload.get("Operations").and_then(Value::as_array)?;
+    operations.iter().find_map(|operation| {
+        let value = operation.get("value").unwrap_or(&Value::Null);
+        let Some(path) = operation.get("path").and_then(Value::as_str) else {
+            return find_denied(&body_paths(value), denied, normalized, false);
+        };
+        let mut paths = Vec::new();
+        push_paths(path, value, &mut paths);
+        // Removing an attribute also removes its sub-attributes
+        let is_remove = operation
+            .get("op")
+            .and_then(Value::as_str)
+            .is_some_and(|op| op.eq_ignore_ascii_case("remove"));
+        find_denied(&paths, denied, normalized, is_remove)
+    })
+}
+
+/// Copy the stored values of the token's denied attributes into a PUT body
+///
+/// The body replaces the whole resource, so without this a token that never
+/// sees e.g. `phoneNumbers` would delete them. Denied sub-attributes of
+/// multi-valued attributes are carried over to the values with the same
+/// `value`.
+pub fn carry_over_denied(permissions: &TokenPermissions, stored: &Value, payload: &mut Value) {
+    for attr in &permissions.denied_attributes {
+        let path = normalize(attr);
+        // Extension attributes: "<urn>:<attribute>" or the whole extension
+        let extension = stored.as_object().and_then(|object| {
+            object.keys().find(|key| {
+                let urn = key.to_ascii_lowercase();
+                urn.starts_with("urn:") && is_within(&path, &urn)
+            })
+        });
+        let segments: Vec<&str> = match extension {
+            Some(urn) => {
+                let rest = path[urn.len()..].trim_start_matches(':');
+                std::iter::once(urn.as_str())
+                    .chain(rest.split('.').filter(|segment| !segment.is_empty()))
+                    .collect()
+            }
+            None => path.split('.').collect(),
+        };
+        if carry_over(stored, payload, &segments) {
+            if let Some(urn) = extension {
+                add_schema(payload, urn);
+            }
+        }
+    }
+}
+
+/// Copy the value at `segments` (matched case-insensitively) from `stored`
+/// into `target`; whether anything was copied
+fn carry_over(stored: &Value, target: &mut Value, segments: &[&str]) -> bool {
+    let (Some((first, rest)), Some(stored), Some(target)) = (
+        segments.split_first(),
+        stored.as_object(),
+        target.as_object_mut(),
+    ) else {
+        return false;
+    };
+    let Some((key, stored_value)) = stored
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(first))
+    else {
+        return false;
+    };
+    if rest.is_empty() {
+        target.insert(key.clone(), stored_value.clone());
+        return true;
+    }
+    match stored_value {
+        Value::Object(_) => {
+            let target_value = target
+                .entry(key.clone())
+                .or_insert_with(|| Value::Object(Default::default()));
+            carry_over(stored_value, target_value, rest)
+        }
+        Value::Array(stored_items) => {
+            let Some(Value::Array(target_items)) = target.get_mut(key) else {
+                return false;
+            };
+            let mut copied = false;
+            for target_item in target_items {
+                let stored_item = stored_items.iter().find(|item| {
+                    item.get("value").is_some() && item.get("value") == target_item.get("value")
+                });
+                if let Some(stored_item) = stored_item {
+                    copied |= carry_over(stored_item, target_item, rest);
+                }
+            }
+            copied
+        }
+        _ => false,
+    }
+}
+
+fn add_schema(payload: &mut Value, urn: &str) {
+    if let Some(schemas) = payload.get_mut("schemas").and_then(Value::as_array_mut) {
+        if !schemas.iter().any(|schema| schema == urn) {
+            schemas.push(Value::String(urn.to_string()));
+        }
+    }
+}
+
+/// Attribute paths set by a resource body, extension attributes qualified
+/// by their URN
+fn body_paths(body: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    if let Value::Object(object) = body {
+        for (key, value) in object {
+            match value {
+                Value::Object(extension) if key.starts_with("urn:") => {
+                    paths.push(key.clone());
+                    for (attr, value) in extension {
+                        push_paths(&format!("{}:{}", key, attr), value, &mut paths);
+                    }
+                }
+                _ => push_paths(key, value, &mut paths),
+            }
+        }
+    }
+    paths
+}
+
+fn push_paths(path: &str, value: &Value, paths: &mut Vec<String>) {
+    paths.push(path.to_string());
+    let objects: Vec<&serde_json::Map<String, Value>> = match value {
+        Value::Object(object) => vec![object],
+        Value::Array(items) => items.iter().filter_map(Value::as_object).collect(),
+        _ => Vec::new(),
+    };
+    for object in objects {
+        for (key, value) in object {
+            push_paths(&format!("{}.{}", path, key), value, paths);
+        }
+    }
+}
+
+/// The first denied attribute that one of `paths` is, or lies below. With
+/// `ancestors`, paths above a denied attribute count too.
+fn find_denied<'a>(
+    paths: &[String],
+    denied: &'a [String],
+    normalized: &[String],
+    ancestors: bool,
+) -> Option<&'a str> {
+    paths.iter().find_map(|path| {
+        let path = normalize(path);
+        normalized
+            .iter()
+            .position(|attr| is_within(&path, attr) || (ancestors && is_within(attr, &path)))
+            .map(|index| denied[index].as_str())
+    })
+}
+
+/// Whether `path` is `attr` or one of its sub-attributes
+fn is_within(path: &str, attr: &str) -> bool {
+    path.strip_prefix(attr)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with(':'))
+}
+
+/// Lowercase attribute path without a core schema URN or value filters, e.g.
+/// `emails[type eq "work"].value` -> `emails.value`
+fn normalize(path: &str) -> String {
+    let mut path = path.to_ascii_lowercase();
+    for urn in [SCIM_SCHEMA_CORE_USER, SCIM_SCHEMA_CORE_GROUP] {
+        let prefix = format!("{}:", urn.to_ascii_lowercase());
+        if let Some(rest) = path.strip_prefix(&prefix) {
+            path = rest.to_string();
+        }
+    }
+    let mut normalized = String::with_capacity(path.len());
+    let mut depth = 0;
+    for c in path.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth == 0 => normalized.push(c),
+            _ => {}
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn denied(attrs: &[&str]) -> (Vec<String>, Vec<String>) {
+        let denied: Vec<String> = attrs.iter().map(|attr| attr.to_string()).collect();
+        let normalized = denied.iter().map(|attr| normalize(attr)).collect();
+        (denied, normalized)
+    }
+
+    #[test]
+    fn test_method_allowed() {
+        let readonly = TokenPermissions {
+            readonly: true,
+            ..Default::default()
+        };
+        assert!(method_allowed(&readonly, &Method::GET, "/scim/v2/Users"));
+        assert!(method_allowed(&readonly, &Method::POST, "/scim/v2/.search"));
+        assert!(!method_allowed(&readonly, &Method::POST, "/scim/v2/Users"));
+        assert!(!method_allowed(
+            &readonly,
+            &Method::DELETE,
+            "/scim/v2/Users/1"
+        ));
+
+        let no_delete = TokenPermissions {
+            allowed_methods: vec!["get".to_string(), "POST".to_string(), "PATCH".to_string()],
+            ..Default::default()
+        };
+        assert!(method_allowed(
+            &no_delete,
+            &Method::PATCH,
+            "/scim/v2/Users/1"
+        ));
+        assert!(!method_allowed(
+            &no_delete,
+            &Method::DELETE,
+            "/scim/v2/Users/1"
+        ));
+        assert!(method_allowed(
+            &TokenPermissions::default(),
+            &Method::DELETE,
+            "/x"
+        ));
+    }
+
+    #[test]
+    fn test_denied_in_write() {
+        let (denied, normalized) = denied(&[
+            "phoneNumbers",
+            "name.familyName",
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber",
+        ]);
+        let check = |payload: Value, is_patch: bool| {
+            denied_in_write(&payload, is_patch, &denied, &normalized)
+        };
+
+        assert_eq!(
+            check(json!({"userName": "a", "name": {"givenName": "A"}}), false),
+            None
+        );
+        assert_eq!(
+            check(json!({"phoneNumbers": [{"value": "1"}]}), false),
+            Some("phoneNumbers")
+        );
+        assert_eq!(
+            check(json!({"name": {"familyName": "B"}}), false),
+            Some("name.familyName")
+        );
+        assert_eq!(
+            check(
+                json!({"urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                    "employeeNumber": "7"
+                }}),
+                false
+            ),
+            Some("urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber")
+        );
+
+        let patch = |op: &str, path: &str, value: Value| json!({"Operations": [{"op": op, "path": path, "value": value}]});
+        assert_eq!(
+            check(
+                patch(
+                    "replace",
+                    r#"phoneNumbers[type eq "work"].value"#,
+                    json!("1")
+                ),
+                true
+            ),
+            Some("phoneNumbers")
+        );
+        assert_eq!(
+            check(
+                patch(
+                    "add",
+                    "urn:ietf:params:scim:schemas:core:2.0:User:PhoneNumbers",
+                    json!([{"value": "1"}])
+                ),
+                true
+            ),
+            Some("phoneNumbers")
+        );
+        assert_eq!(
+            check(patch("replace", "name", json!({"familyName": "B"})), true),
+            Some("name.familyName")
+        );
+        assert_eq!(
+            check(patch("replace", "name", json!({"givenName": "A"})), true),
+            None
+        );
+        assert_eq!(
+            check(
+                json!({"Operations": [{"op": "remove", "path": "name"}]}),
+                true
+            ),
+            Some("name.familyName")
+        );
+        assert_eq!(
+            check(
+                json!({"Operations": [{"op": "replace", "value": {"phoneNumbers": []}}]}),
+                true
+            ),
+            Some("phoneNumbers")
+        );
+    }
+
+    #[test]
+    fn test_carry_over_denied() {
+        let enterprise = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+        let permissions = TokenPermissions {
+            denied_attributes: vec![
+                "phoneNumbers".to_string(),
+                "name.familyName".to_string(),
+                "emails.type".to_string(),
+                format!("{}:employeeNumber", enterprise),
+            ],
+            ..Default::default()
+        };
+        let stored = json!({
+            "schemas": [SCIM_SCHEMA_CORE_USER, enterprise],
+            "userName": "a",
+            "name": {"givenName": "A", "familyName": "B"},
+            "phoneNumbers": [{"value": "1"}],
+            "emails": [{"value": "a@example.com", "type": "work"}],
+            enterprise: {"employeeNumber": "7", "department": "Sales"}
+        });
+        let mut payload = json!({
+            "schemas": [SCIM_SCHEMA_CORE_USER],
+            "userName": "a2",
+            "name": {"givenName": "A2"},
+            "emails": [{"value": "a@example.com"}, {"value": "a2@example.com"}]
+        });
+        carry_over_denied(&permissions, &stored, &mut payload);
+        assert_eq!(
+            payload,
+            json!({
+                "schemas": [SCIM_SCHEMA_CORE_USER, enterprise],
+                "userName": "a2",
+                "name": {"givenName": "A2", "familyName": "B"},
+                "phoneNumbers": [{"value": "1"}],
+                "emails": [
+                    {"value": "a@example.com", "type": "work"},
+                    {"value": "a2@example.com"}
+                ],
+                enterprise: {"employeeNumber": "7"}
+            })
+        );
+
+        // Attributes the resource does not have are not added
+        let mut payload = json!({"userName": "a2"});
+        carry_over_denied(
+            &TokenPermissions {
+                denied_attributes: vec!["addresses".to_string()],
+                ..Default::default()
+            },
+            &stored,
+            &mut payload,
+        );
+        assert_eq!(payload, json!({"userName": "a2"}));
+    }
+
+    #[test]
+    fn test_denied_in_query() {
+        let (denied, normalized) = denied(&["phoneNumbers", "name.familyName"]);
+        let check = |name: &str, value: &str| {
+            let query = HashMap::from([(name.to_string(), value.to_string())]);
+            denied_in_query(&query, &denied, &normalized)
+        };
+
+        assert_eq!(check("filter", r#"userName eq "phoneNumbers""#), None);
+        assert_eq!(
+            check(
+                "filter",
+                r#"userName eq "a" or phoneNumbers[value sw "+81"]"#
+            ),
+            Some("phoneNumbers")
+        );
+        assert_eq!(
+            check("filter", "name.familyName pr"),
+            Some("name.familyName")
+        );
+        assert_eq!(check("sortBy", "name.familyName"), Some("name.familyName"));
+        assert_eq!(check("sortBy", "name.givenName"), None);
+    }
+
+    #[test]
+    fn test_strip_denied() {
+        let filter = AttributeFilter {
+            attributes: None,
+            excluded_attributes: None,
+            withheld_attributes: vec!["phoneNumbers".to_string(), "name.familyName".to_string()],
+        };
+        let list = json!({
+            "schemas": [SCIM_API_MESSAGES_LIST_RESPONSE],
+            "Resources": [{
+                "schemas": [SCIM_SCHEMA_CORE_USER],
+                "userName": "a",
+                "name": {"givenName": "A", "familyName": "B"},
+                "phoneNumbers": [{"value": "1"}]
+            }]
+        });
+        let stripped: Value = serde_json::from_slice(
+            &strip_denied(&serde_json::to_vec(&list).unwrap(), &filter).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            stripped["Resources"][0],
+            json!({
+                "schemas": [SCIM_SCHEMA_CORE_USER],
+                "userName": "a",
+                "name": {"givenName": "A"}
+            })
+        );
+
+        // Other documents are passed on as they are
+        let error = json!({"schemas": ["x"], "phoneNumbers": "1"});
+        let stripped: Value = serde_json::from_slice(
+            &strip_denied(&serde_json::to_vec(&error).unwrap(), &filter).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stripped, error);
+
+        // Projected resources carry no schemas but still an id
+        let projected = json!({"id": "1", "userName": "a", "phoneNumbers": [{"value": "1"}]});
+        let stripped: Value = serde_json::from_slice(
+            &strip_denied(&serde_json::to_vec(&projected).unwrap(), &filter).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stripped, json!({"id": "1", "userName": "a"}));
+    }
+
+    #[tokio::test]
+    async fn test_strip_denied_ndjson() {
+        let filter = AttributeFilter {
+            attributes: None,
+            excluded_attributes: None,
+            withheld_attributes: vec!["phoneNumbers".to_string()],
+        };
+        let user = |user_name: &str| {
+            json!({
+                "schemas": [SCIM_SCHEMA_CORE_USER],
+                "userName": user_name,
+                "phoneNumbers": [{"value": "1"}]
+            })
+        };
+        let mut ndjson = Vec::new();
+        for user_name in ["a", "b", "c"] {
+            serde_json::to_writer(&mut ndjson, &user(user_name)).unwrap();
+            ndjson.push(b'\n');
+        }
+
+        // Chunks ending in the middle of a line
+        let chunks: Vec<Result<Bytes, std::io::Error>> = ndjson
+            .chunks(7)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        let body = strip_denied_ndjson(Body::from_stream(stream::iter(chunks)), filter);
+        let bytes = body::to_bytes(body, usize::MAX).await.unwrap();
+
+        let lines: Vec<Value> = bytes
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        for (line, user_name) in lines.iter().zip(["a", "b", "c"]) {
+            assert_eq!(
+                line,
+                &json!({"schemas": [SCIM_SCHEMA_CORE_USER], "userName": user_name})
+            );
+        }
+        assert!(bytes.ends_with(b"\n"));
+    }
+}
@@ -0,0 +1,285 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, Uri},
+    Json,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::backend::ScimBackend;
+use crate::config::AppConfig;
+use crate::parser::ResourceType;
+use crate::password::PasswordManagerRegistry;
+use crate::share::{self, SharedResource, MAX_SHARE_TTL_SECONDS};
+use crate::stats::SyncStats;
+
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
+
+/// `GET /admin/tenants`
+///
+/// Compares the tenants provisioned in the database with the configured
+/// ones, to spot a configured tenant that was never initialized or tables
+/// left behind by a removed tenant. Authenticated with `server.admin_token`
+/// by the auth middleware.
+pub async fn initialized_tenants(
+    State((backend, app_config, _)): State<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let initialized = backend
+        .list_initialized_tenants()
+        .await
+        .map_err(|e| e.to_response())?;
+
+    let mut configured: Vec<u32> = app_config.tenants.iter().map(|t| t.id).collect();
+    configured.sort_unstable();
+    configured.dedup();
+
+    let not_initialized: Vec<u32> = configured
+        .iter()
+        .copied()
+        .filter(|id| !initialized.contains(id))
+        .collect();
+    let not_configured: Vec<u32> = initialized
+        .iter()
+        .copied()
+        .filter(|id| !configured.contains(id))
+        .collect();
+
+    Ok(Json(json!({
+        "initialized": initialized,
+        "configured": configured,
+        "notInitialized": not_initialized,
+        "notConfigured": not_configured
+    })))
+}
+
+/// Body of `POST /admin/tenants/{id}/share`
+#[derive(Debug, Deserialize)]
+pub struct ShareRequest {
+    /// "User" or "Group"
+    pub resource_type: String,
+    pub resource_id: String,
+    pub ttl_seconds: i64,
+}
+
+fn admin_error(status: StatusCode, message: &str) -> (StatusCode, Json<Value>) {
+    (status, Json(json!({"message": message})))
+}
+
+/// `POST /admin/tenants/{id}/share`
+///
+/// Issues a read-only link to one user or group that expires after
+/// `ttl_seconds`, signed with `server.share_link_secret`. 404 when share
+/// links are disabled, the tenant is not configured or the resource does
+/// not exist.
+pub async fn create_share_link(
+    State((backend, app_config, _)): State<AppState>,
+    Path(tenant_id): Path<u32>,
+    uri: Uri,
+    headers: HeaderMap,
+    Json(request): Json<ShareRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let Some(secret) = &app_config.server.share_link_secret else {
+        return Err(admin_error(
+            StatusCode::NOT_FOUND,
+            "Share links are disabled",
+        ));
+    };
+    let Some(tenant) = app_config.tenants.iter().find(|t| t.id == tenant_id) else {
+        return Err(admin_error(StatusCode::NOT_FOUND, "Tenant not found"));
+    };
+    if !(1..=MAX_SHARE_TTL_SECONDS).contains(&request.ttl_seconds) {
+        return Err(admin_error(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "ttl_seconds must be between 1 and {}",
+                MAX_SHARE_TTL_SECONDS
+            ),
+        ));
+    }
+
+    let exists = match request.resource_type.as_str() {
+        "User" => backend
+            .find_user_by_id(tenant_id, &request.resource_id, false)
+            .await
+            .map_err(|e| e.to_response())?
+            .is_some(),
+        "Group" => backend
+            .find_group_by_id(tenant_id, &request.resource_id)
+            .await
+            .map_err(|e| e.to_response())?
+            .is_some(),
+        _ => {
+            return Err(admin_error(
+                StatusCode::BAD_REQUEST,
+                "resource_type must be \"User\" or \"Group\"",
+            ))
+        }
+    };
+    if !exists {
+        return Err(admin_error(
+            StatusCode::NOT_FOUND,
+            &format!("{} not found", request.resource_type),
+        ));
+    }
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(request.ttl_seconds);
+    let token = share::sign(
+        secret,
+        &SharedResource {
+            tenant: tenant_id,
+            resource_type: request.resource_type,
+            id: request.resource_id,
+            exp: expires_at.timestamp(),
+        },
+    );
+    let base_url = crate::auth::resolve_server_base_url(tenant, &uri, &headers);
+
+    Ok(Json(json!({
+        "url": format!("{}/shared/{}", base_url, token),
+        "expiresAt": crate::utils::format_scim_datetime(expires_at)
+    })))
+}
+
+/// `POST /admin/tenants/{id}/vacuum`
+///
+/// Reclaims the space left behind by bulk deletes in a tenant's tables and
+/// refreshes their planner statistics. Returns once the database is done.
+/// 404 when `server.maintenance_endpoints` is off or the tenant is not
+/// configured or not initialized.
+pub async fn vacuum_tenant(
+    State((backend, app_config, _)): State<AppState>,
+    Path(tenant_id): Path<u32>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if !app_config.server.maintenance_endpoints {
+        return Err(admin_error(
+            StatusCode::NOT_FOUND,
+            "Maintenance endpoints are disabled",
+        ));
+    }
+    if !app_config.tenants.iter().any(|t| t.id == tenant_id) {
+        return Err(admin_error(StatusCode::NOT_FOUND, "Tenant not found"));
+    }
+    let initialized = backend
+        .list_initialized_tenants()
+        .await
+        .map_err(|e| e.to_response())?;
+    if !initialized.contains(&tenant_id) {
+        return Err(admin_error(
+            StatusCode::NOT_FOUND,
+            "Tenant is not initialized",
+        ));
+    }
+
+    backend
+        .vacuum_tenant(tenant_id)
+        .await
+        .map_err(|e| e.to_response())?;
+
+    Ok(Json(json!({
+        "tenant": tenant_id,
+        "status": "completed"
+    })))
+}
+
+/// `GET /admin/tenants/{id}/stats`
+///
+/// Writes the tenant received by resource type and outcome, the time of the
+/// last successful write and the authentication failures, counted since
+/// `since` (process start, or the first persisted snapshot with
+/// `server.stats.persist`). 404 when the tenant is not configured.
+pub async fn tenant_stats(
+    State((app_config, sync_stats)): State<(Arc<AppConfig>, Arc<SyncStats>)>,
+    Path(tenant_id): Path<u32>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if !app_config.tenants.iter().any(|t| t.id == tenant_id) {
+        return Err(admin_error(StatusCode::NOT_FOUND, "Tenant not found"));
+    }
+
+    let stats = sync_stats.tenant(tenant_id);
+    Ok(Json(json!({
+        "tenant": tenant_id,
+        "since": stats.since,
+        "authFailures": stats.auth_failures,
+        "resources": stats.resources
+    })))
+}
+
+/// `GET /admin/tenants/{id}/history/{resource_type}/{resource_id}`
+///
+/// Every logged change of one user or group, oldest first. With `?at=` (an
+/// RFC 3339 time) it returns the resource as it stood at that time instead,
+/// replayed from the log; `resource` is null if it did not exist then. 404
+/// when the backend keeps no history, the tenant is not configured or the
+/// resource has no history.
+pub async fn resource_history(
+    State((backend, app_config, _)): State<AppState>,
+    Path((tenant_id, resource_type, resource_id)): Path<(u32, String, String)>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if !backend.keeps_history() {
+        return Err(admin_error(
+            StatusCode::NOT_FOUND,
+            "The backend keeps no history",
+        ));
+    }
+    if !app_config.tenants.iter().any(|t| t.id == tenant_id) {
+        return Err(admin_error(StatusCode::NOT_FOUND, "Tenant not found"));
+    }
+    let resource_type = match resource_type.as_str() {
+        "User" => ResourceType::User,
+        "Group" => ResourceType::Group,
+        _ => {
+            return Err(admin_error(
+                StatusCode::BAD_REQUEST,
+                "resource_type must be \"User\" or \"Group\"",
+            ))
+        }
+    };
+
+    if let Some(at) = params.get("at") {
+        let at = DateTime::parse_from_rfc3339(at)
+            .map_err(|_| admin_error(StatusCode::BAD_REQUEST, "at must be an RFC 3339 time"))?
+            .with_timezone(&Utc);
+        let resource = backend
+            .resource_at(tenant_id, resource_type, &resource_id, Some(at))
+            .await
+            .map_err(|e| e.to_response())?;
+        return Ok(Json(json!({
+            "at": event_time(at),
+            "resource": resource
+        })));
+    }
+
+    let events = backend
+        .history(tenant_id, resource_type, &resource_id)
+        .await
+        .map_err(|e| e.to_response())?;
+    if events.is_empty() {
+        return Err(admin_error(StatusCode::NOT_FOUND, "No history found"));
+    }
+    let events: Vec<Value> = events
+        .into_iter()
+        .map(|event| {
+            json!({
+                "eventId": event.event_id,
+                "operation": event.operation.as_str(),
+                "actor": event.actor,
+                "timestamp": event_time(event.timestamp),
+                "resource": event.payload
+            })
+        })
+        .collect();
+    Ok(Json(json!({ "events": events })))
+}
+
+/// Event times keep their microseconds, so they can be passed back as `at`
+fn event_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
@@ -1,5 +1,9 @@
+use crate::config::CompatibilityConfig;
 use crate::parser::ResourceType;
-use crate::schema::definitions::{find_attribute, Returned, GROUP_SCHEMA, USER_SCHEMA};
+use crate::schema::definitions::{
+    find_attribute, Returned, GROUP_SCHEMA, SCIM_SCHEMA_CORE_GROUP, SCIM_SCHEMA_CORE_USER,
+    USER_SCHEMA,
+};
 use serde_json::{Map, Value};
 
 /// Query parameters for SCIM attribute filtering per RFC 7644 section 3.4.2.5
@@ -9,6 +13,9 @@ pub struct AttributeFilter {
     pub attributes: Option<Vec<String>>,
     /// Comma-separated list of attributes to exclude from default set
     pub excluded_attributes: Option<Vec<String>>,
+    /// Attributes never returned, whatever the request asks for: top-level
+    /// names, `parent.sub` paths or extension-qualified `urn:...:attr` paths
+    pub withheld_attributes: Vec<String>,
 }
 
 impl AttributeFilter {
@@ -33,6 +40,72 @@ impl AttributeFilter {
         Self {
             attributes,
             excluded_attributes,
+            withheld_attributes: Vec::new(),
+        }
+    }
+
+    /// Withhold attributes the tenant's compatibility settings keep out of
+    /// responses. They remain stored and usable in filters.
+    pub fn with_compatibility(mut self, compatibility: &CompatibilityConfig) -> Self {
+        if !compatibility.return_external_id {
+            self.withheld_attributes.push("externalId".to_string());
+        }
+        self
+    }
+
+    /// Whether an extension attached after filtering belongs in the response
+    pub fn includes_extension(&self, urn: &str) -> bool {
+        let names_extension = |attr: &String| {
+            attr.eq_ignore_ascii_case(urn)
+                || attr
+                    .get(..urn.len() + 1)
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", urn)))
+        };
+
+        if let Some(ref attrs) = self.attributes {
+            return attrs.iter().any(names_extension);
+        }
+        !self
+            .excluded_attributes
+            .as_ref()
+            .is_some_and(|excluded| excluded.iter().any(|attr| attr.eq_ignore_ascii_case(urn)))
+    }
+
+    /// Remove the withheld attributes from a resource
+    pub fn withhold(&self, resource: &mut Value) {
+        let Value::Object(obj) = resource else {
+            return;
+        };
+        for attr in &self.withheld_attributes {
+            let attr = strip_core_urn(attr);
+            if !attr.starts_with("urn:") {
+                remove_attribute_path(obj, attr);
+                continue;
+            }
+            // Extension attributes live in an object keyed by the URN, and
+            // the URN itself contains dots, so match it before splitting
+            let Some(urn) = obj
+                .keys()
+                .find(|urn| {
+                    attr.eq_ignore_ascii_case(urn)
+                        || attr
+                            .get(..urn.len() + 1)
+                            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", urn)))
+                })
+                .cloned()
+            else {
+                continue;
+            };
+            match attr.get(urn.len() + 1..) {
+                Some(path) => {
+                    if let Some(Value::Object(extension)) = obj.get_mut(&urn) {
+                        remove_attribute_path(extension, path);
+                    }
+                }
+                None => {
+                    obj.remove(&urn);
+                }
+            }
         }
     }
 
@@ -40,7 +113,9 @@ impl AttributeFilter {
     /// Returns filtered JSON value according to RFC 7644 specification
     pub fn apply_to_resource(&self, resource: &Value, resource_type: ResourceType) -> Value {
         // First, remove null fields to comply with SCIM specification
-        let resource_no_nulls = Self::remove_null_fields(resource);
+        let mut resource_no_nulls = Self::remove_null_fields(resource);
+
+        self.withhold(&mut resource_no_nulls);
 
         // If no filtering specified, return resource without nulls
         if self.attributes.is_none() && self.excluded_attributes.is_none() {
@@ -390,6 +465,47 @@ impl AttributeFilter {
     }
 }
 
+/// `attr` without a core schema URN prefix
+fn strip_core_urn(attr: &str) -> &str {
+    for urn in [SCIM_SCHEMA_CORE_USER, SCIM_SCHEMA_CORE_GROUP] {
+        if let Some(prefix) = attr.get(..urn.len() + 1) {
+            if prefix.eq_ignore_ascii_case(&format!("{}:", urn)) {
+                return &attr[urn.len() + 1..];
+            }
+        }
+    }
+    attr
+}
+
+/// Remove `name` or `name.sub` (in every value of a multi-valued attribute)
+fn remove_attribute_path(obj: &mut Map<String, Value>, path: &str) {
+    let (name, sub) = match path.split_once('.') {
+        Some((name, sub)) => (name, Some(sub)),
+        None => (path, None),
+    };
+    let Some(key) = obj
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .cloned()
+    else {
+        return;
+    };
+    match (sub, obj.get_mut(&key)) {
+        (None, _) => {
+            obj.remove(&key);
+        }
+        (Some(sub), Some(Value::Object(inner))) => remove_attribute_path(inner, sub),
+        (Some(sub), Some(Value::Array(items))) => {
+            for item in items {
+                if let Value::Object(inner) = item {
+                    remove_attribute_path(inner, sub);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -639,4 +755,93 @@ mod tests {
         let members = cleaned_group["members"].as_array().unwrap();
         assert!(members.is_empty());
     }
+
+    #[test]
+    fn test_includes_extension() {
+        let urn = "urn:example:ext";
+        assert!(AttributeFilter::from_params(None, None).includes_extension(urn));
+        assert!(!AttributeFilter::from_params(Some("userName"), None).includes_extension(urn));
+        assert!(
+            AttributeFilter::from_params(Some("userName,urn:example:ext"), None)
+                .includes_extension(urn)
+        );
+        assert!(
+            AttributeFilter::from_params(Some("urn:example:ext:createdBy"), None)
+                .includes_extension(urn)
+        );
+        assert!(
+            !AttributeFilter::from_params(None, Some("URN:example:ext")).includes_extension(urn)
+        );
+        assert!(AttributeFilter::from_params(None, Some("userName")).includes_extension(urn));
+    }
+
+    #[test]
+    fn test_withheld_external_id() {
+        let user = json!({
+            "id": "123",
+            "userName": "john",
+            "externalId": "ext-123"
+        });
+        let compatibility = CompatibilityConfig {
+            return_external_id: false,
+            ..Default::default()
+        };
+
+        let filter = AttributeFilter::from_params(None, None).with_compatibility(&compatibility);
+        let result = filter.apply_to_resource(&user, ResourceType::User);
+        assert!(result.get("externalId").is_none());
+        assert_eq!(result["userName"], "john");
+
+        // Explicitly requesting it does not bring it back
+        let filter = AttributeFilter::from_params(Some("externalId"), None)
+            .with_compatibility(&compatibility);
+        let result = filter.apply_to_resource(&user, ResourceType::User);
+        assert!(result.get("externalId").is_none());
+        assert_eq!(result["id"], "123");
+
+        let filter =
+            AttributeFilter::from_params(None, None).with_compatibility(&Default::default());
+        let result = filter.apply_to_resource(&user, ResourceType::User);
+        assert_eq!(result["externalId"], "ext-123");
+    }
+
+    #[test]
+    fn test_withhold_paths() {
+        let mut user = json!({
+            "id": "123",
+            "userName": "john",
+            "name": {"givenName": "John", "familyName": "Doe"},
+            "emails": [{"value": "a@example.com", "type": "work"}, {"value": "b@example.com"}],
+            "phoneNumbers": [{"value": "555"}],
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                "employeeNumber": "7",
+                "department": "Sales"
+            }
+        });
+        let filter = AttributeFilter {
+            attributes: None,
+            excluded_attributes: None,
+            withheld_attributes: vec![
+                "PHONENUMBERS".to_string(),
+                "urn:ietf:params:scim:schemas:core:2.0:User:name.familyName".to_string(),
+                "emails.type".to_string(),
+                "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:employeeNumber"
+                    .to_string(),
+            ],
+        };
+
+        filter.withhold(&mut user);
+        assert_eq!(
+            user,
+            json!({
+                "id": "123",
+                "userName": "john",
+                "name": {"givenName": "John"},
+                "emails": [{"value": "a@example.com"}, {"value": "b@example.com"}],
+                "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                    "department": "Sales"
+                }
+            })
+        );
+    }
 }
@@ -1,49 +1,216 @@
 use axum::{
+    body::{Body, Bytes},
     extract::{Request, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode, Uri},
     response::{IntoResponse, Response},
+    Extension,
 };
-use std::sync::Arc;
+use chrono::{DateTime, SubsecRound, Utc};
+use futures_util::stream;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::io::AsyncReadExt;
 
-use crate::{auth::TenantInfo, backend::ScimBackend, config::AppConfig};
+use crate::{
+    auth::TenantInfo,
+    backend::ScimBackend,
+    config::{AppConfig, CustomEndpoint},
+    password::PasswordManagerRegistry,
+};
+
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
 
 /// Handle custom endpoint requests
 pub async fn handle_custom_endpoint(
-    State((_, app_config)): State<(Arc<dyn ScimBackend>, Arc<AppConfig>)>,
+    State((_, app_config, _)): State<AppState>,
     request: Request,
 ) -> impl IntoResponse {
-    let path = request.uri().path();
-
     // Get tenant info from auth middleware
-    let tenant_info = request.extensions().get::<TenantInfo>().cloned();
-
-    // Find matching custom endpoint
-    if let Some((tenant, endpoint)) = app_config.find_custom_endpoint(path) {
-        // Verify tenant matches (this should always be true if auth middleware worked correctly)
-        if let Some(info) = tenant_info {
-            if info.tenant_id != tenant.id {
-                return (StatusCode::FORBIDDEN, "Tenant mismatch").into_response();
-            }
-        } else {
-            // This shouldn't happen if auth middleware is properly configured
-            return (StatusCode::INTERNAL_SERVER_ERROR, "No tenant info found").into_response();
-        }
+    let Some(tenant_info) = request.extensions().get::<TenantInfo>() else {
+        // This shouldn't happen if auth middleware is properly configured
+        return (StatusCode::INTERNAL_SERVER_ERROR, "No tenant info found").into_response();
+    };
+
+    configured_response(
+        &app_config,
+        tenant_info,
+        request.uri().path(),
+        "GET",
+        request.headers(),
+    )
+    .await
+}
+
+/// Handle POST requests to custom endpoints configured with `method: POST`
+///
+/// The request body, if any, is read within axum's default body limit and
+/// ignored; the endpoint's configured response is returned as for GET.
+pub async fn handle_custom_endpoint_post(
+    State((_, app_config, _)): State<AppState>,
+    Extension(tenant_info): Extension<TenantInfo>,
+    uri: Uri,
+    headers: HeaderMap,
+    _body: Bytes,
+) -> impl IntoResponse {
+    configured_response(&app_config, &tenant_info, uri.path(), "POST", &headers).await
+}
+
+/// The static response of the tenant's endpoint at `path`
+///
+/// Tenants on different hosts may configure the same path with different
+/// methods, so the route can carry a method this tenant's endpoint lacks.
+async fn configured_response(
+    app_config: &AppConfig,
+    tenant_info: &TenantInfo,
+    path: &str,
+    method: &str,
+    headers: &HeaderMap,
+) -> Response {
+    // Several tenants on different hosts may share the path; serve the request's tenant
+    let Some(endpoint) = app_config.find_custom_endpoint(tenant_info.tenant_id, path) else {
+        return (StatusCode::NOT_FOUND, "Custom endpoint not found").into_response();
+    };
+    let endpoint_method = endpoint.effective_method();
+    if endpoint_method != method {
+        return (
+            StatusCode::METHOD_NOT_ALLOWED,
+            [(header::ALLOW, endpoint_method)],
+        )
+            .into_response();
+    }
 
-        // Return custom response
-        let response = Response::builder()
-            .status(endpoint.status_code)
-            .header("content-type", &endpoint.content_type);
-
-        if let Ok(response) = response.body(endpoint.response.clone()) {
-            response.into_response()
-        } else {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to create response",
+    if let Some(file) = &endpoint.response_file {
+        return file_response(endpoint, file, method, headers)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!(path = %endpoint.path, file = %file, error = %e, "Failed to read custom endpoint response_file");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to read response file",
+                )
+                    .into_response()
+            });
+    }
+
+    // Return custom response; base64 was validated at config load
+    let body = endpoint.inline_body().unwrap_or_default();
+    let response = Response::builder()
+        .status(endpoint.status_code)
+        .header("content-type", &endpoint.content_type);
+
+    if let Ok(response) = response.body(Body::from(body)) {
+        response.into_response()
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to create response",
+        )
+            .into_response()
+    }
+}
+
+/// Bytes read from the file per streamed chunk
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+lazy_static! {
+    /// Contents of `cache_response_file` files by path, with the modification
+    /// time they were read at
+    static ref FILE_CACHE: Mutex<HashMap<String, (SystemTime, Bytes)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Serve an endpoint's `response_file`
+///
+/// Sent with `Last-Modified`; a GET whose `If-Modified-Since` is not older
+/// than the file gets 304 when the endpoint answers 200. The file is streamed
+/// unless the endpoint caches it.
+async fn file_response(
+    endpoint: &CustomEndpoint,
+    file: &str,
+    method: &str,
+    headers: &HeaderMap,
+) -> std::io::Result<Response> {
+    // Stat the open file so the length matches the bytes streamed from it
+    let mut opened = tokio::fs::File::open(file).await?;
+    let metadata = opened.metadata().await?;
+    let modified = metadata.modified()?;
+    // HTTP dates have whole seconds
+    let last_modified = DateTime::<Utc>::from(modified).trunc_subsecs(0);
+    let last_modified_header = last_modified
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+
+    if method == "GET" && endpoint.status_code == 200 {
+        let since = headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok());
+        if since.is_some_and(|since| last_modified <= since) {
+            return Ok((
+                StatusCode::NOT_MODIFIED,
+                [(header::LAST_MODIFIED, last_modified_header)],
             )
-                .into_response()
+                .into_response());
         }
+    }
+
+    let (length, body) = if endpoint.cache_response_file {
+        let bytes = cached_file(file, &mut opened, modified).await?;
+        (bytes.len() as u64, Body::from(bytes))
     } else {
-        (StatusCode::NOT_FOUND, "Custom endpoint not found").into_response()
+        let length = metadata.len();
+        let chunks = stream::unfold(Some(opened.take(length)), |reader| async move {
+            let mut reader = reader?;
+            let mut chunk = vec![0; FILE_CHUNK_SIZE];
+            match reader.read(&mut chunk).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    chunk.truncate(n);
+                    Some((Ok(Bytes::from(chunk)), Some(reader)))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        (length, Body::from_stream(chunks))
+    };
+
+    Response::builder()
+        .status(endpoint.status_code)
+        .header(header::CONTENT_TYPE, &endpoint.content_type)
+        .header(header::CONTENT_LENGTH, length)
+        .header(header::LAST_MODIFIED, last_modified_header)
+        .body(body)
+        .map_err(std::io::Error::other)
+}
+
+/// Contents of a cached `response_file`, read again when it was modified
+async fn cached_file(
+    file: &str,
+    opened: &mut tokio::fs::File,
+    modified: SystemTime,
+) -> std::io::Result<Bytes> {
+    if let Some((cached_modified, bytes)) = FILE_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(file)
+    {
+        if *cached_modified == modified {
+            return Ok(bytes.clone());
+        }
     }
+
+    let mut contents = Vec::new();
+    opened.read_to_end(&mut contents).await?;
+    let bytes = Bytes::from(contents);
+    FILE_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(file.to_string(), (modified, bytes.clone()));
+    Ok(bytes)
 }
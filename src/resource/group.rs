@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Extension, Query, State},
+    extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode, Uri},
     response::{IntoResponse, Response},
     Json,
@@ -7,18 +7,32 @@ use axum::{
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc};
 
-use crate::extractors::ScimJson;
+use crate::extractors::{ScimJson, Tenant};
 
 use super::attribute_filter::AttributeFilter;
+use super::list_params::{filter_param, ListParams};
+use super::list_response::list_response;
+use super::ndjson::{export_filter, ndjson_response, wants_ndjson};
+use super::{member_count, provenance};
 use crate::auth::TenantInfo;
 use crate::backend::ScimBackend;
-use crate::config::AppConfig;
-use crate::error::scim_error_response;
-use crate::models::{Group, ScimListResponse, ScimPatchOp};
-use crate::parser::filter_parser::parse_filter;
-use crate::parser::{ResourceType, SortSpec};
-
-type AppState = (Arc<dyn ScimBackend>, Arc<AppConfig>);
+use crate::config::{AppConfig, CompatibilityConfig};
+use crate::error::{
+    patch_not_supported_response, precondition_required_response, scim_error_response,
+    too_many_patch_operations_response, AppError, ValidationIssue,
+};
+use crate::models::{Group, ScimListResponse};
+use crate::parser::filter_parser::{parse_filter, FilterOperator};
+use crate::parser::patch_parser::parse_patch_op;
+use crate::parser::{ResourceType, SortOrder, SortSpec};
+use crate::password::PasswordManagerRegistry;
+use crate::schema::{validate_patch_value_lengths, validate_value_lengths};
+
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
 
 // Helper function to extract resource ID from URI path
 fn extract_resource_id_from_uri(uri: &Uri) -> Option<String> {
@@ -36,24 +50,13 @@ fn extract_resource_id_from_uri(uri: &Uri) -> Option<String> {
     None
 }
 
-// Helper function to construct resource location URL
-fn build_resource_location(
-    tenant_info: &TenantInfo,
-    resource_type: &str,
-    resource_id: &str,
-) -> String {
-    // The base_path already includes the tenant path, so we just append the resource type and ID
-    format!(
-        "{}/{}/{}",
-        tenant_info.base_path.trim_end_matches('/'),
-        resource_type,
-        resource_id
-    )
-}
-
 // Helper function to set meta.location for a group
-fn set_group_location(tenant_info: &TenantInfo, group: &mut Group) {
-    let location = build_resource_location(tenant_info, "Groups", &group.base.id);
+pub(crate) fn set_group_location(tenant_info: &TenantInfo, group: &mut Group) {
+    let location = crate::utils::build_resource_location(
+        &tenant_info.base_path,
+        tenant_info.tenant_config.groups_path(),
+        &group.base.id,
+    );
 
     // Ensure meta exists
     if group.base.meta.is_none() {
@@ -62,7 +65,7 @@ fn set_group_location(tenant_info: &TenantInfo, group: &mut Group) {
             created: Some(now.clone()),
             last_modified: Some(now),
             location: Some(location),
-            resource_type: Some("Group".to_string()),
+            resource_type: Some(ResourceType::Group.scim_name().to_string()),
             version: None,
         });
     } else if let Some(ref mut meta) = group.base.meta {
@@ -71,7 +74,7 @@ fn set_group_location(tenant_info: &TenantInfo, group: &mut Group) {
 }
 
 // Helper function to fix group refs with base URL and tenant path
-fn fix_group_refs(tenant_info: &TenantInfo, group: &mut Group) {
+pub(crate) fn fix_group_refs(tenant_info: &TenantInfo, group: &mut Group) {
     let tenant_id = tenant_info.tenant_id;
 
     // Fix meta location
@@ -89,33 +92,33 @@ fn fix_group_refs(tenant_info: &TenantInfo, group: &mut Group) {
         }
     }
 
-    // Fix members $ref
+    // Resolve members $ref against the tenant's $ref base URL
     if let Some(ref mut members) = group.base.members {
         for member in members {
             if let Some(ref mut ref_) = member.ref_ {
-                if ref_.starts_with(&format!("/{}/", tenant_id)) {
-                    // Replace tenant ID-based path with full base URL + resource path
-                    let resource_path = ref_.replace(&format!("/{}/", tenant_id), "");
-                    *ref_ = format!(
-                        "{}/{}",
-                        tenant_info.base_path.trim_end_matches('/'),
-                        resource_path
-                    );
+                if let Some(resolved) = crate::utils::resolve_tenant_ref(
+                    &tenant_info.tenant_config,
+                    &tenant_info.ref_base_path,
+                    ref_,
+                ) {
+                    *ref_ = resolved;
                 }
             }
         }
     }
 }
 
-// Helper function to validate that all group members exist
+// Helper function to add an issue for every group member that does not exist
 async fn validate_group_members(
     backend: &Arc<dyn ScimBackend>,
     tenant_id: u32,
     members: &Option<Vec<scim_v2::models::group::Member>>,
+    issues: &mut Vec<ValidationIssue>,
 ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
     if let Some(members) = members {
-        for member in members {
+        for (index, member) in members.iter().enumerate() {
             if let Some(member_id) = &member.value {
+                let path = format!("members[{}].value", index);
                 // Check if the member type is User (default if not specified)
                 let member_type = member.type_.as_deref().unwrap_or("User");
 
@@ -124,10 +127,9 @@ async fn validate_group_members(
                         match backend.find_user_by_id(tenant_id, member_id, false).await {
                             Ok(Some(_)) => continue, // User exists, continue
                             Ok(None) => {
-                                return Err(scim_error_response(
-                                    StatusCode::BAD_REQUEST,
-                                    "invalidValue",
-                                    &format!("User with id '{}' does not exist.", member_id),
+                                issues.push(ValidationIssue::invalid_value(
+                                    path,
+                                    format!("User with id '{}' does not exist.", member_id),
                                 ));
                             }
                             Err(e) => {
@@ -143,10 +145,9 @@ async fn validate_group_members(
                         match backend.find_group_by_id(tenant_id, member_id).await {
                             Ok(Some(_)) => continue, // Group exists, continue
                             Ok(None) => {
-                                return Err(scim_error_response(
-                                    StatusCode::BAD_REQUEST,
-                                    "invalidValue",
-                                    &format!("Group with id '{}' does not exist.", member_id),
+                                issues.push(ValidationIssue::invalid_value(
+                                    path,
+                                    format!("Group with id '{}' does not exist.", member_id),
                                 ));
                             }
                             Err(e) => {
@@ -159,10 +160,9 @@ async fn validate_group_members(
                         }
                     }
                     _ => {
-                        return Err(scim_error_response(
-                            StatusCode::BAD_REQUEST,
-                            "invalidValue",
-                            &format!("Invalid member type '{}'.", member_type),
+                        issues.push(ValidationIssue::invalid_value(
+                            format!("members[{}].type", index),
+                            format!("Invalid member type '{}'.", member_type),
                         ));
                     }
                 }
@@ -172,6 +172,21 @@ async fn validate_group_members(
     Ok(())
 }
 
+// Reject the request with every collected validation issue
+fn reject_invalid_group(
+    app_config: &AppConfig,
+    tenant_id: u32,
+    issues: Vec<ValidationIssue>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if issues.is_empty() {
+        return Ok(());
+    }
+    let detailed = app_config
+        .get_effective_compatibility(tenant_id)
+        .detailed_validation_errors;
+    Err(AppError::Validation(issues).to_validation_response(detailed))
+}
+
 // Helper function to apply attribute filtering to groups and create list response
 fn create_filtered_group_list_response(
     groups: Vec<Group>,
@@ -197,28 +212,45 @@ fn create_filtered_group_list_response(
         total_results: total,
         start_index: Some(start_index.unwrap_or(1)),
         items_per_page: Some(filtered_resources.len() as i64),
+        next_start_index: None,
         resources: filtered_resources,
     }
 }
 
 // Multi-tenant handlers with tenant_id extraction and validation
 pub async fn create_group(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
-    ScimJson(payload): ScimJson<serde_json::Value>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
+    ScimJson(mut payload): ScimJson<serde_json::Value>,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
+    // Oversized strings (e.g. a 5 MB displayName) are rejected before anything else
+    if let Err(e) = validate_value_lengths(
+        &payload,
+        app_config
+            .get_effective_compatibility(tenant_id)
+            .max_attribute_value_length,
+    ) {
+        return Err(e.to_response());
+    }
+
+    // Provenance is server-maintained; ignore any the client sends
+    provenance::strip_extension(&mut payload);
+
     // Create a Group from the JSON payload
     let mut group = Group::default();
 
+    // Problems are collected so one 400 reports all of them
+    let mut issues = Vec::new();
+
     // Extract required fields
     if let Some(display_name) = payload.get("displayName").and_then(|v| v.as_str()) {
         group.base.display_name = display_name.to_string();
     } else {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({"message": "displayName is required"})),
+        issues.push(ValidationIssue::invalid_value(
+            "displayName",
+            "displayName is required",
         ));
     }
 
@@ -256,80 +288,130 @@ pub async fn create_group(
     }
 
     // Validate that all group members exist before creating the group
-    validate_group_members(&backend, tenant_id, &group.base.members).await?;
+    validate_group_members(&backend, tenant_id, &group.base.members, &mut issues).await?;
+    reject_invalid_group(&app_config, tenant_id, issues)?;
+
+    match backend
+        .create_group(tenant_id, &group, tenant_info.principal_name.as_deref())
+        .await
+    {
+        Ok(created_group) => {
+            created_group_response(&backend, &app_config, &tenant_info, created_group).await
+        }
+        Err(e) => Err(e.to_response()),
+    }
+}
 
-    match backend.create_group(tenant_id, &group).await {
-        Ok(mut created_group) => {
-            // Set meta.location for SCIM compliance
-            set_group_location(&tenant_info, &mut created_group);
+/// 201 response for a newly created group, with Location and ETag headers
+async fn created_group_response(
+    backend: &Arc<dyn ScimBackend>,
+    app_config: &AppConfig,
+    tenant_info: &TenantInfo,
+    mut created_group: Group,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let tenant_id = tenant_info.tenant_id;
 
-            fix_group_refs(&tenant_info, &mut created_group);
+    // Set meta.location for SCIM compliance
+    set_group_location(tenant_info, &mut created_group);
 
-            // Apply compatibility transformations based on tenant settings
-            let compatibility = app_config.get_effective_compatibility(tenant_id);
-            created_group = crate::utils::convert_group_datetime_for_response(
-                created_group,
-                &compatibility.meta_datetime_format,
-            );
-            created_group = crate::utils::handle_group_empty_members_for_response(
-                created_group,
-                compatibility.show_empty_groups_members,
-            );
+    fix_group_refs(tenant_info, &mut created_group);
 
-            // Build Location header URL
-            let location_url =
-                build_resource_location(&tenant_info, "Groups", &created_group.base.id);
+    // Apply compatibility transformations based on tenant settings
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    created_group = crate::utils::convert_group_datetime_for_response(
+        created_group,
+        &compatibility.meta_datetime_format,
+    );
+    created_group = crate::utils::handle_group_empty_members_for_response(
+        created_group,
+        compatibility.show_empty_groups_members,
+    );
 
-            // Convert to JSON and remove null fields to comply with SCIM specification
-            let group_json = serde_json::to_value(&created_group).map_err(|_| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"message": "Serialization error"})),
-                )
-            })?;
+    // The Location header carries the same URL as meta.location (RFC 7644 3.3)
+    let location_url = match created_group
+        .base
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.location.clone())
+    {
+        Some(location) => location,
+        None => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"message": "Created group missing location"})),
+            ))
+        }
+    };
 
-            let cleaned_group_json = AttributeFilter::remove_null_fields(&group_json);
+    // Convert to JSON and remove null fields to comply with SCIM specification
+    let group_json = serde_json::to_value(&created_group).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"message": "Serialization error"})),
+        )
+    })?;
+
+    let response_filter =
+        AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+    let mut cleaned_group_json =
+        response_filter.apply_to_resource(&group_json, ResourceType::Group);
+
+    provenance::attach(
+        backend.as_ref(),
+        tenant_info,
+        ResourceType::Group,
+        &response_filter,
+        std::slice::from_mut(&mut cleaned_group_json),
+    )
+    .await
+    .map_err(|e| e.to_response())?;
+    member_count::attach(
+        backend.as_ref(),
+        tenant_info.tenant_id,
+        compatibility,
+        &response_filter,
+        std::slice::from_mut(&mut cleaned_group_json),
+    )
+    .await
+    .map_err(|e| e.to_response())?;
+
+    // Create response with Location and ETag headers
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Location",
+        HeaderValue::from_str(&location_url).map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"message": "Invalid location header"})),
+            )
+        })?,
+    );
 
-            // Create response with Location and ETag headers
-            let mut headers = HeaderMap::new();
+    // Add ETag header (Phase 2: ETag response headers)
+    if let Some(ref meta) = created_group.base.meta {
+        if let Some(ref version) = meta.version {
             headers.insert(
-                "Location",
-                HeaderValue::from_str(&location_url).map_err(|_| {
+                "ETag",
+                HeaderValue::from_str(version).map_err(|_| {
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"message": "Invalid location header"})),
+                        Json(serde_json::json!({"message": "Invalid ETag header"})),
                     )
                 })?,
             );
-
-            // Add ETag header (Phase 2: ETag response headers)
-            if let Some(ref meta) = created_group.base.meta {
-                if let Some(ref version) = meta.version {
-                    headers.insert(
-                        "ETag",
-                        HeaderValue::from_str(version).map_err(|_| {
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(serde_json::json!({"message": "Invalid ETag header"})),
-                            )
-                        })?,
-                    );
-                }
-            }
-
-            let mut response = Json(cleaned_group_json).into_response();
-            *response.status_mut() = StatusCode::CREATED;
-            response.headers_mut().extend(headers);
-
-            Ok(response)
         }
-        Err(e) => Err(e.to_response()),
     }
+
+    let mut response = Json(cleaned_group_json).into_response();
+    *response.status_mut() = StatusCode::CREATED;
+    response.headers_mut().extend(headers);
+
+    Ok(response)
 }
 
 pub async fn get_group(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
     Query(params): Query<HashMap<String, String>>,
@@ -351,7 +433,8 @@ pub async fn get_group(
     let attribute_filter = AttributeFilter::from_params(
         params.get("attributes").map(String::as_str),
         params.get("excludedAttributes").map(String::as_str),
-    );
+    )
+    .with_compatibility(app_config.get_effective_compatibility(tenant_id));
 
     match backend.find_group_by_id(tenant_id, &id).await {
         Ok(Some(mut group)) => {
@@ -400,8 +483,26 @@ pub async fn get_group(
                 )
             })?;
 
-            let filtered_group =
+            let mut filtered_group =
                 attribute_filter.apply_to_resource(&group_json, ResourceType::Group);
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::Group,
+                &attribute_filter,
+                std::slice::from_mut(&mut filtered_group),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
+            member_count::attach(
+                backend.as_ref(),
+                tenant_info.tenant_id,
+                compatibility,
+                &attribute_filter,
+                std::slice::from_mut(&mut filtered_group),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
 
             // Build response with ETag header (Phase 2: ETag response headers)
             let mut headers = HeaderMap::new();
@@ -432,105 +533,183 @@ pub async fn get_group(
     }
 }
 
+/// Reject Group filters on attributes the tenant's compatibility turns off
+fn check_group_filter_supported(
+    filter_str: &str,
+    compatibility: &CompatibilityConfig,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    // Check if group members filter is supported
+    if filter_str.contains("members") && !compatibility.support_group_members_filter {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "unsupported",
+            "Filtering Groups by members is not supported",
+        ));
+    }
+
+    // Check if displayName filter is supported
+    if (filter_str.contains("displayName") || filter_str.contains("displayname"))
+        && !compatibility.support_group_displayname_filter
+    {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "unsupported",
+            "Filtering Groups by displayName is not supported",
+        ));
+    }
+
+    Ok(())
+}
+
+/// `GET {base}/Groups/.export`
+///
+/// Streams the tenant's groups, or those matching `filter`, as NDJSON. Only
+/// served when the tenant's compatibility enables `export_endpoints`.
+pub async fn export_groups(
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let compatibility = app_config.get_effective_compatibility(tenant_info.tenant_id);
+    let filter = export_filter(compatibility, &params, ResourceType::Group)?;
+    if let Some(filter_str) = filter_param(&params) {
+        check_group_filter_supported(filter_str, compatibility)?;
+    }
+
+    let attribute_filter = AttributeFilter::from_params(
+        params.get("attributes").map(String::as_str),
+        params.get("excludedAttributes").map(String::as_str),
+    )
+    .with_compatibility(compatibility);
+
+    Ok(groups_ndjson(
+        backend,
+        tenant_info.info,
+        compatibility.clone(),
+        attribute_filter,
+        filter,
+    ))
+}
+
+/// Stream the tenant's groups, or those matching `filter`, as NDJSON
+fn groups_ndjson(
+    backend: Arc<dyn ScimBackend>,
+    tenant_info: TenantInfo,
+    compatibility: CompatibilityConfig,
+    attribute_filter: AttributeFilter,
+    filter: Option<FilterOperator>,
+) -> Response {
+    let tenant_id = tenant_info.tenant_id;
+    // Order by creation time with id as tie-breaker so paging is stable
+    let sort_spec = SortSpec::new("meta.created".to_string(), SortOrder::Ascending);
+    ndjson_response(move |start_index, count| {
+        let backend = backend.clone();
+        let tenant_info = tenant_info.clone();
+        let compatibility = compatibility.clone();
+        let attribute_filter = attribute_filter.clone();
+        let sort_spec = sort_spec.clone();
+        let filter = filter.clone();
+        async move {
+            let (groups, _) = match &filter {
+                Some(filter) => {
+                    backend
+                        .find_groups_by_filter(
+                            tenant_id,
+                            filter,
+                            Some(start_index),
+                            Some(count),
+                            Some(&sort_spec),
+                        )
+                        .await?
+                }
+                None => {
+                    backend
+                        .find_all_groups_sorted(
+                            tenant_id,
+                            Some(start_index),
+                            Some(count),
+                            Some(&sort_spec),
+                        )
+                        .await?
+                }
+            };
+            let mut resources: Vec<serde_json::Value> = groups
+                .into_iter()
+                .map(|mut group| {
+                    set_group_location(&tenant_info, &mut group);
+                    fix_group_refs(&tenant_info, &mut group);
+                    let group = crate::utils::convert_group_datetime_for_response(
+                        group,
+                        &compatibility.meta_datetime_format,
+                    );
+                    let group = crate::utils::handle_group_empty_members_for_response(
+                        group,
+                        compatibility.show_empty_groups_members,
+                    );
+                    let group_json = serde_json::to_value(&group).unwrap_or_default();
+                    attribute_filter.apply_to_resource(&group_json, ResourceType::Group)
+                })
+                .collect();
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::Group,
+                &attribute_filter,
+                &mut resources,
+            )
+            .await?;
+            member_count::attach(
+                backend.as_ref(),
+                tenant_info.tenant_id,
+                &compatibility,
+                &attribute_filter,
+                &mut resources,
+            )
+            .await?;
+            Ok(resources)
+        }
+    })
+}
+
 pub async fn search_groups(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<(StatusCode, Json<ScimListResponse>), (StatusCode, Json<serde_json::Value>)> {
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
-    let filter = params.get("filter").map(String::as_str);
-    let start_index = params.get("startIndex").and_then(|s| s.parse::<i64>().ok());
-    let count = params.get("count").and_then(|s| s.parse::<i64>().ok());
-    let sort_by = params.get("sortBy").cloned();
-    let sort_order = params.get("sortOrder").cloned();
+    let filter = filter_param(&params);
+    let ListParams {
+        start_index,
+        count,
+        sort_by,
+        sort_order,
+    } = ListParams::from_query(&params, app_config.get_effective_compatibility(tenant_id))?;
 
     // Parse attribute filtering parameters
     let attribute_filter = AttributeFilter::from_params(
         params.get("attributes").map(String::as_str),
         params.get("excludedAttributes").map(String::as_str),
-    );
+    )
+    .with_compatibility(app_config.get_effective_compatibility(tenant_id));
 
     // Get compatibility settings for this tenant
     let compatibility = app_config.get_effective_compatibility(tenant_id);
 
-    // Handle filter for user membership: members[value eq "user-id"]
-    if let Some(filter_str) = filter {
-        if filter_str.starts_with("members[value eq ") && filter_str.ends_with("]") {
-            // Check if group members filter is supported
-            if !compatibility.support_group_members_filter {
-                return Err(scim_error_response(
-                    StatusCode::BAD_REQUEST,
-                    "unsupported",
-                    "Filtering Groups by members is not supported",
-                ));
-            }
-            // Extract user ID from filter
-            let start_quote = filter_str.find('"').ok_or_else(|| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({"message": "Invalid filter format"})),
-                )
-            })?;
-            let end_quote = filter_str.rfind('"').ok_or_else(|| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({"message": "Invalid filter format"})),
-                )
-            })?;
-
-            if start_quote >= end_quote {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({"message": "Invalid filter format"})),
-                ));
-            }
-
-            let user_id = &filter_str[start_quote + 1..end_quote];
-
-            // Get groups by user
-            match backend.find_groups_by_user_id(tenant_id, user_id).await {
-                Ok(mut groups) => {
-                    // Set location and fix refs for all groups
-                    for group in &mut groups {
-                        set_group_location(&tenant_info, group);
-                        fix_group_refs(&tenant_info, group);
-                        // Apply compatibility transformations
-                        *group = crate::utils::convert_group_datetime_for_response(
-                            group.clone(),
-                            &compatibility.meta_datetime_format,
-                        );
-                        *group = crate::utils::handle_group_empty_members_for_response(
-                            group.clone(),
-                            compatibility.show_empty_groups_members,
-                        );
-                    }
-                    let total_results = groups.len() as i64;
-                    let response = create_filtered_group_list_response(
-                        groups,
-                        total_results,
-                        start_index,
-                        &attribute_filter,
-                    );
-                    return Ok((StatusCode::OK, Json(response)));
-                }
-                Err(e) => return Err(e.to_response()),
-            }
-        }
+    // NDJSON export: stream every group page by page instead of one ListResponse
+    if wants_ndjson(&tenant_info, &params)? {
+        return Ok(groups_ndjson(
+            backend,
+            tenant_info.info,
+            compatibility.clone(),
+            attribute_filter,
+            None,
+        ));
     }
 
     // Handle general filtering
     if let Some(filter_str) = filter {
-        // Check if displayName filter is supported
-        if (filter_str.contains("displayName") || filter_str.contains("displayname"))
-            && !compatibility.support_group_displayname_filter
-        {
-            return Err(scim_error_response(
-                StatusCode::BAD_REQUEST,
-                "unsupported",
-                "Filtering Groups by displayName is not supported",
-            ));
-        }
+        check_group_filter_supported(filter_str, compatibility)?;
 
         match parse_filter(filter_str) {
             Ok(filter_op) => {
@@ -561,22 +740,41 @@ pub async fn search_groups(
                                 compatibility.show_empty_groups_members,
                             );
                         }
-                        let response = create_filtered_group_list_response(
+                        let mut response = create_filtered_group_list_response(
                             groups,
                             total,
                             start_index,
                             &attribute_filter,
                         );
-                        return Ok((StatusCode::OK, Json(response)));
+                        provenance::attach(
+                            backend.as_ref(),
+                            &tenant_info,
+                            ResourceType::Group,
+                            &attribute_filter,
+                            &mut response.resources,
+                        )
+                        .await
+                        .map_err(|e| e.to_response())?;
+                        member_count::attach(
+                            backend.as_ref(),
+                            tenant_info.tenant_id,
+                            compatibility,
+                            &attribute_filter,
+                            &mut response.resources,
+                        )
+                        .await
+                        .map_err(|e| e.to_response())?;
+                        return Ok(list_response(response, compatibility));
                     }
                     Err(e) => return Err(e.to_response()),
                 }
             }
             Err(e) => {
                 eprintln!("Filter parsing error for '{}': {}", filter_str, e);
-                return Err((
+                return Err(scim_error_response(
                     StatusCode::BAD_REQUEST,
-                    Json(json!({"message": format!("Invalid filter: {}", e)})),
+                    "invalidFilter",
+                    &format!("Invalid filter: {}", e),
                 ));
             }
         }
@@ -609,23 +807,51 @@ pub async fn search_groups(
                     compatibility.show_empty_groups_members,
                 );
             }
-            let response =
+            let mut response =
                 create_filtered_group_list_response(groups, total, start_index, &attribute_filter);
-            Ok((StatusCode::OK, Json(response)))
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::Group,
+                &attribute_filter,
+                &mut response.resources,
+            )
+            .await
+            .map_err(|e| e.to_response())?;
+            member_count::attach(
+                backend.as_ref(),
+                tenant_info.tenant_id,
+                compatibility,
+                &attribute_filter,
+                &mut response.resources,
+            )
+            .await
+            .map_err(|e| e.to_response())?;
+            Ok(list_response(response, compatibility))
         }
         Err(e) => Err(e.to_response()),
     }
 }
 
 pub async fn update_group(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
-    ScimJson(payload): ScimJson<serde_json::Value>,
+    ScimJson(mut payload): ScimJson<serde_json::Value>,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
+    // Oversized strings (e.g. a 5 MB displayName) are rejected before anything else
+    if let Err(e) = validate_value_lengths(
+        &payload,
+        app_config
+            .get_effective_compatibility(tenant_id)
+            .max_attribute_value_length,
+    ) {
+        return Err(e.to_response());
+    }
+
     // Extract group ID from URI
     let id = match extract_resource_id_from_uri(&uri) {
         Some(id) => id,
@@ -637,6 +863,27 @@ pub async fn update_group(
         }
     };
 
+    crate::resource::shared::check_body_id(&payload, &id)?;
+
+    // Provenance is server-maintained; ignore any the client sends
+    provenance::strip_extension(&mut payload);
+
+    // Denied attributes keep their stored values
+    if let Some(permissions) = tenant_info
+        .permissions
+        .as_ref()
+        .filter(|permissions| !permissions.denied_attributes.is_empty())
+    {
+        if let Some(stored) = backend
+            .find_group_by_id(tenant_id, &id)
+            .await
+            .map_err(|e| e.to_response())?
+        {
+            let stored = serde_json::to_value(&stored).unwrap_or_default();
+            crate::permissions::carry_over_denied(permissions, &stored, &mut payload);
+        }
+    }
+
     // Convert JSON payload to Group - similar to create
     let mut group = Group::default();
     group.base.id = id.clone();
@@ -679,7 +926,14 @@ pub async fn update_group(
     }
 
     // Validate that all group members exist before updating the group
-    validate_group_members(&backend, tenant_id, &group.base.members).await?;
+    let mut issues = Vec::new();
+    validate_group_members(&backend, tenant_id, &group.base.members, &mut issues).await?;
+    reject_invalid_group(&app_config, tenant_id, issues)?;
+
+    // Tenants with require_if_match reject unconditional writes
+    if tenant_info.tenant_config.require_if_match && !headers.contains_key("if-match") {
+        return Err(precondition_required_response());
+    }
 
     // Phase 3: Handle conditional requests (If-Match) - Optimistic Concurrency Control
     if let Some(if_match) = headers.get("if-match") {
@@ -704,18 +958,23 @@ pub async fn update_group(
                         }
                     }
                 }
-                Ok(None) => {
-                    return Err((
-                        StatusCode::NOT_FOUND,
-                        Json(json!({"message": "Group not found"})),
-                    ));
-                }
+                // Nothing to compare with; the write below creates the resource
+                // with put_creates_missing and returns 404 otherwise
+                Ok(None) => {}
                 Err(e) => return Err(e.to_response()),
             }
         }
     }
 
-    match backend.update_group(tenant_id, &id, &group).await {
+    match backend
+        .update_group(
+            tenant_id,
+            &id,
+            &group,
+            tenant_info.principal_name.as_deref(),
+        )
+        .await
+    {
         Ok(Some(mut updated_group)) => {
             // Set meta.location for SCIM compliance
             set_group_location(&tenant_info, &mut updated_group);
@@ -741,7 +1000,29 @@ pub async fn update_group(
                 )
             })?;
 
-            let cleaned_group_json = AttributeFilter::remove_null_fields(&group_json);
+            let response_filter =
+                AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+            let mut cleaned_group_json =
+                response_filter.apply_to_resource(&group_json, ResourceType::Group);
+
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::Group,
+                &response_filter,
+                std::slice::from_mut(&mut cleaned_group_json),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
+            member_count::attach(
+                backend.as_ref(),
+                tenant_info.tenant_id,
+                compatibility,
+                &response_filter,
+                std::slice::from_mut(&mut cleaned_group_json),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
 
             // Build response with ETag header (Phase 2: ETag response headers)
             let mut headers = HeaderMap::new();
@@ -764,6 +1045,46 @@ pub async fn update_group(
             response.headers_mut().extend(headers);
             Ok(response)
         }
+        Ok(None)
+            if app_config
+                .get_effective_compatibility(tenant_id)
+                .put_creates_missing =>
+        {
+            // Create-if-missing for clients that allocate ids themselves; the
+            // id must look like one the server would have generated
+            if !crate::utils::is_valid_resource_id(&id) {
+                return Err(scim_error_response(
+                    StatusCode::BAD_REQUEST,
+                    "invalidValue",
+                    &format!(
+                        "Cannot create a Group with id {}: ids are lowercase hyphenated UUIDs",
+                        id
+                    ),
+                ));
+            }
+            // Unlike an update, a create needs everything a POST needs
+            if group.base.display_name.is_empty() {
+                return Err(scim_error_response(
+                    StatusCode::BAD_REQUEST,
+                    "invalidValue",
+                    "displayName is required",
+                ));
+            }
+            match backend
+                .create_group_with_id(
+                    tenant_id,
+                    &id,
+                    &group,
+                    tenant_info.principal_name.as_deref(),
+                )
+                .await
+            {
+                Ok(created_group) => {
+                    created_group_response(&backend, &app_config, &tenant_info, created_group).await
+                }
+                Err(e) => Err(e.to_response()),
+            }
+        }
         Ok(None) => Err((
             StatusCode::NOT_FOUND,
             Json(json!({"message": "Group not found"})),
@@ -773,11 +1094,11 @@ pub async fn update_group(
 }
 
 pub async fn delete_group(
-    State((backend, _)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
-) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
     // Extract group ID from URI
@@ -791,6 +1112,11 @@ pub async fn delete_group(
         }
     };
 
+    // Tenants with require_if_match reject unconditional writes
+    if tenant_info.tenant_config.require_if_match && !headers.contains_key("if-match") {
+        return Err(precondition_required_response());
+    }
+
     // Phase 3: Handle conditional requests (If-Match) - Optimistic Concurrency Control
     if let Some(if_match) = headers.get("if-match") {
         if let Ok(if_match_str) = if_match.to_str() {
@@ -825,8 +1151,61 @@ pub async fn delete_group(
         }
     }
 
-    match backend.delete_group(tenant_id, &id).await {
-        Ok(true) => Ok(StatusCode::NO_CONTENT),
+    // Legacy clients expect the deleted resource back, so read it first,
+    // along with its provenance, which goes with the row
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    let deleted_group = if compatibility.delete_returns_resource {
+        let mut group = match backend.find_group_by_id(tenant_id, &id).await {
+            Ok(Some(group)) => group,
+            Ok(None) => {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    Json(json!({"message": "Group not found"})),
+                ));
+            }
+            Err(e) => return Err(e.to_response()),
+        };
+        set_group_location(&tenant_info, &mut group);
+        fix_group_refs(&tenant_info, &mut group);
+        let group = crate::utils::convert_group_datetime_for_response(
+            group,
+            &compatibility.meta_datetime_format,
+        );
+        let group = crate::utils::handle_group_empty_members_for_response(
+            group,
+            compatibility.show_empty_groups_members,
+        );
+        let group_json = serde_json::to_value(&group).map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"message": "Serialization error"})),
+            )
+        })?;
+        let response_filter =
+            AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+        let mut group_json = response_filter.apply_to_resource(&group_json, ResourceType::Group);
+        provenance::attach(
+            backend.as_ref(),
+            &tenant_info,
+            ResourceType::Group,
+            &response_filter,
+            std::slice::from_mut(&mut group_json),
+        )
+        .await
+        .map_err(|e| e.to_response())?;
+        Some(group_json)
+    } else {
+        None
+    };
+
+    match backend
+        .delete_group(tenant_id, &id, tenant_info.principal_name.as_deref())
+        .await
+    {
+        Ok(true) => Ok(match deleted_group {
+            Some(group_json) => Json(group_json).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        }),
         Ok(false) => Err((
             StatusCode::NOT_FOUND,
             Json(json!({"message": "Group not found"})),
@@ -836,14 +1215,33 @@ pub async fn delete_group(
 }
 
 pub async fn patch_group(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
-    ScimJson(patch_ops): ScimJson<ScimPatchOp>,
+    ScimJson(body): ScimJson<serde_json::Value>,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    if !compatibility.patch_supported {
+        return Err(patch_not_supported_response());
+    }
+    let patch_ops =
+        parse_patch_op(body, compatibility.strict_patch_schema).map_err(|e| e.to_response())?;
+    if patch_ops.operations.len() > compatibility.max_patch_operations as usize {
+        return Err(too_many_patch_operations_response(
+            patch_ops.operations.len(),
+            compatibility.max_patch_operations,
+        ));
+    }
+    if let Err(e) = validate_patch_value_lengths(
+        &patch_ops.operations,
+        compatibility.max_attribute_value_length,
+    ) {
+        return Err(e.to_response());
+    }
+
     // Extract group ID from URI
     let id = match extract_resource_id_from_uri(&uri) {
         Some(id) => id,
@@ -855,6 +1253,11 @@ pub async fn patch_group(
         }
     };
 
+    // Tenants with require_if_match reject unconditional writes
+    if tenant_info.tenant_config.require_if_match && !headers.contains_key("if-match") {
+        return Err(precondition_required_response());
+    }
+
     // Phase 3: Handle conditional requests (If-Match) - Optimistic Concurrency Control
     if let Some(if_match) = headers.get("if-match") {
         if let Ok(if_match_str) = if_match.to_str() {
@@ -889,7 +1292,28 @@ pub async fn patch_group(
         }
     }
 
-    match backend.patch_group(tenant_id, &id, &patch_ops).await {
+    if patch_ops
+        .operations
+        .iter()
+        .any(|op| provenance::targets_extension(op.path.as_deref(), op.value.as_ref()))
+    {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "mutability",
+            "Provenance attributes are readOnly",
+        ));
+    }
+
+    match backend
+        .patch_group(
+            tenant_id,
+            &id,
+            &patch_ops,
+            compatibility,
+            tenant_info.principal_name.as_deref(),
+        )
+        .await
+    {
         Ok(Some(mut group)) => {
             // Set meta.location for SCIM compliance
             set_group_location(&tenant_info, &mut group);
@@ -915,7 +1339,29 @@ pub async fn patch_group(
                 )
             })?;
 
-            let cleaned_group_json = AttributeFilter::remove_null_fields(&group_json);
+            let response_filter =
+                AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+            let mut cleaned_group_json =
+                response_filter.apply_to_resource(&group_json, ResourceType::Group);
+
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::Group,
+                &response_filter,
+                std::slice::from_mut(&mut cleaned_group_json),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
+            member_count::attach(
+                backend.as_ref(),
+                tenant_info.tenant_id,
+                compatibility,
+                &response_filter,
+                std::slice::from_mut(&mut cleaned_group_json),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
 
             // Build response with ETag header (Phase 2: ETag response headers)
             let mut headers = HeaderMap::new();
@@ -0,0 +1,262 @@
+use axum::{
+    extract::{Query, State},
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc};
+
+use super::attribute_filter::AttributeFilter;
+use super::list_response::list_response;
+use super::provenance;
+use super::user::{fix_user_refs, set_user_location};
+use crate::auth::TenantInfo;
+use crate::backend::ScimBackend;
+use crate::config::AppConfig;
+use crate::error::scim_error_response;
+use crate::extractors::{ScimJson, Tenant};
+use crate::models::{ScimListResponse, User};
+use crate::parser::ResourceType;
+use crate::password::PasswordManagerRegistry;
+use crate::schema::should_fetch_external_attributes;
+
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
+
+/// Body of the bulk membership endpoints: `{"ids": ["...", ...]}`
+#[derive(Debug, Deserialize)]
+pub struct MemberIdsRequest {
+    pub ids: Vec<String>,
+}
+
+/// `POST {base}/Groups/{id}/members:add`
+///
+/// Non-standard endpoint for very large groups. Responds with
+/// `{added, already_present, not_found}`.
+pub async fn add_group_members(
+    State((backend, _, _)): State<AppState>,
+    tenant_info: Tenant,
+    uri: Uri,
+    ScimJson(request): ScimJson<MemberIdsRequest>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let id = checked_group_id(&tenant_info, &uri, &request)?;
+
+    match backend
+        .add_group_members(
+            tenant_info.tenant_id,
+            &id,
+            &request.ids,
+            tenant_info.principal_name.as_deref(),
+        )
+        .await
+    {
+        Ok(Some(outcome)) => Ok(Json(outcome).into_response()),
+        Ok(None) => Err(group_not_found()),
+        Err(e) => Err(e.to_response()),
+    }
+}
+
+/// `POST {base}/Groups/{id}/members:remove`
+///
+/// Responds with `{removed, not_members}`.
+pub async fn remove_group_members(
+    State((backend, _, _)): State<AppState>,
+    tenant_info: Tenant,
+    uri: Uri,
+    ScimJson(request): ScimJson<MemberIdsRequest>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let id = checked_group_id(&tenant_info, &uri, &request)?;
+
+    match backend
+        .remove_group_members(
+            tenant_info.tenant_id,
+            &id,
+            &request.ids,
+            tenant_info.principal_name.as_deref(),
+        )
+        .await
+    {
+        Ok(Some(outcome)) => Ok(Json(outcome).into_response()),
+        Ok(None) => Err(group_not_found()),
+        Err(e) => Err(e.to_response()),
+    }
+}
+
+/// `GET {base}/Groups/{id}/effectiveMembers`
+///
+/// Non-standard endpoint listing the users of a group and of the groups
+/// nested below it, each once and ordered by userName. `shape=refs` returns
+/// `{value, display, $ref}` entries instead of User resources.
+pub async fn effective_members(
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let tenant_id = tenant_info.tenant_id;
+    let effective_members = &tenant_info.tenant_config.effective_members;
+    if !effective_members.enabled {
+        // Disabled endpoints look like any other unknown path
+        return Err((StatusCode::NOT_FOUND, Json(json!({"message": "Not found"}))));
+    }
+    let id = group_id_from_path(&uri)?;
+
+    let refs = match params.get("shape").map(String::as_str) {
+        None => false,
+        Some("refs") => true,
+        Some(shape) => {
+            return Err(scim_error_response(
+                StatusCode::BAD_REQUEST,
+                "invalidValue",
+                &format!("Unknown shape '{}', expected 'refs'", shape),
+            ))
+        }
+    };
+    let start_index = params.get("startIndex").and_then(|s| s.parse::<i64>().ok());
+    let count = params.get("count").and_then(|s| s.parse::<i64>().ok());
+
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    let attribute_filter = AttributeFilter::from_params(
+        params.get("attributes").map(String::as_str),
+        params.get("excludedAttributes").map(String::as_str),
+    )
+    .with_compatibility(compatibility);
+    let include_groups = !refs
+        && should_fetch_external_attributes(
+            &attribute_filter,
+            ResourceType::User,
+            app_config.include_user_groups(tenant_id),
+        );
+
+    let (mut users, total) = match backend
+        .find_effective_members(
+            tenant_id,
+            &id,
+            effective_members.max_depth,
+            start_index,
+            count,
+            include_groups,
+        )
+        .await
+    {
+        Ok(Some(page)) => page,
+        Ok(None) => return Err(group_not_found()),
+        Err(e) => return Err(e.to_response()),
+    };
+
+    let mut resources = Vec::with_capacity(users.len());
+    for user in &mut users {
+        set_user_location(&tenant_info, user);
+        fix_user_refs(&tenant_info, user);
+        if refs {
+            resources.push(member_ref(user));
+            continue;
+        }
+        let user = crate::utils::convert_user_datetime_for_response(
+            user.clone(),
+            &compatibility.meta_datetime_format,
+        );
+        let user = crate::utils::handle_user_empty_groups_for_response(
+            user,
+            compatibility.show_empty_groups_members,
+        );
+        let user_json = serde_json::to_value(&user).unwrap_or_default();
+        resources.push(attribute_filter.apply_to_resource(&user_json, ResourceType::User));
+    }
+    if !refs {
+        provenance::attach(
+            backend.as_ref(),
+            &tenant_info,
+            ResourceType::User,
+            &attribute_filter,
+            &mut resources,
+        )
+        .await
+        .map_err(|e| e.to_response())?;
+    }
+
+    let response = ScimListResponse {
+        schemas: vec!["urn:ietf:params:scim:api:messages:2.0:ListResponse".to_string()],
+        total_results: total,
+        start_index: Some(start_index.unwrap_or(1)),
+        items_per_page: Some(resources.len() as i64),
+        next_start_index: None,
+        resources,
+    };
+    Ok(list_response(response, compatibility))
+}
+
+/// `{value, display, $ref}` of a user, as in a group's `members`
+fn member_ref(user: &User) -> Value {
+    let display = user.base.display_name.clone().or_else(|| {
+        let name = user.base.name.as_ref()?;
+        name.formatted.clone().or_else(|| {
+            Some(format!(
+                "{} {}",
+                name.given_name.as_deref()?,
+                name.family_name.as_deref()?
+            ))
+        })
+    });
+    let location = user
+        .base
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.location.clone());
+    json!({
+        "value": user.base.id,
+        "display": display,
+        "$ref": location
+    })
+}
+
+/// Check the tenant allows the endpoints and the batch size, then return
+/// the group id from `.../Groups/{id}/members:<action>`
+fn checked_group_id(
+    tenant_info: &TenantInfo,
+    uri: &Uri,
+    request: &MemberIdsRequest,
+) -> Result<String, (StatusCode, Json<Value>)> {
+    let member_batch = &tenant_info.tenant_config.member_batch;
+    if !member_batch.enabled {
+        // Disabled endpoints look like any other unknown path
+        return Err((StatusCode::NOT_FOUND, Json(json!({"message": "Not found"}))));
+    }
+
+    if request.ids.len() > member_batch.max_batch_size {
+        return Err(scim_error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "invalidValue",
+            &format!(
+                "At most {} ids are accepted per request",
+                member_batch.max_batch_size
+            ),
+        ));
+    }
+
+    group_id_from_path(uri)
+}
+
+/// The group id from `.../Groups/{id}/<action>`
+fn group_id_from_path(uri: &Uri) -> Result<String, (StatusCode, Json<Value>)> {
+    let segments: Vec<&str> = uri.path().split('/').collect();
+    match segments.as_slice() {
+        [.., "Groups", id, _] if !id.is_empty() => Ok(id.to_string()),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"message": "Group ID not found in path"})),
+        )),
+    }
+}
+
+fn group_not_found() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({"message": "Group not found"})),
+    )
+}
@@ -0,0 +1,196 @@
+//! Query parameters shared by the `GET /Users` and `GET /Groups` list handlers
+//!
+//! Per RFC 7644 Section 3.4.2.4 a `startIndex` below 1 is read as 1 and a
+//! negative `count` as 0; those are coerced rather than rejected. Some clients
+//! send numbers such as `10.0`, which are truncated to integers, and an empty
+//! value counts as not sent, so `count=` gets `default_page_size` resources.
+//! The same goes for `filter`: `filter=` or a blank filter lists everything.
+//! Values that are not numbers, or that exceed [`MAX_PAGINATION_VALUE`], get
+//! 400 `invalidValue`, as does a `sortOrder` other than ascending/descending.
+
+use axum::{http::StatusCode, Json};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::config::CompatibilityConfig;
+use crate::error::scim_error_response;
+
+/// Largest accepted `startIndex` or `count`; anything above is a client bug
+pub const MAX_PAGINATION_VALUE: i64 = i32::MAX as i64;
+
+/// Validated paging and sorting parameters of a list request
+#[derive(Debug, Default, PartialEq)]
+pub struct ListParams {
+    pub start_index: Option<i64>,
+    pub count: Option<i64>,
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,
+}
+
+impl ListParams {
+    /// Read the paging and sorting parameters; a missing `count` is
+    /// `compatibility.default_page_size`
+    pub fn from_query(
+        params: &HashMap<String, String>,
+        compatibility: &CompatibilityConfig,
+    ) -> Result<Self, (StatusCode, Json<Value>)> {
+        let start_index = parse_integer(params, "startIndex")?.map(|n| n.max(1));
+        let count = parse_integer(params, "count")?
+            .map(|n| n.max(0))
+            .or(Some(i64::from(compatibility.default_page_size)));
+
+        let sort_order = params.get("sortOrder").cloned();
+        if let Some(order) = &sort_order {
+            if !order.eq_ignore_ascii_case("ascending") && !order.eq_ignore_ascii_case("descending")
+            {
+                return Err(invalid_value(&format!(
+                    "sortOrder must be \"ascending\" or \"descending\", got \"{}\"",
+                    order
+                )));
+            }
+        }
+
+        Ok(Self {
+            start_index,
+            count,
+            sort_by: params.get("sortBy").cloned(),
+            sort_order,
+        })
+    }
+}
+
+/// The `filter` query parameter, or None when it is missing or blank
+pub fn filter_param(params: &HashMap<String, String>) -> Option<&str> {
+    params
+        .get("filter")
+        .map(String::as_str)
+        .filter(|filter| !filter.trim().is_empty())
+}
+
+fn parse_integer(
+    params: &HashMap<String, String>,
+    name: &str,
+) -> Result<Option<i64>, (StatusCode, Json<Value>)> {
+    let Some(raw) = params
+        .get(name)
+        .map(|raw| raw.trim())
+        .filter(|raw| !raw.is_empty())
+    else {
+        return Ok(None);
+    };
+    let value: i64 = match raw.parse::<i64>() {
+        Ok(value) => value,
+        Err(e) => match e.kind() {
+            std::num::IntErrorKind::PosOverflow | std::num::IntErrorKind::NegOverflow => {
+                return Err(invalid_value(&format!("{} is out of range: {}", name, raw)));
+            }
+            // Numbers with a fraction or exponent are truncated
+            _ => match raw.parse::<f64>() {
+                Ok(value) if value.is_finite() => value.trunc() as i64,
+                _ => {
+                    return Err(invalid_value(&format!(
+                        "{} must be a number, got \"{}\"",
+                        name, raw
+                    )))
+                }
+            },
+        },
+    };
+    if value > MAX_PAGINATION_VALUE {
+        return Err(invalid_value(&format!(
+            "{} must not exceed {}, got {}",
+            name, MAX_PAGINATION_VALUE, value
+        )));
+    }
+    Ok(Some(value))
+}
+
+fn invalid_value(detail: &str) -> (StatusCode, Json<Value>) {
+    scim_error_response(StatusCode::BAD_REQUEST, "invalidValue", detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(pairs: &[(&str, &str)]) -> Result<ListParams, (StatusCode, Json<Value>)> {
+        let params = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ListParams::from_query(&params, &CompatibilityConfig::default())
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_coerced() {
+        let params = parse(&[("startIndex", "-5"), ("count", "-1")]).unwrap();
+        assert_eq!(params.start_index, Some(1));
+        assert_eq!(params.count, Some(0));
+    }
+
+    #[test]
+    fn test_numbers_are_truncated() {
+        for (raw, expected) in [
+            ("10", 10),
+            ("10.0", 10),
+            ("10.5", 10),
+            (" 7.99 ", 7),
+            ("1e1", 10),
+        ] {
+            let params = parse(&[("count", raw), ("startIndex", raw)]).unwrap();
+            assert_eq!(params.count, Some(expected), "{}", raw);
+            assert_eq!(params.start_index, Some(expected), "{}", raw);
+        }
+        let params = parse(&[("startIndex", "0.5"), ("count", "-0.5")]).unwrap();
+        assert_eq!(params.start_index, Some(1));
+        assert_eq!(params.count, Some(0));
+    }
+
+    #[test]
+    fn test_missing_or_empty_count_uses_default_page_size() {
+        let compatibility = CompatibilityConfig {
+            default_page_size: 25,
+            ..Default::default()
+        };
+        for pairs in [vec![], vec![("count", "")], vec![("count", "  ")]] {
+            let params = pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            let params = ListParams::from_query(&params, &compatibility).unwrap();
+            assert_eq!(params.count, Some(25));
+            assert_eq!(params.start_index, None);
+        }
+        let params = parse(&[("startIndex", "")]).unwrap();
+        assert_eq!(params.start_index, None);
+    }
+
+    #[test]
+    fn test_malformed_values_are_rejected() {
+        for pairs in [
+            [("count", "abc")],
+            [("count", "NaN")],
+            [("startIndex", "inf")],
+            [("startIndex", "99999999999999999999")],
+            [("count", "2147483648")],
+            [("count", "1e10")],
+            [("sortOrder", "desc")],
+        ] {
+            let (status, Json(body)) = parse(&pairs).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["scimType"], "invalidValue");
+            assert!(body["detail"].as_str().unwrap().contains(pairs[0].0));
+        }
+    }
+
+    #[test]
+    fn test_blank_filter_is_no_filter() {
+        for raw in ["", " ", "\t "] {
+            let params = HashMap::from([("filter".to_string(), raw.to_string())]);
+            assert_eq!(filter_param(&params), None, "{:?}", raw);
+        }
+        assert_eq!(filter_param(&HashMap::new()), None);
+        let params = HashMap::from([("filter".to_string(), "userName pr".to_string())]);
+        assert_eq!(filter_param(&params), Some("userName pr"));
+    }
+}
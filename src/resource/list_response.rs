@@ -0,0 +1,36 @@
+use axum::{
+    http::{HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::config::CompatibilityConfig;
+use crate::models::ScimListResponse;
+
+/// Header carrying totalResults for clients that page from headers
+pub const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
+
+/// 200 response for a list request, with `X-Total-Count` when the tenant
+/// enables `total_count_header` and `nextStartIndex` when it enables
+/// `include_next_start_index` and another page remains
+pub fn list_response(mut list: ScimListResponse, compatibility: &CompatibilityConfig) -> Response {
+    if compatibility.include_next_start_index {
+        list.next_start_index = next_start_index(&list);
+    }
+    let total_results = list.total_results;
+    let mut response = (StatusCode::OK, Json(list)).into_response();
+    if compatibility.total_count_header {
+        response
+            .headers_mut()
+            .insert(TOTAL_COUNT_HEADER, HeaderValue::from(total_results));
+    }
+    response
+}
+
+/// startIndex of the page after this one, or None on the last page
+fn next_start_index(list: &ScimListResponse) -> Option<i64> {
+    let start_index = list.start_index?;
+    let items_per_page = list.items_per_page.filter(|&n| n > 0)?;
+    let next = start_index + items_per_page;
+    (next <= list.total_results).then_some(next)
+}
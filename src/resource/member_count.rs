@@ -0,0 +1,47 @@
+use serde_json::{json, Value};
+
+use super::attribute_filter::AttributeFilter;
+use crate::backend::ScimBackend;
+use crate::config::CompatibilityConfig;
+use crate::error::AppResult;
+
+/// Non-standard top-level Group attribute holding the number of direct
+/// members, returned when the tenant sets `include_member_count`
+pub const MEMBER_COUNT: &str = "memberCount";
+
+/// Add `memberCount` to already filtered Group resources.
+///
+/// The counts come from one `COUNT(*)` query over the listed groups, so
+/// `members` need not be loaded. Does nothing unless the tenant includes
+/// member counts and the attribute filter keeps the attribute.
+pub async fn attach(
+    backend: &dyn ScimBackend,
+    tenant_id: u32,
+    compatibility: &CompatibilityConfig,
+    attribute_filter: &AttributeFilter,
+    resources: &mut [Value],
+) -> AppResult<()> {
+    // Matched like an extension URN: named in `attributes`, or not excluded
+    if !compatibility.include_member_count || !attribute_filter.includes_extension(MEMBER_COUNT) {
+        return Ok(());
+    }
+
+    let ids: Vec<String> = resources
+        .iter()
+        .filter_map(|resource| resource.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    let counts = backend.count_group_members(tenant_id, &ids).await?;
+
+    for resource in resources.iter_mut() {
+        let Some(id) = resource.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let count = counts.get(id).copied().unwrap_or(0);
+        if let Value::Object(obj) = resource {
+            obj.insert(MEMBER_COUNT.to_string(), json!(count));
+        }
+    }
+
+    Ok(())
+}
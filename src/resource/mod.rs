@@ -1,7 +1,32 @@
+#[cfg(feature = "server")]
+pub mod admin;
 pub mod attribute_filter;
+#[cfg(feature = "server")]
 pub mod custom;
+#[cfg(feature = "server")]
+#[cfg(feature = "server")]
 pub mod group;
+#[cfg(feature = "server")]
+pub mod group_members;
+#[cfg(feature = "server")]
+pub mod list_params;
+#[cfg(feature = "server")]
+pub mod list_response;
+#[cfg(feature = "server")]
+pub mod member_count;
+#[cfg(feature = "server")]
+pub mod ndjson;
+#[cfg(feature = "server")]
+pub mod provenance;
+#[cfg(feature = "server")]
 pub mod resource_type;
+#[cfg(feature = "server")]
 pub mod schema;
+#[cfg(feature = "server")]
+pub mod search;
+#[cfg(feature = "server")]
 pub mod service_provider;
+#[cfg(feature = "server")]
+pub mod shared;
+#[cfg(feature = "server")]
 pub mod user;
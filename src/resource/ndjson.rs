@@ -0,0 +1,134 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::stream;
+use serde_json::{json, Value};
+use std::{collections::HashMap, future::Future};
+
+use super::list_params::filter_param;
+use crate::auth::TenantInfo;
+use crate::backend::database::filter::validate_filter_attributes;
+use crate::config::CompatibilityConfig;
+use crate::error::{scim_error_response, AppResult};
+use crate::parser::filter_parser::{parse_filter, FilterOperator};
+use crate::parser::ResourceType;
+
+/// Resources fetched from the backend per round trip while streaming an export
+pub const EXPORT_PAGE_SIZE: i64 = 100;
+
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Returns true when the list request asks for `format=ndjson`.
+///
+/// Fails when the tenant has not enabled exports, when another format is
+/// requested, or when a filter is combined with an export (exports always
+/// cover every resource of the tenant).
+pub fn wants_ndjson(
+    tenant_info: &TenantInfo,
+    params: &HashMap<String, String>,
+) -> Result<bool, (StatusCode, Json<Value>)> {
+    let Some(format) = params.get("format") else {
+        return Ok(false);
+    };
+
+    if !format.eq_ignore_ascii_case("ndjson") {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "invalidValue",
+            &format!("Unsupported format '{}'", format),
+        ));
+    }
+    if !tenant_info.tenant_config.ndjson_export {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "invalidValue",
+            "NDJSON export is not enabled for this tenant",
+        ));
+    }
+    if filter_param(params).is_some() {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "invalidFilter",
+            "filter cannot be combined with format=ndjson",
+        ));
+    }
+
+    Ok(true)
+}
+
+/// Parses the optional `filter` of a `.export` request
+///
+/// Disabled export endpoints look like any other unknown path. The filter is
+/// checked here because errors found once the stream has started can only
+/// abort it.
+pub fn export_filter(
+    compatibility: &CompatibilityConfig,
+    params: &HashMap<String, String>,
+    resource_type: ResourceType,
+) -> Result<Option<FilterOperator>, (StatusCode, Json<Value>)> {
+    if !compatibility.export_endpoints {
+        return Err((StatusCode::NOT_FOUND, Json(json!({"message": "Not found"}))));
+    }
+    let Some(filter_str) = filter_param(params) else {
+        return Ok(None);
+    };
+
+    let filter = parse_filter(filter_str).map_err(|e| {
+        scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "invalidFilter",
+            &format!("Invalid filter: {}", e),
+        )
+    })?;
+    validate_filter_attributes(&filter, resource_type).map_err(|e| e.to_response())?;
+    Ok(Some(filter))
+}
+
+/// Streams resources as newline-delimited JSON, one object per line.
+///
+/// `fetch_page(start_index, count)` is called with 1-based offsets until it
+/// returns a short page, so only one page is held in memory at a time.
+/// Backend errors after the headers have been sent abort the stream.
+pub fn ndjson_response<F, Fut>(fetch_page: F) -> Response
+where
+    F: FnMut(i64, i64) -> Fut + Send + 'static,
+    Fut: Future<Output = AppResult<Vec<Value>>> + Send + 'static,
+{
+    let pages = stream::unfold(
+        (fetch_page, Some(1_i64)),
+        |(mut fetch_page, next_index)| async move {
+            let start_index = next_index?;
+
+            match fetch_page(start_index, EXPORT_PAGE_SIZE).await {
+                Ok(resources) if resources.is_empty() => None,
+                Ok(resources) => {
+                    let fetched = resources.len() as i64;
+                    let mut chunk = Vec::new();
+                    for resource in &resources {
+                        if let Err(e) = serde_json::to_writer(&mut chunk, resource) {
+                            return Some((Err(std::io::Error::other(e)), (fetch_page, None)));
+                        }
+                        chunk.push(b'\n');
+                    }
+
+                    let next_index = (fetched == EXPORT_PAGE_SIZE).then_some(start_index + fetched);
+                    Some((Ok(Bytes::from(chunk)), (fetch_page, next_index)))
+                }
+                Err(e) => Some((
+                    Err(std::io::Error::other(e.to_string())),
+                    (fetch_page, None),
+                )),
+            }
+        },
+    );
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)],
+        Body::from_stream(pages),
+    )
+        .into_response()
+}
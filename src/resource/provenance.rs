@@ -0,0 +1,127 @@
+use serde_json::{json, Value};
+
+use super::attribute_filter::AttributeFilter;
+use crate::auth::TenantInfo;
+use crate::backend::ScimBackend;
+use crate::error::AppResult;
+use crate::parser::ResourceType;
+
+/// Extension schema carrying who created and last modified a resource.
+/// Both sub-attributes are readOnly and only returned when the tenant sets
+/// `expose_provenance`.
+pub const PROVENANCE_SCHEMA: &str = "urn:scim-server:scim:extension:provenance";
+
+/// Schema resource advertised on /Schemas for tenants exposing provenance
+pub fn schema_resource() -> Value {
+    let attribute = |name: &str, description: &str| {
+        json!({
+            "name": name,
+            "type": "string",
+            "multiValued": false,
+            "description": description,
+            "required": false,
+            "caseExact": true,
+            "mutability": "readOnly",
+            "returned": "default",
+            "uniqueness": "none"
+        })
+    };
+
+    json!({
+        "id": PROVENANCE_SCHEMA,
+        "name": "Provenance",
+        "description": "Principals that created and last modified the resource",
+        "attributes": [
+            attribute("createdBy", "Principal that created the resource"),
+            attribute("lastModifiedBy", "Principal behind the most recent write"),
+        ],
+        "meta": {
+            "resourceType": "Schema",
+            "location": PROVENANCE_SCHEMA
+        }
+    })
+}
+
+/// Add the provenance extension to already filtered resources.
+///
+/// Does nothing unless the tenant exposes provenance and the attribute
+/// filter keeps the extension.
+pub async fn attach(
+    backend: &dyn ScimBackend,
+    tenant_info: &TenantInfo,
+    resource_type: ResourceType,
+    attribute_filter: &AttributeFilter,
+    resources: &mut [Value],
+) -> AppResult<()> {
+    if !tenant_info.tenant_config.expose_provenance
+        || !attribute_filter.includes_extension(PROVENANCE_SCHEMA)
+    {
+        return Ok(());
+    }
+
+    let ids: Vec<String> = resources
+        .iter()
+        .filter_map(|resource| resource.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    let provenance = backend
+        .find_provenance(tenant_info.tenant_id, resource_type, &ids)
+        .await?;
+
+    for resource in resources.iter_mut() {
+        let Some(entry) = resource
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| provenance.get(id))
+        else {
+            continue;
+        };
+        let Value::Object(obj) = resource else {
+            continue;
+        };
+
+        let mut extension = serde_json::Map::new();
+        if let Some(ref created_by) = entry.created_by {
+            extension.insert("createdBy".to_string(), json!(created_by));
+        }
+        if let Some(ref last_modified_by) = entry.last_modified_by {
+            extension.insert("lastModifiedBy".to_string(), json!(last_modified_by));
+        }
+        if extension.is_empty() {
+            continue;
+        }
+
+        obj.insert(PROVENANCE_SCHEMA.to_string(), Value::Object(extension));
+        if let Some(Value::Array(schemas)) = obj.get_mut("schemas") {
+            if !schemas.iter().any(|s| s == PROVENANCE_SCHEMA) {
+                schemas.push(json!(PROVENANCE_SCHEMA));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a PATCH operation path or value tries to write the extension
+pub fn targets_extension(path: Option<&str>, value: Option<&Value>) -> bool {
+    let names_extension = |name: &str| {
+        name.get(..PROVENANCE_SCHEMA.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(PROVENANCE_SCHEMA))
+    };
+
+    match path {
+        Some(path) => names_extension(path),
+        None => matches!(value, Some(Value::Object(obj)) if obj.keys().any(|k| names_extension(k))),
+    }
+}
+
+/// Drop client-supplied provenance from a POST or PUT body; it is readOnly
+pub fn strip_extension(payload: &mut Value) {
+    let Value::Object(obj) = payload else {
+        return;
+    };
+    obj.retain(|key, _| !targets_extension(Some(key), None));
+    if let Some(Value::Array(schemas)) = obj.get_mut("schemas") {
+        schemas.retain(|s| !s.as_str().is_some_and(|s| targets_extension(Some(s), None)));
+    }
+}
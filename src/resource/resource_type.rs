@@ -9,53 +9,69 @@ use std::sync::Arc;
 use crate::auth::TenantInfo;
 use crate::backend::ScimBackend;
 use crate::config::AppConfig;
-use crate::schema::SCIM_SCHEMA_ENTERPRISE_USER;
+use crate::models::ScimListResponse;
+use crate::parser::ResourceType;
+use crate::password::PasswordManagerRegistry;
+use crate::schema::{SCIM_API_MESSAGES_LIST_RESPONSE, SCIM_SCHEMA_ENTERPRISE_USER};
 
-type AppState = (Arc<dyn ScimBackend>, Arc<AppConfig>);
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
 
 pub async fn resource_types(
-    State((_storage, _)): State<AppState>,
+    State((_storage, _, _)): State<AppState>,
     Extension(tenant_info): Extension<TenantInfo>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+) -> Result<(StatusCode, Json<ScimListResponse>), (StatusCode, Json<Value>)> {
     let _tenant_id = tenant_info.tenant_id;
 
-    let resources = json!({
-        "schemas": ["urn:ietf:params:scim:api:messages:2.0:ListResponse"],
-        "totalResults": 2,
-        "Resources": [
-            {
-                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ResourceType"],
-                "id": "User",
-                "name": "User",
-                "endpoint": "/Users",
-                "description": "User Account",
-                "schema": "urn:ietf:params:scim:schemas:core:2.0:User",
-                "schemaExtensions": [
-                    {
-                        "schema": SCIM_SCHEMA_ENTERPRISE_USER,
-                        "required": false
-                    }
-                ],
-                "meta": {
-                    "resourceType": "ResourceType",
-                    "location": "urn:ietf:params:scim:schemas:core:2.0:User"
-                }
-            },
-            {
-                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ResourceType"],
-                "id": "Group",
-                "name": "Group",
-                "endpoint": "/Groups",
-                "description": "Group",
-                "schema": "urn:ietf:params:scim:schemas:core:2.0:Group",
-                "schemaExtensions": [],
-                "meta": {
-                    "resourceType": "ResourceType",
-                    "location": "urn:ietf:params:scim:schemas:core:2.0:Group"
+    let tenant_config = &tenant_info.tenant_config;
+    let mut resources = Vec::new();
+    if tenant_config.resource_enabled(ResourceType::User) {
+        resources.push(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ResourceType"],
+            "id": "User",
+            "name": "User",
+            "endpoint": format!("/{}", tenant_config.users_path()),
+            "description": "User Account",
+            "schema": "urn:ietf:params:scim:schemas:core:2.0:User",
+            "schemaExtensions": [
+                {
+                    "schema": SCIM_SCHEMA_ENTERPRISE_USER,
+                    "required": false
                 }
+            ],
+            "meta": {
+                "resourceType": "ResourceType",
+                "location": "urn:ietf:params:scim:schemas:core:2.0:User"
+            }
+        }));
+    }
+    if tenant_config.resource_enabled(ResourceType::Group) {
+        resources.push(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:ResourceType"],
+            "id": "Group",
+            "name": "Group",
+            "endpoint": format!("/{}", tenant_config.groups_path()),
+            "description": "Group",
+            "schema": "urn:ietf:params:scim:schemas:core:2.0:Group",
+            "schemaExtensions": [],
+            "meta": {
+                "resourceType": "ResourceType",
+                "location": "urn:ietf:params:scim:schemas:core:2.0:Group"
             }
-        ]
-    });
+        }));
+    }
+
+    let list_response = ScimListResponse {
+        schemas: vec![SCIM_API_MESSAGES_LIST_RESPONSE.to_string()],
+        total_results: resources.len() as i64,
+        start_index: None,
+        items_per_page: None,
+        next_start_index: None,
+        resources,
+    };
 
-    Ok((StatusCode::OK, Json(resources)))
+    Ok((StatusCode::OK, Json(list_response)))
 }
@@ -9,12 +9,20 @@ use std::sync::Arc;
 use crate::auth::TenantInfo;
 use crate::backend::ScimBackend;
 use crate::config::AppConfig;
+use crate::models::ScimListResponse;
+use crate::parser::ResourceType;
+use crate::password::PasswordManagerRegistry;
 use crate::schema::{
     get_all_schemas, AttributeType, Mutability, Returned, Uniqueness,
-    SCIM_API_MESSAGES_LIST_RESPONSE,
+    SCIM_API_MESSAGES_LIST_RESPONSE, SCIM_SCHEMA_CORE_GROUP, SCIM_SCHEMA_CORE_USER,
+    SCIM_SCHEMA_ENTERPRISE_USER,
 };
 
-type AppState = (Arc<dyn ScimBackend>, Arc<AppConfig>);
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
 
 // Convert AttributeType to JSON string representation
 fn attribute_type_to_string(attr_type: &AttributeType) -> &'static str {
@@ -26,6 +34,7 @@ fn attribute_type_to_string(attr_type: &AttributeType) -> &'static str {
         AttributeType::DateTime => "dateTime",
         AttributeType::Reference => "reference",
         AttributeType::Complex => "complex",
+        AttributeType::Binary => "binary",
     }
 }
 
@@ -114,9 +123,9 @@ fn build_attribute_json(attr: &crate::schema::AttributeDefinition) -> Value {
 }
 
 pub async fn schemas(
-    State((_storage, _)): State<AppState>,
+    State((_storage, _, _)): State<AppState>,
     Extension(tenant_info): Extension<TenantInfo>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+) -> Result<(StatusCode, Json<ScimListResponse>), (StatusCode, Json<Value>)> {
     let _tenant_id = tenant_info.tenant_id;
 
     // Get all schemas from the centralized schema module
@@ -125,7 +134,19 @@ pub async fn schemas(
     // Build schema resources
     let mut resources = Vec::new();
 
+    let tenant_config = &tenant_info.tenant_config;
     for schema_def in all_schemas {
+        // Schemas of resource types the tenant does not serve are left out
+        let resource_type = match schema_def.id {
+            SCIM_SCHEMA_CORE_USER | SCIM_SCHEMA_ENTERPRISE_USER => Some(ResourceType::User),
+            SCIM_SCHEMA_CORE_GROUP => Some(ResourceType::Group),
+            _ => None,
+        };
+        if resource_type.is_some_and(|resource_type| !tenant_config.resource_enabled(resource_type))
+        {
+            continue;
+        }
+
         let attributes: Vec<Value> = schema_def
             .attributes
             .iter()
@@ -144,6 +165,10 @@ pub async fn schemas(
         }));
     }
 
+    if tenant_info.tenant_config.expose_provenance {
+        resources.push(super::provenance::schema_resource());
+    }
+
     // Add ServiceProviderConfig schema (this is not a resource schema but a configuration schema)
     resources.push(json!({
         "id": "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig",
@@ -359,13 +384,14 @@ pub async fn schemas(
         }
     }));
 
-    let schemas = json!({
-        "schemas": [SCIM_API_MESSAGES_LIST_RESPONSE],
-        "totalResults": resources.len(),
-        "startIndex": 1,
-        "itemsPerPage": resources.len(),
-        "Resources": resources
-    });
+    let schemas = ScimListResponse {
+        schemas: vec![SCIM_API_MESSAGES_LIST_RESPONSE.to_string()],
+        total_results: resources.len() as i64,
+        start_index: Some(1),
+        items_per_page: Some(resources.len() as i64),
+        next_start_index: None,
+        resources,
+    };
 
     Ok((StatusCode::OK, Json(schemas)))
 }
@@ -0,0 +1,319 @@
+//! Root `POST {base_path}/.search` across Users and Groups (RFC 7644 §3.4.3)
+//!
+//! The combined result lists every matching User before every matching
+//! Group. `startIndex` and `count` page through that combined list, and
+//! `sortBy` orders the resources within each type.
+
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::attribute_filter::AttributeFilter;
+use super::group::{fix_group_refs, set_group_location};
+use super::list_response::list_response;
+use super::user::{fix_user_refs, set_user_location};
+use super::{member_count, provenance};
+use crate::auth::TenantInfo;
+use crate::backend::database::filter::validate_filter_attributes;
+use crate::backend::database::list_query::page_bounds;
+use crate::backend::ScimBackend;
+use crate::config::AppConfig;
+use crate::error::{scim_error_response, AppResult};
+use crate::extractors::ScimJson;
+use crate::models::{Group, ScimListResponse, User};
+use crate::parser::filter_parser::{parse_filter, FilterOperator};
+use crate::parser::{ResourceType, SortSpec};
+use crate::password::PasswordManagerRegistry;
+use crate::schema::should_fetch_external_attributes;
+
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
+
+pub const SEARCH_REQUEST_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:SearchRequest";
+
+/// Body of a `.search` request
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    pub filter: Option<String>,
+    pub start_index: Option<i64>,
+    pub count: Option<i64>,
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,
+    pub attributes: Option<Vec<String>>,
+    pub excluded_attributes: Option<Vec<String>>,
+}
+
+/// Remove the top-level `meta.resourceType eq "<type>"` conjuncts of `filter`
+///
+/// The requested type names are pushed onto `types`; what remains of the
+/// filter is returned, or `None` when nothing does. Predicates nested under
+/// `or` or `not` are left in place for the backends to evaluate.
+fn split_resource_type(filter: FilterOperator, types: &mut Vec<String>) -> Option<FilterOperator> {
+    match filter {
+        FilterOperator::And(left, right) => {
+            let left = split_resource_type(*left, types);
+            let right = split_resource_type(*right, types);
+            match (left, right) {
+                (Some(left), Some(right)) => {
+                    Some(FilterOperator::And(Box::new(left), Box::new(right)))
+                }
+                (left, right) => left.or(right),
+            }
+        }
+        FilterOperator::Equal(attr, Value::String(name))
+            if attr.eq_ignore_ascii_case("meta.resourceType") =>
+        {
+            types.push(name);
+            None
+        }
+        filter => Some(filter),
+    }
+}
+
+async fn search_users_page(
+    backend: &dyn ScimBackend,
+    tenant_id: u32,
+    filter: Option<&FilterOperator>,
+    start_index: i64,
+    count: i64,
+    sort_spec: Option<&SortSpec>,
+    include_groups: bool,
+) -> AppResult<(Vec<User>, i64)> {
+    match filter {
+        Some(filter) => {
+            backend
+                .find_users_by_filter(
+                    tenant_id,
+                    filter,
+                    Some(start_index),
+                    Some(count),
+                    sort_spec,
+                    include_groups,
+                )
+                .await
+        }
+        None => {
+            backend
+                .find_all_users_sorted(
+                    tenant_id,
+                    Some(start_index),
+                    Some(count),
+                    sort_spec,
+                    include_groups,
+                )
+                .await
+        }
+    }
+}
+
+async fn search_groups_page(
+    backend: &dyn ScimBackend,
+    tenant_id: u32,
+    filter: Option<&FilterOperator>,
+    start_index: i64,
+    count: i64,
+    sort_spec: Option<&SortSpec>,
+) -> AppResult<(Vec<Group>, i64)> {
+    match filter {
+        Some(filter) => {
+            backend
+                .find_groups_by_filter(tenant_id, filter, Some(start_index), Some(count), sort_spec)
+                .await
+        }
+        None => {
+            backend
+                .find_all_groups_sorted(tenant_id, Some(start_index), Some(count), sort_spec)
+                .await
+        }
+    }
+}
+
+pub async fn search_resources(
+    State((backend, app_config, _)): State<AppState>,
+    Extension(tenant_info): Extension<TenantInfo>,
+    ScimJson(request): ScimJson<SearchRequest>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let tenant_id = tenant_info.tenant_id;
+
+    if !request.schemas.iter().any(|s| s == SEARCH_REQUEST_SCHEMA) {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "invalidSyntax",
+            &format!("schemas must contain {}", SEARCH_REQUEST_SCHEMA),
+        ));
+    }
+
+    // A blank filter matches everything, as with `GET ?filter=`
+    let filter = match request.filter.as_deref().filter(|f| !f.trim().is_empty()) {
+        Some(filter_str) => Some(parse_filter(filter_str).map_err(|e| {
+            scim_error_response(
+                StatusCode::BAD_REQUEST,
+                "invalidFilter",
+                &format!("Invalid filter: {}", e),
+            )
+        })?),
+        None => None,
+    };
+    // `meta.resourceType eq` conjuncts pick the resource types to query and
+    // are not passed on to the backends
+    let mut requested_types = Vec::new();
+    let filter = filter.and_then(|filter| split_resource_type(filter, &mut requested_types));
+    // Resource types the tenant does not serve are never searched
+    let requested = |resource_type: ResourceType| {
+        tenant_info.tenant_config.resource_enabled(resource_type)
+            && requested_types
+                .iter()
+                .all(|name| name == resource_type.scim_name())
+    };
+    // A filter on attributes only one resource type defines matches none of
+    // the other; it is invalid only when neither type defines them
+    let defined_for = |resource_type| {
+        filter.as_ref().map_or(Ok(()), |filter| {
+            validate_filter_attributes(filter, resource_type)
+        })
+    };
+    let (search_users, search_groups) = match (
+        defined_for(ResourceType::User),
+        defined_for(ResourceType::Group),
+    ) {
+        (Err(e), Err(_)) => return Err(e.to_response()),
+        (users, groups) => (
+            users.is_ok() && requested(ResourceType::User),
+            groups.is_ok() && requested(ResourceType::Group),
+        ),
+    };
+    let sort_spec =
+        SortSpec::from_params(request.sort_by.as_deref(), request.sort_order.as_deref());
+
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    let attribute_filter = AttributeFilter::from_params(
+        request.attributes.map(|a| a.join(",")).as_deref(),
+        request.excluded_attributes.map(|a| a.join(",")).as_deref(),
+    )
+    .with_compatibility(compatibility);
+    let include_groups = should_fetch_external_attributes(
+        &attribute_filter,
+        ResourceType::User,
+        app_config.include_user_groups(tenant_id),
+    );
+
+    // The page is cut from Users first; whatever room is left is filled
+    // with Groups, starting after the Groups earlier pages already showed
+    let count = request
+        .count
+        .or(Some(i64::from(compatibility.default_page_size)));
+    let (limit, offset) = page_bounds(request.start_index, count);
+    let (users, user_total) = if search_users {
+        search_users_page(
+            backend.as_ref(),
+            tenant_id,
+            filter.as_ref(),
+            offset + 1,
+            limit,
+            sort_spec.as_ref(),
+            include_groups,
+        )
+        .await
+        .map_err(|e| e.to_response())?
+    } else {
+        (Vec::new(), 0)
+    };
+    let group_offset = offset.saturating_sub(user_total);
+    let group_limit = limit - users.len() as i64;
+    let (groups, group_total) = if search_groups {
+        search_groups_page(
+            backend.as_ref(),
+            tenant_id,
+            filter.as_ref(),
+            group_offset + 1,
+            group_limit,
+            sort_spec.as_ref(),
+        )
+        .await
+        .map_err(|e| e.to_response())?
+    } else {
+        (Vec::new(), 0)
+    };
+
+    let mut user_resources: Vec<Value> = users
+        .into_iter()
+        .map(|mut user| {
+            set_user_location(&tenant_info, &mut user);
+            fix_user_refs(&tenant_info, &mut user);
+            let user = crate::utils::convert_user_datetime_for_response(
+                user,
+                &compatibility.meta_datetime_format,
+            );
+            let user = crate::utils::handle_user_empty_groups_for_response(
+                user,
+                compatibility.show_empty_groups_members,
+            );
+            let user_json = serde_json::to_value(&user).unwrap_or_default();
+            attribute_filter.apply_to_resource(&user_json, ResourceType::User)
+        })
+        .collect();
+    let mut group_resources: Vec<Value> = groups
+        .into_iter()
+        .map(|mut group| {
+            set_group_location(&tenant_info, &mut group);
+            fix_group_refs(&tenant_info, &mut group);
+            let group = crate::utils::convert_group_datetime_for_response(
+                group,
+                &compatibility.meta_datetime_format,
+            );
+            let group = crate::utils::handle_group_empty_members_for_response(
+                group,
+                compatibility.show_empty_groups_members,
+            );
+            let group_json = serde_json::to_value(&group).unwrap_or_default();
+            attribute_filter.apply_to_resource(&group_json, ResourceType::Group)
+        })
+        .collect();
+    for (resource_type, resources) in [
+        (ResourceType::User, &mut user_resources),
+        (ResourceType::Group, &mut group_resources),
+    ] {
+        provenance::attach(
+            backend.as_ref(),
+            &tenant_info,
+            resource_type,
+            &attribute_filter,
+            resources,
+        )
+        .await
+        .map_err(|e| e.to_response())?;
+    }
+    member_count::attach(
+        backend.as_ref(),
+        tenant_id,
+        compatibility,
+        &attribute_filter,
+        &mut group_resources,
+    )
+    .await
+    .map_err(|e| e.to_response())?;
+
+    let mut resources = user_resources;
+    resources.append(&mut group_resources);
+    let response = ScimListResponse {
+        schemas: vec!["urn:ietf:params:scim:api:messages:2.0:ListResponse".to_string()],
+        total_results: user_total + group_total,
+        start_index: Some(offset + 1),
+        items_per_page: Some(resources.len() as i64),
+        next_start_index: None,
+        resources,
+    };
+    Ok(list_response(response, compatibility))
+}
@@ -3,18 +3,38 @@ use axum::{
     http::StatusCode,
     Json,
 };
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
 use scim_v2::models::{
     scim_schema::Meta,
     service_provider_config::{
         AuthenticationScheme, Bulk, Filter, ServiceProviderConfig, Supported,
     },
 };
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 use std::sync::Arc;
 
 use crate::auth::TenantInfo;
 use crate::backend::ScimBackend;
-use crate::config::AppConfig;
+use crate::config::{AppConfig, CompatibilityConfig};
+use crate::password::PasswordManagerRegistry;
+
+/// documentationUri for tenants that do not configure their own
+const DEFAULT_DOCUMENTATION_URI: &str = env!("CARGO_PKG_REPOSITORY");
+
+/// Non-standard extension describing this server (`expose_backend_info`)
+pub const SCIM_SERVER_EXTENSION: &str = "urn:scim-server:extension:1.0:ServiceProviderConfig";
+
+lazy_static! {
+    /// meta.created and meta.lastModified of every ServiceProviderConfig;
+    /// the document is built from configuration read at startup
+    static ref STARTED_AT: DateTime<Utc> = Utc::now();
+}
+
+/// Fix the ServiceProviderConfig timestamps to the server start time
+pub fn record_start_time() {
+    lazy_static::initialize(&STARTED_AT);
+}
 
 /// Create authentication schemes for a specific tenant
 fn create_authentication_schemes_for_tenant(tenant_info: &TenantInfo) -> Vec<AuthenticationScheme> {
@@ -71,24 +91,28 @@ fn create_authentication_schemes_for_tenant(tenant_info: &TenantInfo) -> Vec<Aut
     }
 }
 
-type AppState = (Arc<dyn ScimBackend>, Arc<AppConfig>);
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
 
 pub async fn service_provider_config(
-    State((_storage, _)): State<AppState>,
+    State((_storage, app_config, _)): State<AppState>,
     Extension(tenant_info): Extension<TenantInfo>,
-) -> Result<(StatusCode, Json<ServiceProviderConfig>), (StatusCode, Json<Value>)> {
-    let _tenant_id = tenant_info.tenant_id;
-
-    // Get the correct path from tenant configuration
-    let tenant_path = tenant_info
-        .tenant_config
-        .path
-        .trim_end_matches('/')
-        .to_string();
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let provider = &tenant_info.tenant_config.service_provider;
 
     // Create auth schemes based on the specific tenant
     let auth_schemes = create_authentication_schemes_for_tenant(&tenant_info);
 
+    let compatibility = app_config.get_effective_compatibility(tenant_info.tenant_id);
+    let started_at = if compatibility.meta_datetime_format == "epoch" {
+        crate::utils::format_epoch_datetime(*STARTED_AT).to_string()
+    } else {
+        crate::utils::format_scim_datetime(*STARTED_AT)
+    };
+
     let config = ServiceProviderConfig {
         authentication_schemes: auth_schemes,
         bulk: Bulk {
@@ -97,7 +121,12 @@ pub async fn service_provider_config(
             max_payload_size: 0,
         },
         change_password: Supported { supported: true },
-        documentation_uri: Some("https://github.com/wadahiro/scim-server".to_string()),
+        documentation_uri: Some(
+            provider
+                .documentation_uri
+                .clone()
+                .unwrap_or_else(|| DEFAULT_DOCUMENTATION_URI.to_string()),
+        ),
         etag: Supported { supported: false },
         filter: Filter {
             supported: true,
@@ -105,17 +134,83 @@ pub async fn service_provider_config(
         },
         meta: Some(Meta {
             resource_type: Some("ServiceProviderConfig".to_string()),
-            created: None,
-            last_modified: None,
+            created: Some(started_at.clone()),
+            last_modified: Some(started_at),
+            // base_path already carries the tenant path
             location: Some(format!(
-                "{}{}/ServiceProviderConfig",
-                tenant_info.base_path, tenant_path
+                "{}/ServiceProviderConfig",
+                tenant_info.base_path.trim_end_matches('/')
             )),
             version: None,
         }),
-        patch: Supported { supported: true },
+        patch: Supported {
+            supported: compatibility.patch_supported,
+        },
         sort: Supported { supported: true },
     };
 
+    let mut config = serde_json::to_value(config).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"message": "Serialization error"})),
+        )
+    })?;
+
+    // Descriptive attributes outside RFC 7643; supportEmail only when configured
+    if let Value::Object(ref mut obj) = config {
+        obj.insert(
+            "name".to_string(),
+            json!(provider.name.as_deref().unwrap_or(env!("CARGO_PKG_NAME"))),
+        );
+        obj.insert(
+            "description".to_string(),
+            json!(provider
+                .description
+                .as_deref()
+                .unwrap_or(env!("CARGO_PKG_DESCRIPTION"))),
+        );
+        if let Some(ref support_email) = provider.support_email {
+            obj.insert("supportEmail".to_string(), json!(support_email));
+        }
+
+        if compatibility.expose_backend_info {
+            obj.insert(
+                SCIM_SERVER_EXTENSION.to_string(),
+                backend_info(&app_config, &tenant_info, compatibility),
+            );
+        }
+    }
+
     Ok((StatusCode::OK, Json(config)))
 }
+
+/// Backend, server version and enabled compatibility flags of a tenant
+fn backend_info(
+    app_config: &AppConfig,
+    tenant_info: &TenantInfo,
+    compatibility: &CompatibilityConfig,
+) -> Value {
+    let backend_type = app_config
+        .backend
+        .database
+        .as_ref()
+        .map_or(app_config.backend.backend_type.as_str(), |database| {
+            database.db_type.as_str()
+        });
+
+    let mut feature_flags = Map::new();
+    if let Ok(Value::Object(flags)) = serde_json::to_value(compatibility) {
+        for (name, value) in flags {
+            if value == Value::Bool(true) {
+                feature_flags.insert(name, value);
+            }
+        }
+    }
+
+    json!({
+        "backendType": backend_type,
+        "version": env!("CARGO_PKG_VERSION"),
+        "tenantId": tenant_info.tenant_id,
+        "featureFlags": feature_flags
+    })
+}
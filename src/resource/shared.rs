@@ -0,0 +1,129 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::backend::ScimBackend;
+use crate::config::AppConfig;
+use crate::error::scim_error_response;
+use crate::parser::ResourceType;
+use crate::password::PasswordManagerRegistry;
+use crate::resource::attribute_filter::AttributeFilter;
+use crate::resource::group::{fix_group_refs, set_group_location};
+use crate::resource::user::{fix_user_refs, set_user_location};
+
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
+
+fn not_found() -> (StatusCode, Json<Value>) {
+    (StatusCode::NOT_FOUND, Json(json!({"message": "Not found"})))
+}
+
+/// `GET /shared/{token}`
+///
+/// Serves the user or group a share link was issued for, without tenant
+/// credentials. The token is the authorization: a bad signature, an expired
+/// token, a removed tenant or resource, or share links being disabled all
+/// look the same (404). The response is the resource as a plain GET would
+/// return it, never cached.
+pub async fn get_shared_resource(
+    State((backend, app_config, _)): State<AppState>,
+    Path(token): Path<String>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let secret = app_config
+        .server
+        .share_link_secret
+        .as_deref()
+        .ok_or_else(not_found)?;
+    let shared = crate::share::verify(secret, &token, chrono::Utc::now().timestamp())
+        .ok_or_else(not_found)?;
+    let tenant = app_config
+        .tenants
+        .iter()
+        .find(|t| t.id == shared.tenant)
+        .ok_or_else(not_found)?;
+
+    let tenant_info = crate::auth::unauthenticated_tenant_info(&app_config, tenant, &uri, &headers);
+    let compatibility = app_config.get_effective_compatibility(tenant.id);
+    let attribute_filter =
+        AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+
+    let resource = match shared.resource_type().ok_or_else(not_found)? {
+        ResourceType::User => {
+            let mut user = backend
+                .find_user_by_id(
+                    tenant.id,
+                    &shared.id,
+                    app_config.include_user_groups(tenant.id),
+                )
+                .await
+                .map_err(|e| e.to_response())?
+                .ok_or_else(not_found)?;
+            *user.password_mut() = None;
+            set_user_location(&tenant_info, &mut user);
+            fix_user_refs(&tenant_info, &mut user);
+            user = crate::utils::convert_user_datetime_for_response(
+                user,
+                &compatibility.meta_datetime_format,
+            );
+            user = crate::utils::handle_user_empty_groups_for_response(
+                user,
+                compatibility.show_empty_groups_members,
+            );
+            let user_json = serde_json::to_value(&user)
+                .map_err(|e| crate::error::AppError::Serialization(e).to_response())?;
+            attribute_filter.apply_to_resource(&user_json, ResourceType::User)
+        }
+        ResourceType::Group => {
+            let mut group = backend
+                .find_group_by_id(tenant.id, &shared.id)
+                .await
+                .map_err(|e| e.to_response())?
+                .ok_or_else(not_found)?;
+            set_group_location(&tenant_info, &mut group);
+            fix_group_refs(&tenant_info, &mut group);
+            group = crate::utils::convert_group_datetime_for_response(
+                group,
+                &compatibility.meta_datetime_format,
+            );
+            group = crate::utils::handle_group_empty_members_for_response(
+                group,
+                compatibility.show_empty_groups_members,
+            );
+            let group_json = serde_json::to_value(&group)
+                .map_err(|e| crate::error::AppError::Serialization(e).to_response())?;
+            attribute_filter.apply_to_resource(&group_json, ResourceType::Group)
+        }
+    };
+
+    let mut response = Json(resource).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    Ok(response)
+}
+
+/// Reject a PUT body whose `id` differs from the resource id in the URL
+///
+/// The URL identifies the resource and id is read-only, so a different id is
+/// a `mutability` error rather than silently ignored. A missing or null id
+/// is accepted.
+pub fn check_body_id(payload: &Value, id: &str) -> Result<(), (StatusCode, Json<Value>)> {
+    match payload.get("id").filter(|v| !v.is_null()) {
+        Some(body_id) if body_id.as_str() != Some(id) => Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "mutability",
+            "id in request body does not match the resource id in the URL",
+        )),
+        _ => Ok(()),
+    }
+}
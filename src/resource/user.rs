@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Extension, Query, State},
+    extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode, Uri},
     response::{IntoResponse, Response},
     Json,
@@ -7,19 +7,93 @@ use axum::{
 use serde_json::{json, Value};
 use std::{collections::HashMap, sync::Arc};
 
-use crate::extractors::ScimJson;
+use crate::extractors::{ScimJson, Tenant};
 
 use super::attribute_filter::AttributeFilter;
+use super::list_params::{filter_param, ListParams};
+use super::list_response::list_response;
+use super::ndjson::{export_filter, ndjson_response, wants_ndjson};
+use super::provenance;
 use crate::auth::TenantInfo;
 use crate::backend::ScimBackend;
-use crate::config::AppConfig;
-use crate::error::scim_error_response;
-use crate::models::{ScimListResponse, ScimPatchOp, User};
-use crate::parser::filter_parser::parse_filter;
-use crate::parser::{ResourceType, SortSpec};
-use crate::schema::{should_fetch_external_attributes, validate_user};
+use crate::config::{AppConfig, CompatibilityConfig};
+use crate::error::{
+    patch_not_supported_response, precondition_required_response, scim_error_response,
+    too_many_patch_operations_response,
+};
+use crate::models::{ScimListResponse, ScimPatchOperation, User};
+use crate::parser::filter_parser::{parse_filter, FilterOperator};
+use crate::parser::patch_parser::parse_patch_op;
+use crate::parser::{ResourceType, SortOrder, SortSpec};
+use crate::password::{PasswordManager, PasswordManagerRegistry};
+use crate::schema::{
+    enforce_single_primaries, should_fetch_external_attributes, validate_canonical_values,
+    validate_formats, validate_password_accepted, validate_patch_canonical_values,
+    validate_patch_formats, validate_patch_password_accepted, validate_patch_timezone,
+    validate_patch_value_lengths, validate_strict_timezone, validate_user, validate_value_lengths,
+};
 
-type AppState = (Arc<dyn ScimBackend>, Arc<AppConfig>);
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
+
+/// Hash a plaintext `password` in a User payload with the tenant's manager
+fn hash_payload_password(
+    password_manager: &PasswordManager,
+    payload: &mut Value,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    match payload.get_mut("password") {
+        Some(Value::String(password)) => hash_plaintext(password_manager, password),
+        _ => Ok(()),
+    }
+}
+
+/// Replace a plaintext password with its hash; values that already are
+/// hashes are kept, as on insert
+fn hash_plaintext(
+    password_manager: &PasswordManager,
+    password: &mut String,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    if !password_manager.is_hashed_password(password) {
+        *password = password_manager
+            .hash_password(password)
+            .map_err(|e| e.to_response())?;
+    }
+    Ok(())
+}
+
+/// Hash plaintext passwords carried by PATCH add/replace operations
+fn hash_patch_passwords(
+    password_manager: &PasswordManager,
+    operations: &mut [ScimPatchOperation],
+) -> Result<(), (StatusCode, Json<Value>)> {
+    for operation in operations {
+        let op = operation.op.to_lowercase();
+        if op != "add" && op != "replace" {
+            continue;
+        }
+        let sets_password = match operation.path.as_deref() {
+            None | Some("") => false,
+            Some(path) => path
+                .rsplit(':')
+                .next()
+                .unwrap_or(path)
+                .eq_ignore_ascii_case("password"),
+        };
+        match operation.value.as_mut() {
+            Some(Value::String(password)) if sets_password => {
+                hash_plaintext(password_manager, password)?;
+            }
+            Some(value @ Value::Object(_)) if !sets_password => {
+                hash_payload_password(password_manager, value)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
 
 // Helper function to extract resource ID from URI path
 fn extract_resource_id_from_uri(uri: &Uri) -> Option<String> {
@@ -37,25 +111,14 @@ fn extract_resource_id_from_uri(uri: &Uri) -> Option<String> {
     None
 }
 
-// Helper function to construct resource location URL
-fn build_resource_location(
-    tenant_info: &TenantInfo,
-    resource_type: &str,
-    resource_id: &str,
-) -> String {
-    // The base_path already includes the tenant path, so we just append the resource type and ID
-    format!(
-        "{}/{}/{}",
-        tenant_info.base_path.trim_end_matches('/'),
-        resource_type,
-        resource_id
-    )
-}
-
 // Helper function to set meta.location for a user
-fn set_user_location(tenant_info: &TenantInfo, user: &mut User) {
+pub(crate) fn set_user_location(tenant_info: &TenantInfo, user: &mut User) {
     if let Some(ref user_id) = user.base.id {
-        let location = build_resource_location(tenant_info, "Users", user_id);
+        let location = crate::utils::build_resource_location(
+            &tenant_info.base_path,
+            tenant_info.tenant_config.users_path(),
+            user_id,
+        );
 
         // Ensure meta exists
         if user.base.meta.is_none() {
@@ -64,7 +127,7 @@ fn set_user_location(tenant_info: &TenantInfo, user: &mut User) {
                 created: Some(now.clone()),
                 last_modified: Some(now),
                 location: Some(location),
-                resource_type: Some("User".to_string()),
+                resource_type: Some(ResourceType::User.scim_name().to_string()),
                 version: None,
             });
         } else if let Some(ref mut meta) = user.base.meta {
@@ -74,7 +137,7 @@ fn set_user_location(tenant_info: &TenantInfo, user: &mut User) {
 }
 
 // Helper function to fix user refs with base URL and tenant path
-fn fix_user_refs(tenant_info: &TenantInfo, user: &mut User) {
+pub(crate) fn fix_user_refs(tenant_info: &TenantInfo, user: &mut User) {
     let tenant_id = tenant_info.tenant_id;
 
     // Fix meta location
@@ -92,18 +155,16 @@ fn fix_user_refs(tenant_info: &TenantInfo, user: &mut User) {
         }
     }
 
-    // Fix groups $ref
+    // Resolve groups $ref against the tenant's $ref base URL
     if let Some(ref mut groups) = user.base.groups {
         for group in groups {
             if let Some(ref mut ref_) = group.ref_ {
-                if ref_.starts_with(&format!("/{}/", tenant_id)) {
-                    // Replace tenant ID-based path with full base URL + resource path
-                    let resource_path = ref_.replace(&format!("/{}/", tenant_id), "");
-                    *ref_ = format!(
-                        "{}/{}",
-                        tenant_info.base_path.trim_end_matches('/'),
-                        resource_path
-                    );
+                if let Some(resolved) = crate::utils::resolve_tenant_ref(
+                    &tenant_info.tenant_config,
+                    &tenant_info.ref_base_path,
+                    ref_,
+                ) {
+                    *ref_ = resolved;
                 }
             }
         }
@@ -135,6 +196,7 @@ fn create_filtered_user_list_response(
         total_results: total,
         start_index: Some(start_index.unwrap_or(1)),
         items_per_page: Some(filtered_resources.len() as i64),
+        next_start_index: None,
         resources: filtered_resources,
     }
 }
@@ -143,12 +205,62 @@ fn create_filtered_user_list_response(
 
 // Multi-tenant handlers with tenant_id extraction and validation
 pub async fn create_user(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
-    ScimJson(payload): ScimJson<serde_json::Value>,
+    State((backend, app_config, passwords)): State<AppState>,
+    tenant_info: Tenant,
+    ScimJson(mut payload): ScimJson<serde_json::Value>,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
+    // Oversized strings (e.g. a 5 MB displayName) are rejected before anything else
+    if let Err(e) = validate_value_lengths(
+        &payload,
+        app_config
+            .get_effective_compatibility(tenant_id)
+            .max_attribute_value_length,
+    ) {
+        return Err(e.to_response());
+    }
+
+    // Tenants whose passwords are managed elsewhere reject them outright
+    if let Err(e) = validate_password_accepted(&payload, tenant_info.tenant_config.accept_password)
+    {
+        return Err(e.to_response());
+    }
+    hash_payload_password(&passwords.for_tenant(tenant_id), &mut payload)?;
+
+    // Enforce tenant-configured canonical values (e.g. emails.type)
+    if let Err(e) = validate_canonical_values(&payload, &tenant_info.tenant_config.canonical_values)
+    {
+        return Err(e.to_response());
+    }
+
+    // Tenant-configured formats (emails, phoneNumbers, ...); may normalize phone numbers
+    if let Err(e) = validate_formats(&mut payload, &tenant_info.tenant_config.validate_formats) {
+        return Err(e.to_response());
+    }
+
+    if app_config
+        .get_effective_compatibility(tenant_id)
+        .validate_timezone
+    {
+        if let Err(e) = validate_strict_timezone(&payload) {
+            return Err(e.to_response());
+        }
+    }
+
+    // Provenance is server-maintained; ignore any the client sends
+    provenance::strip_extension(&mut payload);
+
+    // Only the first primary=true value of each multi-valued attribute is kept
+    if let Err(e) = enforce_single_primaries(&mut payload, ResourceType::User) {
+        return Err(e.to_response());
+    }
+
+    // A missing userName is reported by validate_user along with other problems
+    if let Some(object) = payload.as_object_mut() {
+        object.entry("userName").or_insert_with(|| json!(""));
+    }
+
     // Convert JSON payload to our User model
     let user: User = match serde_json::from_value(payload) {
         Ok(user) => user,
@@ -162,92 +274,128 @@ pub async fn create_user(
 
     // Validate user data
     if let Err(e) = validate_user(&user.base) {
-        return Err(e.to_response());
+        let detailed = app_config
+            .get_effective_compatibility(tenant_id)
+            .detailed_validation_errors;
+        return Err(e.to_validation_response(detailed));
     }
 
-    match backend.create_user(tenant_id, &user).await {
-        Ok(mut created_user) => {
-            // Set meta.location for SCIM compliance
-            set_user_location(&tenant_info, &mut created_user);
+    match backend
+        .create_user(tenant_id, &user, tenant_info.principal_name.as_deref())
+        .await
+    {
+        Ok(created_user) => {
+            created_user_response(&backend, &app_config, &tenant_info, created_user).await
+        }
+        Err(e) => Err(e.to_response()),
+    }
+}
 
-            // Fix refs with base URL
-            fix_user_refs(&tenant_info, &mut created_user);
+/// 201 response for a newly created user, with Location and ETag headers
+async fn created_user_response(
+    backend: &Arc<dyn ScimBackend>,
+    app_config: &AppConfig,
+    tenant_info: &TenantInfo,
+    mut created_user: User,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let tenant_id = tenant_info.tenant_id;
 
-            // Apply compatibility transformations based on tenant settings
-            let compatibility = app_config.get_effective_compatibility(tenant_id);
-            created_user = crate::utils::convert_user_datetime_for_response(
-                created_user,
-                &compatibility.meta_datetime_format,
-            );
-            created_user = crate::utils::handle_user_groups_inclusion_for_response(
-                created_user,
-                compatibility.include_user_groups,
-            );
-            created_user = crate::utils::handle_user_empty_groups_for_response(
-                created_user,
-                compatibility.show_empty_groups_members,
-            );
+    // Set meta.location for SCIM compliance
+    set_user_location(tenant_info, &mut created_user);
 
-            // Build Location header URL
-            let location_url = if let Some(ref user_id) = created_user.base.id {
-                build_resource_location(&tenant_info, "Users", user_id)
-            } else {
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"message": "Created user missing ID"})),
-                ));
-            };
+    // Fix refs with base URL
+    fix_user_refs(tenant_info, &mut created_user);
 
-            // Convert to JSON and remove null fields to comply with SCIM specification
-            let user_json = serde_json::to_value(&created_user).map_err(|_| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"message": "Serialization error"})),
-                )
-            })?;
+    // Apply compatibility transformations based on tenant settings
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    created_user = crate::utils::convert_user_datetime_for_response(
+        created_user,
+        &compatibility.meta_datetime_format,
+    );
+    created_user = crate::utils::handle_user_groups_inclusion_for_response(
+        created_user,
+        app_config.include_user_groups(tenant_id),
+    );
+    created_user = crate::utils::handle_user_empty_groups_for_response(
+        created_user,
+        compatibility.show_empty_groups_members,
+    );
 
-            let cleaned_user_json = AttributeFilter::remove_null_fields(&user_json);
+    // The Location header carries the same URL as meta.location (RFC 7644 3.3)
+    let location_url = match created_user
+        .base
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.location.clone())
+    {
+        Some(location) => location,
+        None => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"message": "Created user missing ID"})),
+            ))
+        }
+    };
 
-            // Create response with Location and ETag headers
-            let mut headers = HeaderMap::new();
+    // Convert to JSON and remove null fields to comply with SCIM specification
+    let user_json = serde_json::to_value(&created_user).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"message": "Serialization error"})),
+        )
+    })?;
+
+    let response_filter =
+        AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+    let mut cleaned_user_json = response_filter.apply_to_resource(&user_json, ResourceType::User);
+
+    provenance::attach(
+        backend.as_ref(),
+        tenant_info,
+        ResourceType::User,
+        &response_filter,
+        std::slice::from_mut(&mut cleaned_user_json),
+    )
+    .await
+    .map_err(|e| e.to_response())?;
+
+    // Create response with Location and ETag headers
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Location",
+        HeaderValue::from_str(&location_url).map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"message": "Invalid location header"})),
+            )
+        })?,
+    );
+
+    // Add ETag header (Phase 2: ETag response headers)
+    if let Some(ref meta) = created_user.base.meta {
+        if let Some(ref version) = meta.version {
             headers.insert(
-                "Location",
-                HeaderValue::from_str(&location_url).map_err(|_| {
+                "ETag",
+                HeaderValue::from_str(version).map_err(|_| {
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"message": "Invalid location header"})),
+                        Json(serde_json::json!({"message": "Invalid ETag header"})),
                     )
                 })?,
             );
-
-            // Add ETag header (Phase 2: ETag response headers)
-            if let Some(ref meta) = created_user.base.meta {
-                if let Some(ref version) = meta.version {
-                    headers.insert(
-                        "ETag",
-                        HeaderValue::from_str(version).map_err(|_| {
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(serde_json::json!({"message": "Invalid ETag header"})),
-                            )
-                        })?,
-                    );
-                }
-            }
-
-            let mut response = Json(cleaned_user_json).into_response();
-            *response.status_mut() = StatusCode::CREATED;
-            response.headers_mut().extend(headers);
-
-            Ok(response)
         }
-        Err(e) => Err(e.to_response()),
     }
+
+    let mut response = Json(cleaned_user_json).into_response();
+    *response.status_mut() = StatusCode::CREATED;
+    response.headers_mut().extend(headers);
+
+    Ok(response)
 }
 
 pub async fn get_user(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
     Query(params): Query<HashMap<String, String>>,
@@ -269,7 +417,8 @@ pub async fn get_user(
     let attribute_filter = AttributeFilter::from_params(
         params.get("attributes").map(String::as_str),
         params.get("excludedAttributes").map(String::as_str),
-    );
+    )
+    .with_compatibility(app_config.get_effective_compatibility(tenant_id));
 
     // Get compatibility settings for this tenant to determine if we should include groups
     let compatibility = app_config.get_effective_compatibility(tenant_id);
@@ -278,7 +427,7 @@ pub async fn get_user(
     let should_include_groups = should_fetch_external_attributes(
         &attribute_filter,
         ResourceType::User,
-        compatibility.include_user_groups,
+        app_config.include_user_groups(tenant_id),
     );
 
     match backend
@@ -332,7 +481,17 @@ pub async fn get_user(
                 )
             })?;
 
-            let filtered_user = attribute_filter.apply_to_resource(&user_json, ResourceType::User);
+            let mut filtered_user =
+                attribute_filter.apply_to_resource(&user_json, ResourceType::User);
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::User,
+                &attribute_filter,
+                std::slice::from_mut(&mut filtered_user),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
 
             // Build response with ETag header (Phase 2: ETag response headers)
             let mut headers = HeaderMap::new();
@@ -363,24 +522,135 @@ pub async fn get_user(
     }
 }
 
+/// `GET {base}/Users/.export`
+///
+/// Streams the tenant's users, or those matching `filter`, as NDJSON. Only
+/// served when the tenant's compatibility enables `export_endpoints`.
+pub async fn export_users(
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let compatibility = app_config.get_effective_compatibility(tenant_info.tenant_id);
+    let filter = export_filter(compatibility, &params, ResourceType::User)?;
+
+    let attribute_filter = AttributeFilter::from_params(
+        params.get("attributes").map(String::as_str),
+        params.get("excludedAttributes").map(String::as_str),
+    )
+    .with_compatibility(compatibility);
+    let include_groups = should_fetch_external_attributes(
+        &attribute_filter,
+        ResourceType::User,
+        app_config.include_user_groups(tenant_info.tenant_id),
+    );
+
+    Ok(users_ndjson(
+        backend,
+        tenant_info.info,
+        compatibility.clone(),
+        attribute_filter,
+        include_groups,
+        filter,
+    ))
+}
+
+/// Stream the tenant's users, or those matching `filter`, as NDJSON
+fn users_ndjson(
+    backend: Arc<dyn ScimBackend>,
+    tenant_info: TenantInfo,
+    compatibility: CompatibilityConfig,
+    attribute_filter: AttributeFilter,
+    include_groups: bool,
+    filter: Option<FilterOperator>,
+) -> Response {
+    let tenant_id = tenant_info.tenant_id;
+    // Order by creation time with id as tie-breaker so paging is stable
+    let sort_spec = SortSpec::new("meta.created".to_string(), SortOrder::Ascending);
+    ndjson_response(move |start_index, count| {
+        let backend = backend.clone();
+        let tenant_info = tenant_info.clone();
+        let compatibility = compatibility.clone();
+        let attribute_filter = attribute_filter.clone();
+        let sort_spec = sort_spec.clone();
+        let filter = filter.clone();
+        async move {
+            let (users, _) = match &filter {
+                Some(filter) => {
+                    backend
+                        .find_users_by_filter(
+                            tenant_id,
+                            filter,
+                            Some(start_index),
+                            Some(count),
+                            Some(&sort_spec),
+                            include_groups,
+                        )
+                        .await?
+                }
+                None => {
+                    backend
+                        .find_all_users_sorted(
+                            tenant_id,
+                            Some(start_index),
+                            Some(count),
+                            Some(&sort_spec),
+                            include_groups,
+                        )
+                        .await?
+                }
+            };
+            let mut resources: Vec<Value> = users
+                .into_iter()
+                .map(|mut user| {
+                    set_user_location(&tenant_info, &mut user);
+                    fix_user_refs(&tenant_info, &mut user);
+                    let user = crate::utils::convert_user_datetime_for_response(
+                        user,
+                        &compatibility.meta_datetime_format,
+                    );
+                    let user = crate::utils::handle_user_empty_groups_for_response(
+                        user,
+                        compatibility.show_empty_groups_members,
+                    );
+                    let user_json = serde_json::to_value(&user).unwrap_or_default();
+                    attribute_filter.apply_to_resource(&user_json, ResourceType::User)
+                })
+                .collect();
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::User,
+                &attribute_filter,
+                &mut resources,
+            )
+            .await?;
+            Ok(resources)
+        }
+    })
+}
+
 pub async fn search_users(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<(StatusCode, Json<ScimListResponse>), (StatusCode, Json<serde_json::Value>)> {
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
-    let filter = params.get("filter").map(String::as_str);
-    let start_index = params.get("startIndex").and_then(|s| s.parse::<i64>().ok());
-    let count = params.get("count").and_then(|s| s.parse::<i64>().ok());
-    let sort_by = params.get("sortBy").cloned();
-    let sort_order = params.get("sortOrder").cloned();
+    let filter = filter_param(&params);
+    let ListParams {
+        start_index,
+        count,
+        sort_by,
+        sort_order,
+    } = ListParams::from_query(&params, app_config.get_effective_compatibility(tenant_id))?;
 
     // Parse attribute filtering parameters
     let attribute_filter = AttributeFilter::from_params(
         params.get("attributes").map(String::as_str),
         params.get("excludedAttributes").map(String::as_str),
-    );
+    )
+    .with_compatibility(app_config.get_effective_compatibility(tenant_id));
 
     // Get compatibility settings for this tenant
     let compatibility = app_config.get_effective_compatibility(tenant_id);
@@ -389,69 +659,19 @@ pub async fn search_users(
     let should_include_groups = should_fetch_external_attributes(
         &attribute_filter,
         ResourceType::User,
-        compatibility.include_user_groups,
+        app_config.include_user_groups(tenant_id),
     );
 
-    // Handle filter for group membership: groups[value eq "group-id"]
-    if let Some(filter_str) = filter {
-        if filter_str.starts_with("groups[value eq ") && filter_str.ends_with("]") {
-            // Extract group ID from filter: groups[value eq "group-id"]
-            let start_quote = filter_str.find('"').ok_or_else(|| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({"message": "Invalid filter format"})),
-                )
-            })?;
-            let end_quote = filter_str.rfind('"').ok_or_else(|| {
-                (
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({"message": "Invalid filter format"})),
-                )
-            })?;
-
-            if start_quote >= end_quote {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({"message": "Invalid filter format"})),
-                ));
-            }
-
-            let group_id = &filter_str[start_quote + 1..end_quote];
-
-            // Get users by group
-            match backend
-                .find_users_by_group_id(tenant_id, group_id, should_include_groups)
-                .await
-            {
-                Ok(mut users) => {
-                    // Set location and fix refs for all users
-                    for user in &mut users {
-                        set_user_location(&tenant_info, user);
-                        fix_user_refs(&tenant_info, user);
-                        // Apply compatibility transformations
-                        *user = crate::utils::convert_user_datetime_for_response(
-                            user.clone(),
-                            &compatibility.meta_datetime_format,
-                        );
-                        // Note: groups field inclusion is already handled at the database level
-                        // Only need to handle empty array display behavior
-                        *user = crate::utils::handle_user_empty_groups_for_response(
-                            user.clone(),
-                            compatibility.show_empty_groups_members,
-                        );
-                    }
-                    let total_results = users.len() as i64;
-                    let response = create_filtered_user_list_response(
-                        users,
-                        total_results,
-                        start_index,
-                        &attribute_filter,
-                    );
-                    return Ok((StatusCode::OK, Json(response)));
-                }
-                Err(e) => return Err(e.to_response()),
-            }
-        }
+    // NDJSON export: stream every user page by page instead of one ListResponse
+    if wants_ndjson(&tenant_info, &params)? {
+        return Ok(users_ndjson(
+            backend,
+            tenant_info.info,
+            compatibility.clone(),
+            attribute_filter,
+            should_include_groups,
+            None,
+        ));
     }
 
     // Handle general filtering
@@ -488,22 +708,32 @@ pub async fn search_users(
                                 compatibility.show_empty_groups_members,
                             );
                         }
-                        let response = create_filtered_user_list_response(
+                        let mut response = create_filtered_user_list_response(
                             users,
                             total,
                             start_index,
                             &attribute_filter,
                         );
-                        return Ok((StatusCode::OK, Json(response)));
+                        provenance::attach(
+                            backend.as_ref(),
+                            &tenant_info,
+                            ResourceType::User,
+                            &attribute_filter,
+                            &mut response.resources,
+                        )
+                        .await
+                        .map_err(|e| e.to_response())?;
+                        return Ok(list_response(response, compatibility));
                     }
                     Err(e) => return Err(e.to_response()),
                 }
             }
             Err(e) => {
                 eprintln!("Filter parsing error for '{}': {}", filter_str, e);
-                return Err((
+                return Err(scim_error_response(
                     StatusCode::BAD_REQUEST,
-                    Json(json!({"message": format!("Invalid filter: {}", e)})),
+                    "invalidFilter",
+                    &format!("Invalid filter: {}", e),
                 ));
             }
         }
@@ -546,23 +776,42 @@ pub async fn search_users(
                     compatibility.show_empty_groups_members,
                 );
             }
-            let response =
+            let mut response =
                 create_filtered_user_list_response(users, total, start_index, &attribute_filter);
-            Ok((StatusCode::OK, Json(response)))
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::User,
+                &attribute_filter,
+                &mut response.resources,
+            )
+            .await
+            .map_err(|e| e.to_response())?;
+            Ok(list_response(response, compatibility))
         }
         Err(e) => Err(e.to_response()),
     }
 }
 
 pub async fn update_user(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, passwords)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
-    ScimJson(payload): ScimJson<serde_json::Value>,
+    ScimJson(mut payload): ScimJson<serde_json::Value>,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
+    // Oversized strings (e.g. a 5 MB displayName) are rejected before anything else
+    if let Err(e) = validate_value_lengths(
+        &payload,
+        app_config
+            .get_effective_compatibility(tenant_id)
+            .max_attribute_value_length,
+    ) {
+        return Err(e.to_response());
+    }
+
     // Extract user ID from URI
     let id = match extract_resource_id_from_uri(&uri) {
         Some(id) => id,
@@ -574,6 +823,64 @@ pub async fn update_user(
         }
     };
 
+    crate::resource::shared::check_body_id(&payload, &id)?;
+
+    // Tenants whose passwords are managed elsewhere reject them outright
+    if let Err(e) = validate_password_accepted(&payload, tenant_info.tenant_config.accept_password)
+    {
+        return Err(e.to_response());
+    }
+    hash_payload_password(&passwords.for_tenant(tenant_id), &mut payload)?;
+
+    // Enforce tenant-configured canonical values (e.g. emails.type)
+    if let Err(e) = validate_canonical_values(&payload, &tenant_info.tenant_config.canonical_values)
+    {
+        return Err(e.to_response());
+    }
+
+    // Tenant-configured formats (emails, phoneNumbers, ...); may normalize phone numbers
+    if let Err(e) = validate_formats(&mut payload, &tenant_info.tenant_config.validate_formats) {
+        return Err(e.to_response());
+    }
+
+    if app_config
+        .get_effective_compatibility(tenant_id)
+        .validate_timezone
+    {
+        if let Err(e) = validate_strict_timezone(&payload) {
+            return Err(e.to_response());
+        }
+    }
+
+    // Provenance is server-maintained; ignore any the client sends
+    provenance::strip_extension(&mut payload);
+
+    // Denied attributes keep their stored values
+    if let Some(permissions) = tenant_info
+        .permissions
+        .as_ref()
+        .filter(|permissions| !permissions.denied_attributes.is_empty())
+    {
+        if let Some(stored) = backend
+            .find_user_by_id(tenant_id, &id, false)
+            .await
+            .map_err(|e| e.to_response())?
+        {
+            let stored = serde_json::to_value(&stored).unwrap_or_default();
+            crate::permissions::carry_over_denied(permissions, &stored, &mut payload);
+        }
+    }
+
+    // Only the first primary=true value of each multi-valued attribute is kept
+    if let Err(e) = enforce_single_primaries(&mut payload, ResourceType::User) {
+        return Err(e.to_response());
+    }
+
+    // A missing userName is reported by validate_user along with other problems
+    if let Some(object) = payload.as_object_mut() {
+        object.entry("userName").or_insert_with(|| json!(""));
+    }
+
     // Convert JSON payload to our User model
     let user: User = match serde_json::from_value(payload) {
         Ok(user) => user,
@@ -587,7 +894,15 @@ pub async fn update_user(
 
     // Validate user data
     if let Err(e) = validate_user(&user.base) {
-        return Err(e.to_response());
+        let detailed = app_config
+            .get_effective_compatibility(tenant_id)
+            .detailed_validation_errors;
+        return Err(e.to_validation_response(detailed));
+    }
+
+    // Tenants with require_if_match reject unconditional writes
+    if tenant_info.tenant_config.require_if_match && !headers.contains_key("if-match") {
+        return Err(precondition_required_response());
     }
 
     // Phase 3: Handle conditional requests (If-Match) - Optimistic Concurrency Control
@@ -613,18 +928,18 @@ pub async fn update_user(
                         }
                     }
                 }
-                Ok(None) => {
-                    return Err((
-                        StatusCode::NOT_FOUND,
-                        Json(json!({"message": "User not found"})),
-                    ));
-                }
+                // Nothing to compare with; the write below creates the resource
+                // with put_creates_missing and returns 404 otherwise
+                Ok(None) => {}
                 Err(e) => return Err(e.to_response()),
             }
         }
     }
 
-    match backend.update_user(tenant_id, &id, &user).await {
+    match backend
+        .update_user(tenant_id, &id, &user, tenant_info.principal_name.as_deref())
+        .await
+    {
         Ok(Some(mut updated_user)) => {
             // Set meta.location for SCIM compliance
             set_user_location(&tenant_info, &mut updated_user);
@@ -652,7 +967,20 @@ pub async fn update_user(
                 )
             })?;
 
-            let cleaned_user_json = AttributeFilter::remove_null_fields(&user_json);
+            let response_filter =
+                AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+            let mut cleaned_user_json =
+                response_filter.apply_to_resource(&user_json, ResourceType::User);
+
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::User,
+                &response_filter,
+                std::slice::from_mut(&mut cleaned_user_json),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
 
             // Build response with ETag header (Phase 2: ETag response headers)
             let mut headers = HeaderMap::new();
@@ -675,6 +1003,33 @@ pub async fn update_user(
             response.headers_mut().extend(headers);
             Ok(response)
         }
+        Ok(None)
+            if app_config
+                .get_effective_compatibility(tenant_id)
+                .put_creates_missing =>
+        {
+            // Create-if-missing for clients that allocate ids themselves; the
+            // id must look like one the server would have generated
+            if !crate::utils::is_valid_resource_id(&id) {
+                return Err(scim_error_response(
+                    StatusCode::BAD_REQUEST,
+                    "invalidValue",
+                    &format!(
+                        "Cannot create a User with id {}: ids are lowercase hyphenated UUIDs",
+                        id
+                    ),
+                ));
+            }
+            match backend
+                .create_user_with_id(tenant_id, &id, &user, tenant_info.principal_name.as_deref())
+                .await
+            {
+                Ok(created_user) => {
+                    created_user_response(&backend, &app_config, &tenant_info, created_user).await
+                }
+                Err(e) => Err(e.to_response()),
+            }
+        }
         Ok(None) => Err((
             StatusCode::NOT_FOUND,
             Json(json!({"message": "User not found"})),
@@ -684,11 +1039,11 @@ pub async fn update_user(
 }
 
 pub async fn delete_user(
-    State((backend, _)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, _)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
-) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
     // Extract user ID from URI
@@ -702,6 +1057,11 @@ pub async fn delete_user(
         }
     };
 
+    // Tenants with require_if_match reject unconditional writes
+    if tenant_info.tenant_config.require_if_match && !headers.contains_key("if-match") {
+        return Err(precondition_required_response());
+    }
+
     // Phase 3: Handle conditional requests (If-Match) - Optimistic Concurrency Control
     if let Some(if_match) = headers.get("if-match") {
         if let Ok(if_match_str) = if_match.to_str() {
@@ -736,8 +1096,64 @@ pub async fn delete_user(
         }
     }
 
-    match backend.delete_user(tenant_id, &id).await {
-        Ok(true) => Ok(StatusCode::NO_CONTENT),
+    // Legacy clients expect the deleted resource back, so read it first,
+    // along with its provenance, which goes with the row
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    let deleted_user = if compatibility.delete_returns_resource {
+        let mut user = match backend
+            .find_user_by_id(tenant_id, &id, app_config.include_user_groups(tenant_id))
+            .await
+        {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    Json(json!({"message": "User not found"})),
+                ));
+            }
+            Err(e) => return Err(e.to_response()),
+        };
+        set_user_location(&tenant_info, &mut user);
+        fix_user_refs(&tenant_info, &mut user);
+        let user = crate::utils::convert_user_datetime_for_response(
+            user,
+            &compatibility.meta_datetime_format,
+        );
+        let user = crate::utils::handle_user_empty_groups_for_response(
+            user,
+            compatibility.show_empty_groups_members,
+        );
+        let user_json = serde_json::to_value(&user).map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"message": "Serialization error"})),
+            )
+        })?;
+        let response_filter =
+            AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+        let mut user_json = response_filter.apply_to_resource(&user_json, ResourceType::User);
+        provenance::attach(
+            backend.as_ref(),
+            &tenant_info,
+            ResourceType::User,
+            &response_filter,
+            std::slice::from_mut(&mut user_json),
+        )
+        .await
+        .map_err(|e| e.to_response())?;
+        Some(user_json)
+    } else {
+        None
+    };
+
+    match backend
+        .delete_user(tenant_id, &id, tenant_info.principal_name.as_deref())
+        .await
+    {
+        Ok(true) => Ok(match deleted_user {
+            Some(user_json) => Json(user_json).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        }),
         Ok(false) => Err((
             StatusCode::NOT_FOUND,
             Json(json!({"message": "User not found"})),
@@ -747,14 +1163,33 @@ pub async fn delete_user(
 }
 
 pub async fn patch_user(
-    State((backend, app_config)): State<AppState>,
-    Extension(tenant_info): Extension<TenantInfo>,
+    State((backend, app_config, passwords)): State<AppState>,
+    tenant_info: Tenant,
     headers: HeaderMap,
     uri: Uri,
-    ScimJson(patch_ops): ScimJson<ScimPatchOp>,
+    ScimJson(body): ScimJson<serde_json::Value>,
 ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
     let tenant_id = tenant_info.tenant_id;
 
+    let compatibility = app_config.get_effective_compatibility(tenant_id);
+    if !compatibility.patch_supported {
+        return Err(patch_not_supported_response());
+    }
+    let mut patch_ops =
+        parse_patch_op(body, compatibility.strict_patch_schema).map_err(|e| e.to_response())?;
+    if patch_ops.operations.len() > compatibility.max_patch_operations as usize {
+        return Err(too_many_patch_operations_response(
+            patch_ops.operations.len(),
+            compatibility.max_patch_operations,
+        ));
+    }
+    if let Err(e) = validate_patch_value_lengths(
+        &patch_ops.operations,
+        compatibility.max_attribute_value_length,
+    ) {
+        return Err(e.to_response());
+    }
+
     // Extract user ID from URI
     let id = match extract_resource_id_from_uri(&uri) {
         Some(id) => id,
@@ -766,6 +1201,11 @@ pub async fn patch_user(
         }
     };
 
+    // Tenants with require_if_match reject unconditional writes
+    if tenant_info.tenant_config.require_if_match && !headers.contains_key("if-match") {
+        return Err(precondition_required_response());
+    }
+
     // Phase 3: Handle conditional requests (If-Match) - Optimistic Concurrency Control
     if let Some(if_match) = headers.get("if-match") {
         if let Ok(if_match_str) = if_match.to_str() {
@@ -800,9 +1240,48 @@ pub async fn patch_user(
         }
     }
 
+    if patch_ops
+        .operations
+        .iter()
+        .any(|op| provenance::targets_extension(op.path.as_deref(), op.value.as_ref()))
+    {
+        return Err(scim_error_response(
+            StatusCode::BAD_REQUEST,
+            "mutability",
+            "Provenance attributes are readOnly",
+        ));
+    }
+
+    if let Err(e) = validate_patch_canonical_values(
+        &patch_ops.operations,
+        &tenant_info.tenant_config.canonical_values,
+    ) {
+        return Err(e.to_response());
+    }
+
+    if let Err(e) = validate_patch_formats(
+        &mut patch_ops.operations,
+        &tenant_info.tenant_config.validate_formats,
+    ) {
+        return Err(e.to_response());
+    }
+    if let Err(e) = validate_patch_password_accepted(
+        &patch_ops.operations,
+        tenant_info.tenant_config.accept_password,
+    ) {
+        return Err(e.to_response());
+    }
+    hash_patch_passwords(&passwords.for_tenant(tenant_id), &mut patch_ops.operations)?;
+
     // Get compatibility settings for PATCH operation validation
     let compatibility = app_config.get_effective_compatibility(tenant_id);
 
+    if compatibility.validate_timezone {
+        if let Err(e) = validate_patch_timezone(&patch_ops.operations) {
+            return Err(e.to_response());
+        }
+    }
+
     // Validate PATCH operations based on compatibility settings
     // Only reject operations that are explicitly disabled
     for operation in &patch_ops.operations {
@@ -836,7 +1315,13 @@ pub async fn patch_user(
     }
 
     match backend
-        .patch_user(tenant_id, &id, &patch_ops, compatibility)
+        .patch_user(
+            tenant_id,
+            &id,
+            &patch_ops,
+            compatibility,
+            tenant_info.principal_name.as_deref(),
+        )
         .await
     {
         Ok(Some(mut user)) => {
@@ -866,7 +1351,20 @@ pub async fn patch_user(
                 )
             })?;
 
-            let cleaned_user_json = AttributeFilter::remove_null_fields(&user_json);
+            let response_filter =
+                AttributeFilter::from_params(None, None).with_compatibility(compatibility);
+            let mut cleaned_user_json =
+                response_filter.apply_to_resource(&user_json, ResourceType::User);
+
+            provenance::attach(
+                backend.as_ref(),
+                &tenant_info,
+                ResourceType::User,
+                &response_filter,
+                std::slice::from_mut(&mut cleaned_user_json),
+            )
+            .await
+            .map_err(|e| e.to_response())?;
 
             // Build response with ETag header (Phase 2: ETag response headers)
             let mut headers = HeaderMap::new();
@@ -0,0 +1,165 @@
+//! Schema checks of outgoing responses (`compatibility.validate_responses`)
+//!
+//! Meant for development and tests: every User and Group the server sends,
+//! alone or inside a ListResponse, is compared with the schema definitions
+//! and violations are logged as errors. Responses are passed on unchanged.
+
+use axum::{
+    body::{self, Body},
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::auth::TenantInfo;
+use crate::config::AppConfig;
+use crate::parser::ResourceType;
+use crate::schema::{
+    validate_scim_response, SCIM_API_MESSAGES_LIST_RESPONSE, SCIM_SCHEMA_CORE_GROUP,
+    SCIM_SCHEMA_CORE_USER,
+};
+
+/// Log schema violations in the JSON responses of tenants that enable
+/// `validate_responses`
+///
+/// Must run after `auth_middleware` so the resolved `TenantInfo` is available.
+/// Requests with `attributes` or `excludedAttributes` may legitimately leave
+/// out required attributes and are not checked.
+pub async fn validate_responses_middleware(
+    State(app_config): State<Arc<AppConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let enabled = request
+        .extensions()
+        .get::<TenantInfo>()
+        .is_some_and(|tenant_info| {
+            app_config
+                .get_effective_compatibility(tenant_info.tenant_id)
+                .validate_responses
+        });
+    if !enabled || is_projected(request.uri().query()) {
+        return next.run(request).await;
+    }
+
+    let method = request.method().clone();
+    let uri = request.uri().clone();
+    let response = next.run(request).await;
+    if !is_json(&response) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if let Ok(json) = serde_json::from_slice::<Value>(&bytes) {
+        for violation in response_violations(&json) {
+            tracing::error!(
+                method = %method,
+                uri = %uri,
+                "Response violates the SCIM schema: {}",
+                violation
+            );
+        }
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+fn is_projected(query: Option<&str>) -> bool {
+    query.is_some_and(|query| {
+        query.split('&').any(|pair| {
+            let name = pair.split('=').next().unwrap_or_default();
+            name.eq_ignore_ascii_case("attributes")
+                || name.eq_ignore_ascii_case("excludedAttributes")
+        })
+    })
+}
+
+pub(crate) fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| {
+            let media_type = media_type.trim();
+            media_type.eq_ignore_ascii_case("application/scim+json")
+                || media_type.eq_ignore_ascii_case("application/json")
+        })
+}
+
+/// Violations of a User, a Group or the Resources of a ListResponse
+fn response_violations(json: &Value) -> Vec<String> {
+    if has_schema(json, SCIM_API_MESSAGES_LIST_RESPONSE) {
+        let resources = json["Resources"].as_array().map(Vec::as_slice);
+        return resources
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .flat_map(|(index, resource)| {
+                resource_violations(resource)
+                    .into_iter()
+                    .map(move |violation| format!("Resources[{}]: {}", index, violation))
+            })
+            .collect();
+    }
+    resource_violations(json)
+}
+
+fn resource_violations(json: &Value) -> Vec<String> {
+    if has_schema(json, SCIM_SCHEMA_CORE_USER) {
+        validate_scim_response(ResourceType::User, json)
+    } else if has_schema(json, SCIM_SCHEMA_CORE_GROUP) {
+        validate_scim_response(ResourceType::Group, json)
+    } else {
+        Vec::new()
+    }
+}
+
+fn has_schema(json: &Value, schema: &str) -> bool {
+    json["schemas"]
+        .as_array()
+        .is_some_and(|schemas| schemas.iter().any(|s| s == schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_list_response_violations() {
+        let list = json!({
+            "schemas": [SCIM_API_MESSAGES_LIST_RESPONSE],
+            "totalResults": 2,
+            "Resources": [
+                {"schemas": [SCIM_SCHEMA_CORE_USER], "id": "1", "userName": "ok"},
+                {"schemas": [SCIM_SCHEMA_CORE_GROUP], "id": "2"}
+            ]
+        });
+        assert_eq!(
+            response_violations(&list),
+            vec!["Resources[1]: displayName is required but missing"]
+        );
+
+        // Errors and discovery documents are not checked
+        let error = json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+            "status": "404"
+        });
+        assert!(response_violations(&error).is_empty());
+    }
+
+    #[test]
+    fn test_is_projected() {
+        assert!(is_projected(Some("attributes=userName")));
+        assert!(is_projected(Some("count=5&excludedAttributes=emails")));
+        assert!(!is_projected(Some("filter=userName%20eq%20%22a%22")));
+        assert!(!is_projected(None));
+    }
+}
@@ -0,0 +1,310 @@
+//! HTTP routes of the server
+//!
+//! The SCIM endpoints are registered once, relative to a tenant base path,
+//! instead of once per tenant. `auth_middleware` resolves the tenant of each
+//! request from its host and path, then `strip_tenant_path` cuts the tenant's
+//! base path off before the tenant routes are matched. The route table stays
+//! the same size however many tenants are configured.
+
+use axum::{
+    extract::Request,
+    http::{header, uri::PathAndQuery, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post, MethodRouter},
+    Json, Router,
+};
+use serde_json::json;
+use std::sync::Arc;
+use tower::Layer;
+
+use crate::auth::{self, TenantInfo};
+use crate::auth_lockout::AuthFailures;
+use crate::backend::ScimBackend;
+use crate::config::{AppConfig, TenantConfig};
+use crate::error::resource_type_disabled_response;
+use crate::parser::ResourceType;
+use crate::password::PasswordManagerRegistry;
+use crate::resource;
+use crate::stats::{self, SyncStats};
+use crate::{compression, logging, maintenance, permissions, response_validation};
+
+type AppState = (
+    Arc<dyn ScimBackend>,
+    Arc<AppConfig>,
+    Arc<PasswordManagerRegistry>,
+);
+
+/// SCIM endpoints, relative to the base path of a tenant
+fn tenant_routes() -> Router<AppState> {
+    use resource::{group, group_members, user};
+
+    Router::new()
+        .route(
+            "/ServiceProviderConfig",
+            get(resource::service_provider::service_provider_config),
+        )
+        .route("/Schemas", get(resource::schema::schemas))
+        .route(
+            "/ResourceTypes",
+            get(resource::resource_type::resource_types),
+        )
+        // Root search across Users and Groups
+        .route("/.search", post(resource::search::search_resources))
+        .route("/Users", get(user::search_users).post(user::create_user))
+        .route("/Users/.export", get(user::export_users))
+        .route(
+            "/Users/{id}",
+            get(user::get_user)
+                .put(user::update_user)
+                .patch(user::patch_user)
+                .delete(user::delete_user),
+        )
+        .route(
+            "/Groups",
+            get(group::search_groups).post(group::create_group),
+        )
+        .route("/Groups/.export", get(group::export_groups))
+        .route(
+            "/Groups/{id}",
+            get(group::get_group)
+                .put(group::update_group)
+                .patch(group::patch_group)
+                .delete(group::delete_group),
+        )
+        .route(
+            "/Groups/{id}/members:add",
+            post(group_members::add_group_members),
+        )
+        .route(
+            "/Groups/{id}/members:remove",
+            post(group_members::remove_group_members),
+        )
+        .route(
+            "/Groups/{id}/effectiveMembers",
+            get(group_members::effective_members),
+        )
+        .layer(middleware::from_fn(negotiate_accept))
+}
+
+/// Media types SCIM responses can be sent as
+const SCIM_MEDIA_RANGES: &[&str] = &[
+    "application/scim+json",
+    "application/json",
+    "application/*",
+    "*/*",
+];
+
+/// Reject requests whose Accept header allows no JSON response with 406
+///
+/// A missing Accept header accepts anything. NDJSON exports
+/// (`format=ndjson` or `.export`) may also ask for `application/x-ndjson`.
+async fn negotiate_accept(request: Request, next: Next) -> Response {
+    let Some(accept) = request.headers().get(header::ACCEPT) else {
+        return next.run(request).await;
+    };
+    let ndjson = request.uri().path().ends_with("/.export")
+        || request.uri().query().is_some_and(|query| {
+            query
+                .split('&')
+                .any(|pair| pair.eq_ignore_ascii_case("format=ndjson"))
+        });
+
+    let acceptable = accept.to_str().is_ok_and(|accept| {
+        accepts(accept, SCIM_MEDIA_RANGES) || (ndjson && accepts(accept, &["application/x-ndjson"]))
+    });
+    if acceptable {
+        return next.run(request).await;
+    }
+
+    let body = Json(json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:Error"],
+        "status": "406",
+        "detail": "Responses are only available as application/scim+json or application/json"
+    }));
+    (StatusCode::NOT_ACCEPTABLE, body).into_response()
+}
+
+/// Whether an Accept header lists one of `media_ranges` with a non-zero quality
+///
+/// An empty header accepts anything.
+fn accepts(accept: &str, media_ranges: &[&str]) -> bool {
+    if accept.trim().is_empty() {
+        return true;
+    }
+    accept.split(',').any(|range| {
+        let mut params = range.split(';');
+        let media_range = params.next().unwrap_or_default().trim();
+        let rejected = params.any(|param| {
+            param
+                .trim()
+                .split_once('=')
+                .filter(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, q)| q.trim().parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+        !rejected
+            && media_ranges
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(media_range))
+    })
+}
+
+/// Rewrite the request path to the part below the resolved tenant's base path
+///
+/// Requests without a tenant (`/health` and the like) or that address the
+/// base path itself are not SCIM endpoints and get 404.
+async fn strip_tenant_path(mut request: Request, next: Next) -> Response {
+    let Some(tenant_info) = request.extensions().get::<TenantInfo>() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let uri = request.uri();
+    let relative = uri
+        .path()
+        .strip_prefix(tenant_info.tenant_config.route_path())
+        .filter(|rest| rest.len() > 1 && rest.starts_with('/'));
+    let Some((relative, resource_type)) =
+        relative.and_then(|relative| route_path(&tenant_info.tenant_config, relative))
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if let Some(resource_type) = resource_type
+        .filter(|&resource_type| !tenant_info.tenant_config.resource_enabled(resource_type))
+    {
+        return resource_type_disabled_response(resource_type).into_response();
+    }
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{}?{}", relative, query),
+        None => relative,
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = PathAndQuery::try_from(path_and_query).ok();
+    match Uri::from_parts(parts) {
+        Ok(relative_uri) => *request.uri_mut() = relative_uri,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    }
+
+    next.run(request).await
+}
+
+/// Map a tenant's Users and Groups endpoint paths onto the `/Users` and
+/// `/Groups` routes, along with the resource type they serve
+///
+/// A tenant that renames an endpoint no longer serves it at the default
+/// path, so `None` (404) is returned for those.
+fn route_path(
+    tenant_config: &TenantConfig,
+    relative: &str,
+) -> Option<(String, Option<ResourceType>)> {
+    let (segment, rest) = match relative[1..].find('/') {
+        Some(end) => relative[1..].split_at(end),
+        None => (&relative[1..], ""),
+    };
+    let resource_type = if segment == tenant_config.users_path() {
+        ResourceType::User
+    } else if segment == tenant_config.groups_path() {
+        ResourceType::Group
+    } else if matches!(segment, "Users" | "Groups") {
+        return None;
+    } else {
+        return Some((relative.to_string(), None));
+    };
+    let route = match resource_type {
+        ResourceType::User => "Users",
+        ResourceType::Group => "Groups",
+    };
+    Some((format!("/{}{}", route, rest), Some(resource_type)))
+}
+
+/// Router serving every configured tenant, the custom endpoints and the
+/// server-wide admin endpoints
+///
+/// Writes are counted into `sync_stats`, e.g. ones restored from a persisted
+/// snapshot.
+pub fn build_router(
+    app_config: Arc<AppConfig>,
+    backend: Arc<dyn ScimBackend>,
+    sync_stats: Arc<SyncStats>,
+) -> Router {
+    let passwords = Arc::new(PasswordManagerRegistry::from_config(&app_config));
+    let state: AppState = (backend, app_config.clone(), passwords);
+    let mut app = Router::new();
+
+    // Custom endpoints are routed as absolute paths, not under tenant URLs.
+    // Only their configured methods are routed, so other methods get 405
+    for (path, methods) in app_config.custom_endpoint_routes() {
+        let mut route = MethodRouter::new();
+        if methods.contains("GET") {
+            route = route.get(resource::custom::handle_custom_endpoint);
+        }
+        if methods.contains("POST") {
+            route = route.post(resource::custom::handle_custom_endpoint_post);
+        }
+        app = app.route(path, route);
+    }
+
+    // Server-wide admin endpoints (enabled by server.admin_token)
+    app = app
+        .route("/admin/tenants", get(resource::admin::initialized_tenants))
+        .route(
+            "/admin/tenants/{id}/share",
+            post(resource::admin::create_share_link),
+        )
+        .route(
+            "/admin/tenants/{id}/vacuum",
+            post(resource::admin::vacuum_tenant),
+        )
+        .route(
+            "/admin/tenants/{id}/history/{resource_type}/{resource_id}",
+            get(resource::admin::resource_history),
+        )
+        .route(
+            "/admin/tenants/{id}/stats",
+            get(resource::admin::tenant_stats).with_state((app_config.clone(), sync_stats.clone())),
+        );
+
+    // Read-only share links (enabled by server.share_link_secret). Only GET
+    // (and HEAD) are routed, so other methods get 405
+    app = app.route(
+        "/shared/{token}",
+        get(resource::shared::get_shared_resource),
+    );
+
+    // Everything else is a tenant's SCIM endpoint
+    let tenants = middleware::from_fn(strip_tenant_path).layer(
+        tenant_routes()
+            .layer(middleware::from_fn_with_state(
+                sync_stats.clone(),
+                stats::stats_middleware,
+            ))
+            .with_state(state.clone()),
+    );
+
+    let compression = compression::layer(&app_config.server.compression);
+    let app = app
+        .fallback_service(tenants)
+        .layer(middleware::from_fn_with_state(
+            app_config.clone(),
+            response_validation::validate_responses_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            app_config.clone(),
+            maintenance::read_only_middleware,
+        ))
+        .layer(middleware::from_fn(
+            permissions::denied_attributes_middleware,
+        ))
+        .layer(middleware::from_fn(logging::logging_middleware))
+        .layer(middleware::from_fn_with_state(
+            (app_config, Arc::new(AuthFailures::default()), sync_stats),
+            auth::auth_middleware,
+        ));
+
+    // Outermost, so the logging middleware sees uncompressed responses
+    match compression {
+        Some(compression) => app.layer(compression).with_state(state),
+        None => app.with_state(state),
+    }
+}
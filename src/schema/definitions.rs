@@ -30,6 +30,7 @@ pub enum AttributeType {
     DateTime,
     Reference,
     Complex,
+    Binary,
 }
 
 /// Mutability of attributes
@@ -442,6 +443,18 @@ lazy_static! {
                         uniqueness: Uniqueness::None,
                         sub_attributes: vec![],
                     },
+                    AttributeDefinition {
+                        name: "primary",
+                        attr_type: AttributeType::Boolean,
+                        multi_valued: false,
+                        description: "A Boolean value indicating the 'primary' or preferred attribute value",
+                        required: false,
+                        case_exact: false,
+                        mutability: Mutability::ReadWrite,
+                        returned: Returned::Default,
+                        uniqueness: Uniqueness::None,
+                        sub_attributes: vec![],
+                    },
                 ],
             },
             AttributeDefinition {
@@ -479,6 +492,18 @@ lazy_static! {
                         uniqueness: Uniqueness::None,
                         sub_attributes: vec![],
                     },
+                    AttributeDefinition {
+                        name: "primary",
+                        attr_type: AttributeType::Boolean,
+                        multi_valued: false,
+                        description: "A Boolean value indicating the 'primary' or preferred attribute value",
+                        required: false,
+                        case_exact: false,
+                        mutability: Mutability::ReadWrite,
+                        returned: Returned::Default,
+                        uniqueness: Uniqueness::None,
+                        sub_attributes: vec![],
+                    },
                 ],
             },
             AttributeDefinition {
@@ -747,13 +772,14 @@ lazy_static! {
                 returned: Returned::Default,
                 uniqueness: Uniqueness::None,
                 sub_attributes: vec![
+                    // Base64-encoded DER; binary values are case exact (RFC 7643 section 2.3.6)
                     AttributeDefinition {
                         name: "value",
-                        attr_type: AttributeType::String,
+                        attr_type: AttributeType::Binary,
                         multi_valued: false,
                         description: "The value of an X.509 certificate",
                         required: false,
-                        case_exact: false,
+                        case_exact: true,
                         mutability: Mutability::ReadWrite,
                         returned: Returned::Default,
                         uniqueness: Uniqueness::None,
@@ -1262,6 +1288,27 @@ pub fn is_multi_valued_attribute(attr: &str, resource_type: ResourceType) -> boo
         .unwrap_or(false)
 }
 
+/// Multi-valued attributes with a `primary` sub-attribute
+pub fn primary_attributes(resource_type: ResourceType) -> Vec<&'static str> {
+    let schema = match resource_type {
+        ResourceType::User => &*USER_SCHEMA,
+        ResourceType::Group => &*GROUP_SCHEMA,
+    };
+
+    schema
+        .attributes
+        .iter()
+        .filter(|attr_def| {
+            attr_def.multi_valued
+                && attr_def
+                    .sub_attributes
+                    .iter()
+                    .any(|sub_attr| sub_attr.name == "primary")
+        })
+        .map(|attr_def| attr_def.name)
+        .collect()
+}
+
 /// Check if attribute is case-exact using schema definitions for specific resource type
 pub fn is_case_exact_field_for_resource(attr_name: &str, resource_type: ResourceType) -> bool {
     let schema = match resource_type {
@@ -1311,6 +1358,13 @@ mod tests {
     fn test_multi_valued_attributes() {
         assert!(is_multi_valued_attribute("emails", ResourceType::User));
         assert!(is_multi_valued_attribute("groups", ResourceType::User));
+        for attr in ["entitlements", "roles", "ims", "x509Certificates"] {
+            assert!(
+                is_multi_valued_attribute(attr, ResourceType::User),
+                "{}",
+                attr
+            );
+        }
         assert!(!is_multi_valued_attribute("userName", ResourceType::User));
 
         assert!(is_multi_valued_attribute("members", ResourceType::Group));
@@ -1320,6 +1374,24 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_primary_attributes() {
+        assert_eq!(
+            primary_attributes(ResourceType::User),
+            vec![
+                "emails",
+                "phoneNumbers",
+                "ims",
+                "photos",
+                "addresses",
+                "entitlements",
+                "roles",
+                "x509Certificates"
+            ]
+        );
+        assert!(primary_attributes(ResourceType::Group).is_empty());
+    }
+
     #[test]
     fn test_find_attribute() {
         let schema = &*USER_SCHEMA;
@@ -1397,8 +1469,10 @@ pub fn any_require_external_table_access(attributes: &[&str], resource_type: Res
 ///
 /// Logic:
 /// - If no attribute filtering is applied, fetch external attributes (default behavior)
-/// - If "attributes" parameter is used, only fetch if external attributes are explicitly requested
-/// - If "excludedAttributes" parameter is used, don't fetch if external attributes are excluded
+/// - If "attributes" parameter is used, only fetch if external attributes are explicitly requested,
+///   even when the default is not to include them
+/// - If "excludedAttributes" parameter is used, fetch per the default unless external attributes
+///   are excluded
 pub fn should_fetch_external_attributes(
     attribute_filter: &AttributeFilter,
     resource_type: ResourceType,
@@ -1415,10 +1489,12 @@ pub fn should_fetch_external_attributes(
         return any_require_external_table_access(&attribute_refs, resource_type);
     }
 
-    // If "excludedAttributes" parameter is used, don't fetch if external attributes are excluded
+    // If "excludedAttributes" parameter is used, don't fetch if external attributes are excluded;
+    // excluding other attributes does not override the default
     if let Some(ref excluded_attributes) = attribute_filter.excluded_attributes {
         let excluded_refs: Vec<&str> = excluded_attributes.iter().map(|s| s.as_str()).collect();
-        return !any_require_external_table_access(&excluded_refs, resource_type);
+        return default_include
+            && !any_require_external_table_access(&excluded_refs, resource_type);
     }
 
     default_include
@@ -1522,6 +1598,22 @@ mod external_table_tests {
             true
         ));
 
+        // Explicitly requested groups are fetched even when not included by default,
+        // but excluding other attributes keeps the default
+        let filter = AttributeFilter::from_params(Some("groups"), None);
+        assert!(should_fetch_external_attributes(
+            &filter,
+            ResourceType::User,
+            false
+        ));
+
+        let filter = AttributeFilter::from_params(None, Some("emails"));
+        assert!(!should_fetch_external_attributes(
+            &filter,
+            ResourceType::User,
+            false
+        ));
+
         // Test Group resource
         let filter = AttributeFilter::from_params(Some("displayName,members"), None);
         assert!(should_fetch_external_attributes(
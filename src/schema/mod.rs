@@ -5,4 +5,10 @@ pub mod validation;
 // Re-export commonly used items from definitions
 pub use definitions::*;
 // Re-export validation functions that are actually used
-pub use validation::{enforce_single_primary, validate_user};
+pub use validation::{
+    enforce_single_primaries, enforce_single_primary, validate_canonical_values, validate_formats,
+    validate_password_accepted, validate_patch_canonical_values, validate_patch_formats,
+    validate_patch_mutability, validate_patch_password_accepted, validate_patch_timezone,
+    validate_patch_value_lengths, validate_scim_response, validate_strict_timezone, validate_user,
+    validate_value_lengths,
+};
@@ -31,9 +31,7 @@ fn normalize_value_recursive(value: &Value, path: &str, resource_type: ResourceT
                 };
 
                 // Check if this field should preserve case using schema definitions
-                // Normalize path for schema lookup by removing array indices
-                let schema_path =
-                    new_path.replace(|c: char| c.is_ascii_digit() || c == '[' || c == ']', "");
+                let schema_path = strip_array_indices(&new_path);
                 let preserve_case =
                     definitions::is_case_exact_field_for_resource(&schema_path, resource_type);
 
@@ -62,9 +60,7 @@ fn normalize_value_recursive(value: &Value, path: &str, resource_type: ResourceT
         }
         Value::String(s) => {
             // Check if this field should preserve case using schema definitions
-            // Normalize path for schema lookup by removing array indices
-            let schema_path =
-                path.replace(|c: char| c.is_ascii_digit() || c == '[' || c == ']', "");
+            let schema_path = strip_array_indices(path);
             if definitions::is_case_exact_field_for_resource(&schema_path, resource_type) {
                 value.clone()
             } else {
@@ -80,9 +76,25 @@ fn normalize_value_recursive(value: &Value, path: &str, resource_type: ResourceT
 /// This function delegates to the schema definitions in definitions.rs
 /// for consistent case-exact behavior across the codebase.
 pub fn is_case_exact_field_for_resource(path: &str, resource_type: ResourceType) -> bool {
-    // Normalize path for schema lookup by removing array indices
-    let schema_path = path.replace(|c: char| c.is_ascii_digit() || c == '[' || c == ']', "");
-    definitions::is_case_exact_field_for_resource(&schema_path, resource_type)
+    definitions::is_case_exact_field_for_resource(&strip_array_indices(path), resource_type)
+}
+
+/// Remove `[n]` array indices from a path for schema lookup
+///
+/// Only bracketed indices are removed, so digits inside attribute names
+/// (e.g. `x509Certificates`) are kept.
+fn strip_array_indices(path: &str) -> String {
+    let mut result = String::with_capacity(path.len());
+    let mut in_index = false;
+    for c in path.chars() {
+        match c {
+            '[' => in_index = true,
+            ']' => in_index = false,
+            _ if !in_index => result.push(c),
+            _ => {}
+        }
+    }
+    result
 }
 
 #[cfg(test)]
@@ -193,6 +205,10 @@ mod tests {
             "groups.value",
             ResourceType::User
         )); // User groups reference
+        assert!(is_case_exact_field_for_resource(
+            "x509Certificates.value",
+            ResourceType::User
+        )); // Base64 certificate data
 
         // Case-exact fields per schema definitions for Group
         assert!(is_case_exact_field_for_resource("id", ResourceType::Group));
@@ -1,6 +1,10 @@
 #![allow(clippy::collapsible_match)]
 
-use crate::error::{AppError, AppResult};
+use crate::config::{EmailFormat, FormatValidationConfig, PhoneNumberFormat};
+use crate::error::{AppError, AppResult, ValidationIssue};
+use crate::models::ScimPatchOperation;
+use crate::parser::patch_parser::ScimPath;
+use crate::parser::ResourceType;
 use chrono_tz::Tz;
 use email_address::EmailAddress;
 use fluent_uri::UriRef;
@@ -8,8 +12,14 @@ use langtag::LangTag;
 use regex::Regex;
 use scim_v2::models::user::User;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::str::FromStr;
 
+use super::definitions::{
+    primary_attributes, AttributeDefinition, AttributeType, Mutability, ENTERPRISE_USER_SCHEMA,
+    GROUP_SCHEMA, SCIM_SCHEMA_ENTERPRISE_USER, USER_SCHEMA,
+};
+
 /// Validates that at most one element in a multi-valued attribute has primary=true
 pub fn validate_primary_constraint(multi_value_attr: &[Value]) -> AppResult<()> {
     let mut primary_count = 0;
@@ -34,66 +44,23 @@ pub fn validate_primary_constraint(multi_value_attr: &[Value]) -> AppResult<()>
 }
 
 /// Validates primary constraint for all multi-valued attributes in a User
-pub fn validate_user_primary_constraints(user_json: &Value) -> AppResult<()> {
+///
+/// Returns one issue per attribute with more than one primary=true value.
+pub fn user_primary_constraint_issues(user_json: &Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
     if let Value::Object(user_obj) = user_json {
-        // Check emails
-        if let Some(emails) = user_obj.get("emails") {
-            if let Value::Array(emails_arr) = emails {
-                validate_primary_constraint(emails_arr)?;
-            }
-        }
-
-        // Check phoneNumbers
-        if let Some(phones) = user_obj.get("phoneNumbers") {
-            if let Value::Array(phones_arr) = phones {
-                validate_primary_constraint(phones_arr)?;
-            }
-        }
-
-        // Check addresses
-        if let Some(addresses) = user_obj.get("addresses") {
-            if let Value::Array(addresses_arr) = addresses {
-                validate_primary_constraint(addresses_arr)?;
-            }
-        }
-
-        // Check photos
-        if let Some(photos) = user_obj.get("photos") {
-            if let Value::Array(photos_arr) = photos {
-                validate_primary_constraint(photos_arr)?;
-            }
-        }
-
-        // Check ims
-        if let Some(ims) = user_obj.get("ims") {
-            if let Value::Array(ims_arr) = ims {
-                validate_primary_constraint(ims_arr)?;
-            }
-        }
-
-        // Check entitlements
-        if let Some(entitlements) = user_obj.get("entitlements") {
-            if let Value::Array(entitlements_arr) = entitlements {
-                validate_primary_constraint(entitlements_arr)?;
-            }
-        }
-
-        // Check roles
-        if let Some(roles) = user_obj.get("roles") {
-            if let Value::Array(roles_arr) = roles {
-                validate_primary_constraint(roles_arr)?;
-            }
-        }
-
-        // Check x509Certificates
-        if let Some(certs) = user_obj.get("x509Certificates") {
-            if let Value::Array(certs_arr) = certs {
-                validate_primary_constraint(certs_arr)?;
+        for attr in primary_attributes(ResourceType::User) {
+            if let Some(Value::Array(values)) = user_obj.get(attr) {
+                if validate_primary_constraint(values).is_err() {
+                    issues.push(ValidationIssue::invalid_value(
+                        attr,
+                        format!("At most one element can have primary=true in {}", attr),
+                    ));
+                }
             }
         }
     }
-
-    Ok(())
+    issues
 }
 
 /// Ensures at most one primary value when adding/replacing multi-valued attributes
@@ -123,6 +90,25 @@ pub fn enforce_single_primary(multi_value_attr: &mut [Value]) -> AppResult<()> {
     Ok(())
 }
 
+/// Ensures at most one primary value in every multi-valued attribute of a resource
+///
+/// Covers the schema's multi-valued attributes with a `primary` sub-attribute;
+/// the first primary=true element of each wins.
+pub fn enforce_single_primaries(
+    resource: &mut Value,
+    resource_type: ResourceType,
+) -> AppResult<()> {
+    if let Value::Object(obj) = resource {
+        for attr in primary_attributes(resource_type) {
+            if let Some(Value::Array(values)) = obj.get_mut(attr) {
+                enforce_single_primary(values)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,6 +165,35 @@ mod tests {
         assert!(emails[1]["primary"].is_null());
     }
 
+    #[test]
+    fn test_enforce_single_primaries() {
+        let mut user = json!({
+            "phoneNumbers": [
+                {"value": "+1-555-0100", "type": "work", "primary": true},
+                {"value": "+1-555-0101", "type": "mobile", "primary": true}
+            ],
+            "addresses": [
+                {"locality": "Tokyo", "primary": false},
+                {"locality": "Osaka", "primary": true},
+                {"locality": "Kyoto", "primary": true}
+            ],
+            "ims": [{"value": "alice", "primary": true}],
+            "groups": [{"value": "g1", "primary": true}, {"value": "g2", "primary": true}]
+        });
+
+        assert!(enforce_single_primaries(&mut user, ResourceType::User).is_ok());
+        assert!(user_primary_constraint_issues(&user).is_empty());
+
+        assert_eq!(user["phoneNumbers"][0]["primary"], true);
+        assert!(user["phoneNumbers"][1]["primary"].is_null());
+        assert_eq!(user["addresses"][0]["primary"], false);
+        assert_eq!(user["addresses"][1]["primary"], true);
+        assert!(user["addresses"][2]["primary"].is_null());
+        assert_eq!(user["ims"][0]["primary"], true);
+        // Attributes without a primary sub-attribute are left alone
+        assert_eq!(user["groups"][1]["primary"], true);
+    }
+
     #[test]
     fn test_user_primary_constraints() {
         let user = json!({
@@ -191,7 +206,8 @@ mod tests {
             ]
         });
 
-        assert!(validate_user_primary_constraints(&user).is_err());
+        assert_eq!(user_primary_constraint_issues(&user).len(), 1);
+        assert_eq!(user_primary_constraint_issues(&user)[0].path, "emails");
     }
 }
 
@@ -258,6 +274,75 @@ pub fn validate_timezone(timezone: &str) -> bool {
     offset_regex.is_match(timezone)
 }
 
+/// Areas of the canonical IANA zone identifiers, e.g. `America/New_York`
+const TIMEZONE_AREAS: &[&str] = &[
+    "Africa",
+    "America",
+    "Antarctica",
+    "Arctic",
+    "Asia",
+    "Atlantic",
+    "Australia",
+    "Europe",
+    "Indian",
+    "Pacific",
+    "Etc",
+];
+
+/// Validates a timezone strictly against the IANA timezone database
+///
+/// Used when `compatibility.validate_timezone` is enabled. Unlike
+/// [`validate_timezone`], UTC offsets are not accepted and neither are the
+/// backward-compatible names outside the regional areas, such as
+/// `US/Eastern` or `EST5EDT`.
+pub fn validate_iana_timezone(timezone: &str) -> bool {
+    if Tz::from_str(timezone).is_err() {
+        return false;
+    }
+    timezone == "UTC"
+        || timezone
+            .split_once('/')
+            .is_some_and(|(area, _)| TIMEZONE_AREAS.contains(&area))
+}
+
+fn check_iana_timezone(value: &Value) -> AppResult<()> {
+    match value {
+        Value::Null => Ok(()),
+        Value::String(timezone) if validate_iana_timezone(timezone) => Ok(()),
+        _ => Err(AppError::InvalidValue(format!(
+            "timezone must be an IANA timezone identifier, got {}",
+            value
+        ))),
+    }
+}
+
+/// Validates the `timezone` of a User payload with [`validate_iana_timezone`]
+pub fn validate_strict_timezone(resource: &Value) -> AppResult<()> {
+    match get_attribute(resource, "timezone") {
+        Some(value) => check_iana_timezone(value),
+        None => Ok(()),
+    }
+}
+
+/// Validates timezones carried by PATCH add/replace operations
+pub fn validate_patch_timezone(operations: &[ScimPatchOperation]) -> AppResult<()> {
+    for operation in operations {
+        let op = operation.op.to_lowercase();
+        if op != "add" && op != "replace" {
+            continue;
+        }
+        let Some(value) = &operation.value else {
+            continue;
+        };
+        match operation.path.as_deref() {
+            None | Some("") => validate_strict_timezone(value)?,
+            Some(path) if path.eq_ignore_ascii_case("timezone") => check_iana_timezone(value)?,
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
 /// Validates locale format according to RFC 5646 (BCP 47)
 /// Language tags like "en", "en-US", "zh-Hans-CN", etc.
 /// This validation is stricter than pure BCP 47 syntax validation - it requires
@@ -330,10 +415,17 @@ fn is_valid_language_code(lang: &str) -> bool {
 }
 
 /// Validates User resource with comprehensive checks
+///
+/// Every problem is collected, so one `AppError::Validation` lists them all.
 pub fn validate_user(user: &User) -> AppResult<()> {
+    let mut issues = Vec::new();
+
     // Core validation
     if user.user_name.is_empty() {
-        return Err(AppError::BadRequest("userName is required".to_string()));
+        issues.push(ValidationIssue::invalid_value(
+            "userName",
+            "userName is required",
+        ));
     }
 
     // Convert user to JSON for primary validation
@@ -341,17 +433,17 @@ pub fn validate_user(user: &User) -> AppResult<()> {
         .map_err(|e| AppError::BadRequest(format!("Failed to serialize user: {}", e)))?;
 
     // Validate primary constraints
-    validate_user_primary_constraints(&user_json)?;
+    issues.extend(user_primary_constraint_issues(&user_json));
 
     // Validate emails
     if let Some(emails) = &user.emails {
-        for email in emails {
+        for (index, email) in emails.iter().enumerate() {
             if let Some(value) = &email.value {
                 if !validate_email(value) {
-                    return Err(AppError::BadRequest(format!(
-                        "Invalid email format: {}",
-                        value
-                    )));
+                    issues.push(ValidationIssue::invalid_value(
+                        format!("emails[{}].value", index),
+                        format!("Invalid email format: {}", value),
+                    ));
                 }
             }
         }
@@ -363,21 +455,21 @@ pub fn validate_user(user: &User) -> AppResult<()> {
     // Validate URLs (profileUrl, photos)
     if let Some(profile_url) = &user.profile_url {
         if !validate_url(profile_url) {
-            return Err(AppError::BadRequest(format!(
-                "Invalid profile URL format: {}",
-                profile_url
-            )));
+            issues.push(ValidationIssue::invalid_value(
+                "profileUrl",
+                format!("Invalid profile URL format: {}", profile_url),
+            ));
         }
     }
 
     if let Some(photos) = &user.photos {
-        for photo in photos {
+        for (index, photo) in photos.iter().enumerate() {
             if let Some(value) = &photo.value {
                 if !validate_url(value) {
-                    return Err(AppError::BadRequest(format!(
-                        "Invalid photo URL format: {}",
-                        value
-                    )));
+                    issues.push(ValidationIssue::invalid_value(
+                        format!("photos[{}].value", index),
+                        format!("Invalid photo URL format: {}", value),
+                    ));
                 }
             }
         }
@@ -386,30 +478,31 @@ pub fn validate_user(user: &User) -> AppResult<()> {
     // Validate timezone
     if let Some(timezone) = &user.timezone {
         if !validate_timezone(timezone) {
-            return Err(AppError::BadRequest(format!(
-                "Invalid timezone format: {}",
-                timezone
-            )));
+            issues.push(ValidationIssue::invalid_value(
+                "timezone",
+                format!("Invalid timezone format: {}", timezone),
+            ));
         }
     }
 
     // Validate locale
     if let Some(locale) = &user.locale {
         if !validate_locale(locale) {
-            return Err(AppError::BadRequest(format!(
-                "Invalid locale format: {}",
-                locale
-            )));
+            issues.push(ValidationIssue::invalid_value(
+                "locale",
+                format!("Invalid locale format: {}", locale),
+            ));
         }
     }
 
     // Validate X.509 certificates
     if let Some(certs) = &user.x509_certificates {
-        for cert in certs {
+        for (index, cert) in certs.iter().enumerate() {
             if let Some(value) = &cert.value {
                 if !validate_x509_certificate(value) {
-                    return Err(AppError::BadRequest(
-                        "Invalid X.509 certificate format".to_string(),
+                    issues.push(ValidationIssue::invalid_value(
+                        format!("x509Certificates[{}].value", index),
+                        "Invalid X.509 certificate format",
                     ));
                 }
             }
@@ -418,23 +511,30 @@ pub fn validate_user(user: &User) -> AppResult<()> {
 
     // Validate Enterprise User extension
     if let Some(enterprise) = &user.enterprise_user {
-        validate_enterprise_user(enterprise)?;
+        issues.extend(enterprise_user_issues(enterprise));
     }
 
-    Ok(())
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(issues))
+    }
 }
 
 /// Validates Enterprise User extension
-pub fn validate_enterprise_user(
+fn enterprise_user_issues(
     enterprise: &scim_v2::models::enterprise_user::EnterpriseUser,
-) -> AppResult<()> {
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
     // Validate manager reference if present
     if let Some(manager) = &enterprise.manager {
         if let Some(value) = &manager.value {
             // Manager value should be a valid user ID (UUID format or similar)
             if value.is_empty() {
-                return Err(AppError::BadRequest(
-                    "Manager value cannot be empty".to_string(),
+                issues.push(ValidationIssue::invalid_value(
+                    format!("{}:manager.value", SCIM_SCHEMA_ENTERPRISE_USER),
+                    "Manager value cannot be empty",
                 ));
             }
         }
@@ -443,9 +543,569 @@ pub fn validate_enterprise_user(
     // Additional business logic validation can be added here
     // For example: validate employee number format, cost center codes, etc.
 
+    issues
+}
+
+/// Validates tenant-configured canonical values (e.g. `emails.type`)
+///
+/// Keys are `attribute.subAttribute` and match case-insensitively; values are
+/// compared exactly. With no configuration every value is accepted.
+pub fn validate_canonical_values(
+    resource: &Value,
+    canonical_values: &HashMap<String, Vec<String>>,
+) -> AppResult<()> {
+    for (key, allowed) in canonical_values {
+        let Some((attr, sub_attr)) = key.split_once('.') else {
+            continue;
+        };
+        if let Some(value) = get_attribute(resource, attr) {
+            validate_sub_attribute_values(value, key, sub_attr, allowed)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a `password` in User payloads of tenants with `accept_password: false`
+pub fn validate_password_accepted(resource: &Value, accept_password: bool) -> AppResult<()> {
+    if accept_password {
+        return Ok(());
+    }
+    match get_attribute(resource, "password") {
+        Some(value) if !value.is_null() => Err(password_not_accepted()),
+        _ => Ok(()),
+    }
+}
+
+/// Rejects PATCH add/replace operations that set `password` when the tenant
+/// does not accept passwords
+pub fn validate_patch_password_accepted(
+    operations: &[ScimPatchOperation],
+    accept_password: bool,
+) -> AppResult<()> {
+    if accept_password {
+        return Ok(());
+    }
+
+    for operation in operations {
+        let op = operation.op.to_lowercase();
+        if op != "add" && op != "replace" {
+            continue;
+        }
+        let Some(value) = &operation.value else {
+            continue;
+        };
+
+        match operation.path.as_deref() {
+            None | Some("") => validate_password_accepted(value, false)?,
+            Some(path) => {
+                let name = path.rsplit(':').next().unwrap_or(path);
+                if name.eq_ignore_ascii_case("password") {
+                    return Err(password_not_accepted());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn password_not_accepted() -> AppError {
+    AppError::InvalidValue("password is not accepted by this tenant".to_string())
+}
+
+/// Validates canonical values carried by PATCH add/replace operations
+pub fn validate_patch_canonical_values(
+    operations: &[ScimPatchOperation],
+    canonical_values: &HashMap<String, Vec<String>>,
+) -> AppResult<()> {
+    if canonical_values.is_empty() {
+        return Ok(());
+    }
+
+    for operation in operations {
+        let op = operation.op.to_lowercase();
+        if op != "add" && op != "replace" {
+            continue;
+        }
+        let Some(value) = &operation.value else {
+            continue;
+        };
+
+        let Some(path) = &operation.path else {
+            validate_canonical_values(value, canonical_values)?;
+            continue;
+        };
+
+        // emails[type eq "work"].type targets the same sub-attribute as emails.type
+        let path = strip_value_filter(path);
+        let (attr, sub_attr) = match path.split_once('.') {
+            Some((attr, sub_attr)) => (attr, Some(sub_attr)),
+            None => (path.as_str(), None),
+        };
+
+        for (key, allowed) in canonical_values {
+            let Some((canonical_attr, canonical_sub)) = key.split_once('.') else {
+                continue;
+            };
+            if !canonical_attr.eq_ignore_ascii_case(attr) {
+                continue;
+            }
+            match sub_attr {
+                Some(sub_attr) if sub_attr.eq_ignore_ascii_case(canonical_sub) => {
+                    validate_canonical_value(value, key, allowed)?
+                }
+                Some(_) => {}
+                None => validate_sub_attribute_values(value, key, canonical_sub, allowed)?,
+            }
+        }
+    }
+
     Ok(())
 }
 
+/// Rejects string values longer than `max_length` characters anywhere in a
+/// resource payload (0 disables the check)
+pub fn validate_value_lengths(resource: &Value, max_length: u32) -> AppResult<()> {
+    if max_length == 0 {
+        return Ok(());
+    }
+    check_value_lengths(resource, "", max_length as usize)
+}
+
+/// Applies [`validate_value_lengths`] to the values of PATCH add/replace operations
+pub fn validate_patch_value_lengths(
+    operations: &[ScimPatchOperation],
+    max_length: u32,
+) -> AppResult<()> {
+    if max_length == 0 {
+        return Ok(());
+    }
+    for operation in operations {
+        let op = operation.op.to_lowercase();
+        if op != "add" && op != "replace" {
+            continue;
+        }
+        if let Some(value) = &operation.value {
+            let path = operation.path.as_deref().unwrap_or_default();
+            check_value_lengths(value, &strip_value_filter(path), max_length as usize)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a PATCH operation that targets an `immutable` attribute
+///
+/// Immutable attributes such as `members.value` are set when the resource is
+/// created or replaced, never updated (RFC 7643 Section 7). A path-less value
+/// is checked attribute by attribute, extension objects included.
+pub fn validate_patch_mutability(
+    operation: &ScimPatchOperation,
+    resource_type: ResourceType,
+) -> AppResult<()> {
+    let paths: Vec<String> = match (operation.path.as_deref(), &operation.value) {
+        (Some(path), _) if !path.is_empty() => vec![path.to_string()],
+        (_, Some(Value::Object(attributes))) => attributes
+            .iter()
+            .flat_map(|(key, value)| match value {
+                Value::Object(extension) if key.contains(':') => extension
+                    .keys()
+                    .map(|attr| format!("{}:{}", key, attr))
+                    .collect(),
+                _ => vec![key.clone()],
+            })
+            .collect(),
+        _ => return Ok(()),
+    };
+
+    for path in paths {
+        // Malformed paths are reported when the operation is applied
+        let Ok(scim_path) = ScimPath::parse(&path) else {
+            continue;
+        };
+        if scim_path.mutability(resource_type) == Some(Mutability::Immutable) {
+            return Err(AppError::Validation(vec![ValidationIssue::mutability(
+                &path,
+                format!("{} is immutable and cannot be modified", path),
+            )]));
+        }
+    }
+    Ok(())
+}
+
+fn check_value_lengths(value: &Value, path: &str, max_length: usize) -> AppResult<()> {
+    match value {
+        // Byte length bounds the character count, so most values skip counting
+        Value::String(s) if s.len() > max_length && s.chars().count() > max_length => {
+            Err(AppError::InvalidValue(format!(
+                "{} is longer than the maximum of {} characters",
+                if path.is_empty() { "value" } else { path },
+                max_length
+            )))
+        }
+        Value::Array(items) => items
+            .iter()
+            .try_for_each(|item| check_value_lengths(item, path, max_length)),
+        Value::Object(object) => object.iter().try_for_each(|(key, item)| {
+            let path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            check_value_lengths(item, &path, max_length)
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Formats checked by [`validate_formats`]
+#[derive(Clone, Copy)]
+enum Format {
+    Email,
+    Url,
+    LanguageTag,
+    Phone(PhoneNumberFormat),
+}
+
+impl Format {
+    fn description(self) -> &'static str {
+        match self {
+            Format::Email => "an email address",
+            Format::Url => "a URL",
+            Format::LanguageTag => "a BCP 47 language tag",
+            Format::Phone(PhoneNumberFormat::E164) => "an E.164 phone number",
+            Format::Phone(_) => "a phone number",
+        }
+    }
+
+    /// Returns the value to store, or `None` when it is malformed
+    fn apply(self, value: &str) -> Option<String> {
+        let valid = match self {
+            Format::Email => validate_email(value),
+            Format::Url => validate_url(value),
+            Format::LanguageTag => LangTag::new(value).is_ok(),
+            Format::Phone(PhoneNumberFormat::E164) => return normalize_e164(value),
+            Format::Phone(_) => validate_phone_number(value),
+        };
+        valid.then(|| value.to_string())
+    }
+}
+
+/// Attributes checked for a configuration, and whether they are multi-valued
+fn format_rules(config: &FormatValidationConfig) -> Vec<(&'static str, bool, Format)> {
+    let mut rules = Vec::new();
+    if config.emails == EmailFormat::Strict {
+        rules.extend([
+            ("emails", true, Format::Email),
+            ("photos", true, Format::Url),
+            ("profileUrl", false, Format::Url),
+            ("preferredLanguage", false, Format::LanguageTag),
+            ("locale", false, Format::LanguageTag),
+        ]);
+    }
+    if config.phone_numbers != PhoneNumberFormat::Off {
+        rules.push(("phoneNumbers", true, Format::Phone(config.phone_numbers)));
+    }
+    rules
+}
+
+/// Validates a phone number loosely: digits and common separators, with an
+/// optional `tel:` prefix and leading `+`
+pub fn validate_phone_number(phone: &str) -> bool {
+    let number = phone.strip_prefix("tel:").unwrap_or(phone);
+    let number = number.strip_prefix('+').unwrap_or(number);
+    number.chars().any(|c| c.is_ascii_digit())
+        && number
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')'))
+}
+
+/// Normalizes a phone number to E.164 (`+` and up to 15 digits)
+///
+/// Separators and a `tel:` prefix are dropped, so `tel:+1-201-555-0123`
+/// becomes `+12015550123`. Numbers without a country code are rejected.
+pub fn normalize_e164(phone: &str) -> Option<String> {
+    if !validate_phone_number(phone) {
+        return None;
+    }
+    let number = phone.strip_prefix("tel:").unwrap_or(phone);
+    let digits: String = number
+        .strip_prefix('+')?
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+    let valid = (2..=15).contains(&digits.len()) && !digits.starts_with('0');
+    valid.then(|| format!("+{}", digits))
+}
+
+fn check_format(value: &mut Value, name: &str, format: Format) -> AppResult<()> {
+    let normalized = match value {
+        Value::Null => return Ok(()),
+        Value::String(s) => format.apply(s),
+        _ => None,
+    };
+    match normalized {
+        Some(normalized) => {
+            *value = Value::String(normalized);
+            Ok(())
+        }
+        None => Err(AppError::InvalidValue(format!(
+            "{} must be {}, got {}",
+            name,
+            format.description(),
+            value
+        ))),
+    }
+}
+
+fn check_multi_valued_format(value: &mut Value, attr: &str, format: Format) -> AppResult<()> {
+    match value {
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                if let Some(item_value) = get_attribute_mut(item, "value") {
+                    check_format(item_value, &format!("{}[{}].value", attr, index), format)?;
+                }
+            }
+            Ok(())
+        }
+        Value::Object(_) => match get_attribute_mut(value, "value") {
+            Some(item_value) => check_format(item_value, &format!("{}.value", attr), format),
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Validates tenant-configured formats (`validate_formats`) of a User payload
+///
+/// Phone numbers are rewritten in place when the tenant normalizes to E.164.
+/// Errors name the attribute and its index, e.g. `emails[1].value`.
+pub fn validate_formats(resource: &mut Value, config: &FormatValidationConfig) -> AppResult<()> {
+    for (attr, multi_valued, format) in format_rules(config) {
+        let Some(value) = get_attribute_mut(resource, attr) else {
+            continue;
+        };
+        if multi_valued {
+            check_multi_valued_format(value, attr, format)?;
+        } else {
+            check_format(value, attr, format)?;
+        }
+    }
+    Ok(())
+}
+
+/// Validates formats carried by PATCH add/replace operations
+pub fn validate_patch_formats(
+    operations: &mut [ScimPatchOperation],
+    config: &FormatValidationConfig,
+) -> AppResult<()> {
+    let rules = format_rules(config);
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    for operation in operations {
+        let op = operation.op.to_lowercase();
+        if op != "add" && op != "replace" {
+            continue;
+        }
+        let Some(value) = &mut operation.value else {
+            continue;
+        };
+
+        let path = match operation.path.as_deref() {
+            None | Some("") => {
+                validate_formats(value, config)?;
+                continue;
+            }
+            Some(path) => path,
+        };
+
+        // emails[type eq "work"].value targets the same sub-attribute as emails.value
+        let stripped = strip_value_filter(path);
+        let (attr, sub_attr) = match stripped.split_once('.') {
+            Some((attr, sub_attr)) => (attr, Some(sub_attr)),
+            None => (stripped.as_str(), None),
+        };
+
+        for (rule_attr, multi_valued, format) in &rules {
+            if !rule_attr.eq_ignore_ascii_case(attr) {
+                continue;
+            }
+            match (multi_valued, sub_attr) {
+                (true, None) => check_multi_valued_format(value, rule_attr, *format)?,
+                (true, Some(sub_attr)) if sub_attr.eq_ignore_ascii_case("value") => {
+                    check_format(value, path, *format)?
+                }
+                (false, None) => check_format(value, rule_attr, *format)?,
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check an outgoing User or Group against its schema definition
+///
+/// Returns one message per violation: a required attribute that is missing,
+/// a writeOnly attribute that is returned, a value of the wrong type or a
+/// multi-valued attribute that is not an array. Attributes the schema does
+/// not define are ignored.
+pub fn validate_scim_response(resource_type: ResourceType, json: &Value) -> Vec<String> {
+    let schema = match resource_type {
+        ResourceType::User => &*USER_SCHEMA,
+        ResourceType::Group => &*GROUP_SCHEMA,
+    };
+
+    let mut violations = Vec::new();
+    check_attributes(&schema.attributes, json, "", &mut violations);
+    if resource_type == ResourceType::User {
+        if let Some(extension) = json.get(SCIM_SCHEMA_ENTERPRISE_USER) {
+            let prefix = format!("{}:", SCIM_SCHEMA_ENTERPRISE_USER);
+            check_attributes(
+                &ENTERPRISE_USER_SCHEMA.attributes,
+                extension,
+                &prefix,
+                &mut violations,
+            );
+        }
+    }
+    violations
+}
+
+fn check_attributes(
+    definitions: &[AttributeDefinition],
+    object: &Value,
+    prefix: &str,
+    violations: &mut Vec<String>,
+) {
+    if !object.is_object() {
+        violations.push(format!(
+            "{} must be an object",
+            prefix.trim_end_matches(':')
+        ));
+        return;
+    }
+
+    for definition in definitions {
+        let path = format!("{}{}", prefix, definition.name);
+        let value = get_attribute(object, definition.name).filter(|value| !value.is_null());
+        let Some(value) = value else {
+            if definition.required {
+                violations.push(format!("{} is required but missing", path));
+            }
+            continue;
+        };
+
+        if definition.mutability == Mutability::WriteOnly {
+            violations.push(format!("{} is writeOnly but returned", path));
+            continue;
+        }
+        if !definition.multi_valued {
+            check_type(definition, value, &path, violations);
+            continue;
+        }
+        match value.as_array() {
+            Some(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    check_type(
+                        definition,
+                        value,
+                        &format!("{}[{}]", path, index),
+                        violations,
+                    );
+                }
+            }
+            None => violations.push(format!("{} is multi-valued but not an array", path)),
+        }
+    }
+}
+
+fn check_type(
+    definition: &AttributeDefinition,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<String>,
+) {
+    let (matches, expected) = match definition.attr_type {
+        AttributeType::Boolean => (value.is_boolean(), "a boolean"),
+        AttributeType::Integer => (value.is_i64() || value.is_u64(), "an integer"),
+        AttributeType::Decimal => (value.is_number(), "a number"),
+        AttributeType::Complex => (value.is_object(), "an object"),
+        AttributeType::String
+        | AttributeType::DateTime
+        | AttributeType::Reference
+        | AttributeType::Binary => (value.is_string(), "a string"),
+    };
+
+    if !matches {
+        violations.push(format!("{} must be {}, got {}", path, expected, value));
+    } else if definition.attr_type == AttributeType::Complex {
+        check_attributes(
+            &definition.sub_attributes,
+            value,
+            &format!("{}.", path),
+            violations,
+        );
+    }
+}
+
+fn get_attribute<'a>(resource: &'a Value, name: &str) -> Option<&'a Value> {
+    resource
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+fn get_attribute_mut<'a>(resource: &'a mut Value, name: &str) -> Option<&'a mut Value> {
+    resource
+        .as_object_mut()?
+        .iter_mut()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+fn strip_value_filter(path: &str) -> String {
+    match (path.find('['), path.rfind(']')) {
+        (Some(start), Some(end)) if start < end => {
+            format!("{}{}", &path[..start], &path[end + 1..])
+        }
+        _ => path.to_string(),
+    }
+}
+
+fn validate_sub_attribute_values(
+    value: &Value,
+    key: &str,
+    sub_attr: &str,
+    allowed: &[String],
+) -> AppResult<()> {
+    let items = match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    for item in items {
+        if let Some(sub_value) = get_attribute(item, sub_attr) {
+            validate_canonical_value(sub_value, key, allowed)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_canonical_value(value: &Value, key: &str, allowed: &[String]) -> AppResult<()> {
+    match value {
+        Value::Null => Ok(()),
+        Value::String(s) if allowed.iter().any(|a| a == s) => Ok(()),
+        other => Err(AppError::InvalidValue(format!(
+            "{} must be one of [{}], got {}",
+            key,
+            allowed.join(", "),
+            other
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod validation_tests {
     use super::*;
@@ -500,6 +1160,47 @@ mod validation_tests {
         assert!(!validate_timezone("PST9PDT")); // Obsolete format not in IANA
     }
 
+    #[test]
+    fn test_iana_timezone_validation() {
+        assert!(validate_iana_timezone("America/New_York"));
+        assert!(validate_iana_timezone("Asia/Tokyo"));
+        assert!(validate_iana_timezone("Etc/GMT+5"));
+        assert!(validate_iana_timezone("UTC"));
+
+        // Legacy links, offsets and unknown zones
+        assert!(!validate_iana_timezone("US/Eastern"));
+        assert!(!validate_iana_timezone("EST5EDT"));
+        assert!(!validate_iana_timezone("GMT"));
+        assert!(!validate_iana_timezone("Etc/GMT+25"));
+        assert!(!validate_iana_timezone("+09:00"));
+        assert!(!validate_iana_timezone("america/new_york"));
+
+        let replace = |path: Option<&str>, value: Value| ScimPatchOperation {
+            op: "replace".to_string(),
+            path: path.map(str::to_string),
+            value: Some(value),
+        };
+        assert!(validate_patch_timezone(&[replace(
+            Some("timezone"),
+            serde_json::json!("Europe/Paris")
+        )])
+        .is_ok());
+        assert!(validate_patch_timezone(&[replace(
+            Some("timezone"),
+            serde_json::json!("US/Pacific")
+        )])
+        .is_err());
+        assert!(validate_patch_timezone(&[replace(
+            None,
+            serde_json::json!({"timeZone": "EST5EDT"})
+        )])
+        .is_err());
+        assert!(
+            validate_patch_timezone(&[replace(Some("title"), serde_json::json!("EST5EDT"))])
+                .is_ok()
+        );
+    }
+
     #[test]
     fn test_locale_validation() {
         // Valid language tags according to RFC 5646 (BCP 47)
@@ -517,4 +1218,277 @@ mod validation_tests {
         assert!(!validate_locale("toolongcode"));
         assert!(!validate_locale("invalid-locale")); // Invalid language code
     }
+
+    #[test]
+    fn test_canonical_values_validation() {
+        let mut canonical = HashMap::new();
+        canonical.insert(
+            "emails.type".to_string(),
+            vec!["work".to_string(), "home".to_string(), "other".to_string()],
+        );
+
+        let allowed = serde_json::json!({"emails": [{"value": "a@example.com", "type": "work"}]});
+        assert!(validate_canonical_values(&allowed, &canonical).is_ok());
+        assert!(validate_canonical_values(&allowed, &HashMap::new()).is_ok());
+
+        let rejected = serde_json::json!({"emails": [{"value": "a@example.com", "type": "pager"}]});
+        assert!(matches!(
+            validate_canonical_values(&rejected, &canonical),
+            Err(AppError::InvalidValue(_))
+        ));
+
+        let patch = |path: Option<&str>, value: Value| ScimPatchOperation {
+            op: "replace".to_string(),
+            path: path.map(str::to_string),
+            value: Some(value),
+        };
+        assert!(validate_patch_canonical_values(
+            &[patch(
+                Some("emails[type eq \"work\"].type"),
+                serde_json::json!("home")
+            )],
+            &canonical
+        )
+        .is_ok());
+        assert!(validate_patch_canonical_values(
+            &[patch(
+                Some("emails[type eq \"work\"].type"),
+                serde_json::json!("pager")
+            )],
+            &canonical
+        )
+        .is_err());
+        assert!(validate_patch_canonical_values(
+            &[patch(
+                Some("emails"),
+                serde_json::json!([{"type": "pager"}])
+            )],
+            &canonical
+        )
+        .is_err());
+        assert!(validate_patch_canonical_values(&[patch(None, rejected)], &canonical).is_err());
+    }
+
+    fn formats(emails: EmailFormat, phone_numbers: PhoneNumberFormat) -> FormatValidationConfig {
+        FormatValidationConfig {
+            emails,
+            phone_numbers,
+        }
+    }
+
+    fn invalid_value_message(result: AppResult<()>) -> String {
+        match result {
+            Err(AppError::InvalidValue(message)) => message,
+            other => panic!("expected InvalidValue, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_email_formats() {
+        let strict = formats(EmailFormat::Strict, PhoneNumberFormat::Off);
+        let mut valid = serde_json::json!({"emails": [{"value": "bjensen@example.com"}]});
+        assert!(validate_formats(&mut valid, &strict).is_ok());
+
+        let mut invalid = serde_json::json!({
+            "emails": [{"value": "bjensen@example.com"}, {"value": "not-an-email"}]
+        });
+        let message = invalid_value_message(validate_formats(&mut invalid, &strict));
+        assert!(message.starts_with("emails[1].value"), "{}", message);
+
+        // Off (the default) keeps accepting anything
+        assert!(validate_formats(&mut invalid, &FormatValidationConfig::default()).is_ok());
+
+        let replace = |path: &str, value: Value| ScimPatchOperation {
+            op: "replace".to_string(),
+            path: Some(path.to_string()),
+            value: Some(value),
+        };
+        let mut ops = [replace(
+            "emails[type eq \"work\"].value",
+            serde_json::json!("nope"),
+        )];
+        assert!(validate_patch_formats(&mut ops, &strict).is_err());
+        let mut ops = [replace(
+            "emails",
+            serde_json::json!([{"value": "a@example.com"}]),
+        )];
+        assert!(validate_patch_formats(&mut ops, &strict).is_ok());
+    }
+
+    #[test]
+    fn test_phone_number_formats() {
+        let lenient = formats(EmailFormat::Off, PhoneNumberFormat::Lenient);
+        let e164 = formats(EmailFormat::Off, PhoneNumberFormat::E164);
+
+        let mut user = serde_json::json!({"phoneNumbers": [{"value": "tel:+1-201-555-0123"}]});
+        assert!(validate_formats(&mut user, &lenient).is_ok());
+        assert_eq!(user["phoneNumbers"][0]["value"], "tel:+1-201-555-0123");
+
+        // E.164 normalizes in place
+        assert!(validate_formats(&mut user, &e164).is_ok());
+        assert_eq!(user["phoneNumbers"][0]["value"], "+12015550123");
+
+        let mut letters = serde_json::json!({"phoneNumbers": [{"value": "555-CALL-NOW"}]});
+        let message = invalid_value_message(validate_formats(&mut letters, &lenient));
+        assert!(message.starts_with("phoneNumbers[0].value"), "{}", message);
+
+        // Lenient accepts local numbers, E.164 needs the country code
+        let mut local = serde_json::json!({"phoneNumbers": [{"value": "(555) 555-0123"}]});
+        assert!(validate_formats(&mut local, &lenient).is_ok());
+        assert!(validate_formats(&mut local, &e164).is_err());
+
+        assert!(validate_formats(&mut letters, &FormatValidationConfig::default()).is_ok());
+
+        let mut ops = [ScimPatchOperation {
+            op: "add".to_string(),
+            path: Some("phoneNumbers".to_string()),
+            value: Some(serde_json::json!([{"value": "+81 3 1234 5678", "type": "work"}])),
+        }];
+        assert!(validate_patch_formats(&mut ops, &e164).is_ok());
+        assert_eq!(
+            ops[0].value.as_ref().unwrap()[0]["value"],
+            serde_json::json!("+81312345678")
+        );
+    }
+
+    #[test]
+    fn test_url_formats() {
+        let strict = formats(EmailFormat::Strict, PhoneNumberFormat::Off);
+        let mut valid = serde_json::json!({
+            "profileUrl": "https://login.example.com/bjensen",
+            "photos": [{"value": "https://photos.example.com/profilephoto.jpg"}]
+        });
+        assert!(validate_formats(&mut valid, &strict).is_ok());
+
+        let mut profile = serde_json::json!({"profileUrl": "not a url"});
+        let message = invalid_value_message(validate_formats(&mut profile, &strict));
+        assert!(message.starts_with("profileUrl"), "{}", message);
+
+        let mut photos = serde_json::json!({"photos": [{"value": "profilephoto"}]});
+        let message = invalid_value_message(validate_formats(&mut photos, &strict));
+        assert!(message.starts_with("photos[0].value"), "{}", message);
+
+        assert!(validate_formats(&mut photos, &FormatValidationConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_language_tag_formats() {
+        let strict = formats(EmailFormat::Strict, PhoneNumberFormat::Off);
+        let mut valid = serde_json::json!({"preferredLanguage": "en-US", "locale": "zh-Hans-CN"});
+        assert!(validate_formats(&mut valid, &strict).is_ok());
+
+        let mut invalid = serde_json::json!({"preferredLanguage": "en_US"});
+        let message = invalid_value_message(validate_formats(&mut invalid, &strict));
+        assert!(message.starts_with("preferredLanguage"), "{}", message);
+
+        let mut ops = [ScimPatchOperation {
+            op: "replace".to_string(),
+            path: None,
+            value: Some(serde_json::json!({"locale": "en US"})),
+        }];
+        assert!(validate_patch_formats(&mut ops, &strict).is_err());
+
+        assert!(validate_formats(&mut invalid, &FormatValidationConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_scim_response() {
+        let user = serde_json::json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "id": "2819c223",
+            "userName": "bjensen",
+            "active": true,
+            "emails": [{"value": "bjensen@example.com", "primary": true}],
+            "meta": {"resourceType": "User", "created": "2024-01-01T00:00:00Z"},
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                "manager": {"value": "26118915"}
+            }
+        });
+        assert!(validate_scim_response(ResourceType::User, &user).is_empty());
+
+        let broken = serde_json::json!({
+            "id": "2819c223",
+            "password": "secret",
+            "active": "true",
+            "emails": {"value": "bjensen@example.com"},
+            "phoneNumbers": [{"value": "555-0123", "primary": "yes"}],
+            "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {"manager": "26118915"}
+        });
+        assert_eq!(
+            validate_scim_response(ResourceType::User, &broken),
+            vec![
+                "userName is required but missing",
+                "emails is multi-valued but not an array",
+                "phoneNumbers[0].primary must be a boolean, got \"yes\"",
+                "active must be a boolean, got \"true\"",
+                "password is writeOnly but returned",
+                "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User:manager must be an object, got \"26118915\"",
+            ]
+        );
+
+        let group = serde_json::json!({"id": "e9e30dba", "members": [{"value": 1}]});
+        assert_eq!(
+            validate_scim_response(ResourceType::Group, &group),
+            vec![
+                "displayName is required but missing",
+                "members[0].value must be a string, got 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_patch_mutability() {
+        let operation = |op: &str, path: Option<&str>, value: Value| ScimPatchOperation {
+            op: op.to_string(),
+            path: path.map(str::to_string),
+            value: Some(value),
+        };
+
+        for path in [
+            "members.value",
+            "members[value eq \"u1\"].value",
+            "members[value eq \"u1\"].$ref",
+            "urn:ietf:params:scim:schemas:core:2.0:Group:members.type",
+        ] {
+            for op in ["add", "replace", "remove"] {
+                let result = validate_patch_mutability(
+                    &operation(op, Some(path), serde_json::json!("u2")),
+                    ResourceType::Group,
+                );
+                let Err(AppError::Validation(issues)) = result else {
+                    panic!("{} {} was accepted", op, path);
+                };
+                assert_eq!(issues[0].scim_type, "mutability");
+                assert_eq!(issues[0].path, path);
+            }
+        }
+
+        // The member list itself and its readWrite attributes stay patchable
+        for (path, value) in [
+            (Some("members"), serde_json::json!([{"value": "u2"}])),
+            (Some("members[value eq \"u1\"]"), Value::Null),
+            (Some("displayName"), serde_json::json!("Renamed")),
+            (None, serde_json::json!({"members": [{"value": "u2"}]})),
+        ] {
+            assert!(validate_patch_mutability(
+                &operation("replace", path, value),
+                ResourceType::Group
+            )
+            .is_ok());
+        }
+        assert!(validate_patch_mutability(
+            &operation(
+                "replace",
+                None,
+                serde_json::json!({
+                    "name": {"givenName": "Barbara"},
+                    "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User": {
+                        "manager": {"value": "26118915"}
+                    }
+                })
+            ),
+            ResourceType::User
+        )
+        .is_ok());
+    }
 }
@@ -6,7 +6,8 @@ use crate::error::AppResult;
 #[allow(dead_code)]
 pub async fn initialize_tenant_schemas(config: &AppConfig) -> AppResult<()> {
     // Create backend configuration from app config
-    if config.backend.backend_type != "database" {
+    let backend_type = config.backend.backend_type.as_str();
+    if backend_type != "database" && backend_type != "event_sourced" {
         return Err(crate::error::AppError::Configuration(format!(
             "Unsupported backend type: {}",
             config.backend.backend_type
@@ -15,7 +16,8 @@ pub async fn initialize_tenant_schemas(config: &AppConfig) -> AppResult<()> {
 
     let database_config = config.backend.database.as_ref().ok_or_else(|| {
         crate::error::AppError::Configuration(
-            "Database configuration is required when backend type is 'database'".to_string(),
+            "Database configuration is required for the 'database' and 'event_sourced' backends"
+                .to_string(),
         )
     })?;
 
@@ -23,6 +25,7 @@ pub async fn initialize_tenant_schemas(config: &AppConfig) -> AppResult<()> {
         database_type: match database_config.db_type.as_str() {
             "postgresql" => crate::backend::DatabaseType::PostgreSQL,
             "sqlite" => crate::backend::DatabaseType::SQLite,
+            "mysql" | "mariadb" => crate::backend::DatabaseType::MySQL,
             _ => {
                 return Err(crate::error::AppError::Configuration(format!(
                     "Unsupported database type: {}",
@@ -34,10 +37,15 @@ pub async fn initialize_tenant_schemas(config: &AppConfig) -> AppResult<()> {
         max_connections: database_config.max_connections,
         connection_timeout: 30,
         options: std::collections::HashMap::new(),
+        external_id_case_insensitive_tenants: Default::default(),
     };
 
-    // Create backend instance
-    let backend = BackendFactory::create(&backend_config).await?;
+    // Create backend instance; the event-sourced one also creates its event log
+    let backend = if backend_type == "event_sourced" {
+        BackendFactory::create_event_sourced(&backend_config).await?
+    } else {
+        BackendFactory::create(&backend_config).await?
+    };
 
     // Initialize schemas for each tenant
     for tenant in &config.tenants {
@@ -0,0 +1,364 @@
+//! Per-tenant sync statistics (`GET /admin/tenants/{id}/stats`)
+//!
+//! Counts the writes each tenant receives by resource type and outcome, so a
+//! report like "the IdP pushed 500 users but only 460 exist" can be checked
+//! against what the server actually saw. Counts live in memory since process
+//! start; with `server.stats.persist` they are also saved to each tenant's
+//! `t{id}_sync_stats` table periodically and restored at startup.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::TenantInfo;
+use crate::backend::ScimBackend;
+use crate::config::AppConfig;
+use crate::parser::ResourceType;
+
+/// Kind of write a request to a Users or Groups endpoint performs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOperation {
+    Create,
+    Update,
+    Patch,
+    Delete,
+}
+
+impl WriteOperation {
+    /// Resource type and write of a request to a tenant-relative path; None
+    /// for reads, searches and other endpoints
+    ///
+    /// `members:add` and `members:remove` count as patches of the group.
+    pub fn of(method: &Method, path: &str) -> Option<(ResourceType, Self)> {
+        let path = path.strip_prefix('/')?;
+        let (segment, rest) = match path.split_once('/') {
+            Some((segment, rest)) => (segment, Some(rest)),
+            None => (path, None),
+        };
+        let resource_type = match segment {
+            "Users" => ResourceType::User,
+            "Groups" => ResourceType::Group,
+            _ => return None,
+        };
+
+        let operation = match (method, rest) {
+            (&Method::POST, None) => WriteOperation::Create,
+            (&Method::PUT, Some(_)) => WriteOperation::Update,
+            (&Method::PATCH, Some(_)) => WriteOperation::Patch,
+            (&Method::DELETE, Some(_)) => WriteOperation::Delete,
+            (&Method::POST, Some(rest)) if rest.contains("/members:") => WriteOperation::Patch,
+            _ => return None,
+        };
+        Some((resource_type, operation))
+    }
+}
+
+/// Write counts of one resource type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceStats {
+    pub creates: u64,
+    pub updates: u64,
+    pub patches: u64,
+    pub deletes: u64,
+    /// Writes rejected with 409, e.g. a duplicate userName
+    pub conflicts: u64,
+    /// Writes rejected with 400
+    pub validation_rejections: u64,
+    /// Time of the last successful write
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+/// Counts of one tenant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantStats {
+    /// When counting started
+    pub since: DateTime<Utc>,
+    /// Requests rejected for missing or wrong credentials
+    #[serde(default)]
+    pub auth_failures: u64,
+    /// Keyed by resource type name ("User", "Group")
+    #[serde(default)]
+    pub resources: BTreeMap<String, ResourceStats>,
+}
+
+impl TenantStats {
+    fn new(since: DateTime<Utc>) -> Self {
+        let resources = [ResourceType::User, ResourceType::Group]
+            .iter()
+            .map(|resource_type| (resource_type.scim_name().to_string(), Default::default()))
+            .collect();
+        Self {
+            since,
+            auth_failures: 0,
+            resources,
+        }
+    }
+}
+
+/// Sync statistics of every tenant
+#[derive(Debug)]
+pub struct SyncStats {
+    started: DateTime<Utc>,
+    tenants: Mutex<HashMap<u32, TenantStats>>,
+}
+
+impl Default for SyncStats {
+    fn default() -> Self {
+        Self {
+            started: Utc::now(),
+            tenants: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SyncStats {
+    /// Count the outcome of a write
+    ///
+    /// Successful writes are counted by operation; 409 and 400 responses as
+    /// conflicts and validation rejections. Other failures are not counted.
+    pub fn record_write(
+        &self,
+        tenant_id: u32,
+        resource_type: ResourceType,
+        operation: WriteOperation,
+        status: StatusCode,
+    ) {
+        let mut tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = tenants
+            .entry(tenant_id)
+            .or_insert_with(|| TenantStats::new(self.started))
+            .resources
+            .entry(resource_type.scim_name().to_string())
+            .or_default();
+
+        match status {
+            StatusCode::CONFLICT => stats.conflicts += 1,
+            StatusCode::BAD_REQUEST => stats.validation_rejections += 1,
+            status if status.is_success() => {
+                match operation {
+                    WriteOperation::Create => stats.creates += 1,
+                    WriteOperation::Update => stats.updates += 1,
+                    WriteOperation::Patch => stats.patches += 1,
+                    WriteOperation::Delete => stats.deletes += 1,
+                }
+                stats.last_write = Some(Utc::now());
+            }
+            _ => {}
+        }
+    }
+
+    /// Count a request rejected by authentication
+    pub fn record_auth_failure(&self, tenant_id: u32) {
+        let mut tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+        tenants
+            .entry(tenant_id)
+            .or_insert_with(|| TenantStats::new(self.started))
+            .auth_failures += 1;
+    }
+
+    /// Counts of a tenant, all zero for a tenant without requests yet
+    pub fn tenant(&self, tenant_id: u32) -> TenantStats {
+        let tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+        tenants
+            .get(&tenant_id)
+            .cloned()
+            .unwrap_or_else(|| TenantStats::new(self.started))
+    }
+
+    /// Replace a tenant's counts with ones saved by an earlier process
+    pub fn restore(&self, tenant_id: u32, stats: TenantStats) {
+        let mut tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+        tenants.insert(tenant_id, stats);
+    }
+
+    fn all(&self) -> Vec<(u32, TenantStats)> {
+        let tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+        tenants
+            .iter()
+            .map(|(tenant_id, stats)| (*tenant_id, stats.clone()))
+            .collect()
+    }
+}
+
+/// Count the writes to a tenant's Users and Groups endpoints
+///
+/// Layered on the tenant routes, so the path is already relative to the
+/// tenant's base path and renamed endpoints appear as `/Users` and `/Groups`.
+pub async fn stats_middleware(
+    State(stats): State<Arc<SyncStats>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let write = request
+        .extensions()
+        .get::<TenantInfo>()
+        .map(|tenant_info| tenant_info.tenant_id)
+        .zip(WriteOperation::of(request.method(), request.uri().path()));
+
+    let response = next.run(request).await;
+    if let Some((tenant_id, (resource_type, operation))) = write {
+        stats.record_write(tenant_id, resource_type, operation, response.status());
+    }
+    response
+}
+
+/// Load the counts saved for every configured tenant
+///
+/// A snapshot that cannot be read is logged and counting starts from zero.
+pub async fn restore_snapshots(
+    backend: &dyn ScimBackend,
+    stats: &SyncStats,
+    app_config: &AppConfig,
+) {
+    for tenant in &app_config.tenants {
+        let snapshot = match backend.load_sync_stats(tenant.id).await {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(tenant_id = tenant.id, error = %e, "Failed to load sync stats");
+                continue;
+            }
+        };
+        match serde_json::from_value(snapshot) {
+            Ok(tenant_stats) => stats.restore(tenant.id, tenant_stats),
+            Err(e) => {
+                tracing::warn!(tenant_id = tenant.id, error = %e, "Ignoring unreadable sync stats")
+            }
+        }
+    }
+}
+
+/// Save the counts of every tenant that received requests
+pub async fn save_snapshots(backend: &dyn ScimBackend, stats: &SyncStats) {
+    for (tenant_id, tenant_stats) in stats.all() {
+        let snapshot = match serde_json::to_value(&tenant_stats) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!(tenant_id, error = %e, "Failed to serialize sync stats");
+                continue;
+            }
+        };
+        if let Err(e) = backend.save_sync_stats(tenant_id, &snapshot).await {
+            tracing::warn!(tenant_id, error = %e, "Failed to save sync stats");
+        }
+    }
+}
+
+/// Save the counts every `interval` in the background
+pub fn spawn_snapshots(
+    backend: Arc<dyn ScimBackend>,
+    stats: Arc<SyncStats>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately; nothing was counted yet
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            save_snapshots(backend.as_ref(), &stats).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_operation_of_request() {
+        let cases = [
+            (
+                Method::POST,
+                "/Users",
+                Some((ResourceType::User, WriteOperation::Create)),
+            ),
+            (
+                Method::PUT,
+                "/Users/1",
+                Some((ResourceType::User, WriteOperation::Update)),
+            ),
+            (
+                Method::PATCH,
+                "/Groups/1",
+                Some((ResourceType::Group, WriteOperation::Patch)),
+            ),
+            (
+                Method::DELETE,
+                "/Groups/1",
+                Some((ResourceType::Group, WriteOperation::Delete)),
+            ),
+            (
+                Method::POST,
+                "/Groups/1/members:add",
+                Some((ResourceType::Group, WriteOperation::Patch)),
+            ),
+            (Method::GET, "/Users/1", None),
+            (Method::POST, "/.search", None),
+            (Method::POST, "/Users/.search", None),
+            (Method::POST, "/Schemas", None),
+        ];
+        for (method, path, expected) in cases {
+            assert_eq!(
+                WriteOperation::of(&method, path),
+                expected,
+                "{} {}",
+                method,
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_write_by_outcome() {
+        let stats = SyncStats::default();
+        stats.record_write(
+            1,
+            ResourceType::User,
+            WriteOperation::Create,
+            StatusCode::CREATED,
+        );
+        stats.record_write(
+            1,
+            ResourceType::User,
+            WriteOperation::Create,
+            StatusCode::CONFLICT,
+        );
+        stats.record_write(
+            1,
+            ResourceType::User,
+            WriteOperation::Patch,
+            StatusCode::BAD_REQUEST,
+        );
+        stats.record_write(
+            1,
+            ResourceType::User,
+            WriteOperation::Delete,
+            StatusCode::NOT_FOUND,
+        );
+        stats.record_auth_failure(1);
+
+        let tenant = stats.tenant(1);
+        assert_eq!(tenant.auth_failures, 1);
+        let users = &tenant.resources["User"];
+        assert_eq!((users.creates, users.patches, users.deletes), (1, 0, 0));
+        assert_eq!((users.conflicts, users.validation_rejections), (1, 1));
+        assert!(users.last_write.is_some());
+        assert_eq!(tenant.resources["Group"], ResourceStats::default());
+
+        // Other tenants are unaffected
+        assert_eq!(stats.tenant(2).auth_failures, 0);
+    }
+}
@@ -0,0 +1,203 @@
+//! OpenTelemetry spans for SCIM requests (`otel` feature)
+//!
+//! `logging_middleware` opens one server span per request, continuing the
+//! caller's trace when a `traceparent` header is sent. Spans are exported
+//! over OTLP/HTTP when `OTEL_EXPORTER_OTLP_ENDPOINT` or
+//! `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is set; the other standard
+//! `OTEL_*` variables configure the exporter as usual.
+
+use axum::http::{HeaderMap, Method};
+use opentelemetry::global::{self, BoxedSpan};
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::{Span, SpanKind, Status, Tracer};
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+
+const TRACER_NAME: &str = "scim-server";
+
+/// Path segments naming the resource type a request works on
+const RESOURCE_TYPES: [&str; 5] = [
+    "Users",
+    "Groups",
+    "ServiceProviderConfig",
+    "Schemas",
+    "ResourceTypes",
+];
+
+/// Export spans over OTLP when an endpoint is configured in the environment
+///
+/// Returns the installed provider, which has to be shut down on exit so the
+/// last batch is flushed. Without an endpoint nothing is installed and the
+/// global no-op tracer drops every span.
+pub fn init_from_env() -> Result<Option<SdkTracerProvider>, String> {
+    let configured = [
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    ]
+    .iter()
+    .any(|name| std::env::var_os(name).is_some());
+    if !configured {
+        return Ok(None);
+    }
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+        .map_err(|e| format!("Failed to create OTLP span exporter: {}", e))?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(TRACER_NAME).build())
+        .build();
+    install(provider.clone());
+    Ok(Some(provider))
+}
+
+/// Make `provider` the global tracer provider, with W3C trace context
+/// propagation
+pub fn install(provider: SdkTracerProvider) {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider);
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// Start the server span of a request
+pub fn start_request_span(
+    method: &Method,
+    path: &str,
+    headers: &HeaderMap,
+    tenant_id: Option<u32>,
+) -> BoxedSpan {
+    let parent =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    let (resource_type, operation) = scim_operation(method, path);
+
+    let mut attributes = vec![
+        KeyValue::new("http.request.method", method.to_string()),
+        KeyValue::new("url.path", path.to_string()),
+        KeyValue::new("scim.operation", operation),
+    ];
+    if let Some(resource_type) = resource_type {
+        attributes.push(KeyValue::new("scim.resource_type", resource_type));
+    }
+    if let Some(tenant_id) = tenant_id {
+        attributes.push(KeyValue::new("scim.tenant_id", i64::from(tenant_id)));
+    }
+
+    let name = match resource_type {
+        Some(resource_type) => format!("{} {}", operation, resource_type),
+        None => format!("{} {}", method, operation),
+    };
+    let tracer = global::tracer(TRACER_NAME);
+    tracer
+        .span_builder(name)
+        .with_kind(SpanKind::Server)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, &parent)
+}
+
+/// Record the response status and end the span
+pub fn end_request_span(mut span: BoxedSpan, status: u16) {
+    span.set_attribute(KeyValue::new(
+        "http.response.status_code",
+        i64::from(status),
+    ));
+    if status >= 500 {
+        span.set_status(Status::error(format!("HTTP {}", status)));
+    }
+    span.end();
+}
+
+/// Resource type and operation of a request, e.g. `(Some("Users"), "get")`
+fn scim_operation(method: &Method, path: &str) -> (Option<&'static str>, &'static str) {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let Some(position) = segments
+        .iter()
+        .rposition(|segment| RESOURCE_TYPES.contains(segment))
+    else {
+        let operation = if segments.last() == Some(&".search") {
+            "search"
+        } else {
+            "other"
+        };
+        return (None, operation);
+    };
+    let resource_type = RESOURCE_TYPES
+        .into_iter()
+        .find(|resource_type| *resource_type == segments[position]);
+
+    let operation = match (method, &segments[position + 1..]) {
+        (_, [".search"]) => "search",
+        (_, [".export"]) => "export",
+        (&Method::GET, []) => "list",
+        (&Method::POST, []) => "create",
+        (&Method::GET, [_, ..]) => "get",
+        (&Method::PUT, [_]) => "replace",
+        (&Method::PATCH, [_]) => "patch",
+        (&Method::DELETE, [_]) => "delete",
+        (&Method::POST, [_, _]) => "members",
+        _ => "other",
+    };
+    (resource_type, operation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scim_operation() {
+        for (method, path, expected) in [
+            (Method::GET, "/scim/v2/Users", (Some("Users"), "list")),
+            (Method::POST, "/scim/v2/Users", (Some("Users"), "create")),
+            (Method::GET, "/t/scim/v2/Users/42", (Some("Users"), "get")),
+            (
+                Method::PUT,
+                "/scim/v2/Groups/42",
+                (Some("Groups"), "replace"),
+            ),
+            (
+                Method::PATCH,
+                "/scim/v2/Groups/42",
+                (Some("Groups"), "patch"),
+            ),
+            (
+                Method::DELETE,
+                "/scim/v2/Users/42",
+                (Some("Users"), "delete"),
+            ),
+            (
+                Method::POST,
+                "/scim/v2/Users/.search",
+                (Some("Users"), "search"),
+            ),
+            (
+                Method::POST,
+                "/scim/v2/Groups/42/members:add",
+                (Some("Groups"), "members"),
+            ),
+            (Method::POST, "/scim/v2/.search", (None, "search")),
+            (Method::GET, "/health", (None, "other")),
+        ] {
+            assert_eq!(
+                scim_operation(&method, path),
+                expected,
+                "{} {}",
+                method,
+                path
+            );
+        }
+    }
+}
@@ -53,6 +53,59 @@ pub fn current_scim_datetime() -> String {
     format_scim_datetime(Utc::now())
 }
 
+/// Builds the absolute `meta.location` URL for a single resource
+///
+/// `base_path` is the resolved tenant base URL (scheme, host and tenant path),
+/// as stored in `TenantInfo::base_path`. Single-resource responses and every
+/// entry of a ListResponse must use this so that both agree on the URL.
+///
+/// Example output: "https://scim.example.com/scim/v2/Users/2819c223"
+pub fn build_resource_location(base_path: &str, resource_type: &str, resource_id: &str) -> String {
+    format!(
+        "{}/{}/{}",
+        base_path.trim_end_matches('/'),
+        resource_type,
+        resource_id
+    )
+}
+
+/// Builds the tenant-relative `$ref` that backends attach to groups and members
+///
+/// Backends have no request context, so they emit `/{tenant_id}/{type}/{id}`
+/// and the resource handlers turn it into an absolute URL with
+/// [`resolve_tenant_ref`].
+pub fn build_tenant_ref(tenant_id: u32, resource_type: &str, resource_id: &str) -> String {
+    format!("/{}/{}/{}", tenant_id, resource_type, resource_id)
+}
+
+/// Resolves a `$ref` produced by [`build_tenant_ref`] against `ref_base_path`
+///
+/// The resource type is named by the tenant's endpoint path for it. Returns
+/// `None` for values that were not built for this tenant (for example an
+/// absolute URL supplied by the client), which are left untouched.
+pub fn resolve_tenant_ref(
+    tenant: &crate::config::TenantConfig,
+    ref_base_path: &str,
+    ref_: &str,
+) -> Option<String> {
+    let resource_path = ref_.strip_prefix(&format!("/{}/", tenant.id))?;
+    let (resource_type, resource_id) = resource_path.split_once('/')?;
+    Some(build_resource_location(
+        ref_base_path,
+        tenant.endpoint_path(resource_type),
+        resource_id,
+    ))
+}
+
+/// Whether `id` has the shape of the ids the server allocates itself
+///
+/// Ids are lowercase hyphenated UUIDs; a client-chosen id (PUT to a missing
+/// resource with `put_creates_missing`) must look the same so every backend
+/// can store it and later lookups find it.
+pub fn is_valid_resource_id(id: &str) -> bool {
+    uuid::Uuid::try_parse(id).is_ok_and(|uuid| uuid.hyphenated().to_string() == id)
+}
+
 /// Convert datetime strings in User metadata to epoch format if needed
 ///
 /// This function modifies the User's meta.created and meta.lastModified fields
@@ -180,6 +233,17 @@ mod tests {
     use super::*;
     use chrono::{TimeZone, Timelike};
 
+    #[test]
+    fn test_is_valid_resource_id() {
+        assert!(is_valid_resource_id("2819c223-7f76-453a-919d-413861904646"));
+        assert!(!is_valid_resource_id(
+            "2819C223-7F76-453A-919D-413861904646"
+        ));
+        assert!(!is_valid_resource_id("2819c2237f76453a919d413861904646"));
+        assert!(!is_valid_resource_id("user-1"));
+        assert!(!is_valid_resource_id(""));
+    }
+
     #[test]
     fn test_scim_datetime_format() {
         // Test with a known timestamp
@@ -214,6 +278,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_resource_location() {
+        assert_eq!(
+            build_resource_location("https://scim.example.com/scim/v2", "Users", "abc123"),
+            "https://scim.example.com/scim/v2/Users/abc123"
+        );
+        // Trailing slash on the base path must not produce a double slash
+        assert_eq!(
+            build_resource_location("http://localhost:3000/tenant-a/scim/v2/", "Groups", "g1"),
+            "http://localhost:3000/tenant-a/scim/v2/Groups/g1"
+        );
+    }
+
+    fn tenant(yaml: &str) -> crate::config::TenantConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_tenant_ref() {
+        let tenant_2 = tenant("{id: 2, path: /scim/v2, auth: {type: unauthenticated}}");
+        let tenant_3 = tenant("{id: 3, path: /scim/v2, auth: {type: unauthenticated}}");
+        let ref_ = build_tenant_ref(2, "Users", "u1");
+        assert_eq!(ref_, "/2/Users/u1");
+        assert_eq!(
+            resolve_tenant_ref(&tenant_2, "https://idm.example.com/scim/v2/", &ref_).as_deref(),
+            Some("https://idm.example.com/scim/v2/Users/u1")
+        );
+        // Refs of other tenants and absolute URLs are not rewritten
+        assert_eq!(
+            resolve_tenant_ref(&tenant_3, "https://idm.example.com", &ref_),
+            None
+        );
+        assert_eq!(
+            resolve_tenant_ref(
+                &tenant_2,
+                "https://idm.example.com",
+                "https://other/Users/u1"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_tenant_ref_with_custom_endpoint_path() {
+        let tenant =
+            tenant("{id: 2, path: /scim, groups_path: teams, auth: {type: unauthenticated}}");
+        assert_eq!(
+            resolve_tenant_ref(&tenant, "https://idm.example.com/scim", "/2/Groups/g1").as_deref(),
+            Some("https://idm.example.com/scim/teams/g1")
+        );
+        assert_eq!(
+            resolve_tenant_ref(&tenant, "https://idm.example.com/scim", "/2/Users/u1").as_deref(),
+            Some("https://idm.example.com/scim/Users/u1")
+        );
+    }
+
     #[test]
     fn test_epoch_datetime_format() {
         // Test with a known timestamp
@@ -0,0 +1,88 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[0].ndjson_export = true;
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_acceptable_accept_headers() {
+    let server = setup_server().await;
+
+    for accept in [
+        "application/scim+json",
+        "application/json",
+        "*/*",
+        "application/*",
+        "APPLICATION/SCIM+JSON; charset=utf-8",
+        "application/xml, application/json;q=0.5",
+        "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+    ] {
+        let response = server
+            .get("/tenant-a/scim/v2/Users")
+            .add_header("accept", accept)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK, "{}", accept);
+    }
+
+    // No Accept header at all
+    let response = server.get("/tenant-a/scim/v2/Users").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_unacceptable_accept_headers() {
+    let server = setup_server().await;
+
+    for accept in [
+        "application/xml",
+        "text/html",
+        "text/*",
+        "application/scim+json;q=0",
+        "application/json; q=0.0, text/plain",
+    ] {
+        let response = server
+            .get("/tenant-a/scim/v2/Users")
+            .add_header("accept", accept)
+            .await;
+        assert_eq!(
+            response.status_code(),
+            StatusCode::NOT_ACCEPTABLE,
+            "{}",
+            accept
+        );
+        let body: Value = response.json();
+        assert_eq!(body["status"], "406");
+    }
+
+    // Discovery endpoints negotiate the same way
+    let response = server
+        .get("/tenant-a/scim/v2/ServiceProviderConfig")
+        .add_header("accept", "application/xml")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_ACCEPTABLE);
+}
+
+#[tokio::test]
+async fn test_ndjson_exports_accept_ndjson() {
+    let server = setup_server().await;
+
+    let response = server
+        .get("/tenant-a/scim/v2/Users?format=ndjson")
+        .add_header("accept", "application/x-ndjson")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    // Only exports are sent as NDJSON
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_header("accept", "application/x-ndjson")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_ACCEPTABLE);
+}
@@ -0,0 +1,100 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[0].accept_password = false;
+    })
+    .await
+}
+
+fn assert_password_rejected(response: &axum_test::TestResponse) {
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+    assert_eq!(body["detail"], "password is not accepted by this tenant");
+}
+
+async fn create_user(server: &TestServer, base: &str, user_name: &str) -> String {
+    let response = server
+        .post(&format!("{}/Users", base))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": user_name
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_password_writes_are_rejected() {
+    let server = setup_server().await;
+    let base = "/tenant-a/scim/v2";
+
+    let response = server
+        .post(&format!("{}/Users", base))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "no.password",
+            "password": "Secret123!"
+        }))
+        .await;
+    assert_password_rejected(&response);
+
+    let id = create_user(&server, base, "no.password").await;
+    let url = format!("{}/Users/{}", base, id);
+
+    let response = server
+        .put(&url)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "no.password",
+            "password": "Secret123!"
+        }))
+        .await;
+    assert_password_rejected(&response);
+
+    for operation in [
+        json!({"op": "replace", "path": "password", "value": "Secret123!"}),
+        json!({"op": "add", "value": {"password": "Secret123!"}}),
+    ] {
+        let response = server
+            .patch(&url)
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [operation]
+            }))
+            .await;
+        assert_password_rejected(&response);
+    }
+
+    // Other attributes are still writable
+    let response = server
+        .patch(&url)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "displayName", "value": "No Password"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_other_tenants_accept_passwords() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/tenant-b/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "with.password",
+            "password": "Secret123!"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    assert!(response.json::<Value>().get("password").is_none());
+}
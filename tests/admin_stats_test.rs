@@ -0,0 +1,195 @@
+/// `GET /admin/tenants/{id}/stats` and persisted sync statistics
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::{AppConfig, AuthConfig};
+use scim_server::stats::{self, SyncStats};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+mod common;
+
+const ADMIN_TOKEN: &str = "admin-secret";
+const TENANT_TOKEN: &str = "tenant-a-token";
+
+/// Tenant 1 (`/tenant-a/scim/v2`) requires a bearer token
+fn app_config() -> AppConfig {
+    let mut app_config = common::create_test_app_config();
+    app_config.server.admin_token = Some(ADMIN_TOKEN.to_string());
+    app_config.tenants[0].auth = AuthConfig {
+        auth_type: "bearer".to_string(),
+        token: Some(TENANT_TOKEN.to_string()),
+        basic: None,
+        basic_users: Vec::new(),
+        tokens: Vec::new(),
+    };
+    app_config
+}
+
+async fn tenant_stats(server: &TestServer, tenant_id: u32) -> Value {
+    let response = server
+        .get(&format!("/admin/tenants/{}/stats", tenant_id))
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+/// Create, conflict, reject, patch, replace and delete users and a group on
+/// tenant 1
+async fn provision(server: &TestServer) {
+    let auth = format!("Bearer {}", TENANT_TOKEN);
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header("Authorization", &auth)
+        .json(&common::create_test_user_json(
+            "stats.alice",
+            "Alice",
+            "Stats",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let alice = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header("Authorization", &auth)
+        .json(&common::create_test_user_json("stats.bob", "Bob", "Stats"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let bob = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    // Duplicate userName
+    server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header("Authorization", &auth)
+        .json(&common::create_test_user_json(
+            "stats.alice",
+            "Alice",
+            "Again",
+        ))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    // Missing userName
+    server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header("Authorization", &auth)
+        .json(&json!({"schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"]}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    server
+        .patch(&format!("/tenant-a/scim/v2/Users/{}", alice))
+        .add_header("Authorization", &auth)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "active", "value": false}]
+        }))
+        .await
+        .assert_status_ok();
+    server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", alice))
+        .add_header("Authorization", &auth)
+        .json(&common::create_test_user_json(
+            "stats.alice",
+            "Alice",
+            "Renamed",
+        ))
+        .await
+        .assert_status_ok();
+    server
+        .delete(&format!("/tenant-a/scim/v2/Users/{}", bob))
+        .add_header("Authorization", &auth)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    server
+        .post("/tenant-a/scim/v2/Groups")
+        .add_header("Authorization", &auth)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Stats Group",
+            "members": [{"value": alice}]
+        }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // Reads are not counted
+    server
+        .get("/tenant-a/scim/v2/Users")
+        .add_header("Authorization", &auth)
+        .await
+        .assert_status_ok();
+    // Wrong token
+    server
+        .get("/tenant-a/scim/v2/Users")
+        .add_header("Authorization", "Bearer wrong")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_stats_count_writes_by_outcome() {
+    let server = common::test_server(|config| *config = app_config()).await;
+    provision(&server).await;
+
+    let stats = tenant_stats(&server, 1).await;
+    assert_eq!(stats["tenant"], 1);
+    assert_eq!(stats["authFailures"], 1);
+
+    let users = &stats["resources"]["User"];
+    assert_eq!(users["creates"], 2);
+    assert_eq!(users["updates"], 1);
+    assert_eq!(users["patches"], 1);
+    assert_eq!(users["deletes"], 1);
+    assert_eq!(users["conflicts"], 1);
+    assert_eq!(users["validationRejections"], 1);
+    assert!(users["lastWrite"].is_string());
+
+    let groups = &stats["resources"]["Group"];
+    assert_eq!(groups["creates"], 1);
+    assert_eq!(groups["conflicts"], 0);
+    assert!(groups["lastWrite"].is_string());
+
+    // Other tenants are counted separately
+    let other = tenant_stats(&server, 2).await;
+    assert_eq!(other["authFailures"], 0);
+    assert_eq!(other["resources"]["User"]["creates"], 0);
+    assert!(other["resources"]["User"]["lastWrite"].is_null());
+}
+
+#[tokio::test]
+async fn test_stats_of_unknown_tenant_and_without_admin_token() {
+    let server = common::test_server(|config| *config = app_config()).await;
+
+    server
+        .get("/admin/tenants/9/stats")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get("/admin/tenants/1/stats")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_persisted_stats_survive_restart() {
+    let app_config = Arc::new(app_config());
+    let backend = common::setup_test_database(&app_config).await.unwrap();
+
+    let sync_stats = Arc::new(SyncStats::default());
+    let app =
+        scim_server::router::build_router(app_config.clone(), backend.clone(), sync_stats.clone());
+    let server = TestServer::new(app).unwrap();
+    provision(&server).await;
+    let before = tenant_stats(&server, 1).await;
+    stats::save_snapshots(backend.as_ref(), &sync_stats).await;
+
+    // A new process starts from the saved counts
+    let restarted = Arc::new(SyncStats::default());
+    stats::restore_snapshots(backend.as_ref(), &restarted, &app_config).await;
+    let app = scim_server::router::build_router(app_config, backend, restarted);
+    let server = TestServer::new(app).unwrap();
+    assert_eq!(tenant_stats(&server, 1).await, before);
+}
@@ -0,0 +1,155 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::backend::database::sqlite::{schema, SqliteBackend};
+use scim_server::backend::{Backend, ScimBackend};
+use serde_json::{json, Value};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+mod common;
+
+const ADMIN_TOKEN: &str = "admin-secret";
+
+async fn memory_pool() -> SqlitePool {
+    // A single connection keeps every tenant in the same in-memory database
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
+
+async fn setup_server(admin_token: Option<&str>, tenant_ids: &[u32]) -> TestServer {
+    let backend = SqliteBackend::new(memory_pool().await);
+    for tenant_id in tenant_ids {
+        backend.init_tenant(*tenant_id).await.unwrap();
+    }
+    let backend: Arc<dyn ScimBackend> = Arc::new(backend);
+
+    let mut app_config = common::create_test_app_config();
+    app_config.server.admin_token = admin_token.map(str::to_string);
+    let app = common::setup_test_app_with_backend(app_config, backend)
+        .await
+        .unwrap();
+    TestServer::new(app).unwrap()
+}
+
+#[tokio::test]
+async fn test_list_initialized_tenants() {
+    let pool = memory_pool().await;
+    let backend = SqliteBackend::new(pool.clone());
+    assert!(backend.list_initialized_tenants().await.unwrap().is_empty());
+
+    for tenant_id in [12, 3, 5] {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+    // Initializing again does not duplicate the tenant
+    backend.init_tenant(3).await.unwrap();
+    assert_eq!(
+        backend.list_initialized_tenants().await.unwrap(),
+        vec![3, 5, 12]
+    );
+
+    schema::drop_tenant_schema(&pool, 5).await.unwrap();
+    assert_eq!(
+        backend.list_initialized_tenants().await.unwrap(),
+        vec![3, 12]
+    );
+}
+
+#[tokio::test]
+async fn test_admin_tenants_reports_drift() {
+    // Tenants 1-3 are configured; 3 was never initialized and 7 is not configured
+    let server = setup_server(Some(ADMIN_TOKEN), &[1, 2, 7]).await;
+
+    let response = server
+        .get("/admin/tenants")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({
+            "initialized": [1, 2, 7],
+            "configured": [1, 2, 3],
+            "notInitialized": [3],
+            "notConfigured": [7]
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_admin_tenants_requires_admin_token() {
+    let server = setup_server(Some(ADMIN_TOKEN), &[1, 2, 3]).await;
+
+    server
+        .get("/admin/tenants")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .get("/admin/tenants")
+        .add_header("Authorization", "Bearer wrong-token")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    // Disabled without an admin token, even for an unauthenticated tenant setup
+    let server = setup_server(None, &[1, 2, 3]).await;
+    server
+        .get("/admin/tenants")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_tenants_below_admin_and_shared_keep_their_auth() {
+    let backend = SqliteBackend::new(memory_pool().await);
+    for tenant_id in [1, 2] {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+    let backend: Arc<dyn ScimBackend> = Arc::new(backend);
+
+    let mut app_config = common::create_test_app_config();
+    app_config.server.admin_token = Some(ADMIN_TOKEN.to_string());
+    for (tenant, path) in app_config
+        .tenants
+        .iter_mut()
+        .zip(["/admin/scim/v2", "/shared/scim/v2"])
+    {
+        tenant.path = path.to_string();
+        tenant.auth.auth_type = "bearer".to_string();
+        tenant.auth.token = Some(format!("tenant-{}-token", tenant.id));
+    }
+    let app = common::setup_test_app_with_backend(app_config, backend)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    for (tenant_id, path) in [(1, "/admin/scim/v2/Users"), (2, "/shared/scim/v2/Users")] {
+        server
+            .get(path)
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        // The admin token is not a tenant credential
+        server
+            .get(path)
+            .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        server
+            .get(path)
+            .add_header(
+                "Authorization",
+                format!("Bearer tenant-{}-token", tenant_id),
+            )
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    // The admin routes themselves still use the admin token
+    server
+        .get("/admin/tenants")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await
+        .assert_status(StatusCode::OK);
+}
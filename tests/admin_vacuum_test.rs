@@ -0,0 +1,114 @@
+/// `POST /admin/tenants/{id}/vacuum` against SQLite
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::backend::database::sqlite::SqliteBackend;
+use scim_server::backend::{Backend, ScimBackend};
+use serde_json::{json, Value};
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+mod common;
+
+const ADMIN_TOKEN: &str = "admin-secret";
+
+/// Tenants 1-3 are configured; only 1 and 2 are initialized
+async fn setup_server(maintenance_endpoints: bool) -> TestServer {
+    // A single connection keeps every tenant in the same in-memory database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let backend = SqliteBackend::new(pool);
+    for tenant_id in [1, 2] {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+    let backend: Arc<dyn ScimBackend> = Arc::new(backend);
+
+    let mut app_config = common::create_test_app_config();
+    app_config.server.admin_token = Some(ADMIN_TOKEN.to_string());
+    app_config.server.maintenance_endpoints = maintenance_endpoints;
+    let app = common::setup_test_app_with_backend(app_config, backend)
+        .await
+        .unwrap();
+    TestServer::new(app).unwrap()
+}
+
+async fn vacuum(server: &TestServer, tenant_id: u32) -> axum_test::TestResponse {
+    server
+        .post(&format!("/admin/tenants/{}/vacuum", tenant_id))
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await
+}
+
+#[tokio::test]
+async fn test_vacuum_after_bulk_delete() {
+    let server = setup_server(true).await;
+
+    let mut ids = Vec::new();
+    for i in 0..20 {
+        let response = server
+            .post("/tenant-a/scim/v2/Users")
+            .json(&common::create_test_user_json(
+                &format!("vacuum.user{}", i),
+                "Vacuum",
+                "User",
+            ))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+    }
+    let (kept, deleted) = ids.split_first().unwrap();
+    for id in deleted {
+        server
+            .delete(&format!("/tenant-a/scim/v2/Users/{}", id))
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    let response = vacuum(&server, 1).await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"tenant": 1, "status": "completed"})
+    );
+
+    // The remaining data is untouched
+    server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", kept))
+        .await
+        .assert_status_ok();
+    let list: Value = server.get("/tenant-a/scim/v2/Users").await.json();
+    assert_eq!(list["totalResults"], 1);
+}
+
+#[tokio::test]
+async fn test_vacuum_unknown_or_uninitialized_tenant() {
+    let server = setup_server(true).await;
+
+    // Configured but never initialized
+    vacuum(&server, 3)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    // Not configured
+    vacuum(&server, 9)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_vacuum_requires_flag_and_admin_token() {
+    let server = setup_server(true).await;
+    server
+        .post("/admin/tenants/1/vacuum")
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let server = setup_server(false).await;
+    let response = vacuum(&server, 1).await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.json::<Value>()["message"],
+        "Maintenance endpoints are disabled"
+    );
+}
@@ -0,0 +1,134 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+
+mod common;
+
+const MAX_LENGTH: usize = 64;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            max_attribute_value_length: MAX_LENGTH as u32,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+fn assert_too_long(response: &axum_test::TestResponse, attribute: &str) {
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+    let detail = body["detail"].as_str().unwrap();
+    assert!(detail.contains(attribute), "{}", detail);
+    assert!(detail.contains(&MAX_LENGTH.to_string()), "{}", detail);
+}
+
+#[tokio::test]
+async fn test_user_value_length_limit() {
+    let server = setup_server().await;
+
+    // Exactly at the limit, counted in characters rather than bytes
+    let mut user = common::create_test_user_json("length.user", "Length", "User");
+    user["displayName"] = json!("é".repeat(MAX_LENGTH));
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    let mut too_long = common::create_test_user_json("length.other", "Length", "Other");
+    too_long["displayName"] = json!("x".repeat(MAX_LENGTH + 1));
+    let response = server.post("/scim/v2/Users").json(&too_long).await;
+    assert_too_long(&response, "displayName");
+
+    // Nested values are checked too, and named by their path
+    user["emails"][0]["value"] = json!(format!("{}@example.com", "a".repeat(MAX_LENGTH)));
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&user)
+        .await;
+    assert_too_long(&response, "emails.value");
+
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "name.givenName", "value": "y".repeat(MAX_LENGTH + 1)}]
+        }))
+        .await;
+    assert_too_long(&response, "name.givenName");
+
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "value": {"title": "z".repeat(MAX_LENGTH + 1)}}]
+        }))
+        .await;
+    assert_too_long(&response, "title");
+
+    // Nothing was changed by the rejected requests
+    let response = server.get(&format!("/scim/v2/Users/{}", user_id)).await;
+    let stored: Value = response.json();
+    assert_eq!(stored["displayName"], "é".repeat(MAX_LENGTH));
+    assert_eq!(stored["name"]["givenName"], "Length");
+}
+
+#[tokio::test]
+async fn test_group_value_length_limit() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "g".repeat(MAX_LENGTH)
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "g".repeat(MAX_LENGTH + 1)
+        }))
+        .await;
+    assert_too_long(&response, "displayName");
+
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "h".repeat(MAX_LENGTH + 1)
+        }))
+        .await;
+    assert_too_long(&response, "displayName");
+
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "displayName", "value": "i".repeat(MAX_LENGTH + 1)}]
+        }))
+        .await;
+    assert_too_long(&response, "displayName");
+}
+
+#[tokio::test]
+async fn test_default_value_length_limit() {
+    let server = common::test_server(|_| {}).await;
+
+    let default_limit = CompatibilityConfig::default().max_attribute_value_length as usize;
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "x".repeat(default_limit + 1)
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "invalidValue");
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{Request, StatusCode},
+    Router,
+};
+use scim_server::config::{
+    AuthConfig, AuthLockoutConfig, HostResolutionConfig, HostResolutionType,
+};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tower::ServiceExt;
+
+const TOKEN: &str = "lockout-token";
+
+/// Address of the reverse proxy in front of the server
+const PROXY: [u8; 4] = [10, 0, 0, 1];
+
+async fn setup_lockout_app() -> Router {
+    setup_lockout_app_with_proxies(Some(vec!["10.0.0.0/8".to_string()])).await
+}
+
+async fn setup_lockout_app_with_proxies(trusted_proxies: Option<Vec<String>>) -> Router {
+    let mut app_config = common::create_test_app_config();
+    let tenant = &mut app_config.tenants[0];
+    tenant.auth = AuthConfig {
+        auth_type: "bearer".to_string(),
+        token: Some(TOKEN.to_string()),
+        basic: None,
+        basic_users: Vec::new(),
+        tokens: Vec::new(),
+    };
+    tenant.host_resolution = Some(HostResolutionConfig {
+        resolution_type: HostResolutionType::XForwarded,
+        trusted_proxies,
+    });
+    tenant.auth_lockout = Some(AuthLockoutConfig {
+        max_failures: 3,
+        window_seconds: 60,
+        lockout_seconds: 1,
+    });
+    common::setup_test_app(app_config).await.unwrap()
+}
+
+async fn get_users(app: &Router, client_ip: &str, token: &str) -> axum::response::Response {
+    // The client's own proxy hop is appended by a trusted proxy
+    send(app, PROXY, &format!("{}, 10.0.0.2", client_ip), token).await
+}
+
+async fn send(
+    app: &Router,
+    peer: [u8; 4],
+    forwarded_for: &str,
+    token: &str,
+) -> axum::response::Response {
+    let mut request = Request::builder()
+        .uri("/tenant-a/scim/v2/Users")
+        .header("Host", "localhost")
+        .header("X-Forwarded-For", forwarded_for)
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((peer, 4711))));
+    app.clone().oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_lockout_after_repeated_failures() {
+    let app = setup_lockout_app().await;
+
+    for _ in 0..3 {
+        let response = get_users(&app, "203.0.113.7", "wrong").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Locked out, even with the right token
+    let response = get_users(&app, "203.0.113.7", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "1");
+
+    // Other clients are unaffected
+    let response = get_users(&app, "203.0.113.8", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The lockout expires
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let response = get_users(&app, "203.0.113.7", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_successful_authentication_resets_failures() {
+    let app = setup_lockout_app().await;
+
+    for _ in 0..2 {
+        let response = get_users(&app, "203.0.113.7", "wrong").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = get_users(&app, "203.0.113.7", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The count starts over, so two more failures do not lock out
+    for _ in 0..2 {
+        let response = get_users(&app, "203.0.113.7", "wrong").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = get_users(&app, "203.0.113.7", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_tenants_without_policy_are_not_locked_out() {
+    let mut app_config = common::create_test_app_config();
+    app_config.tenants[0].auth = AuthConfig {
+        auth_type: "bearer".to_string(),
+        token: Some(TOKEN.to_string()),
+        basic: None,
+        basic_users: Vec::new(),
+        tokens: Vec::new(),
+    };
+    let app = common::setup_test_app(app_config).await.unwrap();
+
+    for _ in 0..10 {
+        let response = get_users(&app, "203.0.113.7", "wrong").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = get_users(&app, "203.0.113.7", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_spoofed_forwarded_for_does_not_dodge_lockout() {
+    let app = setup_lockout_app().await;
+
+    // The client forges a new leftmost address on every attempt; the proxy
+    // appends the address it really connected from
+    for i in 0..3 {
+        let forged = format!("198.51.100.{}, 203.0.113.7, 10.0.0.2", i);
+        let response = send(&app, PROXY, &forged, "wrong").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = send(&app, PROXY, "198.51.100.99, 203.0.113.7", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_forwarded_for_is_ignored_without_trusted_proxies() {
+    let app = setup_lockout_app_with_proxies(None).await;
+
+    // Every peer would be trusted, so failures are counted under the peer
+    for i in 0..3 {
+        let forged = format!("198.51.100.{}", i);
+        let response = send(&app, [203, 0, 113, 9], &forged, "wrong").await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = send(&app, [203, 0, 113, 9], "198.51.100.99", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // A forged header naming a locked out address does not lock out others
+    let response = send(&app, [203, 0, 113, 10], "203.0.113.9", TOKEN).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -18,16 +18,31 @@ async fn test_unauthenticated_access() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -35,12 +50,31 @@ async fn test_unauthenticated_access() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -67,16 +101,31 @@ async fn test_bearer_token_authentication_success() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -84,12 +133,31 @@ async fn test_bearer_token_authentication_success() {
                 auth_type: "bearer".to_string(),
                 token: Some("test-secret-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -117,16 +185,31 @@ async fn test_bearer_token_authentication_failure() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -134,12 +217,31 @@ async fn test_bearer_token_authentication_failure() {
                 auth_type: "bearer".to_string(),
                 token: Some("test-secret-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -167,16 +269,31 @@ async fn test_bearer_token_missing_header() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -184,12 +301,31 @@ async fn test_bearer_token_missing_header() {
                 auth_type: "bearer".to_string(),
                 token: Some("test-secret-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -216,16 +352,31 @@ async fn test_basic_authentication_success() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -236,12 +387,31 @@ async fn test_basic_authentication_success() {
                     username: "testuser".to_string(),
                     password: "testpass".to_string(),
                 }),
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -273,16 +443,31 @@ async fn test_basic_authentication_failure() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -293,12 +478,31 @@ async fn test_basic_authentication_failure() {
                     username: "testuser".to_string(),
                     password: "testpass".to_string(),
                 }),
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -330,16 +534,31 @@ async fn test_multi_tenant_authentication() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![
             TenantConfig {
                 id: 1,
@@ -348,12 +567,31 @@ async fn test_multi_tenant_authentication() {
                     auth_type: "bearer".to_string(),
                     token: Some("tenant-a-token".to_string()),
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
             TenantConfig {
                 id: 2,
@@ -365,12 +603,31 @@ async fn test_multi_tenant_authentication() {
                         username: "tenant-b-user".to_string(),
                         password: "tenant-b-pass".to_string(),
                     }),
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
         ],
     };
@@ -416,16 +673,31 @@ async fn test_tenant_not_found() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -433,12 +705,31 @@ async fn test_tenant_not_found() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -0,0 +1,119 @@
+//! Writes grouped on a transaction begun through the backend traits
+use scim_server::backend::database::DatabaseBackendConfig;
+use scim_server::backend::{BackendFactory, Provenance, ScimBackend};
+use scim_server::models::{Group, User};
+use scim_server::parser::ResourceType;
+use std::sync::Arc;
+
+const TENANT_ID: u32 = 1;
+
+async fn setup_backend() -> Arc<dyn ScimBackend> {
+    let backend = BackendFactory::create(&DatabaseBackendConfig::memory_sqlite())
+        .await
+        .unwrap();
+    backend.init_tenant(TENANT_ID).await.unwrap();
+    backend
+}
+
+/// Create a user and a group listing it in one transaction
+async fn write_user_and_group(backend: &Arc<dyn ScimBackend>, commit: bool) -> (String, String) {
+    let mut user = User::default();
+    user.base.user_name = "tx.user".to_string();
+    let mut group = Group::default();
+    group.base.display_name = "Tx Group".to_string();
+
+    let mut tx = backend.begin_transaction().await.unwrap();
+    let user = backend
+        .create_user_in(&mut tx, TENANT_ID, &user)
+        .await
+        .unwrap();
+    let user_id = user.id().clone().unwrap();
+    let group = backend
+        .create_group_in(&mut tx, TENANT_ID, &group)
+        .await
+        .unwrap();
+    let added = backend
+        .add_group_members_in(
+            &mut tx,
+            TENANT_ID,
+            group.id(),
+            std::slice::from_ref(&user_id),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(added.added, 1);
+    if commit {
+        tx.commit().await.unwrap();
+    }
+    (user_id, group.id().clone())
+}
+
+#[tokio::test]
+async fn test_committed_writes_are_visible_together() {
+    let backend = setup_backend().await;
+    let (user_id, group_id) = write_user_and_group(&backend, true).await;
+
+    let group = backend
+        .find_group_by_id(TENANT_ID, &group_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let members = group.base.members.unwrap_or_default();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].value.as_deref(), Some(user_id.as_str()));
+}
+
+#[tokio::test]
+async fn test_dropped_transaction_leaves_nothing_behind() {
+    let backend = setup_backend().await;
+    let (user_id, group_id) = write_user_and_group(&backend, false).await;
+
+    assert!(backend
+        .find_user_by_id(TENANT_ID, &user_id, false)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(backend
+        .find_group_by_id(TENANT_ID, &group_id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_actor_is_written_with_the_resource() {
+    let backend = setup_backend().await;
+    let mut user = User::default();
+    user.base.user_name = "tx.actor".to_string();
+
+    let mut tx = backend
+        .begin_transaction()
+        .await
+        .unwrap()
+        .with_actor(Some("alice"));
+    let created = backend
+        .create_user_in(&mut tx, TENANT_ID, &user)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+    let id = created.id().clone().unwrap();
+
+    let updated = backend
+        .update_user(TENANT_ID, &id, &created, Some("bob"))
+        .await
+        .unwrap();
+    assert!(updated.is_some());
+
+    let provenance = backend
+        .find_provenance(TENANT_ID, ResourceType::User, std::slice::from_ref(&id))
+        .await
+        .unwrap();
+    assert_eq!(
+        provenance.get(&id),
+        Some(&Provenance {
+            created_by: Some("alice".to_string()),
+            last_modified_by: Some("bob".to_string()),
+        })
+    );
+}
@@ -0,0 +1,97 @@
+/// Backend connection loss
+///
+/// When no database connection can be obtained, handlers must answer with a
+/// retryable 503 SCIM error that does not leak the driver message.
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::backend::database::sqlite::SqliteBackend;
+use scim_server::backend::{Backend, ScimBackend};
+use serde_json::Value;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+
+fn assert_unavailable(response: &axum_test::TestResponse) {
+    response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    assert!(response.headers().get("retry-after").is_some());
+    let body: Value = response.json();
+    assert_eq!(
+        body["schemas"][0],
+        "urn:ietf:params:scim:api:messages:2.0:Error"
+    );
+    assert_eq!(body["status"], "503");
+    let detail = body["detail"].as_str().unwrap();
+    assert!(!detail.contains("pool"), "{}", detail);
+    assert!(!detail.contains("connection"), "{}", detail);
+}
+
+#[tokio::test]
+async fn test_pool_acquire_timeout_returns_503() {
+    // A single connection that the test holds on to, so every acquire by
+    // the backend times out
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_millis(100))
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let backend = SqliteBackend::new(pool.clone());
+    backend.init_tenant(3).await.unwrap();
+    let backend: Arc<dyn ScimBackend> = Arc::new(backend);
+
+    let app = common::setup_test_app_with_backend(common::create_test_app_config(), backend)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let held = pool.acquire().await.unwrap();
+
+    assert_unavailable(&server.get("/scim/v2/Users").await);
+    assert_unavailable(
+        &server
+            .post("/scim/v2/Groups")
+            .json(&serde_json::json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+                "displayName": "Unreachable"
+            }))
+            .await,
+    );
+
+    // Requests succeed again once a connection is available
+    drop(held);
+    server
+        .get("/scim/v2/Users")
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_closed_pool_returns_503() {
+    let pool = SqlitePoolOptions::new()
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let backend = SqliteBackend::new(pool.clone());
+    backend.init_tenant(3).await.unwrap();
+    let backend: Arc<dyn ScimBackend> = Arc::new(backend);
+
+    let app = common::setup_test_app_with_backend(common::create_test_app_config(), backend)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    pool.close().await;
+
+    assert_unavailable(
+        &server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(
+                "closed.pool",
+                "Closed",
+                "Pool",
+            ))
+            .await,
+    );
+}
@@ -0,0 +1,110 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+fn user_with_email_type(user_name: &str, email_type: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": user_name,
+        "emails": [{"value": format!("{}@example.com", user_name), "type": email_type}]
+    })
+}
+
+fn assert_invalid_value(response: &axum_test::TestResponse) {
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+    assert_eq!(body["status"], "400");
+}
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        // Tenant 1 (/tenant-a) restricts email types; tenant 3 (/scim/v2) does not
+        app_config.tenants[0].canonical_values.insert(
+            "emails.type".to_string(),
+            vec!["work".to_string(), "home".to_string(), "other".to_string()],
+        );
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_allowed_email_type_is_accepted() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&user_with_email_type("alice", "work"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user: Value = response.json();
+    let user_id = user["id"].as_str().unwrap();
+
+    let response = server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .json(&user_with_email_type("alice", "home"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    let response = server
+        .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "replace",
+                "path": "emails[type eq \"home\"].type",
+                "value": "other"
+            }]
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_disallowed_email_type_is_rejected() {
+    let server = setup_server().await;
+
+    assert_invalid_value(
+        &server
+            .post("/tenant-a/scim/v2/Users")
+            .json(&user_with_email_type("bob", "pager"))
+            .await,
+    );
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&user_with_email_type("bob", "work"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user: Value = response.json();
+    let user_id = user["id"].as_str().unwrap();
+
+    assert_invalid_value(
+        &server
+            .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+            .json(&user_with_email_type("bob", "pager"))
+            .await,
+    );
+    assert_invalid_value(
+        &server
+            .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{
+                    "op": "add",
+                    "path": "emails",
+                    "value": [{"value": "bob2@example.com", "type": "pager"}]
+                }]
+            }))
+            .await,
+    );
+
+    // Tenants without canonical values accept any type
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&user_with_email_type("carol", "pager"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+}
@@ -1,27 +1,28 @@
-use axum::{
-    middleware,
-    routing::{delete, get, patch, post, put},
-    Router,
-};
+use axum::Router;
+use axum_test::TestServer;
 use scim_server::backend::database::DatabaseBackendConfig;
 use scim_server::backend::{BackendFactory, DatabaseType, ScimBackend};
 use scim_server::config::{
     AppConfig, AuthConfig, BackendConfig, CompatibilityConfig, DatabaseConfig, ServerConfig,
     TenantConfig,
 };
+use scim_server::stats::SyncStats;
 use serde_json::json;
+use std::collections::HashSet;
 use std::sync::Arc;
 #[cfg(test)]
 use testcontainers::ContainerAsync;
 #[cfg(test)]
+use testcontainers_modules::mysql::Mysql;
+#[cfg(test)]
 use testcontainers_modules::postgres::Postgres;
-use url::Url;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(dead_code)]
 pub enum TestDatabaseType {
     Sqlite,
     Postgres,
+    Mysql,
 }
 
 #[allow(dead_code)]
@@ -29,16 +30,31 @@ pub struct TestDatabase {
     pub database_type: TestDatabaseType,
     #[cfg(test)]
     pub postgres_container: Option<ContainerAsync<Postgres>>,
+    #[cfg(test)]
+    pub mysql_container: Option<ContainerAsync<Mysql>>,
+}
+
+/// Tenants the server matches externalId case-insensitively in
+fn external_id_case_insensitive_tenants(app_config: &AppConfig) -> HashSet<u32> {
+    app_config
+        .tenants
+        .iter()
+        .filter(|tenant| tenant.external_id_case_insensitive)
+        .map(|tenant| tenant.id)
+        .collect()
 }
 
 /// Create backend for testing with in-memory SQLite database
-pub async fn setup_test_database() -> Result<Arc<dyn ScimBackend>, Box<dyn std::error::Error>> {
+pub async fn setup_test_database(
+    app_config: &AppConfig,
+) -> Result<Arc<dyn ScimBackend>, Box<dyn std::error::Error>> {
     let backend_config = DatabaseBackendConfig {
         database_type: DatabaseType::SQLite,
         connection_path: ":memory:".to_string(),
         max_connections: 1,
         connection_timeout: 30,
         options: std::collections::HashMap::new(),
+        external_id_case_insensitive_tenants: external_id_case_insensitive_tenants(app_config),
     };
 
     let backend = BackendFactory::create(&backend_config).await?;
@@ -57,6 +73,7 @@ pub async fn setup_test_database() -> Result<Arc<dyn ScimBackend>, Box<dyn std::
 #[cfg(test)]
 #[allow(dead_code)]
 pub async fn setup_postgres_test_database(
+    app_config: &AppConfig,
 ) -> Result<(Arc<dyn ScimBackend>, ContainerAsync<Postgres>), Box<dyn std::error::Error>> {
     use testcontainers::runners::AsyncRunner;
 
@@ -76,6 +93,7 @@ pub async fn setup_postgres_test_database(
         max_connections: 5,
         connection_timeout: 30,
         options: std::collections::HashMap::new(),
+        external_id_case_insensitive_tenants: external_id_case_insensitive_tenants(app_config),
     };
 
     let backend = BackendFactory::create(&backend_config).await?;
@@ -90,118 +108,79 @@ pub async fn setup_postgres_test_database(
     Ok((backend, postgres_container))
 }
 
-/// Create a test app with in-memory database and given tenant configuration
-pub async fn setup_test_app(app_config: AppConfig) -> Result<Router, Box<dyn std::error::Error>> {
-    let backend = setup_test_database().await?;
-
-    let app_config_arc = Arc::new(app_config.clone());
+/// Create backend for testing with MySQL using TestContainers
+#[cfg(test)]
+#[allow(dead_code)]
+pub async fn setup_mysql_test_database(
+    app_config: &AppConfig,
+) -> Result<(Arc<dyn ScimBackend>, ContainerAsync<Mysql>), Box<dyn std::error::Error>> {
+    use testcontainers::runners::AsyncRunner;
 
-    // Build our application with multi-tenant routes based on tenant configuration
-    let mut app = Router::new();
+    let mysql_container = Mysql::default()
+        .start()
+        .await
+        .expect("Failed to start mysql container");
 
-    // Add custom endpoints first (before SCIM routes)
-    for tenant in &app_config.tenants {
-        for endpoint in &tenant.custom_endpoints {
-            app = app.route(
-                &endpoint.path,
-                get(scim_server::resource::custom::handle_custom_endpoint),
-            );
-        }
-    }
+    let connection_string = format!(
+        "mysql://root@127.0.0.1:{}/test",
+        mysql_container.get_host_port_ipv4(3306).await?
+    );
 
-    // Add routes for each tenant based on their configured URL path
-    for tenant in &app_config.tenants {
-        // Extract path from tenant path (remove protocol and host if present)
-        let base_path = if tenant.path.starts_with("http://") || tenant.path.starts_with("https://")
-        {
-            // Extract path from full URL
-            if let Ok(url) = Url::parse(&tenant.path) {
-                url.path().trim_end_matches('/').to_string()
-            } else {
-                "/scim".to_string() // fallback
-            }
-        } else {
-            // Already a path
-            tenant.path.trim_end_matches('/').to_string()
-        };
+    let backend_config = DatabaseBackendConfig {
+        database_type: DatabaseType::MySQL,
+        connection_path: connection_string,
+        max_connections: 5,
+        connection_timeout: 30,
+        options: std::collections::HashMap::new(),
+        external_id_case_insensitive_tenants: external_id_case_insensitive_tenants(app_config),
+    };
 
-        // ServiceProviderConfig routes
-        app = app.route(
-            &format!("{}/ServiceProviderConfig", base_path),
-            get(scim_server::resource::service_provider::service_provider_config),
-        );
+    let backend = BackendFactory::create(&backend_config).await?;
 
-        // Schema and ResourceType routes
-        app = app.route(
-            &format!("{}/Schemas", base_path),
-            get(scim_server::resource::schema::schemas),
-        );
-        app = app.route(
-            &format!("{}/ResourceTypes", base_path),
-            get(scim_server::resource::resource_type::resource_types),
-        );
+    // Create tables for all tenants that tests use
+    // Use standard tenant IDs that match the URL routing
+    let tenant_ids = vec![1, 2, 3];
+    for tenant_id in tenant_ids {
+        backend.init_tenant(tenant_id).await?;
+    }
 
-        // User routes
-        app = app.route(
-            &format!("{}/Users", base_path),
-            post(scim_server::resource::user::create_user),
-        );
-        app = app.route(
-            &format!("{}/Users", base_path),
-            get(scim_server::resource::user::search_users),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            get(scim_server::resource::user::get_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            put(scim_server::resource::user::update_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            patch(scim_server::resource::user::patch_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            delete(scim_server::resource::user::delete_user),
-        );
+    Ok((backend, mysql_container))
+}
 
-        // Group routes
-        app = app.route(
-            &format!("{}/Groups", base_path),
-            post(scim_server::resource::group::create_group),
-        );
-        app = app.route(
-            &format!("{}/Groups", base_path),
-            get(scim_server::resource::group::search_groups),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            get(scim_server::resource::group::get_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            put(scim_server::resource::group::update_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            patch(scim_server::resource::group::patch_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            delete(scim_server::resource::group::delete_group),
-        );
-    }
+/// Create a test app with in-memory database and given tenant configuration
+pub async fn setup_test_app(app_config: AppConfig) -> Result<Router, Box<dyn std::error::Error>> {
+    let backend = setup_test_database(&app_config).await?;
+    setup_test_app_with_backend(app_config, backend).await
+}
 
-    let app = app
-        .layer(middleware::from_fn_with_state(
-            app_config_arc.clone(),
-            scim_server::auth::auth_middleware,
-        ))
-        .with_state((backend, app_config_arc));
+/// Test server on an in-memory database for `create_test_app_config`, after
+/// `configure` adjusted it
+#[allow(dead_code)]
+pub async fn test_server(configure: impl FnOnce(&mut AppConfig)) -> TestServer {
+    let mut app_config = create_test_app_config();
+    configure(&mut app_config);
+    let app = setup_test_app(app_config).await.unwrap();
+    TestServer::new(app).unwrap()
+}
 
-    Ok(app)
+/// Create a test app on top of an already initialized backend
+///
+/// Responses of every tenant are checked against the schema definitions.
+pub async fn setup_test_app_with_backend(
+    mut app_config: AppConfig,
+    backend: Arc<dyn ScimBackend>,
+) -> Result<Router, Box<dyn std::error::Error>> {
+    app_config.compatibility.validate_responses = true;
+    for tenant in &mut app_config.tenants {
+        if let Some(compatibility) = tenant.compatibility.as_mut() {
+            compatibility.validate_responses = true;
+        }
+    }
+    Ok(scim_server::router::build_router(
+        Arc::new(app_config),
+        backend,
+        Arc::new(SyncStats::default()),
+    ))
 }
 
 /// Create a test app with PostgreSQL using TestContainers
@@ -210,118 +189,22 @@ pub async fn setup_test_app(app_config: AppConfig) -> Result<Router, Box<dyn std
 pub async fn setup_postgres_test_app(
     app_config: AppConfig,
 ) -> Result<(Router, ContainerAsync<Postgres>), Box<dyn std::error::Error>> {
-    let (backend, postgres_container) = setup_postgres_test_database().await?;
-
-    let app_config_arc = Arc::new(app_config.clone());
-
-    // Build our application with multi-tenant routes based on tenant configuration
-    let mut app = Router::new();
-
-    // Add custom endpoints first (before SCIM routes)
-    for tenant in &app_config.tenants {
-        for endpoint in &tenant.custom_endpoints {
-            app = app.route(
-                &endpoint.path,
-                get(scim_server::resource::custom::handle_custom_endpoint),
-            );
-        }
-    }
-
-    // Add routes for each tenant based on their configured URL path
-    for tenant in &app_config.tenants {
-        // Extract path from tenant path (remove protocol and host if present)
-        let base_path = if tenant.path.starts_with("http://") || tenant.path.starts_with("https://")
-        {
-            // Extract path from full URL
-            if let Ok(url) = Url::parse(&tenant.path) {
-                url.path().trim_end_matches('/').to_string()
-            } else {
-                "/scim".to_string() // fallback
-            }
-        } else {
-            // Already a path
-            tenant.path.trim_end_matches('/').to_string()
-        };
-
-        // ServiceProviderConfig routes
-        app = app.route(
-            &format!("{}/ServiceProviderConfig", base_path),
-            get(scim_server::resource::service_provider::service_provider_config),
-        );
-
-        // Schema and ResourceType routes
-        app = app.route(
-            &format!("{}/Schemas", base_path),
-            get(scim_server::resource::schema::schemas),
-        );
-        app = app.route(
-            &format!("{}/ResourceTypes", base_path),
-            get(scim_server::resource::resource_type::resource_types),
-        );
-
-        // User routes
-        app = app.route(
-            &format!("{}/Users", base_path),
-            post(scim_server::resource::user::create_user),
-        );
-        app = app.route(
-            &format!("{}/Users", base_path),
-            get(scim_server::resource::user::search_users),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            get(scim_server::resource::user::get_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            put(scim_server::resource::user::update_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            patch(scim_server::resource::user::patch_user),
-        );
-        app = app.route(
-            &format!("{}/Users/{{id}}", base_path),
-            delete(scim_server::resource::user::delete_user),
-        );
-
-        // Group routes
-        app = app.route(
-            &format!("{}/Groups", base_path),
-            post(scim_server::resource::group::create_group),
-        );
-        app = app.route(
-            &format!("{}/Groups", base_path),
-            get(scim_server::resource::group::search_groups),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            get(scim_server::resource::group::get_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            put(scim_server::resource::group::update_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            patch(scim_server::resource::group::patch_group),
-        );
-        app = app.route(
-            &format!("{}/Groups/{{id}}", base_path),
-            delete(scim_server::resource::group::delete_group),
-        );
-    }
-
-    let app = app
-        .layer(middleware::from_fn_with_state(
-            app_config_arc.clone(),
-            scim_server::auth::auth_middleware,
-        ))
-        .with_state((backend, app_config_arc));
-
+    let (backend, postgres_container) = setup_postgres_test_database(&app_config).await?;
+    let app = setup_test_app_with_backend(app_config, backend).await?;
     Ok((app, postgres_container))
 }
 
+/// Create a test app with MySQL using TestContainers
+#[cfg(test)]
+#[allow(dead_code)]
+pub async fn setup_mysql_test_app(
+    app_config: AppConfig,
+) -> Result<(Router, ContainerAsync<Mysql>), Box<dyn std::error::Error>> {
+    let (backend, mysql_container) = setup_mysql_test_database(&app_config).await?;
+    let app = setup_test_app_with_backend(app_config, backend).await?;
+    Ok((app, mysql_container))
+}
+
 /// Unified setup function for any database type
 #[allow(dead_code)]
 pub async fn setup_test_app_with_db(
@@ -337,6 +220,8 @@ pub async fn setup_test_app_with_db(
                     database_type: TestDatabaseType::Sqlite,
                     #[cfg(test)]
                     postgres_container: None,
+                    #[cfg(test)]
+                    mysql_container: None,
                 },
             ))
         }
@@ -349,6 +234,7 @@ pub async fn setup_test_app_with_db(
                     TestDatabase {
                         database_type: TestDatabaseType::Postgres,
                         postgres_container: Some(postgres_container),
+                        mysql_container: None,
                     },
                 ))
             }
@@ -357,6 +243,24 @@ pub async fn setup_test_app_with_db(
                 panic!("PostgreSQL test database setup requires test configuration")
             }
         }
+        TestDatabaseType::Mysql => {
+            #[cfg(test)]
+            {
+                let (app, mysql_container) = setup_mysql_test_app(app_config).await?;
+                Ok((
+                    app,
+                    TestDatabase {
+                        database_type: TestDatabaseType::Mysql,
+                        postgres_container: None,
+                        mysql_container: Some(mysql_container),
+                    },
+                ))
+            }
+            #[cfg(not(test))]
+            {
+                panic!("MySQL test database setup requires test configuration")
+            }
+        }
     }
 }
 
@@ -367,16 +271,31 @@ pub fn create_test_app_config() -> AppConfig {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 10,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![
             TenantConfig {
                 id: 1,
@@ -385,12 +304,31 @@ pub fn create_test_app_config() -> AppConfig {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
             TenantConfig {
                 id: 2,
@@ -399,12 +337,31 @@ pub fn create_test_app_config() -> AppConfig {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
             TenantConfig {
                 id: 3,
@@ -413,12 +370,31 @@ pub fn create_test_app_config() -> AppConfig {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
         ],
     }
@@ -431,16 +407,31 @@ pub fn create_token_auth_config() -> AppConfig {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 10,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -450,10 +441,29 @@ pub fn create_token_auth_config() -> AppConfig {
                 auth_type: "token".to_string(),
                 token: Some("test-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     }
 }
@@ -464,16 +474,31 @@ pub fn create_bearer_auth_config() -> AppConfig {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 10,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -483,10 +508,29 @@ pub fn create_bearer_auth_config() -> AppConfig {
                 auth_type: "bearer".to_string(),
                 token: Some("test-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     }
 }
@@ -0,0 +1,135 @@
+use axum_test::TestServer;
+use flate2::read::GzDecoder;
+use http::StatusCode;
+use scim_server::config::{CompressionAlgorithm, CompressionConfig};
+use serde_json::{json, Value};
+use std::io::Read;
+
+mod common;
+
+async fn setup_server(compression: CompressionConfig) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.server.compression = compression;
+    })
+    .await
+}
+
+fn enabled(min_size_bytes: u64) -> CompressionConfig {
+    CompressionConfig {
+        enabled: true,
+        min_size_bytes,
+        ..Default::default()
+    }
+}
+
+async fn create_users(server: &TestServer, count: usize) {
+    for i in 0..count {
+        let response = server
+            .post("/tenant-a/scim/v2/Users")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": format!("compressed.user.{:03}", i),
+                "displayName": format!("Compressed User {:03} of a large listing", i),
+                "emails": [{"value": format!("compressed.user.{:03}@example.com", i), "type": "work"}]
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+}
+
+fn gunzip(bytes: &[u8]) -> Value {
+    let mut decoded = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut decoded).unwrap();
+    serde_json::from_str(&decoded).unwrap()
+}
+
+#[tokio::test]
+async fn test_large_list_is_gzip_compressed() {
+    let server = setup_server(enabled(1024)).await;
+    create_users(&server, 50).await;
+
+    let response = server
+        .get("/tenant-a/scim/v2/Users?count=100")
+        .add_header("accept-encoding", "gzip")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+
+    let list = gunzip(response.as_bytes());
+    assert_eq!(list["totalResults"], 50);
+    assert_eq!(list["Resources"].as_array().unwrap().len(), 50);
+
+    // Without Accept-Encoding the same list is sent as is
+    let response = server.get("/tenant-a/scim/v2/Users?count=100").await;
+    assert!(response.headers().get("content-encoding").is_none());
+    let list: Value = response.json();
+    assert_eq!(list["totalResults"], 50);
+}
+
+#[tokio::test]
+async fn test_small_responses_are_not_compressed() {
+    let server = setup_server(enabled(64 * 1024)).await;
+    create_users(&server, 1).await;
+
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_header("accept-encoding", "gzip, br")
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.headers().get("content-encoding").is_none());
+}
+
+#[tokio::test]
+async fn test_headers_survive_compression() {
+    let server = setup_server(enabled(0)).await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header("accept-encoding", "gzip")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "headers.survive"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+
+    let user = gunzip(response.as_bytes());
+    assert_eq!(
+        response.headers()["location"],
+        user["meta"]["location"].as_str().unwrap()
+    );
+    assert_eq!(
+        response.headers()["etag"],
+        user["meta"]["version"].as_str().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_only_configured_algorithms_are_offered() {
+    let server = setup_server(CompressionConfig {
+        algorithms: vec![CompressionAlgorithm::Br],
+        ..enabled(0)
+    })
+    .await;
+
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_header("accept-encoding", "gzip")
+        .await;
+    assert!(response.headers().get("content-encoding").is_none());
+
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_header("accept-encoding", "gzip, br")
+        .await;
+    assert_eq!(response.headers()["content-encoding"], "br");
+
+    // Disabled (the default) never compresses
+    let server = setup_server(CompressionConfig::default()).await;
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_header("accept-encoding", "gzip, br")
+        .await;
+    assert!(response.headers().get("content-encoding").is_none());
+}
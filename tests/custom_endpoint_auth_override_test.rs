@@ -14,16 +14,31 @@ async fn test_custom_endpoint_auth_override_from_tenant_to_unauthenticated() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -31,23 +46,48 @@ async fn test_custom_endpoint_auth_override_from_tenant_to_unauthenticated() {
                 auth_type: "bearer".to_string(),
                 token: Some("tenant-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/api/public".to_string(),
                 response: json!({"message": "This endpoint overrides tenant auth to be public"})
                     .to_string(),
                 status_code: 200,
                 content_type: "application/json".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: Some(AuthConfig {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 }),
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -89,16 +129,31 @@ async fn test_custom_endpoint_auth_override_from_unauthenticated_to_bearer() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -106,22 +161,47 @@ async fn test_custom_endpoint_auth_override_from_unauthenticated_to_bearer() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/api/secure".to_string(),
                 response: json!({"message": "This endpoint overrides tenant auth to require bearer token"}).to_string(),
                 status_code: 200,
                 content_type: "application/json".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: Some(AuthConfig {
                     auth_type: "bearer".to_string(),
                     token: Some("custom-endpoint-token".to_string()),
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 }),
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -163,16 +243,31 @@ async fn test_custom_endpoint_auth_override_from_bearer_to_basic() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -180,10 +275,13 @@ async fn test_custom_endpoint_auth_override_from_bearer_to_basic() {
                 auth_type: "bearer".to_string(),
                 token: Some("tenant-bearer-token".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/api/basic-auth".to_string(),
                 response:
@@ -191,6 +289,10 @@ async fn test_custom_endpoint_auth_override_from_bearer_to_basic() {
                         .to_string(),
                 status_code: 200,
                 content_type: "application/json".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: Some(AuthConfig {
                     auth_type: "basic".to_string(),
                     token: None,
@@ -198,9 +300,27 @@ async fn test_custom_endpoint_auth_override_from_bearer_to_basic() {
                         username: "custom-user".to_string(),
                         password: "custom-pass".to_string(),
                     }),
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 }),
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -251,16 +371,31 @@ async fn test_custom_endpoint_without_auth_override_inherits_tenant_auth() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -268,18 +403,41 @@ async fn test_custom_endpoint_without_auth_override_inherits_tenant_auth() {
                 auth_type: "bearer".to_string(),
                 token: Some("tenant-token-inherited".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/api/inherit-auth".to_string(),
                 response: json!({"message": "This endpoint inherits tenant auth"}).to_string(),
                 status_code: 200,
                 content_type: "application/json".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: None, // No override - should inherit tenant auth
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
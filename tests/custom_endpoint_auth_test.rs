@@ -14,16 +14,31 @@ async fn test_custom_endpoint_with_unauthenticated_access() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -31,18 +46,41 @@ async fn test_custom_endpoint_with_unauthenticated_access() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/custom/health".to_string(),
                 status_code: 200,
                 content_type: "application/json".to_string(),
                 response: "{\"status\":\"healthy\"}".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: None,
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -66,16 +104,31 @@ async fn test_custom_endpoint_with_bearer_auth() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -83,18 +136,41 @@ async fn test_custom_endpoint_with_bearer_auth() {
                 auth_type: "bearer".to_string(),
                 token: Some("test-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/api/status".to_string(),
                 status_code: 200,
                 content_type: "text/plain".to_string(),
                 response: "Service is running".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: None,
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -129,16 +205,31 @@ async fn test_custom_endpoint_with_basic_auth() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -149,18 +240,41 @@ async fn test_custom_endpoint_with_basic_auth() {
                     username: "admin".to_string(),
                     password: "secret123".to_string(),
                 }),
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/metrics".to_string(),
                 status_code: 200,
                 content_type: "text/plain".to_string(),
                 response: "requests_total 42".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: None,
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -198,16 +312,31 @@ async fn test_multiple_tenants_with_custom_endpoints() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![
             TenantConfig {
                 id: 1,
@@ -216,18 +345,41 @@ async fn test_multiple_tenants_with_custom_endpoints() {
                     auth_type: "bearer".to_string(),
                     token: Some("tenant1-token".to_string()),
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![CustomEndpoint {
                     path: "/tenant1/status".to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
                     response: "{\"tenant\":\"1\",\"status\":\"ok\"}".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 }],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
             TenantConfig {
                 id: 2,
@@ -236,18 +388,41 @@ async fn test_multiple_tenants_with_custom_endpoints() {
                     auth_type: "bearer".to_string(),
                     token: Some("tenant2-token".to_string()),
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![CustomEndpoint {
                     path: "/tenant2/status".to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
                     response: "{\"tenant\":\"2\",\"status\":\"ok\"}".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 }],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
         ],
     };
@@ -293,16 +468,31 @@ async fn test_custom_endpoint_not_found() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -310,18 +500,41 @@ async fn test_custom_endpoint_not_found() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/custom/test".to_string(),
                 status_code: 200,
                 content_type: "text/plain".to_string(),
                 response: "test".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: None,
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
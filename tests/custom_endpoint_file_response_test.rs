@@ -0,0 +1,144 @@
+/// Custom endpoints answering with `response_file` and `response_base64`
+use axum_test::TestServer;
+use base64::{engine::general_purpose, Engine as _};
+use http::StatusCode;
+use scim_server::config::CustomEndpoint;
+use std::time::{Duration, SystemTime};
+
+mod common;
+
+const LOGO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logo.png");
+
+fn endpoint(path: &str) -> CustomEndpoint {
+    CustomEndpoint {
+        path: path.to_string(),
+        response: String::new(),
+        status_code: 200,
+        content_type: "image/png".to_string(),
+        method: None,
+        response_file: None,
+        response_base64: None,
+        cache_response_file: false,
+        auth: None,
+    }
+}
+
+async fn setup_server(endpoints: Vec<CustomEndpoint>) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[0].custom_endpoints = endpoints;
+        app_config.validate_custom_endpoints().unwrap();
+    })
+    .await
+}
+
+/// A copy of the logo that the test may modify
+fn copy_logo(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("scim-{}-{}.png", name, std::process::id()));
+    std::fs::copy(LOGO, &path).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+/// Overwrite `path`, moving its modification time forward
+fn rewrite(path: &str, contents: &[u8]) {
+    std::fs::write(path, contents).unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_file_response_round_trips_bytes() {
+    let mut logo = endpoint("/branding/logo.png");
+    logo.response_file = Some(LOGO.to_string());
+    let server = setup_server(vec![logo]).await;
+    let expected = std::fs::read(LOGO).unwrap();
+
+    let response = server.get("/branding/logo.png").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "image/png");
+    assert_eq!(
+        response.header("content-length"),
+        expected.len().to_string().as_str()
+    );
+    assert_eq!(response.as_bytes().as_ref(), expected.as_slice());
+}
+
+#[tokio::test]
+async fn test_file_response_conditional_get() {
+    let mut logo = endpoint("/branding/logo.png");
+    logo.response_file = Some(LOGO.to_string());
+    let server = setup_server(vec![logo]).await;
+
+    let response = server.get("/branding/logo.png").await;
+    response.assert_status_ok();
+    let last_modified = response.header("last-modified");
+
+    let response = server
+        .get("/branding/logo.png")
+        .add_header("If-Modified-Since", last_modified.clone())
+        .await;
+    response.assert_status(StatusCode::NOT_MODIFIED);
+    assert!(response.as_bytes().is_empty());
+    assert_eq!(response.header("last-modified"), last_modified);
+
+    // Older than the file, or not a date
+    for since in ["Thu, 01 Jan 1970 00:00:00 GMT", "yesterday"] {
+        let response = server
+            .get("/branding/logo.png")
+            .add_header("If-Modified-Since", since)
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.as_bytes().as_ref(), std::fs::read(LOGO).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_file_response_follows_file_changes() {
+    let streamed_path = copy_logo("streamed");
+    let cached_path = copy_logo("cached");
+    let mut streamed = endpoint("/branding/streamed.png");
+    streamed.response_file = Some(streamed_path.clone());
+    let mut cached = endpoint("/branding/cached.png");
+    cached.response_file = Some(cached_path.clone());
+    cached.cache_response_file = true;
+    let server = setup_server(vec![streamed, cached]).await;
+
+    let logo = std::fs::read(LOGO).unwrap();
+    for path in ["/branding/streamed.png", "/branding/cached.png"] {
+        assert_eq!(server.get(path).await.as_bytes().as_ref(), logo.as_slice());
+    }
+
+    rewrite(&streamed_path, b"replaced streamed");
+    rewrite(&cached_path, b"replaced cached");
+    for (path, expected) in [
+        ("/branding/streamed.png", "replaced streamed"),
+        ("/branding/cached.png", "replaced cached"),
+    ] {
+        let response = server.get(path).await;
+        assert_eq!(response.as_bytes().as_ref(), expected.as_bytes());
+        assert_eq!(
+            response.header("content-length"),
+            expected.len().to_string().as_str()
+        );
+    }
+
+    std::fs::remove_file(streamed_path).unwrap();
+    std::fs::remove_file(cached_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_base64_response_round_trips_bytes() {
+    let logo = std::fs::read(LOGO).unwrap();
+    let mut inline = endpoint("/branding/inline.png");
+    inline.response_base64 = Some(general_purpose::STANDARD.encode(&logo));
+    let server = setup_server(vec![inline]).await;
+
+    let response = server.get("/branding/inline.png").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "image/png");
+    assert_eq!(
+        response.header("content-length"),
+        logo.len().to_string().as_str()
+    );
+    assert_eq!(response.as_bytes().as_ref(), logo.as_slice());
+}
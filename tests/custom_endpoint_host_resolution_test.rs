@@ -17,16 +17,31 @@ async fn test_custom_endpoint_basic_with_tenant_isolation() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![
             TenantConfig {
                 id: 1,
@@ -35,18 +50,41 @@ async fn test_custom_endpoint_basic_with_tenant_isolation() {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![CustomEndpoint {
                     path: "/tenant1/custom/status".to_string(),
                     response: r#"{"tenant": "tenant1", "status": "ok"}"#.to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 }],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
             TenantConfig {
                 id: 2,
@@ -55,18 +93,41 @@ async fn test_custom_endpoint_basic_with_tenant_isolation() {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![CustomEndpoint {
                     path: "/tenant2/custom/status".to_string(),
                     response: r#"{"tenant": "tenant2", "status": "healthy"}"#.to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 }],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
         ],
     };
@@ -111,6 +172,8 @@ async fn test_tenant_config_supports_route() {
             auth_type: "unauthenticated".to_string(),
             token: None,
             basic: None,
+            basic_users: Vec::new(),
+            tokens: Vec::new(),
         },
         host: Some("api.example.com".to_string()),
         host_resolution: Some(HostResolutionConfig {
@@ -118,14 +181,35 @@ async fn test_tenant_config_supports_route() {
             trusted_proxies: Some(vec!["192.168.1.0/24".to_string()]),
         }),
         override_base_url: Some("https://api.example.com".to_string()),
+        ref_base_url: None,
         custom_endpoints: vec![CustomEndpoint {
             path: "/custom/health".to_string(),
             response: json!({"status": "healthy"}).to_string(),
             status_code: 200,
             content_type: "application/json".to_string(),
+            method: None,
+            response_file: None,
+            response_base64: None,
+            cache_response_file: false,
             auth: None,
         }],
         compatibility: None,
+        read_only: Default::default(),
+        require_if_match: false,
+        canonical_values: Default::default(),
+        validate_formats: Default::default(),
+        external_id_case_insensitive: false,
+        accept_password: true,
+        auth_lockout: None,
+        ndjson_export: false,
+        expose_provenance: false,
+        member_batch: Default::default(),
+        effective_members: Default::default(),
+        service_provider: Default::default(),
+        password_algorithm: None,
+        users_path: None,
+        groups_path: None,
+        enabled_resources: None,
     };
 
     // Verify that host resolution configuration is properly structured
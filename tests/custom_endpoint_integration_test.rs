@@ -14,16 +14,31 @@ async fn test_custom_endpoint_basic_functionality() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -31,10 +46,13 @@ async fn test_custom_endpoint_basic_functionality() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![
                 CustomEndpoint {
                     path: "/api/status".to_string(),
@@ -45,6 +63,10 @@ async fn test_custom_endpoint_basic_functionality() {
                     .to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None, // Inherit tenant's auth config
                 },
                 CustomEndpoint {
@@ -56,10 +78,30 @@ async fn test_custom_endpoint_basic_functionality() {
                     .to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 },
             ],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -100,16 +142,31 @@ async fn test_custom_endpoint_with_route() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![
             // Single tenant with host resolution enabled
             TenantConfig {
@@ -119,6 +176,8 @@ async fn test_custom_endpoint_with_route() {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: Some("tenant1.example.com".to_string()),
                 host_resolution: Some(HostResolutionConfig {
@@ -126,6 +185,7 @@ async fn test_custom_endpoint_with_route() {
                     trusted_proxies: None,
                 }),
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![CustomEndpoint {
                     path: "/api/tenant-info".to_string(),
                     response: json!({
@@ -136,9 +196,29 @@ async fn test_custom_endpoint_with_route() {
                     .to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 }],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
         ],
     };
@@ -182,16 +262,31 @@ async fn test_custom_endpoint_with_authentication() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -199,10 +294,13 @@ async fn test_custom_endpoint_with_authentication() {
                 auth_type: "bearer".to_string(),
                 token: Some("secret-token-123".to_string()),
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/api/protected".to_string(),
                 response: json!({
@@ -212,9 +310,29 @@ async fn test_custom_endpoint_with_authentication() {
                 .to_string(),
                 status_code: 200,
                 content_type: "application/json".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: None,
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -252,16 +370,31 @@ async fn test_custom_endpoint_different_content_types() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -269,16 +402,23 @@ async fn test_custom_endpoint_different_content_types() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![
                 CustomEndpoint {
                     path: "/api/json".to_string(),
                     response: json!({"type": "json"}).to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 },
                 CustomEndpoint {
@@ -286,6 +426,10 @@ async fn test_custom_endpoint_different_content_types() {
                     response: "This is plain text".to_string(),
                     status_code: 200,
                     content_type: "text/plain".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 },
                 CustomEndpoint {
@@ -293,10 +437,30 @@ async fn test_custom_endpoint_different_content_types() {
                     response: "<response><status>ok</status></response>".to_string(),
                     status_code: 200,
                     content_type: "application/xml".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 },
             ],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -330,16 +494,31 @@ async fn test_custom_endpoint_priority_over_scim() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![TenantConfig {
             id: 1,
             path: "/scim/v2".to_string(),
@@ -347,10 +526,13 @@ async fn test_custom_endpoint_priority_over_scim() {
                 auth_type: "unauthenticated".to_string(),
                 token: None,
                 basic: None,
+                basic_users: Vec::new(),
+                tokens: Vec::new(),
             },
             host: None,
             host_resolution: None,
             override_base_url: None,
+            ref_base_url: None,
             custom_endpoints: vec![CustomEndpoint {
                 path: "/scim/v2/custom-override".to_string(),
                 response: json!({
@@ -360,9 +542,29 @@ async fn test_custom_endpoint_priority_over_scim() {
                 .to_string(),
                 status_code: 200,
                 content_type: "application/json".to_string(),
+                method: None,
+                response_file: None,
+                response_base64: None,
+                cache_response_file: false,
                 auth: None,
             }],
             compatibility: None,
+            read_only: Default::default(),
+            require_if_match: false,
+            canonical_values: Default::default(),
+            validate_formats: Default::default(),
+            external_id_case_insensitive: false,
+            accept_password: true,
+            auth_lockout: None,
+            ndjson_export: false,
+            expose_provenance: false,
+            member_batch: Default::default(),
+            effective_members: Default::default(),
+            service_provider: Default::default(),
+            password_algorithm: None,
+            users_path: None,
+            groups_path: None,
+            enabled_resources: None,
         }],
     };
 
@@ -391,16 +593,31 @@ async fn test_multiple_tenants_different_custom_endpoint_paths() {
         server: ServerConfig {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            log_format: "text".to_string(),
+            log_level: "info".to_string(),
+            expose_internal_errors: false,
+            admin_token: None,
+            custom_endpoint_max_response_bytes: 64 * 1024,
+            max_tenants: None,
+            share_link_secret: None,
+            maintenance_endpoints: false,
+            compression: Default::default(),
+            stats: Default::default(),
         },
+        password: Default::default(),
         backend: BackendConfig {
             backend_type: "database".to_string(),
+            event_sourced: None,
             database: Some(DatabaseConfig {
                 db_type: "sqlite".to_string(),
                 url: ":memory:".to_string(),
                 max_connections: 1,
+                sqlite: Default::default(),
+                postgres: Default::default(),
             }),
         },
         compatibility: CompatibilityConfig::default(),
+        read_only: Default::default(),
         tenants: vec![
             TenantConfig {
                 id: 1,
@@ -409,18 +626,41 @@ async fn test_multiple_tenants_different_custom_endpoint_paths() {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![CustomEndpoint {
                     path: "/api/tenant1/status".to_string(),
                     response: json!({"tenant": 1, "status": "active"}).to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 }],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
             TenantConfig {
                 id: 2,
@@ -429,18 +669,41 @@ async fn test_multiple_tenants_different_custom_endpoint_paths() {
                     auth_type: "unauthenticated".to_string(),
                     token: None,
                     basic: None,
+                    basic_users: Vec::new(),
+                    tokens: Vec::new(),
                 },
                 host: None,
                 host_resolution: None,
                 override_base_url: None,
+                ref_base_url: None,
                 custom_endpoints: vec![CustomEndpoint {
                     path: "/api/tenant2/status".to_string(),
                     response: json!({"tenant": 2, "status": "running"}).to_string(),
                     status_code: 200,
                     content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
                     auth: None,
                 }],
                 compatibility: None,
+                read_only: Default::default(),
+                require_if_match: false,
+                canonical_values: Default::default(),
+                validate_formats: Default::default(),
+                external_id_case_insensitive: false,
+                accept_password: true,
+                auth_lockout: None,
+                ndjson_export: false,
+                expose_provenance: false,
+                member_batch: Default::default(),
+                effective_members: Default::default(),
+                service_provider: Default::default(),
+                password_algorithm: None,
+                users_path: None,
+                groups_path: None,
+                enabled_resources: None,
             },
         ],
     };
@@ -460,3 +723,157 @@ async fn test_multiple_tenants_different_custom_endpoint_paths() {
     response.assert_status_ok();
     response.assert_json(&json!({"tenant": 2, "status": "running"}));
 }
+
+#[tokio::test]
+async fn test_custom_endpoint_wrong_method_is_405() {
+    let server = common::test_server(|app_config| {
+        app_config.tenants[0].custom_endpoints.push(CustomEndpoint {
+            path: "/api/status".to_string(),
+            response: json!({"status": "healthy"}).to_string(),
+            status_code: 200,
+            content_type: "application/json".to_string(),
+            method: None,
+            response_file: None,
+            response_base64: None,
+            cache_response_file: false,
+            auth: None,
+        });
+    })
+    .await;
+
+    server.get("/api/status").await.assert_status_ok();
+
+    for response in [
+        server.post("/api/status").await,
+        server.put("/api/status").await,
+        server.patch("/api/status").await,
+        server.delete("/api/status").await,
+    ] {
+        response.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.header("allow"), "GET,HEAD");
+    }
+
+    // Unknown paths are still 404
+    server
+        .post("/api/unknown")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_post_custom_endpoint() {
+    let server = common::test_server(|app_config| {
+        app_config.tenants[0].custom_endpoints.push(CustomEndpoint {
+            path: "/api/webhook".to_string(),
+            response: json!({"received": true}).to_string(),
+            status_code: 202,
+            content_type: "application/json".to_string(),
+            method: Some("POST".to_string()),
+            response_file: None,
+            response_base64: None,
+            cache_response_file: false,
+            auth: None,
+        });
+    })
+    .await;
+
+    // The body is accepted but does not change the configured response
+    let response = server
+        .post("/api/webhook")
+        .json(&json!({"event": "user.created"}))
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    response.assert_json(&json!({"received": true}));
+    server
+        .post("/api/webhook")
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+
+    for response in [
+        server.get("/api/webhook").await,
+        server.put("/api/webhook").await,
+    ] {
+        response.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.header("allow"), "POST");
+    }
+}
+
+#[tokio::test]
+async fn test_host_tenants_route_different_methods_on_one_path() {
+    let server = common::test_server(|app_config| {
+        for (tenant, host, method) in [(0, "a.example.com", "GET"), (1, "b.example.com", "POST")] {
+            app_config.tenants[tenant].host = Some(host.to_string());
+            app_config.tenants[tenant]
+                .custom_endpoints
+                .push(CustomEndpoint {
+                    path: "/api/hook".to_string(),
+                    response: json!({"host": host}).to_string(),
+                    status_code: 200,
+                    content_type: "application/json".to_string(),
+                    method: Some(method.to_string()),
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
+                    auth: None,
+                });
+        }
+        app_config.validate_custom_endpoints().unwrap();
+    })
+    .await;
+
+    let response = server
+        .get("/api/hook")
+        .add_header("Host", "a.example.com")
+        .await;
+    response.assert_status_ok();
+    response.assert_json(&json!({"host": "a.example.com"}));
+    let response = server
+        .post("/api/hook")
+        .add_header("Host", "b.example.com")
+        .await;
+    response.assert_status_ok();
+    response.assert_json(&json!({"host": "b.example.com"}));
+
+    // Each tenant only answers its own method
+    let response = server
+        .post("/api/hook")
+        .add_header("Host", "a.example.com")
+        .await;
+    response.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.header("allow"), "GET");
+    server
+        .get("/api/hook")
+        .add_header("Host", "b.example.com")
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn test_host_disambiguated_tenants_share_custom_endpoint_path() {
+    let server = common::test_server(|app_config| {
+        for (tenant, host) in [(0, "a.example.com"), (1, "b.example.com")] {
+            app_config.tenants[tenant].host = Some(host.to_string());
+            app_config.tenants[tenant]
+                .custom_endpoints
+                .push(CustomEndpoint {
+                    path: "/api/info".to_string(),
+                    response: json!({"host": host}).to_string(),
+                    status_code: 200,
+                    content_type: "application/json".to_string(),
+                    method: None,
+                    response_file: None,
+                    response_base64: None,
+                    cache_response_file: false,
+                    auth: None,
+                });
+        }
+        app_config.validate_custom_endpoints().unwrap();
+    })
+    .await;
+
+    for host in ["a.example.com", "b.example.com"] {
+        let response = server.get("/api/info").add_header("Host", host).await;
+        response.assert_status_ok();
+        response.assert_json(&json!({"host": host}));
+    }
+}
@@ -0,0 +1,98 @@
+/// `filter=` and blank filters list every resource
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    let server = common::test_server(|_| {}).await;
+
+    for user_name in ["blank.alice", "blank.bob", "blank.carol"] {
+        server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(user_name, "Blank", "Filter"))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+    server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Blank Filter Group"
+        }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    server
+}
+
+async fn list(server: &TestServer, path_and_query: &str) -> Value {
+    let response = server.get(path_and_query).await;
+    assert_eq!(
+        response.status_code(),
+        StatusCode::OK,
+        "{}: {}",
+        path_and_query,
+        response.text()
+    );
+    response.json()
+}
+
+#[tokio::test]
+async fn test_empty_filter_lists_all_users() {
+    let server = setup_server().await;
+
+    for query in ["filter=", "filter=%20", "filter=%20%09%20"] {
+        let body = list(&server, &format!("/scim/v2/Users?{}", query)).await;
+        assert_eq!(body["totalResults"], 3, "{}", query);
+        assert_eq!(body["Resources"].as_array().unwrap().len(), 3, "{}", query);
+    }
+
+    // Still paginated
+    let body = list(&server, "/scim/v2/Users?filter=&startIndex=2&count=1").await;
+    assert_eq!(body["totalResults"], 3);
+    assert_eq!(body["startIndex"], 2);
+    assert_eq!(body["itemsPerPage"], 1);
+    assert_eq!(body["Resources"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_empty_filter_lists_all_groups() {
+    let server = setup_server().await;
+
+    for query in ["filter=", "filter=%20"] {
+        let body = list(&server, &format!("/scim/v2/Groups?{}", query)).await;
+        assert_eq!(body["totalResults"], 1, "{}", query);
+        assert_eq!(
+            body["Resources"][0]["displayName"], "Blank Filter Group",
+            "{}",
+            query
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_blank_search_request_filter_searches_everything() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/scim/v2/.search")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:SearchRequest"],
+            "filter": " "
+        }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["totalResults"], 4);
+}
+
+#[tokio::test]
+async fn test_malformed_filter_is_still_rejected() {
+    let server = setup_server().await;
+
+    server
+        .get("/scim/v2/Users?filter=userName%20eq")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,128 @@
+/// Tenants serving only some of the SCIM resource types
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::parser::ResourceType;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        // Tenant 3 (/scim/v2) only provisions Users; tenant 1 serves both
+        app_config.tenants[2].enabled_resources = Some(vec![ResourceType::User]);
+    })
+    .await
+}
+
+fn group_json(name: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+        "displayName": name
+    })
+}
+
+fn assert_not_implemented(response: &axum_test::TestResponse) {
+    response.assert_status(StatusCode::NOT_IMPLEMENTED);
+    let body: Value = response.json();
+    assert_eq!(
+        body["schemas"][0],
+        "urn:ietf:params:scim:api:messages:2.0:Error"
+    );
+    assert_eq!(body["status"], "501");
+}
+
+#[tokio::test]
+async fn test_disabled_groups_answer_not_implemented() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&group_json("Junk"))
+        .await;
+    assert_not_implemented(&response);
+    assert_not_implemented(&server.get("/scim/v2/Groups").await);
+    assert_not_implemented(&server.get("/scim/v2/Groups/some-id").await);
+    assert_not_implemented(&server.delete("/scim/v2/Groups/some-id").await);
+
+    let response = server.get("/scim/v2/ResourceTypes").await;
+    response.assert_status_ok();
+    let resource_types: Value = response.json();
+    assert_eq!(resource_types["totalResults"], 1);
+    assert_eq!(resource_types["Resources"][0]["id"], "User");
+
+    let response = server.get("/scim/v2/Schemas").await;
+    response.assert_status_ok();
+    let schema_ids: Vec<String> = response.json::<Value>()["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|schema| schema["id"].as_str().unwrap().to_string())
+        .collect();
+    assert!(schema_ids.contains(&"urn:ietf:params:scim:schemas:core:2.0:User".to_string()));
+    assert!(!schema_ids.contains(&"urn:ietf:params:scim:schemas:core:2.0:Group".to_string()));
+
+    // Users work as usual, without the groups attribute
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json("only.user", "Only", "User"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user: Value = response.json();
+    assert!(user.get("groups").is_none(), "{}", user);
+    let response = server
+        .get(&format!("/scim/v2/Users/{}", user["id"].as_str().unwrap()))
+        .await;
+    response.assert_status_ok();
+    assert!(response.json::<Value>().get("groups").is_none());
+
+    // Root search only covers the enabled type
+    let response = server
+        .post("/scim/v2/.search")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:SearchRequest"]
+        }))
+        .await;
+    response.assert_status_ok();
+    let results: Value = response.json();
+    assert_eq!(results["totalResults"], 1);
+    assert_eq!(results["Resources"][0]["meta"]["resourceType"], "User");
+}
+
+#[tokio::test]
+async fn test_other_tenants_serve_every_resource_type() {
+    let server = setup_server().await;
+
+    server
+        .post("/tenant-a/scim/v2/Groups")
+        .json(&group_json("Real Group"))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = server.get("/tenant-a/scim/v2/ResourceTypes").await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["totalResults"], 2);
+}
+
+#[tokio::test]
+async fn test_disabled_users() {
+    let server = common::test_server(|app_config| {
+        app_config.tenants[2].enabled_resources = Some(vec![ResourceType::Group]);
+    })
+    .await;
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json("no.user", "No", "User"))
+        .await;
+    assert_not_implemented(&response);
+    server
+        .post("/scim/v2/Groups")
+        .json(&group_json("Groups Only"))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = server.get("/scim/v2/ResourceTypes").await;
+    let resource_types: Value = response.json();
+    assert_eq!(resource_types["totalResults"], 1);
+    assert_eq!(resource_types["Resources"][0]["id"], "Group");
+}
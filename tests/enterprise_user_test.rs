@@ -244,7 +244,8 @@ async fn test_email_validation() {
 
     response.assert_status(StatusCode::BAD_REQUEST);
     let error: Value = response.json();
-    assert!(error["error"]
+    assert_eq!(error["scimType"], "invalidValue");
+    assert!(error["detail"]
         .as_str()
         .unwrap()
         .contains("Invalid email format"));
@@ -340,7 +341,8 @@ async fn test_locale_timezone_validation() {
 
     response.assert_status(StatusCode::BAD_REQUEST);
     let error: Value = response.json();
-    assert!(error["error"]
+    assert_eq!(error["scimType"], "invalidValue");
+    assert!(error["detail"]
         .as_str()
         .unwrap()
         .contains("Invalid locale format"));
@@ -360,7 +362,8 @@ async fn test_locale_timezone_validation() {
 
     response.assert_status(StatusCode::BAD_REQUEST);
     let error: Value = response.json();
-    assert!(error["error"]
+    assert_eq!(error["scimType"], "invalidValue");
+    assert!(error["detail"]
         .as_str()
         .unwrap()
         .contains("Invalid timezone format"));
@@ -110,3 +110,44 @@ async fn test_malformed_json() {
             || response.status_code() == StatusCode::UNSUPPORTED_MEDIA_TYPE
     );
 }
+
+#[tokio::test]
+async fn test_unbalanced_filter_is_invalid_filter() {
+    let tenant_config = common::create_test_app_config();
+    let app = common::setup_test_app(tenant_config).await.unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    for (path, filter, detail) in [
+        (
+            "/tenant-a/scim/v2/Users",
+            "userName eq \"alice",
+            "Unterminated quoted string starting at position 13",
+        ),
+        (
+            "/tenant-a/scim/v2/Users",
+            "emails[value eq \"x\"",
+            "Unclosed '[' at position 7",
+        ),
+        (
+            "/tenant-a/scim/v2/Groups",
+            "displayName eq \"a\")",
+            "Unexpected ')' at position 19",
+        ),
+    ] {
+        let response = server.get(path).add_query_param("filter", filter).await;
+        assert_eq!(
+            response.status_code(),
+            StatusCode::BAD_REQUEST,
+            "{}",
+            filter
+        );
+        let json: serde_json::Value = response.json();
+        assert_eq!(json["scimType"], "invalidFilter", "{}", filter);
+        assert!(
+            json["detail"].as_str().unwrap().contains(detail),
+            "{}: {}",
+            filter,
+            json["detail"]
+        );
+    }
+}
@@ -432,3 +432,167 @@ async fn test_group_version_functionality() {
     let created_group: serde_json::Value = create_response.json();
     assert_eq!(created_group["meta"]["version"], "W/\"1\"");
 }
+
+// require_if_match tenant option
+async fn require_if_match_server() -> TestServer {
+    common::test_server(|app_config| {
+        for tenant in app_config.tenants.iter_mut() {
+            tenant.require_if_match = tenant.path == "/scim/v2";
+        }
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_require_if_match_rejects_missing_header() {
+    let server = require_if_match_server().await;
+
+    // Creates are not conditional and stay allowed
+    let create_response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "strictuser"
+        }))
+        .await;
+    assert_eq!(create_response.status_code(), StatusCode::CREATED);
+    let user_id = create_response.json::<serde_json::Value>()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let group_response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Strict Group"
+        }))
+        .await;
+    assert_eq!(group_response.status_code(), StatusCode::CREATED);
+    let group_id = group_response.json::<serde_json::Value>()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let patch_payload = json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "displayName", "value": "Renamed"}]
+    });
+
+    let responses = vec![
+        server
+            .put(&format!("/scim/v2/Users/{}", user_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": "strictuser"
+            }))
+            .await,
+        server
+            .patch(&format!("/scim/v2/Users/{}", user_id))
+            .json(&patch_payload)
+            .await,
+        server.delete(&format!("/scim/v2/Users/{}", user_id)).await,
+        server
+            .put(&format!("/scim/v2/Groups/{}", group_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+                "displayName": "Strict Group"
+            }))
+            .await,
+        server
+            .patch(&format!("/scim/v2/Groups/{}", group_id))
+            .json(&patch_payload)
+            .await,
+        server
+            .delete(&format!("/scim/v2/Groups/{}", group_id))
+            .await,
+    ];
+
+    for response in responses {
+        assert_eq!(response.status_code(), StatusCode::PRECONDITION_REQUIRED);
+        let error: serde_json::Value = response.json();
+        assert_eq!(
+            error["schemas"][0],
+            "urn:ietf:params:scim:api:messages:2.0:Error"
+        );
+        assert_eq!(error["status"], "428");
+    }
+
+    // Nothing was changed or removed
+    let user: serde_json::Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+    assert_eq!(user["meta"]["version"], "W/\"1\"");
+    let group: serde_json::Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_eq!(group["displayName"], "Strict Group");
+}
+
+#[tokio::test]
+async fn test_require_if_match_accepts_present_header() {
+    let server = require_if_match_server().await;
+
+    let create_response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "strictuser"
+        }))
+        .await;
+    let etag = extract_etag(&create_response).unwrap();
+    let user_id = create_response.json::<serde_json::Value>()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let patch_response = server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .add_header("If-Match", &etag)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "displayName", "value": "Strict User"}]
+        }))
+        .await;
+    assert_eq!(patch_response.status_code(), StatusCode::OK);
+    let etag = extract_etag(&patch_response).unwrap();
+
+    // A stale version still fails with 412, not 428
+    let stale_response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .add_header("If-Match", "W/\"1\"")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "strictuser"
+        }))
+        .await;
+    assert_eq!(
+        stale_response.status_code(),
+        StatusCode::PRECONDITION_FAILED
+    );
+
+    let delete_response = server
+        .delete(&format!("/scim/v2/Users/{}", user_id))
+        .add_header("If-Match", &etag)
+        .await;
+    assert_eq!(delete_response.status_code(), StatusCode::NO_CONTENT);
+
+    // Tenants without the option keep accepting unconditional writes
+    let other_response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "lenientuser"
+        }))
+        .await;
+    let other_id = other_response.json::<serde_json::Value>()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let delete_response = server
+        .delete(&format!("/tenant-a/scim/v2/Users/{}", other_id))
+        .await;
+    assert_eq!(delete_response.status_code(), StatusCode::NO_CONTENT);
+}
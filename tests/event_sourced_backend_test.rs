@@ -0,0 +1,519 @@
+use async_trait::async_trait;
+use axum_test::TestServer;
+use chrono::{DateTime, Utc};
+use http::StatusCode;
+use scim_server::backend::database::sqlite::SqliteBackend;
+use scim_server::backend::database::DbTransaction;
+use scim_server::backend::event_sourced::sqlite::SqliteEventStore;
+use scim_server::backend::event_sourced::{
+    Event, EventOperation, EventSourcedBackend, EventStore, NewEvent, Snapshot,
+};
+use scim_server::backend::{Backend, GroupBackend, HistoryBackend, UserBackend};
+use scim_server::error::{AppError, AppResult};
+use scim_server::models::{Group, User};
+use scim_server::parser::ResourceType;
+use serde_json::{json, Value};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+mod common;
+
+const BASE: &str = "/scim/v2";
+const TOKEN: &str = "event-token";
+const ADMIN_TOKEN: &str = "admin-token";
+
+async fn memory_pool() -> SqlitePool {
+    // A single connection keeps the tables and the event log in one in-memory database
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
+
+async fn setup(snapshot_every: u32) -> (TestServer, Arc<EventSourcedBackend>, SqlitePool) {
+    let pool = memory_pool().await;
+    let backend = Arc::new(
+        EventSourcedBackend::new(
+            Box::new(SqliteBackend::new(pool.clone())),
+            Box::new(SqliteEventStore::new(pool.clone())),
+        )
+        .with_snapshot_every(snapshot_every),
+    );
+    for tenant_id in [1, 2, 3] {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+
+    let mut app_config = common::create_test_app_config();
+    app_config.server.admin_token = Some(ADMIN_TOKEN.to_string());
+    app_config.tenants[2].auth.auth_type = "bearer".to_string();
+    app_config.tenants[2].auth.token = Some(TOKEN.to_string());
+    let app = common::setup_test_app_with_backend(app_config, backend.clone())
+        .await
+        .unwrap();
+    (TestServer::new(app).unwrap(), backend, pool)
+}
+
+fn auth() -> String {
+    format!("Bearer {}", TOKEN)
+}
+
+async fn create(server: &TestServer, endpoint: &str, body: Value) -> String {
+    let response = server
+        .post(&format!("{}/{}", BASE, endpoint))
+        .add_header("Authorization", auth())
+        .json(&body)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+fn replace_display_name(value: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "displayName", "value": value}]
+    })
+}
+
+#[tokio::test]
+async fn test_user_lifecycle_is_logged() {
+    let (server, backend, _pool) = setup(100).await;
+
+    let user_id = create(
+        &server,
+        "Users",
+        common::create_test_user_json("es.user", "Event", "Sourced"),
+    )
+    .await;
+    let path = format!("{}/Users/{}", BASE, user_id);
+    server
+        .patch(&path)
+        .add_header("Authorization", auth())
+        .json(&replace_display_name("Patched"))
+        .await
+        .assert_status(StatusCode::OK);
+    server
+        .put(&path)
+        .add_header("Authorization", auth())
+        .json(&common::create_test_user_json(
+            "es.renamed",
+            "Event",
+            "Sourced",
+        ))
+        .await
+        .assert_status(StatusCode::OK);
+
+    // Before the delete, replaying the log reproduces the stored user
+    let current = backend
+        .resource_at(3, ResourceType::User, &user_id, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current["userName"], "es.renamed");
+
+    server
+        .delete(&path)
+        .add_header("Authorization", auth())
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let history = backend
+        .history(3, ResourceType::User, &user_id)
+        .await
+        .unwrap();
+    let operations: Vec<EventOperation> = history.iter().map(|e| e.operation).collect();
+    assert_eq!(
+        operations,
+        vec![
+            EventOperation::Create,
+            EventOperation::Patch,
+            EventOperation::Replace,
+            EventOperation::Delete
+        ]
+    );
+    assert!(history.windows(2).all(|w| w[0].event_id < w[1].event_id));
+    assert_eq!(history[0].payload.as_ref().unwrap()["userName"], "es.user");
+    assert_eq!(
+        history[1].payload.as_ref().unwrap()["displayName"],
+        "Patched"
+    );
+    assert_eq!(history[3].payload, None);
+    // The authenticated principal is recorded on every event
+    assert!(history
+        .iter()
+        .all(|e| e.actor.as_deref() == Some("bearer") && e.tenant_id == 3));
+
+    // Time travel: the state right after each event
+    let after_patch = backend
+        .resource_at(3, ResourceType::User, &user_id, Some(history[1].timestamp))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(after_patch["userName"], "es.user");
+    assert_eq!(after_patch["displayName"], "Patched");
+    assert_eq!(
+        backend
+            .resource_at(3, ResourceType::User, &user_id, None)
+            .await
+            .unwrap(),
+        None
+    );
+
+    // Other tenants' logs are separate
+    assert!(backend
+        .history(1, ResourceType::User, &user_id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_membership_changes_are_logged_on_the_group() {
+    let (server, backend, _pool) = setup(100).await;
+
+    let user_id = create(
+        &server,
+        "Users",
+        common::create_test_user_json("es.member", "Group", "Member"),
+    )
+    .await;
+    let group_id = create(
+        &server,
+        "Groups",
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Event Group",
+            "members": [{"value": user_id, "type": "User"}]
+        }),
+    )
+    .await;
+
+    // Deleting the member removes it from the group, which is an event of the group too
+    server
+        .delete(&format!("{}/Users/{}", BASE, user_id))
+        .add_header("Authorization", auth())
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let history = backend
+        .history(3, ResourceType::Group, &group_id)
+        .await
+        .unwrap();
+    let operations: Vec<EventOperation> = history.iter().map(|e| e.operation).collect();
+    assert_eq!(
+        operations,
+        vec![EventOperation::Create, EventOperation::RemoveMembers]
+    );
+    assert_eq!(
+        history[0].payload.as_ref().unwrap()["members"][0]["value"],
+        json!(user_id)
+    );
+    let members = &history[1].payload.as_ref().unwrap()["members"];
+    assert!(members.is_null() || members.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_snapshots_every_n_events() {
+    let (server, backend, pool) = setup(2).await;
+
+    let group_id = create(
+        &server,
+        "Groups",
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Snapshot 0"
+        }),
+    )
+    .await;
+    for i in 1..=4 {
+        server
+            .patch(&format!("{}/Groups/{}", BASE, group_id))
+            .add_header("Authorization", auth())
+            .json(&replace_display_name(&format!("Snapshot {}", i)))
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    // Five events: snapshots after the second and the fourth
+    let snapshots: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT event_id, state FROM snapshots WHERE resource_id = ?1 ORDER BY event_id",
+    )
+    .bind(&group_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let history = backend
+        .history(3, ResourceType::Group, &group_id)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 5);
+    assert_eq!(
+        snapshots.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        vec![history[1].event_id, history[3].event_id]
+    );
+    let state: Value = serde_json::from_str(&snapshots[1].1).unwrap();
+    assert_eq!(state["displayName"], "Snapshot 3");
+
+    // Replays starting from a snapshot agree with the log
+    let current = backend
+        .resource_at(3, ResourceType::Group, &group_id, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current["displayName"], "Snapshot 4");
+    let at_third = backend
+        .resource_at(
+            3,
+            ResourceType::Group,
+            &group_id,
+            Some(history[2].timestamp),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(at_third["displayName"], "Snapshot 2");
+}
+
+async fn admin_get(server: &TestServer, path: &str) -> axum_test::TestResponse {
+    server
+        .get(path)
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await
+}
+
+#[tokio::test]
+async fn test_history_endpoint() {
+    let (server, _backend, _pool) = setup(2).await;
+
+    let user_id = create(
+        &server,
+        "Users",
+        common::create_test_user_json("es.history", "Event", "History"),
+    )
+    .await;
+    for display_name in ["First", "Second"] {
+        server
+            .patch(&format!("{}/Users/{}", BASE, user_id))
+            .add_header("Authorization", auth())
+            .json(&replace_display_name(display_name))
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    let path = format!("/admin/tenants/3/history/User/{}", user_id);
+    let response = admin_get(&server, &path).await;
+    response.assert_status(StatusCode::OK);
+    let events = response.json::<Value>()["events"]
+        .as_array()
+        .unwrap()
+        .clone();
+    let operations: Vec<&str> = events
+        .iter()
+        .map(|e| e["operation"].as_str().unwrap())
+        .collect();
+    assert_eq!(operations, vec!["create", "patch", "patch"]);
+    assert!(events.iter().all(|e| e["actor"] == "bearer"));
+    assert_eq!(events[2]["resource"]["displayName"], "Second");
+
+    // An event's timestamp passed back as `at` replays the state right after
+    // it, past the snapshot taken at the second event
+    let response = server
+        .get(&path)
+        .add_query_param("at", events[0]["timestamp"].as_str().unwrap())
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let at_create = response.json::<Value>();
+    assert_eq!(at_create["resource"]["userName"], "es.history");
+    assert!(at_create["resource"]["displayName"].is_null());
+
+    let response = server
+        .get(&path)
+        .add_query_param("at", "2000-01-01T00:00:00Z")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.json::<Value>()["resource"].is_null());
+
+    admin_get(&server, "/admin/tenants/3/history/User/unknown")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    admin_get(
+        &server,
+        &format!("/admin/tenants/99/history/User/{}", user_id),
+    )
+    .await
+    .assert_status(StatusCode::NOT_FOUND);
+    admin_get(
+        &server,
+        &format!("/admin/tenants/3/history/Users/{}", user_id),
+    )
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .get(&path)
+        .add_query_param("at", "yesterday")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    // Tenant credentials do not open admin routes
+    server
+        .get(&path)
+        .add_header("Authorization", auth())
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_history_endpoint_without_event_log() {
+    let server = common::test_server(|app_config| {
+        app_config.server.admin_token = Some(ADMIN_TOKEN.to_string());
+    })
+    .await;
+
+    admin_get(&server, "/admin/tenants/3/history/User/any")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+/// Event store whose appends always fail
+struct FailingEventStore;
+
+#[async_trait]
+impl EventStore for FailingEventStore {
+    async fn init(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn append(&self, _tx: &mut DbTransaction, _event: NewEvent) -> AppResult<Event> {
+        Err(AppError::Database("event log unavailable".to_string()))
+    }
+
+    async fn events(
+        &self,
+        _tx: &mut DbTransaction,
+        _tenant_id: u32,
+        _resource_type: ResourceType,
+        _resource_id: &str,
+        _after_event_id: i64,
+        _until: Option<DateTime<Utc>>,
+    ) -> AppResult<Vec<Event>> {
+        Ok(Vec::new())
+    }
+
+    async fn latest_snapshot(
+        &self,
+        _tx: &mut DbTransaction,
+        _tenant_id: u32,
+        _resource_type: ResourceType,
+        _resource_id: &str,
+        _until: Option<DateTime<Utc>>,
+    ) -> AppResult<Option<Snapshot>> {
+        Ok(None)
+    }
+
+    async fn save_snapshot(&self, _tx: &mut DbTransaction, _snapshot: &Snapshot) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_failed_append_rolls_back_the_write() {
+    let pool = memory_pool().await;
+    let backend = EventSourcedBackend::new(
+        Box::new(SqliteBackend::new(pool.clone())),
+        Box::new(FailingEventStore),
+    );
+    backend.init_tenant(1).await.unwrap();
+
+    let mut user = User::default();
+    user.base.user_name = "es.unlogged".to_string();
+    assert!(backend.create_user(1, &user, Some("bearer")).await.is_err());
+
+    // No row without its event
+    assert!(backend
+        .find_user_by_username(1, "es.unlogged", false)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// A group as JSON, leaving out an empty member list like the log does
+fn without_empty_members(group: &Group) -> Value {
+    let mut value = serde_json::to_value(group).unwrap();
+    if value["members"].as_array().is_some_and(Vec::is_empty) {
+        value.as_object_mut().unwrap().remove("members");
+    }
+    value
+}
+
+#[tokio::test]
+async fn test_replaying_the_log_reproduces_the_tables() {
+    let (_server, backend, _pool) = setup(2).await;
+
+    let mut user = User::default();
+    user.base.user_name = "es.replayed".to_string();
+    let user = backend.create_user(3, &user, Some("bearer")).await.unwrap();
+    let user_id = user.id().clone().unwrap();
+
+    let mut group = Group::default();
+    group.base.display_name = "Replayed".to_string();
+    let group = backend
+        .create_group(3, &group, Some("bearer"))
+        .await
+        .unwrap();
+    let group_id = group.id().clone();
+    backend
+        .add_group_members(3, &group_id, std::slice::from_ref(&user_id), Some("bearer"))
+        .await
+        .unwrap()
+        .unwrap();
+    let mut renamed = backend
+        .find_group_by_id(3, &group_id)
+        .await
+        .unwrap()
+        .unwrap();
+    renamed.base.display_name = "Replayed Again".to_string();
+    backend
+        .update_group(3, &group_id, &renamed, Some("bearer"))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let stored = backend
+        .find_group_by_id(3, &group_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let replayed = backend
+        .resource_at(3, ResourceType::Group, &group_id, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(replayed, serde_json::to_value(&stored).unwrap());
+
+    // The cascaded membership removal is part of the user's delete
+    assert!(backend
+        .delete_user(3, &user_id, Some("bearer"))
+        .await
+        .unwrap());
+    let stored = backend
+        .find_group_by_id(3, &group_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let replayed = backend
+        .resource_at(3, ResourceType::Group, &group_id, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(replayed, without_empty_members(&stored));
+    assert_eq!(
+        backend
+            .resource_at(3, ResourceType::User, &user_id, None)
+            .await
+            .unwrap(),
+        None
+    );
+}
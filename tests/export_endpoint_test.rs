@@ -0,0 +1,158 @@
+/// `GET /Users/.export` and `GET /Groups/.export` NDJSON streams
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        // Tenant 3 (/scim/v2) enables the export endpoints; tenant 1 does not
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            export_endpoints: true,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+/// Parses an NDJSON body, asserting every line is a single JSON object
+fn parse_ndjson(body: &str) -> Vec<Value> {
+    assert!(body.ends_with('\n'), "body must end with a newline");
+    body.lines()
+        .map(|line| {
+            let value: Value = serde_json::from_str(line)
+                .unwrap_or_else(|e| panic!("invalid NDJSON line {:?}: {}", line, e));
+            assert!(value.is_object(), "line is not an object: {}", line);
+            value
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_users_export_streams_every_user() {
+    let server = setup_server().await;
+
+    let total = 2000;
+    for i in 0..total {
+        let mut user =
+            common::create_test_user_json(&format!("export.user{:04}", i), "Export", "User");
+        // Hashing is slow, so only some users carry a password
+        if i % 400 == 0 {
+            user["password"] = json!("Secret-Passw0rd!");
+        }
+        if i % 2 == 1 {
+            user["active"] = json!(false);
+        }
+        server
+            .post("/scim/v2/Users")
+            .json(&user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let response = server.get("/scim/v2/Users/.export").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+    assert!(response.headers().get("content-length").is_none());
+
+    let users = parse_ndjson(&response.text());
+    assert_eq!(users.len(), total);
+    let user_names: HashSet<&str> = users
+        .iter()
+        .map(|user| user["userName"].as_str().unwrap())
+        .collect();
+    assert_eq!(user_names.len(), total, "every user exported exactly once");
+    for user in &users {
+        assert!(user.get("password").is_none(), "{}", user);
+        assert!(user.get("Resources").is_none());
+        assert_eq!(user["meta"]["resourceType"], "User");
+    }
+
+    // filter and attribute selection narrow the stream
+    let response = server
+        .get("/scim/v2/Users/.export")
+        .add_query_param("filter", "active eq true")
+        .add_query_param("attributes", "userName")
+        .await;
+    response.assert_status(StatusCode::OK);
+    let users = parse_ndjson(&response.text());
+    assert_eq!(users.len(), total / 2);
+    for user in &users {
+        assert!(user.get("id").is_some());
+        assert!(user.get("userName").is_some());
+        assert!(user.get("name").is_none());
+        assert!(user.get("active").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_groups_export() {
+    let server = setup_server().await;
+
+    for name in ["Export A", "Export B", "Other C"] {
+        server
+            .post("/scim/v2/Groups")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+                "displayName": name
+            }))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let response = server.get("/scim/v2/Groups/.export").await;
+    response.assert_status(StatusCode::OK);
+    let groups = parse_ndjson(&response.text());
+    assert_eq!(groups.len(), 3);
+    assert!(groups.iter().all(|g| g["meta"]["resourceType"] == "Group"));
+
+    let response = server
+        .get("/scim/v2/Groups/.export")
+        .add_query_param("filter", "displayName sw \"Export\"")
+        .add_query_param("excludedAttributes", "meta")
+        .await;
+    response.assert_status(StatusCode::OK);
+    let groups = parse_ndjson(&response.text());
+    assert_eq!(groups.len(), 2);
+    assert!(groups.iter().all(|g| g.get("meta").is_none()));
+}
+
+#[tokio::test]
+async fn test_export_rejects_invalid_filter_before_streaming() {
+    let server = setup_server().await;
+
+    for filter in ["userName eq", "nonexistent eq \"x\""] {
+        let response = server
+            .get("/scim/v2/Users/.export")
+            .add_query_param("filter", filter)
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        assert_eq!(response.json::<Value>()["scimType"], "invalidFilter");
+    }
+}
+
+#[tokio::test]
+async fn test_export_accepts_ndjson_media_type() {
+    let server = setup_server().await;
+
+    server
+        .get("/scim/v2/Users/.export")
+        .add_header("accept", "application/x-ndjson")
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_export_endpoints_disabled_by_default() {
+    let server = setup_server().await;
+
+    for path in [
+        "/tenant-a/scim/v2/Users/.export",
+        "/tenant-a/scim/v2/Groups/.export",
+    ] {
+        server.get(path).await.assert_status(StatusCode::NOT_FOUND);
+    }
+}
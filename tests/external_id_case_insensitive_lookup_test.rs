@@ -0,0 +1,104 @@
+//! externalId lookups of tenants with `external_id_case_insensitive`
+//!
+//! The indexed column holds externalId as stored, including values written
+//! before the flag was set, so flagged tenants compare it lowercased.
+use scim_server::backend::database::DatabaseBackendConfig;
+use scim_server::backend::{BackendFactory, ScimBackend};
+use scim_server::models::{Group, User};
+use scim_server::parser::filter_parser::parse_filter;
+use std::sync::Arc;
+
+const CASE_INSENSITIVE: u32 = 1;
+const CASE_EXACT: u32 = 2;
+
+async fn setup_backend() -> Arc<dyn ScimBackend> {
+    let config = DatabaseBackendConfig::memory_sqlite()
+        .with_external_id_case_insensitive_tenants([CASE_INSENSITIVE]);
+    let backend = BackendFactory::create(&config).await.unwrap();
+    for tenant_id in [CASE_INSENSITIVE, CASE_EXACT] {
+        backend.init_tenant(tenant_id).await.unwrap();
+
+        // Stored with its original case, e.g. before the flag was set
+        let mut user = User::default();
+        user.base.user_name = "legacy.user".to_string();
+        user.external_id = Some("Legacy-ID".to_string());
+        backend.create_user(tenant_id, &user, None).await.unwrap();
+
+        let mut group = Group::default();
+        group.base.display_name = "Legacy Group".to_string();
+        group.external_id = Some("Legacy-Group".to_string());
+        backend.create_group(tenant_id, &group, None).await.unwrap();
+    }
+    backend
+}
+
+async fn count_users(backend: &Arc<dyn ScimBackend>, tenant_id: u32, filter: &str) -> i64 {
+    let filter = parse_filter(filter).unwrap();
+    let (_, total) = backend
+        .find_users_by_filter(tenant_id, &filter, None, None, None, false)
+        .await
+        .unwrap();
+    total
+}
+
+#[tokio::test]
+async fn test_case_insensitive_tenant_matches_any_case() {
+    let backend = setup_backend().await;
+
+    for filter in [
+        // Answered by the externalId lookup
+        r#"externalId eq "legacy-id""#,
+        // Compared with the column inside a larger filter
+        r#"externalId eq "legacy-id" and userName pr"#,
+        r#"externalId sw "legacy-""#,
+        r#"externalId co "cy-i""#,
+    ] {
+        assert_eq!(
+            count_users(&backend, CASE_INSENSITIVE, filter).await,
+            1,
+            "{}",
+            filter
+        );
+    }
+    assert_eq!(
+        count_users(&backend, CASE_INSENSITIVE, r#"externalId ne "legacy-id""#).await,
+        0
+    );
+
+    assert!(backend
+        .find_user_by_external_id(CASE_INSENSITIVE, "LEGACY-id", false)
+        .await
+        .unwrap()
+        .is_some());
+    assert!(backend
+        .find_group_by_external_id(CASE_INSENSITIVE, "legacy-group")
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_other_tenants_stay_case_exact() {
+    let backend = setup_backend().await;
+
+    assert_eq!(
+        count_users(&backend, CASE_EXACT, r#"externalId eq "Legacy-ID""#).await,
+        1
+    );
+    for filter in [
+        r#"externalId eq "legacy-id""#,
+        r#"externalId eq "legacy-id" and userName pr"#,
+    ] {
+        assert_eq!(
+            count_users(&backend, CASE_EXACT, filter).await,
+            0,
+            "{}",
+            filter
+        );
+    }
+    assert!(backend
+        .find_group_by_external_id(CASE_EXACT, "legacy-group")
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,79 @@
+/// Case sensitivity of `eq`, `ne`, `co`, `sw` and `ew` filters
+///
+/// `userName` is compared case-insensitively and `externalId`, which is
+/// `caseExact`, with the case given in the filter.
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    let server = common::test_server(|_| {}).await;
+
+    for (user_name, external_id) in [("Alice.Smith", "Ext-ABC-1"), ("bob.jones", "ext-abc-2")] {
+        let mut user = common::create_test_user_json(user_name, "Case", "User");
+        user["externalId"] = Value::String(external_id.to_string());
+        server
+            .post("/scim/v2/Users")
+            .json(&user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+    server
+}
+
+async fn matching_user_names(server: &TestServer, filter: &str) -> Vec<String> {
+    let response = server
+        .get("/scim/v2/Users")
+        .add_query_param("filter", filter)
+        .await;
+    response.assert_status_ok();
+    let mut names: Vec<String> = response.json::<Value>()["Resources"]
+        .as_array()
+        .map(|resources| {
+            resources
+                .iter()
+                .map(|r| r["userName"].as_str().unwrap().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn test_filter_case_sensitivity_matrix() {
+    let server = setup_server().await;
+    let alice = vec!["Alice.Smith"];
+    let bob = vec!["bob.jones"];
+    let none: Vec<&str> = Vec::new();
+
+    let cases = [
+        // Case-insensitive
+        (r#"userName eq "ALICE.smith""#, &alice),
+        (r#"userName ne "ALICE.SMITH""#, &bob),
+        (r#"userName co "SMITH""#, &alice),
+        (r#"userName sw "BOB""#, &bob),
+        (r#"userName ew "Jones""#, &bob),
+        // Case-exact
+        (r#"externalId eq "Ext-ABC-1""#, &alice),
+        (r#"externalId eq "ext-abc-1""#, &none),
+        (r#"externalId ne "Ext-ABC-1""#, &bob),
+        (r#"externalId co "ABC""#, &alice),
+        (r#"externalId co "abc""#, &bob),
+        (r#"externalId sw "Ext""#, &alice),
+        (r#"externalId sw "EXT""#, &none),
+        (r#"externalId ew "ABC-1""#, &alice),
+        (r#"externalId ew "abc-1""#, &none),
+    ];
+
+    for (filter, expected) in cases {
+        assert_eq!(
+            &matching_user_names(&server, filter).await,
+            expected,
+            "{}",
+            filter
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server(user_names: &[&str]) -> TestServer {
+    let server = common::test_server(|_| {}).await;
+
+    for user_name in user_names {
+        let response = server
+            .post("/tenant-a/scim/v2/Users")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": user_name
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+
+    server
+}
+
+/// userNames matched by a percent-encoded filter query
+async fn search(server: &TestServer, encoded_filter: &str) -> Vec<String> {
+    let response = server
+        .get(&format!(
+            "/tenant-a/scim/v2/Users?filter={}",
+            encoded_filter
+        ))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK, "{}", encoded_filter);
+    let body: Value = response.json();
+    body["Resources"]
+        .as_array()
+        .map(|resources| {
+            resources
+                .iter()
+                .map(|user| user["userName"].as_str().unwrap().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_percent_encoded_filter_values() {
+    let server = setup_server(&[
+        "alice@example.com",
+        "alice%40example.com",
+        "alice+tag@example.com",
+        "Team (A) \"ops\"",
+    ])
+    .await;
+
+    assert_eq!(
+        search(&server, "userName%20eq%20%22alice%40example.com%22").await,
+        ["alice@example.com"]
+    );
+    // Double-encoded values are decoded once, leaving a literal %40
+    assert_eq!(
+        search(&server, "userName%20eq%20%22alice%2540example.com%22").await,
+        ["alice%40example.com"]
+    );
+    assert_eq!(
+        search(&server, "userName%20eq%20%22alice%2Btag%40example.com%22").await,
+        ["alice+tag@example.com"]
+    );
+    assert_eq!(
+        search(
+            &server,
+            "userName%20eq%20%22Team%20(A)%20%5C%22ops%5C%22%22"
+        )
+        .await,
+        ["Team (A) \"ops\""]
+    );
+    assert_eq!(
+        search(
+            &server,
+            "(userName%20sw%20%22Team%20(%22)%20or%20userName%20eq%20%22nobody%22"
+        )
+        .await,
+        ["Team (A) \"ops\""]
+    );
+}
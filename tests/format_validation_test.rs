@@ -0,0 +1,108 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::{EmailFormat, PhoneNumberFormat};
+use serde_json::{json, Value};
+
+mod common;
+
+fn user_with_phone(user_name: &str, phone: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": user_name,
+        "phoneNumbers": [{"value": phone, "type": "work"}]
+    })
+}
+
+fn assert_invalid_value(response: &axum_test::TestResponse, attribute: &str) {
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+    assert!(
+        body["detail"].as_str().unwrap().contains(attribute),
+        "{}",
+        body
+    );
+}
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        // Tenant 1 (/tenant-a) validates formats; tenant 3 (/scim/v2) keeps the defaults
+        app_config.tenants[0].validate_formats.emails = EmailFormat::Strict;
+        app_config.tenants[0].validate_formats.phone_numbers = PhoneNumberFormat::E164;
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_phone_numbers_are_normalized_to_e164() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&user_with_phone("alice", "tel:+1-201-555-0123"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user: Value = response.json();
+    assert_eq!(user["phoneNumbers"][0]["value"], "+12015550123");
+    let user_id = user["id"].as_str().unwrap();
+
+    let response = server
+        .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "replace",
+                "path": "phoneNumbers[type eq \"work\"].value",
+                "value": "+44 20 7946 0958"
+            }]
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let user: Value = response.json();
+    assert_eq!(user["phoneNumbers"][0]["value"], "+442079460958");
+}
+
+#[tokio::test]
+async fn test_malformed_values_are_rejected() {
+    let server = setup_server().await;
+
+    assert_invalid_value(
+        &server
+            .post("/tenant-a/scim/v2/Users")
+            .json(&user_with_phone("bob", "555-CALL-NOW"))
+            .await,
+        "phoneNumbers[0].value",
+    );
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&user_with_phone("bob", "+1 201 555 0123"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user: Value = response.json();
+    let user_id = user["id"].as_str().unwrap();
+
+    assert_invalid_value(
+        &server
+            .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{
+                    "op": "add",
+                    "path": "emails",
+                    "value": [{"value": "bob@example.com"}, {"value": "not-an-email"}]
+                }]
+            }))
+            .await,
+        "emails[1].value",
+    );
+
+    // Tenants without validate_formats keep accepting any phone number
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&user_with_phone("carol", "555-CALL-NOW"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user: Value = response.json();
+    assert_eq!(user["phoneNumbers"][0]["value"], "555-CALL-NOW");
+}
@@ -0,0 +1,161 @@
+/// Non-standard `memberCount` on Group responses (`include_member_count`)
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server(include_member_count: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            include_member_count,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+async fn create_user(server: &TestServer, user_name: &str) -> String {
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(user_name, "Count", "Member"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+async fn create_group(server: &TestServer, display_name: &str, member_ids: &[&str]) -> Value {
+    let members: Vec<Value> = member_ids.iter().map(|id| json!({"value": id})).collect();
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": display_name,
+            "members": members
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json()
+}
+
+async fn patch_members(server: &TestServer, group_id: &str, operation: Value) -> Value {
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [operation]
+        }))
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+async fn member_count(server: &TestServer, group_id: &str) -> Value {
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    group["memberCount"].clone()
+}
+
+#[tokio::test]
+async fn test_member_count_follows_member_changes() {
+    let server = setup_server(true).await;
+    let alice = create_user(&server, "count.alice").await;
+    let bob = create_user(&server, "count.bob").await;
+    let carol = create_user(&server, "count.carol").await;
+
+    let group = create_group(&server, "Counted", &[&alice, &bob]).await;
+    assert_eq!(group["memberCount"], 2);
+    let group_id = group["id"].as_str().unwrap().to_string();
+    assert_eq!(member_count(&server, &group_id).await, 2);
+
+    let patched = patch_members(
+        &server,
+        &group_id,
+        json!({"op": "add", "path": "members", "value": [{"value": carol}]}),
+    )
+    .await;
+    assert_eq!(patched["memberCount"], 3);
+    assert_eq!(member_count(&server, &group_id).await, 3);
+
+    patch_members(
+        &server,
+        &group_id,
+        json!({"op": "remove", "path": format!("members[value eq \"{}\"]", alice)}),
+    )
+    .await;
+    assert_eq!(member_count(&server, &group_id).await, 2);
+
+    let emptied = patch_members(
+        &server,
+        &group_id,
+        json!({"op": "remove", "path": "members"}),
+    )
+    .await;
+    assert_eq!(emptied["memberCount"], 0);
+    assert_eq!(member_count(&server, &group_id).await, 0);
+}
+
+#[tokio::test]
+async fn test_member_count_in_list_responses() {
+    let server = setup_server(true).await;
+    let alice = create_user(&server, "count.alice").await;
+    let empty = create_group(&server, "Empty", &[]).await;
+    assert_eq!(empty["memberCount"], 0);
+    create_group(&server, "Single", &[&alice]).await;
+
+    // Counted without returning the members themselves
+    let list: Value = server
+        .get("/scim/v2/Groups")
+        .add_query_param("excludedAttributes", "members")
+        .await
+        .json();
+    let mut counts: Vec<(String, Value)> = list["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|group| {
+            assert!(group.get("members").is_none());
+            (
+                group["displayName"].as_str().unwrap().to_string(),
+                group["memberCount"].clone(),
+            )
+        })
+        .collect();
+    counts.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        counts,
+        vec![
+            ("Empty".to_string(), json!(0)),
+            ("Single".to_string(), json!(1))
+        ]
+    );
+
+    let filtered: Value = server
+        .get("/scim/v2/Groups")
+        .add_query_param("filter", "displayName eq \"Single\"")
+        .await
+        .json();
+    assert_eq!(filtered["Resources"][0]["memberCount"], 1);
+
+    // Named like any other attribute
+    let projected: Value = server
+        .get("/scim/v2/Groups")
+        .add_query_param("excludedAttributes", "memberCount")
+        .await
+        .json();
+    assert!(projected["Resources"][0].get("memberCount").is_none());
+}
+
+#[tokio::test]
+async fn test_member_count_is_off_by_default() {
+    let server = setup_server(false).await;
+    let alice = create_user(&server, "count.alice").await;
+    let group = create_group(&server, "Uncounted", &[&alice]).await;
+    assert!(group.get("memberCount").is_none());
+
+    let group_id = group["id"].as_str().unwrap();
+    assert_eq!(member_count(&server, group_id).await, Value::Null);
+}
@@ -224,3 +224,87 @@ async fn test_include_user_groups_tenant_override() {
         "groups field should not exist due to tenant override"
     );
 }
+
+/// Server whose only user is a member of one group; returns the user's id
+async fn setup_member(include_user_groups: bool) -> (TestServer, String) {
+    let mut app_config = create_test_app_config();
+    app_config.compatibility.include_user_groups = include_user_groups;
+    let app = setup_test_app(app_config).await.unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "explicit.groups@example.com"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user_id = response.json::<serde_json::Value>()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Explicit Group",
+            "members": [{"value": user_id, "type": "User"}]
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    (server, user_id)
+}
+
+#[tokio::test]
+async fn test_attribute_parameters_override_include_user_groups() {
+    // (include_user_groups, query, groups expected)
+    let cases = [
+        (false, "attributes=groups", true),
+        (false, "attributes=userName,groups", true),
+        (false, "excludedAttributes=emails", false),
+        (true, "excludedAttributes=groups", false),
+        (true, "excludedAttributes=emails", true),
+        (true, "attributes=userName", false),
+    ];
+
+    for (include_user_groups, query, expected) in cases {
+        let (server, user_id) = setup_member(include_user_groups).await;
+
+        let user: serde_json::Value = server
+            .get(&format!("/scim/v2/Users/{}?{}", user_id, query))
+            .await
+            .json();
+        let list: serde_json::Value = server
+            .get(&format!("/scim/v2/Users?{}", query))
+            .await
+            .json();
+
+        for (endpoint, user) in [
+            ("GET /Users/{id}", &user),
+            ("GET /Users", &list["Resources"][0]),
+        ] {
+            let groups = user.get("groups").and_then(|g| g.as_array());
+            if expected {
+                let groups = groups.unwrap_or_else(|| {
+                    panic!(
+                        "{} should return groups for {} with include_user_groups: {}",
+                        endpoint, query, include_user_groups
+                    )
+                });
+                assert_eq!(groups.len(), 1);
+                assert_eq!(groups[0]["display"], "Explicit Group");
+            } else {
+                assert!(
+                    groups.is_none(),
+                    "{} should omit groups for {} with include_user_groups: {}",
+                    endpoint,
+                    query,
+                    include_user_groups
+                );
+            }
+        }
+    }
+}
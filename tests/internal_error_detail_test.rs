@@ -0,0 +1,55 @@
+/// Internal error details in responses
+///
+/// 500 responses must not echo SQL or other internal messages unless
+/// `server.expose_internal_errors` is enabled.
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::backend::database::sqlite::SqliteBackend;
+use scim_server::backend::{Backend, ScimBackend};
+use scim_server::error::set_expose_internal_errors;
+use serde_json::Value;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::sync::Arc;
+
+mod common;
+
+// The setting is process-wide, so both modes are checked in a single test
+#[tokio::test]
+async fn test_internal_error_detail_only_when_exposed() {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let backend = SqliteBackend::new(pool.clone());
+    backend.init_tenant(3).await.unwrap();
+    let backend: Arc<dyn ScimBackend> = Arc::new(backend);
+
+    let app = common::setup_test_app_with_backend(common::create_test_app_config(), backend)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    // Any query on the users table now fails inside the database
+    sqlx::query("DROP TABLE t3_users")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = server.get("/scim/v2/Users").await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    let body: Value = response.json();
+    assert_eq!(body["error"], "Internal server error");
+    assert!(!response.text().contains("t3_users"));
+
+    set_expose_internal_errors(true);
+    let response = server.get("/scim/v2/Users").await;
+    set_expose_internal_errors(false);
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    let body: Value = response.json();
+    assert!(
+        body["error"].as_str().unwrap().contains("t3_users"),
+        "{}",
+        body
+    );
+}
@@ -0,0 +1,90 @@
+/// `count` and `startIndex` sent as decimals or left empty
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+
+mod common;
+
+const USERS: usize = 15;
+const DEFAULT_PAGE_SIZE: u32 = 12;
+
+async fn setup_server() -> TestServer {
+    let server = common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            default_page_size: DEFAULT_PAGE_SIZE,
+            ..Default::default()
+        });
+    })
+    .await;
+
+    for i in 0..USERS {
+        server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(
+                &format!("count.user{:02}", i),
+                "Count",
+                "User",
+            ))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+    server
+}
+
+async fn list(server: &TestServer, query: &str) -> Value {
+    let response = server.get(&format!("/scim/v2/Users{}", query)).await;
+    response.assert_status_ok();
+    let list: Value = response.json();
+    assert_eq!(list["totalResults"], USERS, "{}", query);
+    list
+}
+
+fn page_size(list: &Value) -> usize {
+    list["Resources"].as_array().map_or(0, Vec::len)
+}
+
+#[tokio::test]
+async fn test_decimal_count_is_truncated() {
+    let server = setup_server().await;
+
+    for query in ["?count=10.0", "?count=10.5", "?count=10"] {
+        let list = list(&server, query).await;
+        assert_eq!(page_size(&list), 10, "{}", query);
+        assert_eq!(list["itemsPerPage"], 10, "{}", query);
+    }
+
+    // startIndex is truncated the same way
+    let list = list(&server, "?startIndex=11.9&count=10.0").await;
+    assert_eq!(list["startIndex"], 11);
+    assert_eq!(page_size(&list), USERS - 10);
+}
+
+#[tokio::test]
+async fn test_missing_or_empty_count_uses_default_page_size() {
+    let server = setup_server().await;
+
+    for query in ["", "?count=", "?count=&startIndex="] {
+        let list = list(&server, query).await;
+        assert_eq!(page_size(&list), DEFAULT_PAGE_SIZE as usize, "{:?}", query);
+    }
+
+    // Root search without a count is paged the same way
+    let response = server
+        .post("/scim/v2/.search")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:SearchRequest"]
+        }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(page_size(&response.json()), DEFAULT_PAGE_SIZE as usize);
+}
+
+#[tokio::test]
+async fn test_non_numeric_count_is_rejected() {
+    let server = setup_server().await;
+
+    let response = server.get("/scim/v2/Users?count=ten").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "invalidValue");
+}
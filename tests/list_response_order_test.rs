@@ -0,0 +1,103 @@
+use http::StatusCode;
+
+mod common;
+
+/// Asserts the raw ListResponse body lists its top-level keys in RFC 7644 order
+fn assert_list_response_key_order(body: &str, keys: &[&str]) {
+    assert!(
+        body.starts_with("{\"schemas\":"),
+        "schemas must be the first key: {}",
+        body
+    );
+
+    // Nested resources follow "Resources", so the first occurrence of each key is top-level
+    let positions: Vec<usize> = keys
+        .iter()
+        .map(|key| {
+            body.find(&format!("\"{}\":", key))
+                .unwrap_or_else(|| panic!("missing key {} in {}", key, body))
+        })
+        .collect();
+    assert!(
+        positions.windows(2).all(|w| w[0] < w[1]),
+        "keys out of order {:?}: {}",
+        keys,
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_search_response_key_order() {
+    let server = common::test_server(|_| {}).await;
+
+    for user_name in ["order.alice", "order.bob"] {
+        server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(user_name, "Order", "User"))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let keys = [
+        "schemas",
+        "totalResults",
+        "startIndex",
+        "itemsPerPage",
+        "Resources",
+    ];
+
+    let response = server
+        .get("/scim/v2/Users?filter=userName%20sw%20%22order.%22")
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_list_response_key_order(&response.text(), &keys);
+
+    let response = server.get("/scim/v2/Groups").await;
+    response.assert_status(StatusCode::OK);
+    assert_list_response_key_order(&response.text(), &keys);
+
+    let response = server.get("/scim/v2/Schemas").await;
+    response.assert_status(StatusCode::OK);
+    assert_list_response_key_order(&response.text(), &keys);
+
+    let response = server.get("/scim/v2/ResourceTypes").await;
+    response.assert_status(StatusCode::OK);
+    assert_list_response_key_order(&response.text(), &["schemas", "totalResults", "Resources"]);
+}
+
+#[tokio::test]
+async fn test_empty_search_keeps_resources() {
+    // Resources is part of every ListResponse; show_empty_groups_members only
+    // governs the members/groups arrays inside resources
+    for show_empty_groups_members in [true, false] {
+        let server = common::test_server(|app_config| {
+            app_config.compatibility.show_empty_groups_members = show_empty_groups_members;
+        })
+        .await;
+
+        for (path, filter) in [
+            ("/scim/v2/Users", None),
+            ("/scim/v2/Users", Some("userName eq \"nobody\"")),
+            ("/scim/v2/Groups", None),
+            ("/scim/v2/Groups", Some("displayName eq \"nobody\"")),
+        ] {
+            let mut request = server.get(path);
+            if let Some(filter) = filter {
+                request = request.add_query_param("filter", filter);
+            }
+            let response = request.await;
+            response.assert_status_ok();
+            let body = response.text();
+            assert!(
+                body.contains("\"Resources\":[]"),
+                "missing empty Resources for {} {:?}: {}",
+                path,
+                filter,
+                body
+            );
+            let list: serde_json::Value = response.json();
+            assert_eq!(list["totalResults"], 0);
+            assert_eq!(list["Resources"], serde_json::json!([]));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+mod common;
+
+const TENANT_COUNT: u32 = 5000;
+
+fn many_tenants_config() -> scim_server::config::AppConfig {
+    let mut app_config = common::create_test_app_config();
+    let template = app_config.tenants[0].clone();
+    app_config.tenants = (1..=TENANT_COUNT)
+        .map(|id| {
+            let mut tenant = template.clone();
+            tenant.id = id;
+            tenant.path = format!("/t{}/scim/v2", id);
+            tenant
+        })
+        .collect();
+    app_config
+}
+
+#[tokio::test]
+async fn test_routing_with_thousands_of_tenants() {
+    let app_config = many_tenants_config();
+    let backend = common::setup_test_database(&app_config).await.unwrap();
+    for tenant_id in [1, 2, TENANT_COUNT] {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+
+    // Route registration does not grow with the tenant count
+    let started = Instant::now();
+    let app = common::setup_test_app_with_backend(app_config.clone(), backend)
+        .await
+        .unwrap();
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "building routes for {} tenants took {:?}",
+        TENANT_COUNT,
+        started.elapsed()
+    );
+    let started = Instant::now();
+    assert!(app_config.overlapping_tenant_paths().is_empty());
+    assert!(started.elapsed() < Duration::from_secs(1));
+    let server = TestServer::new(app).unwrap();
+
+    let user_id = {
+        let response = server
+            .post(&format!("/t{}/scim/v2/Users", TENANT_COUNT))
+            .json(&common::create_test_user_json(
+                "last.tenant",
+                "Last",
+                "Tenant",
+            ))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let user: Value = response.json();
+        assert_eq!(
+            user["meta"]["location"],
+            format!(
+                "http://localhost/t{}/scim/v2/Users/{}",
+                TENANT_COUNT,
+                user["id"].as_str().unwrap()
+            )
+        );
+        user["id"].as_str().unwrap().to_string()
+    };
+
+    server
+        .get(&format!("/t{}/scim/v2/Users/{}", TENANT_COUNT, user_id))
+        .await
+        .assert_status(StatusCode::OK);
+    // Tenants whose paths share a prefix stay apart
+    for tenant_id in [1, 2] {
+        server
+            .get(&format!("/t{}/scim/v2/Users/{}", tenant_id, user_id))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    // Discovery endpoints of a tenant in the middle of the list
+    server
+        .get("/t2500/scim/v2/ServiceProviderConfig")
+        .await
+        .assert_status(StatusCode::OK);
+
+    // Unknown endpoints under a tenant and unknown tenants are 404,
+    // unsupported methods on known endpoints 405
+    server
+        .get("/t1/scim/v2/Unknown")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get(&format!("/t{}/scim/v2/Users", TENANT_COUNT + 1))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .delete("/t1/scim/v2/Users")
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[test]
+fn test_max_tenants_is_enforced() {
+    let mut app_config = many_tenants_config();
+    app_config.server.max_tenants = Some(TENANT_COUNT as usize);
+    assert!(app_config.validate_tenant_count().is_ok());
+
+    app_config.server.max_tenants = Some(100);
+    assert_eq!(
+        app_config.validate_tenant_count().unwrap_err(),
+        "5000 tenants are configured, more than the 100 allowed by server.max_tenants"
+    );
+}
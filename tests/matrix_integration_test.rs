@@ -1,10 +1,12 @@
 use axum_test::TestServer;
 use http::StatusCode;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 
 mod common;
 
 use common::TestDatabaseType;
+use scim_server::config::CompatibilityConfig;
 
 // Macro to run the same test with different database types
 macro_rules! matrix_test {
@@ -19,6 +21,11 @@ macro_rules! matrix_test {
             async fn [<$test_name _postgres>]() {
                 $test_fn(TestDatabaseType::Postgres).await;
             }
+
+            #[tokio::test]
+            async fn [<$test_name _mysql>]() {
+                $test_fn(TestDatabaseType::Mysql).await;
+            }
         }
     };
 }
@@ -34,6 +41,7 @@ async fn user_crud_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     // Test POST - Create a user
@@ -135,6 +143,7 @@ async fn group_crud_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "SQLite",
         TestDatabaseType::Postgres => "PostgreSQL",
+        TestDatabaseType::Mysql => "MySQL",
     };
 
     // Test POST - Create a group
@@ -195,6 +204,7 @@ async fn group_membership_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     // Create a user first
@@ -278,6 +288,7 @@ async fn group_to_group_membership_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "SQLite",
         TestDatabaseType::Postgres => "PostgreSQL",
+        TestDatabaseType::Mysql => "MySQL",
     };
 
     // Create parent group
@@ -364,6 +375,7 @@ async fn group_list_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "SQLite",
         TestDatabaseType::Postgres => "PostgreSQL",
+        TestDatabaseType::Mysql => "MySQL",
     };
 
     // Create a group first
@@ -410,6 +422,7 @@ async fn group_patch_operations_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     // Create a user first for testing membership
@@ -586,6 +599,7 @@ async fn enhanced_filter_search_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     println!("\n🔍 Enhanced Filter Search Test ({:?})", db_type);
@@ -859,6 +873,7 @@ async fn nested_attributes_filter_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     println!("\n🔍 Nested Attributes Filter Test ({:?})", db_type);
@@ -1122,6 +1137,7 @@ async fn multi_value_attributes_filter_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     println!("\n🔍 Multi-Value Attributes Filter Test ({:?})", db_type);
@@ -1445,6 +1461,7 @@ async fn enhanced_group_filter_search_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "SQLite",
         TestDatabaseType::Postgres => "PostgreSQL",
+        TestDatabaseType::Mysql => "MySQL",
     };
 
     println!("\n🔍 Enhanced Group Filter Search Test ({:?})", db_type);
@@ -1554,6 +1571,7 @@ async fn case_sensitivity_filtering_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     println!("\n🔍 Case Sensitivity Filtering Test ({:?})", db_type);
@@ -1631,6 +1649,7 @@ async fn complex_query_patterns_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     println!("\n🔍 Complex Query Patterns Test ({:?})", db_type);
@@ -1971,6 +1990,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     println!("\n🔧 Advanced Filter Operators Test ({:?})", db_type);
@@ -1981,13 +2001,14 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
 
     let advanced_users = vec![
         // (username, age, salary, hire_date, performance_score, manager_level)
-        ("alex.young", "22", "55000", "2023-01-15", "3.2", "1"),
-        ("betty.old", "45", "95000", "2018-03-20", "4.8", "3"),
-        ("carlos.mid", "35", "85000", "2020-07-10", "4.2", "2"),
+        // Salaries are zero-padded: filters compare them as strings
+        ("alex.young", "22", "055000", "2023-01-15", "3.2", "1"),
+        ("betty.old", "45", "095000", "2018-03-20", "4.8", "3"),
+        ("carlos.mid", "35", "085000", "2020-07-10", "4.2", "2"),
         ("donna.senior", "52", "120000", "2015-09-05", "4.9", "4"),
-        ("eric.junior", "28", "68000", "2022-11-12", "3.8", "1"),
+        ("eric.junior", "28", "068000", "2022-11-12", "3.8", "1"),
         ("fiona.expert", "40", "105000", "2017-02-28", "4.5", "3"),
-        ("george.newbie", "25", "62000", "2023-06-01", "3.1", "1"),
+        ("george.newbie", "25", "062000", "2023-06-01", "3.1", "1"),
         ("helen.veteran", "48", "135000", "2012-04-15", "4.7", "4"),
     ];
 
@@ -2007,11 +2028,11 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
                 "primary": true
             }],
             "active": true,
-            "age": age,
-            "salary": salary,
-            "hireDate": hire_date,
-            "performanceScore": performance_score,
-            "managerLevel": manager_level
+            "title": manager_level,
+            "employeeNumber": age,
+            "costCenter": salary,
+            "division": hire_date,
+            "organization": performance_score
         });
 
         let response = server
@@ -2038,7 +2059,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     // Test 1: Greater Than (gt) - 30歳より上
     println!("\n   Test 1: gt (Greater Than) - 30歳より上");
     let response = server
-        .get("/scim/v2/Users?filter=age%20gt%20%2230%22")
+        .get("/scim/v2/Users?filter=employeeNumber%20gt%20%2230%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2056,7 +2077,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     // Test 2: Greater Than or Equal (ge) - 給与85000以上
     println!("\n   Test 2: ge (Greater Than or Equal) - 給与85000以上");
     let response = server
-        .get("/scim/v2/Users?filter=salary%20ge%20%2285000%22")
+        .get("/scim/v2/Users?filter=costCenter%20ge%20%22085000%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2072,7 +2093,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     // Test 3: Less Than (lt) - 30歳未満
     println!("\n   Test 3: lt (Less Than) - 30歳未満");
     let response = server
-        .get("/scim/v2/Users?filter=age%20lt%20%2230%22")
+        .get("/scim/v2/Users?filter=employeeNumber%20lt%20%2230%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2085,7 +2106,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     // Test 4: Less Than or Equal (le) - 給与70000以下
     println!("\n   Test 4: le (Less Than or Equal) - 給与70000以下");
     let response = server
-        .get("/scim/v2/Users?filter=salary%20le%20%2270000%22")
+        .get("/scim/v2/Users?filter=costCenter%20le%20%22070000%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2101,7 +2122,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     // Test 5: Not Equal (ne) - マネージャーレベル1でない
     println!("\n   Test 5: ne (Not Equal) - マネージャーレベル1でない");
     let response = server
-        .get("/scim/v2/Users?filter=managerLevel%20ne%20%221%22")
+        .get("/scim/v2/Users?filter=title%20ne%20%221%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2152,7 +2173,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     // Test 8: Ends With (ew) - 雇用日が\"05\"で終わる (xx-xx-05)
     println!("\n   Test 8: ew (Ends With) - 雇用日が'05'で終わる");
     let response = server
-        .get("/scim/v2/Users?filter=hireDate%20ew%20%2205%22")
+        .get("/scim/v2/Users?filter=division%20ew%20%2205%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2165,7 +2186,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     // Test 9: Present (pr) - performanceScoreフィールドが存在する
     println!("\n   Test 9: pr (Present) - performanceScoreフィールドが存在する");
     let response = server
-        .get("/scim/v2/Users?filter=performanceScore%20pr")
+        .get("/scim/v2/Users?filter=organization%20pr")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2183,7 +2204,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
 
     // 30歳以上のユーザーを取得
     let response_age = server
-        .get("/scim/v2/Users?filter=age%20ge%20%2230%22")
+        .get("/scim/v2/Users?filter=employeeNumber%20ge%20%2230%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
     response_age.assert_status(StatusCode::OK);
@@ -2192,7 +2213,7 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
 
     // 給与90000以上のユーザーを取得
     let response_salary = server
-        .get("/scim/v2/Users?filter=salary%20ge%20%2290000%22")
+        .get("/scim/v2/Users?filter=costCenter%20ge%20%22090000%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
     response_salary.assert_status(StatusCode::OK);
@@ -2205,6 +2226,15 @@ async fn advanced_filter_operators_test(db_type: TestDatabaseType) {
     println!("      ✅ 30歳以上のユーザー: {}件", age_30_plus);
     println!("      ✅ 給与90000以上のユーザー: {}件", salary_90k_plus);
 
+    // Attributes no schema defines are rejected before any SQL is built
+    let response = server
+        .get("/scim/v2/Users?filter=age%20gt%20%2230%22")
+        .add_header(http::header::ACCEPT, "application/scim+json")
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let error: Value = response.json();
+    assert_eq!(error["scimType"], "invalidFilter");
+
     println!(
         "\n✅ 全ての高度なフィルタオペレータテストが成功！({:?})",
         db_type
@@ -2226,6 +2256,7 @@ async fn edge_case_filtering_test(db_type: TestDatabaseType) {
     let db_prefix = match db_type {
         TestDatabaseType::Sqlite => "sqlite",
         TestDatabaseType::Postgres => "postgres",
+        TestDatabaseType::Mysql => "mysql",
     };
 
     println!("\n⚡ Edge Case Filtering Test ({:?})", db_type);
@@ -2488,7 +2519,7 @@ async fn edge_case_filtering_test(db_type: TestDatabaseType) {
     // Edge Case 11: 空文字列での検索（存在するが空）
     println!("\n   Edge Case 11: 空文字列フィールドでの検索");
     let response = server
-        .get("/scim/v2/Users?filter=title%20eq%20%22%5D")
+        .get("/scim/v2/Users?filter=title%20eq%20%22%22")
         .add_header(http::header::ACCEPT, "application/scim+json")
         .await;
 
@@ -2533,25 +2564,2925 @@ async fn edge_case_filtering_test(db_type: TestDatabaseType) {
     println!("   🔍 境界値テスト: 長い文字列、複雑なドメイン");
 }
 
-// Generate matrix tests for each test function
-matrix_test!(user_crud, user_crud_test);
-matrix_test!(group_crud, group_crud_test);
-matrix_test!(group_list, group_list_test);
-matrix_test!(group_patch_operations, group_patch_operations_test);
-matrix_test!(group_membership, group_membership_test);
-matrix_test!(group_to_group_membership, group_to_group_membership_test);
-matrix_test!(group_error_scenarios, group_error_scenarios_test);
-matrix_test!(enhanced_filter_search, enhanced_filter_search_test);
-matrix_test!(
-    enhanced_group_filter_search,
-    enhanced_group_filter_search_test
-);
-matrix_test!(case_sensitivity_filtering, case_sensitivity_filtering_test);
-matrix_test!(nested_attributes_filter, nested_attributes_filter_test);
-matrix_test!(
-    multi_value_attributes_filter,
-    multi_value_attributes_filter_test
-);
-matrix_test!(complex_query_patterns, complex_query_patterns_test);
-matrix_test!(advanced_filter_operators, advanced_filter_operators_test);
-matrix_test!(edge_case_filtering, edge_case_filtering_test);
+async fn last_modified_sort_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    // Create users with distinct, increasing timestamps
+    let mut ids = Vec::new();
+    for name in ["poll-alpha", "poll-bravo", "poll-charlie"] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(name, "Poll", "User"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let user: Value = response.json();
+        ids.push(user["id"].as_str().unwrap().to_string());
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // Touch the oldest user so it becomes the most recently modified one
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", ids[0]))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "title", "value": "Changed"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+
+    // A user outside the filtered set, modified last
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(
+            "other-user",
+            "Other",
+            "User",
+        ))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let ids_of = |body: Value| -> Vec<String> {
+        body["Resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // lastModified descending, combined with a filter
+    let response = server
+        .get("/scim/v2/Users?filter=userName%20sw%20%22poll-%22&sortBy=meta.lastModified&sortOrder=descending")
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        ids_of(response.json()),
+        vec![ids[0].clone(), ids[2].clone(), ids[1].clone()],
+        "lastModified descending with filter failed for {:?}",
+        db_type
+    );
+
+    // Attribute names are case-insensitive
+    let response = server
+        .get("/scim/v2/Users?filter=userName%20sw%20%22poll-%22&sortBy=META.LASTMODIFIED&sortOrder=descending")
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        ids_of(response.json()),
+        vec![ids[0].clone(), ids[2].clone(), ids[1].clone()]
+    );
+
+    // meta.created descending is unaffected by the later modification
+    let response = server
+        .get("/scim/v2/Users?filter=userName%20sw%20%22poll-%22&sortBy=meta.created&sortOrder=descending")
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        ids_of(response.json()),
+        vec![ids[2].clone(), ids[1].clone(), ids[0].clone()]
+    );
+
+    // Unfiltered: the most recently modified user comes first
+    let response = server
+        .get("/scim/v2/Users?sortBy=meta.lastModified&sortOrder=descending&count=2")
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 4);
+    assert_eq!(body["Resources"][0]["userName"], "other-user");
+    assert_eq!(body["Resources"][1]["id"], ids[0].as_str());
+}
+
+async fn group_membership_rollback_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user_ids = Vec::new();
+    for name in ["tx-user-1", "tx-user-2"] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(name, "Tx", "User"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let user: Value = response.json();
+        user_ids.push(user["id"].as_str().unwrap().to_string());
+    }
+
+    // A duplicate member makes the second membership insert fail
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Rollback Group",
+            "members": [
+                {"value": user_ids[0], "type": "User"},
+                {"value": user_ids[0], "type": "User"}
+            ]
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    // Neither the group nor a partial membership may persist
+    let response = server
+        .get("/scim/v2/Groups?filter=displayName%20eq%20%22Rollback%20Group%22")
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 0, "group leaked for {:?}", db_type);
+
+    let user: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_ids[0]))
+        .await
+        .json();
+    assert!(
+        user["groups"].as_array().is_none_or(|g| g.is_empty()),
+        "partial membership leaked for {:?}: {}",
+        db_type,
+        user["groups"]
+    );
+
+    // Same for a PUT that replaces the member list
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Stable Group",
+            "members": [{"value": user_ids[0], "type": "User"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group: Value = response.json();
+    let group_id = group["id"].as_str().unwrap();
+
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Renamed Group",
+            "members": [
+                {"value": user_ids[1], "type": "User"},
+                {"value": user_ids[1], "type": "User"}
+            ]
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_eq!(group["displayName"], "Stable Group");
+    let members = group["members"].as_array().unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0]["value"], user_ids[0].as_str());
+}
+
+async fn group_patch_without_path_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user_ids = Vec::new();
+    for name in ["nopath-user-1", "nopath-user-2", "nopath-user-3"] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(name, "NoPath", "User"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let user: Value = response.json();
+        user_ids.push(user["id"].as_str().unwrap().to_string());
+    }
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "No Path Group",
+            "members": [{"value": user_ids[0], "type": "User"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group: Value = response.json();
+    let group_id = group["id"].as_str().unwrap();
+
+    let member_values = |group: &Value| -> Vec<String> {
+        let mut values: Vec<String> = group["members"]
+            .as_array()
+            .map(|members| {
+                members
+                    .iter()
+                    .map(|m| m["value"].as_str().unwrap().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        values.sort();
+        values
+    };
+
+    // add without path: members are appended (existing ones skipped), displayName overwritten
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "add",
+                "value": {
+                    "displayName": "No Path Group Added",
+                    "members": [
+                        {"value": user_ids[0], "type": "User"},
+                        {"value": user_ids[1], "type": "User"}
+                    ]
+                }
+            }]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let patched: Value = response.json();
+    assert_eq!(patched["displayName"], "No Path Group Added");
+    let mut expected = vec![user_ids[0].clone(), user_ids[1].clone()];
+    expected.sort();
+    assert_eq!(member_values(&patched), expected, "add for {:?}", db_type);
+
+    // replace without path: the member list is replaced as a whole
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "replace",
+                "value": {
+                    "members": [{"value": user_ids[2], "type": "User"}]
+                }
+            }]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let replaced: Value = response.json();
+    assert_eq!(replaced["displayName"], "No Path Group Added");
+    assert_eq!(
+        member_values(&replaced),
+        vec![user_ids[2].clone()],
+        "replace for {:?}",
+        db_type
+    );
+
+    // The stored state matches the PATCH response
+    let response = server.get(&format!("/scim/v2/Groups/{}", group_id)).await;
+    response.assert_status(StatusCode::OK);
+    let stored: Value = response.json();
+    assert_eq!(member_values(&stored), vec![user_ids[2].clone()]);
+
+    // remove still requires a path
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "remove"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+}
+
+/// Returns the sorted userNames of pr-* users matching `filter`
+async fn present_filter_matches(server: &TestServer, filter: &str) -> Vec<String> {
+    prefixed_filter_matches(server, "pr-", filter).await
+}
+
+/// Returns the sorted userNames of users starting with `prefix` that match `filter`
+async fn prefixed_filter_matches(server: &TestServer, prefix: &str, filter: &str) -> Vec<String> {
+    let filter = format!("userName sw \"{}\" and ({})", prefix, filter)
+        .replace(' ', "%20")
+        .replace('"', "%22")
+        .replace('+', "%2B")
+        .replace('[', "%5B")
+        .replace(']', "%5D");
+    let response = server
+        .get(&format!("/scim/v2/Users?filter={}", filter))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    let mut names: Vec<String> = body["Resources"]
+        .as_array()
+        .map(|resources| {
+            resources
+                .iter()
+                .map(|u| u["userName"].as_str().unwrap().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+async fn present_operator_nested_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let users = [
+        common::create_test_user_json("pr-full", "Full", "User"),
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "pr-empty",
+            "name": {"givenName": "", "familyName": "Empty"},
+            "emails": []
+        }),
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "pr-bare"
+        }),
+    ];
+    for user in &users {
+        server
+            .post("/scim/v2/Users")
+            .json(user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    // Nested scalar: present only when non-null and non-empty
+    assert_eq!(
+        present_filter_matches(&server, "name.givenName pr").await,
+        vec!["pr-full"]
+    );
+    // Complex attribute with at least one non-empty sub-attribute
+    assert_eq!(
+        present_filter_matches(&server, "name pr").await,
+        vec!["pr-empty", "pr-full"]
+    );
+    // Multi-valued attribute: an empty array is not present
+    assert_eq!(
+        present_filter_matches(&server, "emails pr").await,
+        vec!["pr-full"]
+    );
+    assert_eq!(
+        present_filter_matches(&server, "emails.value pr").await,
+        vec!["pr-full"]
+    );
+    assert_eq!(
+        present_filter_matches(&server, "not (emails pr)").await,
+        vec!["pr-bare", "pr-empty"]
+    );
+    // Attribute absent on every resource
+    assert!(
+        present_filter_matches(&server, "title pr").await.is_empty(),
+        "{:?}",
+        db_type
+    );
+}
+
+async fn value_path_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let users = [
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "vp-match",
+            "active": true,
+            "emails": [{"value": "match@corp.com", "type": "work"}],
+            "addresses": [{"type": "work", "locality": "Berlin"}]
+        }),
+        // type=work and @corp.com sit on different entries, as do work and Berlin
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "vp-split",
+            "active": true,
+            "emails": [
+                {"value": "split@home.com", "type": "work"},
+                {"value": "split@corp.com", "type": "home"}
+            ],
+            "addresses": [
+                {"type": "work", "locality": "Paris"},
+                {"type": "home", "locality": "Berlin"}
+            ]
+        }),
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "vp-inactive",
+            "active": false,
+            "emails": [{"value": "inactive@corp.com", "type": "work"}]
+        }),
+    ];
+    for user in &users {
+        server
+            .post("/scim/v2/Users")
+            .json(user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    // Both bracketed conditions must hold for the same email entry
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "vp-",
+            "emails[type eq \"work\" and value co \"@corp.com\"]"
+        )
+        .await,
+        vec!["vp-inactive", "vp-match"]
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "vp-",
+            "emails[type eq \"work\" and value co \"@corp.com\"] and active eq true"
+        )
+        .await,
+        vec!["vp-match"],
+        "{:?}",
+        db_type
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "vp-",
+            "emails[type eq \"home\" or value co \"@corp.com\"]"
+        )
+        .await,
+        vec!["vp-inactive", "vp-match", "vp-split"]
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "vp-",
+            "emails[type eq \"work\" and not (value co \"@corp.com\")]"
+        )
+        .await,
+        vec!["vp-split"]
+    );
+    // A trailing sub-attribute comparison applies to the filtered entry
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "vp-",
+            "addresses[type eq \"work\"].locality eq \"Berlin\""
+        )
+        .await,
+        vec!["vp-match"],
+        "{:?}",
+        db_type
+    );
+}
+
+async fn not_equal_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let users = [
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ne-employee",
+            "externalId": "EXT-001",
+            "userType": "Employee",
+            "active": true
+        }),
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ne-contractor",
+            "externalId": "ext-001",
+            "userType": "Contractor",
+            "active": false
+        }),
+        // No externalId, userType or active at all
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ne-bare"
+        }),
+    ];
+    for user in &users {
+        server
+            .post("/scim/v2/Users")
+            .json(user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    assert_eq!(
+        prefixed_filter_matches(&server, "ne-", "active ne true").await,
+        vec!["ne-bare", "ne-contractor"],
+        "{:?}",
+        db_type
+    );
+    // userType is case-insensitive
+    assert_eq!(
+        prefixed_filter_matches(&server, "ne-", "userType ne \"Employee\"").await,
+        vec!["ne-bare", "ne-contractor"]
+    );
+    assert_eq!(
+        prefixed_filter_matches(&server, "ne-", "userType ne \"employee\"").await,
+        vec!["ne-bare", "ne-contractor"]
+    );
+    // externalId is case-exact, so "ext-001" differs from "EXT-001"
+    assert_eq!(
+        prefixed_filter_matches(&server, "ne-", "externalId ne \"EXT-001\"").await,
+        vec!["ne-bare", "ne-contractor"]
+    );
+    // An attribute absent on every user is not equal to any value
+    assert_eq!(
+        prefixed_filter_matches(&server, "ne-", "title ne \"Manager\"").await,
+        vec!["ne-bare", "ne-contractor", "ne-employee"]
+    );
+}
+
+/// Creates the two users the rename conflict tests replace into each other
+async fn create_rename_conflict_users(server: &TestServer, prefix: &str) -> (String, String) {
+    let mut ids = Vec::new();
+    for (user_name, external_id) in [("holder", "EXT-HOLDER"), ("renamer", "EXT-RENAMER")] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": format!("{}-{}", prefix, user_name),
+                "externalId": format!("{}-{}", prefix, external_id)
+            }))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+    }
+    (ids[0].clone(), ids[1].clone())
+}
+
+/// Asserts a 409 uniqueness error that names `attribute` but not the holder
+fn assert_uniqueness_conflict(
+    response: &axum_test::TestResponse,
+    attribute: &str,
+    holder_id: &str,
+) {
+    response.assert_status(StatusCode::CONFLICT);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "uniqueness");
+    assert_eq!(body["status"], "409");
+    let detail = body["detail"].as_str().unwrap();
+    assert!(detail.contains(attribute), "detail: {}", detail);
+    assert!(
+        !detail.contains(holder_id),
+        "detail leaks holder id: {}",
+        detail
+    );
+}
+
+async fn user_rename_conflict_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+    let (holder_id, renamer_id) = create_rename_conflict_users(&server, "rc").await;
+
+    // userName clash is detected case-insensitively
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", renamer_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "RC-Holder",
+            "externalId": "rc-EXT-RENAMER"
+        }))
+        .await;
+    assert_uniqueness_conflict(&response, "userName", &holder_id);
+
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", renamer_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "rc-renamer",
+            "externalId": "rc-EXT-HOLDER"
+        }))
+        .await;
+    assert_uniqueness_conflict(&response, "externalId", &holder_id);
+
+    // Neither failed PUT changed the user
+    let response = server.get(&format!("/scim/v2/Users/{}", renamer_id)).await;
+    let user: Value = response.json();
+    assert_eq!(user["userName"], "rc-renamer");
+    assert_eq!(user["externalId"], "rc-EXT-RENAMER");
+}
+
+async fn user_rename_self_case_change_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+    let (_, renamer_id) = create_rename_conflict_users(&server, "rs").await;
+
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", renamer_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "RS-Renamer",
+            "externalId": "rs-EXT-RENAMER"
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["userName"], "RS-Renamer");
+}
+
+async fn user_rename_conflict_race_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+    let (holder_id, renamer_id) = create_rename_conflict_users(&server, "rr").await;
+
+    // Simulate a concurrent rename that passed the pre-check: only the
+    // database unique constraint is left to catch the clash
+    scim_server::backend::database::user_update::set_skip_conflict_precheck(true);
+    let username_response = server
+        .put(&format!("/scim/v2/Users/{}", renamer_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "rr-holder"
+        }))
+        .await;
+    let external_id_response = server
+        .put(&format!("/scim/v2/Users/{}", renamer_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "rr-renamer",
+            "externalId": "rr-EXT-HOLDER"
+        }))
+        .await;
+    scim_server::backend::database::user_update::set_skip_conflict_precheck(false);
+
+    assert_uniqueness_conflict(&username_response, "userName", &holder_id);
+    assert_uniqueness_conflict(&external_id_response, "externalId", &holder_id);
+}
+
+async fn patch_active_representations_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "active-patch",
+            "active": true
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    let patch_active = |value: Value| {
+        let server = &server;
+        let user_id = &user_id;
+        async move {
+            server
+                .patch(&format!("/scim/v2/Users/{}", user_id))
+                .json(&json!({
+                    "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                    "Operations": [{"op": "replace", "path": "active", "value": value}]
+                }))
+                .await
+        }
+    };
+
+    // Boolean, string and integer forms all deactivate, then reactivate
+    for (deactivate, reactivate) in [
+        (json!(false), json!(true)),
+        (json!("false"), json!("true")),
+        (json!(0), json!(1)),
+    ] {
+        let response = patch_active(deactivate.clone()).await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(response.json::<Value>()["active"], json!(false));
+
+        // Stored as a JSON boolean, not a string or number
+        let user: Value = server
+            .get(&format!("/scim/v2/Users/{}", user_id))
+            .await
+            .json();
+        assert_eq!(
+            user["active"],
+            json!(false),
+            "{} on {:?}",
+            deactivate,
+            db_type
+        );
+        assert_eq!(
+            prefixed_filter_matches(&server, "active-", "active eq false").await,
+            vec!["active-patch"]
+        );
+
+        let response = patch_active(reactivate).await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(response.json::<Value>()["active"], json!(true));
+    }
+
+    let response = patch_active(json!("disabled")).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "invalidValue");
+}
+
+async fn meta_attribute_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut users = Vec::new();
+    for name in ["meta-a", "meta-b"] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(name, "Meta", "User"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        users.push(response.json::<Value>());
+        // Keep the timestamps apart at millisecond precision
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let (a, b) = (&users[0], &users[1]);
+
+    // resourceType is fixed by the endpoint
+    assert_eq!(
+        prefixed_filter_matches(&server, "meta-", "meta.resourceType eq \"User\"").await,
+        vec!["meta-a", "meta-b"]
+    );
+    assert!(
+        prefixed_filter_matches(&server, "meta-", "meta.resourceType eq \"Group\"")
+            .await
+            .is_empty()
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "meta-",
+            "meta.resourceType eq \"User\" and name.givenName eq \"meta\" and userName ew \"b\""
+        )
+        .await,
+        vec!["meta-b"]
+    );
+
+    // location is compared from the resource endpoint onwards
+    assert_eq!(
+        prefixed_filter_matches(&server, "meta-", "meta.location co \"/Users/\"").await,
+        vec!["meta-a", "meta-b"]
+    );
+    let location_a = a["meta"]["location"].as_str().unwrap();
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "meta-",
+            &format!("meta.location eq \"{}\"", location_a)
+        )
+        .await,
+        vec!["meta-a"]
+    );
+    let id_b = b["id"].as_str().unwrap();
+    assert_eq!(
+        prefixed_filter_matches(&server, "meta-", &format!("meta.location ew \"{}\"", id_b)).await,
+        vec!["meta-b"]
+    );
+
+    // Timestamps compare as dateTimes, exactly as returned in meta
+    let created_a = a["meta"]["created"].as_str().unwrap();
+    let created_b = b["meta"]["created"].as_str().unwrap();
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "meta-",
+            &format!("meta.created eq \"{}\"", created_a)
+        )
+        .await,
+        vec!["meta-a"]
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "meta-",
+            &format!("meta.created lt \"{}\"", created_b)
+        )
+        .await,
+        vec!["meta-a"]
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "meta-",
+            &format!("meta.created ge \"{}\"", created_b)
+        )
+        .await,
+        vec!["meta-b"]
+    );
+
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", a["id"].as_str().unwrap()))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "displayName", "value": "Meta A"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let last_modified_b = b["meta"]["lastModified"].as_str().unwrap();
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "meta-",
+            &format!("meta.lastModified gt \"{}\"", last_modified_b)
+        )
+        .await,
+        vec!["meta-a"]
+    );
+
+    // Groups are scoped the same way
+    server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Meta Group"
+        }))
+        .await
+        .assert_status(StatusCode::CREATED);
+    for (filter, expected) in [
+        ("meta.resourceType eq \"Group\"", 1),
+        ("meta.resourceType eq \"User\"", 0),
+        ("meta.location co \"/Groups/\"", 1),
+        ("meta.location co \"/Users/\"", 0),
+    ] {
+        let response = server
+            .get("/scim/v2/Groups")
+            .add_query_param("filter", filter)
+            .await;
+        response.assert_status(StatusCode::OK);
+        assert_eq!(
+            response.json::<Value>()["totalResults"],
+            expected,
+            "{} on {:?}",
+            filter,
+            db_type
+        );
+    }
+
+    // Ordering makes no sense for a URI, and timestamps must be dateTimes
+    for filter in ["meta.location gt \"x\"", "meta.created eq \"yesterday\""] {
+        server
+            .get("/scim/v2/Users")
+            .add_query_param("filter", filter)
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+}
+
+async fn patch_active_fast_path_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user = common::create_test_user_json("fast-active", "Fast", "Active");
+    user["title"] = json!("Engineer");
+    user["phoneNumbers"] = json!([{"value": "+1-555-0100", "type": "work"}]);
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let before: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+
+    for (operation, active) in [
+        (
+            json!({"op": "replace", "path": "active", "value": false}),
+            false,
+        ),
+        (json!({"op": "replace", "value": {"active": true}}), true),
+    ] {
+        // Keep lastModified distinguishable at millisecond precision
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let response = server
+            .patch(&format!("/scim/v2/Users/{}", user_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [operation]
+            }))
+            .await;
+        response.assert_status(StatusCode::OK);
+        let patched: Value = response.json();
+        assert_eq!(patched["active"], json!(active), "{:?}", db_type);
+
+        let after: Value = server
+            .get(&format!("/scim/v2/Users/{}", user_id))
+            .await
+            .json();
+        assert_eq!(after["active"], json!(active));
+        assert!(
+            after["meta"]["lastModified"].as_str().unwrap()
+                > before["meta"]["lastModified"].as_str().unwrap()
+        );
+        assert_ne!(after["meta"]["version"], before["meta"]["version"]);
+
+        // Everything else is exactly as before
+        let strip = |user: &Value| {
+            let mut user = user.clone();
+            let obj = user.as_object_mut().unwrap();
+            obj.remove("active");
+            obj["meta"].as_object_mut().unwrap().remove("lastModified");
+            obj["meta"].as_object_mut().unwrap().remove("version");
+            user
+        };
+        assert_eq!(strip(&after), strip(&before));
+
+        // The normalized copy is updated too, so filters see the new value
+        let expected: Vec<String> = if active {
+            vec![]
+        } else {
+            vec!["fast-active".to_string()]
+        };
+        assert_eq!(
+            prefixed_filter_matches(&server, "fast-", "active eq false").await,
+            expected
+        );
+    }
+
+    // Unknown users are still 404
+    let missing = match db_type {
+        TestDatabaseType::Sqlite => "missing-user".to_string(),
+        TestDatabaseType::Postgres => "00000000-0000-0000-0000-000000000000".to_string(),
+        TestDatabaseType::Mysql => "missing-user".to_string(),
+    };
+    server
+        .patch(&format!("/scim/v2/Users/{}", missing))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "active", "value": false}]
+        }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+async fn multi_condition_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    for (user_name, user_type, active, external_id) in [
+        ("alice", "Employee", true, Some("EXT-001")),
+        ("bob", "Employee", false, Some("EXT-002")),
+        ("carol", "Contractor", true, Some("EXT-003")),
+        ("admin", "Contractor", false, Some("EXT-ADMIN")),
+        ("dave", "Employee", true, None),
+    ] {
+        let mut user = json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": user_name,
+            "userType": user_type,
+            "active": active
+        });
+        if let Some(external_id) = external_id {
+            user["externalId"] = json!(external_id);
+        }
+        server
+            .post("/scim/v2/Users")
+            .json(&user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    for (filter, expected) in [
+        (
+            "(userType eq \"Employee\" and active eq true) or externalId eq \"EXT-ADMIN\"",
+            vec!["admin", "alice", "dave"],
+        ),
+        (
+            "(userName sw \"alice\" or userName sw \"bob\") and active eq true",
+            vec!["alice"],
+        ),
+        (
+            "not (userType eq \"Employee\") and active eq true",
+            vec!["carol"],
+        ),
+        (
+            "not (active eq true) OR userName eq \"dave\"",
+            vec!["admin", "bob", "dave"],
+        ),
+        // externalId is caseExact
+        (
+            "externalId eq \"ext-admin\" or userName eq \"nobody\"",
+            vec![],
+        ),
+    ] {
+        let response = server
+            .get("/scim/v2/Users")
+            .add_query_param("filter", filter)
+            .await;
+        response.assert_status(StatusCode::OK);
+        let body: Value = response.json();
+
+        let mut user_names: Vec<&str> = body["Resources"]
+            .as_array()
+            .map(|resources| {
+                resources
+                    .iter()
+                    .map(|u| u["userName"].as_str().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        user_names.sort();
+        assert_eq!(user_names, expected, "{} on {:?}", filter, db_type);
+        assert_eq!(
+            body["totalResults"],
+            expected.len(),
+            "{} on {:?}",
+            filter,
+            db_type
+        );
+    }
+}
+
+async fn group_member_batch_test(db_type: TestDatabaseType) {
+    let mut tenant_config = common::create_test_app_config();
+    tenant_config.tenants[2].member_batch.enabled = true;
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user_ids = Vec::new();
+    for i in 0..3 {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(
+                &format!("batch-{}", i),
+                "Batch",
+                "Member",
+            ))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        user_ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+    }
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Batch Group"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let add_url = format!("/scim/v2/Groups/{}/members:add", group_id);
+
+    let response = server
+        .post(&add_url)
+        .json(&json!({"ids": [user_ids[0], user_ids[1]]}))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"added": 2, "already_present": 0, "not_found": []})
+    );
+
+    // Overlapping batch with an unknown UUID and a non-UUID id
+    let unknown = "11111111-2222-3333-4444-555555555555";
+    let response = server
+        .post(&add_url)
+        .json(&json!({"ids": [user_ids[1], user_ids[2], unknown, "bogus"]}))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"added": 1, "already_present": 1, "not_found": [unknown, "bogus"]})
+    );
+
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_eq!(group["members"].as_array().unwrap().len(), 3);
+    assert_eq!(group["meta"]["version"], "W/\"3\"");
+
+    let response = server
+        .post(&format!("/scim/v2/Groups/{}/members:remove", group_id))
+        .json(&json!({"ids": [user_ids[0], unknown]}))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"removed": 1, "not_members": 1})
+    );
+
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    let members: Vec<&str> = group["members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["value"].as_str().unwrap())
+        .collect();
+    assert_eq!(members.len(), 2);
+    assert!(!members.contains(&user_ids[0].as_str()));
+    assert_eq!(group["meta"]["version"], "W/\"4\"");
+}
+
+async fn locale_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut bob_id = String::new();
+    for (user_name, locale) in [
+        ("alice", "en"),
+        ("bob", "en-US"),
+        ("carol", "zh-Hant-TW"),
+        ("dave", "fr-CA"),
+        ("erin", "ca"),
+    ] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": user_name,
+                "locale": locale
+            }))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        if user_name == "bob" {
+            bob_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+        }
+    }
+
+    for (filter, expected) in [
+        ("locale eq \"EN-us\"", vec!["bob"]),
+        ("locale sw \"en\"", vec!["alice", "bob"]),
+        ("locale sw \"EN-\"", vec!["bob"]),
+        ("locale ew \"us\"", vec!["bob"]),
+        ("locale co \"Hant\"", vec!["carol"]),
+        ("locale co \"ca\"", vec!["dave", "erin"]),
+        // Partial subtags do not match
+        ("locale sw \"e\"", vec![]),
+        ("locale ew \"a\"", vec![]),
+        ("locale co \"an\"", vec![]),
+    ] {
+        let response = server
+            .get("/scim/v2/Users")
+            .add_query_param("filter", filter)
+            .await;
+        response.assert_status(StatusCode::OK);
+        let body: Value = response.json();
+
+        let mut user_names: Vec<&str> = body["Resources"]
+            .as_array()
+            .map(|resources| {
+                resources
+                    .iter()
+                    .map(|u| u["userName"].as_str().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        user_names.sort();
+        assert_eq!(user_names, expected, "{} on {:?}", filter, db_type);
+    }
+
+    // Malformed tags are rejected on create and on patch
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "frank",
+            "locale": "en_US"
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "invalidValue");
+
+    for (locale, status) in [
+        ("en_US", StatusCode::BAD_REQUEST),
+        ("ja-JP", StatusCode::OK),
+    ] {
+        let response = server
+            .patch(&format!("/scim/v2/Users/{}", bob_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "path": "locale", "value": locale}]
+            }))
+            .await;
+        response.assert_status(status);
+        if status == StatusCode::BAD_REQUEST {
+            assert_eq!(response.json::<Value>()["scimType"], "invalidValue");
+        } else {
+            assert_eq!(response.json::<Value>()["locale"], locale);
+        }
+    }
+}
+
+async fn multi_valued_sub_attribute_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let cert = |tag: &str| {
+        format!(
+            "MIIC{}{}",
+            tag,
+            "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVo".repeat(4)
+        )
+    };
+
+    for (user_name, entitlements, roles, ims, certificate) in [
+        (
+            "alice",
+            json!([{"value": "admin"}, {"value": "billing"}]),
+            json!([{"value": "Manager", "type": "work"}]),
+            json!([{"value": "alice.chat", "type": "xmpp"}]),
+            cert("Aa"),
+        ),
+        (
+            "bob",
+            json!([{"value": "billing"}]),
+            json!([{"value": "Engineer", "type": "work"}, {"value": "Mentor", "type": "volunteer"}]),
+            json!([{"value": "bob.chat", "type": "skype"}]),
+            cert("Bb"),
+        ),
+        ("carol", json!([]), json!([]), json!([]), cert("Cc")),
+    ] {
+        server
+            .post("/scim/v2/Users")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": user_name,
+                "entitlements": entitlements,
+                "roles": roles,
+                "ims": ims,
+                "x509Certificates": [{"value": certificate}]
+            }))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    for (filter, expected) in [
+        ("entitlements.value eq \"admin\"".to_string(), vec!["alice"]),
+        (
+            "entitlements[value eq \"billing\"]".to_string(),
+            vec!["alice", "bob"],
+        ),
+        ("entitlements.value pr".to_string(), vec!["alice", "bob"]),
+        (
+            "not (entitlements.value eq \"admin\")".to_string(),
+            vec!["bob", "carol"],
+        ),
+        ("roles.value eq \"engineer\"".to_string(), vec!["bob"]),
+        ("roles[value sw \"M\"]".to_string(), vec!["alice", "bob"]),
+        ("roles.type eq \"volunteer\"".to_string(), vec!["bob"]),
+        (
+            "roles[type eq \"work\" and value eq \"Manager\"]".to_string(),
+            vec!["alice"],
+        ),
+        ("ims.value co \"chat\"".to_string(), vec!["alice", "bob"]),
+        ("ims.type eq \"skype\"".to_string(), vec!["bob"]),
+        (
+            format!("x509Certificates.value eq \"{}\"", cert("Bb")),
+            vec!["bob"],
+        ),
+        (
+            format!("x509Certificates[value eq \"{}\"]", cert("Cc")),
+            vec!["carol"],
+        ),
+    ] {
+        let response = server
+            .get("/scim/v2/Users")
+            .add_query_param("filter", &filter)
+            .await;
+        response.assert_status(StatusCode::OK);
+        let body: Value = response.json();
+
+        let mut user_names: Vec<&str> = body["Resources"]
+            .as_array()
+            .map(|resources| {
+                resources
+                    .iter()
+                    .map(|u| u["userName"].as_str().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        user_names.sort();
+        assert_eq!(user_names, expected, "{} on {:?}", filter, db_type);
+    }
+}
+
+async fn create_group_with_members(
+    server: &TestServer,
+    display_name: &str,
+    members: Value,
+) -> String {
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": display_name,
+            "members": members
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+async fn effective_members_test(db_type: TestDatabaseType) {
+    let mut tenant_config = common::create_test_app_config();
+    tenant_config.tenants[2].effective_members.enabled = true;
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user_ids = HashMap::new();
+    for name in ["dave", "bob", "alice", "carol", "erin"] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(name, name, "Nested"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+        user_ids.insert(name, id);
+    }
+    let user = |name: &str| json!({"value": user_ids[name], "type": "User"});
+
+    // A > B > C, with bob both directly in A and in C
+    let c_id =
+        create_group_with_members(&server, "Level C", json!([user("dave"), user("bob")])).await;
+    let b_id = create_group_with_members(
+        &server,
+        "Level B",
+        json!([user("carol"), {"value": c_id, "type": "Group"}]),
+    )
+    .await;
+    let a_id = create_group_with_members(
+        &server,
+        "Level A",
+        json!([user("alice"), user("bob"), {"value": b_id, "type": "Group"}]),
+    )
+    .await;
+
+    let user_names = |body: &Value| -> Vec<String> {
+        body["Resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["userName"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let response = server
+        .get(&format!("/scim/v2/Groups/{}/effectiveMembers", a_id))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 4, "{:?}", db_type);
+    assert_eq!(
+        user_names(&body),
+        vec!["alice", "bob", "carol", "dave"],
+        "{:?}",
+        db_type
+    );
+
+    let response = server
+        .get(&format!(
+            "/scim/v2/Groups/{}/effectiveMembers?startIndex=3&count=2",
+            a_id
+        ))
+        .await;
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 4);
+    assert_eq!(body["startIndex"], 3);
+    assert_eq!(user_names(&body), vec!["carol", "dave"]);
+
+    let response = server
+        .get(&format!(
+            "/scim/v2/Groups/{}/effectiveMembers?shape=refs&count=1",
+            b_id
+        ))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 3);
+    let entry = &body["Resources"][0];
+    assert_eq!(entry["value"], user_ids["bob"].as_str());
+    assert_eq!(entry["display"], "bob Nested");
+    assert!(entry["$ref"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/scim/v2/Users/{}", user_ids["bob"])));
+    assert!(entry.get("userName").is_none());
+
+    // A cycle C > A ends at the depth limit instead of recursing forever
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", c_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "add",
+                "path": "members",
+                "value": [{"value": a_id, "type": "Group"}]
+            }]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let response = server
+        .get(&format!("/scim/v2/Groups/{}/effectiveMembers", c_id))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(
+        user_names(&body),
+        vec!["alice", "bob", "carol", "dave"],
+        "cycle on {:?}",
+        db_type
+    );
+
+    let response = server
+        .get("/scim/v2/Groups/00000000-0000-0000-0000-000000000000/effectiveMembers")
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    let response = server
+        .get(&format!(
+            "/scim/v2/Groups/{}/effectiveMembers?shape=tree",
+            a_id
+        ))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    // Tenants without effective_members.enabled do not serve the endpoint
+    let response = server
+        .get(&format!(
+            "/tenant-a/scim/v2/Groups/{}/effectiveMembers",
+            a_id
+        ))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+}
+
+async fn group_members_replace_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user_ids = Vec::new();
+    for name in ["replace-1", "replace-2", "replace-3", "replace-4"] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(name, "Replace", "User"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        user_ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+    }
+    let group_id = create_group_with_members(
+        &server,
+        "Replace Group",
+        json!([
+            {"value": user_ids[0], "type": "User"},
+            {"value": user_ids[1], "type": "User"}
+        ]),
+    )
+    .await;
+    let group_path = format!("/scim/v2/Groups/{}", group_id);
+    let created: Value = server.get(&group_path).await.json();
+    let created_last_modified = created["meta"]["lastModified"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let member_values = |group: &Value| -> Vec<String> {
+        let mut values: Vec<String> = group["members"]
+            .as_array()
+            .map(|members| {
+                members
+                    .iter()
+                    .map(|m| m["value"].as_str().unwrap().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        values.sort();
+        values
+    };
+
+    // lastModified has millisecond precision
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+    // Readers running alongside the replace see the old or the new members,
+    // never an emptied group
+    let patch = server.patch(&group_path).json(&json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{
+            "op": "replace",
+            "path": "members",
+            "value": [
+                {"value": user_ids[2], "type": "User"},
+                {"value": user_ids[3], "type": "User"}
+            ]
+        }]
+    }));
+    let (response, read_1, read_2, read_3) = tokio::join!(
+        patch,
+        server.get(&group_path),
+        server.get(&group_path),
+        server.get(&group_path)
+    );
+    response.assert_status(StatusCode::OK);
+    for read in [read_1, read_2, read_3] {
+        read.assert_status(StatusCode::OK);
+        assert_eq!(
+            member_values(&read.json()).len(),
+            2,
+            "transient membership for {:?}",
+            db_type
+        );
+    }
+
+    let mut expected = vec![user_ids[2].clone(), user_ids[3].clone()];
+    expected.sort();
+    let replaced: Value = response.json();
+    assert_eq!(member_values(&replaced), expected, "{:?}", db_type);
+
+    let stored: Value = server.get(&group_path).await.json();
+    assert_eq!(member_values(&stored), expected, "{:?}", db_type);
+    let last_modified = stored["meta"]["lastModified"].as_str().unwrap();
+    assert!(
+        last_modified > created_last_modified.as_str(),
+        "lastModified did not advance for {:?}: {} -> {}",
+        db_type,
+        created_last_modified,
+        last_modified
+    );
+
+    // The users' groups follow the new member list
+    let removed: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_ids[0]))
+        .await
+        .json();
+    assert!(removed["groups"].as_array().is_none_or(|g| g.is_empty()));
+    let added: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_ids[2]))
+        .await
+        .json();
+    assert_eq!(added["groups"][0]["value"], group_id.as_str());
+}
+
+async fn group_member_display_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user_ids = Vec::new();
+    for (user_name, display_name) in [("alice", "Alice Smith"), ("bob", "Bob Jones")] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": user_name,
+                "displayName": display_name
+            }))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        user_ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+    }
+    let (alice_id, bob_id) = (&user_ids[0], &user_ids[1]);
+
+    let engineering_id = create_group_with_members(
+        &server,
+        "Engineering",
+        json!([{"value": alice_id, "type": "User"}, {"value": bob_id, "type": "User"}]),
+    )
+    .await;
+    create_group_with_members(&server, "Sales", json!([{"value": bob_id, "type": "User"}])).await;
+    create_group_with_members(
+        &server,
+        "Admins",
+        json!([{"value": engineering_id, "type": "Group"}]),
+    )
+    .await;
+    create_group_with_members(&server, "Empty", json!([])).await;
+
+    for (filter, expected) in [
+        ("displayName eq \"Sales\"".to_string(), vec!["Sales"]),
+        (
+            "members.display co \"Alice\"".to_string(),
+            vec!["Engineering"],
+        ),
+        (
+            "members.display co \"jones\"".to_string(),
+            vec!["Engineering", "Sales"],
+        ),
+        (
+            "members.display eq \"bob jones\"".to_string(),
+            vec!["Engineering", "Sales"],
+        ),
+        // Nested groups are matched by their displayName
+        ("members.display sw \"Engin\"".to_string(), vec!["Admins"]),
+        ("members[type eq \"Group\"]".to_string(), vec!["Admins"]),
+        (
+            "members[display ew \"smith\" and type eq \"User\"]".to_string(),
+            vec!["Engineering"],
+        ),
+        (
+            "members.display co \"o\" and displayName sw \"S\"".to_string(),
+            vec!["Sales"],
+        ),
+        (
+            "members pr".to_string(),
+            vec!["Admins", "Engineering", "Sales"],
+        ),
+        ("not (members pr)".to_string(), vec!["Empty"]),
+        (
+            format!("members.value eq \"{}\"", alice_id),
+            vec!["Engineering"],
+        ),
+    ] {
+        let response = server
+            .get("/scim/v2/Groups")
+            .add_query_param("filter", &filter)
+            .await;
+        response.assert_status(StatusCode::OK);
+        let body: Value = response.json();
+        assert_eq!(
+            body["totalResults"],
+            expected.len(),
+            "{} on {:?}",
+            filter,
+            db_type
+        );
+
+        let mut display_names: Vec<&str> = body["Resources"]
+            .as_array()
+            .map(|resources| {
+                resources
+                    .iter()
+                    .map(|g| g["displayName"].as_str().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        display_names.sort();
+        assert_eq!(display_names, expected, "{} on {:?}", filter, db_type);
+    }
+}
+
+/// GET `path` with `filter` and paging parameters, returning the response body
+async fn list_page(
+    server: &TestServer,
+    path: &str,
+    filter: &str,
+    sort_by: Option<&str>,
+    start_index: usize,
+    count: usize,
+) -> Value {
+    let mut request = server
+        .get(path)
+        .add_query_param("filter", filter)
+        .add_query_param("startIndex", start_index)
+        .add_query_param("count", count);
+    if let Some(sort_by) = sort_by {
+        request = request.add_query_param("sortBy", sort_by);
+    }
+    let response = request.await;
+    response.assert_status(StatusCode::OK);
+    response.json()
+}
+
+fn resource_ids(body: &Value) -> Vec<String> {
+    body["Resources"]
+        .as_array()
+        .map(|resources| {
+            resources
+                .iter()
+                .map(|r| r["id"].as_str().unwrap().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn filtered_pagination_total_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    // 30 matching and 10 non-matching users and groups
+    let mut matching_user_ids = Vec::new();
+    for i in 1..=40 {
+        let user_name = if i <= 30 {
+            format!("match.{:02}", i)
+        } else {
+            format!("other.{:02}", i)
+        };
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(&user_name, "Page", "User"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        if i <= 30 {
+            matching_user_ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+        }
+    }
+    for i in 1..=40 {
+        let display_name = if i <= 30 {
+            format!("Match Group {:02}", i)
+        } else {
+            format!("Other Group {:02}", i)
+        };
+        create_group_with_members(&server, &display_name, json!([])).await;
+    }
+    let members: Vec<Value> = matching_user_ids
+        .iter()
+        .map(|id| json!({"value": id, "type": "User"}))
+        .collect();
+    let group_id = create_group_with_members(&server, "Matchers", json!(members)).await;
+
+    for (path, filter, sort_by) in [
+        ("/scim/v2/Users", "userName sw \"match\"".to_string(), None),
+        (
+            "/scim/v2/Users",
+            "userName sw \"match\"".to_string(),
+            Some("userName"),
+        ),
+        (
+            "/scim/v2/Users",
+            format!("groups[value eq \"{}\"]", group_id),
+            None,
+        ),
+        (
+            "/scim/v2/Groups",
+            "displayName sw \"Match G\"".to_string(),
+            None,
+        ),
+        (
+            "/scim/v2/Groups",
+            "displayName sw \"Match G\"".to_string(),
+            Some("displayName"),
+        ),
+    ] {
+        let all = list_page(&server, path, &filter, sort_by, 1, 100).await;
+        assert_eq!(all["totalResults"], 30, "{} on {:?}", filter, db_type);
+        let all_ids = resource_ids(&all);
+        assert_eq!(all_ids.len(), 30, "{} on {:?}", filter, db_type);
+
+        let page = list_page(&server, path, &filter, sort_by, 11, 5).await;
+        assert_eq!(page["totalResults"], 30, "{} on {:?}", filter, db_type);
+        assert_eq!(page["itemsPerPage"], 5, "{} on {:?}", filter, db_type);
+        assert_eq!(page["startIndex"], 11, "{} on {:?}", filter, db_type);
+        assert_eq!(
+            resource_ids(&page),
+            all_ids[10..15],
+            "{} on {:?}",
+            filter,
+            db_type
+        );
+
+        // A page past the end still reports the full total
+        let past_end = list_page(&server, path, &filter, sort_by, 31, 5).await;
+        assert_eq!(past_end["totalResults"], 30, "{} on {:?}", filter, db_type);
+        assert!(resource_ids(&past_end).is_empty());
+    }
+
+    let sorted = list_page(
+        &server,
+        "/scim/v2/Users",
+        "userName sw \"match\"",
+        Some("userName"),
+        11,
+        5,
+    )
+    .await;
+    let user_names: Vec<&str> = sorted["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["userName"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        user_names,
+        vec!["match.11", "match.12", "match.13", "match.14", "match.15"]
+    );
+}
+
+async fn root_search(server: &TestServer, body: Value) -> Value {
+    let mut request = json!({"schemas": ["urn:ietf:params:scim:api:messages:2.0:SearchRequest"]});
+    request
+        .as_object_mut()
+        .unwrap()
+        .extend(body.as_object().unwrap().clone());
+    let response = server.post("/scim/v2/.search").json(&request).await;
+    response.assert_status(StatusCode::OK);
+    response.json()
+}
+
+async fn root_search_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user = common::create_test_user_json("search.person", "Search", "Person");
+    user["displayName"] = json!("Searchable Person");
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let mut other = common::create_test_user_json("other.person", "Other", "Person");
+    other["displayName"] = json!("Other Person");
+    server
+        .post("/scim/v2/Users")
+        .json(&other)
+        .await
+        .assert_status(StatusCode::CREATED);
+    let group_id = create_group_with_members(&server, "Searchable Team", json!([])).await;
+    create_group_with_members(&server, "Other Team", json!([])).await;
+
+    // One filter over both resource types, Users listed before Groups
+    let body = root_search(&server, json!({"filter": "displayName sw \"Searchable\""})).await;
+    assert_eq!(body["totalResults"], 2);
+    assert_eq!(resource_ids(&body), vec![user_id.clone(), group_id.clone()]);
+    let resource_types: Vec<&str> = body["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["meta"]["resourceType"].as_str().unwrap())
+        .collect();
+    assert_eq!(resource_types, vec!["User", "Group"]);
+    assert!(body["Resources"][1]["meta"]["location"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/scim/v2/Groups/{}", group_id)));
+
+    // Pages run across the boundary between the two types
+    for (start_index, expected) in [(1, &user_id), (2, &group_id)] {
+        let page = root_search(
+            &server,
+            json!({"filter": "displayName sw \"Searchable\"", "startIndex": start_index, "count": 1}),
+        )
+        .await;
+        assert_eq!(page["totalResults"], 2);
+        assert_eq!(page["startIndex"], start_index);
+        assert_eq!(resource_ids(&page), vec![expected.clone()]);
+    }
+    let past_end = root_search(
+        &server,
+        json!({"filter": "displayName sw \"Searchable\"", "startIndex": 3, "count": 10}),
+    )
+    .await;
+    assert_eq!(past_end["totalResults"], 2);
+    assert!(resource_ids(&past_end).is_empty());
+
+    // Without a filter every resource matches
+    let all = root_search(&server, json!({"count": 0})).await;
+    assert_eq!(all["totalResults"], 4);
+
+    // An attribute only one type has matches only that type
+    let users_only = root_search(&server, json!({"filter": "userName eq \"search.person\""})).await;
+    assert_eq!(resource_ids(&users_only), vec![user_id]);
+
+    // A SearchRequest must say so
+    server
+        .post("/scim/v2/.search")
+        .json(&json!({"filter": "displayName pr"}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+async fn root_search_resource_type_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user = common::create_test_user_json("typed.person", "Typed", "Person");
+    user["displayName"] = json!("Typed Person");
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let group_id = create_group_with_members(&server, "Typed Team", json!([])).await;
+
+    // No resourceType predicate: both types are returned
+    let body = root_search(&server, json!({"filter": "displayName sw \"Typed\""})).await;
+    assert_eq!(resource_ids(&body), vec![user_id.clone(), group_id.clone()]);
+
+    let users = root_search(&server, json!({"filter": "meta.resourceType eq \"User\""})).await;
+    assert_eq!(users["totalResults"], 1);
+    assert_eq!(resource_ids(&users), vec![user_id.clone()]);
+
+    let groups = root_search(&server, json!({"filter": "meta.resourceType eq \"Group\""})).await;
+    assert_eq!(groups["totalResults"], 1);
+    assert_eq!(resource_ids(&groups), vec![group_id.clone()]);
+
+    // The rest of the filter still applies to the selected type
+    let body = root_search(
+        &server,
+        json!({"filter": "displayName sw \"Typed\" and meta.resourceType eq \"Group\""}),
+    )
+    .await;
+    assert_eq!(resource_ids(&body), vec![group_id]);
+    let body = root_search(
+        &server,
+        json!({"filter": "meta.resourceType eq \"User\" and displayName eq \"Nobody\""}),
+    )
+    .await;
+    assert_eq!(body["totalResults"], 0);
+
+    // An attribute only Users define, restricted to Groups, matches nothing
+    let body = root_search(
+        &server,
+        json!({"filter": "meta.resourceType eq \"Group\" and userName eq \"typed.person\""}),
+    )
+    .await;
+    assert_eq!(body["totalResults"], 0);
+
+    // Conflicting or unknown types match nothing
+    for filter in [
+        "meta.resourceType eq \"User\" and meta.resourceType eq \"Group\"",
+        "meta.resourceType eq \"Device\"",
+    ] {
+        let body = root_search(&server, json!({ "filter": filter })).await;
+        assert_eq!(body["totalResults"], 0, "{}", filter);
+    }
+}
+
+async fn patch_user_ops(server: &TestServer, user_id: &str, operations: Value) -> Value {
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": operations
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.json()
+}
+
+async fn enterprise_extension_patch_paths_test(db_type: TestDatabaseType) {
+    const ENTERPRISE: &str = "urn:ietf:params:scim:schemas:extension:enterprise:2.0:User";
+
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(
+            "azure.user",
+            "Azure",
+            "User",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    // URN plus colon path to an extension attribute
+    let user = patch_user_ops(
+        &server,
+        &user_id,
+        json!([{"op": "add", "path": format!("{}:manager", ENTERPRISE), "value": {"value": "mgr-1"}}]),
+    )
+    .await;
+    assert_eq!(user[ENTERPRISE]["manager"]["value"], "mgr-1");
+    assert!(user["schemas"]
+        .as_array()
+        .unwrap()
+        .contains(&json!(ENTERPRISE)));
+
+    // Nested form
+    let user = patch_user_ops(
+        &server,
+        &user_id,
+        json!([{"op": "replace", "path": format!("{}:manager.value", ENTERPRISE), "value": "mgr-2"}]),
+    )
+    .await;
+    assert_eq!(user[ENTERPRISE]["manager"]["value"], "mgr-2");
+
+    // No path, value keyed by the full attribute URN
+    let user = patch_user_ops(
+        &server,
+        &user_id,
+        json!([{"op": "add", "value": {format!("{}:department", ENTERPRISE): "Sales"}}]),
+    )
+    .await;
+    assert_eq!(user[ENTERPRISE]["department"], "Sales");
+    assert_eq!(user[ENTERPRISE]["manager"]["value"], "mgr-2");
+
+    let stored: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+    assert_eq!(stored[ENTERPRISE], user[ENTERPRISE]);
+
+    // Removing the last extension attribute drops the extension schema
+    let user = patch_user_ops(
+        &server,
+        &user_id,
+        json!([
+            {"op": "remove", "path": format!("{}:manager", ENTERPRISE)},
+            {"op": "remove", "path": format!("{}:department", ENTERPRISE)}
+        ]),
+    )
+    .await;
+    assert!(user[ENTERPRISE].is_null());
+    assert_eq!(
+        user["schemas"],
+        json!(["urn:ietf:params:scim:schemas:core:2.0:User"])
+    );
+}
+
+async fn patch_request(
+    server: &TestServer,
+    resource: &str,
+    id: &str,
+    operations: Value,
+) -> axum_test::TestResponse {
+    server
+        .patch(&format!("/scim/v2/{}/{}", resource, id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": operations
+        }))
+        .await
+}
+
+async fn patch_atomicity_test(db_type: TestDatabaseType) {
+    let mut tenant_config = common::create_test_app_config();
+    // Case-insensitive externalIds are checked after the resource is written
+    tenant_config.tenants[2].external_id_case_insensitive = true;
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(
+            "atomic.user",
+            "Atomic",
+            "User",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let before: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+
+    // The first two operations are valid, the third is not
+    let response = patch_request(
+        &server,
+        "Users",
+        &user_id,
+        json!([
+            {"op": "replace", "path": "displayName", "value": "Half Applied"},
+            {"op": "add", "path": "title", "value": "Engineer"},
+            {"op": "replace", "path": "active", "value": "maybe"}
+        ]),
+    )
+    .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let after: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+    assert_eq!(after, before, "user changed for {:?}", db_type);
+
+    // The last operation fails only in the backend, once the patched user
+    // has been written
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "atomic.other",
+            "externalId": "Atomic-Ext"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let other_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let response = patch_request(
+        &server,
+        "Users",
+        &user_id,
+        json!([
+            {"op": "replace", "path": "displayName", "value": "Half Applied"},
+            {"op": "add", "path": "title", "value": "Engineer"},
+            {"op": "replace", "path": "externalId", "value": "atomic-ext"}
+        ]),
+    )
+    .await;
+    assert_uniqueness_conflict(&response, "externalId", &other_id);
+
+    let after: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+    assert_eq!(after, before, "user changed for {:?}", db_type);
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Atomic Group"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let before: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+
+    let response = patch_request(
+        &server,
+        "Groups",
+        &group_id,
+        json!([
+            {"op": "replace", "path": "displayName", "value": "Half Applied"},
+            {"op": "add", "path": "members", "value": [{"value": user_id, "type": "User"}]},
+            {"op": "remove", "value": {"displayName": "Half Applied"}}
+        ]),
+    )
+    .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let after: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_eq!(after, before, "group changed for {:?}", db_type);
+}
+
+async fn external_id_case_insensitive_test(db_type: TestDatabaseType) {
+    let mut app_config = common::create_test_app_config();
+    app_config.tenants[2].external_id_case_insensitive = true;
+    let (app, _test_db) = common::setup_test_app_with_db(app_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ci.holder",
+            "externalId": "CI-Ext-001"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let holder: Value = response.json();
+    // Stored as sent; only comparisons ignore case
+    assert_eq!(holder["externalId"], "CI-Ext-001");
+    let holder_id = holder["id"].as_str().unwrap().to_string();
+
+    for filter in [
+        "externalId eq \"ci-ext-001\"",
+        "externalId eq \"CI-EXT-001\"",
+    ] {
+        let response = server
+            .get("/scim/v2/Users")
+            .add_query_param("filter", filter)
+            .await;
+        response.assert_status(StatusCode::OK);
+        let list: Value = response.json();
+        assert_eq!(list["totalResults"], 1, "{} on {:?}", filter, db_type);
+        assert_eq!(list["Resources"][0]["id"], holder_id.as_str());
+        assert_eq!(list["Resources"][0]["externalId"], "CI-Ext-001");
+    }
+
+    // A value differing only in case is a duplicate
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ci.other",
+            "externalId": "ci-ext-001"
+        }))
+        .await;
+    assert_uniqueness_conflict(&response, "externalId", &holder_id);
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "CI Group",
+            "externalId": "CI-Group"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group: Value = response.json();
+    assert_eq!(group["externalId"], "CI-Group");
+    let group_id = group["id"].as_str().unwrap().to_string();
+
+    let response = patch_request(
+        &server,
+        "Groups",
+        &group_id,
+        json!([{"op": "replace", "path": "externalId", "value": "CI-Group-Renamed"}]),
+    )
+    .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["externalId"], "CI-Group-Renamed");
+
+    let response = server
+        .get("/scim/v2/Groups")
+        .add_query_param("filter", "externalId eq \"ci-group-RENAMED\"")
+        .await;
+    let list: Value = response.json();
+    assert_eq!(list["totalResults"], 1, "group on {:?}", db_type);
+    assert_eq!(list["Resources"][0]["id"], group_id.as_str());
+
+    // Tenants without the flag keep externalId caseExact
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ci.exact",
+            "externalId": "CI-Ext-001"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_query_param("filter", "externalId eq \"ci-ext-001\"")
+        .await;
+    assert_eq!(response.json::<Value>()["totalResults"], 0);
+}
+
+async fn filter_ids(server: &TestServer, endpoint: &str, filter: &str) -> (i64, Vec<String>) {
+    let response = server.get(endpoint).add_query_param("filter", filter).await;
+    response.assert_status(StatusCode::OK);
+    let list: Value = response.json();
+    (list["totalResults"].as_i64().unwrap(), resource_ids(&list))
+}
+
+async fn external_id_lookup_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut user = common::create_test_user_json("lookup.user", "Lookup", "User");
+    user["externalId"] = json!("Obj-0001");
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let mut other = common::create_test_user_json("lookup.other", "Lookup", "Other");
+    other["externalId"] = json!("Obj-0002");
+    server
+        .post("/scim/v2/Users")
+        .json(&other)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // Same answer as a filter evaluated on the stored document
+    let by_lookup = filter_ids(&server, "/scim/v2/Users", "externalId eq \"Obj-0001\"").await;
+    let by_document = filter_ids(
+        &server,
+        "/scim/v2/Users",
+        "not (externalId ne \"Obj-0001\")",
+    )
+    .await;
+    assert_eq!(by_lookup, (1, vec![user_id.clone()]), "{:?}", db_type);
+    assert_eq!(by_lookup, by_document);
+
+    // caseExact, and missing values match nothing
+    for filter in ["externalId eq \"obj-0001\"", "externalId eq \"Obj-9999\""] {
+        assert_eq!(
+            filter_ids(&server, "/scim/v2/Users", filter).await,
+            (0, vec![]),
+            "{} on {:?}",
+            filter,
+            db_type
+        );
+    }
+
+    // Pagination still applies to the single match
+    let response = server
+        .get("/scim/v2/Users")
+        .add_query_param("filter", "externalId eq \"Obj-0001\"")
+        .add_query_param("startIndex", "2")
+        .await;
+    let list: Value = response.json();
+    assert_eq!(list["totalResults"], 1);
+    assert!(resource_ids(&list).is_empty());
+
+    // Combined with other conditions
+    assert_eq!(
+        filter_ids(
+            &server,
+            "/scim/v2/Users",
+            "externalId eq \"Obj-0001\" and userName eq \"lookup.user\""
+        )
+        .await,
+        (1, vec![user_id.clone()])
+    );
+    assert_eq!(
+        filter_ids(
+            &server,
+            "/scim/v2/Users",
+            "externalId eq \"Obj-0001\" and userName eq \"lookup.other\""
+        )
+        .await
+        .0,
+        0
+    );
+
+    // PUT and PATCH keep the lookup in sync
+    user["externalId"] = json!("Obj-0003");
+    server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&user)
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(
+        filter_ids(&server, "/scim/v2/Users", "externalId eq \"Obj-0001\"")
+            .await
+            .0,
+        0
+    );
+    assert_eq!(
+        filter_ids(&server, "/scim/v2/Users", "externalId eq \"Obj-0003\"").await,
+        (1, vec![user_id.clone()])
+    );
+    patch_request(
+        &server,
+        "Users",
+        &user_id,
+        json!([{"op": "replace", "path": "externalId", "value": "Obj-0004"}]),
+    )
+    .await
+    .assert_status(StatusCode::OK);
+    assert_eq!(
+        filter_ids(&server, "/scim/v2/Users", "externalId eq \"Obj-0004\"").await,
+        (1, vec![user_id])
+    );
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Lookup Group",
+            "externalId": "Grp-0001"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    assert_eq!(
+        filter_ids(&server, "/scim/v2/Groups", "externalId eq \"Grp-0001\"").await,
+        (1, vec![group_id.clone()])
+    );
+    assert_eq!(
+        filter_ids(&server, "/scim/v2/Groups", "externalId eq \"GRP-0001\"")
+            .await
+            .0,
+        0
+    );
+    patch_request(
+        &server,
+        "Groups",
+        &group_id,
+        json!([{"op": "replace", "path": "externalId", "value": "Grp-0002"}]),
+    )
+    .await
+    .assert_status(StatusCode::OK);
+    assert_eq!(
+        filter_ids(&server, "/scim/v2/Groups", "externalId eq \"Grp-0002\"").await,
+        (1, vec![group_id])
+    );
+}
+
+async fn put_body_id_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json("put.id", "Put", "Id"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let group_id = create_group_with_members(&server, "Put Id Group", json!([])).await;
+    let other_id = "2819c223-7f76-453a-919d-413861904646";
+
+    // Users: matching id and absent id are accepted, a different id is not
+    let mut payload = common::create_test_user_json("put.id", "Matching", "Id");
+    payload["id"] = json!(user_id);
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&payload)
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["id"], user_id);
+
+    let payload = common::create_test_user_json("put.id", "Absent", "Id");
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&payload)
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["name"]["givenName"], "Absent");
+
+    let mut payload = common::create_test_user_json("put.id", "Mismatch", "Id");
+    payload["id"] = json!(other_id);
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&payload)
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "mutability");
+
+    let user: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+    assert_eq!(user["id"], user_id);
+    assert_eq!(user["name"]["givenName"], "Absent");
+    server
+        .get(&format!("/scim/v2/Users/{}", other_id))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // Groups follow the same rule
+    let group_payload = |display_name: &str, id: Option<&str>| {
+        let mut payload = json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": display_name
+        });
+        if let Some(id) = id {
+            payload["id"] = json!(id);
+        }
+        payload
+    };
+
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&group_payload("Matching Group", Some(&group_id)))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["id"], group_id);
+
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&group_payload("Absent Group", None))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["displayName"], "Absent Group");
+
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&group_payload("Mismatch Group", Some(other_id)))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "mutability");
+
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_eq!(group["id"], group_id);
+    assert_eq!(group["displayName"], "Absent Group");
+}
+
+async fn meta_datetime_range_filter_test(db_type: TestDatabaseType) {
+    let tenant_config = common::create_test_app_config();
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let mut last_modified = Vec::new();
+    for name in ["range-a", "range-b", "range-c"] {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(name, "Range", "User"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let user: Value = response.json();
+        let value = user["meta"]["lastModified"].as_str().unwrap();
+        last_modified.push(chrono::DateTime::parse_from_rfc3339(value).unwrap());
+        // Keep the timestamps apart at millisecond precision
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let utc = |index: usize| {
+        last_modified[index]
+            .with_timezone(&chrono::Utc)
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+    };
+
+    // A closed range includes both ends
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "range-",
+            &format!(
+                "meta.lastModified ge \"{}\" and meta.lastModified le \"{}\"",
+                utc(0),
+                utc(1)
+            )
+        )
+        .await,
+        vec!["range-a", "range-b"]
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "range-",
+            &format!(
+                "meta.lastModified gt \"{}\" and meta.lastModified lt \"{}\"",
+                utc(0),
+                utc(2)
+            )
+        )
+        .await,
+        vec!["range-b"]
+    );
+
+    // Values are compared as instants, whatever offset they are written in
+    let tokyo = chrono::FixedOffset::east_opt(9 * 3600).unwrap();
+    let tokyo_b = last_modified[1]
+        .with_timezone(&tokyo)
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, false);
+    assert!(tokyo_b.ends_with("+09:00"));
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "range-",
+            &format!("meta.lastModified gt \"{}\"", tokyo_b)
+        )
+        .await,
+        vec!["range-c"]
+    );
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "range-",
+            &format!("meta.created lt \"{}\"", tokyo_b)
+        )
+        .await,
+        vec!["range-a"]
+    );
+
+    // Whole seconds and microseconds are accepted too
+    assert_eq!(
+        prefixed_filter_matches(
+            &server,
+            "range-",
+            "meta.created gt \"2000-01-01T00:00:00Z\""
+        )
+        .await,
+        vec!["range-a", "range-b", "range-c"]
+    );
+    assert!(prefixed_filter_matches(
+        &server,
+        "range-",
+        "meta.lastModified lt \"2000-01-01T00:00:00.000001Z\""
+    )
+    .await
+    .is_empty());
+}
+
+async fn delete_returns_resource_test(db_type: TestDatabaseType) {
+    let mut tenant_config = common::create_test_app_config();
+    // /scim/v2 answers like legacy clients expect, /tenant-a keeps the default
+    tenant_config.tenants[2].compatibility = Some(CompatibilityConfig {
+        delete_returns_resource: true,
+        ..Default::default()
+    });
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    for base in ["/scim/v2", "/tenant-a/scim/v2"] {
+        let response = server
+            .post(&format!("{}/Users", base))
+            .json(&common::create_test_user_json(
+                "deleted.user",
+                "Deleted",
+                "User",
+            ))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let user: Value = response.json();
+        let user_id = user["id"].as_str().unwrap();
+
+        let response = server
+            .post(&format!("{}/Groups", base))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+                "displayName": "Deleted Group",
+                "members": [{"value": user_id, "type": "User"}]
+            }))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let group: Value = response.json();
+        let group_id = group["id"].as_str().unwrap();
+
+        let group_response = server
+            .delete(&format!("{}/Groups/{}", base, group_id))
+            .await;
+        let user_response = server.delete(&format!("{}/Users/{}", base, user_id)).await;
+
+        if base == "/scim/v2" {
+            // The resource as it was before the delete
+            group_response.assert_status(StatusCode::OK);
+            let deleted: Value = group_response.json();
+            assert_eq!(deleted["id"], group_id);
+            assert_eq!(deleted["displayName"], "Deleted Group");
+            assert_eq!(deleted["members"][0]["value"], user_id);
+            assert_eq!(deleted["meta"]["location"], group["meta"]["location"]);
+
+            user_response.assert_status(StatusCode::OK);
+            let deleted: Value = user_response.json();
+            assert_eq!(deleted["id"], user_id);
+            assert_eq!(deleted["userName"], "deleted.user");
+            assert_eq!(deleted["meta"]["version"], user["meta"]["version"]);
+        } else {
+            group_response.assert_status(StatusCode::NO_CONTENT);
+            assert!(group_response.as_bytes().is_empty());
+            user_response.assert_status(StatusCode::NO_CONTENT);
+            assert!(user_response.as_bytes().is_empty());
+        }
+
+        // Deleted either way
+        server
+            .get(&format!("{}/Users/{}", base, user_id))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        server
+            .get(&format!("{}/Groups/{}", base, group_id))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        server
+            .delete(&format!("{}/Users/{}", base, user_id))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+}
+
+async fn put_creates_missing_test(db_type: TestDatabaseType) {
+    let mut tenant_config = common::create_test_app_config();
+    // /scim/v2 upserts by id, /tenant-a keeps the standard 404
+    tenant_config.tenants[2].compatibility = Some(CompatibilityConfig {
+        put_creates_missing: true,
+        ..Default::default()
+    });
+    let (app, _test_db) = common::setup_test_app_with_db(tenant_config, db_type)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let user_id = "6f2b1c5e-3a4d-4e8f-9b0a-1c2d3e4f5a6b";
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&common::create_test_user_json(
+            "migrated.user",
+            "Migrated",
+            "User",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert_eq!(created["id"], user_id);
+    assert_eq!(created["meta"]["resourceType"], "User");
+    assert_eq!(created["meta"]["created"], created["meta"]["lastModified"]);
+    let location = created["meta"]["location"].as_str().unwrap();
+    assert!(location.ends_with(&format!("/scim/v2/Users/{}", user_id)));
+    assert_eq!(response.header("location"), location);
+
+    // Now it exists, so the next PUT is a normal replace
+    let mut replacement = common::create_test_user_json("migrated.user", "Renamed", "User");
+    replacement["id"] = json!(user_id);
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&replacement)
+        .await;
+    response.assert_status_ok();
+    let updated: Value = response.json();
+    assert_eq!(updated["name"]["givenName"], "Renamed");
+    // Same creation instant (backends may render it with more precision)
+    let created_at = |resource: &Value| {
+        chrono::DateTime::parse_from_rfc3339(resource["meta"]["created"].as_str().unwrap())
+            .unwrap()
+            .timestamp_millis()
+    };
+    assert_eq!(created_at(&updated), created_at(&created));
+    assert_ne!(updated["meta"]["version"], created["meta"]["version"]);
+
+    // userName stays unique
+    let response = server
+        .put("/scim/v2/Users/0a9b8c7d-6e5f-4a3b-8c1d-0e9f8a7b6c5d")
+        .json(&common::create_test_user_json(
+            "MIGRATED.user",
+            "Other",
+            "User",
+        ))
+        .await;
+    response.assert_status(StatusCode::CONFLICT);
+    assert_eq!(response.json::<Value>()["scimType"], "uniqueness");
+
+    // If-Match is checked only against an existing resource
+    let response = server
+        .put("/scim/v2/Users/2b3c4d5e-6f70-4819-a2b3-c4d5e6f7a8b9")
+        .add_header("If-Match", "W/\"1\"")
+        .json(&common::create_test_user_json(
+            "conditional.user",
+            "Conditional",
+            "User",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .add_header("If-Match", "W/\"stale\"")
+        .json(&replacement)
+        .await;
+    response.assert_status(StatusCode::PRECONDITION_FAILED);
+
+    // Ids must look like the ones the server generates
+    let response = server
+        .put("/scim/v2/Users/user-42")
+        .json(&common::create_test_user_json("odd.id", "Odd", "Id"))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "invalidValue");
+
+    let group_id = "1e2d3c4b-5a69-4788-96a5-b4c3d2e1f0a9";
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Migrated Group",
+            "members": [{"value": user_id, "type": "User"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group: Value = response.json();
+    assert_eq!(group["id"], group_id);
+    server
+        .put("/scim/v2/Groups/3c4d5e6f-7081-4a2b-b3c4-d5e6f7a8b9c0")
+        .add_header("If-Match", "W/\"1\"")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Conditional Group"
+        }))
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(group["members"][0]["value"], user_id);
+    assert_eq!(
+        response.header("location"),
+        group["meta"]["location"].as_str().unwrap()
+    );
+    server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Migrated Group Renamed"
+        }))
+        .await
+        .assert_status_ok();
+    let response = server.get(&format!("/scim/v2/Users/{}", user_id)).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["userName"], "migrated.user");
+
+    // Flag off: unknown ids stay 404 and nothing is created
+    let missing = "7c6b5a49-3827-4615-a4b3-c2d1e0f9a8b7";
+    server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", missing))
+        .json(&common::create_test_user_json(
+            "not.created",
+            "Not",
+            "Created",
+        ))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .put(&format!("/tenant-a/scim/v2/Groups/{}", missing))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Not Created"
+        }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", missing))
+        .add_header("If-Match", "W/\"1\"")
+        .json(&common::create_test_user_json(
+            "not.created",
+            "Not",
+            "Created",
+        ))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", missing))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+// Generate matrix tests for each test function
+matrix_test!(user_crud, user_crud_test);
+matrix_test!(group_crud, group_crud_test);
+matrix_test!(group_list, group_list_test);
+matrix_test!(group_patch_operations, group_patch_operations_test);
+matrix_test!(group_membership, group_membership_test);
+matrix_test!(group_to_group_membership, group_to_group_membership_test);
+matrix_test!(group_error_scenarios, group_error_scenarios_test);
+matrix_test!(enhanced_filter_search, enhanced_filter_search_test);
+matrix_test!(
+    enhanced_group_filter_search,
+    enhanced_group_filter_search_test
+);
+matrix_test!(case_sensitivity_filtering, case_sensitivity_filtering_test);
+matrix_test!(nested_attributes_filter, nested_attributes_filter_test);
+matrix_test!(
+    multi_value_attributes_filter,
+    multi_value_attributes_filter_test
+);
+matrix_test!(complex_query_patterns, complex_query_patterns_test);
+matrix_test!(advanced_filter_operators, advanced_filter_operators_test);
+matrix_test!(edge_case_filtering, edge_case_filtering_test);
+matrix_test!(last_modified_sort, last_modified_sort_test);
+matrix_test!(group_membership_rollback, group_membership_rollback_test);
+matrix_test!(group_patch_without_path, group_patch_without_path_test);
+matrix_test!(present_operator_nested, present_operator_nested_test);
+matrix_test!(value_path_filter, value_path_filter_test);
+matrix_test!(not_equal_filter, not_equal_filter_test);
+matrix_test!(user_rename_conflict, user_rename_conflict_test);
+matrix_test!(
+    user_rename_self_case_change,
+    user_rename_self_case_change_test
+);
+matrix_test!(user_rename_conflict_race, user_rename_conflict_race_test);
+matrix_test!(
+    patch_active_representations,
+    patch_active_representations_test
+);
+matrix_test!(meta_attribute_filter, meta_attribute_filter_test);
+matrix_test!(patch_active_fast_path, patch_active_fast_path_test);
+matrix_test!(multi_condition_filter, multi_condition_filter_test);
+matrix_test!(group_member_batch, group_member_batch_test);
+matrix_test!(locale_filter, locale_filter_test);
+matrix_test!(
+    multi_valued_sub_attribute_filter,
+    multi_valued_sub_attribute_filter_test
+);
+matrix_test!(
+    group_member_display_filter,
+    group_member_display_filter_test
+);
+matrix_test!(filtered_pagination_total, filtered_pagination_total_test);
+matrix_test!(root_search, root_search_test);
+matrix_test!(root_search_resource_type, root_search_resource_type_test);
+matrix_test!(external_id_lookup, external_id_lookup_test);
+matrix_test!(
+    enterprise_extension_patch_paths,
+    enterprise_extension_patch_paths_test
+);
+matrix_test!(patch_atomicity, patch_atomicity_test);
+matrix_test!(
+    external_id_case_insensitive,
+    external_id_case_insensitive_test
+);
+matrix_test!(group_members_replace, group_members_replace_test);
+matrix_test!(effective_members, effective_members_test);
+matrix_test!(put_body_id, put_body_id_test);
+matrix_test!(meta_datetime_range_filter, meta_datetime_range_filter_test);
+matrix_test!(delete_returns_resource, delete_returns_resource_test);
+matrix_test!(put_creates_missing, put_creates_missing_test);
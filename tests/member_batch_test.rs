@@ -0,0 +1,236 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+use scim_server::config::{AuthConfig, MemberBatchConfig};
+
+async fn setup_server(member_batch: MemberBatchConfig) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].member_batch = member_batch;
+    })
+    .await
+}
+
+fn enabled() -> MemberBatchConfig {
+    MemberBatchConfig {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+async fn create_users(server: &TestServer, prefix: &str, count: usize) -> Vec<String> {
+    let mut ids = Vec::with_capacity(count);
+    for i in 0..count {
+        let response = server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(
+                &format!("{}.{}", prefix, i),
+                "Batch",
+                "User",
+            ))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+    }
+    ids
+}
+
+async fn create_group(server: &TestServer, display_name: &str) -> Value {
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": display_name
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json()
+}
+
+#[tokio::test]
+async fn test_member_batch_add_5k() {
+    let server = setup_server(enabled()).await;
+    let user_ids = create_users(&server, "bulk", 5000).await;
+    let group = create_group(&server, "All Staff").await;
+    let group_id = group["id"].as_str().unwrap();
+
+    let response = server
+        .post(&format!("/scim/v2/Groups/{}/members:add", group_id))
+        .json(&json!({ "ids": user_ids }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"added": 5000, "already_present": 0, "not_found": []})
+    );
+
+    // One version bump and a fresh lastModified for the whole batch
+    let response = server.get(&format!("/scim/v2/Groups/{}", group_id)).await;
+    response.assert_status(StatusCode::OK);
+    let fetched: Value = response.json();
+    assert_eq!(fetched["members"].as_array().unwrap().len(), 5000);
+    assert_eq!(fetched["members"][0]["type"], "User");
+    assert_eq!(fetched["meta"]["version"], "W/\"2\"");
+    assert_ne!(
+        fetched["meta"]["lastModified"],
+        group["meta"]["lastModified"]
+    );
+
+    // The users see the new membership too
+    let response = server
+        .get(&format!("/scim/v2/Users/{}", user_ids[4999]))
+        .await;
+    assert_eq!(response.json::<Value>()["groups"][0]["value"], group_id);
+}
+
+#[tokio::test]
+async fn test_member_batch_overlapping_readd_is_idempotent() {
+    let server = setup_server(enabled()).await;
+    let user_ids = create_users(&server, "overlap", 6).await;
+    let nested = create_group(&server, "Nested").await;
+    let group = create_group(&server, "Overlap").await;
+    let group_id = group["id"].as_str().unwrap();
+    let add_url = format!("/scim/v2/Groups/{}/members:add", group_id);
+
+    let response = server
+        .post(&add_url)
+        .json(&json!({ "ids": user_ids[..4] }))
+        .await;
+    assert_eq!(response.json::<Value>()["added"], 4);
+
+    // Overlapping batch, with a repeated id and a nested group
+    let mut ids: Vec<Value> = user_ids[2..].iter().map(|id| json!(id)).collect();
+    ids.push(json!(user_ids[5]));
+    ids.push(nested["id"].clone());
+    let response = server.post(&add_url).json(&json!({ "ids": ids })).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"added": 3, "already_present": 2, "not_found": []})
+    );
+
+    // Re-sending the same batch changes nothing
+    let version = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json::<Value>()["meta"]["version"]
+        .clone();
+    let response = server.post(&add_url).json(&json!({ "ids": ids })).await;
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"added": 0, "already_present": 5, "not_found": []})
+    );
+    let fetched: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_eq!(fetched["meta"]["version"], version);
+    let members = fetched["members"].as_array().unwrap();
+    assert_eq!(members.len(), 7);
+    assert!(members
+        .iter()
+        .any(|m| m["value"] == nested["id"] && m["type"] == "Group"));
+
+    let response = server
+        .post(&format!("/scim/v2/Groups/{}/members:remove", group_id))
+        .json(&json!({ "ids": [user_ids[0], user_ids[1], user_ids[1], "missing"] }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({"removed": 2, "not_members": 1})
+    );
+    let fetched: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_eq!(fetched["members"].as_array().unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_member_batch_reports_not_found() {
+    let server = setup_server(enabled()).await;
+    let user_ids = create_users(&server, "missing", 2).await;
+    let group = create_group(&server, "Reporting").await;
+    let group_id = group["id"].as_str().unwrap();
+
+    let response = server
+        .post(&format!("/scim/v2/Groups/{}/members:add", group_id))
+        .json(&json!({
+            "ids": [
+                user_ids[0],
+                "00000000-0000-0000-0000-000000000000",
+                user_ids[1],
+                "not-a-uuid",
+                group_id
+            ]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>(),
+        json!({
+            "added": 2,
+            "already_present": 0,
+            "not_found": ["00000000-0000-0000-0000-000000000000", "not-a-uuid", group_id]
+        })
+    );
+
+    // Unknown groups are a 404
+    let response = server
+        .post("/scim/v2/Groups/00000000-0000-0000-0000-000000000000/members:add")
+        .json(&json!({ "ids": user_ids }))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_member_batch_gating() {
+    // Disabled by default
+    let server = setup_server(MemberBatchConfig::default()).await;
+    let group = create_group(&server, "Gated").await;
+    let response = server
+        .post(&format!(
+            "/scim/v2/Groups/{}/members:add",
+            group["id"].as_str().unwrap()
+        ))
+        .json(&json!({ "ids": [] }))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+
+    // Batches above the limit are rejected as a whole
+    let server = setup_server(MemberBatchConfig {
+        enabled: true,
+        max_batch_size: 2,
+    })
+    .await;
+    let user_ids = create_users(&server, "limit", 3).await;
+    let group = create_group(&server, "Limited").await;
+    let response = server
+        .post(&format!(
+            "/scim/v2/Groups/{}/members:add",
+            group["id"].as_str().unwrap()
+        ))
+        .json(&json!({ "ids": user_ids }))
+        .await;
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Tenant authentication applies as for any other endpoint
+    let server = common::test_server(|app_config| {
+        app_config.tenants[2].member_batch = enabled();
+        app_config.tenants[2].auth = AuthConfig {
+            auth_type: "bearer".to_string(),
+            token: Some("batch-token".to_string()),
+            basic: None,
+            basic_users: Vec::new(),
+            tokens: Vec::new(),
+        };
+    })
+    .await;
+    let response = server
+        .post("/scim/v2/Groups/00000000-0000-0000-0000-000000000000/members:remove")
+        .json(&json!({ "ids": [] }))
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,90 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|_| {}).await
+}
+
+#[tokio::test]
+async fn test_user_meta_resource_type_is_canonical() {
+    let server = setup_server().await;
+
+    let mut user = common::create_test_user_json("meta.user", "Meta", "User");
+    user["meta"] = json!({"resourceType": "user"});
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert_eq!(created["meta"]["resourceType"], "User");
+    let user_id = created["id"].as_str().unwrap();
+
+    let response = server.get(&format!("/scim/v2/Users/{}", user_id)).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["meta"]["resourceType"], "User");
+
+    let response = server
+        .get("/scim/v2/Users")
+        .add_query_param("filter", "userName eq \"meta.user\"")
+        .await;
+    response.assert_status_ok();
+    let list: Value = response.json();
+    assert_eq!(list["Resources"][0]["meta"]["resourceType"], "User");
+
+    let mut replacement = common::create_test_user_json("meta.user", "Meta", "Replaced");
+    replacement["meta"] = json!({"resourceType": "USER"});
+    let response = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&replacement)
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["meta"]["resourceType"], "User");
+
+    let response = server.get(&format!("/scim/v2/Users/{}", user_id)).await;
+    assert_eq!(response.json::<Value>()["meta"]["resourceType"], "User");
+}
+
+#[tokio::test]
+async fn test_group_meta_resource_type_is_canonical() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Meta Group",
+            "meta": {"resourceType": "group"}
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert_eq!(created["meta"]["resourceType"], "Group");
+    let group_id = created["id"].as_str().unwrap();
+
+    let response = server.get(&format!("/scim/v2/Groups/{}", group_id)).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["meta"]["resourceType"], "Group");
+
+    let response = server
+        .get("/scim/v2/Groups")
+        .add_query_param("filter", "displayName eq \"Meta Group\"")
+        .await;
+    response.assert_status_ok();
+    let list: Value = response.json();
+    assert_eq!(list["Resources"][0]["meta"]["resourceType"], "Group");
+
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Meta Group",
+            "meta": {"resourceType": "GROUP"}
+        }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["meta"]["resourceType"], "Group");
+
+    let response = server.get(&format!("/scim/v2/Groups/{}", group_id)).await;
+    assert_eq!(response.json::<Value>()["meta"]["resourceType"], "Group");
+}
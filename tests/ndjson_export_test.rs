@@ -0,0 +1,147 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        // Tenant 3 (/scim/v2) allows exports; tenant 1 (/tenant-a) does not
+        app_config.tenants[2].ndjson_export = true;
+    })
+    .await
+}
+
+/// Parses an NDJSON body, asserting every line is a single JSON object
+fn parse_ndjson(body: &str) -> Vec<Value> {
+    assert!(body.ends_with('\n'), "body must end with a newline");
+    body.lines()
+        .map(|line| {
+            let value: Value = serde_json::from_str(line)
+                .unwrap_or_else(|e| panic!("invalid NDJSON line {:?}: {}", line, e));
+            assert!(value.is_object(), "line is not an object: {}", line);
+            value
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_users_ndjson_export_spans_pages() {
+    let server = setup_server().await;
+
+    // More than one backend page so the export has to follow the cursor
+    let total = 150;
+    for i in 0..total {
+        server
+            .post("/scim/v2/Users")
+            .json(&common::create_test_user_json(
+                &format!("export.user{:03}", i),
+                "Export",
+                "User",
+            ))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let response = server.get("/scim/v2/Users?format=ndjson").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+
+    let users = parse_ndjson(&response.text());
+    assert_eq!(users.len(), total);
+    let user_names: HashSet<&str> = users
+        .iter()
+        .map(|user| user["userName"].as_str().unwrap())
+        .collect();
+    assert_eq!(user_names.len(), total, "every user exported exactly once");
+    for user in &users {
+        assert!(user.get("Resources").is_none());
+        assert_eq!(user["meta"]["resourceType"], "User");
+        assert!(user["meta"]["location"]
+            .as_str()
+            .unwrap()
+            .contains("/scim/v2/Users/"));
+    }
+}
+
+#[tokio::test]
+async fn test_groups_ndjson_export() {
+    let server = setup_server().await;
+
+    for name in ["Export A", "Export B", "Export C"] {
+        server
+            .post("/scim/v2/Groups")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+                "displayName": name
+            }))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let response = server.get("/scim/v2/Groups?format=ndjson").await;
+    response.assert_status(StatusCode::OK);
+
+    let groups = parse_ndjson(&response.text());
+    assert_eq!(groups.len(), 3);
+    assert!(groups.iter().all(|g| g["meta"]["resourceType"] == "Group"));
+}
+
+#[tokio::test]
+async fn test_ndjson_export_applies_attribute_selection() {
+    let server = setup_server().await;
+
+    server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(
+            "export.attrs",
+            "Export",
+            "Attrs",
+        ))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = server
+        .get("/scim/v2/Users?format=ndjson&attributes=userName")
+        .await;
+    response.assert_status(StatusCode::OK);
+
+    let users = parse_ndjson(&response.text());
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0]["userName"], "export.attrs");
+    assert!(users[0].get("id").is_some());
+    assert!(users[0].get("name").is_none());
+}
+
+#[tokio::test]
+async fn test_empty_ndjson_export() {
+    let server = setup_server().await;
+
+    let response = server.get("/scim/v2/Users?format=ndjson").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.text(), "");
+}
+
+#[tokio::test]
+async fn test_ndjson_export_rejections() {
+    let server = setup_server().await;
+
+    // Not enabled for tenant-a
+    let response = server.get("/tenant-a/scim/v2/Users?format=ndjson").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+
+    let response = server.get("/scim/v2/Groups?format=csv").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+
+    let response = server
+        .get("/scim/v2/Users?format=ndjson&filter=userName%20eq%20%22x%22")
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidFilter");
+}
@@ -0,0 +1,84 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+
+mod common;
+
+const PARENT: &str = "/tenant-a/scim/v2";
+const NESTED: &str = "/tenant-a/scim/v2/contractors";
+
+async fn setup_server(nested_first: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[1].path = NESTED.to_string();
+        if nested_first {
+            app_config.tenants.swap(0, 1);
+        }
+    })
+    .await
+}
+
+async fn create_user(server: &TestServer, base: &str, given: &str) -> String {
+    let response = server
+        .post(&format!("{}/Users", base))
+        .json(&common::create_test_user_json(
+            "shared.name",
+            given,
+            "Nested",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user: Value = response.json();
+    assert!(user["meta"]["location"]
+        .as_str()
+        .unwrap()
+        .contains(&format!("{}/Users/", base)));
+    user["id"].as_str().unwrap().to_string()
+}
+
+async fn listed_given_names(server: &TestServer, base: &str) -> Vec<String> {
+    let response = server.get(&format!("{}/Users", base)).await;
+    response.assert_status(StatusCode::OK);
+    response.json::<Value>()["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["name"]["givenName"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_nested_tenant_paths_are_isolated() {
+    // Tenant order in the configuration must not matter
+    for nested_first in [false, true] {
+        let server = setup_server(nested_first).await;
+
+        // The same userName can exist once in each tenant
+        let parent_id = create_user(&server, PARENT, "Parent").await;
+        let nested_id = create_user(&server, NESTED, "Contractor").await;
+
+        assert_eq!(listed_given_names(&server, PARENT).await, vec!["Parent"]);
+        assert_eq!(
+            listed_given_names(&server, NESTED).await,
+            vec!["Contractor"]
+        );
+
+        server
+            .get(&format!("{}/Users/{}", PARENT, parent_id))
+            .await
+            .assert_status(StatusCode::OK);
+        server
+            .get(&format!("{}/Users/{}", NESTED, nested_id))
+            .await
+            .assert_status(StatusCode::OK);
+
+        // Neither tenant sees the other's user
+        server
+            .get(&format!("{}/Users/{}", PARENT, nested_id))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        server
+            .get(&format!("{}/Users/{}", NESTED, parent_id))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,63 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+
+mod common;
+use scim_server::config::CompatibilityConfig;
+
+async fn setup_server(include_next_start_index: bool) -> TestServer {
+    let server = common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            include_next_start_index,
+            ..Default::default()
+        });
+    })
+    .await;
+
+    for i in 0..6 {
+        let user = common::create_test_user_json(&format!("page.user{}", i), "Page", "User");
+        server
+            .post("/scim/v2/Users")
+            .json(&user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+    server
+}
+
+async fn get_page(server: &TestServer, start_index: i64) -> Value {
+    let response = server
+        .get("/scim/v2/Users")
+        .add_query_param("startIndex", start_index)
+        .add_query_param("count", 2)
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn test_next_start_index_points_at_following_page() {
+    let server = setup_server(true).await;
+
+    // Three pages of two: 1-2, 3-4, 5-6
+    let first = get_page(&server, 1).await;
+    assert_eq!(first["totalResults"], 6);
+    assert_eq!(first["itemsPerPage"], 2);
+    assert_eq!(first["nextStartIndex"], 3);
+
+    let second = get_page(&server, 3).await;
+    assert_eq!(second["nextStartIndex"], 5);
+
+    let last = get_page(&server, 5).await;
+    assert_eq!(last["Resources"].as_array().unwrap().len(), 2);
+    assert!(last.get("nextStartIndex").is_none());
+}
+
+#[tokio::test]
+async fn test_next_start_index_absent_by_default() {
+    let server = setup_server(false).await;
+
+    let first = get_page(&server, 1).await;
+    assert_eq!(first["totalResults"], 6);
+    assert!(first.get("nextStartIndex").is_none());
+}
@@ -0,0 +1,88 @@
+//! OpenTelemetry request spans (`otel` feature)
+#![cfg(feature = "otel")]
+
+use axum_test::TestServer;
+use http::StatusCode;
+use opentelemetry::trace::{SpanKind, TraceId};
+use opentelemetry::{KeyValue, Value};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use scim_server::telemetry;
+
+mod common;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+    span.attributes
+        .iter()
+        .find(|KeyValue { key: k, .. }| k.as_str() == key)
+        .map(|kv| &kv.value)
+}
+
+#[tokio::test]
+async fn test_request_span_recorded_with_scim_attributes() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    telemetry::install(provider);
+
+    let app_config = common::create_test_app_config();
+    let tenant_id = app_config.tenants[2].id;
+    let app = common::setup_test_app(app_config).await.unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let user = common::create_test_user_json("otel.user", "Otel", "User");
+    server
+        .post("/scim/v2/Users")
+        .add_header(
+            "traceparent",
+            format!("00-{}-00f067aa0ba902b7-01", TRACE_ID),
+        )
+        .json(&user)
+        .await
+        .assert_status(StatusCode::CREATED);
+    server
+        .get("/scim/v2/Users/missing")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 2, "{:?}", spans);
+
+    let create = &spans[0];
+    assert_eq!(create.span_kind, SpanKind::Server);
+    assert_eq!(
+        create.span_context.trace_id(),
+        TraceId::from_hex(TRACE_ID).unwrap(),
+        "the caller's trace is continued"
+    );
+    assert!(create.parent_span_is_remote);
+    assert_eq!(
+        attribute(create, "scim.tenant_id"),
+        Some(&Value::I64(i64::from(tenant_id)))
+    );
+    assert_eq!(
+        attribute(create, "scim.resource_type"),
+        Some(&Value::from("Users"))
+    );
+    assert_eq!(
+        attribute(create, "scim.operation"),
+        Some(&Value::from("create"))
+    );
+    assert_eq!(
+        attribute(create, "http.response.status_code"),
+        Some(&Value::I64(201))
+    );
+
+    let get = &spans[1];
+    assert_ne!(
+        get.span_context.trace_id(),
+        TraceId::from_hex(TRACE_ID).unwrap()
+    );
+    assert_eq!(attribute(get, "scim.operation"), Some(&Value::from("get")));
+    assert_eq!(
+        attribute(get, "http.response.status_code"),
+        Some(&Value::I64(404))
+    );
+}
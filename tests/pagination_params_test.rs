@@ -0,0 +1,113 @@
+/// Validation of startIndex, count and sortOrder on the list endpoints
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+const ENDPOINTS: [&str; 2] = ["/scim/v2/Users", "/scim/v2/Groups"];
+
+async fn setup_server() -> TestServer {
+    let server = common::test_server(|_| {}).await;
+
+    for i in 0..3 {
+        let user = common::create_test_user_json(&format!("param.user{}", i), "Param", "User");
+        server
+            .post("/scim/v2/Users")
+            .json(&user)
+            .await
+            .assert_status(StatusCode::CREATED);
+        server
+            .post("/scim/v2/Groups")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+                "displayName": format!("Param Group {}", i)
+            }))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+    server
+}
+
+#[tokio::test]
+async fn test_malformed_pagination_params_are_rejected() {
+    let server = setup_server().await;
+
+    let cases = [
+        ("startIndex", "abc"),
+        ("startIndex", "NaN"),
+        ("startIndex", "99999999999999999999"),
+        ("startIndex", "2147483648"),
+        ("count", "abc"),
+        ("count", "1.5x"),
+        ("count", "-99999999999999999999"),
+        ("count", "2147483648"),
+        ("sortOrder", "desc"),
+        ("sortOrder", "random"),
+    ];
+    for endpoint in ENDPOINTS {
+        for (name, value) in cases {
+            let response = server
+                .get(endpoint)
+                .add_query_param(name, value)
+                .add_query_param("sortBy", "id")
+                .await;
+            response.assert_status(StatusCode::BAD_REQUEST);
+            let body: Value = response.json();
+            assert_eq!(
+                body["scimType"], "invalidValue",
+                "{} {}={}",
+                endpoint, name, value
+            );
+            assert!(
+                body["detail"].as_str().unwrap().contains(name),
+                "{} {}={}: {}",
+                endpoint,
+                name,
+                value,
+                body["detail"]
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_out_of_range_pagination_params_are_coerced() {
+    let server = setup_server().await;
+
+    for endpoint in ENDPOINTS {
+        // startIndex below 1 reads as 1
+        for start_index in ["0", "-5"] {
+            let response = server
+                .get(endpoint)
+                .add_query_param("startIndex", start_index)
+                .await;
+            response.assert_status_ok();
+            let body: Value = response.json();
+            assert_eq!(
+                body["startIndex"], 1,
+                "{} startIndex={}",
+                endpoint, start_index
+            );
+            assert_eq!(body["totalResults"], 3);
+            assert_eq!(body["Resources"].as_array().unwrap().len(), 3);
+        }
+
+        // Negative count reads as 0: only the total is returned
+        let response = server.get(endpoint).add_query_param("count", "-1").await;
+        response.assert_status_ok();
+        let body: Value = response.json();
+        assert_eq!(body["totalResults"], 3, "{} count=-1", endpoint);
+        assert_eq!(body["Resources"], json!([]));
+
+        // Both sort orders are accepted regardless of case
+        for order in ["ascending", "DESCENDING"] {
+            server
+                .get(endpoint)
+                .add_query_param("sortBy", "id")
+                .add_query_param("sortOrder", order)
+                .await
+                .assert_status_ok();
+        }
+    }
+}
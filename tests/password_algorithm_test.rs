@@ -208,3 +208,86 @@ async fn test_service_provider_config_password_support() {
     // Verify PATCH is supported (required for password changes)
     assert_eq!(config["patch"]["supported"], true);
 }
+
+#[tokio::test]
+async fn test_tenant_password_algorithms_via_api() {
+    use scim_server::backend::database::sqlite::SqliteBackend;
+    use scim_server::backend::{Backend, ScimBackend};
+    use scim_server::password::PasswordAlgorithm;
+    use sqlx::Row;
+    use std::sync::Arc;
+
+    // One connection, so the test reads the database the server writes
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let mut app_config = create_test_app_config();
+    app_config.tenants[0].password_algorithm = Some(PasswordAlgorithm::Bcrypt);
+    app_config.tenants[2].password_algorithm = Some(PasswordAlgorithm::Argon2id);
+    let tenants = [
+        (app_config.tenants[0].id, "/tenant-a/scim/v2", "$2"),
+        (app_config.tenants[2].id, "/scim/v2", "$argon2id$"),
+    ];
+    let backend = SqliteBackend::new(pool.clone());
+    for (tenant_id, _, _) in tenants {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+    let backend: Arc<dyn ScimBackend> = Arc::new(backend);
+    let app = common::setup_test_app_with_backend(app_config, backend)
+        .await
+        .unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let stored_password = |tenant_id: u32, user_id: String| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query(&format!(
+                "SELECT json_extract(data_orig, '$.password') AS password FROM t{}_users WHERE id = ?1",
+                tenant_id
+            ))
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+            .get::<String, _>("password")
+        }
+    };
+
+    for (tenant_id, base_path, prefix) in tenants {
+        let mut user = common::create_test_user_json("hashed.user", "Hashed", "User");
+        user["password"] = json!("CreatePassword123!");
+        let response = server
+            .post(&format!("{}/Users", base_path))
+            .json(&user)
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+        let hash = stored_password(tenant_id, user_id.clone()).await;
+        assert!(hash.starts_with(prefix), "{}: {}", base_path, hash);
+
+        // Replacing and patching the password use the tenant's algorithm too
+        user["password"] = json!("ReplacePassword123!");
+        server
+            .put(&format!("{}/Users/{}", base_path, user_id))
+            .json(&user)
+            .await
+            .assert_status_ok();
+        let replaced = stored_password(tenant_id, user_id.clone()).await;
+        assert!(replaced.starts_with(prefix), "{}: {}", base_path, replaced);
+        assert_ne!(replaced, hash);
+
+        server
+            .patch(&format!("{}/Users/{}", base_path, user_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "path": "password", "value": "PatchPassword123!"}]
+            }))
+            .await
+            .assert_status_ok();
+        let patched = stored_password(tenant_id, user_id).await;
+        assert!(patched.starts_with(prefix), "{}: {}", base_path, patched);
+        assert_ne!(patched, replaced);
+    }
+}
@@ -0,0 +1,150 @@
+/// PatchOp body shapes accepted by `PATCH /Users/{id}` and `PATCH /Groups/{id}`
+///
+/// Both endpoints read the body the same way, leniently by default and
+/// strictly with `compatibility.strict_patch_schema`.
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+
+mod common;
+
+const PATCH_OP: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+
+async fn setup_server(strict_patch_schema: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            strict_patch_schema,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+/// URLs of a freshly created user and group
+async fn create_resources(server: &TestServer) -> [String; 2] {
+    let user = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(
+            "shape.user",
+            "Shape",
+            "User",
+        ))
+        .await;
+    user.assert_status(StatusCode::CREATED);
+    let group = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Shape Group"
+        }))
+        .await;
+    group.assert_status(StatusCode::CREATED);
+    [
+        format!(
+            "/scim/v2/Users/{}",
+            user.json::<Value>()["id"].as_str().unwrap()
+        ),
+        format!(
+            "/scim/v2/Groups/{}",
+            group.json::<Value>()["id"].as_str().unwrap()
+        ),
+    ]
+}
+
+/// (name, body, accepted leniently, accepted strictly)
+fn shapes() -> Vec<(&'static str, Value, bool, bool)> {
+    let ops = json!([{"op": "replace", "path": "externalId", "value": "shape-ext"}]);
+    vec![
+        (
+            "canonical",
+            json!({"schemas": [PATCH_OP], "Operations": ops}),
+            true,
+            true,
+        ),
+        (
+            "lowercase operations",
+            json!({"schemas": [PATCH_OP], "operations": ops}),
+            true,
+            false,
+        ),
+        ("missing schemas", json!({"Operations": ops}), true, false),
+        (
+            "other schema",
+            json!({"schemas": ["urn:example:Other"], "Operations": ops}),
+            true,
+            false,
+        ),
+        (
+            "unknown member",
+            json!({"schemas": [PATCH_OP], "Operations": ops, "comment": "x"}),
+            true,
+            false,
+        ),
+        (
+            "missing operations",
+            json!({"schemas": [PATCH_OP]}),
+            false,
+            false,
+        ),
+        (
+            "operations twice",
+            json!({"schemas": [PATCH_OP], "Operations": ops, "operations": ops}),
+            false,
+            false,
+        ),
+        (
+            "operations object",
+            json!({"schemas": [PATCH_OP], "Operations": {}}),
+            false,
+            false,
+        ),
+    ]
+}
+
+async fn assert_shapes(strict: bool) {
+    let server = setup_server(strict).await;
+    let urls = create_resources(&server).await;
+
+    for (name, body, lenient, strict_accepted) in shapes() {
+        let accepted = if strict { strict_accepted } else { lenient };
+        let mut statuses = Vec::new();
+        for url in &urls {
+            let response = server.patch(url).json(&body).await;
+            if !accepted {
+                assert_eq!(
+                    response.json::<Value>()["scimType"],
+                    "invalidSyntax",
+                    "{} (strict={}) on {}",
+                    name,
+                    strict,
+                    url
+                );
+            }
+            statuses.push(response.status_code());
+        }
+
+        let expected = if accepted {
+            StatusCode::OK
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        assert_eq!(
+            statuses,
+            vec![expected; urls.len()],
+            "{} (strict={})",
+            name,
+            strict
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_lenient_patch_body_shapes() {
+    assert_shapes(false).await;
+}
+
+#[tokio::test]
+async fn test_strict_patch_body_shapes() {
+    assert_shapes(true).await;
+}
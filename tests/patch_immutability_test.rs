@@ -0,0 +1,134 @@
+/// PATCH operations on immutable attributes such as `members.value`
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server(enforce_immutability: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            enforce_immutability,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+async fn create_user(server: &TestServer, user_name: &str) -> String {
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(
+            user_name,
+            "Immutable",
+            "User",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+/// A group with one member, returning the group and member ids
+async fn create_group(server: &TestServer) -> (String, String) {
+    let member_id = create_user(server, "immutable.member").await;
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Immutable Group",
+            "members": [{"value": member_id}]
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    (group_id, member_id)
+}
+
+fn patch_body(operations: Value) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": operations
+    })
+}
+
+async fn member_ids(server: &TestServer, group_id: &str) -> Vec<String> {
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    group["members"]
+        .as_array()
+        .map(|members| {
+            members
+                .iter()
+                .map(|m| m["value"].as_str().unwrap().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_patch_of_immutable_member_attribute_is_rejected() {
+    let server = setup_server(true).await;
+    let (group_id, member_id) = create_group(&server).await;
+    let other_id = create_user(&server, "immutable.other").await;
+
+    for (path, value) in [
+        (
+            format!("members[value eq \"{}\"].value", member_id),
+            json!(other_id),
+        ),
+        ("members.value".to_string(), json!(other_id)),
+        (
+            format!("members[value eq \"{}\"].$ref", member_id),
+            json!(format!("https://example.com/scim/v2/Users/{}", other_id)),
+        ),
+    ] {
+        let response = server
+            .patch(&format!("/scim/v2/Groups/{}", group_id))
+            .json(&patch_body(json!([
+                {"op": "replace", "path": path, "value": value}
+            ])))
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let body: Value = response.json();
+        assert_eq!(body["scimType"], "mutability", "{}", path);
+        assert!(body["detail"].as_str().unwrap().contains("immutable"));
+    }
+
+    // Nothing was written
+    assert_eq!(
+        member_ids(&server, &group_id).await,
+        vec![member_id.clone()]
+    );
+
+    // Adding and removing whole members is still allowed
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&patch_body(json!([
+            {"op": "add", "path": "members", "value": [{"value": other_id}]},
+            {"op": "remove", "path": format!("members[value eq \"{}\"]", member_id)}
+        ])))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(member_ids(&server, &group_id).await, vec![other_id]);
+}
+
+#[tokio::test]
+async fn test_immutability_check_can_be_disabled() {
+    let server = setup_server(false).await;
+    let (group_id, member_id) = create_group(&server).await;
+    let other_id = create_user(&server, "immutable.other").await;
+
+    let response = server
+        .patch(&format!("/scim/v2/Groups/{}", group_id))
+        .json(&patch_body(json!([{
+            "op": "replace",
+            "path": format!("members[value eq \"{}\"].value", member_id),
+            "value": other_id
+        }])))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(member_ids(&server, &group_id).await, vec![other_id]);
+}
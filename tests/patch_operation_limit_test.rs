@@ -0,0 +1,136 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|_| {}).await
+}
+
+async fn create(server: &TestServer, base: &str, endpoint: &str, body: Value) -> String {
+    let response = server
+        .post(&format!("{}/{}", base, endpoint))
+        .json(&body)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+fn patch_body(count: usize) -> Value {
+    let operations: Vec<Value> = (0..count)
+        .map(|i| json!({"op": "replace", "path": "displayName", "value": format!("Name {}", i)}))
+        .collect();
+    json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": operations
+    })
+}
+
+#[tokio::test]
+async fn test_user_patch_operation_limit() {
+    let server = setup_server().await;
+    let id = create(
+        &server,
+        "/tenant-a/scim/v2",
+        "Users",
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "patch.limit"
+        }),
+    )
+    .await;
+    let url = format!("/tenant-a/scim/v2/Users/{}", id);
+
+    let response = server.patch(&url).json(&patch_body(1)).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["displayName"], "Name 0");
+
+    let response = server.patch(&url).json(&patch_body(100)).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["displayName"], "Name 99");
+
+    let response = server.patch(&url).json(&patch_body(101)).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+    assert_eq!(
+        body["detail"],
+        "PATCH request contains 101 operations, maximum allowed is 100"
+    );
+
+    // The rejected PATCH changed nothing
+    let user: Value = server.get(&url).await.json();
+    assert_eq!(user["displayName"], "Name 99");
+}
+
+#[tokio::test]
+async fn test_group_patch_operation_limit() {
+    let server = setup_server().await;
+    let id = create(
+        &server,
+        "/tenant-a/scim/v2",
+        "Groups",
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Patch Limit"
+        }),
+    )
+    .await;
+    let url = format!("/tenant-a/scim/v2/Groups/{}", id);
+
+    server
+        .patch(&url)
+        .json(&patch_body(1))
+        .await
+        .assert_status(StatusCode::OK);
+    server
+        .patch(&url)
+        .json(&patch_body(100))
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = server.patch(&url).json(&patch_body(101)).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["detail"],
+        "PATCH request contains 101 operations, maximum allowed is 100"
+    );
+}
+
+#[tokio::test]
+async fn test_patch_operation_limit_is_configurable() {
+    let server = common::test_server(|app_config| {
+        app_config.tenants[0].compatibility = Some(CompatibilityConfig {
+            max_patch_operations: 2,
+            ..Default::default()
+        });
+    })
+    .await;
+
+    let base = "/tenant-a/scim/v2";
+    let id = create(
+        &server,
+        base,
+        "Users",
+        json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "patch.limit.custom"
+        }),
+    )
+    .await;
+    let url = format!("{}/Users/{}", base, id);
+
+    server
+        .patch(&url)
+        .json(&patch_body(2))
+        .await
+        .assert_status(StatusCode::OK);
+    let response = server.patch(&url).json(&patch_body(3)).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["detail"],
+        "PATCH request contains 3 operations, maximum allowed is 2"
+    );
+}
@@ -0,0 +1,124 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+use scim_server::config::CompatibilityConfig;
+
+const TENANT_A: &str = "/tenant-a/scim/v2";
+const TENANT_B: &str = "/tenant-b/scim/v2";
+
+/// Tenant A has PATCH disabled, tenant B keeps the default
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[0].compatibility = Some(CompatibilityConfig {
+            patch_supported: false,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+async fn create(server: &TestServer, base: &str, endpoint: &str, body: Value) -> String {
+    let response = server
+        .post(&format!("{}/{}", base, endpoint))
+        .json(&body)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+fn group_json(display_name: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+        "displayName": display_name
+    })
+}
+
+fn replace_display_name(value: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+        "Operations": [{"op": "replace", "path": "displayName", "value": value}]
+    })
+}
+
+async fn patch_advertised(server: &TestServer, base: &str) -> Value {
+    let response = server.get(&format!("{}/ServiceProviderConfig", base)).await;
+    response.assert_status(StatusCode::OK);
+    response.json::<Value>()["patch"]["supported"].clone()
+}
+
+#[tokio::test]
+async fn test_patch_disabled_for_one_tenant() {
+    let server = setup_server().await;
+
+    let user_id = create(
+        &server,
+        TENANT_A,
+        "Users",
+        common::create_test_user_json("patch.off", "Patch", "Off"),
+    )
+    .await;
+    let group_id = create(&server, TENANT_A, "Groups", group_json("Patch Off")).await;
+
+    for path in [
+        format!("{}/Users/{}", TENANT_A, user_id),
+        format!("{}/Groups/{}", TENANT_A, group_id),
+    ] {
+        let response = server
+            .patch(&path)
+            .json(&replace_display_name("Changed"))
+            .await;
+        response.assert_status(StatusCode::NOT_IMPLEMENTED);
+        let body: Value = response.json();
+        assert_eq!(
+            body["schemas"][0],
+            "urn:ietf:params:scim:api:messages:2.0:Error"
+        );
+        assert_eq!(body["status"], "501");
+        assert_eq!(body["detail"], "PATCH is not supported");
+    }
+
+    // Nothing was changed, and PUT still works
+    let response = server
+        .get(&format!("{}/Groups/{}", TENANT_A, group_id))
+        .await;
+    assert_eq!(response.json::<Value>()["displayName"], "Patch Off");
+    server
+        .put(&format!("{}/Groups/{}", TENANT_A, group_id))
+        .json(&group_json("Replaced"))
+        .await
+        .assert_status(StatusCode::OK);
+
+    assert_eq!(patch_advertised(&server, TENANT_A).await, json!(false));
+}
+
+#[tokio::test]
+async fn test_patch_still_supported_for_other_tenant() {
+    let server = setup_server().await;
+
+    let user_id = create(
+        &server,
+        TENANT_B,
+        "Users",
+        common::create_test_user_json("patch.on", "Patch", "On"),
+    )
+    .await;
+    let group_id = create(&server, TENANT_B, "Groups", group_json("Patch On")).await;
+
+    let response = server
+        .patch(&format!("{}/Users/{}", TENANT_B, user_id))
+        .json(&replace_display_name("Patched User"))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["displayName"], "Patched User");
+
+    let response = server
+        .patch(&format!("{}/Groups/{}", TENANT_B, group_id))
+        .json(&replace_display_name("Patched Group"))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["displayName"], "Patched Group");
+
+    assert_eq!(patch_advertised(&server, TENANT_B).await, json!(true));
+}
@@ -0,0 +1,248 @@
+use axum_test::TestServer;
+use base64::{engine::general_purpose, Engine as _};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+use scim_server::config::{AppConfig, AuthConfig, BasicAuthConfig, CompatibilityConfig};
+
+const PROVENANCE: &str = "urn:scim-server:scim:extension:provenance";
+
+/// Tenant 3 (`/scim/v2`) with basic users alice and bob
+fn app_config(expose_provenance: bool) -> AppConfig {
+    let mut app_config = common::create_test_app_config();
+    let tenant = &mut app_config.tenants[2];
+    tenant.expose_provenance = expose_provenance;
+    tenant.auth = AuthConfig {
+        auth_type: "basic".to_string(),
+        token: None,
+        basic: Some(BasicAuthConfig {
+            username: "alice".to_string(),
+            password: "alice-pass".to_string(),
+        }),
+        basic_users: vec![BasicAuthConfig {
+            username: "bob".to_string(),
+            password: "bob-pass".to_string(),
+        }],
+        tokens: Vec::new(),
+    };
+    app_config
+}
+
+async fn setup_server(expose_provenance: bool) -> TestServer {
+    common::test_server(|config| *config = app_config(expose_provenance)).await
+}
+
+fn basic(username: &str, password: &str) -> String {
+    format!(
+        "Basic {}",
+        general_purpose::STANDARD.encode(format!("{}:{}", username, password))
+    )
+}
+
+#[tokio::test]
+async fn test_provenance_tracks_each_writer() {
+    let server = setup_server(true).await;
+    let alice = basic("alice", "alice-pass");
+    let bob = basic("bob", "bob-pass");
+
+    let response = server
+        .post("/scim/v2/Users")
+        .add_header("Authorization", &alice)
+        .json(&common::create_test_user_json("prov.user", "Prov", "User"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert_eq!(created[PROVENANCE]["createdBy"], "alice");
+    assert_eq!(created[PROVENANCE]["lastModifiedBy"], "alice");
+    assert!(created["schemas"]
+        .as_array()
+        .unwrap()
+        .contains(&json!(PROVENANCE)));
+    let user_id = created["id"].as_str().unwrap();
+
+    // An update by the second basic user changes lastModifiedBy only
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .add_header("Authorization", &bob)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "displayName", "value": "Bob Edit"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let patched: Value = response.json();
+    assert_eq!(patched[PROVENANCE]["createdBy"], "alice");
+    assert_eq!(patched[PROVENANCE]["lastModifiedBy"], "bob");
+
+    let response = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .add_header("Authorization", &alice)
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>()[PROVENANCE]["lastModifiedBy"],
+        "bob"
+    );
+
+    // Groups are tracked too, and list responses carry the extension
+    let response = server
+        .post("/scim/v2/Groups")
+        .add_header("Authorization", &bob)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Prov Group"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    let response = server
+        .put(&format!("/scim/v2/Groups/{}", group_id))
+        .add_header("Authorization", &alice)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Prov Group Renamed"
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+
+    let response = server
+        .get("/scim/v2/Groups")
+        .add_header("Authorization", &alice)
+        .await;
+    response.assert_status(StatusCode::OK);
+    let group = &response.json::<Value>()["Resources"][0];
+    assert_eq!(group[PROVENANCE]["createdBy"], "bob");
+    assert_eq!(group[PROVENANCE]["lastModifiedBy"], "alice");
+}
+
+#[tokio::test]
+async fn test_provenance_is_read_only() {
+    let server = setup_server(true).await;
+    let alice = basic("alice", "alice-pass");
+
+    let mut user = common::create_test_user_json("prov.readonly", "Prov", "ReadOnly");
+    user[PROVENANCE] = json!({"createdBy": "mallory"});
+    let response = server
+        .post("/scim/v2/Users")
+        .add_header("Authorization", &alice)
+        .json(&user)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert_eq!(created[PROVENANCE]["createdBy"], "alice");
+
+    let response = server
+        .patch(&format!(
+            "/scim/v2/Users/{}",
+            created["id"].as_str().unwrap()
+        ))
+        .add_header("Authorization", &alice)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "replace",
+                "path": format!("{}:createdBy", PROVENANCE),
+                "value": "mallory"
+            }]
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "mutability");
+}
+
+#[tokio::test]
+async fn test_provenance_absent_when_disabled() {
+    let server = setup_server(false).await;
+    let alice = basic("alice", "alice-pass");
+    let bob = basic("bob", "bob-pass");
+
+    let response = server
+        .post("/scim/v2/Users")
+        .add_header("Authorization", &alice)
+        .json(&common::create_test_user_json(
+            "prov.hidden",
+            "Prov",
+            "Hidden",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert!(created.get(PROVENANCE).is_none());
+    assert!(!created["schemas"]
+        .as_array()
+        .unwrap()
+        .contains(&json!(PROVENANCE)));
+
+    let response = server
+        .get(&format!(
+            "/scim/v2/Users/{}?attributes={}",
+            created["id"].as_str().unwrap(),
+            PROVENANCE
+        ))
+        .add_header("Authorization", &bob)
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.json::<Value>().get(PROVENANCE).is_none());
+
+    let response = server
+        .get("/scim/v2/Users")
+        .add_header("Authorization", &bob)
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.json::<Value>()["Resources"][0]
+        .get(PROVENANCE)
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_provenance_in_deleted_resource() {
+    let server = common::test_server(|config| {
+        *config = app_config(true);
+        config.tenants[2].compatibility = Some(CompatibilityConfig {
+            delete_returns_resource: true,
+            ..Default::default()
+        });
+    })
+    .await;
+    let alice = basic("alice", "alice-pass");
+    let bob = basic("bob", "bob-pass");
+
+    let response = server
+        .post("/scim/v2/Users")
+        .add_header("Authorization", &alice)
+        .json(&common::create_test_user_json(
+            "prov.deleted",
+            "Prov",
+            "Deleted",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let response = server
+        .post("/scim/v2/Groups")
+        .add_header("Authorization", &alice)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Prov Deleted"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    for path in [
+        format!("/scim/v2/Users/{}", user_id),
+        format!("/scim/v2/Groups/{}", group_id),
+    ] {
+        let response = server.delete(&path).add_header("Authorization", &bob).await;
+        response.assert_status(StatusCode::OK);
+        let deleted: Value = response.json();
+        assert_eq!(deleted[PROVENANCE]["createdBy"], "alice", "{}", path);
+        assert_eq!(deleted[PROVENANCE]["lastModifiedBy"], "alice", "{}", path);
+        assert!(deleted["schemas"]
+            .as_array()
+            .unwrap()
+            .contains(&json!(PROVENANCE)));
+    }
+}
@@ -0,0 +1,144 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+fn user_json(user_name: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": user_name
+    })
+}
+
+fn assert_read_only_rejection(response: &axum_test::TestResponse) {
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(response.headers().get("retry-after").is_some());
+    let body: Value = response.json();
+    assert_eq!(
+        body["schemas"][0],
+        "urn:ietf:params:scim:api:messages:2.0:Error"
+    );
+    assert_eq!(body["status"], "503");
+    assert_eq!(body["detail"], "tenant is in read-only maintenance mode");
+}
+
+#[tokio::test]
+async fn test_tenant_read_only_rejects_writes_and_serves_reads() {
+    let app_config = common::create_test_app_config();
+    // Clones share the runtime flags, so this handle can toggle the running app
+    let handle = app_config.clone();
+    let app = common::setup_test_app(app_config).await.unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&user_json("before.maintenance"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user: Value = response.json();
+    let user_id = user["id"].as_str().unwrap();
+
+    // Tenant 1 (/tenant-a) enters maintenance
+    handle.tenants[0].read_only.set(true);
+
+    assert_read_only_rejection(
+        &server
+            .post("/tenant-a/scim/v2/Users")
+            .json(&user_json("during.maintenance"))
+            .await,
+    );
+    assert_read_only_rejection(
+        &server
+            .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+            .json(&user_json("renamed.user"))
+            .await,
+    );
+    assert_read_only_rejection(
+        &server
+            .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{"op": "replace", "path": "active", "value": false}]
+            }))
+            .await,
+    );
+    assert_read_only_rejection(
+        &server
+            .delete(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+            .await,
+    );
+    assert_read_only_rejection(
+        &server
+            .post("/tenant-a/scim/v2/Groups")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+                "displayName": "Blocked"
+            }))
+            .await,
+    );
+
+    // Reads and discovery endpoints keep working
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let fetched: Value = response.json();
+    assert_eq!(fetched["userName"], "before.maintenance");
+    assert_eq!(
+        server.get("/tenant-a/scim/v2/Users").await.status_code(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        server
+            .get("/tenant-a/scim/v2/ServiceProviderConfig")
+            .await
+            .status_code(),
+        StatusCode::OK
+    );
+
+    // Other tenants are unaffected
+    let response = server
+        .post("/tenant-b/scim/v2/Users")
+        .json(&user_json("other.tenant"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    // Leaving maintenance takes effect on the next request
+    handle.tenants[0].read_only.set(false);
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&user_json("after.maintenance"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_global_read_only_applies_to_all_tenants() {
+    let app_config = common::create_test_app_config();
+    let handle = app_config.clone();
+    let app = common::setup_test_app(app_config).await.unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    handle.read_only.set(true);
+
+    for base in ["/tenant-a/scim/v2", "/tenant-b/scim/v2", "/scim/v2"] {
+        assert_read_only_rejection(
+            &server
+                .post(&format!("{}/Users", base))
+                .json(&user_json("global.maintenance"))
+                .await,
+        );
+        assert_eq!(
+            server.get(&format!("{}/Users", base)).await.status_code(),
+            StatusCode::OK
+        );
+    }
+
+    handle.read_only.set(false);
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&user_json("global.maintenance"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+}
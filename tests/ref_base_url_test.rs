@@ -0,0 +1,190 @@
+/// `$ref` URL resolution tests
+///
+/// `members[].$ref` and `groups[].$ref` must be built from the same tenant base
+/// URL as `meta.location` (honoring `override_base_url`), unless `ref_base_url`
+/// overrides the `$ref` base on its own.
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+const BASE: &str = "https://idm.example.com/scim/v2";
+
+async fn setup_server(ref_base_url: Option<&str>) -> TestServer {
+    common::test_server(|app_config| {
+        let tenant = app_config
+            .tenants
+            .iter_mut()
+            .find(|t| t.path == "/scim/v2")
+            .unwrap();
+        tenant.override_base_url = Some("https://idm.example.com".to_string());
+        tenant.ref_base_url = ref_base_url.map(String::from);
+    })
+    .await
+}
+
+/// Creates a user, a group containing the user and a parent group containing
+/// that group; returns (user_id, group_id, parent_id)
+async fn create_fixture(server: &TestServer) -> (String, String, String) {
+    let user: Value = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ref.user"
+        }))
+        .await
+        .json();
+    let user_id = user["id"].as_str().unwrap().to_string();
+
+    let group: Value = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Ref Group",
+            "members": [{"value": user_id, "type": "User"}]
+        }))
+        .await
+        .json();
+    let group_id = group["id"].as_str().unwrap().to_string();
+
+    let parent: Value = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Ref Parent",
+            "members": [{"value": group_id, "type": "Group"}]
+        }))
+        .await
+        .json();
+    let parent_id = parent["id"].as_str().unwrap().to_string();
+
+    (user_id, group_id, parent_id)
+}
+
+fn assert_refs(resource: &Value, attribute: &str, ref_base: &str, ids: &[(&str, &str)]) {
+    let entries = resource[attribute].as_array().unwrap();
+    assert_eq!(entries.len(), ids.len(), "{}: {}", attribute, resource);
+    for (resource_type, id) in ids {
+        let entry = entries
+            .iter()
+            .find(|e| e["value"] == *id)
+            .unwrap_or_else(|| panic!("{} not in {}", id, resource));
+        assert_eq!(
+            entry["$ref"],
+            format!("{}/{}/{}", ref_base, resource_type, id)
+        );
+    }
+}
+
+fn assert_location(resource: &Value, resource_type: &str) {
+    assert_eq!(
+        resource["meta"]["location"],
+        format!(
+            "{}/{}/{}",
+            BASE,
+            resource_type,
+            resource["id"].as_str().unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_refs_follow_override_base_url() {
+    let server = setup_server(None).await;
+    let (user_id, group_id, parent_id) = create_fixture(&server).await;
+
+    // User read: groups[].$ref
+    let response = server.get(&format!("/scim/v2/Users/{}", user_id)).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let user: Value = response.json();
+    assert_location(&user, "Users");
+    assert_refs(&user, "groups", BASE, &[("Groups", &group_id)]);
+
+    let list: Value = server.get("/scim/v2/Users").await.json();
+    let listed = &list["Resources"][0];
+    assert_location(listed, "Users");
+    assert_refs(listed, "groups", BASE, &[("Groups", &group_id)]);
+
+    // Group read: members[].$ref for both User and Group members
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_location(&group, "Groups");
+    assert_refs(&group, "members", BASE, &[("Users", &user_id)]);
+
+    let parent: Value = server
+        .get(&format!("/scim/v2/Groups/{}", parent_id))
+        .await
+        .json();
+    assert_location(&parent, "Groups");
+    assert_refs(&parent, "members", BASE, &[("Groups", &group_id)]);
+
+    let list: Value = server.get("/scim/v2/Groups").await.json();
+    for group in list["Resources"].as_array().unwrap() {
+        assert_location(group, "Groups");
+        for member in group["members"].as_array().unwrap() {
+            assert!(member["$ref"].as_str().unwrap().starts_with(BASE));
+        }
+    }
+
+    // Write responses resolve refs the same way
+    let patched: Value = server
+        .patch(&format!("/scim/v2/Groups/{}", parent_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "add",
+                "path": "members",
+                "value": [{"value": user_id, "type": "User"}]
+            }]
+        }))
+        .await
+        .json();
+    assert_location(&patched, "Groups");
+    assert_refs(
+        &patched,
+        "members",
+        BASE,
+        &[("Groups", &group_id), ("Users", &user_id)],
+    );
+
+    let replaced: Value = server
+        .put(&format!("/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "ref.user",
+            "displayName": "Ref User"
+        }))
+        .await
+        .json();
+    assert_location(&replaced, "Users");
+    assert_refs(
+        &replaced,
+        "groups",
+        BASE,
+        &[("Groups", &group_id), ("Groups", &parent_id)],
+    );
+}
+
+#[tokio::test]
+async fn test_ref_base_url_overrides_only_refs() {
+    let server = setup_server(Some("https://refs.example.com/")).await;
+    let (user_id, group_id, _) = create_fixture(&server).await;
+    let ref_base = "https://refs.example.com/scim/v2";
+
+    let user: Value = server
+        .get(&format!("/scim/v2/Users/{}", user_id))
+        .await
+        .json();
+    assert_location(&user, "Users");
+    assert_refs(&user, "groups", ref_base, &[("Groups", &group_id)]);
+
+    let group: Value = server
+        .get(&format!("/scim/v2/Groups/{}", group_id))
+        .await
+        .json();
+    assert_location(&group, "Groups");
+    assert_refs(&group, "members", ref_base, &[("Users", &user_id)]);
+}
@@ -0,0 +1,141 @@
+/// Tenants serving Users or Groups under a renamed path segment
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        // Tenant 3 (/scim/v2) serves Groups at /scim/v2/Teams
+        app_config.tenants[2].groups_path = Some("Teams".to_string());
+        app_config.validate_resource_paths().unwrap();
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_custom_groups_path_routes_and_urls() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&common::create_test_user_json(
+            "team.member",
+            "Team",
+            "Member",
+        ))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    let response = server
+        .post("/scim/v2/Teams")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Team A",
+            "members": [{"value": user_id}]
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let group: Value = response.json();
+    let group_id = group["id"].as_str().unwrap();
+    let location = group["meta"]["location"].as_str().unwrap();
+    assert!(
+        location.ends_with(&format!("/scim/v2/Teams/{}", group_id)),
+        "{}",
+        location
+    );
+    assert_eq!(response.headers()["location"], location);
+    let member_ref = group["members"][0]["$ref"].as_str().unwrap();
+    assert!(
+        member_ref.ends_with(&format!("/scim/v2/Users/{}", user_id)),
+        "{}",
+        member_ref
+    );
+
+    // The renamed endpoint serves every Group operation
+    let response = server.get(&format!("/scim/v2/Teams/{}", group_id)).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["meta"]["location"], location);
+    server
+        .patch(&format!("/scim/v2/Teams/{}", group_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "displayName", "value": "Team B"}]
+        }))
+        .await
+        .assert_status_ok();
+    let response = server
+        .get("/scim/v2/Teams")
+        .add_query_param("filter", "displayName eq \"Team B\"")
+        .await;
+    response.assert_status_ok();
+    let list: Value = response.json();
+    assert_eq!(list["totalResults"], 1);
+    assert_eq!(list["Resources"][0]["meta"]["location"], location);
+
+    // The user's group $ref points at the renamed endpoint
+    let response = server.get(&format!("/scim/v2/Users/{}", user_id)).await;
+    response.assert_status_ok();
+    let group_ref = response.json::<Value>()["groups"][0]["$ref"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(
+        group_ref.ends_with(&format!("/scim/v2/Teams/{}", group_id)),
+        "{}",
+        group_ref
+    );
+
+    // The default path is no longer served for this tenant
+    for path in [
+        "/scim/v2/Groups".to_string(),
+        format!("/scim/v2/Groups/{}", group_id),
+    ] {
+        server.get(&path).await.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    let response = server.get("/scim/v2/ResourceTypes").await;
+    response.assert_status_ok();
+    let endpoints: Vec<Value> = response.json::<Value>()["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|resource_type| resource_type["endpoint"].clone())
+        .collect();
+    assert_eq!(endpoints, vec![json!("/Users"), json!("/Teams")]);
+
+    server
+        .delete(&format!("/scim/v2/Teams/{}", group_id))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_other_tenants_keep_default_paths() {
+    let server = setup_server().await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Default Group"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let location = response.json::<Value>()["meta"]["location"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(
+        location.contains("/tenant-a/scim/v2/Groups/"),
+        "{}",
+        location
+    );
+
+    server
+        .get("/tenant-a/scim/v2/Teams")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,101 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+use scim_server::config::CompatibilityConfig;
+
+async fn setup_server(return_external_id: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            return_external_id,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_external_id_returned_by_default() {
+    let server = setup_server(true).await;
+
+    let mut user = common::create_test_user_json("ext.default", "Ext", "Default");
+    user["externalId"] = json!("ext-default");
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.json::<Value>()["externalId"], "ext-default");
+}
+
+#[tokio::test]
+async fn test_user_external_id_withheld_but_filterable() {
+    let server = setup_server(false).await;
+
+    let mut user = common::create_test_user_json("ext.hidden", "Ext", "Hidden");
+    user["externalId"] = json!("ext-hidden");
+    let response = server.post("/scim/v2/Users").json(&user).await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert!(created.get("externalId").is_none());
+    let user_id = created["id"].as_str().unwrap();
+
+    let response = server
+        .get(&format!("/scim/v2/Users/{}?attributes=externalId", user_id))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.json::<Value>().get("externalId").is_none());
+
+    // Still stored, so filtering by it finds the user
+    let response = server
+        .get("/scim/v2/Users")
+        .add_query_param("filter", "externalId eq \"ext-hidden\"")
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 1);
+    assert_eq!(body["Resources"][0]["id"], user_id);
+    assert!(body["Resources"][0].get("externalId").is_none());
+
+    // PATCH responses are filtered the same way and the value is kept
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "displayName", "value": "Hidden"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.json::<Value>().get("externalId").is_none());
+
+    let response = server
+        .get("/scim/v2/Users")
+        .add_query_param("filter", "externalId eq \"ext-hidden\"")
+        .await;
+    assert_eq!(response.json::<Value>()["totalResults"], 1);
+}
+
+#[tokio::test]
+async fn test_group_external_id_withheld_but_filterable() {
+    let server = setup_server(false).await;
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Hidden Group",
+            "externalId": "grp-hidden"
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created: Value = response.json();
+    assert!(created.get("externalId").is_none());
+
+    let response = server
+        .get("/scim/v2/Groups")
+        .add_query_param("filter", "externalId eq \"grp-hidden\"")
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 1);
+    assert_eq!(body["Resources"][0]["id"], created["id"]);
+    assert!(body["Resources"][0].get("externalId").is_none());
+}
@@ -11,6 +11,38 @@ use serde_json::{json, Value};
 
 mod common;
 
+/// Asserts that a 201 response has a Location header equal to its
+/// meta.location, and that the URL addresses the created resource
+fn assert_created_location(
+    response: &axum_test::TestResponse,
+    resource_path: &str,
+) -> (String, String) {
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let location_header = response
+        .headers()
+        .get("location")
+        .expect("Location header is missing from POST response")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let body: Value = response.json();
+    let id = body["id"].as_str().unwrap().to_string();
+    assert_eq!(body["meta"]["location"], location_header.as_str());
+    assert!(
+        location_header.starts_with("http"),
+        "location must be absolute"
+    );
+    assert!(
+        location_header.ends_with(&format!("{}/{}", resource_path, id)),
+        "{}",
+        location_header
+    );
+
+    (location_header, id)
+}
+
 #[tokio::test]
 async fn test_user_creation_location_header() {
     let tenant_config = common::create_test_app_config();
@@ -26,34 +58,15 @@ async fn test_user_creation_location_header() {
         .post("/tenant-a/scim/v2/Users")
         .json(&new_user_data)
         .await;
-
-    assert_eq!(response.status_code(), StatusCode::CREATED);
-
-    // Check if Location header is present
-    let headers = response.headers();
-    println!("Response headers: {:?}", headers);
-
-    if let Some(location_header) = headers.get("location") {
-        let location_value = location_header.to_str().unwrap();
-        println!("✅ Location header found: {}", location_value);
-
-        // Verify the location header format
-        assert!(location_value.contains("/tenant-a/scim/v2/Users/"));
-    } else {
-        println!("❌ Location header is missing from POST response");
-    }
-
-    // Check meta.location in response body
-    let user_response: Value = response.json();
-    if let Some(meta) = user_response.get("meta") {
-        if let Some(location) = meta.get("location") {
-            println!("✅ meta.location found: {}", location);
-        } else {
-            println!("❌ meta.location is missing from response body");
-        }
-    } else {
-        println!("❌ meta object is missing from response body");
-    }
+    let (location, id) = assert_created_location(&response, "/tenant-a/scim/v2/Users");
+
+    // The advertised URL serves the created resource
+    let path = &location[location.find("/tenant-a/").unwrap()..];
+    let response = server.get(path).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let user: Value = response.json();
+    assert_eq!(user["id"], id.as_str());
+    assert_eq!(user["meta"]["location"], location.as_str());
 }
 
 #[tokio::test]
@@ -71,32 +84,92 @@ async fn test_group_creation_location_header() {
         .post("/tenant-a/scim/v2/Groups")
         .json(&new_group_data)
         .await;
+    let (location, id) = assert_created_location(&response, "/tenant-a/scim/v2/Groups");
+
+    let path = &location[location.find("/tenant-a/").unwrap()..];
+    let response = server.get(path).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let group: Value = response.json();
+    assert_eq!(group["id"], id.as_str());
+    assert_eq!(group["meta"]["location"], location.as_str());
+
+    // Tenants are kept apart: another tenant's Location uses its own base path
+    let response = server.post("/scim/v2/Groups").json(&new_group_data).await;
+    let (location, _) = assert_created_location(&response, "/scim/v2/Groups");
+    assert!(!location.contains("/tenant-a/"));
+}
 
-    assert_eq!(response.status_code(), StatusCode::CREATED);
-
-    // Check if Location header is present
-    let headers = response.headers();
-    println!("Response headers: {:?}", headers);
-
-    if let Some(location_header) = headers.get("location") {
-        let location_value = location_header.to_str().unwrap();
-        println!("✅ Location header found: {}", location_value);
+#[tokio::test]
+async fn test_user_list_meta_location_matches_individual_get() {
+    let tenant_config = common::create_test_app_config();
+    let app = common::setup_test_app(tenant_config).await.unwrap();
+    let server = TestServer::new(app).unwrap();
 
-        // Verify the location header format
-        assert!(location_value.contains("/tenant-a/scim/v2/Groups/"));
-    } else {
-        println!("❌ Location header is missing from POST response");
+    for name in ["list.location.one", "list.location.two"] {
+        let response = server
+            .post("/tenant-a/scim/v2/Users")
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": name
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
     }
 
-    // Check meta.location in response body
-    let group_response: Value = response.json();
-    if let Some(meta) = group_response.get("meta") {
-        if let Some(location) = meta.get("location") {
-            println!("✅ meta.location found: {}", location);
-        } else {
-            println!("❌ meta.location is missing from response body");
+    // Plain, filtered and sorted lists must all carry the same URLs as the single GET
+    for query in [
+        "",
+        "?filter=userName%20sw%20%22list.location%22",
+        "?sortBy=userName&sortOrder=descending",
+    ] {
+        let response = server
+            .get(&format!("/tenant-a/scim/v2/Users{}", query))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let list: Value = response.json();
+        let resources = list["Resources"].as_array().unwrap();
+        assert_eq!(resources.len(), 2, "query {:?}", query);
+
+        for resource in resources {
+            let id = resource["id"].as_str().unwrap();
+            let location = resource["meta"]["location"].as_str().unwrap();
+            assert!(location.starts_with("http"), "location must be absolute");
+            assert!(location.ends_with(&format!("/tenant-a/scim/v2/Users/{}", id)));
+
+            let single: Value = server
+                .get(&format!("/tenant-a/scim/v2/Users/{}", id))
+                .await
+                .json();
+            assert_eq!(single["meta"]["location"], resource["meta"]["location"]);
         }
-    } else {
-        println!("❌ meta object is missing from response body");
     }
 }
+
+#[tokio::test]
+async fn test_group_list_meta_location_matches_individual_get() {
+    let tenant_config = common::create_test_app_config();
+    let app = common::setup_test_app(tenant_config).await.unwrap();
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "List Location Group"
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let list: Value = server.get("/scim/v2/Groups").await.json();
+    let resources = list["Resources"].as_array().unwrap();
+    assert_eq!(resources.len(), 1);
+
+    let id = resources[0]["id"].as_str().unwrap();
+    let location = resources[0]["meta"]["location"].as_str().unwrap();
+    assert!(location.ends_with(&format!("/scim/v2/Groups/{}", id)));
+    assert!(!location.contains("/tenant-a/"));
+
+    let single: Value = server.get(&format!("/scim/v2/Groups/{}", id)).await.json();
+    assert_eq!(single["meta"]["location"], resources[0]["meta"]["location"]);
+}
@@ -1,5 +1,7 @@
 use axum_test::TestServer;
 use http::StatusCode;
+use scim_server::config::{CompatibilityConfig, ServiceProviderInfo};
+use serde_json::Value;
 
 mod common;
 
@@ -36,3 +38,95 @@ async fn test_service_provider_config() {
     assert!(location_tenant_a.starts_with("http://"));
     assert!(location_tenant_a.contains("/tenant-a/scim/v2/ServiceProviderConfig"));
 }
+
+#[tokio::test]
+async fn test_service_provider_config_per_tenant_details() {
+    let server = common::test_server(|app_config| {
+        app_config.tenants[0].service_provider = ServiceProviderInfo {
+            documentation_uri: Some("https://docs.customer-a.example/scim".to_string()),
+            name: Some("Customer A Directory".to_string()),
+            description: None,
+            support_email: Some("scim-support@customer-a.example".to_string()),
+        };
+        app_config.tenants[1].service_provider = ServiceProviderInfo {
+            documentation_uri: Some("https://help.customer-b.example".to_string()),
+            ..Default::default()
+        };
+    })
+    .await;
+
+    let tenant_a: Value = server
+        .get("/tenant-a/scim/v2/ServiceProviderConfig")
+        .await
+        .json();
+    let tenant_b: Value = server
+        .get("/tenant-b/scim/v2/ServiceProviderConfig")
+        .await
+        .json();
+    let tenant_default: Value = server.get("/scim/v2/ServiceProviderConfig").await.json();
+
+    assert_eq!(
+        tenant_a["documentationUri"],
+        "https://docs.customer-a.example/scim"
+    );
+    assert_eq!(
+        tenant_b["documentationUri"],
+        "https://help.customer-b.example"
+    );
+    assert_eq!(
+        tenant_default["documentationUri"],
+        "https://github.com/wadahiro/scim-server"
+    );
+
+    assert_eq!(tenant_a["name"], "Customer A Directory");
+    assert_eq!(tenant_a["supportEmail"], "scim-support@customer-a.example");
+    // Unset fields fall back to the server defaults
+    assert_eq!(tenant_b["name"], "scim-server");
+    assert_eq!(tenant_a["description"], tenant_b["description"]);
+    assert!(tenant_b.get("supportEmail").is_none());
+
+    // Location is the tenant base URL, timestamps are the server start time
+    assert_eq!(
+        tenant_a["meta"]["location"],
+        "http://localhost/tenant-a/scim/v2/ServiceProviderConfig"
+    );
+    assert!(tenant_a["meta"]["created"].is_string());
+    assert_eq!(
+        tenant_a["meta"]["created"],
+        tenant_b["meta"]["lastModified"]
+    );
+}
+
+#[tokio::test]
+async fn test_service_provider_config_backend_info() {
+    const EXTENSION: &str = "urn:scim-server:extension:1.0:ServiceProviderConfig";
+
+    let server = common::test_server(|app_config| {
+        app_config.tenants[0].compatibility = Some(CompatibilityConfig {
+            expose_backend_info: true,
+            total_count_header: true,
+            ..Default::default()
+        });
+    })
+    .await;
+
+    let enabled: Value = server
+        .get("/tenant-a/scim/v2/ServiceProviderConfig")
+        .await
+        .json();
+    let info = &enabled[EXTENSION];
+    assert_eq!(info["backendType"], "sqlite");
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(info["tenantId"], 1);
+    let flags = info["featureFlags"].as_object().unwrap();
+    assert_eq!(flags["expose_backend_info"], true);
+    assert_eq!(flags["total_count_header"], true);
+    assert!(!flags.contains_key("validate_timezone"));
+
+    // Off by default
+    let disabled: Value = server
+        .get("/tenant-b/scim/v2/ServiceProviderConfig")
+        .await
+        .json();
+    assert!(disabled.get(EXTENSION).is_none());
+}
@@ -0,0 +1,225 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::share::{self, SharedResource};
+use serde_json::{json, Value};
+
+mod common;
+
+const ADMIN_TOKEN: &str = "admin-secret";
+const SHARE_SECRET: &str = "share-secret";
+const TENANT_TOKEN: &str = "tenant-token";
+
+async fn setup_server(share_link_secret: Option<&str>) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.server.admin_token = Some(ADMIN_TOKEN.to_string());
+        app_config.server.share_link_secret = share_link_secret.map(str::to_string);
+        // The tenant requires a token, which share links do without
+        app_config.tenants[0].auth.auth_type = "bearer".to_string();
+        app_config.tenants[0].auth.token = Some(TENANT_TOKEN.to_string());
+    })
+    .await
+}
+
+async fn create_user(server: &TestServer) -> String {
+    let mut user = common::create_test_user_json("alice", "Alice", "Smith");
+    user["password"] = json!("Secret-Passw0rd");
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header("Authorization", format!("Bearer {}", TENANT_TOKEN))
+        .json(&user)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+async fn share(server: &TestServer, body: Value) -> axum_test::TestResponse {
+    server
+        .post("/admin/tenants/1/share")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .json(&body)
+        .await
+}
+
+/// Path of a share link URL, e.g. `/shared/<token>`
+fn shared_path(url: &str) -> &str {
+    &url[url.find("/shared/").unwrap()..]
+}
+
+#[tokio::test]
+async fn test_share_link_serves_resource_read_only() {
+    let server = setup_server(Some(SHARE_SECRET)).await;
+    let id = create_user(&server).await;
+
+    let response = share(
+        &server,
+        json!({"resource_type": "User", "resource_id": id, "ttl_seconds": 300}),
+    )
+    .await;
+    response.assert_status(StatusCode::OK);
+    let body = response.json::<Value>();
+    let url = body["url"].as_str().unwrap();
+    assert!(url.starts_with("http://"));
+    assert!(body["expiresAt"].is_string());
+
+    let response = server.get(shared_path(url)).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.header("cache-control"), "no-store");
+    let user = response.json::<Value>();
+    assert_eq!(user["id"], json!(id));
+    assert_eq!(user["userName"], "alice");
+    assert!(user.get("password").is_none());
+    assert!(user["meta"]["location"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/tenant-a/scim/v2/Users/{}", id)));
+
+    // Only GET is routed on the shared path
+    server
+        .patch(shared_path(url))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "active", "value": false}]
+        }))
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    server
+        .put(shared_path(url))
+        .json(&common::create_test_user_json(
+            "mallory", "Mallory", "Smith",
+        ))
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    server
+        .delete(shared_path(url))
+        .await
+        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn test_expired_share_link_is_not_found() {
+    let server = setup_server(Some(SHARE_SECRET)).await;
+    let id = create_user(&server).await;
+
+    let token = share::sign(
+        SHARE_SECRET,
+        &SharedResource {
+            tenant: 1,
+            resource_type: "User".to_string(),
+            id,
+            exp: chrono::Utc::now().timestamp() - 1,
+        },
+    );
+    server
+        .get(&format!("/shared/{}", token))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_tampered_share_link_is_not_found() {
+    let server = setup_server(Some(SHARE_SECRET)).await;
+    let id = create_user(&server).await;
+
+    let response = share(
+        &server,
+        json!({"resource_type": "User", "resource_id": id, "ttl_seconds": 300}),
+    )
+    .await;
+    let url = response.json::<Value>()["url"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let path = shared_path(&url);
+
+    // Change one character of the signed payload
+    let token = path.trim_start_matches("/shared/");
+    let (payload, signature) = token.split_once('.').unwrap();
+    let mut payload = payload.to_string();
+    let first = payload.remove(0);
+    payload.insert(0, if first == 'e' { 'f' } else { 'e' });
+    let tampered = format!("/shared/{}.{}", payload, signature);
+    server
+        .get(&tampered)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // A token signed with another secret
+    let token = share::sign(
+        "other-secret",
+        &SharedResource {
+            tenant: 1,
+            resource_type: "User".to_string(),
+            id,
+            exp: chrono::Utc::now().timestamp() + 300,
+        },
+    );
+    server
+        .get(&format!("/shared/{}", token))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_share_link_requests_are_validated() {
+    let server = setup_server(Some(SHARE_SECRET)).await;
+    let id = create_user(&server).await;
+
+    share(
+        &server,
+        json!({"resource_type": "User", "resource_id": "missing", "ttl_seconds": 300}),
+    )
+    .await
+    .assert_status(StatusCode::NOT_FOUND);
+    share(
+        &server,
+        json!({"resource_type": "Device", "resource_id": id, "ttl_seconds": 300}),
+    )
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+    share(
+        &server,
+        json!({"resource_type": "User", "resource_id": id, "ttl_seconds": 0}),
+    )
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .post("/admin/tenants/9/share")
+        .add_header("Authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .json(&json!({"resource_type": "User", "resource_id": id, "ttl_seconds": 300}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // Issuing links needs the admin token
+    server
+        .post("/admin/tenants/1/share")
+        .json(&json!({"resource_type": "User", "resource_id": id, "ttl_seconds": 300}))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_share_links_disabled_without_secret() {
+    let server = setup_server(None).await;
+    let id = create_user(&server).await;
+
+    share(
+        &server,
+        json!({"resource_type": "User", "resource_id": id, "ttl_seconds": 300}),
+    )
+    .await
+    .assert_status(StatusCode::NOT_FOUND);
+
+    let token = share::sign(
+        SHARE_SECRET,
+        &SharedResource {
+            tenant: 1,
+            resource_type: "User".to_string(),
+            id,
+            exp: chrono::Utc::now().timestamp() + 300,
+        },
+    );
+    server
+        .get(&format!("/shared/{}", token))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,104 @@
+/// SQLite concurrency tests
+///
+/// A file-backed SQLite database (WAL, busy_timeout) must absorb a burst of
+/// concurrent writes without surfacing "database is locked" as 500 errors.
+use axum::body::Body;
+use http::{Request, StatusCode};
+use scim_server::backend::database::config::{SQLITE_BUSY_TIMEOUT_MS, SQLITE_SERIALIZE_WRITES};
+use scim_server::backend::database::DatabaseBackendConfig;
+use scim_server::backend::BackendFactory;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+mod common;
+
+const CONCURRENT_CREATES: usize = 50;
+
+/// Temporary database file, removed together with its WAL files on drop
+struct TempDatabase(PathBuf);
+
+impl TempDatabase {
+    fn new() -> Self {
+        Self(std::env::temp_dir().join(format!("scim-concurrency-{}.db", uuid::Uuid::new_v4())))
+    }
+}
+
+impl Drop for TempDatabase {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.0.display(), suffix));
+        }
+    }
+}
+
+async fn run_concurrent_creates(serialize_writes: bool) {
+    let database = TempDatabase::new();
+    let backend_config = DatabaseBackendConfig::sqlite(database.0.display().to_string())
+        .with_max_connections(10)
+        .with_option(SQLITE_BUSY_TIMEOUT_MS.to_string(), "5000".to_string())
+        .with_option(
+            SQLITE_SERIALIZE_WRITES.to_string(),
+            serialize_writes.to_string(),
+        );
+    let backend = BackendFactory::create(&backend_config).await.unwrap();
+    for tenant_id in [1, 2, 3] {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+
+    let app = common::setup_test_app_with_backend(common::create_test_app_config(), backend)
+        .await
+        .unwrap();
+
+    let tasks: Vec<_> = (0..CONCURRENT_CREATES)
+        .map(|i| {
+            let app = app.clone();
+            tokio::spawn(async move {
+                let body = json!({
+                    "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                    "userName": format!("concurrent-{}", i)
+                });
+                let request = Request::builder()
+                    .method("POST")
+                    .uri("/scim/v2/Users")
+                    .header("content-type", "application/scim+json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                app.oneshot(request).await.unwrap().status()
+            })
+        })
+        .collect();
+
+    let mut statuses = Vec::new();
+    for task in tasks {
+        statuses.push(task.await.unwrap());
+    }
+
+    assert!(
+        statuses.iter().all(|status| *status == StatusCode::CREATED),
+        "unexpected statuses (serialize_writes={}): {:?}",
+        serialize_writes,
+        statuses
+    );
+
+    let request = Request::builder()
+        .uri("/scim/v2/Users?count=1")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(list["totalResults"], CONCURRENT_CREATES);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_concurrent_creates_file_database() {
+    run_concurrent_creates(false).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_concurrent_creates_serialized_writes() {
+    run_concurrent_creates(true).await;
+}
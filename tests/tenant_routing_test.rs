@@ -0,0 +1,138 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+
+mod common;
+
+/// Tenants 1 and 2 share `/scim/v2` on different hosts, tenant 3 serves
+/// `/scim/v2` on any other host and tenant 4 a nested path-only base path
+async fn routing_server() -> TestServer {
+    let mut app_config = common::create_test_app_config();
+    let template = app_config.tenants[0].clone();
+    let tenant = |id: u32, path: &str, host: Option<&str>| {
+        let mut tenant = template.clone();
+        tenant.id = id;
+        tenant.path = path.to_string();
+        tenant.host = host.map(str::to_string);
+        tenant
+    };
+    app_config.tenants = vec![
+        tenant(1, "/scim/v2", Some("a.example.com")),
+        tenant(2, "/scim/v2", Some("b.example.com")),
+        tenant(3, "/scim/v2", None),
+        tenant(4, "/scim/v2/contractors", None),
+    ];
+
+    let backend = common::setup_test_database(&app_config).await.unwrap();
+    for tenant_id in 1..=4 {
+        backend.init_tenant(tenant_id).await.unwrap();
+    }
+    let app = common::setup_test_app_with_backend(app_config, backend)
+        .await
+        .unwrap();
+    TestServer::new(app).unwrap()
+}
+
+async fn create_user(server: &TestServer, host: &str, path: &str, user_name: &str) -> Value {
+    let response = server
+        .post(&format!("{}/Users", path))
+        .add_header("host", host)
+        .json(&common::create_test_user_json(user_name, "Routed", "User"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json()
+}
+
+async fn user_names(server: &TestServer, host: &str, path: &str) -> Vec<String> {
+    let response = server
+        .get(&format!("{}/Users", path))
+        .add_header("host", host)
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    body["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["userName"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_host_based_tenants_share_a_path() {
+    let server = routing_server().await;
+
+    let user = create_user(&server, "a.example.com", "/scim/v2", "host.a").await;
+    assert_eq!(
+        user["meta"]["location"],
+        format!(
+            "http://a.example.com/scim/v2/Users/{}",
+            user["id"].as_str().unwrap()
+        )
+    );
+    create_user(&server, "b.example.com", "/scim/v2", "host.b").await;
+
+    assert_eq!(
+        user_names(&server, "a.example.com", "/scim/v2").await,
+        ["host.a"]
+    );
+    assert_eq!(
+        user_names(&server, "b.example.com", "/scim/v2").await,
+        ["host.b"]
+    );
+    // Other hosts fall back to the path-only tenant
+    assert!(user_names(&server, "c.example.com", "/scim/v2")
+        .await
+        .is_empty());
+
+    // A user of one host's tenant is not found through the other host
+    server
+        .get(&format!("/scim/v2/Users/{}", user["id"].as_str().unwrap()))
+        .add_header("host", "b.example.com")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_path_only_tenants_with_nested_paths() {
+    let server = routing_server().await;
+
+    create_user(&server, "c.example.com", "/scim/v2", "outer").await;
+    let user = create_user(&server, "c.example.com", "/scim/v2/contractors", "inner").await;
+    assert_eq!(
+        user["meta"]["location"],
+        format!(
+            "http://c.example.com/scim/v2/contractors/Users/{}",
+            user["id"].as_str().unwrap()
+        )
+    );
+
+    // Among path-only tenants the longest matching base path wins
+    assert_eq!(
+        user_names(&server, "c.example.com", "/scim/v2").await,
+        ["outer"]
+    );
+    assert_eq!(
+        user_names(&server, "c.example.com", "/scim/v2/contractors").await,
+        ["inner"]
+    );
+    // A matching host-scoped tenant wins over any path-only one, and has no
+    // `/contractors` endpoint
+    server
+        .get("/scim/v2/contractors/Users")
+        .add_header("host", "a.example.com")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    // Paths below no tenant and the bare base path are not SCIM endpoints
+    server
+        .get("/other/scim/v2/Users")
+        .add_header("host", "c.example.com")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get("/scim/v2/contractors")
+        .add_header("host", "c.example.com")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
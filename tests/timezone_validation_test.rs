@@ -0,0 +1,146 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+use scim_server::config::CompatibilityConfig;
+
+async fn setup_server(validate_timezone: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            validate_timezone,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+fn user_with_timezone(user_name: &str, timezone: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": user_name,
+        "timezone": timezone
+    })
+}
+
+fn assert_invalid_value(response: &axum_test::TestResponse) {
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "invalidValue");
+    assert!(body["detail"].as_str().unwrap().contains("timezone"));
+}
+
+#[tokio::test]
+async fn test_filter_by_timezone() {
+    let server = setup_server(true).await;
+
+    for (user_name, timezone) in [
+        ("tokyo.user", "Asia/Tokyo"),
+        ("ny.user", "America/New_York"),
+        ("la.user", "America/Los_Angeles"),
+    ] {
+        server
+            .post("/scim/v2/Users")
+            .json(&user_with_timezone(user_name, timezone))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    for (filter, expected) in [
+        ("timezone eq \"Asia/Tokyo\"", vec!["tokyo.user"]),
+        ("timezone sw \"America/\"", vec!["la.user", "ny.user"]),
+        ("timezone eq \"Europe/London\"", vec![]),
+    ] {
+        let response = server
+            .get("/scim/v2/Users")
+            .add_query_param("filter", filter)
+            .await;
+        response.assert_status(StatusCode::OK);
+        let body: Value = response.json();
+        let mut user_names: Vec<&str> = body["Resources"]
+            .as_array()
+            .map(|resources| {
+                resources
+                    .iter()
+                    .map(|u| u["userName"].as_str().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        user_names.sort();
+        assert_eq!(user_names, expected, "{}", filter);
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_timezones_rejected_when_enabled() {
+    let server = setup_server(true).await;
+
+    for timezone in ["EST5EDT", "US/Eastern", "Etc/GMT+25", "+09:00", "Nowhere"] {
+        assert_invalid_value(
+            &server
+                .post("/scim/v2/Users")
+                .json(&user_with_timezone("strict.user", timezone))
+                .await,
+        );
+    }
+
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&user_with_timezone("strict.user", "Europe/Berlin"))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+    assert_invalid_value(
+        &server
+            .put(&format!("/scim/v2/Users/{}", user_id))
+            .json(&user_with_timezone("strict.user", "US/Eastern"))
+            .await,
+    );
+
+    for operation in [
+        json!({"op": "replace", "path": "timezone", "value": "EST5EDT"}),
+        json!({"op": "add", "value": {"timezone": "US/Pacific"}}),
+    ] {
+        assert_invalid_value(
+            &server
+                .patch(&format!("/scim/v2/Users/{}", user_id))
+                .json(&json!({
+                    "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                    "Operations": [operation]
+                }))
+                .await,
+        );
+    }
+
+    let response = server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "timezone", "value": "Asia/Tokyo"}]
+        }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["timezone"], "Asia/Tokyo");
+}
+
+#[tokio::test]
+async fn test_legacy_timezones_accepted_by_default() {
+    let server = setup_server(false).await;
+
+    for (user_name, timezone) in [("legacy.link", "US/Eastern"), ("offset", "+09:00")] {
+        server
+            .post("/scim/v2/Users")
+            .json(&user_with_timezone(user_name, timezone))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    // Unknown zones are still rejected
+    assert_invalid_value(
+        &server
+            .post("/scim/v2/Users")
+            .json(&user_with_timezone("unknown.zone", "Nowhere"))
+            .await,
+    );
+}
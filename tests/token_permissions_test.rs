@@ -0,0 +1,305 @@
+use axum_test::TestServer;
+use http::{header, StatusCode};
+use scim_server::config::{AuthConfig, ScopedTokenConfig, TokenPermissions};
+use serde_json::{json, Value};
+
+mod common;
+
+const ADMIN: &str = "Bearer admin-token";
+const HR: &str = "Bearer hr-token";
+const ANALYTICS: &str = "Bearer analytics-token";
+
+async fn setup_server() -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[0].auth = AuthConfig {
+            auth_type: "bearer".to_string(),
+            token: Some("admin-token".to_string()),
+            basic: None,
+            basic_users: Vec::new(),
+            tokens: vec![
+                ScopedTokenConfig {
+                    token: "hr-token".to_string(),
+                    name: Some("hr".to_string()),
+                    permissions: TokenPermissions {
+                        allowed_methods: vec![
+                            "GET".to_string(),
+                            "POST".to_string(),
+                            "PUT".to_string(),
+                            "PATCH".to_string(),
+                        ],
+                        denied_attributes: vec![
+                            "phoneNumbers".to_string(),
+                            "addresses".to_string(),
+                        ],
+                        readonly: false,
+                    },
+                },
+                ScopedTokenConfig {
+                    token: "analytics-token".to_string(),
+                    name: None,
+                    permissions: TokenPermissions {
+                        readonly: true,
+                        ..Default::default()
+                    },
+                },
+            ],
+        };
+        app_config.tenants[0].ndjson_export = true;
+    })
+    .await
+}
+
+async fn create_user(server: &TestServer, user_name: &str) -> String {
+    let mut user = common::create_test_user_json(user_name, "Test", "User");
+    user["phoneNumbers"] = json!([{"value": "+81-3-1234-5678", "type": "work"}]);
+    user["addresses"] = json!([{"locality": "Tokyo", "type": "work"}]);
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header(header::AUTHORIZATION, ADMIN)
+        .json(&user)
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_readonly_token_cannot_write() {
+    let server = setup_server().await;
+    let user_id = create_user(&server, "readonly.target").await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header(header::AUTHORIZATION, ANALYTICS)
+        .json(&common::create_test_user_json(
+            "readonly.new",
+            "New",
+            "User",
+        ))
+        .await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    let body: Value = response.json();
+    assert_eq!(body["status"], "403");
+    assert_eq!(
+        body["schemas"][0],
+        "urn:ietf:params:scim:api:messages:2.0:Error"
+    );
+
+    server
+        .delete(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, ANALYTICS)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    // Reads, including POST .search, still work
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, ANALYTICS)
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["phoneNumbers"][0]["type"], "work");
+    server
+        .post("/tenant-a/scim/v2/.search")
+        .add_header(header::AUTHORIZATION, ANALYTICS)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:SearchRequest"],
+            "filter": "userName eq \"readonly.target\""
+        }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_allowed_methods() {
+    let server = setup_server().await;
+    let user_id = create_user(&server, "methods.target").await;
+
+    server
+        .delete(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    server
+        .delete(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, ADMIN)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_denied_attributes_are_never_returned() {
+    let server = setup_server().await;
+    let user_id = create_user(&server, "hidden.phone").await;
+
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .await;
+    response.assert_status_ok();
+    let user: Value = response.json();
+    assert_eq!(user["userName"], "hidden.phone");
+    assert!(user.get("phoneNumbers").is_none());
+    assert!(user.get("addresses").is_none());
+
+    // Asking for them explicitly does not help
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_query_param("attributes", "userName,phoneNumbers")
+        .add_header(header::AUTHORIZATION, HR)
+        .await;
+    response.assert_status_ok();
+    let list: Value = response.json();
+    assert_eq!(list["totalResults"], 1);
+    assert!(list["Resources"][0].get("phoneNumbers").is_none());
+
+    // Nor does probing them with a filter or sortBy
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_query_param("filter", "phoneNumbers.value sw \"+81\"")
+        .add_header(header::AUTHORIZATION, HR)
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["scimType"], "invalidFilter");
+    server
+        .get("/tenant-a/scim/v2/Users")
+        .add_query_param("sortBy", "addresses.locality")
+        .add_header(header::AUTHORIZATION, HR)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_denied_attributes_cannot_be_written() {
+    let server = setup_server().await;
+    let user_id = create_user(&server, "locked.phone").await;
+
+    let response = server
+        .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{
+                "op": "replace",
+                "path": "phoneNumbers[type eq \"work\"].value",
+                "value": "+81-3-0000-0000"
+            }]
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["scimType"], "mutability");
+    assert!(body["detail"].as_str().unwrap().contains("phoneNumbers"));
+
+    // A path-less PATCH is checked too
+    server
+        .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "add", "value": {"addresses": [{"locality": "Osaka"}]}}]
+        }))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let mut user = common::create_test_user_json("locked.phone", "Test", "User");
+    user["phoneNumbers"] = json!([{"value": "+81-3-0000-0000"}]);
+    server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .json(&user)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    // Other attributes can still be managed
+    let response = server
+        .patch(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [{"op": "replace", "path": "name.givenName", "value": "Changed"}]
+        }))
+        .await;
+    response.assert_status_ok();
+    let user: Value = response.json();
+    assert_eq!(user["name"]["givenName"], "Changed");
+    assert!(user.get("phoneNumbers").is_none());
+
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, ADMIN)
+        .await;
+    let user: Value = response.json();
+    assert_eq!(user["phoneNumbers"][0]["value"], "+81-3-1234-5678");
+    assert_eq!(user["addresses"][0]["locality"], "Tokyo");
+}
+
+#[tokio::test]
+async fn test_put_keeps_denied_attributes() {
+    let server = setup_server().await;
+    let user_id = create_user(&server, "replaced.user").await;
+
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .await;
+    let mut user: Value = response.json();
+    assert!(user.get("phoneNumbers").is_none());
+    user["name"]["givenName"] = json!("Replaced");
+
+    let response = server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, HR)
+        .json(&user)
+        .await;
+    response.assert_status_ok();
+    assert!(response.json::<Value>().get("phoneNumbers").is_none());
+
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .add_header(header::AUTHORIZATION, ADMIN)
+        .await;
+    let user: Value = response.json();
+    assert_eq!(user["name"]["givenName"], "Replaced");
+    assert_eq!(user["phoneNumbers"][0]["value"], "+81-3-1234-5678");
+    assert_eq!(user["addresses"][0]["locality"], "Tokyo");
+}
+
+#[tokio::test]
+async fn test_denied_attributes_are_stripped_from_ndjson_exports() {
+    let server = setup_server().await;
+    for user_name in ["export.one", "export.two"] {
+        create_user(&server, user_name).await;
+    }
+
+    let response = server
+        .get("/tenant-a/scim/v2/Users")
+        .add_query_param("format", "ndjson")
+        .add_header(header::AUTHORIZATION, HR)
+        .await;
+    response.assert_status_ok();
+    let users: Vec<Value> = response
+        .text()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(users.len(), 2);
+    for user in &users {
+        assert!(user["userName"].as_str().unwrap().starts_with("export."));
+        assert!(user.get("phoneNumbers").is_none());
+        assert!(user.get("addresses").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_oversized_writes_are_rejected() {
+    let server = setup_server().await;
+
+    // Larger than axum's default 2 MiB body limit
+    let mut user = common::create_test_user_json("too.large", "Test", "User");
+    user["title"] = json!("x".repeat(3 * 1024 * 1024));
+    server
+        .post("/tenant-a/scim/v2/Users")
+        .add_header(header::AUTHORIZATION, HR)
+        .json(&user)
+        .await
+        .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
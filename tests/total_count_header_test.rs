@@ -0,0 +1,77 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+use scim_server::config::CompatibilityConfig;
+
+async fn setup_server(total_count_header: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.tenants[2].compatibility = Some(CompatibilityConfig {
+            total_count_header,
+            ..Default::default()
+        });
+    })
+    .await
+}
+
+fn total_count(response: &axum_test::TestResponse) -> i64 {
+    response
+        .header("X-Total-Count")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_total_count_header_matches_total_results() {
+    let server = setup_server(true).await;
+
+    for i in 0..3 {
+        let user = common::create_test_user_json(&format!("count.user{}", i), "Count", "User");
+        server
+            .post("/scim/v2/Users")
+            .json(&user)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+    server
+        .post("/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "Count Group"
+        }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // Header reflects the total, not the page size
+    let response = server.get("/scim/v2/Users?count=1").await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["totalResults"], 3);
+    assert_eq!(body["Resources"].as_array().unwrap().len(), 1);
+    assert_eq!(total_count(&response), 3);
+
+    let response = server
+        .get("/scim/v2/Users")
+        .add_query_param("filter", "userName eq \"count.user1\"")
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["totalResults"], 1);
+    assert_eq!(total_count(&response), 1);
+
+    let response = server.get("/scim/v2/Groups").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["totalResults"], 1);
+    assert_eq!(total_count(&response), 1);
+}
+
+#[tokio::test]
+async fn test_total_count_header_off_by_default() {
+    let server = setup_server(false).await;
+
+    let response = server.get("/scim/v2/Users").await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.maybe_header("X-Total-Count").is_none());
+}
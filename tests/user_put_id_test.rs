@@ -0,0 +1,82 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::Value;
+
+mod common;
+
+async fn setup_user(server: &TestServer, user_name: &str) -> String {
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&common::create_test_user_json(user_name, "Test", "User"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let user: Value = response.json();
+    user["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_put_user_with_same_user_name() {
+    let server = common::test_server(|_| {}).await;
+    let user_id = setup_user(&server, "same.user").await;
+
+    let mut payload = common::create_test_user_json("same.user", "Updated", "User");
+    payload["id"] = Value::String(user_id.clone());
+
+    let response = server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .json(&payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let user: Value = response.json();
+    assert_eq!(user["id"], user_id);
+    assert_eq!(user["userName"], "same.user");
+    assert_eq!(user["name"]["givenName"], "Updated");
+}
+
+#[tokio::test]
+async fn test_put_user_can_change_user_name() {
+    let server = common::test_server(|_| {}).await;
+    let user_id = setup_user(&server, "old.name").await;
+
+    // The URL id identifies the user, so a new userName is a rename
+    let response = server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .json(&common::create_test_user_json("new.name", "Test", "User"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let user: Value = response.json();
+    assert_eq!(user["id"], user_id);
+    assert_eq!(user["userName"], "new.name");
+
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .await;
+    let user: Value = response.json();
+    assert_eq!(user["userName"], "new.name");
+}
+
+#[tokio::test]
+async fn test_put_user_with_different_id_is_rejected() {
+    let server = common::test_server(|_| {}).await;
+    let user_id = setup_user(&server, "target.user").await;
+    let other_id = setup_user(&server, "other.user").await;
+
+    let mut payload = common::create_test_user_json("target.user", "Wrong", "Data");
+    payload["id"] = Value::String(other_id);
+
+    let response = server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .json(&payload)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json();
+    assert_eq!(body["status"], "400");
+    assert_eq!(body["scimType"], "mutability");
+
+    // Neither user was modified
+    let response = server
+        .get(&format!("/tenant-a/scim/v2/Users/{}", user_id))
+        .await;
+    let user: Value = response.json();
+    assert_eq!(user["name"]["givenName"], "Test");
+}
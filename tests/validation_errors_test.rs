@@ -0,0 +1,125 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use scim_server::config::CompatibilityConfig;
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup_server(detailed: bool) -> TestServer {
+    common::test_server(|app_config| {
+        app_config.compatibility = CompatibilityConfig {
+            detailed_validation_errors: detailed,
+            ..Default::default()
+        };
+    })
+    .await
+}
+
+fn invalid_user() -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "emails": [
+            {"value": "valid@example.com", "type": "work"},
+            {"value": "not-an-email", "type": "home"}
+        ],
+        "locale": "xx_YY"
+    })
+}
+
+#[tokio::test]
+async fn test_user_reports_all_violations() {
+    let server = setup_server(true).await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&invalid_user())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let body: Value = response.json();
+    assert_eq!(body["status"], "400");
+    assert_eq!(body["scimType"], "invalidValue");
+    let detail = body["detail"].as_str().unwrap();
+    assert!(detail.starts_with("3 validation errors: "), "{}", detail);
+
+    let errors = body["errors"].as_array().unwrap();
+    let paths: Vec<&str> = errors.iter().map(|e| e["path"].as_str().unwrap()).collect();
+    assert_eq!(paths, ["userName", "emails[1].value", "locale"]);
+    for error in errors {
+        assert_eq!(error["scimType"], "invalidValue");
+        let message = error["message"].as_str().unwrap();
+        assert!(detail.contains(message), "{} not in {}", message, detail);
+    }
+    assert_eq!(errors[0]["message"], "userName is required");
+    assert_eq!(errors[1]["message"], "Invalid email format: not-an-email");
+
+    // PUT validates the same way
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "valid.user"
+        }))
+        .await;
+    let id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let response = server
+        .put(&format!("/tenant-a/scim/v2/Users/{}", id))
+        .json(&invalid_user())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["errors"].as_array().unwrap().len(),
+        3
+    );
+}
+
+#[tokio::test]
+async fn test_group_reports_all_violations() {
+    let server = setup_server(true).await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Groups")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "members": [
+                {"value": "missing-user", "type": "User"},
+                {"value": "missing-group", "type": "Group"}
+            ]
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let body: Value = response.json();
+    let errors = body["errors"].as_array().unwrap();
+    let paths: Vec<&str> = errors.iter().map(|e| e["path"].as_str().unwrap()).collect();
+    assert_eq!(
+        paths,
+        ["displayName", "members[0].value", "members[1].value"]
+    );
+    assert!(body["detail"]
+        .as_str()
+        .unwrap()
+        .contains("Group with id 'missing-group' does not exist."));
+}
+
+#[tokio::test]
+async fn test_errors_array_can_be_disabled() {
+    let server = setup_server(false).await;
+
+    let response = server
+        .post("/tenant-a/scim/v2/Users")
+        .json(&invalid_user())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let body: Value = response.json();
+    assert!(body.get("errors").is_none());
+    // The detail still names every problem
+    let detail = body["detail"].as_str().unwrap();
+    assert!(detail.starts_with("3 validation errors: "), "{}", detail);
+    assert!(
+        detail.contains("Invalid locale format: xx_YY"),
+        "{}",
+        detail
+    );
+}
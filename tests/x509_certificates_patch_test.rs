@@ -0,0 +1,177 @@
+use axum_test::TestServer;
+use http::StatusCode;
+use serde_json::{json, Value};
+
+mod common;
+
+/// Base64 certificate data, long enough to pass certificate validation
+fn cert(tag: &str) -> String {
+    format!(
+        "MIIC{}{}",
+        tag,
+        "QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVo".repeat(4)
+    )
+}
+
+async fn setup_server_with_user(certs: &[String]) -> (TestServer, String) {
+    let server = common::test_server(|_| {}).await;
+
+    let certificates: Vec<Value> = certs.iter().map(|c| json!({"value": c})).collect();
+    let response = server
+        .post("/scim/v2/Users")
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+            "userName": "cert.holder",
+            "x509Certificates": certificates
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let user_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    (server, user_id)
+}
+
+async fn patch(server: &TestServer, user_id: &str, operation: Value) {
+    server
+        .patch(&format!("/scim/v2/Users/{}", user_id))
+        .json(&json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [operation]
+        }))
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+async fn stored_certificates(server: &TestServer, user_id: &str) -> Vec<Value> {
+    let response = server.get(&format!("/scim/v2/Users/{}", user_id)).await;
+    response.assert_status(StatusCode::OK);
+    response.json::<Value>()["x509Certificates"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_patch_add_x509_certificates() {
+    let (server, user_id) = setup_server_with_user(&[cert("Aa")]).await;
+
+    patch(
+        &server,
+        &user_id,
+        json!({
+            "op": "add",
+            "path": "x509Certificates",
+            "value": [{"value": cert("Bb"), "display": "Backup"}]
+        }),
+    )
+    .await;
+
+    // A single certificate object is appended as well
+    patch(
+        &server,
+        &user_id,
+        json!({
+            "op": "add",
+            "path": "x509Certificates",
+            "value": {"value": cert("Cc")}
+        }),
+    )
+    .await;
+
+    let certs = stored_certificates(&server, &user_id).await;
+    let values: Vec<&str> = certs.iter().map(|c| c["value"].as_str().unwrap()).collect();
+    assert_eq!(values, vec![cert("Aa"), cert("Bb"), cert("Cc")]);
+    assert_eq!(certs[1]["display"], "Backup");
+}
+
+#[tokio::test]
+async fn test_patch_replace_x509_certificate_by_filter() {
+    let (server, user_id) = setup_server_with_user(&[cert("Aa"), cert("Bb")]).await;
+
+    patch(
+        &server,
+        &user_id,
+        json!({
+            "op": "replace",
+            "path": format!("x509Certificates[value eq \"{}\"].display", cert("Aa")),
+            "value": "Signing"
+        }),
+    )
+    .await;
+    patch(
+        &server,
+        &user_id,
+        json!({
+            "op": "replace",
+            "path": format!("x509Certificates[value eq \"{}\"]", cert("Bb")),
+            "value": {"display": "Encryption", "type": "encryption"}
+        }),
+    )
+    .await;
+
+    let certs = stored_certificates(&server, &user_id).await;
+    assert_eq!(
+        certs,
+        vec![
+            json!({"value": cert("Aa"), "display": "Signing"}),
+            json!({"value": cert("Bb"), "display": "Encryption", "type": "encryption"}),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_patch_remove_x509_certificate_by_filter_is_case_exact() {
+    let (server, user_id) = setup_server_with_user(&[cert("Aa"), cert("Bb")]).await;
+
+    // Base64 is case-sensitive, so a lowercased value matches nothing
+    patch(
+        &server,
+        &user_id,
+        json!({
+            "op": "remove",
+            "path": format!("x509Certificates[value eq \"{}\"]", cert("Aa").to_lowercase())
+        }),
+    )
+    .await;
+    assert_eq!(stored_certificates(&server, &user_id).await.len(), 2);
+
+    patch(
+        &server,
+        &user_id,
+        json!({
+            "op": "remove",
+            "path": format!("x509Certificates[value eq \"{}\"]", cert("Aa"))
+        }),
+    )
+    .await;
+    assert_eq!(
+        stored_certificates(&server, &user_id).await,
+        vec![json!({"value": cert("Bb")})]
+    );
+}
+
+#[tokio::test]
+async fn test_filter_by_x509_certificate_value_is_case_exact() {
+    let (server, _) = setup_server_with_user(&[cert("Aa")]).await;
+
+    let total_for = |filter: String| {
+        let server = &server;
+        async move {
+            let response = server
+                .get("/scim/v2/Users")
+                .add_query_param("filter", filter)
+                .await;
+            response.assert_status(StatusCode::OK);
+            response.json::<Value>()["totalResults"].as_i64().unwrap()
+        }
+    };
+
+    for template in [
+        "x509Certificates.value eq \"{}\"",
+        "x509Certificates[value eq \"{}\"]",
+    ] {
+        let exact = template.replace("{}", &cert("Aa"));
+        let lowered = template.replace("{}", &cert("Aa").to_lowercase());
+        assert_eq!(total_for(exact).await, 1);
+        assert_eq!(total_for(lowered).await, 0);
+    }
+}